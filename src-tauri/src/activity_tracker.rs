@@ -0,0 +1,482 @@
+//! Activity Tracker
+//!
+//! Opt-in (`activity.trackingEnabled`) local time tracking for timesheets.
+//! The editor reports heartbeats while the user edits or focuses files, and
+//! commands entered in integrated terminals are recorded as they are sent,
+//! whether or not the editor reported anything. Heartbeats closer together
+//! than the idle timeout (`activity.idleTimeoutSecs`, default 5 minutes) are
+//! joined into active spans.
+//!
+//! Each day is stored as `~/.rainy-aether/activity/<YYYY-MM-DD>.json`;
+//! nothing leaves the machine unless exported.
+
+use chrono::{Local, NaiveDate, TimeZone};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const DEFAULT_IDLE_TIMEOUT_SECS: i64 = 300;
+
+/// Minimum interval between writes of the current day's log
+const SAVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Files listed per day in summaries
+const TOP_FILES: usize = 10;
+
+/// A period of continuous activity (Unix seconds)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivitySpan {
+    pub start: i64,
+    pub end: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FileActivity {
+    pub edits: u64,
+    pub last_touched: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceDay {
+    editing: Vec<ActivitySpan>,
+    terminal: Vec<ActivitySpan>,
+    files: BTreeMap<String, FileActivity>,
+    terminal_commands: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct DayLog {
+    date: String,
+    workspaces: BTreeMap<String, WorkspaceDay>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TouchedFile {
+    pub path: String,
+    pub edits: u64,
+}
+
+/// One workspace's activity on one day
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DailySummary {
+    pub date: String,
+    pub workspace_root: String,
+    pub active_secs: i64,
+    pub terminal_secs: i64,
+    pub files_touched: usize,
+    pub terminal_commands: u64,
+    /// RFC 3339, local time
+    pub first_activity: Option<String>,
+    pub last_activity: Option<String>,
+    pub top_files: Vec<TouchedFile>,
+    pub spans: Vec<ActivitySpan>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivityKind {
+    /// Text changed in a file
+    Edit,
+    /// The editor is focused on a file
+    Focus,
+    /// An integrated terminal is focused
+    Terminal,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+struct Tracker {
+    today: DayLog,
+    last_saved: Option<Instant>,
+    dirty: bool,
+}
+
+static TRACKER: Lazy<Mutex<Option<Tracker>>> = Lazy::new(|| Mutex::new(None));
+
+fn activity_dir() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join(".rainy-aether").join("activity"))
+}
+
+fn day_path(date: &str) -> Option<PathBuf> {
+    Some(activity_dir()?.join(format!("{}.json", date)))
+}
+
+fn load_day(date: &str) -> DayLog {
+    day_path(date)
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_else(|| DayLog {
+            date: date.to_string(),
+            ..Default::default()
+        })
+}
+
+fn save_day(log: &DayLog) -> Result<(), String> {
+    let path = day_path(&log.date).ok_or("Could not determine home directory")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create activity directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(log).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write activity log: {}", e))
+}
+
+fn tracking_enabled(workspace_root: &str) -> bool {
+    crate::configuration_manager::resolve_setting(Some(workspace_root), "activity.trackingEnabled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+fn idle_timeout(workspace_root: &str) -> i64 {
+    crate::configuration_manager::resolve_setting(Some(workspace_root), "activity.idleTimeoutSecs")
+        .and_then(|v| v.as_i64())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_IDLE_TIMEOUT_SECS)
+}
+
+/// Extend the last span or start a new one after an idle gap
+fn heartbeat(spans: &mut Vec<ActivitySpan>, now: i64, idle: i64) {
+    match spans.last_mut() {
+        Some(last) if now - last.end <= idle => last.end = last.end.max(now),
+        _ => spans.push(ActivitySpan {
+            start: now,
+            end: now,
+        }),
+    }
+}
+
+/// Run `update` on today's log, rolling over at midnight and saving
+/// at most every `SAVE_INTERVAL`
+fn with_today(update: impl FnOnce(&mut DayLog)) {
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    let Ok(mut guard) = TRACKER.lock() else {
+        return;
+    };
+    if guard.as_ref().is_some_and(|t| t.today.date != today) {
+        if let Some(previous) = guard.take().filter(|t| t.dirty) {
+            if let Err(e) = save_day(&previous.today) {
+                eprintln!("[ActivityTracker] {}", e);
+            }
+        }
+    }
+    let tracker = guard.get_or_insert_with(|| Tracker {
+        today: load_day(&today),
+        last_saved: None,
+        dirty: false,
+    });
+    update(&mut tracker.today);
+    tracker.dirty = true;
+    if tracker
+        .last_saved
+        .is_none_or(|saved| saved.elapsed() >= SAVE_INTERVAL)
+    {
+        match save_day(&tracker.today) {
+            Ok(()) => {
+                tracker.last_saved = Some(Instant::now());
+                tracker.dirty = false;
+            }
+            Err(e) => eprintln!("[ActivityTracker] {}", e),
+        }
+    }
+}
+
+/// Write pending activity to disk (on exit)
+pub(crate) fn flush() {
+    if let Ok(mut guard) = TRACKER.lock() {
+        if let Some(tracker) = guard.as_mut().filter(|t| t.dirty) {
+            if save_day(&tracker.today).is_ok() {
+                tracker.dirty = false;
+                tracker.last_saved = Some(Instant::now());
+            }
+        }
+    }
+}
+
+/// Workspace a terminal opened in `cwd` belongs to: a workspace with
+/// activity today that contains it, else the nearest folder holding
+/// `.rainy` or `.git`, else `cwd` itself
+fn terminal_workspace(cwd: &str) -> String {
+    let known = TRACKER.lock().ok().and_then(|guard| {
+        guard.as_ref().and_then(|t| {
+            t.today
+                .workspaces
+                .keys()
+                .filter(|root| Path::new(cwd).starts_with(root.as_str()))
+                .max_by_key(|root| root.len())
+                .cloned()
+        })
+    });
+    known
+        .or_else(|| {
+            Path::new(cwd)
+                .ancestors()
+                .find(|dir| dir.join(".rainy").is_dir() || dir.join(".git").exists())
+                .map(|dir| dir.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| cwd.to_string())
+}
+
+/// Record a command entered in a terminal opened inside a tracked workspace
+pub(crate) fn record_terminal_command(cwd: Option<&str>) {
+    let Some(cwd) = cwd else {
+        return;
+    };
+    let root = terminal_workspace(cwd);
+    if !tracking_enabled(&root) {
+        return;
+    }
+    let idle = idle_timeout(&root);
+    let now = Local::now().timestamp();
+    with_today(|log| {
+        let day = log.workspaces.entry(root).or_default();
+        heartbeat(&mut day.terminal, now, idle);
+        day.terminal_commands += 1;
+    });
+}
+
+fn format_timestamp(secs: i64) -> Option<String> {
+    Local
+        .timestamp_opt(secs, 0)
+        .single()
+        .map(|t| t.to_rfc3339())
+}
+
+fn span_total(spans: &[ActivitySpan]) -> i64 {
+    spans.iter().map(|s| s.end - s.start).sum()
+}
+
+fn summarize(date: &str, root: &str, day: &WorkspaceDay) -> DailySummary {
+    let mut top_files: Vec<TouchedFile> = day
+        .files
+        .iter()
+        .map(|(path, activity)| TouchedFile {
+            path: path.clone(),
+            edits: activity.edits,
+        })
+        .collect();
+    top_files.sort_by_key(|f| std::cmp::Reverse(f.edits));
+    top_files.truncate(TOP_FILES);
+
+    let first = day
+        .editing
+        .iter()
+        .chain(&day.terminal)
+        .map(|s| s.start)
+        .min();
+    let last = day.editing.iter().chain(&day.terminal).map(|s| s.end).max();
+    DailySummary {
+        date: date.to_string(),
+        workspace_root: root.to_string(),
+        active_secs: span_total(&day.editing),
+        terminal_secs: span_total(&day.terminal),
+        files_touched: day.files.len(),
+        terminal_commands: day.terminal_commands,
+        first_activity: first.and_then(format_timestamp),
+        last_activity: last.and_then(format_timestamp),
+        top_files,
+        spans: day.editing.clone(),
+    }
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| format!("Invalid date: {}", date))
+}
+
+/// Summaries for each day in `from..=to` (YYYY-MM-DD, defaulting to today)
+fn collect_summaries(
+    from: Option<String>,
+    to: Option<String>,
+    workspace_root: Option<&str>,
+) -> Result<Vec<DailySummary>, String> {
+    flush();
+    let today = Local::now().date_naive();
+    let from = from
+        .as_deref()
+        .map(parse_date)
+        .transpose()?
+        .unwrap_or(today);
+    let to = to.as_deref().map(parse_date).transpose()?.unwrap_or(today);
+    if from > to {
+        return Err("Start date is after end date".to_string());
+    }
+
+    let mut summaries = Vec::new();
+    for date in from.iter_days().take_while(|d| *d <= to) {
+        let date = date.format("%Y-%m-%d").to_string();
+        if !day_path(&date).is_some_and(|p| p.exists()) {
+            continue;
+        }
+        let log = load_day(&date);
+        for (root, day) in &log.workspaces {
+            if workspace_root.is_some_and(|w| w != root) {
+                continue;
+            }
+            summaries.push(summarize(&date, root, day));
+        }
+    }
+    Ok(summaries)
+}
+
+/// Report editor activity (the frontend sends these as heartbeats)
+#[tauri::command]
+pub fn activity_record(
+    workspace_root: String,
+    kind: ActivityKind,
+    path: Option<String>,
+) -> Result<(), String> {
+    if !tracking_enabled(&workspace_root) {
+        return Ok(());
+    }
+    let idle = idle_timeout(&workspace_root);
+    let now = Local::now().timestamp();
+    with_today(|log| {
+        let day = log.workspaces.entry(workspace_root).or_default();
+        match kind {
+            ActivityKind::Edit | ActivityKind::Focus => heartbeat(&mut day.editing, now, idle),
+            ActivityKind::Terminal => heartbeat(&mut day.terminal, now, idle),
+        }
+        if let Some(path) = path {
+            let file = day.files.entry(path).or_default();
+            if kind == ActivityKind::Edit {
+                file.edits += 1;
+            }
+            file.last_touched = now;
+        }
+    });
+    Ok(())
+}
+
+/// Daily summaries between two dates (inclusive, YYYY-MM-DD; default today)
+#[tauri::command]
+pub fn activity_get_summaries(
+    from: Option<String>,
+    to: Option<String>,
+    workspace_root: Option<String>,
+) -> Result<Vec<DailySummary>, String> {
+    collect_summaries(from, to, workspace_root.as_deref())
+}
+
+/// Export a timesheet as CSV (one row per workspace and day) or JSON
+#[tauri::command]
+pub fn activity_export(
+    destination: String,
+    format: Option<ExportFormat>,
+    from: Option<String>,
+    to: Option<String>,
+    workspace_root: Option<String>,
+) -> Result<usize, String> {
+    let summaries = collect_summaries(from, to, workspace_root.as_deref())?;
+    let content = match format.unwrap_or_default() {
+        ExportFormat::Json => {
+            serde_json::to_string_pretty(&summaries).map_err(|e| e.to_string())?
+        }
+        ExportFormat::Csv => {
+            let escape = |value: &str| {
+                if value.contains([',', '"', '\n']) {
+                    format!("\"{}\"", value.replace('"', "\"\""))
+                } else {
+                    value.to_string()
+                }
+            };
+            let mut csv = String::from(
+                "date,workspace,active_hours,terminal_hours,first_activity,last_activity,files_touched,terminal_commands\n",
+            );
+            for s in &summaries {
+                csv.push_str(&format!(
+                    "{},{},{:.2},{:.2},{},{},{},{}\n",
+                    s.date,
+                    escape(&s.workspace_root),
+                    s.active_secs as f64 / 3600.0,
+                    s.terminal_secs as f64 / 3600.0,
+                    s.first_activity.as_deref().unwrap_or_default(),
+                    s.last_activity.as_deref().unwrap_or_default(),
+                    s.files_touched,
+                    s.terminal_commands
+                ));
+            }
+            csv
+        }
+    };
+    std::fs::write(&destination, content).map_err(|e| format!("Failed to write export: {}", e))?;
+    println!(
+        "[ActivityTracker] Exported {} day(s) to {}",
+        summaries.len(),
+        destination
+    );
+    Ok(summaries.len())
+}
+
+/// Delete recorded activity, for one workspace or entirely
+#[tauri::command]
+pub fn activity_clear(workspace_root: Option<String>) -> Result<(), String> {
+    let mut guard = TRACKER.lock().map_err(|e| e.to_string())?;
+    let Some(dir) = activity_dir() else {
+        return Ok(());
+    };
+    let entries = std::fs::read_dir(&dir).into_iter().flatten().flatten();
+    match workspace_root {
+        None => {
+            *guard = None;
+            for entry in entries {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+        Some(root) => {
+            if let Some(tracker) = guard.as_mut() {
+                tracker.today.workspaces.remove(&root);
+            }
+            for entry in entries {
+                let mut log: DayLog = match std::fs::read_to_string(entry.path())
+                    .ok()
+                    .and_then(|c| serde_json::from_str(&c).ok())
+                {
+                    Some(log) => log,
+                    None => continue,
+                };
+                if log.workspaces.remove(&root).is_some() {
+                    save_day(&log)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Workspaces with recorded activity, most active first
+#[tauri::command]
+pub fn activity_list_workspaces() -> Result<Vec<String>, String> {
+    flush();
+    let Some(dir) = activity_dir() else {
+        return Ok(Vec::new());
+    };
+    let mut totals: HashMap<String, i64> = HashMap::new();
+    for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+        let Some(log) = std::fs::read_to_string(entry.path())
+            .ok()
+            .and_then(|c| serde_json::from_str::<DayLog>(&c).ok())
+        else {
+            continue;
+        };
+        for (root, day) in log.workspaces {
+            *totals.entry(root).or_default() += span_total(&day.editing);
+        }
+    }
+    let mut roots: Vec<(String, i64)> = totals.into_iter().collect();
+    roots.sort_by_key(|(_, secs)| std::cmp::Reverse(*secs));
+    Ok(roots.into_iter().map(|(root, _)| root).collect())
+}
@@ -0,0 +1,248 @@
+//! Workspace Agent Configuration
+//!
+//! Loads `.rainy/agents.json` so teams can commit agent behaviour alongside
+//! the code: default provider/model, system prompt additions, which tools
+//! agents get, and sandbox rules. The file is cached by modification time;
+//! `watch_agent_config` reloads it on change and emits
+//! `agents:config-changed` so the frontend agent manager picks it up.
+//!
+//! Sandbox path rules and read-only mode are enforced by the agent file
+//! tools; the agent runtime asks `agent_enabled_tools` and
+//! `agent_command_allowed` for the tool and command filters.
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{AppHandle, Emitter};
+
+const CONFIG_FILE: &str = "agents.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AgentSandboxRules {
+    /// Agents may read files but not create, modify or delete them
+    pub read_only: bool,
+    /// Gitignore-style patterns agents may neither read nor modify
+    pub deny_paths: Vec<String>,
+    /// Command prefixes agents may run; empty allows any command not denied
+    pub allowed_commands: Vec<String>,
+    /// Command prefixes agents may never run
+    pub denied_commands: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WorkspaceAgentConfig {
+    pub default_provider: Option<String>,
+    pub default_model: Option<String>,
+    /// Appended to the system prompt of every agent run in the workspace
+    pub system_prompt: Option<String>,
+    /// Tools offered to agents; all tools when unset
+    pub enabled_tools: Option<Vec<String>>,
+    pub disabled_tools: Vec<String>,
+    pub sandbox: AgentSandboxRules,
+}
+
+impl WorkspaceAgentConfig {
+    pub fn tool_enabled(&self, tool: &str) -> bool {
+        !self.disabled_tools.iter().any(|t| t == tool)
+            && self
+                .enabled_tools
+                .as_ref()
+                .is_none_or(|enabled| enabled.iter().any(|t| t == tool))
+    }
+
+    /// Whether the sandbox lets agents run `command`
+    pub fn command_allowed(&self, command: &str) -> bool {
+        let command = command.trim();
+        let matches = |prefix: &String| {
+            command == prefix
+                || command
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+        };
+        !self.sandbox.denied_commands.iter().any(matches)
+            && (self.sandbox.allowed_commands.is_empty()
+                || self.sandbox.allowed_commands.iter().any(matches))
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentConfigReport {
+    pub workspace_root: String,
+    pub path: String,
+    pub exists: bool,
+    pub config: WorkspaceAgentConfig,
+    /// Parse error; the defaults are used until the file is fixed
+    pub error: Option<String>,
+}
+
+struct CachedConfig {
+    modified: Option<SystemTime>,
+    config: WorkspaceAgentConfig,
+    error: Option<String>,
+}
+
+static CONFIG_CACHE: Lazy<Mutex<HashMap<PathBuf, CachedConfig>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static WATCHERS: Lazy<Mutex<HashMap<PathBuf, RecommendedWatcher>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn config_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(".rainy").join(CONFIG_FILE)
+}
+
+fn read_config(path: &Path) -> (WorkspaceAgentConfig, Option<String>) {
+    match std::fs::read_to_string(path) {
+        Ok(content) => match serde_json::from_str(&content) {
+            Ok(config) => (config, None),
+            Err(e) => {
+                eprintln!("[AgentConfig] Invalid {}: {}", path.display(), e);
+                (
+                    WorkspaceAgentConfig::default(),
+                    Some(format!("Invalid {}: {}", CONFIG_FILE, e)),
+                )
+            }
+        },
+        Err(_) => (WorkspaceAgentConfig::default(), None),
+    }
+}
+
+fn load_report(workspace_root: &Path) -> AgentConfigReport {
+    let path = config_path(workspace_root);
+    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+
+    let (config, error) = {
+        let mut cache = match CONFIG_CACHE.lock() {
+            Ok(cache) => cache,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        match cache.get(workspace_root) {
+            Some(cached) if cached.modified == modified => {
+                (cached.config.clone(), cached.error.clone())
+            }
+            _ => {
+                let (config, error) = read_config(&path);
+                cache.insert(
+                    workspace_root.to_path_buf(),
+                    CachedConfig {
+                        modified,
+                        config: config.clone(),
+                        error: error.clone(),
+                    },
+                );
+                (config, error)
+            }
+        }
+    };
+
+    AgentConfigReport {
+        workspace_root: workspace_root.to_string_lossy().to_string(),
+        exists: modified.is_some(),
+        path: path.to_string_lossy().to_string(),
+        config,
+        error,
+    }
+}
+
+/// The agent configuration of a workspace (defaults when there is no file)
+pub(crate) fn load(workspace_root: &Path) -> WorkspaceAgentConfig {
+    load_report(workspace_root).config
+}
+
+/// Get the workspace's `.rainy/agents.json` configuration
+#[tauri::command]
+pub fn get_agent_config(workspace_root: String) -> Result<AgentConfigReport, String> {
+    Ok(load_report(Path::new(&workspace_root)))
+}
+
+/// The names in `tools` the workspace lets agents use
+#[tauri::command]
+pub fn agent_enabled_tools(workspace_root: String, tools: Vec<String>) -> Vec<String> {
+    let config = load(Path::new(&workspace_root));
+    tools
+        .into_iter()
+        .filter(|tool| config.tool_enabled(tool))
+        .collect()
+}
+
+/// Whether the workspace sandbox lets agents run `command`
+#[tauri::command]
+pub fn agent_command_allowed(workspace_root: String, command: String) -> bool {
+    load(Path::new(&workspace_root)).command_allowed(&command)
+}
+
+/// Watch `.rainy/agents.json` and emit `agents:config-changed` when it changes
+#[tauri::command]
+pub fn watch_agent_config(app: AppHandle, workspace_root: String) -> Result<(), String> {
+    let root = PathBuf::from(&workspace_root);
+    let mut watchers = WATCHERS.lock().map_err(|e| e.to_string())?;
+    if watchers.contains_key(&root) {
+        return Ok(());
+    }
+
+    let rainy_dir = root.join(".rainy");
+    let event_root = root.clone();
+    let mut last_report: Option<(WorkspaceAgentConfig, Option<String>, bool)> = None;
+    let mut watcher =
+        notify::recommended_watcher(move |res: Result<notify::Event, notify::Error>| {
+            let Ok(event) = res else {
+                return;
+            };
+            let relevant = event.paths.iter().any(|path| {
+                path.file_name().is_some_and(|name| name == CONFIG_FILE)
+                    || path.file_name().is_some_and(|name| name == ".rainy")
+            });
+            if !relevant {
+                return;
+            }
+
+            // .rainy created after the watch started
+            let rainy_dir = event_root.join(".rainy");
+            if event.paths.contains(&rainy_dir) && rainy_dir.is_dir() {
+                if let Ok(mut watchers) = WATCHERS.lock() {
+                    if let Some(watcher) = watchers.get_mut(&event_root) {
+                        let _ = watcher.watch(&rainy_dir, RecursiveMode::NonRecursive);
+                    }
+                }
+            }
+
+            let report = load_report(&event_root);
+            let key = (report.config.clone(), report.error.clone(), report.exists);
+            if last_report.as_ref() == Some(&key) {
+                return;
+            }
+            last_report = Some(key);
+            println!("[AgentConfig] Reloaded {}", report.path);
+            if let Err(e) = app.emit("agents:config-changed", &report) {
+                eprintln!("[AgentConfig] Failed to emit config change: {}", e);
+            }
+        })
+        .map_err(|e| e.to_string())?;
+
+    watcher
+        .watch(&root, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch {}: {}", workspace_root, e))?;
+    if rainy_dir.is_dir() {
+        watcher
+            .watch(&rainy_dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {}", rainy_dir.display(), e))?;
+    }
+    watchers.insert(root, watcher);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn unwatch_agent_config(workspace_root: String) -> Result<(), String> {
+    WATCHERS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(Path::new(&workspace_root));
+    Ok(())
+}
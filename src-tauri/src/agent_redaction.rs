@@ -0,0 +1,316 @@
+//! Agent Redaction
+//!
+//! Scrubs secrets from text crossing the agent boundary: prompts before they
+//! are sent to a provider, and responses and logs before they are persisted.
+//! Detection combines the secret scanner rules (cloud keys, VCS and chat
+//! tokens, private keys, high-entropy assignments), every API key stored in
+//! the credential manager, and extra regexes from `agent.redaction.patterns`.
+//! Matches are replaced with `[REDACTED:<rule>]`.
+//!
+//! Each run keeps a report of what was redacted — rule, direction and count,
+//! never the secret — read with `agent_redaction_report`. Filtering is on by
+//! default; `agent.redaction.enabled` turns it off.
+
+use crate::credential_manager::CredentialManager;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::sync::Mutex;
+
+/// Stored keys shorter than this are too likely to match ordinary text
+const MIN_STORED_KEY_LENGTH: usize = 8;
+
+/// Reports kept for the most recent runs
+const MAX_REPORTS: usize = 50;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RedactionDirection {
+    /// Text sent to a provider
+    Prompt,
+    /// Model output written to memory or history
+    Response,
+    /// Agent runtime output written to logs
+    Log,
+}
+
+/// Matches of one rule in one direction
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Redaction {
+    pub rule_id: String,
+    pub description: String,
+    pub direction: RedactionDirection,
+    pub count: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactedText {
+    pub text: String,
+    pub redactions: Vec<Redaction>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionReport {
+    pub run_id: String,
+    pub redactions: Vec<Redaction>,
+    /// Sum of all counts
+    pub total: usize,
+    pub updated_at: i64,
+}
+
+/// Compiled `agent.redaction.patterns`, keyed by the setting's source strings
+static CUSTOM_PATTERNS: Lazy<Mutex<(Vec<String>, Vec<Regex>)>> =
+    Lazy::new(|| Mutex::new((Vec::new(), Vec::new())));
+
+/// Reports ordered from least to most recently updated
+static REPORTS: Lazy<Mutex<Vec<RedactionReport>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// A secret to replace
+struct Span {
+    range: Range<usize>,
+    rule_id: String,
+    description: String,
+}
+
+fn enabled(workspace_root: Option<&str>) -> bool {
+    crate::configuration_manager::resolve_setting(workspace_root, "agent.redaction.enabled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true)
+}
+
+fn custom_patterns(workspace_root: Option<&str>) -> Vec<Regex> {
+    let sources: Vec<String> =
+        crate::configuration_manager::resolve_setting(workspace_root, "agent.redaction.patterns")
+            .and_then(|v| v.as_array().cloned())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .filter(|s| !s.is_empty())
+            .collect();
+    let Ok(mut cache) = CUSTOM_PATTERNS.lock() else {
+        return Vec::new();
+    };
+    if cache.0 != sources {
+        let compiled = sources
+            .iter()
+            .filter_map(|source| match Regex::new(source) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    eprintln!("[AgentRedaction] Invalid pattern {}: {}", source, e);
+                    None
+                }
+            })
+            .collect();
+        *cache = (sources, compiled);
+    }
+    cache.1.clone()
+}
+
+fn find_spans(text: &str, workspace_root: Option<&str>) -> Vec<Span> {
+    let mut spans = Vec::new();
+
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        for span in crate::secret_scanner::secret_spans(content) {
+            spans.push(Span {
+                range: offset + span.range.start..offset + span.range.end,
+                rule_id: span.rule_id.to_string(),
+                description: span.description.to_string(),
+            });
+        }
+        offset += line.len();
+    }
+
+    for (provider_id, key) in CredentialManager::known_credentials() {
+        if key.len() < MIN_STORED_KEY_LENGTH {
+            continue;
+        }
+        for (start, _) in text.match_indices(key.as_str()) {
+            spans.push(Span {
+                range: start..start + key.len(),
+                rule_id: "stored-credential".to_string(),
+                description: format!("Stored {} API key", provider_id),
+            });
+        }
+    }
+
+    for regex in custom_patterns(workspace_root) {
+        for found in regex.find_iter(text).filter(|m| !m.is_empty()) {
+            spans.push(Span {
+                range: found.range(),
+                rule_id: "custom".to_string(),
+                description: format!("Pattern {}", regex.as_str()),
+            });
+        }
+    }
+
+    // Overlapping matches are replaced once, credited to the earliest
+    spans.sort_by_key(|s| (s.range.start, std::cmp::Reverse(s.range.end)));
+    let mut merged: Vec<Span> = Vec::new();
+    for span in spans {
+        match merged.last_mut() {
+            Some(last) if span.range.start < last.range.end => {
+                last.range.end = last.range.end.max(span.range.end);
+            }
+            _ => merged.push(span),
+        }
+    }
+    merged
+}
+
+fn add_redaction(
+    redactions: &mut Vec<Redaction>,
+    rule_id: &str,
+    description: &str,
+    direction: RedactionDirection,
+    count: usize,
+) {
+    match redactions
+        .iter_mut()
+        .find(|r| r.rule_id == rule_id && r.description == description && r.direction == direction)
+    {
+        Some(existing) => existing.count += count,
+        None => redactions.push(Redaction {
+            rule_id: rule_id.to_string(),
+            description: description.to_string(),
+            direction,
+            count,
+        }),
+    }
+}
+
+/// Replace secrets in `text`; returns it unchanged when redaction is disabled
+pub(crate) fn redact(
+    text: &str,
+    workspace_root: Option<&str>,
+    direction: RedactionDirection,
+) -> RedactedText {
+    let mut redactions = Vec::new();
+    if !enabled(workspace_root) {
+        return RedactedText {
+            text: text.to_string(),
+            redactions,
+        };
+    }
+
+    let mut output = String::with_capacity(text.len());
+    let mut last = 0;
+    for span in find_spans(text, workspace_root) {
+        output.push_str(&text[last..span.range.start]);
+        output.push_str(&format!("[REDACTED:{}]", span.rule_id));
+        last = span.range.end;
+        add_redaction(
+            &mut redactions,
+            &span.rule_id,
+            &span.description,
+            direction,
+            1,
+        );
+    }
+    output.push_str(&text[last..]);
+    RedactedText {
+        text: output,
+        redactions,
+    }
+}
+
+fn record(run_id: &str, redactions: &[Redaction]) {
+    if redactions.is_empty() {
+        return;
+    }
+    let Ok(mut reports) = REPORTS.lock() else {
+        return;
+    };
+    let mut report = match reports.iter().position(|r| r.run_id == run_id) {
+        Some(index) => reports.remove(index),
+        None => RedactionReport {
+            run_id: run_id.to_string(),
+            redactions: Vec::new(),
+            total: 0,
+            updated_at: 0,
+        },
+    };
+    for redaction in redactions {
+        add_redaction(
+            &mut report.redactions,
+            &redaction.rule_id,
+            &redaction.description,
+            redaction.direction,
+            redaction.count,
+        );
+        report.total += redaction.count;
+    }
+    report.updated_at = chrono::Utc::now().timestamp_millis();
+    reports.push(report);
+    if reports.len() > MAX_REPORTS {
+        let excess = reports.len() - MAX_REPORTS;
+        reports.drain(..excess);
+    }
+}
+
+/// Redact `text` and add what was found to the report of `run_id`
+pub(crate) fn redact_for_run(
+    run_id: &str,
+    text: &str,
+    workspace_root: Option<&str>,
+    direction: RedactionDirection,
+) -> String {
+    let redacted = redact(text, workspace_root, direction);
+    record(run_id, &redacted.redactions);
+    redacted.text
+}
+
+/// Redact a prompt before sending it, or a response before persisting it.
+/// With a `run_id` the redactions are added to that run's report.
+#[tauri::command]
+pub fn agent_redact(
+    text: String,
+    direction: RedactionDirection,
+    run_id: Option<String>,
+    workspace_root: Option<String>,
+) -> Result<RedactedText, String> {
+    let redacted = redact(&text, workspace_root.as_deref(), direction);
+    if let Some(run_id) = run_id {
+        record(&run_id, &redacted.redactions);
+    }
+    Ok(redacted)
+}
+
+/// What was redacted during a run (empty if nothing was)
+#[tauri::command]
+pub fn agent_redaction_report(run_id: String) -> Result<RedactionReport, String> {
+    let reports = REPORTS.lock().map_err(|e| e.to_string())?;
+    Ok(reports
+        .iter()
+        .find(|r| r.run_id == run_id)
+        .cloned()
+        .unwrap_or(RedactionReport {
+            run_id,
+            redactions: Vec::new(),
+            total: 0,
+            updated_at: 0,
+        }))
+}
+
+/// Reports of recent runs, most recent first
+#[tauri::command]
+pub fn agent_redaction_reports() -> Result<Vec<RedactionReport>, String> {
+    let reports = REPORTS.lock().map_err(|e| e.to_string())?;
+    Ok(reports.iter().rev().cloned().collect())
+}
+
+/// Drop the report of one run, or all reports
+#[tauri::command]
+pub fn agent_redaction_clear(run_id: Option<String>) -> Result<(), String> {
+    let mut reports = REPORTS.lock().map_err(|e| e.to_string())?;
+    match run_id {
+        Some(run_id) => reports.retain(|r| r.run_id != run_id),
+        None => reports.clear(),
+    }
+    Ok(())
+}
@@ -5,49 +5,110 @@
 //! - Development: npm/tsx watch mode
 //! - Production: packaged binary via Tauri sidecar
 //!
+//! The process runs under the shared `process_supervisor`, which restarts it
+//! with exponential backoff when it crashes and captures its stdout/stderr.
+//! Lifecycle changes are emitted as `agent-server/lifecycle` events.
+//!
 //! Cross-platform: macOS, Linux, Windows
 
+use crate::process_supervisor::{
+    HealthCheck, ProcessEvent, ProcessSpec, RestartPolicy, SupervisedProcess,
+};
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Manager};
-use tauri_plugin_shell::process::CommandChild;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Preferred port for the agent server
+const DEFAULT_PORT: u16 = 3847;
+
+/// Number of ports tried after the preferred one when it is taken
+const PORT_SEARCH_RANGE: u16 = 20;
+
+/// Maximum automatic restarts before giving up
+const MAX_RESTARTS: u32 = 5;
+
+/// Base and maximum delay between automatic restarts
+const RESTART_BASE_DELAY_MS: u64 = 1000;
+const RESTART_MAX_DELAY_MS: u64 = 30_000;
+
+/// A run that stays up this long resets the restart counter
+const STABLE_RUN_SECS: u64 = 60;
+
+/// Health check results for the agent server
+#[derive(Debug, Clone, Default)]
+struct HealthMetrics {
+    last_check_ms: Option<u64>,
+    last_ok: Option<bool>,
+}
 
 /// State for the agent server process
 pub struct AgentServerState {
-    /// Whether the server is currently running
-    pub is_running: Arc<Mutex<bool>>,
     /// Port the server is listening on
     pub port: Arc<Mutex<u16>>,
-    /// Child process handle for stopping
-    pub child: Arc<Mutex<Option<CommandChild>>>,
+    /// Supervised server process
+    pub process: Arc<Mutex<Option<Arc<SupervisedProcess>>>>,
+    health: Arc<Mutex<HealthMetrics>>,
 }
 
 impl Default for AgentServerState {
     fn default() -> Self {
         Self {
-            is_running: Arc::new(Mutex::new(false)),
-            port: Arc::new(Mutex::new(3847)),
-            child: Arc::new(Mutex::new(None)),
+            port: Arc::new(Mutex::new(DEFAULT_PORT)),
+            process: Arc::new(Mutex::new(None)),
+            health: Arc::new(Mutex::new(HealthMetrics::default())),
         }
     }
 }
 
-/// Start the agent server sidecar
-#[tauri::command]
-pub async fn agent_server_start(app: AppHandle) -> Result<u16, String> {
-    use tauri_plugin_shell::ShellExt;
+impl AgentServerState {
+    fn current_process(&self) -> Option<Arc<SupervisedProcess>> {
+        self.process.lock().ok().and_then(|p| p.clone())
+    }
 
-    let state = app.state::<AgentServerState>();
+    fn is_running(&self) -> bool {
+        self.current_process().is_some_and(|p| p.is_running())
+    }
+}
 
-    // Check if already running
-    {
-        let is_running = state.is_running.lock().map_err(|e| e.to_string())?;
-        if *is_running {
-            let port = state.port.lock().map_err(|e| e.to_string())?;
-            return Ok(*port);
-        }
+/// Emit a lifecycle change to the frontend
+fn emit_lifecycle(app: &AppHandle, state: &str, extra: serde_json::Value) {
+    let server = app.state::<AgentServerState>();
+    let port = server.port.lock().map(|p| *p).unwrap_or(DEFAULT_PORT);
+    let restart_count = server.current_process().map(|p| p.restarts()).unwrap_or(0);
+
+    let mut payload = serde_json::json!({
+        "state": state,
+        "port": port,
+        "restart_count": restart_count,
+    });
+    if let (Some(target), serde_json::Value::Object(fields)) = (payload.as_object_mut(), extra) {
+        target.extend(fields);
     }
 
-    let port: u16 = 3847;
+    if let Err(e) = app.emit("agent-server/lifecycle", payload) {
+        eprintln!("[AgentServer] Failed to emit lifecycle event: {:?}", e);
+    }
+}
+
+/// Find a free port, starting at the preferred one
+fn select_port(preferred: u16) -> Result<u16, String> {
+    (preferred..preferred.saturating_add(PORT_SEARCH_RANGE))
+        .find(|port| std::net::TcpListener::bind(("127.0.0.1", *port)).is_ok())
+        .ok_or_else(|| {
+            format!(
+                "No free port found in range {}-{}",
+                preferred,
+                preferred.saturating_add(PORT_SEARCH_RANGE - 1)
+            )
+        })
+}
+
+/// Build the server command for the current build mode
+fn build_command(
+    app: &AppHandle,
+    port: u16,
+) -> Result<tauri_plugin_shell::process::Command, String> {
+    use tauri_plugin_shell::ShellExt;
 
     // Get the app resource directory for finding the server files
     let _resource_dir = app
@@ -55,7 +116,6 @@ pub async fn agent_server_start(app: AppHandle) -> Result<u16, String> {
         .resource_dir()
         .map_err(|e| format!("Failed to get resource dir: {}", e))?;
 
-    // Try to spawn the server
     // In production, use the binary; in dev, use tsx
     #[cfg(debug_assertions)]
     {
@@ -77,62 +137,130 @@ pub async fn agent_server_start(app: AppHandle) -> Result<u16, String> {
             return Err(format!("Server directory does not exist: {:?}", server_dir));
         }
 
-        let command = app
+        Ok(app
             .shell()
             .command("pnpm")
             .args(["dev"])
             .current_dir(&server_dir)
-            .env("INNGEST_PORT", port.to_string());
-
-        match command.spawn() {
-            Ok((_rx, child)) => {
-                // Store child process for later cleanup
-                {
-                    let mut child_lock = state.child.lock().map_err(|e| e.to_string())?;
-                    *child_lock = Some(child);
-                }
-
-                let mut is_running = state.is_running.lock().map_err(|e| e.to_string())?;
-                *is_running = true;
-                let mut p = state.port.lock().map_err(|e| e.to_string())?;
-                *p = port;
-                println!("[AgentServer] Started in dev mode on port {}", port);
-            }
-            Err(e) => {
-                return Err(format!("Failed to start agent server: {}", e));
-            }
-        }
+            .env("INNGEST_PORT", port.to_string()))
     }
 
     #[cfg(not(debug_assertions))]
     {
         // Production mode: use sidecar binary
-        let sidecar = app
+        Ok(app
             .shell()
             .sidecar("rainy-agents-server")
             .map_err(|e| format!("Failed to get sidecar: {}", e))?
-            .env("INNGEST_PORT", port.to_string());
-
-        match sidecar.spawn() {
-            Ok((_rx, child)) => {
-                // Store child process for later cleanup
-                {
-                    let mut child_lock = state.child.lock().map_err(|e| e.to_string())?;
-                    *child_lock = Some(child);
-                }
-
-                let mut is_running = state.is_running.lock().map_err(|e| e.to_string())?;
-                *is_running = true;
-                let mut p = state.port.lock().map_err(|e| e.to_string())?;
-                *p = port;
-                println!("[AgentServer] Started sidecar on port {}", port);
-            }
-            Err(e) => {
-                return Err(format!("Failed to spawn sidecar: {}", e));
+            .env("INNGEST_PORT", port.to_string()))
+    }
+}
+
+/// Agent server output can echo prompts and tool results
+fn redact_log_line(line: &str) -> String {
+    crate::agent_redaction::redact_for_run(
+        "agent-server",
+        line,
+        None,
+        crate::agent_redaction::RedactionDirection::Log,
+    )
+}
+
+/// Forward supervisor events to logs and lifecycle events
+fn handle_process_event(app: &AppHandle, event: &ProcessEvent) {
+    match event {
+        ProcessEvent::Started { pid } => {
+            emit_lifecycle(app, "running", serde_json::json!({ "pid": pid }));
+        }
+        ProcessEvent::Output { stream, line } => {
+            let line = redact_log_line(line);
+            if *stream == "stderr" {
+                eprintln!("[AgentServer] {}", line);
+            } else {
+                println!("[AgentServer] {}", line);
             }
         }
+        ProcessEvent::Exited { code, uptime } => {
+            eprintln!(
+                "[AgentServer] Server exited unexpectedly (code {:?}) after {}s",
+                code,
+                uptime.as_secs()
+            );
+            emit_lifecycle(app, "crashed", serde_json::json!({ "code": code }));
+        }
+        ProcessEvent::Restarting { attempt, delay } => {
+            emit_lifecycle(
+                app,
+                "restarting",
+                serde_json::json!({ "attempt": attempt, "delay_ms": delay.as_millis() as u64 }),
+            );
+        }
+        ProcessEvent::GaveUp { restarts } => {
+            eprintln!(
+                "[AgentServer] Giving up after {} restart attempts",
+                restarts
+            );
+            emit_lifecycle(app, "failed", serde_json::json!({}));
+        }
+        ProcessEvent::Stopped => {
+            emit_lifecycle(app, "stopped", serde_json::json!({}));
+        }
+    }
+}
+
+/// Start the agent server sidecar
+#[tauri::command]
+pub async fn agent_server_start(app: AppHandle) -> Result<u16, String> {
+    let state = app.state::<AgentServerState>();
+
+    // Check if already running
+    if state.is_running() {
+        let port = state.port.lock().map_err(|e| e.to_string())?;
+        return Ok(*port);
+    }
+
+    let preferred = *state.port.lock().map_err(|e| e.to_string())?;
+    let port = select_port(preferred)?;
+    if port != preferred {
+        println!(
+            "[AgentServer] Port {} is in use, using port {} instead",
+            preferred, port
+        );
     }
+    *state.port.lock().map_err(|e| e.to_string())? = port;
+
+    emit_lifecycle(&app, "starting", serde_json::json!({}));
+
+    let command: std::process::Command = build_command(&app, port)?.into();
+    let spec = ProcessSpec {
+        restart: RestartPolicy::OnFailure {
+            max_restarts: MAX_RESTARTS,
+            base_delay: Duration::from_millis(RESTART_BASE_DELAY_MS),
+            max_delay: Duration::from_millis(RESTART_MAX_DELAY_MS),
+            stable_after: Duration::from_secs(STABLE_RUN_SECS),
+        },
+        health_check: Some(HealthCheck::Tcp {
+            port,
+            timeout: Duration::from_secs(1),
+        }),
+        // `pnpm dev` runs the server as a child; a crash must not leave it
+        // holding the port the restart binds
+        process_group: true,
+        ..ProcessSpec::from_command("agent-server", &command)
+    };
+
+    let handler_app = app.clone();
+    let process = SupervisedProcess::spawn(
+        spec,
+        Some(Arc::new(move |event: &ProcessEvent| {
+            handle_process_event(&handler_app, event)
+        })),
+    )
+    .map_err(|e| format!("Failed to start agent server: {}", e))?;
+
+    *state.process.lock().map_err(|e| e.to_string())? = Some(process);
 
+    println!("[AgentServer] Started on port {}", port);
     Ok(port)
 }
 
@@ -141,19 +269,14 @@ pub async fn agent_server_start(app: AppHandle) -> Result<u16, String> {
 pub async fn agent_server_stop(app: AppHandle) -> Result<(), String> {
     let state = app.state::<AgentServerState>();
 
-    // Kill the child process if we have one
-    {
-        let mut child_lock = state.child.lock().map_err(|e| e.to_string())?;
-        if let Some(child) = child_lock.take() {
-            if let Err(e) = child.kill() {
-                eprintln!("[AgentServer] Warning: Failed to kill child process: {}", e);
-            }
-        }
+    // Stop the supervised process if we have one
+    let process = state.process.lock().map_err(|e| e.to_string())?.take();
+    if let Some(process) = process {
+        tauri::async_runtime::spawn_blocking(move || process.shutdown())
+            .await
+            .map_err(|e| format!("Failed to stop agent server: {}", e))?;
     }
 
-    let mut is_running = state.is_running.lock().map_err(|e| e.to_string())?;
-    *is_running = false;
-
     println!("[AgentServer] Stopped");
     Ok(())
 }
@@ -163,11 +286,11 @@ pub async fn agent_server_stop(app: AppHandle) -> Result<(), String> {
 pub fn agent_server_status(app: AppHandle) -> Result<serde_json::Value, String> {
     let state = app.state::<AgentServerState>();
 
-    let is_running = state.is_running.lock().map_err(|e| e.to_string())?;
+    let is_running = state.is_running();
     let port = state.port.lock().map_err(|e| e.to_string())?;
 
     Ok(serde_json::json!({
-        "running": *is_running,
+        "running": is_running,
         "port": *port,
         "url": format!("http://localhost:{}", *port),
         "inngest_endpoint": format!("http://localhost:{}/api/inngest", *port),
@@ -176,15 +299,66 @@ pub fn agent_server_status(app: AppHandle) -> Result<serde_json::Value, String>
 
 /// Health check for the agent server
 #[tauri::command]
-pub async fn agent_server_health(_app: AppHandle) -> Result<bool, String> {
-    let port: u16 = 3847;
+pub async fn agent_server_health(app: AppHandle) -> Result<bool, String> {
+    let state = app.state::<AgentServerState>();
+    let Some(process) = state.current_process() else {
+        return Ok(false);
+    };
 
     // Use a simple TCP check
-    match std::net::TcpStream::connect_timeout(
-        &format!("127.0.0.1:{}", port).parse().unwrap(),
-        std::time::Duration::from_secs(1),
-    ) {
-        Ok(_) => Ok(true),
-        Err(_) => Ok(false),
+    let started = Instant::now();
+    let healthy = process.check_health().unwrap_or(false);
+
+    if let Ok(mut health) = state.health.lock() {
+        health.last_check_ms = Some(started.elapsed().as_millis() as u64);
+        health.last_ok = Some(healthy);
     }
+
+    Ok(healthy)
+}
+
+/// Get runtime metrics for the agent server
+#[tauri::command]
+pub fn agent_server_metrics(app: AppHandle) -> Result<serde_json::Value, String> {
+    let state = app.state::<AgentServerState>();
+    let process = state.current_process();
+    let port = *state.port.lock().map_err(|e| e.to_string())?;
+    let health = state.health.lock().map_err(|e| e.to_string())?.clone();
+
+    Ok(serde_json::json!({
+        "running": process.as_ref().is_some_and(|p| p.is_running()),
+        "port": port,
+        "pid": process.as_ref().and_then(|p| p.pid()),
+        "uptime_secs": process.as_ref().and_then(|p| p.uptime()).map(|u| u.as_secs()),
+        "restart_count": process.as_ref().map(|p| p.restarts()).unwrap_or(0),
+        "total_crashes": process.as_ref().map(|p| p.total_exits()).unwrap_or(0),
+        "last_exit_code": process.as_ref().and_then(|p| p.last_exit_code()),
+        "last_health_check_ms": health.last_check_ms,
+        "last_health_ok": health.last_ok,
+    }))
+}
+
+/// Get the most recent captured output lines from the agent server
+#[tauri::command]
+pub fn agent_server_logs(
+    app: AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<crate::process_supervisor::CapturedLine>, String> {
+    let state = app.state::<AgentServerState>();
+    Ok(state
+        .current_process()
+        .map(|p| p.recent_output(limit.unwrap_or(200)))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|mut captured| {
+            // Already counted in the run report when the line was printed
+            captured.line = crate::agent_redaction::redact(
+                &captured.line,
+                None,
+                crate::agent_redaction::RedactionDirection::Log,
+            )
+            .text;
+            captured
+        })
+        .collect())
 }
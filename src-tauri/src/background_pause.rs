@@ -0,0 +1,173 @@
+//! Background Pause
+//!
+//! One switch to hold all background activity — useful while benchmarking or
+//! on a metered connection — or only some of it. Each subsystem checks the
+//! switch itself:
+//! - watchers: project and git watcher events are held and delivered on
+//!   resume; VFS polling stops
+//! - indexers: workspace warmup waits before its next stage or file
+//! - auto-fetch and update checks: `git_fetch` and `check_for_updates` called
+//!   with `background: true` return without touching the network
+//! - agent runs: the frontend holds a run before each provider request until
+//!   agent runs are resumed
+//!
+//! Changes are announced with `background:pause-changed`. Pauses are not
+//! persisted; a restart resumes everything.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// How often paused work checks whether it may continue
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum BackgroundSubsystem {
+    Watchers,
+    AutoFetch,
+    Indexers,
+    AgentRuns,
+    UpdateChecks,
+}
+
+impl BackgroundSubsystem {
+    const ALL: [BackgroundSubsystem; 5] = [
+        BackgroundSubsystem::Watchers,
+        BackgroundSubsystem::AutoFetch,
+        BackgroundSubsystem::Indexers,
+        BackgroundSubsystem::AgentRuns,
+        BackgroundSubsystem::UpdateChecks,
+    ];
+}
+
+#[derive(Debug, Clone)]
+struct Pause {
+    since: i64,
+    reason: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubsystemPauseStatus {
+    pub subsystem: BackgroundSubsystem,
+    pub paused: bool,
+    /// When the pause started (ms since epoch)
+    pub since: Option<i64>,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BackgroundPauseStatus {
+    /// Every subsystem is paused
+    pub all_paused: bool,
+    pub any_paused: bool,
+    pub subsystems: Vec<SubsystemPauseStatus>,
+}
+
+static PAUSES: Lazy<Mutex<HashMap<BackgroundSubsystem, Pause>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Whether a subsystem should hold its background work
+pub(crate) fn is_paused(subsystem: BackgroundSubsystem) -> bool {
+    PAUSES
+        .lock()
+        .map(|pauses| pauses.contains_key(&subsystem))
+        .unwrap_or(false)
+}
+
+/// Block while a subsystem is paused; returns early once `cancelled` says so
+pub(crate) fn wait_while_paused(subsystem: BackgroundSubsystem, cancelled: impl Fn() -> bool) {
+    while is_paused(subsystem) && !cancelled() {
+        std::thread::sleep(PAUSE_POLL_INTERVAL);
+    }
+}
+
+/// Async counterpart of `wait_while_paused` for tokio tasks
+pub(crate) async fn wait_while_paused_async(subsystem: BackgroundSubsystem) {
+    while is_paused(subsystem) {
+        tokio::time::sleep(PAUSE_POLL_INTERVAL).await;
+    }
+}
+
+fn status() -> BackgroundPauseStatus {
+    let pauses = PAUSES.lock().map(|p| p.clone()).unwrap_or_default();
+    let subsystems: Vec<SubsystemPauseStatus> = BackgroundSubsystem::ALL
+        .iter()
+        .map(|&subsystem| {
+            let pause = pauses.get(&subsystem);
+            SubsystemPauseStatus {
+                subsystem,
+                paused: pause.is_some(),
+                since: pause.map(|p| p.since),
+                reason: pause.and_then(|p| p.reason.clone()),
+            }
+        })
+        .collect();
+    BackgroundPauseStatus {
+        all_paused: subsystems.iter().all(|s| s.paused),
+        any_paused: subsystems.iter().any(|s| s.paused),
+        subsystems,
+    }
+}
+
+fn announce(app: &AppHandle) -> BackgroundPauseStatus {
+    let status = status();
+    if let Err(e) = app.emit("background:pause-changed", &status) {
+        eprintln!("[BackgroundPause] Failed to emit pause change: {}", e);
+    }
+    status
+}
+
+/// Pause the given subsystems, or all of them
+#[tauri::command]
+pub fn background_pause(
+    app: AppHandle,
+    subsystems: Option<Vec<BackgroundSubsystem>>,
+    reason: Option<String>,
+) -> Result<BackgroundPauseStatus, String> {
+    let subsystems = subsystems.unwrap_or_else(|| BackgroundSubsystem::ALL.to_vec());
+    {
+        let mut pauses = PAUSES.lock().map_err(|e| e.to_string())?;
+        let since = chrono::Utc::now().timestamp_millis();
+        for subsystem in subsystems {
+            pauses.entry(subsystem).or_insert_with(|| Pause {
+                since,
+                reason: reason.clone(),
+            });
+        }
+    }
+    println!("[BackgroundPause] Paused: {:?}", reason);
+    Ok(announce(&app))
+}
+
+/// Resume the given subsystems, or all of them
+#[tauri::command]
+pub fn background_resume(
+    app: AppHandle,
+    subsystems: Option<Vec<BackgroundSubsystem>>,
+) -> Result<BackgroundPauseStatus, String> {
+    {
+        let mut pauses = PAUSES.lock().map_err(|e| e.to_string())?;
+        match subsystems {
+            Some(subsystems) => {
+                for subsystem in subsystems {
+                    pauses.remove(&subsystem);
+                }
+            }
+            None => pauses.clear(),
+        }
+    }
+    println!("[BackgroundPause] Resumed");
+    Ok(announce(&app))
+}
+
+/// Which subsystems are paused, since when and why
+#[tauri::command]
+pub fn get_background_pause_status() -> Result<BackgroundPauseStatus, String> {
+    Ok(status())
+}
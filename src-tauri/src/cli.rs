@@ -0,0 +1,672 @@
+//! Command Line
+//!
+//! Arguments handled before the GUI starts:
+//! - `rainy-aether --list-extensions [--show-versions]`
+//! - `rainy-aether --install-extension <publisher.name[@version] | file.vsix>`
+//!   (repeatable; ids are downloaded from Open VSX)
+//! - `rainy-aether diff <left> <right>` (or `--diff`)
+//! - `rainy-aether --merge <local> <remote> <base> <result>`
+//! - `rainy-aether [--wait] <files…>`
+//!
+//! Extension commands run headless through the extension manager's install
+//! path and exit. Everything else opens in the GUI: a running instance gets
+//! the arguments through single-instance, otherwise this process becomes the
+//! GUI and queues them for `cli_take_pending`; either way the frontend sees a
+//! `cli:open` event.
+//!
+//! `--wait` makes the command usable as a git editor or mergetool
+//! (`git config core.editor "rainy-aether --wait"`). The waiting process
+//! creates a marker in `~/.rainy-aether/cli-wait/`, launches the request
+//! without `--wait`, and returns once the frontend calls `cli_wait_done`.
+//! The GUI touches the markers it holds every few seconds, so a waiter whose
+//! GUI went away stops instead of blocking git forever.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter};
+
+const OPEN_VSX_URL: &str = "https://open-vsx.org";
+
+const WAIT_TOKEN_FLAG: &str = "--wait-token";
+
+const MARKER_PENDING: &str = "pending";
+const MARKER_OPEN: &str = "open";
+
+/// How often the GUI touches the markers it holds
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A marker untouched this long belongs to a GUI that is gone
+const STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// How long a waiter gives the GUI to pick up its request
+const ACCEPT_TIMEOUT: Duration = Duration::from_secs(60);
+
+const USAGE: &str = "Usage: rainy-aether [options] [paths...]
+
+  diff <left> <right>                  Compare two files
+  -d, --diff <left> <right>            Same as diff
+  -m, --merge <local> <remote> <base> <result>
+                                       Resolve a three-way merge into <result>
+  -w, --wait                           Wait for the files to be closed before returning
+  --list-extensions                    List installed extensions
+  --show-versions                      Show versions with --list-extensions
+  --install-extension <id | path>      Install an extension (publisher.name[@version] or a .vsix)
+  -v, --version                        Print the version
+  -h, --help                           Print this help";
+
+/// A request for the GUI, emitted as `cli:open`
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "action")]
+pub enum CliRequest {
+    #[serde(rename = "open", rename_all = "camelCase")]
+    Open {
+        /// Files to open in the editor
+        paths: Vec<String>,
+        /// Folders to open as the workspace (the last one wins)
+        folders: Vec<String>,
+        wait_token: Option<String>,
+    },
+    #[serde(rename = "diff", rename_all = "camelCase")]
+    Diff {
+        left: String,
+        right: String,
+        wait_token: Option<String>,
+    },
+    #[serde(rename = "merge", rename_all = "camelCase")]
+    Merge {
+        local: String,
+        remote: String,
+        base: String,
+        result: String,
+        wait_token: Option<String>,
+    },
+}
+
+/// What the arguments ask for
+#[derive(Debug, Default)]
+struct ParsedArgs {
+    help: bool,
+    version: bool,
+    list_extensions: bool,
+    show_versions: bool,
+    install: Vec<String>,
+    diff: Option<(String, String)>,
+    /// local, remote, base, result
+    merge: Option<[String; 4]>,
+    wait: bool,
+    wait_token: Option<String>,
+    paths: Vec<String>,
+}
+
+impl ParsedArgs {
+    fn headless(&self) -> bool {
+        self.help || self.version || self.list_extensions || !self.install.is_empty()
+    }
+
+    fn opens_something(&self) -> bool {
+        self.diff.is_some() || self.merge.is_some() || !self.paths.is_empty()
+    }
+}
+
+fn take_values(
+    args: &mut impl Iterator<Item = String>,
+    flag: &str,
+    count: usize,
+) -> Result<Vec<String>, String> {
+    let values: Vec<String> = args.take(count).collect();
+    if values.len() < count {
+        return Err(format!("{} needs {} arguments", flag, count));
+    }
+    Ok(values)
+}
+
+/// Parse arguments without the program name
+fn parse(args: Vec<String>) -> Result<ParsedArgs, String> {
+    let mut parsed = ParsedArgs::default();
+    let mut args = args.into_iter().peekable();
+
+    if args.peek().map(String::as_str) == Some("diff") {
+        args.next();
+        let values = take_values(&mut args, "diff", 2)?;
+        parsed.diff = Some((values[0].clone(), values[1].clone()));
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => parsed.help = true,
+            "-v" | "--version" => parsed.version = true,
+            "--list-extensions" => parsed.list_extensions = true,
+            "--show-versions" => parsed.show_versions = true,
+            "--install-extension" => {
+                parsed.install.extend(take_values(&mut args, &arg, 1)?);
+            }
+            "-d" | "--diff" => {
+                let values = take_values(&mut args, &arg, 2)?;
+                parsed.diff = Some((values[0].clone(), values[1].clone()));
+            }
+            "-m" | "--merge" => {
+                let values = take_values(&mut args, &arg, 4)?;
+                parsed.merge = Some([
+                    values[0].clone(),
+                    values[1].clone(),
+                    values[2].clone(),
+                    values[3].clone(),
+                ]);
+            }
+            "-w" | "--wait" => parsed.wait = true,
+            WAIT_TOKEN_FLAG => {
+                parsed.wait_token = take_values(&mut args, &arg, 1)?.pop();
+            }
+            // Deep links are handled by the deep link plugin
+            _ if arg.starts_with(&format!("{}://", crate::deep_link::SCHEME)) => {}
+            // Finder adds a process serial number on older macOS
+            _ if arg.starts_with("-psn_") => {}
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                eprintln!("Warning: '{}' is not a known option", arg);
+            }
+            _ => parsed.paths.push(arg),
+        }
+    }
+    Ok(parsed)
+}
+
+fn absolute(path: &str, cwd: &Path) -> String {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_string_lossy().to_string()
+    } else {
+        cwd.join(path).to_string_lossy().to_string()
+    }
+}
+
+/// The GUI request described by parsed arguments
+fn request_for(parsed: &ParsedArgs, cwd: &Path) -> Option<CliRequest> {
+    let wait_token = parsed.wait_token.clone();
+    if let Some([local, remote, base, result]) = &parsed.merge {
+        return Some(CliRequest::Merge {
+            local: absolute(local, cwd),
+            remote: absolute(remote, cwd),
+            base: absolute(base, cwd),
+            result: absolute(result, cwd),
+            wait_token,
+        });
+    }
+    if let Some((left, right)) = &parsed.diff {
+        return Some(CliRequest::Diff {
+            left: absolute(left, cwd),
+            right: absolute(right, cwd),
+            wait_token,
+        });
+    }
+    if parsed.paths.is_empty() {
+        return None;
+    }
+    let (folders, paths) = parsed
+        .paths
+        .iter()
+        .map(|p| absolute(p, cwd))
+        .partition(|p| Path::new(p).is_dir());
+    Some(CliRequest::Open {
+        paths,
+        folders,
+        wait_token,
+    })
+}
+
+fn wait_dir() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join(".rainy-aether").join("cli-wait"))
+}
+
+/// Marker file for a wait token; tokens are generated here, but arrive
+/// from other processes, so anything that isn't a plain name is refused
+fn marker_path(token: &str) -> Option<PathBuf> {
+    if token.is_empty() || !token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+    Some(wait_dir()?.join(token))
+}
+
+// ============================================================================
+// BEFORE THE GUI
+// ============================================================================
+
+/// Handle arguments that don't need the GUI. Returns the exit code when the
+/// process should exit instead of starting the GUI.
+pub fn handle_before_gui() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        return None;
+    }
+    let parsed = match parse(args.clone()) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            attach_console();
+            eprintln!("{}\n\n{}", e, USAGE);
+            return Some(2);
+        }
+    };
+
+    if parsed.headless() {
+        attach_console();
+        return Some(run_headless(&parsed));
+    }
+    if parsed.wait && parsed.wait_token.is_none() {
+        attach_console();
+        if !parsed.opens_something() {
+            eprintln!("--wait needs a file, a diff or a merge to wait for");
+            return Some(2);
+        }
+        return Some(run_waiting(args));
+    }
+    None
+}
+
+/// Windows release builds have no console of their own
+fn attach_console() {
+    #[cfg(windows)]
+    unsafe {
+        use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+fn run_headless(parsed: &ParsedArgs) -> i32 {
+    if parsed.help {
+        println!("{}", USAGE);
+        return 0;
+    }
+    if parsed.version {
+        println!("{}", env!("CARGO_PKG_VERSION"));
+        return 0;
+    }
+
+    let mut code = 0;
+    if !parsed.install.is_empty() {
+        println!("Installing extensions...");
+        for target in &parsed.install {
+            match install_extension(target) {
+                Ok((id, version)) => {
+                    println!(
+                        "Extension '{}' v{} was successfully installed.",
+                        id, version
+                    )
+                }
+                Err(e) => {
+                    eprintln!("Failed to install '{}': {}", target, e);
+                    code = 1;
+                }
+            }
+        }
+    }
+    if parsed.list_extensions {
+        match crate::extension_manager::list_installed_extensions() {
+            Ok(extensions) => {
+                for ext in extensions {
+                    let disabled = if ext.enabled { "" } else { " (disabled)" };
+                    if parsed.show_versions {
+                        println!("{}@{}{}", ext.id, ext.version, disabled);
+                    } else {
+                        println!("{}{}", ext.id, disabled);
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to list extensions: {}", e);
+                code = 1;
+            }
+        }
+    }
+    code
+}
+
+fn install_extension(target: &str) -> Result<(String, String), String> {
+    let path = Path::new(target);
+    let data = if target.to_lowercase().ends_with(".vsix") || path.is_file() {
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", target, e))?
+    } else {
+        download_extension(target)?
+    };
+    crate::extension_manager::install_vsix(data)
+}
+
+/// Fetch `publisher.name[@version]` from Open VSX
+fn download_extension(id: &str) -> Result<Vec<u8>, String> {
+    let (id, version) = match id.split_once('@') {
+        Some((id, version)) => (id, Some(version)),
+        None => (id, None),
+    };
+    let (namespace, name) = id
+        .split_once('.')
+        .filter(|(namespace, name)| !namespace.is_empty() && !name.is_empty())
+        .ok_or_else(|| format!("Expected publisher.name, got '{}'", id))?;
+    let url = match version {
+        Some(version) => format!("{}/api/{}/{}/{}", OPEN_VSX_URL, namespace, name, version),
+        None => format!("{}/api/{}/{}", OPEN_VSX_URL, namespace, name),
+    };
+
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    runtime.block_on(async {
+        let client = reqwest::Client::new();
+        let response = client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach Open VSX: {}", e))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(format!("Extension '{}' not found", id));
+        }
+        let metadata: serde_json::Value = response
+            .error_for_status()
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid Open VSX response: {}", e))?;
+        let download = metadata
+            .pointer("/files/download")
+            .and_then(|v| v.as_str())
+            .ok_or("Open VSX did not return a download link")?;
+        let bytes = client
+            .get(download)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Download failed: {}", e))?
+            .bytes()
+            .await
+            .map_err(|e| format!("Download failed: {}", e))?;
+        Ok(bytes.to_vec())
+    })
+}
+
+/// Hand the request to the GUI and block until it reports the files closed
+fn run_waiting(args: Vec<String>) -> i32 {
+    let token = uuid::Uuid::new_v4().to_string();
+    let Some(marker) = marker_path(&token) else {
+        eprintln!("Failed to locate the home directory");
+        return 1;
+    };
+    if let Err(e) = marker
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&marker, MARKER_PENDING))
+    {
+        eprintln!("Failed to create wait marker: {}", e);
+        return 1;
+    }
+
+    let mut forwarded: Vec<String> = args
+        .into_iter()
+        .filter(|arg| arg != "-w" && arg != "--wait")
+        .collect();
+    forwarded.push(WAIT_TOKEN_FLAG.to_string());
+    forwarded.push(token);
+
+    let child = std::env::current_exe().and_then(|exe| {
+        std::process::Command::new(exe)
+            .args(&forwarded)
+            .stdin(std::process::Stdio::null())
+            .spawn()
+    });
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            let _ = std::fs::remove_file(&marker);
+            eprintln!("Failed to launch the editor: {}", e);
+            return 1;
+        }
+    };
+
+    let started = Instant::now();
+    loop {
+        std::thread::sleep(Duration::from_millis(300));
+        let Ok(state) = std::fs::read_to_string(&marker) else {
+            // Removed by cli_wait_done
+            return 0;
+        };
+        if state.trim() == MARKER_PENDING {
+            if let Ok(Some(status)) = child.try_wait() {
+                if !status.success() {
+                    let _ = std::fs::remove_file(&marker);
+                    eprintln!("The editor exited with {}", status);
+                    return status.code().unwrap_or(1);
+                }
+            }
+            if started.elapsed() > ACCEPT_TIMEOUT {
+                let _ = std::fs::remove_file(&marker);
+                eprintln!("The editor did not open the request");
+                return 1;
+            }
+            continue;
+        }
+        let touched = std::fs::metadata(&marker)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+        if touched.is_some_and(|age| age > STALE_AFTER) {
+            let _ = std::fs::remove_file(&marker);
+            eprintln!("The editor closed before the files were");
+            return 1;
+        }
+    }
+}
+
+// ============================================================================
+// IN THE GUI
+// ============================================================================
+
+static PENDING: Lazy<Mutex<Vec<CliRequest>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Wait tokens this process has accepted and keeps alive
+static HELD_WAITS: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Accept a waiter's request so it knows the GUI has it
+fn hold_wait(token: &str) {
+    let Some(marker) = marker_path(token) else {
+        return;
+    };
+    if std::fs::write(&marker, MARKER_OPEN).is_ok() {
+        if let Ok(mut held) = HELD_WAITS.lock() {
+            held.insert(token.to_string());
+        }
+    }
+}
+
+/// Handle the arguments of this launch (`queue`) or of a second launch
+/// forwarded by single-instance; returns whether they asked to open anything
+pub(crate) fn handle_args(app: &AppHandle, args: Vec<String>, cwd: &Path, queue: bool) -> bool {
+    // Deep links are handled by the deep-link plugin
+    let args: Vec<String> = args
+        .into_iter()
+        .skip(1)
+        .filter(|arg| !crate::deep_link::is_link_arg(arg))
+        .collect();
+    let parsed = match parse(args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("[CLI] Ignoring arguments: {}", e);
+            return false;
+        }
+    };
+    let Some(request) = request_for(&parsed, cwd) else {
+        return false;
+    };
+    if let Some(token) = &parsed.wait_token {
+        hold_wait(token);
+    }
+    println!("[CLI] {:?}", request);
+    if queue {
+        if let Ok(mut pending) = PENDING.lock() {
+            pending.push(request);
+        }
+    } else {
+        crate::deep_link::focus_window(app);
+        let _ = app.emit("cli:open", &request);
+    }
+    true
+}
+
+/// Queue the launch arguments and keep held wait markers fresh; called once from setup
+pub fn start(app: &AppHandle) {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    handle_args(app, std::env::args().collect(), &cwd, true);
+
+    std::thread::spawn(|| loop {
+        std::thread::sleep(HEARTBEAT_INTERVAL);
+        let Ok(mut held) = HELD_WAITS.lock() else {
+            continue;
+        };
+        held.retain(|token| {
+            let Some(marker) = marker_path(token) else {
+                return false;
+            };
+            std::fs::OpenOptions::new()
+                .write(true)
+                .open(&marker)
+                .and_then(|file| file.set_modified(SystemTime::now()))
+                .is_ok()
+        });
+    });
+}
+
+/// Requests received before the frontend was listening (the launch arguments)
+#[tauri::command]
+pub fn cli_take_pending() -> Result<Vec<CliRequest>, String> {
+    let mut pending = PENDING.lock().map_err(|e| e.to_string())?;
+    Ok(std::mem::take(&mut *pending))
+}
+
+/// Release a `--wait` caller once its files are closed (or the merge is done)
+#[tauri::command]
+pub fn cli_wait_done(wait_token: String) -> Result<(), String> {
+    let marker = marker_path(&wait_token).ok_or("Invalid wait token")?;
+    if let Ok(mut held) = HELD_WAITS.lock() {
+        held.remove(&wait_token);
+    }
+    match std::fs::remove_file(&marker) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to release waiter: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_paths_and_wait() {
+        let parsed = parse(args(&["--wait", "a.txt", "src"])).expect("parses");
+        assert!(parsed.wait);
+        assert_eq!(parsed.paths, ["a.txt", "src"]);
+        assert!(!parsed.headless());
+        assert!(parsed.opens_something());
+    }
+
+    #[test]
+    fn parses_diff_subcommand_and_flag() {
+        let parsed = parse(args(&["diff", "l", "r"])).expect("parses");
+        assert_eq!(parsed.diff, Some(("l".to_string(), "r".to_string())));
+        assert!(parsed.paths.is_empty());
+
+        let parsed = parse(args(&["-w", "--diff", "l", "r"])).expect("parses");
+        assert!(parsed.wait);
+        assert_eq!(parsed.diff, Some(("l".to_string(), "r".to_string())));
+    }
+
+    #[test]
+    fn parses_merge_in_order() {
+        let parsed = parse(args(&["--merge", "l", "r", "b", "out"])).expect("parses");
+        assert_eq!(
+            parsed.merge,
+            Some(["l", "r", "b", "out"].map(|s| s.to_string()))
+        );
+    }
+
+    #[test]
+    fn missing_values_are_errors() {
+        assert!(parse(args(&["diff", "only-one"])).is_err());
+        assert!(parse(args(&["-m", "a", "b", "c"])).is_err());
+        assert!(parse(args(&["--install-extension"])).is_err());
+        assert!(parse(args(&["--wait-token"])).is_err());
+    }
+
+    #[test]
+    fn extension_flags_are_headless() {
+        let parsed = parse(args(&[
+            "--install-extension",
+            "a.b",
+            "--install-extension",
+            "c.vsix",
+            "--list-extensions",
+            "--show-versions",
+        ]))
+        .expect("parses");
+        assert_eq!(parsed.install, ["a.b", "c.vsix"]);
+        assert!(parsed.list_extensions && parsed.show_versions);
+        assert!(parsed.headless());
+    }
+
+    #[test]
+    fn skips_deep_links_psn_and_unknown_options() {
+        let parsed = parse(args(&[
+            "rainy://open?path=/x",
+            "-psn_0_1234",
+            "--bogus",
+            "-",
+            "file",
+        ]))
+        .expect("parses");
+        assert_eq!(parsed.paths, ["-", "file"]);
+    }
+
+    #[test]
+    fn keeps_wait_token() {
+        let parsed = parse(args(&["--wait-token", "abc-1", "f"])).expect("parses");
+        assert_eq!(parsed.wait_token.as_deref(), Some("abc-1"));
+    }
+
+    #[test]
+    fn merge_wins_over_diff_and_paths_resolve_against_cwd() {
+        let cwd = std::env::temp_dir();
+        let parsed = parse(args(&["-d", "l", "r", "-m", "a", "b", "c", "d"])).expect("parses");
+        match request_for(&parsed, &cwd) {
+            Some(CliRequest::Merge { local, result, .. }) => {
+                assert_eq!(local, cwd.join("a").to_string_lossy());
+                assert_eq!(result, cwd.join("d").to_string_lossy());
+            }
+            other => panic!("expected a merge, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn open_request_splits_folders_from_files() {
+        let cwd = std::env::temp_dir();
+        let parsed = parse(args(&["missing-file.txt", "."])).expect("parses");
+        match request_for(&parsed, &cwd) {
+            Some(CliRequest::Open { paths, folders, .. }) => {
+                assert_eq!(paths, [cwd.join("missing-file.txt").to_string_lossy()]);
+                assert_eq!(folders, [cwd.join(".").to_string_lossy()]);
+            }
+            other => panic!("expected an open request, got {:?}", other),
+        }
+        assert!(request_for(&ParsedArgs::default(), &cwd).is_none());
+    }
+
+    #[test]
+    fn marker_paths_refuse_non_plain_tokens() {
+        assert!(marker_path("").is_none());
+        assert!(marker_path("../escape").is_none());
+        assert!(marker_path("a/b").is_none());
+        if let Some(path) = marker_path("token-1") {
+            assert!(path.ends_with("cli-wait/token-1"));
+        }
+    }
+}
@@ -0,0 +1,632 @@
+//! Code Chunker
+//!
+//! Splits source files along declaration boundaries (functions, classes, impls,
+//! types) with a configurable token budget. Shared by agent reads of large files
+//! and any indexer that needs semantically meaningful slices instead of naive
+//! byte ranges.
+//!
+//! Boundaries are declarations in the tree-sitter syntax tree for languages
+//! with a grammar here (Rust, TypeScript/JavaScript, Python, Go, C, C++,
+//! Java), and per-language declaration patterns for the rest of the C family.
+//! Leading doc comments, attributes and decorators stay attached to the
+//! declaration they describe. Oversized declarations are split at nested
+//! declarations, then at blank lines, and finally by line count.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use tree_sitter::{Node, Parser};
+
+/// Rough characters-per-token ratio used for budget estimates
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Default token budget per chunk
+pub const DEFAULT_CHUNK_TOKENS: usize = 800;
+
+/// Larger sources are chunked with the declaration patterns instead of parsed
+const MAX_PARSE_BYTES: usize = 2 * 1024 * 1024;
+
+/// Languages with dedicated boundary patterns
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChunkLanguage {
+    Rust,
+    TypeScript,
+    JavaScript,
+    Python,
+    Go,
+    C,
+    Cpp,
+    Java,
+    /// C-like languages without a grammar here (Kotlin, C#, Swift)
+    CFamily,
+    Plain,
+}
+
+impl ChunkLanguage {
+    /// Detect the language from a file extension
+    pub fn from_extension(extension: &str) -> Self {
+        match extension.to_lowercase().as_str() {
+            "rs" => Self::Rust,
+            "ts" | "tsx" | "mts" | "cts" => Self::TypeScript,
+            "js" | "jsx" | "mjs" | "cjs" => Self::JavaScript,
+            "py" | "pyi" => Self::Python,
+            "go" => Self::Go,
+            "c" | "h" => Self::C,
+            "cc" | "cpp" | "cxx" | "hpp" => Self::Cpp,
+            "java" => Self::Java,
+            "kt" | "cs" | "swift" => Self::CFamily,
+            _ => Self::Plain,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::TypeScript => "typescript",
+            Self::JavaScript => "javascript",
+            Self::Python => "python",
+            Self::Go => "go",
+            Self::C => "c",
+            Self::Cpp => "cpp",
+            Self::Java => "java",
+            Self::CFamily => "c-family",
+            Self::Plain => "plain",
+        }
+    }
+
+    fn declaration_pattern(&self) -> Option<&'static Regex> {
+        match self {
+            Self::Rust => Some(&RUST_DECL),
+            Self::TypeScript | Self::JavaScript => Some(&TS_DECL),
+            Self::Python => Some(&PYTHON_DECL),
+            Self::Go => Some(&GO_DECL),
+            Self::C | Self::Cpp | Self::Java | Self::CFamily => Some(&C_FAMILY_DECL),
+            Self::Plain => None,
+        }
+    }
+
+    fn grammar(&self) -> Option<SyntaxGrammar> {
+        let grammar = match self {
+            Self::Rust => SyntaxGrammar {
+                language: tree_sitter_rust::LANGUAGE.into(),
+                declarations: &[
+                    ("function_item", "fn"),
+                    ("struct_item", "struct"),
+                    ("enum_item", "enum"),
+                    ("union_item", "union"),
+                    ("impl_item", "impl"),
+                    ("trait_item", "trait"),
+                    ("mod_item", "mod"),
+                    ("type_item", "type"),
+                    ("macro_definition", "macro_rules!"),
+                    ("const_item", "const"),
+                    ("static_item", "static"),
+                ],
+                top_level_only: &["const_item", "static_item"],
+                wrappers: &[],
+                leading: &["line_comment", "block_comment", "attribute_item"],
+            },
+            // The TSX grammar also reads plain TypeScript, except `<T>value` casts
+            Self::TypeScript | Self::JavaScript => SyntaxGrammar {
+                language: match self {
+                    Self::TypeScript => tree_sitter_typescript::LANGUAGE_TSX.into(),
+                    _ => tree_sitter_javascript::LANGUAGE.into(),
+                },
+                declarations: &[
+                    ("function_declaration", "function"),
+                    ("generator_function_declaration", "function*"),
+                    ("class_declaration", "class"),
+                    ("abstract_class_declaration", "class"),
+                    ("interface_declaration", "interface"),
+                    ("type_alias_declaration", "type"),
+                    ("enum_declaration", "enum"),
+                    ("internal_module", "namespace"),
+                    ("method_definition", "method"),
+                    ("lexical_declaration", "const"),
+                ],
+                top_level_only: &["lexical_declaration"],
+                wrappers: &["export_statement"],
+                leading: &["comment", "decorator"],
+            },
+            Self::Python => SyntaxGrammar {
+                language: tree_sitter_python::LANGUAGE.into(),
+                declarations: &[
+                    ("function_definition", "def"),
+                    ("class_definition", "class"),
+                ],
+                top_level_only: &[],
+                wrappers: &["decorated_definition"],
+                leading: &["comment"],
+            },
+            Self::Go => SyntaxGrammar {
+                language: tree_sitter_go::LANGUAGE.into(),
+                declarations: &[
+                    ("function_declaration", "func"),
+                    ("method_declaration", "func"),
+                    ("type_declaration", "type"),
+                    ("var_declaration", "var"),
+                    ("const_declaration", "const"),
+                ],
+                top_level_only: &["var_declaration", "const_declaration"],
+                wrappers: &[],
+                leading: &["comment"],
+            },
+            Self::C | Self::Cpp => SyntaxGrammar {
+                language: match self {
+                    Self::C => tree_sitter_c::LANGUAGE.into(),
+                    _ => tree_sitter_cpp::LANGUAGE.into(),
+                },
+                declarations: &[
+                    ("function_definition", "function"),
+                    ("struct_specifier", "struct"),
+                    ("union_specifier", "union"),
+                    ("enum_specifier", "enum"),
+                    ("class_specifier", "class"),
+                    ("namespace_definition", "namespace"),
+                ],
+                top_level_only: &[],
+                wrappers: &["template_declaration", "type_definition", "declaration"],
+                leading: &["comment"],
+            },
+            Self::Java => SyntaxGrammar {
+                language: tree_sitter_java::LANGUAGE.into(),
+                declarations: &[
+                    ("class_declaration", "class"),
+                    ("interface_declaration", "interface"),
+                    ("enum_declaration", "enum"),
+                    ("record_declaration", "record"),
+                    ("annotation_type_declaration", "@interface"),
+                    ("method_declaration", "method"),
+                    ("constructor_declaration", "constructor"),
+                ],
+                top_level_only: &[],
+                wrappers: &[],
+                leading: &["line_comment", "block_comment"],
+            },
+            Self::CFamily | Self::Plain => return None,
+        };
+        Some(grammar)
+    }
+
+    /// Whether a line only decorates the declaration that follows it
+    fn is_leading_line(&self, trimmed: &str) -> bool {
+        match self {
+            Self::Rust => {
+                trimmed.starts_with("///")
+                    || trimmed.starts_with("//!")
+                    || trimmed.starts_with("#[")
+            }
+            Self::Python => trimmed.starts_with('@') || trimmed.starts_with('#'),
+            _ => {
+                trimmed.starts_with("//")
+                    || trimmed.starts_with("/*")
+                    || trimmed.starts_with('*')
+                    || trimmed.starts_with('@')
+            }
+        }
+    }
+}
+
+static RUST_DECL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:(?:async|const|unsafe|extern\s+\S+)\s+)*(fn|struct|enum|impl|trait|mod|type|macro_rules!)\s*<?\s*([A-Za-z_][A-Za-z0-9_]*)?",
+    )
+    .unwrap()
+});
+
+static TS_DECL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(?:async\s+)?(function\*?|class|interface|type|enum|namespace|const|let)\s+([A-Za-z_$][A-Za-z0-9_$]*)",
+    )
+    .unwrap()
+});
+
+static PYTHON_DECL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(?:async\s+)?(def|class)\s+([A-Za-z_][A-Za-z0-9_]*)").unwrap());
+
+static GO_DECL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(func|type|var|const)\s+(?:\([^)]*\)\s*)?([A-Za-z_][A-Za-z0-9_]*)?").unwrap()
+});
+
+static C_FAMILY_DECL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*(?:(?:public|private|protected|internal|static|final|abstract|virtual|inline|override|export|data|sealed|open)\s+)*(class|struct|interface|enum|namespace|fun|func|[A-Za-z_][A-Za-z0-9_<>,:*&\s]*?)\s+\**([A-Za-z_][A-Za-z0-9_]*)\s*(?:\(|\{|:|<|$)",
+    )
+    .unwrap()
+});
+
+/// Which syntax nodes are declarations in a language's tree-sitter grammar
+struct SyntaxGrammar {
+    language: tree_sitter::Language,
+    /// Node kind and the keyword reported as the chunk kind
+    declarations: &'static [(&'static str, &'static str)],
+    /// Declarations that only count outside other declarations (locals)
+    top_level_only: &'static [&'static str],
+    /// Nodes wrapping a declaration that belong to its chunk (`export`,
+    /// decorators, `template <...>`)
+    wrappers: &'static [&'static str],
+    /// Sibling nodes directly above a declaration that belong to its chunk
+    leading: &'static [&'static str],
+}
+
+/// A slice of a source file
+#[derive(Debug, Clone, Serialize)]
+pub struct CodeChunk {
+    /// 1-based, inclusive
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Declaration keyword ("fn", "class", ...) or "block" for other code
+    pub kind: String,
+    pub name: Option<String>,
+    pub token_estimate: usize,
+    pub content: String,
+}
+
+/// Estimate the token count of a piece of text
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(CHARS_PER_TOKEN)
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// A declaration boundary: line index plus kind and name
+#[derive(Clone)]
+struct Boundary {
+    line: usize,
+    kind: String,
+    name: Option<String>,
+}
+
+/// A declaration found in the syntax tree
+struct Declaration {
+    boundary: Boundary,
+    /// Not inside another declaration
+    top_level: bool,
+}
+
+/// Name of a declaration node
+fn declaration_name(node: Node, source: &str) -> Option<String> {
+    let name = node
+        .child_by_field_name("name")
+        // Rust `impl Type`
+        .or_else(|| {
+            (node.kind() == "impl_item")
+                .then(|| node.child_by_field_name("type"))
+                .flatten()
+        })
+        // C function declarators, Go type specs, JS `const x = ...`
+        .or_else(|| {
+            let mut current = node.child_by_field_name("declarator").or_else(|| {
+                node.named_child(0)
+                    .filter(|child| matches!(child.kind(), "type_spec" | "variable_declarator"))
+            })?;
+            loop {
+                match current
+                    .child_by_field_name("declarator")
+                    .or_else(|| current.child_by_field_name("name"))
+                {
+                    Some(inner) => current = inner,
+                    None => return Some(current),
+                }
+            }
+        })?;
+    let text = source.get(name.byte_range())?;
+    // Names spanning lines are expressions, not identifiers
+    (!text.contains('\n')).then(|| text.to_string())
+}
+
+/// First line of a declaration's chunk: its wrappers and the leading
+/// comments, attributes and decorators directly above it
+fn declaration_start(node: Node, grammar: &SyntaxGrammar) -> usize {
+    let mut outer = node;
+    while let Some(parent) = outer.parent() {
+        if !grammar.wrappers.contains(&parent.kind()) {
+            break;
+        }
+        outer = parent;
+    }
+
+    let mut line = outer.start_position().row;
+    let mut previous = outer.prev_named_sibling();
+    while let Some(sibling) = previous {
+        if !grammar.leading.contains(&sibling.kind()) || sibling.end_position().row + 1 < line {
+            break;
+        }
+        line = sibling.start_position().row;
+        previous = sibling.prev_named_sibling();
+    }
+    line
+}
+
+/// Declarations of a source from its syntax tree, in line order; None
+/// without a grammar or when the source can't be parsed
+fn syntax_declarations(content: &str, language: ChunkLanguage) -> Option<Vec<Declaration>> {
+    let grammar = language.grammar()?;
+    if content.len() > MAX_PARSE_BYTES {
+        return None;
+    }
+    let mut parser = Parser::new();
+    parser.set_language(&grammar.language).ok()?;
+    let tree = parser.parse(content, None)?;
+
+    let mut declarations = Vec::new();
+    let mut stack = vec![(tree.root_node(), false)];
+    while let Some((node, inside)) = stack.pop() {
+        let kind = grammar
+            .declarations
+            .iter()
+            .find(|(node_kind, _)| *node_kind == node.kind())
+            .filter(|(node_kind, _)| !(inside && grammar.top_level_only.contains(node_kind)))
+            // `struct x;` and `struct x y;` only name a type
+            .filter(|_| {
+                !node.kind().ends_with("_specifier") || node.child_by_field_name("body").is_some()
+            })
+            .map(|(_, label)| *label);
+        if let Some(kind) = kind {
+            declarations.push(Declaration {
+                boundary: Boundary {
+                    line: declaration_start(node, &grammar),
+                    kind: kind.to_string(),
+                    name: declaration_name(node, content),
+                },
+                top_level: !inside,
+            });
+        }
+        let inside = inside || kind.is_some();
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            stack.push((child, inside));
+        }
+    }
+
+    declarations.sort_by_key(|declaration| declaration.boundary.line);
+    Some(declarations)
+}
+
+/// Boundaries of the declarations accepted by `filter`, dropping any that
+/// start on or before the line of the previous one
+fn declaration_boundaries(
+    declarations: &[Declaration],
+    filter: impl Fn(&Declaration) -> bool,
+) -> Vec<Boundary> {
+    let mut boundaries: Vec<Boundary> = Vec::new();
+    for declaration in declarations.iter().filter(|d| filter(d)) {
+        if boundaries
+            .last()
+            .is_some_and(|b| b.line >= declaration.boundary.line)
+        {
+            continue;
+        }
+        boundaries.push(declaration.boundary.clone());
+    }
+    boundaries
+}
+
+/// Find declaration boundaries at the given indentation (None = any indentation)
+fn find_boundaries(
+    lines: &[&str],
+    language: ChunkLanguage,
+    indent: Option<usize>,
+) -> Vec<Boundary> {
+    let Some(pattern) = language.declaration_pattern() else {
+        return Vec::new();
+    };
+
+    let mut boundaries = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() || indent.is_some_and(|n| indent_of(line) != n) {
+            continue;
+        }
+        let Some(captures) = pattern.captures(line) else {
+            continue;
+        };
+
+        // Pull leading docs/attributes/decorators into the declaration
+        let mut start = i;
+        while start > 0 && language.is_leading_line(lines[start - 1].trim_start()) {
+            start -= 1;
+        }
+        if boundaries
+            .last()
+            .is_some_and(|b: &Boundary| b.line >= start)
+        {
+            continue;
+        }
+
+        boundaries.push(Boundary {
+            line: start,
+            kind: captures
+                .get(1)
+                .map(|m| m.as_str().trim().to_string())
+                .unwrap_or_else(|| "block".to_string()),
+            name: captures.get(2).map(|m| m.as_str().to_string()),
+        });
+    }
+    boundaries
+}
+
+/// Split the file into segments starting at each boundary
+fn segments(total: usize, boundaries: &[Boundary]) -> Vec<(usize, usize, String, Option<String>)> {
+    let mut result = Vec::new();
+    let mut cursor = 0;
+    let mut kind = "block".to_string();
+    let mut name = None;
+
+    for boundary in boundaries {
+        if boundary.line > cursor {
+            result.push((cursor, boundary.line, kind.clone(), name.clone()));
+        }
+        cursor = boundary.line;
+        kind = boundary.kind.clone();
+        name = boundary.name.clone();
+    }
+    if total > cursor {
+        result.push((cursor, total, kind, name));
+    }
+    result
+}
+
+fn make_chunk(
+    lines: &[&str],
+    start: usize,
+    end: usize,
+    kind: String,
+    name: Option<String>,
+) -> CodeChunk {
+    let content = lines[start..end].join("\n");
+    CodeChunk {
+        start_line: start + 1,
+        end_line: end,
+        kind,
+        name,
+        token_estimate: estimate_tokens(&content),
+        content,
+    }
+}
+
+/// Split an oversized range: nested declarations, then blank lines, then line count
+fn split_oversized(
+    lines: &[&str],
+    (start, end): (usize, usize),
+    (kind, name): (String, Option<String>),
+    language: ChunkLanguage,
+    declarations: Option<&[Declaration]>,
+    max_tokens: usize,
+) -> Vec<CodeChunk> {
+    let mut out = Vec::new();
+    let mut pieces: Vec<(usize, usize)> = Vec::new();
+
+    // Nested declarations (methods inside classes/impls)
+    let nested: Vec<Boundary> = match declarations {
+        Some(declarations) => declaration_boundaries(declarations, |d| {
+            d.boundary.line > start && d.boundary.line < end
+        }),
+        None => find_boundaries(&lines[..end], language, None)
+            .into_iter()
+            .filter(|b| b.line > start)
+            .collect(),
+    };
+    let mut cursor = start;
+    for boundary in &nested {
+        pieces.push((cursor, boundary.line));
+        cursor = boundary.line;
+    }
+    pieces.push((cursor, end));
+
+    // Blank lines, then fixed line windows, for pieces still over budget
+    let mut refined = Vec::new();
+    for (piece_start, piece_end) in pieces {
+        let text_tokens = estimate_tokens(&lines[piece_start..piece_end].join("\n"));
+        if text_tokens <= max_tokens {
+            refined.push((piece_start, piece_end));
+            continue;
+        }
+
+        let mut window_start = piece_start;
+        let mut window_tokens = 0;
+        for (i, line) in lines.iter().enumerate().take(piece_end).skip(piece_start) {
+            let line_tokens = estimate_tokens(line) + 1;
+            let at_blank = line.trim().is_empty();
+            if window_tokens + line_tokens > max_tokens && i > window_start {
+                refined.push((window_start, i));
+                window_start = i;
+                window_tokens = 0;
+            } else if at_blank && window_tokens >= max_tokens / 2 {
+                refined.push((window_start, i + 1));
+                window_start = i + 1;
+                window_tokens = 0;
+                continue;
+            }
+            window_tokens += line_tokens;
+        }
+        if window_start < piece_end {
+            refined.push((window_start, piece_end));
+        }
+    }
+
+    for (i, (piece_start, piece_end)) in refined.into_iter().enumerate() {
+        if piece_start >= piece_end {
+            continue;
+        }
+        let piece_name = name.as_ref().map(|n| {
+            if i == 0 {
+                n.clone()
+            } else {
+                format!("{} (part {})", n, i + 1)
+            }
+        });
+        out.push(make_chunk(
+            lines,
+            piece_start,
+            piece_end,
+            kind.clone(),
+            piece_name,
+        ));
+    }
+    out
+}
+
+/// Split source text into chunks of at most `max_tokens` (estimated)
+pub fn chunk_source(content: &str, language: ChunkLanguage, max_tokens: usize) -> Vec<CodeChunk> {
+    let max_tokens = max_tokens.max(16);
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.is_empty() {
+        return Vec::new();
+    }
+
+    let declarations = syntax_declarations(content, language);
+    let boundaries = match &declarations {
+        Some(declarations) => declaration_boundaries(declarations, |d| d.top_level),
+        None => {
+            // Python and Go declarations live at column 0; elsewhere allow any top-level indent
+            let top_level = match language {
+                ChunkLanguage::Python | ChunkLanguage::Go | ChunkLanguage::Rust => Some(0),
+                _ => lines
+                    .iter()
+                    .filter(|l| !l.trim().is_empty())
+                    .map(|l| indent_of(l))
+                    .min(),
+            };
+            find_boundaries(&lines, language, top_level)
+        }
+    };
+
+    let mut raw = Vec::new();
+    for (start, end, kind, name) in segments(lines.len(), &boundaries) {
+        let tokens = estimate_tokens(&lines[start..end].join("\n"));
+        if tokens > max_tokens {
+            raw.extend(split_oversized(
+                &lines,
+                (start, end),
+                (kind, name),
+                language,
+                declarations.as_deref(),
+                max_tokens,
+            ));
+        } else {
+            raw.push(make_chunk(&lines, start, end, kind, name));
+        }
+    }
+
+    // Merge small neighbours so chunks use the budget instead of one per tiny item
+    let mut merged: Vec<CodeChunk> = Vec::with_capacity(raw.len());
+    for chunk in raw {
+        if let Some(last) = merged.last_mut() {
+            if last.token_estimate + chunk.token_estimate <= max_tokens / 2 {
+                last.end_line = chunk.end_line;
+                last.content = lines[last.start_line - 1..last.end_line].join("\n");
+                last.token_estimate = estimate_tokens(&last.content);
+                if last.name.is_none() {
+                    last.kind = chunk.kind;
+                    last.name = chunk.name;
+                }
+                continue;
+            }
+        }
+        merged.push(chunk);
+    }
+
+    merged
+}
@@ -0,0 +1,245 @@
+//! Replicated text buffer
+//!
+//! Each shared document is a yrs (Yjs) document holding one text. Local
+//! edits travel to the other replicas as yrs updates; yrs orders concurrent
+//! inserts and holds back updates that arrive before the ones they depend
+//! on, so every replica that has applied the same updates ends up with the
+//! same text regardless of delivery order.
+//!
+//! Offsets at the API boundary are UTF-16 code units, matching the editor.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use yrs::updates::decoder::Decode;
+use yrs::{
+    Doc, GetString, OffsetKind, Options, ReadTxn, StateVector, Text, TextRef, Transact, Update,
+};
+
+/// Name of the text inside every document
+const TEXT_NAME: &str = "content";
+
+/// A replicated edit: a yrs update (v1 encoding, base64)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DocOp {
+    pub update: String,
+}
+
+/// An edit to apply to the local editor model, relative to the text after
+/// the previous change in the same batch
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextChange {
+    pub offset: usize,
+    pub delete_length: usize,
+    pub text: String,
+}
+
+/// Full replica state sent to a participant opening a document: the whole
+/// document as one yrs update (v1 encoding, base64)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DocSnapshot {
+    pub state: String,
+}
+
+fn decode_update(data: &str) -> Result<Update, String> {
+    let bytes = STANDARD
+        .decode(data)
+        .map_err(|e| format!("Invalid document update: {}", e))?;
+    Update::decode_v1(&bytes).map_err(|e| format!("Invalid document update: {}", e))
+}
+
+/// The change turning `before` into `after`, if they differ
+fn text_change(before: &str, after: &str) -> Option<TextChange> {
+    if before == after {
+        return None;
+    }
+    let prefix: usize = before
+        .chars()
+        .zip(after.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    let suffix: usize = before[prefix..]
+        .chars()
+        .rev()
+        .zip(after[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    Some(TextChange {
+        offset: before[..prefix].encode_utf16().count(),
+        delete_length: before[prefix..before.len() - suffix].encode_utf16().count(),
+        text: after[prefix..after.len() - suffix].to_string(),
+    })
+}
+
+pub struct TextDocument {
+    doc: Doc,
+    text: TextRef,
+}
+
+impl TextDocument {
+    fn empty(site: u64) -> Self {
+        let doc = Doc::with_options(Options {
+            client_id: site,
+            offset_kind: OffsetKind::Utf16,
+            ..Options::default()
+        });
+        let text = doc.get_or_insert_text(TEXT_NAME);
+        Self { doc, text }
+    }
+
+    /// Seed a document with existing file content; guests start from the
+    /// host's snapshot instead, so the content is only inserted once
+    pub fn new(site: u64, content: &str) -> Self {
+        let document = Self::empty(site);
+        document
+            .text
+            .insert(&mut document.doc.transact_mut(), 0, content);
+        document
+    }
+
+    pub fn from_snapshot(site: u64, snapshot: DocSnapshot) -> Result<Self, String> {
+        let document = Self::empty(site);
+        let update = decode_update(&snapshot.state)?;
+        document
+            .doc
+            .transact_mut()
+            .apply_update(update)
+            .map_err(|e| format!("Invalid document snapshot: {}", e))?;
+        Ok(document)
+    }
+
+    pub fn snapshot(&self) -> DocSnapshot {
+        let state = self
+            .doc
+            .transact()
+            .encode_state_as_update_v1(&StateVector::default());
+        DocSnapshot {
+            state: STANDARD.encode(state),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.text.get_string(&self.doc.transact())
+    }
+
+    /// Apply an edit made in the local editor and return the ops to broadcast
+    pub fn local_edit(&mut self, offset: usize, delete_length: usize, text: &str) -> Vec<DocOp> {
+        let mut txn = self.doc.transact_mut();
+        let length = self.text.len(&txn);
+        let offset = u32::try_from(offset).unwrap_or(u32::MAX).min(length);
+        let delete_length = u32::try_from(delete_length)
+            .unwrap_or(u32::MAX)
+            .min(length - offset);
+        if delete_length == 0 && text.is_empty() {
+            return Vec::new();
+        }
+
+        if delete_length > 0 {
+            self.text.remove_range(&mut txn, offset, delete_length);
+        }
+        if !text.is_empty() {
+            self.text.insert(&mut txn, offset, text);
+        }
+        vec![DocOp {
+            update: STANDARD.encode(txn.encode_update_v1()),
+        }]
+    }
+
+    /// Apply a remote op; returns the changes to the visible text, which
+    /// are none while yrs holds the op back for a missing dependency
+    pub fn apply_remote(&mut self, op: &DocOp) -> Result<Vec<TextChange>, String> {
+        let update = decode_update(&op.update)?;
+        let before = self.text();
+        self.doc
+            .transact_mut()
+            .apply_update(update)
+            .map_err(|e| format!("Failed to apply document update: {}", e))?;
+        Ok(text_change(&before, &self.text()).into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(offset: usize, delete_length: usize, text: &str) -> Option<TextChange> {
+        Some(TextChange {
+            offset,
+            delete_length,
+            text: text.to_string(),
+        })
+    }
+
+    #[test]
+    fn text_change_of_identical_text_is_none() {
+        assert_eq!(text_change("same", "same"), None);
+    }
+
+    #[test]
+    fn text_change_insert_delete_and_replace() {
+        assert_eq!(text_change("hello", "hello world"), change(5, 0, " world"));
+        assert_eq!(text_change("hello world", "world"), change(0, 6, ""));
+        assert_eq!(text_change("let a = 1;", "let b = 1;"), change(4, 1, "b"));
+    }
+
+    #[test]
+    fn text_change_inside_repeated_characters() {
+        // The common prefix is taken first; the suffix only covers the rest
+        assert_eq!(text_change("aaa", "aaaa"), change(3, 0, "a"));
+        assert_eq!(text_change("abab", "ab"), change(2, 2, ""));
+    }
+
+    #[test]
+    fn text_change_offsets_are_utf16() {
+        // "😀" is two UTF-16 code units, "é" one
+        assert_eq!(text_change("😀é x", "😀é yx"), change(4, 0, "y"));
+        assert_eq!(text_change("a😀b", "ab"), change(1, 2, ""));
+    }
+
+    #[test]
+    fn replicas_converge_on_concurrent_edits() {
+        let mut host = TextDocument::new(1, "hello");
+        let mut guest = TextDocument::from_snapshot(2, host.snapshot()).expect("snapshot");
+
+        let from_host = host.local_edit(5, 0, "!");
+        let from_guest = guest.local_edit(0, 1, "J");
+        for op in &from_guest {
+            host.apply_remote(op).expect("applies");
+        }
+        for op in &from_host {
+            guest.apply_remote(op).expect("applies");
+        }
+        assert_eq!(host.text(), "Jello!");
+        assert_eq!(guest.text(), host.text());
+    }
+
+    #[test]
+    fn remote_ops_report_visible_changes() {
+        let mut host = TextDocument::new(1, "a😀c");
+        let mut guest = TextDocument::from_snapshot(2, host.snapshot()).expect("snapshot");
+        let ops = host.local_edit(3, 1, "d");
+        let changes = guest.apply_remote(&ops[0]).expect("applies");
+        assert_eq!(changes, [change(3, 1, "d").expect("change")]);
+        assert_eq!(guest.text(), "a😀d");
+    }
+
+    #[test]
+    fn local_edits_clamp_and_skip_no_ops() {
+        let mut document = TextDocument::new(1, "abc");
+        assert!(document.local_edit(1, 0, "").is_empty());
+        assert_eq!(document.local_edit(10, 5, "z").len(), 1);
+        assert_eq!(document.text(), "abcz");
+    }
+
+    #[test]
+    fn rejects_invalid_updates() {
+        let mut document = TextDocument::new(1, "");
+        let op = DocOp {
+            update: "not base64!".to_string(),
+        };
+        assert!(document.apply_remote(&op).is_err());
+    }
+}
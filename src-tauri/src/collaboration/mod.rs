@@ -0,0 +1,15 @@
+//! Collaboration
+//!
+//! Live-share style sessions: a host shares workspace folders and
+//! participants edit the same buffers concurrently. Buffer edits travel as
+//! CRDT updates (yrs documents, see `crdt`) over a WebSocket
+//! connection to the host, alongside presence (open document, cursors and
+//! selections). Transport and document sync live here; the frontend only
+//! applies `collab:remote-change` events to its editor models and reports
+//! local edits.
+
+pub mod crdt;
+pub mod session;
+mod websocket;
+
+pub use session::*;
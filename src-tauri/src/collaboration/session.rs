@@ -0,0 +1,1224 @@
+//! Collaboration sessions
+//!
+//! The host shares workspace folders (read-only or read-write) and runs a
+//! WebSocket server; guests connect with the join URL, which carries the
+//! session token. The host is the hub: it owns the authoritative replicas,
+//! serves directory listings and document snapshots, checks write access and
+//! relays ops and presence between guests.
+//!
+//! Events emitted to the frontend:
+//! - `collab:remote-change` — `{ document, site, changes }` to apply to the editor model
+//! - `collab:presence` — a participant's document and selections changed
+//! - `collab:participant-joined` / `collab:participant-left`
+//! - `collab:error` — e.g. an edit rejected because the folder is read-only
+//! - `collab:session-ended` — the connection to the host was lost
+
+use super::crdt::{DocOp, DocSnapshot, TextChange, TextDocument};
+use super::websocket::{self, Outgoing, WsReader, WsWriter};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+const DEFAULT_PORT: u16 = 7427;
+
+/// How long a guest waits for the host to answer a request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long the host waits for a new connection to introduce itself
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ShareMode {
+    ReadOnly,
+    ReadWrite,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareFolderRequest {
+    pub path: String,
+    pub mode: ShareMode,
+}
+
+/// A shared folder as guests see it; documents are addressed as `<id>/<relative path>`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedFolderInfo {
+    pub id: String,
+    pub name: String,
+    pub mode: ShareMode,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Participant {
+    pub site: u64,
+    pub name: String,
+    pub host: bool,
+}
+
+/// UTF-16 offsets
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectionRange {
+    pub anchor: usize,
+    pub active: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Presence {
+    pub site: u64,
+    pub name: String,
+    pub document: Option<String>,
+    pub selections: Vec<SelectionRange>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedDirectoryEntry {
+    pub name: String,
+    pub is_directory: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CollaborationSessionInfo {
+    pub session_id: String,
+    /// "host" or "guest"
+    pub role: String,
+    pub site: u64,
+    /// URL guests join with (host only)
+    pub join_url: Option<String>,
+    pub folders: Vec<SharedFolderInfo>,
+    pub participants: Vec<Participant>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RemoteChangeEvent {
+    document: String,
+    site: u64,
+    changes: Vec<TextChange>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ParticipantLeftEvent {
+    site: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CollaborationErrorEvent {
+    document: Option<String>,
+    message: String,
+}
+
+/// Wire protocol between host and guests
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+enum Message {
+    Hello {
+        site: u64,
+        name: String,
+    },
+    Welcome {
+        session_id: String,
+        folders: Vec<SharedFolderInfo>,
+        participants: Vec<Participant>,
+        presence: Vec<Presence>,
+    },
+    Joined {
+        participant: Participant,
+    },
+    Left {
+        site: u64,
+    },
+    Presence {
+        presence: Presence,
+    },
+    ListDirectory {
+        request_id: String,
+        folder: String,
+        path: String,
+    },
+    DirectoryEntries {
+        request_id: String,
+        entries: Vec<SharedDirectoryEntry>,
+    },
+    OpenDocument {
+        request_id: String,
+        document: String,
+    },
+    Snapshot {
+        request_id: String,
+        document: String,
+        snapshot: DocSnapshot,
+    },
+    Ops {
+        document: String,
+        site: u64,
+        ops: Vec<DocOp>,
+    },
+    SaveDocument {
+        request_id: String,
+        document: String,
+    },
+    Ack {
+        request_id: String,
+    },
+    Error {
+        request_id: Option<String>,
+        document: Option<String>,
+        message: String,
+    },
+}
+
+impl Message {
+    fn request_id(&self) -> Option<&str> {
+        match self {
+            Message::DirectoryEntries { request_id, .. }
+            | Message::Snapshot { request_id, .. }
+            | Message::Ack { request_id } => Some(request_id),
+            Message::Error { request_id, .. } => request_id.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+fn send(tx: &UnboundedSender<Outgoing>, message: &Message) {
+    match serde_json::to_string(message) {
+        Ok(text) => {
+            let _ = tx.send(Outgoing::Text(text));
+        }
+        Err(e) => eprintln!("[Collaboration] Failed to encode message: {}", e),
+    }
+}
+
+struct SharedFolder {
+    info: SharedFolderInfo,
+    root: PathBuf,
+}
+
+enum Role {
+    Host {
+        token: String,
+        join_url: String,
+        folders: Vec<SharedFolder>,
+        guests: Mutex<HashMap<u64, UnboundedSender<Outgoing>>>,
+    },
+    Guest {
+        folders: Vec<SharedFolderInfo>,
+        host: UnboundedSender<Outgoing>,
+        requests: Mutex<HashMap<String, oneshot::Sender<Message>>>,
+    },
+}
+
+struct Session {
+    id: String,
+    site: u64,
+    name: String,
+    role: Role,
+    app: AppHandle,
+    /// Open replicas keyed by `<folder id>/<relative path>`
+    documents: Mutex<HashMap<String, TextDocument>>,
+    participants: Mutex<HashMap<u64, Participant>>,
+    presence: Mutex<HashMap<u64, Presence>>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+#[derive(Default)]
+pub struct CollaborationState {
+    session: Mutex<Option<Arc<Session>>>,
+}
+
+impl CollaborationState {
+    fn current(&self) -> Result<Arc<Session>, String> {
+        self.session
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or_else(|| "No collaboration session is active".to_string())
+    }
+}
+
+/// Also the yrs client id of this participant's document replicas
+fn new_site_id() -> u64 {
+    uuid::Uuid::new_v4().as_u128() as u64
+}
+
+fn default_display_name() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "Anonymous".to_string())
+}
+
+/// Best guess at the address other machines can reach us on
+fn local_ip() -> Option<std::net::IpAddr> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    // No packets are sent; this only selects the outbound interface
+    socket.connect("8.8.8.8:80").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+fn query_param<'a>(target: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = target.split_once('?')?;
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key == name).then_some(value)
+    })
+}
+
+/// Split a document key and reject paths that could escape the folder
+fn split_document(document: &str) -> Result<(&str, &Path), String> {
+    let (folder, relative) = document
+        .split_once('/')
+        .ok_or_else(|| format!("Invalid document reference: {}", document))?;
+    let relative = Path::new(relative);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return Err(format!("Invalid document path: {}", document));
+    }
+    Ok((folder, relative))
+}
+
+impl Session {
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        if let Err(e) = self.app.emit(event, payload) {
+            eprintln!("[Collaboration] Failed to emit {}: {}", event, e);
+        }
+    }
+
+    fn is_host(&self) -> bool {
+        matches!(self.role, Role::Host { .. })
+    }
+
+    fn folders(&self) -> Vec<SharedFolderInfo> {
+        match &self.role {
+            Role::Host { folders, .. } => folders.iter().map(|f| f.info.clone()).collect(),
+            Role::Guest { folders, .. } => folders.clone(),
+        }
+    }
+
+    fn folder_mode(&self, folder_id: &str) -> Result<ShareMode, String> {
+        self.folders()
+            .iter()
+            .find(|f| f.id == folder_id)
+            .map(|f| f.mode)
+            .ok_or_else(|| format!("Folder {} is not shared", folder_id))
+    }
+
+    fn participant_list(&self) -> Vec<Participant> {
+        self.participants
+            .lock()
+            .map(|p| p.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn info(&self) -> CollaborationSessionInfo {
+        CollaborationSessionInfo {
+            session_id: self.id.clone(),
+            role: if self.is_host() { "host" } else { "guest" }.to_string(),
+            site: self.site,
+            join_url: match &self.role {
+                Role::Host { join_url, .. } => Some(join_url.clone()),
+                Role::Guest { .. } => None,
+            },
+            folders: self.folders(),
+            participants: self.participant_list(),
+        }
+    }
+
+    fn track(&self, task: JoinHandle<()>) {
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.retain(|t| !t.is_finished());
+            tasks.push(task);
+        }
+    }
+
+    fn shutdown(&self) {
+        match &self.role {
+            Role::Host { guests, .. } => {
+                if let Ok(mut guests) = guests.lock() {
+                    for (_, tx) in guests.drain() {
+                        let _ = tx.send(Outgoing::Close);
+                    }
+                }
+            }
+            Role::Guest { host, .. } => {
+                let _ = host.send(Outgoing::Close);
+            }
+        }
+        if let Ok(mut tasks) = self.tasks.lock() {
+            for task in tasks.drain(..) {
+                task.abort();
+            }
+        }
+    }
+
+    /// Send to every guest except `except` (host only)
+    fn broadcast(&self, message: &Message, except: Option<u64>) {
+        let Role::Host { guests, .. } = &self.role else {
+            return;
+        };
+        let Ok(text) = serde_json::to_string(message) else {
+            return;
+        };
+        if let Ok(guests) = guests.lock() {
+            for (site, tx) in guests.iter() {
+                if Some(*site) != except {
+                    let _ = tx.send(Outgoing::Text(text.clone()));
+                }
+            }
+        }
+    }
+
+    /// Send a message to everyone else in the session
+    fn publish(&self, message: &Message) {
+        match &self.role {
+            Role::Host { .. } => self.broadcast(message, None),
+            Role::Guest { host, .. } => send(host, message),
+        }
+    }
+
+    /// Absolute path inside a shared folder (host only)
+    fn resolve_path(
+        &self,
+        folder_id: &str,
+        relative: &Path,
+    ) -> Result<(PathBuf, ShareMode), String> {
+        let Role::Host { folders, .. } = &self.role else {
+            return Err("Only the host can access shared files".to_string());
+        };
+        let folder = folders
+            .iter()
+            .find(|f| f.info.id == folder_id)
+            .ok_or_else(|| format!("Folder {} is not shared", folder_id))?;
+        let path = folder.root.join(relative);
+        // Symlinks must not lead outside the shared folder; a file that
+        // doesn't exist yet is checked through its parent
+        let canonical = match path.canonicalize() {
+            Ok(canonical) => canonical,
+            Err(_) => match (path.parent(), path.file_name()) {
+                (Some(parent), Some(name)) => parent
+                    .canonicalize()
+                    .map_err(|e| format!("Cannot resolve {:?}: {}", relative, e))?
+                    .join(name),
+                _ => return Err(format!("Cannot resolve {:?}", relative)),
+            },
+        };
+        if !canonical.starts_with(&folder.root) {
+            return Err(format!("{:?} is outside the shared folder", relative));
+        }
+        Ok((canonical, folder.info.mode))
+    }
+
+    fn resolve_document(&self, document: &str) -> Result<(PathBuf, ShareMode), String> {
+        let (folder_id, relative) = split_document(document)?;
+        self.resolve_path(folder_id, relative)
+    }
+
+    fn host_list_directory(
+        &self,
+        folder: &str,
+        path: &str,
+    ) -> Result<Vec<SharedDirectoryEntry>, String> {
+        let relative = Path::new(path);
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(format!("Invalid directory path: {}", path));
+        }
+        let (directory, _) = self.resolve_path(folder, relative)?;
+
+        let mut entries: Vec<SharedDirectoryEntry> = std::fs::read_dir(&directory)
+            .map_err(|e| format!("Failed to read {:?}: {}", directory, e))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| SharedDirectoryEntry {
+                name: entry.file_name().to_string_lossy().to_string(),
+                is_directory: entry.file_type().map(|t| t.is_dir()).unwrap_or(false),
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            b.is_directory
+                .cmp(&a.is_directory)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        Ok(entries)
+    }
+
+    /// Load a document into the host replica set if needed and snapshot it
+    fn host_snapshot(&self, document: &str) -> Result<DocSnapshot, String> {
+        let mut documents = self.documents.lock().map_err(|e| e.to_string())?;
+        if let Some(doc) = documents.get(document) {
+            return Ok(doc.snapshot());
+        }
+        let (path, _) = self.resolve_document(document)?;
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        let doc = TextDocument::new(self.site, &content);
+        let snapshot = doc.snapshot();
+        documents.insert(document.to_string(), doc);
+        Ok(snapshot)
+    }
+
+    fn host_save(&self, document: &str) -> Result<(), String> {
+        let (path, _) = self.resolve_document(document)?;
+        let text = self
+            .documents
+            .lock()
+            .map_err(|e| e.to_string())?
+            .get(document)
+            .map(TextDocument::text)
+            .ok_or_else(|| format!("{} is not open", document))?;
+        crate::project_manager::write_atomic(&path, text.as_bytes())
+    }
+
+    /// Apply ops from another participant to the local replica
+    fn apply_remote_ops(&self, document: &str, site: u64, ops: &[DocOp]) -> Result<(), String> {
+        let changes: Vec<TextChange> = {
+            let mut documents = self.documents.lock().map_err(|e| e.to_string())?;
+            let doc = documents
+                .get_mut(document)
+                .ok_or_else(|| format!("{} is not open", document))?;
+            let mut changes = Vec::new();
+            for op in ops {
+                changes.extend(doc.apply_remote(op)?);
+            }
+            changes
+        };
+        if !changes.is_empty() {
+            self.emit(
+                "collab:remote-change",
+                RemoteChangeEvent {
+                    document: document.to_string(),
+                    site,
+                    changes,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn update_presence(&self, presence: Presence) {
+        if let Ok(mut all) = self.presence.lock() {
+            all.insert(presence.site, presence.clone());
+        }
+        self.emit("collab:presence", presence);
+    }
+
+    fn remove_participant(&self, site: u64) {
+        if let Ok(mut participants) = self.participants.lock() {
+            participants.remove(&site);
+        }
+        if let Ok(mut presence) = self.presence.lock() {
+            presence.remove(&site);
+        }
+        self.emit("collab:participant-left", ParticipantLeftEvent { site });
+    }
+
+    /// Handle a message from a guest (host only)
+    fn handle_guest_message(&self, site: u64, tx: &UnboundedSender<Outgoing>, message: Message) {
+        let reply_error = |request_id: Option<String>, document: Option<String>, message| {
+            send(
+                tx,
+                &Message::Error {
+                    request_id,
+                    document,
+                    message,
+                },
+            )
+        };
+
+        match message {
+            Message::ListDirectory {
+                request_id,
+                folder,
+                path,
+            } => match self.host_list_directory(&folder, &path) {
+                Ok(entries) => send(
+                    tx,
+                    &Message::DirectoryEntries {
+                        request_id,
+                        entries,
+                    },
+                ),
+                Err(e) => reply_error(Some(request_id), None, e),
+            },
+            Message::OpenDocument {
+                request_id,
+                document,
+            } => match self.host_snapshot(&document) {
+                Ok(snapshot) => send(
+                    tx,
+                    &Message::Snapshot {
+                        request_id,
+                        document,
+                        snapshot,
+                    },
+                ),
+                Err(e) => reply_error(Some(request_id), Some(document), e),
+            },
+            Message::Ops { document, ops, .. } => {
+                match self.resolve_document(&document) {
+                    Ok((_, ShareMode::ReadWrite)) => {}
+                    Ok((_, ShareMode::ReadOnly)) => {
+                        return reply_error(
+                            None,
+                            Some(document),
+                            "This folder is shared read-only".to_string(),
+                        )
+                    }
+                    Err(e) => return reply_error(None, Some(document), e),
+                }
+                if let Err(e) = self.apply_remote_ops(&document, site, &ops) {
+                    return reply_error(None, Some(document), e);
+                }
+                self.broadcast(
+                    &Message::Ops {
+                        document,
+                        site,
+                        ops,
+                    },
+                    Some(site),
+                );
+            }
+            Message::SaveDocument {
+                request_id,
+                document,
+            } => {
+                let result = match self.resolve_document(&document) {
+                    Ok((_, ShareMode::ReadWrite)) => self.host_save(&document),
+                    Ok((_, ShareMode::ReadOnly)) => {
+                        Err("This folder is shared read-only".to_string())
+                    }
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(()) => send(tx, &Message::Ack { request_id }),
+                    Err(e) => reply_error(Some(request_id), Some(document), e),
+                }
+            }
+            Message::Presence { mut presence } => {
+                // Guests can only speak for themselves
+                presence.site = site;
+                self.update_presence(presence.clone());
+                self.broadcast(&Message::Presence { presence }, Some(site));
+            }
+            other => eprintln!("[Collaboration] Unexpected message from guest: {:?}", other),
+        }
+    }
+
+    /// Handle a message from the host (guest only)
+    fn handle_host_message(&self, message: Message) {
+        if let (Some(request_id), Role::Guest { requests, .. }) = (message.request_id(), &self.role)
+        {
+            let waiter = requests
+                .lock()
+                .ok()
+                .and_then(|mut requests| requests.remove(request_id));
+            if let Some(waiter) = waiter {
+                let _ = waiter.send(self.open_replica(message));
+                return;
+            }
+        }
+
+        match message {
+            Message::Ops {
+                document,
+                site,
+                ops,
+            } => {
+                // Ops for documents we haven't opened are covered by the snapshot we'd get on open
+                let is_open = self
+                    .documents
+                    .lock()
+                    .map(|docs| docs.contains_key(&document))
+                    .unwrap_or(false);
+                if is_open {
+                    if let Err(e) = self.apply_remote_ops(&document, site, &ops) {
+                        eprintln!("[Collaboration] Failed to apply ops: {}", e);
+                    }
+                }
+            }
+            Message::Presence { presence } => self.update_presence(presence),
+            Message::Joined { participant } => {
+                if let Ok(mut participants) = self.participants.lock() {
+                    participants.insert(participant.site, participant.clone());
+                }
+                self.emit("collab:participant-joined", participant);
+            }
+            Message::Left { site } => self.remove_participant(site),
+            Message::Error {
+                document, message, ..
+            } => self.emit(
+                "collab:error",
+                CollaborationErrorEvent { document, message },
+            ),
+            _ => {}
+        }
+    }
+
+    /// Create the replica for an answered open before any later message is
+    /// read, so ops following the snapshot find the document open. The
+    /// waiting request gets an `Ack` (or the error) in place of the snapshot.
+    fn open_replica(&self, message: Message) -> Message {
+        let Message::Snapshot {
+            request_id,
+            document,
+            snapshot,
+        } = message
+        else {
+            return message;
+        };
+        let opened = TextDocument::from_snapshot(self.site, snapshot).and_then(|doc| {
+            self.documents
+                .lock()
+                .map_err(|e| e.to_string())?
+                .insert(document.clone(), doc);
+            Ok(())
+        });
+        match opened {
+            Ok(()) => Message::Ack { request_id },
+            Err(message) => Message::Error {
+                request_id: Some(request_id),
+                document: Some(document),
+                message,
+            },
+        }
+    }
+
+    /// Send a request to the host and wait for the matching response (guest only)
+    async fn request(&self, build: impl FnOnce(String) -> Message) -> Result<Message, String> {
+        let Role::Guest { host, requests, .. } = &self.role else {
+            return Err("Only guests send requests to the host".to_string());
+        };
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let (waiter, response) = oneshot::channel();
+        requests
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(request_id.clone(), waiter);
+        send(host, &build(request_id.clone()));
+
+        let result = tokio::time::timeout(REQUEST_TIMEOUT, response).await;
+        if let Ok(mut requests) = requests.lock() {
+            requests.remove(&request_id);
+        }
+        match result {
+            Ok(Ok(Message::Error { message, .. })) => Err(message),
+            Ok(Ok(message)) => Ok(message),
+            Ok(Err(_)) => Err("The collaboration session ended".to_string()),
+            Err(_) => Err("The host did not respond".to_string()),
+        }
+    }
+}
+
+/// Forward queued frames to the socket until the channel closes or a close is sent
+fn spawn_writer(mut writer: WsWriter) -> (UnboundedSender<Outgoing>, JoinHandle<()>) {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let task = tokio::spawn(async move {
+        while let Some(outgoing) = rx.recv().await {
+            match writer.send(outgoing).await {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => {
+                    eprintln!("[Collaboration] Connection write failed: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+    (tx, task)
+}
+
+async fn read_message(reader: &mut WsReader) -> Option<Message> {
+    loop {
+        match reader.next_text().await {
+            Ok(Some(text)) => match serde_json::from_str(&text) {
+                Ok(message) => return Some(message),
+                Err(e) => eprintln!("[Collaboration] Ignoring malformed message: {}", e),
+            },
+            Ok(None) => return None,
+            Err(e) => {
+                eprintln!("[Collaboration] Connection read failed: {}", e);
+                return None;
+            }
+        }
+    }
+}
+
+/// Serve one guest connection for the lifetime of the session
+async fn serve_guest(session: Arc<Session>, stream: tokio::net::TcpStream) {
+    let Role::Host { token, guests, .. } = &session.role else {
+        return;
+    };
+    let (mut reader, writer, target) = match websocket::accept(stream).await {
+        Ok(connection) => connection,
+        Err(e) => {
+            eprintln!("[Collaboration] Handshake failed: {}", e);
+            return;
+        }
+    };
+    let (tx, writer_task) = spawn_writer(writer);
+    session.track(writer_task);
+    reader.answer_pings(tx.clone());
+
+    if query_param(&target, "token") != Some(token.as_str()) {
+        send(
+            &tx,
+            &Message::Error {
+                request_id: None,
+                document: None,
+                message: "Invalid session token".to_string(),
+            },
+        );
+        let _ = tx.send(Outgoing::Close);
+        return;
+    }
+
+    let (site, name) = match tokio::time::timeout(HELLO_TIMEOUT, read_message(&mut reader)).await {
+        Ok(Some(Message::Hello { site, name })) => (site, name),
+        _ => {
+            let _ = tx.send(Outgoing::Close);
+            return;
+        }
+    };
+
+    let participant = Participant {
+        site,
+        name,
+        host: false,
+    };
+    send(
+        &tx,
+        &Message::Welcome {
+            session_id: session.id.clone(),
+            folders: session.folders(),
+            participants: session.participant_list(),
+            presence: session
+                .presence
+                .lock()
+                .map(|p| p.values().cloned().collect())
+                .unwrap_or_default(),
+        },
+    );
+    session.broadcast(
+        &Message::Joined {
+            participant: participant.clone(),
+        },
+        None,
+    );
+    if let Ok(mut all) = guests.lock() {
+        all.insert(site, tx.clone());
+    }
+    if let Ok(mut participants) = session.participants.lock() {
+        participants.insert(site, participant.clone());
+    }
+    println!("[Collaboration] {} joined the session", participant.name);
+    session.emit("collab:participant-joined", participant);
+
+    while let Some(message) = read_message(&mut reader).await {
+        session.handle_guest_message(site, &tx, message);
+    }
+
+    if let Ok(mut all) = guests.lock() {
+        all.remove(&site);
+    }
+    let _ = tx.send(Outgoing::Close);
+    session.remove_participant(site);
+    session.broadcast(&Message::Left { site }, None);
+}
+
+fn end_session(session: &Arc<Session>, reason: &str) {
+    let state = session.app.state::<CollaborationState>();
+    if let Ok(mut current) = state.session.lock() {
+        if current.as_ref().is_some_and(|s| Arc::ptr_eq(s, session)) {
+            *current = None;
+        }
+    }
+    println!("[Collaboration] Session ended: {}", reason);
+    session.emit("collab:session-ended", reason.to_string());
+    session.shutdown();
+}
+
+fn set_session(state: &CollaborationState, session: Arc<Session>) -> Result<(), String> {
+    let mut current = state.session.lock().map_err(|e| e.to_string())?;
+    if current.is_some() {
+        session.shutdown();
+        return Err("Leave the current collaboration session first".to_string());
+    }
+    *current = Some(session);
+    Ok(())
+}
+
+/// Host a session sharing the given folders. Listens on localhost unless
+/// `bind_address` says otherwise, e.g. `0.0.0.0` to let other machines join.
+#[tauri::command]
+pub async fn collab_host_session(
+    app: AppHandle,
+    state: State<'_, CollaborationState>,
+    folders: Vec<ShareFolderRequest>,
+    display_name: Option<String>,
+    port: Option<u16>,
+    bind_address: Option<String>,
+) -> Result<CollaborationSessionInfo, String> {
+    if state.current().is_ok() {
+        return Err("Leave the current collaboration session first".to_string());
+    }
+    if folders.is_empty() {
+        return Err("Select at least one folder to share".to_string());
+    }
+
+    let mut shared = Vec::new();
+    for (index, folder) in folders.into_iter().enumerate() {
+        let root = PathBuf::from(&folder.path)
+            .canonicalize()
+            .map_err(|e| format!("Cannot share {}: {}", folder.path, e))?;
+        if !root.is_dir() {
+            return Err(format!("{} is not a folder", folder.path));
+        }
+        shared.push(SharedFolder {
+            info: SharedFolderInfo {
+                id: index.to_string(),
+                name: root
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| folder.path.clone()),
+                mode: folder.mode,
+            },
+            root,
+        });
+    }
+
+    let bind_address = bind_address.unwrap_or_else(|| "127.0.0.1".to_string());
+    let listener = TcpListener::bind((bind_address.as_str(), port.unwrap_or(DEFAULT_PORT)))
+        .await
+        .map_err(|e| format!("Failed to listen on {}: {}", bind_address, e))?;
+    let address = listener.local_addr().map_err(|e| e.to_string())?;
+    let advertised = if address.ip().is_unspecified() {
+        local_ip().unwrap_or(address.ip())
+    } else {
+        address.ip()
+    };
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let token = uuid::Uuid::new_v4().to_string().replace('-', "");
+    let site = new_site_id();
+    let name = display_name.unwrap_or_else(default_display_name);
+    let join_url = format!(
+        "ws://{}/{}?token={}",
+        std::net::SocketAddr::new(advertised, address.port()),
+        session_id,
+        token
+    );
+
+    let session = Arc::new(Session {
+        id: session_id,
+        site,
+        name: name.clone(),
+        role: Role::Host {
+            token,
+            join_url,
+            folders: shared,
+            guests: Mutex::new(HashMap::new()),
+        },
+        app,
+        documents: Mutex::new(HashMap::new()),
+        participants: Mutex::new(HashMap::from([(
+            site,
+            Participant {
+                site,
+                name,
+                host: true,
+            },
+        )])),
+        presence: Mutex::new(HashMap::new()),
+        tasks: Mutex::new(Vec::new()),
+    });
+
+    let accept_session = Arc::clone(&session);
+    let accept_task = tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => {
+                    println!("[Collaboration] Connection from {}", peer);
+                    let _ = stream.set_nodelay(true);
+                    let task = tokio::spawn(serve_guest(Arc::clone(&accept_session), stream));
+                    accept_session.track(task);
+                }
+                Err(e) => eprintln!("[Collaboration] Accept failed: {}", e),
+            }
+        }
+    });
+    session.track(accept_task);
+
+    set_session(&state, Arc::clone(&session))?;
+    println!(
+        "[Collaboration] Hosting session {} on {}",
+        session.id, address
+    );
+    Ok(session.info())
+}
+
+/// Join a session with the URL the host shared
+#[tauri::command]
+pub async fn collab_join_session(
+    app: AppHandle,
+    state: State<'_, CollaborationState>,
+    url: String,
+    display_name: Option<String>,
+) -> Result<CollaborationSessionInfo, String> {
+    if state.current().is_ok() {
+        return Err("Leave the current collaboration session first".to_string());
+    }
+
+    let (mut reader, writer) = websocket::connect(url.trim())
+        .await
+        .map_err(|e| format!("Failed to connect: {}", e))?;
+    let (host, writer_task) = spawn_writer(writer);
+    reader.answer_pings(host.clone());
+
+    let site = new_site_id();
+    let name = display_name.unwrap_or_else(default_display_name);
+    send(
+        &host,
+        &Message::Hello {
+            site,
+            name: name.clone(),
+        },
+    );
+
+    let welcome = tokio::time::timeout(REQUEST_TIMEOUT, read_message(&mut reader)).await;
+    let (session_id, folders, mut participants, presence) = match welcome {
+        Ok(Some(Message::Welcome {
+            session_id,
+            folders,
+            participants,
+            presence,
+        })) => (session_id, folders, participants, presence),
+        Ok(Some(Message::Error { message, .. })) => {
+            writer_task.abort();
+            return Err(message);
+        }
+        _ => {
+            writer_task.abort();
+            return Err("The host did not accept the connection".to_string());
+        }
+    };
+    participants.push(Participant {
+        site,
+        name: name.clone(),
+        host: false,
+    });
+
+    let session = Arc::new(Session {
+        id: session_id,
+        site,
+        name,
+        role: Role::Guest {
+            folders,
+            host,
+            requests: Mutex::new(HashMap::new()),
+        },
+        app,
+        documents: Mutex::new(HashMap::new()),
+        participants: Mutex::new(participants.into_iter().map(|p| (p.site, p)).collect()),
+        presence: Mutex::new(presence.into_iter().map(|p| (p.site, p)).collect()),
+        tasks: Mutex::new(vec![writer_task]),
+    });
+
+    let read_session = Arc::clone(&session);
+    let read_task = tokio::spawn(async move {
+        while let Some(message) = read_message(&mut reader).await {
+            read_session.handle_host_message(message);
+        }
+        end_session(&read_session, "Disconnected from host");
+    });
+    session.track(read_task);
+
+    set_session(&state, Arc::clone(&session))?;
+    println!("[Collaboration] Joined session {}", session.id);
+    Ok(session.info())
+}
+
+/// Leave the current session; when hosting this ends it for everyone
+#[tauri::command]
+pub fn collab_leave_session(state: State<'_, CollaborationState>) -> Result<(), String> {
+    let session = state.session.lock().map_err(|e| e.to_string())?.take();
+    if let Some(session) = session {
+        println!("[Collaboration] Leaving session {}", session.id);
+        session.shutdown();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn collab_get_session(
+    state: State<'_, CollaborationState>,
+) -> Result<Option<CollaborationSessionInfo>, String> {
+    Ok(state.current().ok().map(|session| session.info()))
+}
+
+/// List a directory of a shared folder (`path` relative to the folder, "" for its root)
+#[tauri::command]
+pub async fn collab_list_directory(
+    state: State<'_, CollaborationState>,
+    folder: String,
+    path: String,
+) -> Result<Vec<SharedDirectoryEntry>, String> {
+    let session = state.current()?;
+    if session.is_host() {
+        return session.host_list_directory(&folder, &path);
+    }
+    match session
+        .request(|request_id| Message::ListDirectory {
+            request_id,
+            folder,
+            path,
+        })
+        .await?
+    {
+        Message::DirectoryEntries { entries, .. } => Ok(entries),
+        _ => Err("Unexpected response from host".to_string()),
+    }
+}
+
+/// Open a shared document and return its current text
+#[tauri::command]
+pub async fn collab_open_document(
+    state: State<'_, CollaborationState>,
+    document: String,
+) -> Result<String, String> {
+    let session = state.current()?;
+    split_document(&document)?;
+    if session.is_host() {
+        session.host_snapshot(&document)?;
+    } else {
+        let response = session
+            .request(|request_id| Message::OpenDocument {
+                request_id,
+                document: document.clone(),
+            })
+            .await?;
+        // The replica was created when the snapshot arrived
+        let Message::Ack { .. } = response else {
+            return Err("Unexpected response from host".to_string());
+        };
+    }
+
+    let documents = session.documents.lock().map_err(|e| e.to_string())?;
+    Ok(documents
+        .get(&document)
+        .map(TextDocument::text)
+        .unwrap_or_default())
+}
+
+/// Drop a guest's local replica once the editor closes the document
+#[tauri::command]
+pub fn collab_close_document(
+    state: State<'_, CollaborationState>,
+    document: String,
+) -> Result<(), String> {
+    let session = state.current()?;
+    // The host keeps its replicas: they are the source of truth for guests
+    if !session.is_host() {
+        session
+            .documents
+            .lock()
+            .map_err(|e| e.to_string())?
+            .remove(&document);
+    }
+    Ok(())
+}
+
+/// Apply a local editor change (UTF-16 offsets) and share it
+#[tauri::command]
+pub fn collab_apply_edit(
+    state: State<'_, CollaborationState>,
+    document: String,
+    offset: usize,
+    delete_length: usize,
+    text: String,
+) -> Result<(), String> {
+    let session = state.current()?;
+    let (folder, _) = split_document(&document)?;
+    if !session.is_host() && session.folder_mode(folder)? == ShareMode::ReadOnly {
+        return Err("This folder is shared read-only".to_string());
+    }
+
+    let ops = session
+        .documents
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get_mut(&document)
+        .ok_or_else(|| format!("{} is not open", document))?
+        .local_edit(offset, delete_length, &text);
+    if !ops.is_empty() {
+        session.publish(&Message::Ops {
+            document,
+            site: session.site,
+            ops,
+        });
+    }
+    Ok(())
+}
+
+/// Share this participant's cursor and selections
+#[tauri::command]
+pub fn collab_update_presence(
+    state: State<'_, CollaborationState>,
+    document: Option<String>,
+    selections: Vec<SelectionRange>,
+) -> Result<(), String> {
+    let session = state.current()?;
+    let presence = Presence {
+        site: session.site,
+        name: session.name.clone(),
+        document,
+        selections,
+    };
+    if let Ok(mut all) = session.presence.lock() {
+        all.insert(session.site, presence.clone());
+    }
+    session.publish(&Message::Presence { presence });
+    Ok(())
+}
+
+/// Write a shared document to disk on the host
+#[tauri::command]
+pub async fn collab_save_document(
+    state: State<'_, CollaborationState>,
+    document: String,
+) -> Result<(), String> {
+    let session = state.current()?;
+    if session.is_host() {
+        return session.host_save(&document);
+    }
+    match session
+        .request(|request_id| Message::SaveDocument {
+            request_id,
+            document,
+        })
+        .await?
+    {
+        Message::Ack { .. } => Ok(()),
+        _ => Err("Unexpected response from host".to_string()),
+    }
+}
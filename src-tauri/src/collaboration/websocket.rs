@@ -0,0 +1,455 @@
+//! Minimal WebSocket transport (RFC 6455)
+//!
+//! Just enough of the protocol for session traffic: the HTTP upgrade
+//! handshake on both ends, text frames (with fragmentation), ping/pong and
+//! close. Plain `ws://` only; sessions are meant for trusted networks or an
+//! SSH/VPN tunnel and are gated by the session token.
+
+use base64::Engine;
+use sha1::{Digest, Sha1};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::UnboundedSender;
+
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Largest accepted message; snapshots of big files fit comfortably
+const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Largest accepted handshake (request or status line plus headers)
+const MAX_HEAD_SIZE: usize = 16 * 1024;
+
+/// How long the peer has to send its handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+/// Frames queued for the writer task
+pub(super) enum Outgoing {
+    Text(String),
+    Pong(Vec<u8>),
+    Close,
+}
+
+pub(super) struct WsReader {
+    reader: BufReader<OwnedReadHalf>,
+    /// Used to answer pings
+    control: Option<UnboundedSender<Outgoing>>,
+}
+
+pub(super) struct WsWriter {
+    writer: OwnedWriteHalf,
+    /// Clients must mask their frames, servers must not
+    mask: bool,
+}
+
+fn accept_key(key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(key.trim().as_bytes());
+    hasher.update(HANDSHAKE_GUID.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(hasher.finalize())
+}
+
+fn invalid(message: impl Into<String>) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.into())
+}
+
+/// Read one handshake line, counting it against the remaining `budget`
+async fn read_head_line(
+    reader: &mut BufReader<OwnedReadHalf>,
+    budget: &mut usize,
+) -> std::io::Result<String> {
+    let mut line = String::new();
+    let read = (&mut *reader)
+        .take(*budget as u64)
+        .read_line(&mut line)
+        .await?;
+    if !line.ends_with('\n') {
+        return Err(invalid(if read == *budget {
+            "Handshake too large"
+        } else {
+            "Connection closed during handshake"
+        }));
+    }
+    *budget -= read;
+    Ok(line)
+}
+
+/// Read the request/status line and headers of an HTTP message, giving up
+/// after `HANDSHAKE_TIMEOUT`
+async fn read_http_head(
+    reader: &mut BufReader<OwnedReadHalf>,
+) -> std::io::Result<(String, Vec<(String, String)>)> {
+    tokio::time::timeout(HANDSHAKE_TIMEOUT, read_http_head_unbounded(reader))
+        .await
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "Handshake timed out"))?
+}
+
+async fn read_http_head_unbounded(
+    reader: &mut BufReader<OwnedReadHalf>,
+) -> std::io::Result<(String, Vec<(String, String)>)> {
+    let mut budget = MAX_HEAD_SIZE;
+    let first_line = read_head_line(reader, &mut budget).await?;
+    let mut headers = Vec::new();
+    loop {
+        let line = read_head_line(reader, &mut budget).await?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if headers.len() > 100 {
+            return Err(invalid("Too many handshake headers"));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    Ok((first_line.trim_end().to_string(), headers))
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+/// Complete the server side of the handshake; returns the request target
+/// (path and query) so the caller can check the session token
+pub(super) async fn accept(stream: TcpStream) -> std::io::Result<(WsReader, WsWriter, String)> {
+    let (read_half, mut write_half) = stream.into_split();
+    let mut reader = BufReader::new(read_half);
+    let (request_line, headers) = read_http_head(&mut reader).await?;
+
+    let target = request_line
+        .strip_prefix("GET ")
+        .and_then(|rest| rest.split_whitespace().next())
+        .ok_or_else(|| invalid("Expected a GET upgrade request"))?
+        .to_string();
+    let key = header(&headers, "sec-websocket-key")
+        .filter(|_| {
+            header(&headers, "upgrade").is_some_and(|u| u.eq_ignore_ascii_case("websocket"))
+        })
+        .ok_or_else(|| invalid("Not a WebSocket upgrade request"))?;
+
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    );
+    write_half.write_all(response.as_bytes()).await?;
+
+    Ok((
+        WsReader {
+            reader,
+            control: None,
+        },
+        WsWriter {
+            writer: write_half,
+            mask: false,
+        },
+        target,
+    ))
+}
+
+/// Connect to a `ws://host:port/path?query` URL
+pub(super) async fn connect(url: &str) -> std::io::Result<(WsReader, WsWriter)> {
+    let rest = url
+        .strip_prefix("ws://")
+        .ok_or_else(|| invalid("Only ws:// session URLs are supported"))?;
+    let (authority, target) = match rest.find(['/', '?']) {
+        Some(index) if rest[index..].starts_with('/') => {
+            (&rest[..index], rest[index..].to_string())
+        }
+        Some(index) => (&rest[..index], format!("/{}", &rest[index..])),
+        None => (rest, "/".to_string()),
+    };
+
+    let stream = TcpStream::connect(authority).await?;
+    stream.set_nodelay(true)?;
+    let (read_half, mut write_half) = stream.into_split();
+    let mut reader = BufReader::new(read_half);
+
+    let key = base64::engine::general_purpose::STANDARD.encode(uuid::Uuid::new_v4().as_bytes());
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        target, authority, key
+    );
+    write_half.write_all(request.as_bytes()).await?;
+
+    let (status_line, headers) = read_http_head(&mut reader).await?;
+    if status_line.split_whitespace().nth(1) != Some("101") {
+        return Err(invalid(format!("Upgrade rejected: {}", status_line)));
+    }
+    if header(&headers, "sec-websocket-accept") != Some(accept_key(&key).as_str()) {
+        return Err(invalid("Invalid Sec-WebSocket-Accept"));
+    }
+
+    Ok((
+        WsReader {
+            reader,
+            control: None,
+        },
+        WsWriter {
+            writer: write_half,
+            mask: true,
+        },
+    ))
+}
+
+impl WsReader {
+    /// Answer pings by queueing pongs on the connection's writer
+    pub(super) fn answer_pings(&mut self, control: UnboundedSender<Outgoing>) {
+        self.control = Some(control);
+    }
+
+    /// Next text message; `None` once the peer closes the connection
+    pub(super) async fn next_text(&mut self) -> std::io::Result<Option<String>> {
+        let mut message: Vec<u8> = Vec::new();
+        loop {
+            let mut head = [0u8; 2];
+            match self.reader.read_exact(&mut head).await {
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e),
+            }
+            let fin = head[0] & 0x80 != 0;
+            let opcode = head[0] & 0x0F;
+            let masked = head[1] & 0x80 != 0;
+            let length = match head[1] & 0x7F {
+                126 => self.reader.read_u16().await? as u64,
+                127 => self.reader.read_u64().await?,
+                n => n as u64,
+            };
+            let length = usize::try_from(length)
+                .ok()
+                .filter(|length| {
+                    message
+                        .len()
+                        .checked_add(*length)
+                        .is_some_and(|total| total <= MAX_MESSAGE_SIZE)
+                })
+                .ok_or_else(|| invalid("Message too large"))?;
+            let mut mask = [0u8; 4];
+            if masked {
+                self.reader.read_exact(&mut mask).await?;
+            }
+            let mut payload = vec![0u8; length];
+            self.reader.read_exact(&mut payload).await?;
+            if masked {
+                for (i, byte) in payload.iter_mut().enumerate() {
+                    *byte ^= mask[i % 4];
+                }
+            }
+
+            match opcode {
+                OPCODE_CLOSE => return Ok(None),
+                OPCODE_PING => {
+                    if let Some(control) = &self.control {
+                        let _ = control.send(Outgoing::Pong(payload));
+                    }
+                }
+                OPCODE_PONG => {}
+                OPCODE_TEXT | OPCODE_BINARY | OPCODE_CONTINUATION => {
+                    message.extend_from_slice(&payload);
+                    if fin {
+                        return String::from_utf8(message)
+                            .map(Some)
+                            .map_err(|_| invalid("Message is not valid UTF-8"));
+                    }
+                }
+                other => return Err(invalid(format!("Unknown opcode {}", other))),
+            }
+        }
+    }
+}
+
+impl WsWriter {
+    async fn send_frame(&mut self, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+        let mut frame = Vec::with_capacity(payload.len() + 14);
+        frame.push(0x80 | opcode);
+        let mask_bit = if self.mask { 0x80 } else { 0 };
+        match payload.len() {
+            n if n < 126 => frame.push(mask_bit | n as u8),
+            n if n <= u16::MAX as usize => {
+                frame.push(mask_bit | 126);
+                frame.extend_from_slice(&(n as u16).to_be_bytes());
+            }
+            n => {
+                frame.push(mask_bit | 127);
+                frame.extend_from_slice(&(n as u64).to_be_bytes());
+            }
+        }
+        if self.mask {
+            let mask: [u8; 4] = uuid::Uuid::new_v4().as_bytes()[..4]
+                .try_into()
+                .unwrap_or_default();
+            frame.extend_from_slice(&mask);
+            frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        } else {
+            frame.extend_from_slice(payload);
+        }
+        self.writer.write_all(&frame).await
+    }
+
+    /// Write a queued frame; returns false once the connection should end
+    pub(super) async fn send(&mut self, outgoing: Outgoing) -> std::io::Result<bool> {
+        match outgoing {
+            Outgoing::Text(text) => self.send_frame(OPCODE_TEXT, text.as_bytes()).await?,
+            Outgoing::Pong(payload) => self.send_frame(OPCODE_PONG, &payload).await?,
+            Outgoing::Close => {
+                self.send_frame(OPCODE_CLOSE, &[]).await?;
+                let _ = self.writer.shutdown().await;
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// A listener on a free loopback port and its address
+    async fn listen() -> (TcpListener, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("binds");
+        let address = listener.local_addr().expect("address").to_string();
+        (listener, address)
+    }
+
+    /// A raw client that has completed the upgrade, and the server's reader
+    async fn raw_client() -> (TcpStream, WsReader) {
+        let (listener, address) = listen().await;
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.expect("accepts");
+            accept(stream).await.expect("upgrades")
+        });
+        let mut client = TcpStream::connect(&address).await.expect("connects");
+        client
+            .write_all(
+                b"GET / HTTP/1.1\r\nUpgrade: websocket\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+            )
+            .await
+            .expect("writes");
+        let (reader, _writer, _) = server.await.expect("server task");
+        (client, reader)
+    }
+
+    /// A masked client frame
+    fn frame(fin: bool, opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [1u8, 2, 3, 4];
+        let mut frame = vec![
+            (if fin { 0x80 } else { 0 }) | opcode,
+            0x80 | payload.len() as u8,
+        ];
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        frame
+    }
+
+    #[test]
+    fn accept_key_matches_rfc_example() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[tokio::test]
+    async fn round_trips_messages_of_every_length_encoding() {
+        let (listener, address) = listen().await;
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.expect("accepts");
+            accept(stream).await.expect("upgrades")
+        });
+        let (mut client_reader, mut client_writer) =
+            connect(&format!("ws://{}?token=abc", address))
+                .await
+                .expect("connects");
+        let (mut server_reader, mut server_writer, target) = server.await.expect("server task");
+        assert_eq!(target, "/?token=abc");
+
+        for length in [5, 200, 70_000] {
+            let text = "x".repeat(length);
+            assert!(client_writer
+                .send(Outgoing::Text(text.clone()))
+                .await
+                .expect("sends"));
+            assert_eq!(
+                server_reader.next_text().await.expect("reads"),
+                Some(text.clone())
+            );
+            server_writer
+                .send(Outgoing::Text(text.clone()))
+                .await
+                .expect("sends");
+            assert_eq!(client_reader.next_text().await.expect("reads"), Some(text));
+        }
+
+        assert!(!client_writer.send(Outgoing::Close).await.expect("closes"));
+        assert_eq!(server_reader.next_text().await.expect("reads"), None);
+    }
+
+    #[tokio::test]
+    async fn joins_fragments_and_answers_interleaved_pings() {
+        let (mut client, mut reader) = raw_client().await;
+        let (control, mut pongs) = tokio::sync::mpsc::unbounded_channel();
+        reader.answer_pings(control);
+
+        let mut frames = frame(false, OPCODE_TEXT, b"Hel");
+        frames.extend(frame(true, OPCODE_PING, b"beat"));
+        frames.extend(frame(true, OPCODE_CONTINUATION, b"lo"));
+        frames.extend(frame(true, OPCODE_CLOSE, b""));
+        client.write_all(&frames).await.expect("writes");
+
+        assert_eq!(
+            reader.next_text().await.expect("reads").as_deref(),
+            Some("Hello")
+        );
+        match pongs.try_recv() {
+            Ok(Outgoing::Pong(payload)) => assert_eq!(payload, b"beat"),
+            _ => panic!("expected a pong"),
+        }
+        assert_eq!(reader.next_text().await.expect("reads"), None);
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_and_invalid_messages() {
+        let (mut client, mut reader) = raw_client().await;
+        let mut head = vec![0x80 | OPCODE_TEXT, 127];
+        head.extend_from_slice(&(MAX_MESSAGE_SIZE as u64 + 1).to_be_bytes());
+        client.write_all(&head).await.expect("writes");
+        assert!(reader.next_text().await.is_err());
+
+        let (mut client, mut reader) = raw_client().await;
+        client
+            .write_all(&frame(true, OPCODE_TEXT, &[0xff, 0xfe]))
+            .await
+            .expect("writes");
+        assert!(reader.next_text().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn refuses_requests_that_are_not_upgrades() {
+        let (listener, address) = listen().await;
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.expect("accepts");
+            accept(stream).await.map(|_| ())
+        });
+        let mut client = TcpStream::connect(&address).await.expect("connects");
+        client
+            .write_all(b"GET / HTTP/1.1\r\nHost: x\r\n\r\n")
+            .await
+            .expect("writes");
+        assert!(server.await.expect("server task").is_err());
+        assert!(connect("http://example.com").await.is_err());
+    }
+}
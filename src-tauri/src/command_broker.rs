@@ -0,0 +1,484 @@
+//! Command Broker
+//!
+//! Runs registered commands instead of arbitrary shell strings. Each command
+//! declares its program and an argument schema; callers (tasks, agents,
+//! extensions) pass named arguments that are validated and turned into argv
+//! without going through a shell.
+//!
+//! Policy comes from settings resolved per workspace:
+//! - `commands.allow`: command IDs that may run (empty = all registered)
+//! - `commands.deny`: command IDs that never run (wins over allow)
+//! - `security.workspace.trusted`: untrusted workspaces only run commands
+//!   registered with `requires_trust: false`
+//!
+//! IDs in allow/deny lists may end with `*` to match a prefix (e.g. `npm.*`).
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use tauri::State;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::watch;
+
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+const DEFAULT_MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// How long output is still read once the process has ended; processes it
+/// left running in the background can keep the pipes open indefinitely
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Type of a command argument
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ArgKind {
+    String,
+    Integer,
+    Boolean,
+    /// Path that must stay inside the workspace
+    Path,
+    /// One of `values`
+    Enum,
+}
+
+/// Schema for one named argument
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArgSpec {
+    pub name: String,
+    pub kind: ArgKind,
+    #[serde(default)]
+    pub required: bool,
+    /// Flag placed before the value (or alone for booleans), e.g. "--release"
+    #[serde(default)]
+    pub flag: Option<String>,
+    /// Regex the value must match (string arguments)
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// Allowed values (enum arguments)
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+/// A runnable command registered with the broker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandSpec {
+    pub id: String,
+    pub program: String,
+    /// Arguments always passed before the schema arguments
+    #[serde(default)]
+    pub fixed_args: Vec<String>,
+    #[serde(default)]
+    pub args: Vec<ArgSpec>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Who registered the command ("tasks", "agent", an extension ID, ...)
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default = "default_requires_trust")]
+    pub requires_trust: bool,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub max_output_bytes: Option<usize>,
+    /// Problem matchers (`$rustc`, `$tsc`, ...) run over the output
+    #[serde(default)]
+    pub problem_matchers: Vec<String>,
+}
+
+fn default_requires_trust() -> bool {
+    true
+}
+
+/// Structured result of a brokered run
+#[derive(Debug, Clone, Serialize)]
+pub struct BrokerResult {
+    pub command_id: String,
+    pub argv: Vec<String>,
+    /// None when the process was killed (timeout or signal)
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub stdout_truncated: bool,
+    pub stderr_truncated: bool,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+    /// Problems found by the command's problem matchers
+    pub diagnostics: Vec<crate::problem_matcher::Diagnostic>,
+}
+
+/// Registered commands
+#[derive(Default)]
+pub struct CommandBrokerState {
+    commands: RwLock<HashMap<String, CommandSpec>>,
+}
+
+/// Output of a process run with size caps
+pub(crate) struct CapturedOutput {
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub stdout_truncated: bool,
+    pub stderr_truncated: bool,
+    pub timed_out: bool,
+}
+
+/// Read a stream until it ends or `stop` is set, keeping at most `limit` bytes
+async fn read_capped<R: AsyncRead + Unpin>(
+    mut reader: R,
+    limit: usize,
+    mut stop: watch::Receiver<bool>,
+) -> (Vec<u8>, bool) {
+    let mut kept = Vec::new();
+    let mut truncated = false;
+    let mut buf = [0u8; 8192];
+
+    loop {
+        let read = tokio::select! {
+            read = reader.read(&mut buf) => read,
+            _ = stop.wait_for(|stop| *stop) => break,
+        };
+        match read {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                let room = limit.saturating_sub(kept.len());
+                if n > room {
+                    truncated = true;
+                }
+                kept.extend_from_slice(&buf[..n.min(room)]);
+            }
+        }
+    }
+    (kept, truncated)
+}
+
+/// Wait for the stdout/stderr readers of a process that has ended. Reading
+/// stops once `OUTPUT_DRAIN_TIMEOUT` has passed (via `stop`), and whatever
+/// each reader kept so far is returned.
+pub(crate) async fn collect_output(
+    stdout_task: tokio::task::JoinHandle<(Vec<u8>, bool)>,
+    stderr_task: tokio::task::JoinHandle<(Vec<u8>, bool)>,
+    stop: watch::Sender<bool>,
+) -> ((Vec<u8>, bool), (Vec<u8>, bool)) {
+    let outputs = async { tokio::join!(stdout_task, stderr_task) };
+    tokio::pin!(outputs);
+    let (stdout, stderr) = match tokio::time::timeout(OUTPUT_DRAIN_TIMEOUT, &mut outputs).await {
+        Ok(outputs) => outputs,
+        Err(_) => {
+            let _ = stop.send(true);
+            outputs.await
+        }
+    };
+    (stdout.unwrap_or_default(), stderr.unwrap_or_default())
+}
+
+/// Run a process, draining stdout/stderr concurrently with a size cap per stream
+/// The child is killed when the timeout elapses; output is read for at most
+/// `OUTPUT_DRAIN_TIMEOUT` after it ends
+pub(crate) async fn run_captured(
+    mut cmd: tokio::process::Command,
+    timeout: Duration,
+    max_output_bytes: usize,
+) -> Result<CapturedOutput, String> {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to spawn command: {}", e))?;
+
+    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+    let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+    let (stop_output, stop) = watch::channel(false);
+    let stdout_task = tokio::spawn(read_capped(stdout, max_output_bytes, stop.clone()));
+    let stderr_task = tokio::spawn(read_capped(stderr, max_output_bytes, stop));
+
+    let (exit_code, timed_out) = match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(Ok(status)) => (status.code(), false),
+        Ok(Err(e)) => return Err(format!("Failed to wait for command: {}", e)),
+        Err(_) => {
+            let _ = child.kill().await;
+            (None, true)
+        }
+    };
+
+    let ((stdout, stdout_truncated), (stderr, stderr_truncated)) =
+        collect_output(stdout_task, stderr_task, stop_output).await;
+
+    Ok(CapturedOutput {
+        exit_code,
+        stdout: String::from_utf8_lossy(&stdout).to_string(),
+        stderr: String::from_utf8_lossy(&stderr).to_string(),
+        stdout_truncated,
+        stderr_truncated,
+        timed_out,
+    })
+}
+
+/// Match a command ID against an allow/deny entry (`*` suffix = prefix match)
+fn id_matches(pattern: &str, id: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => id.starts_with(prefix),
+        None => pattern == id,
+    }
+}
+
+fn setting_list(workspace_root: Option<&str>, key: &str) -> Vec<String> {
+    crate::configuration_manager::resolve_setting(workspace_root, key)
+        .and_then(|v| v.as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+        .collect()
+}
+
+/// Check allow/deny lists and workspace trust for a command
+fn check_policy(spec: &CommandSpec, workspace_root: Option<&str>) -> Result<(), String> {
+    use crate::configuration_manager::resolve_setting;
+
+    if setting_list(workspace_root, "commands.deny")
+        .iter()
+        .any(|p| id_matches(p, &spec.id))
+    {
+        return Err(format!("Command '{}' is denied by commands.deny", spec.id));
+    }
+
+    let allow = setting_list(workspace_root, "commands.allow");
+    if !allow.is_empty() && !allow.iter().any(|p| id_matches(p, &spec.id)) {
+        return Err(format!("Command '{}' is not in commands.allow", spec.id));
+    }
+
+    let trusted = resolve_setting(workspace_root, "security.workspace.trusted")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    if spec.requires_trust && !trusted {
+        return Err(format!(
+            "Command '{}' requires a trusted workspace",
+            spec.id
+        ));
+    }
+
+    Ok(())
+}
+
+/// Resolve a path argument and make sure it stays inside the workspace
+fn validate_path_arg(value: &str, workspace_root: Option<&Path>) -> Result<String, String> {
+    let Some(root) = workspace_root else {
+        return Err("Path arguments require a workspace root".to_string());
+    };
+    let root = root
+        .canonicalize()
+        .map_err(|e| format!("Invalid workspace root: {}", e))?;
+
+    let joined = root.join(value);
+    // The path itself may not exist yet (output files); check its parent then
+    let resolved = joined
+        .canonicalize()
+        .or_else(|_| {
+            let parent = joined.parent().unwrap_or(&root).canonicalize()?;
+            Ok::<PathBuf, std::io::Error>(parent.join(joined.file_name().unwrap_or_default()))
+        })
+        .map_err(|e| format!("Invalid path '{}': {}", value, e))?;
+
+    if !resolved.starts_with(&root) {
+        return Err(format!("Path '{}' is outside the workspace", value));
+    }
+    Ok(resolved.to_string_lossy().to_string())
+}
+
+/// Validate named arguments against the schema and build argv
+fn build_argv(
+    spec: &CommandSpec,
+    args: &HashMap<String, serde_json::Value>,
+    workspace_root: Option<&Path>,
+) -> Result<Vec<String>, String> {
+    if let Some(unknown) = args
+        .keys()
+        .find(|k| !spec.args.iter().any(|a| &a.name == *k))
+    {
+        return Err(format!("Unknown argument '{}' for '{}'", unknown, spec.id));
+    }
+
+    let mut argv = spec.fixed_args.clone();
+    for arg in &spec.args {
+        let Some(value) = args.get(&arg.name).filter(|v| !v.is_null()) else {
+            if arg.required {
+                return Err(format!("Missing required argument '{}'", arg.name));
+            }
+            continue;
+        };
+
+        let rendered = match arg.kind {
+            ArgKind::Boolean => {
+                let enabled = value
+                    .as_bool()
+                    .ok_or_else(|| format!("Argument '{}' must be a boolean", arg.name))?;
+                if enabled {
+                    if let Some(flag) = &arg.flag {
+                        argv.push(flag.clone());
+                    }
+                }
+                continue;
+            }
+            ArgKind::Integer => value
+                .as_i64()
+                .ok_or_else(|| format!("Argument '{}' must be an integer", arg.name))?
+                .to_string(),
+            ArgKind::String | ArgKind::Path | ArgKind::Enum => {
+                let text = value
+                    .as_str()
+                    .ok_or_else(|| format!("Argument '{}' must be a string", arg.name))?;
+                // The program would take it for an option
+                if arg.kind == ArgKind::String && text.starts_with('-') {
+                    return Err(format!("Argument '{}' may not start with '-'", arg.name));
+                }
+                match arg.kind {
+                    ArgKind::Path => validate_path_arg(text, workspace_root)?,
+                    ArgKind::Enum if !arg.values.iter().any(|v| v == text) => {
+                        return Err(format!(
+                            "Argument '{}' must be one of: {}",
+                            arg.name,
+                            arg.values.join(", ")
+                        ))
+                    }
+                    _ => {
+                        if let Some(pattern) = &arg.pattern {
+                            let re = regex::Regex::new(pattern).map_err(|e| {
+                                format!("Invalid pattern for '{}': {}", arg.name, e)
+                            })?;
+                            if !re.is_match(text) {
+                                return Err(format!(
+                                    "Argument '{}' does not match {}",
+                                    arg.name, pattern
+                                ));
+                            }
+                        }
+                        text.to_string()
+                    }
+                }
+            }
+        };
+
+        if let Some(flag) = &arg.flag {
+            argv.push(flag.clone());
+        }
+        argv.push(rendered);
+    }
+
+    Ok(argv)
+}
+
+/// Register (or replace) a runnable command
+#[tauri::command]
+pub fn broker_register_command(
+    state: State<'_, CommandBrokerState>,
+    spec: CommandSpec,
+) -> Result<(), String> {
+    if spec.id.trim().is_empty() || spec.program.trim().is_empty() {
+        return Err("Command id and program are required".to_string());
+    }
+    for arg in &spec.args {
+        if arg.kind == ArgKind::Enum && arg.values.is_empty() {
+            return Err(format!("Enum argument '{}' has no values", arg.name));
+        }
+        if let Some(pattern) = &arg.pattern {
+            regex::Regex::new(pattern)
+                .map_err(|e| format!("Invalid pattern for '{}': {}", arg.name, e))?;
+        }
+    }
+
+    let mut commands = state.commands.write().map_err(|e| e.to_string())?;
+    commands.insert(spec.id.clone(), spec);
+    Ok(())
+}
+
+/// Remove a registered command
+#[tauri::command]
+pub fn broker_unregister_command(
+    state: State<'_, CommandBrokerState>,
+    id: String,
+) -> Result<bool, String> {
+    let mut commands = state.commands.write().map_err(|e| e.to_string())?;
+    Ok(commands.remove(&id).is_some())
+}
+
+/// List registered commands, optionally only those from one source
+#[tauri::command]
+pub fn broker_list_commands(
+    state: State<'_, CommandBrokerState>,
+    source: Option<String>,
+) -> Result<Vec<CommandSpec>, String> {
+    let commands = state.commands.read().map_err(|e| e.to_string())?;
+    let mut list: Vec<CommandSpec> = commands
+        .values()
+        .filter(|c| source.is_none() || c.source == source)
+        .cloned()
+        .collect();
+    list.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(list)
+}
+
+/// Run a registered command with named arguments
+/// `timeout_ms` can only shorten the command's own timeout
+#[tauri::command]
+pub async fn broker_run_command(
+    state: State<'_, CommandBrokerState>,
+    id: String,
+    args: Option<HashMap<String, serde_json::Value>>,
+    workspace_root: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<BrokerResult, String> {
+    let spec = state
+        .commands
+        .read()
+        .map_err(|e| e.to_string())?
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| format!("Unknown command: {}", id))?;
+
+    check_policy(&spec, workspace_root.as_deref())?;
+    let matchers = crate::problem_matcher::resolve_matchers(&spec.problem_matchers)?;
+
+    let root = workspace_root.as_deref().map(PathBuf::from);
+    let argv = build_argv(&spec, &args.unwrap_or_default(), root.as_deref())?;
+
+    let mut cmd = tokio::process::Command::new(&spec.program);
+    cmd.args(&argv);
+    if let Some(root) = &root {
+        cmd.current_dir(root);
+    }
+
+    let limit = spec.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS);
+    let timeout = Duration::from_millis(timeout_ms.map_or(limit, |t| t.min(limit)));
+    let max_output = spec.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES);
+
+    let started = Instant::now();
+    let output = run_captured(cmd, timeout, max_output).await?;
+
+    let mut full_argv = vec![spec.program.clone()];
+    full_argv.extend(argv);
+
+    let diagnostics = if matchers.is_empty() {
+        Vec::new()
+    } else {
+        // Compilers report on either stream
+        let combined = format!("{}\n{}", output.stdout, output.stderr);
+        crate::problem_matcher::match_output(&combined, matchers, root.as_deref())?
+    };
+
+    Ok(BrokerResult {
+        command_id: id,
+        argv: full_argv,
+        exit_code: output.exit_code,
+        stdout: output.stdout,
+        stderr: output.stderr,
+        stdout_truncated: output.stdout_truncated,
+        stderr_truncated: output.stderr_truncated,
+        timed_out: output.timed_out,
+        duration_ms: started.elapsed().as_millis() as u64,
+        diagnostics,
+    })
+}
@@ -0,0 +1,693 @@
+//! Inline Completions
+//!
+//! Serves fill-in-the-middle (ghost text) completions for the editor. Each
+//! request assembles context from the text around the cursor, the file's
+//! imports and snippets from recently edited files, then asks the configured
+//! provider:
+//! - `ollama`: a local model through `/api/generate` with a suffix
+//! - `mistral`: Codestral's FIM endpoint
+//! - `openai`, `groq`, `cerebras`: chat completions with a cursor marker
+//!
+//! Requests are debounced per document, and a newer request for the same
+//! document cancels the one in flight. Results are cached, and a cached
+//! completion keeps being offered while the user types along with it.
+
+use crate::provider_client::{self, api_key, HTTP_CLIENT};
+use lru::LruCache;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+const DEFAULT_PROVIDER: &str = "ollama";
+const DEFAULT_OLLAMA_ENDPOINT: &str = "http://localhost:11434";
+const DEFAULT_DEBOUNCE_MS: u64 = 150;
+const DEFAULT_MAX_TOKENS: u64 = 128;
+
+/// Characters of context before and after the cursor
+const PREFIX_CHARS: usize = 4000;
+const SUFFIX_CHARS: usize = 1000;
+
+/// Import lines collected from the top of the file
+const MAX_IMPORT_LINES: usize = 30;
+
+/// Snippets from other recently edited files
+const RECENT_EDITS: usize = 3;
+const RECENT_EDIT_LINES: usize = 12;
+
+const CACHE_SIZE: usize = 256;
+
+/// Latency samples kept for percentiles
+const LATENCY_SAMPLES: usize = 200;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+const CHAT_PROMPT: &str = "You are a code completion engine. Reply with only the code that \
+belongs at <CURSOR>, without repeating the surrounding code, explanations or code fences. \
+Reply with nothing if no completion makes sense.";
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineCompletionRequest {
+    /// Document path or URI; requests for the same document supersede each other
+    pub document_id: String,
+    pub language_id: Option<String>,
+    pub text: String,
+    /// 1-based cursor position, column in UTF-16 code units (as in Monaco)
+    pub line: usize,
+    pub column: usize,
+    pub workspace_root: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineCompletionResult {
+    /// Pass back to accept/reject; empty when there is no completion
+    pub id: String,
+    pub text: String,
+    /// "ok", "empty" or "cancelled"
+    pub status: String,
+    pub cached: bool,
+    pub latency_ms: u64,
+    pub provider: String,
+    pub model: String,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineCompletionMetrics {
+    pub requests: u64,
+    pub served: u64,
+    pub cache_hits: u64,
+    pub cancelled: u64,
+    pub errors: u64,
+    pub accepted: u64,
+    pub rejected: u64,
+    /// accepted / (accepted + rejected)
+    pub acceptance_rate: f64,
+    pub avg_latency_ms: u64,
+    pub p50_latency_ms: u64,
+    pub p95_latency_ms: u64,
+}
+
+/// Provider settings resolved for one request
+struct ProviderConfig {
+    provider: String,
+    model: String,
+    endpoint: Option<String>,
+    max_tokens: u64,
+}
+
+/// Text around the cursor
+struct CompletionContext {
+    /// Everything before the cursor, which type-through compares against
+    before: String,
+    prefix: String,
+    suffix: String,
+    /// Imports and recent edits, prepended to the prefix
+    preamble: String,
+}
+
+/// Latest request per document, so newer ones can cancel it
+struct PendingRequest {
+    generation: u64,
+    cancel: Arc<Notify>,
+}
+
+/// Last completion shown per document, for type-through reuse
+struct ShownCompletion {
+    /// Document text before the cursor when it was shown
+    before: String,
+    text: String,
+}
+
+struct RecentEdit {
+    document_id: String,
+    snippet: String,
+}
+
+#[derive(Default)]
+struct Metrics {
+    requests: u64,
+    served: u64,
+    cache_hits: u64,
+    cancelled: u64,
+    errors: u64,
+    accepted: u64,
+    rejected: u64,
+    latencies: VecDeque<u64>,
+}
+
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+static PENDING: Lazy<Mutex<HashMap<String, PendingRequest>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static CACHE: Lazy<Mutex<LruCache<String, String>>> =
+    Lazy::new(|| Mutex::new(LruCache::new(NonZeroUsize::new(CACHE_SIZE).unwrap())));
+
+static SHOWN: Lazy<Mutex<HashMap<String, ShownCompletion>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static RECENT: Lazy<Mutex<VecDeque<RecentEdit>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// Completions handed out and not yet accepted or rejected, by ID
+static OUTSTANDING: Lazy<Mutex<LruCache<String, String>>> =
+    Lazy::new(|| Mutex::new(LruCache::new(NonZeroUsize::new(64).unwrap())));
+
+static METRICS: Lazy<Mutex<Metrics>> = Lazy::new(|| Mutex::new(Metrics::default()));
+
+fn with_metrics(update: impl FnOnce(&mut Metrics)) {
+    if let Ok(mut metrics) = METRICS.lock() {
+        update(&mut metrics);
+    }
+}
+
+fn provider_config(workspace_root: Option<&str>) -> ProviderConfig {
+    let setting = |key: &str| crate::configuration_manager::resolve_setting(workspace_root, key);
+    let string = |key: &str| {
+        setting(key)
+            .and_then(|v| v.as_str().map(str::to_string))
+            .filter(|v| !v.is_empty())
+    };
+    let provider =
+        string("editor.inlineCompletions.provider").unwrap_or_else(|| DEFAULT_PROVIDER.to_string());
+    let model = string("editor.inlineCompletions.model").unwrap_or_else(|| {
+        match provider.as_str() {
+            "mistral" => "codestral-latest",
+            "openai" => "gpt-4o-mini",
+            "groq" => "llama-3.3-70b-versatile",
+            "cerebras" => "zai-glm-4.6",
+            _ => "qwen2.5-coder:1.5b",
+        }
+        .to_string()
+    });
+    ProviderConfig {
+        provider,
+        model,
+        endpoint: string("editor.inlineCompletions.endpoint"),
+        max_tokens: setting("editor.inlineCompletions.maxTokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_MAX_TOKENS),
+    }
+}
+
+/// Byte offset of a 1-based line and UTF-16 column
+fn cursor_offset(text: &str, line: usize, column: usize) -> usize {
+    let mut line_start = 0;
+    for _ in 1..line.max(1) {
+        match text[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return text.len(),
+        }
+    }
+    let line_end = text[line_start..]
+        .find('\n')
+        .map_or(text.len(), |i| line_start + i);
+    let mut units = 0;
+    for (i, c) in text[line_start..line_end].char_indices() {
+        if units >= column.saturating_sub(1) {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    line_end
+}
+
+fn tail_chars(text: &str, count: usize) -> &str {
+    let start = text
+        .char_indices()
+        .rev()
+        .nth(count.saturating_sub(1))
+        .map_or(0, |(i, _)| i);
+    &text[start..]
+}
+
+fn head_chars(text: &str, count: usize) -> &str {
+    let end = text
+        .char_indices()
+        .nth(count)
+        .map_or(text.len(), |(i, _)| i);
+    &text[..end]
+}
+
+fn is_import_line(line: &str) -> bool {
+    let line = line.trim_start();
+    [
+        "import ", "from ", "use ", "#include", "require(", "package ",
+    ]
+    .iter()
+    .any(|keyword| line.starts_with(keyword))
+        || (line.starts_with("const ") && line.contains("require("))
+}
+
+/// Comment prefix for context lines in the language
+fn comment_prefix(language_id: Option<&str>) -> &'static str {
+    match language_id.unwrap_or_default() {
+        "python" | "ruby" | "shellscript" | "yaml" | "toml" | "dockerfile" => "#",
+        "html" | "xml" | "markdown" => "<!--",
+        "sql" | "lua" => "--",
+        _ => "//",
+    }
+}
+
+fn build_context(request: &InlineCompletionRequest) -> CompletionContext {
+    let offset = cursor_offset(&request.text, request.line, request.column);
+    let (before, after) = request.text.split_at(offset);
+    let prefix = tail_chars(before, PREFIX_CHARS).to_string();
+    let suffix = head_chars(after, SUFFIX_CHARS).to_string();
+
+    let comment = comment_prefix(request.language_id.as_deref());
+    let mut preamble = String::new();
+
+    // Imports that scrolled out of the prefix window
+    if prefix.len() < before.len() {
+        let imports: Vec<&str> = before[..before.len() - prefix.len()]
+            .lines()
+            .filter(|line| is_import_line(line))
+            .take(MAX_IMPORT_LINES)
+            .collect();
+        if !imports.is_empty() {
+            preamble.push_str(&imports.join("\n"));
+            preamble.push('\n');
+        }
+    }
+
+    if let Ok(recent) = RECENT.lock() {
+        for edit in recent
+            .iter()
+            .filter(|e| e.document_id != request.document_id)
+            .take(RECENT_EDITS)
+        {
+            preamble.push_str(&format!(
+                "{} Recently edited: {}\n",
+                comment, edit.document_id
+            ));
+            for line in edit.snippet.lines() {
+                preamble.push_str(&format!("{} {}\n", comment, line));
+            }
+        }
+    }
+
+    CompletionContext {
+        before: before.to_string(),
+        prefix,
+        suffix,
+        preamble,
+    }
+}
+
+/// Remember the lines around the cursor as this document's recent edit
+fn record_edit(request: &InlineCompletionRequest) {
+    let lines: Vec<&str> = request.text.lines().collect();
+    let cursor = request.line.saturating_sub(1).min(lines.len());
+    let start = cursor.saturating_sub(RECENT_EDIT_LINES / 2);
+    let end = (start + RECENT_EDIT_LINES).min(lines.len());
+    let snippet = lines[start..end].join("\n");
+    if let Ok(mut recent) = RECENT.lock() {
+        recent.retain(|e| e.document_id != request.document_id);
+        recent.push_front(RecentEdit {
+            document_id: request.document_id.clone(),
+            snippet,
+        });
+        recent.truncate(RECENT_EDITS + 1);
+    }
+}
+
+fn cache_key(config: &ProviderConfig, context: &CompletionContext) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        config.provider.as_str(),
+        config.model.as_str(),
+        context.preamble.as_str(),
+        context.prefix.as_str(),
+        context.suffix.as_str(),
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// The rest of the previously shown completion if the user typed along with it
+fn type_through(document_id: &str, before: &str) -> Option<String> {
+    let shown = SHOWN.lock().ok()?;
+    let shown = shown.get(document_id)?;
+    let typed = before.strip_prefix(shown.before.as_str())?;
+    let rest = shown.text.strip_prefix(typed)?;
+    (!typed.is_empty() && !rest.is_empty()).then(|| rest.to_string())
+}
+
+async fn fetch_completion(
+    config: &ProviderConfig,
+    context: &CompletionContext,
+    workspace_root: Option<&str>,
+) -> Result<String, String> {
+    let redact = |text: &str| {
+        crate::agent_redaction::redact_for_run(
+            "inline-completion",
+            text,
+            workspace_root,
+            crate::agent_redaction::RedactionDirection::Prompt,
+        )
+    };
+    let send = |request: reqwest::RequestBuilder| {
+        provider_client::send(request.timeout(REQUEST_TIMEOUT), &config.provider)
+    };
+    let prompt = redact(&format!("{}{}", context.preamble, context.prefix));
+    let suffix = redact(&context.suffix);
+    let text = match config.provider.as_str() {
+        "ollama" => {
+            let endpoint = config
+                .endpoint
+                .as_deref()
+                .unwrap_or(DEFAULT_OLLAMA_ENDPOINT)
+                .trim_end_matches('/');
+            let body = json!({
+                "model": config.model,
+                "prompt": prompt,
+                "suffix": suffix,
+                "stream": false,
+                "options": { "temperature": 0.1, "num_predict": config.max_tokens },
+            });
+            let request = HTTP_CLIENT
+                .post(format!("{}/api/generate", endpoint))
+                .json(&body);
+            send(request)
+                .await?
+                .get("response")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        }
+        "mistral" => {
+            let endpoint = config
+                .endpoint
+                .as_deref()
+                .unwrap_or("https://api.mistral.ai/v1/fim/completions");
+            let body = json!({
+                "model": config.model,
+                "prompt": prompt,
+                "suffix": suffix,
+                "max_tokens": config.max_tokens,
+                "temperature": 0.1,
+            });
+            let request = HTTP_CLIENT
+                .post(endpoint)
+                .bearer_auth(api_key(&config.provider)?)
+                .json(&body);
+            send(request)
+                .await?
+                .pointer("/choices/0/message/content")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        }
+        "openai" | "groq" | "cerebras" => {
+            let endpoint = config
+                .endpoint
+                .as_deref()
+                .or_else(|| provider_client::chat_completions_endpoint(&config.provider))
+                .unwrap_or_default();
+            let body = json!({
+                "model": config.model,
+                "temperature": 0.1,
+                "max_tokens": config.max_tokens,
+                "messages": [
+                    { "role": "system", "content": CHAT_PROMPT },
+                    { "role": "user", "content": format!("{}<CURSOR>{}", prompt, suffix) },
+                ],
+            });
+            let request = HTTP_CLIENT
+                .post(endpoint)
+                .bearer_auth(api_key(&config.provider)?)
+                .json(&body);
+            send(request)
+                .await?
+                .pointer("/choices/0/message/content")
+                .and_then(Value::as_str)
+                .map(strip_fences)
+        }
+        other => return Err(format!("Unsupported completion provider: {}", other)),
+    };
+    Ok(trim_suffix_overlap(
+        &text.unwrap_or_default(),
+        &context.suffix,
+    ))
+}
+
+fn strip_fences(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    if lines
+        .first()
+        .is_some_and(|l| l.trim_start().starts_with("```"))
+    {
+        let end = if lines.last().is_some_and(|l| l.trim() == "```") {
+            lines.len() - 1
+        } else {
+            lines.len()
+        };
+        return lines[1..end.max(1)].join("\n");
+    }
+    text.to_string()
+}
+
+/// Drop the end of a completion that repeats the text after the cursor
+fn trim_suffix_overlap(text: &str, suffix: &str) -> String {
+    let next_line = suffix.lines().next().unwrap_or_default().trim_end();
+    if !next_line.is_empty() {
+        if let Some(stripped) = text.trim_end().strip_suffix(next_line) {
+            return stripped.to_string();
+        }
+    }
+    text.trim_end().to_string()
+}
+
+fn finish(
+    request: &InlineCompletionRequest,
+    context: &CompletionContext,
+    config: &ProviderConfig,
+    text: String,
+    cached: bool,
+    started: Instant,
+) -> InlineCompletionResult {
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let status = if text.is_empty() { "empty" } else { "ok" };
+    let id = if text.is_empty() {
+        String::new()
+    } else {
+        uuid::Uuid::new_v4().to_string()
+    };
+    if !text.is_empty() {
+        if let Ok(mut shown) = SHOWN.lock() {
+            shown.insert(
+                request.document_id.clone(),
+                ShownCompletion {
+                    before: context.before.clone(),
+                    text: text.clone(),
+                },
+            );
+        }
+        if let Ok(mut outstanding) = OUTSTANDING.lock() {
+            outstanding.put(id.clone(), request.document_id.clone());
+        }
+    }
+    with_metrics(|m| {
+        m.served += 1;
+        if cached {
+            m.cache_hits += 1;
+        }
+        m.latencies.push_back(latency_ms);
+        if m.latencies.len() > LATENCY_SAMPLES {
+            m.latencies.pop_front();
+        }
+    });
+    InlineCompletionResult {
+        id,
+        text,
+        status: status.to_string(),
+        cached,
+        latency_ms,
+        provider: config.provider.clone(),
+        model: config.model.clone(),
+    }
+}
+
+fn cancelled(config: &ProviderConfig, started: Instant) -> InlineCompletionResult {
+    with_metrics(|m| m.cancelled += 1);
+    InlineCompletionResult {
+        id: String::new(),
+        text: String::new(),
+        status: "cancelled".to_string(),
+        cached: false,
+        latency_ms: started.elapsed().as_millis() as u64,
+        provider: config.provider.clone(),
+        model: config.model.clone(),
+    }
+}
+
+/// Request a completion at the cursor
+///
+/// Waits for the debounce period (`editor.inlineCompletions.debounceMs`)
+/// first; a newer request for the same document cancels this one, which then
+/// resolves with status "cancelled".
+#[tauri::command]
+pub async fn inline_completion_request(
+    request: InlineCompletionRequest,
+) -> Result<InlineCompletionResult, String> {
+    let started = Instant::now();
+    with_metrics(|m| m.requests += 1);
+    let config = provider_config(request.workspace_root.as_deref());
+    let context = build_context(&request);
+    record_edit(&request);
+
+    // Typing along with the completion on screen needs no new request
+    if let Some(rest) = type_through(&request.document_id, &context.before) {
+        return Ok(finish(&request, &context, &config, rest, true, started));
+    }
+    let key = cache_key(&config, &context);
+    if let Some(text) = CACHE.lock().ok().and_then(|mut c| c.get(&key).cloned()) {
+        return Ok(finish(&request, &context, &config, text, true, started));
+    }
+
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let cancel = Arc::new(Notify::new());
+    {
+        let mut pending = PENDING.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = pending.insert(
+            request.document_id.clone(),
+            PendingRequest {
+                generation,
+                cancel: cancel.clone(),
+            },
+        ) {
+            previous.cancel.notify_one();
+        }
+    }
+
+    let mut debounce = crate::configuration_manager::resolve_setting(
+        request.workspace_root.as_deref(),
+        "editor.inlineCompletions.debounceMs",
+    )
+    .and_then(|v| v.as_u64())
+    .unwrap_or(DEFAULT_DEBOUNCE_MS);
+    if crate::power_manager::current_mode() == crate::power_manager::PowerMode::Efficiency {
+        debounce *= 2;
+    }
+
+    let outcome = tokio::select! {
+        _ = cancel.notified() => None,
+        result = async {
+            tokio::time::sleep(Duration::from_millis(debounce)).await;
+            fetch_completion(&config, &context, request.workspace_root.as_deref()).await
+        } => Some(result),
+    };
+
+    if let Ok(mut pending) = PENDING.lock() {
+        if pending
+            .get(&request.document_id)
+            .is_some_and(|p| p.generation == generation)
+        {
+            pending.remove(&request.document_id);
+        }
+    }
+
+    match outcome {
+        None => Ok(cancelled(&config, started)),
+        Some(Err(e)) => {
+            with_metrics(|m| m.errors += 1);
+            Err(e)
+        }
+        Some(Ok(text)) => {
+            if let Ok(mut cache) = CACHE.lock() {
+                cache.put(key, text.clone());
+            }
+            Ok(finish(&request, &context, &config, text, false, started))
+        }
+    }
+}
+
+/// Cancel the in-flight request for a document (cursor moved, editor closed)
+#[tauri::command]
+pub fn inline_completion_cancel(document_id: String) -> Result<(), String> {
+    if let Some(pending) = PENDING
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&document_id)
+    {
+        pending.cancel.notify_one();
+    }
+    Ok(())
+}
+
+fn resolve_outstanding(id: &str) -> Result<String, String> {
+    OUTSTANDING
+        .lock()
+        .map_err(|e| e.to_string())?
+        .pop(id)
+        .ok_or_else(|| format!("Unknown completion: {}", id))
+}
+
+/// The user accepted a completion
+#[tauri::command]
+pub fn inline_completion_accept(id: String) -> Result<(), String> {
+    let document_id = resolve_outstanding(&id)?;
+    // The accepted text is now part of the document
+    if let Ok(mut shown) = SHOWN.lock() {
+        shown.remove(&document_id);
+    }
+    with_metrics(|m| m.accepted += 1);
+    Ok(())
+}
+
+/// The user dismissed a completion
+#[tauri::command]
+pub fn inline_completion_reject(id: String) -> Result<(), String> {
+    let document_id = resolve_outstanding(&id)?;
+    if let Ok(mut shown) = SHOWN.lock() {
+        shown.remove(&document_id);
+    }
+    with_metrics(|m| m.rejected += 1);
+    Ok(())
+}
+
+/// Request counts, acceptance and latency percentiles
+#[tauri::command]
+pub fn inline_completion_metrics() -> Result<InlineCompletionMetrics, String> {
+    let metrics = METRICS.lock().map_err(|e| e.to_string())?;
+    let mut latencies: Vec<u64> = metrics.latencies.iter().copied().collect();
+    latencies.sort_unstable();
+    let percentile = |p: usize| {
+        if latencies.is_empty() {
+            0
+        } else {
+            latencies[(latencies.len() - 1) * p / 100]
+        }
+    };
+    let decided = metrics.accepted + metrics.rejected;
+    Ok(InlineCompletionMetrics {
+        requests: metrics.requests,
+        served: metrics.served,
+        cache_hits: metrics.cache_hits,
+        cancelled: metrics.cancelled,
+        errors: metrics.errors,
+        accepted: metrics.accepted,
+        rejected: metrics.rejected,
+        acceptance_rate: if decided == 0 {
+            0.0
+        } else {
+            metrics.accepted as f64 / decided as f64
+        },
+        avg_latency_ms: if latencies.is_empty() {
+            0
+        } else {
+            latencies.iter().sum::<u64>() / latencies.len() as u64
+        },
+        p50_latency_ms: percentile(50),
+        p95_latency_ms: percentile(95),
+    })
+}
@@ -232,6 +232,25 @@ fn save_json_file(path: &PathBuf, data: &HashMap<String, Value>) -> Result<(), S
     Ok(())
 }
 
+/// Resolve a setting with scope priority workspace > user, without an AppHandle.
+/// Used by backend modules that enforce settings themselves (e.g. agent file policies).
+pub(crate) fn resolve_setting(workspace_path: Option<&str>, key: &str) -> Option<Value> {
+    if let Some(ws_path) = workspace_path {
+        let ws_settings_path = PathBuf::from(ws_path).join(".rainy").join("settings.json");
+        if let Some(value) = load_json_file(&ws_settings_path)
+            .ok()
+            .and_then(|settings| settings.get(key).cloned())
+        {
+            return Some(value);
+        }
+    }
+
+    let user_settings_path = dirs::home_dir()?
+        .join(".rainy-aether")
+        .join("settings.json");
+    load_json_file(&user_settings_path).ok()?.get(key).cloned()
+}
+
 /// Validate configuration value against schema
 fn validate_value(
     key: &str,
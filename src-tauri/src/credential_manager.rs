@@ -365,6 +365,22 @@ impl CredentialManager {
         // Store new credential
         Self::store_credential(provider_id, api_key)
     }
+
+    /// Provider ids and keys of every credential loaded this session
+    ///
+    /// Used to scrub stored keys from text leaving the app; credentials only
+    /// in the OS keychain are included once they have been read.
+    pub fn known_credentials() -> Vec<(String, String)> {
+        CREDENTIAL_CACHE
+            .lock()
+            .map(|cache| {
+                cache
+                    .iter()
+                    .map(|(id, key)| (id.clone(), key.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 }
 
 // ============================================================================
@@ -0,0 +1,341 @@
+//! Deep Links
+//!
+//! Handles `rainy://` URLs so browsers, terminals and CI logs can link into
+//! the IDE:
+//!
+//! - `rainy://open-file?path=/abs/file.rs&line=12&column=4`
+//! - `rainy://open-workspace?path=/abs/project`
+//! - `rainy://open-diff?path=/abs/file.rs&base=main&head=HEAD`
+//!
+//! Links are validated here before the frontend sees them: paths must be
+//! absolute and exist, and the workspace they fall in must be trusted, since
+//! a link can come from anywhere. Trust lives in the user settings
+//! (`security.trustedWorkspaces`, added to by `deep_link_trust_workspace`) so
+//! a repository cannot trust itself through its own `.rainy/settings.json`.
+//! Accepted links are focused in a window and emitted as `deep-link:open`;
+//! rejected ones as `deep-link:rejected` so the user can see why nothing
+//! happened, and trust the workspace if that was the reason. Links that arrive before the frontend
+//! listens (the one the app was launched with) wait in a queue read by
+//! `deep_link_take_pending`. `deepLinks.enabled` turns the handler off.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+pub const SCHEME: &str = "rainy";
+
+const TRUSTED_WORKSPACES_KEY: &str = "security.trustedWorkspaces";
+
+/// A validated deep link action
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "action")]
+pub enum DeepLinkAction {
+    #[serde(rename = "open-file", rename_all = "camelCase")]
+    File {
+        path: String,
+        /// 1-based
+        line: Option<u32>,
+        /// 1-based
+        column: Option<u32>,
+        workspace_root: Option<String>,
+    },
+    #[serde(rename = "open-workspace")]
+    Workspace { path: String },
+    #[serde(rename = "open-diff", rename_all = "camelCase")]
+    Diff {
+        path: String,
+        /// Ref to compare from; the frontend defaults to HEAD
+        base: Option<String>,
+        /// Ref to compare to; None means the working tree
+        head: Option<String>,
+        workspace_root: Option<String>,
+    },
+}
+
+/// Payload of the `deep-link:rejected` event
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedDeepLink {
+    pub url: String,
+    pub reason: String,
+    /// Set when the link was refused only because this workspace is not trusted
+    pub untrusted_workspace: Option<String>,
+}
+
+/// Why a link was refused
+enum Rejection {
+    Invalid(String),
+    Untrusted(PathBuf),
+}
+
+impl From<String> for Rejection {
+    fn from(reason: String) -> Self {
+        Rejection::Invalid(reason)
+    }
+}
+
+impl Rejection {
+    fn reason(&self) -> String {
+        match self {
+            Rejection::Invalid(reason) => reason.clone(),
+            Rejection::Untrusted(root) => format!("Workspace is not trusted: {}", root.display()),
+        }
+    }
+}
+
+static PENDING: Lazy<Mutex<Vec<DeepLinkAction>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+fn param(query: &HashMap<String, String>, key: &str) -> Option<String> {
+    query.get(key).filter(|v| !v.is_empty()).cloned()
+}
+
+fn number_param(query: &HashMap<String, String>, key: &str) -> Result<Option<u32>, String> {
+    param(query, key)
+        .map(|v| {
+            v.parse::<u32>()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| format!("Invalid {}: {}", key, v))
+        })
+        .transpose()
+}
+
+/// Canonical absolute path of an existing file or directory
+fn existing_path(value: Option<String>) -> Result<PathBuf, String> {
+    let value = value.ok_or("Missing path parameter")?;
+    let path = Path::new(&value);
+    if !path.is_absolute() {
+        return Err(format!("Path must be absolute: {}", value));
+    }
+    path.canonicalize()
+        .map_err(|_| format!("Path does not exist: {}", value))
+}
+
+/// Refs come from untrusted input and end up in git calls
+fn validate_ref(value: Option<String>) -> Result<Option<String>, String> {
+    match value {
+        Some(r) if r.starts_with('-') || r.chars().any(|c| c.is_whitespace() || c.is_control()) => {
+            Err(format!("Invalid ref: {}", r))
+        }
+        other => Ok(other),
+    }
+}
+
+/// The workspace a path belongs to: its repository, or the folder itself
+fn workspace_of(path: &Path) -> Option<PathBuf> {
+    if let Ok(repo) = git2::Repository::discover(path) {
+        if let Some(workdir) = repo.workdir() {
+            return workdir.canonicalize().ok();
+        }
+    }
+    if path.is_dir() {
+        Some(path.to_path_buf())
+    } else {
+        path.parent().map(Path::to_path_buf)
+    }
+}
+
+/// Folders the user trusts, read from the user settings only
+fn trusted_workspaces() -> Vec<PathBuf> {
+    crate::configuration_manager::resolve_setting(None, TRUSTED_WORKSPACES_KEY)
+        .and_then(|v| v.as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|v| v.as_str())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// A workspace is trusted when it, or a folder containing it, was trusted
+fn check_trust(workspace_root: &Path) -> Result<(), Rejection> {
+    if trusted_workspaces()
+        .iter()
+        .any(|trusted| workspace_root.starts_with(trusted))
+    {
+        Ok(())
+    } else {
+        Err(Rejection::Untrusted(workspace_root.to_path_buf()))
+    }
+}
+
+/// Parse and validate a `rainy://` URL
+pub fn parse(url: &Url) -> Result<DeepLinkAction, String> {
+    parse_link(url).map_err(|rejection| rejection.reason())
+}
+
+fn parse_link(url: &Url) -> Result<DeepLinkAction, Rejection> {
+    if url.scheme() != SCHEME {
+        return Err(format!("Unsupported scheme: {}", url.scheme()).into());
+    }
+    // `rainy://open-file?…` puts the action in the host, `rainy:open-file?…` in the path
+    let action = url
+        .host_str()
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| url.path())
+        .trim_matches('/')
+        .to_string();
+    let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+    match action.as_str() {
+        "open-file" => {
+            let path = existing_path(param(&query, "path"))?;
+            if !path.is_file() {
+                return Err(format!("Not a file: {}", path.display()).into());
+            }
+            let root = workspace_of(&path);
+            if let Some(root) = &root {
+                check_trust(root)?;
+            }
+            Ok(DeepLinkAction::File {
+                path: path.to_string_lossy().to_string(),
+                line: number_param(&query, "line")?,
+                column: number_param(&query, "column")?,
+                workspace_root: root.map(|r| r.to_string_lossy().to_string()),
+            })
+        }
+        "open-workspace" => {
+            let path = existing_path(param(&query, "path"))?;
+            if !path.is_dir() {
+                return Err(format!("Not a folder: {}", path.display()).into());
+            }
+            check_trust(&path)?;
+            Ok(DeepLinkAction::Workspace {
+                path: path.to_string_lossy().to_string(),
+            })
+        }
+        "open-diff" => {
+            let path = existing_path(param(&query, "path"))?;
+            let root = workspace_of(&path);
+            if let Some(root) = &root {
+                check_trust(root)?;
+            }
+            Ok(DeepLinkAction::Diff {
+                path: path.to_string_lossy().to_string(),
+                base: validate_ref(param(&query, "base"))?,
+                head: validate_ref(param(&query, "head"))?,
+                workspace_root: root.map(|r| r.to_string_lossy().to_string()),
+            })
+        }
+        other => Err(format!("Unknown action: {}", other).into()),
+    }
+}
+
+fn enabled() -> bool {
+    crate::configuration_manager::resolve_setting(None, "deepLinks.enabled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true)
+}
+
+/// Whether a launch argument is a deep link (delivered through the deep-link
+/// plugin rather than as a command line path)
+pub(crate) fn is_link_arg(arg: &str) -> bool {
+    arg.strip_prefix(SCHEME)
+        .is_some_and(|rest| rest.starts_with(':'))
+}
+
+/// Bring a window forward so the link opens somewhere visible
+pub(crate) fn focus_window(app: &AppHandle) {
+    let windows = app.webview_windows();
+    let window = windows.get("main").or_else(|| windows.values().next());
+    if let Some(window) = window {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn handle_urls(app: &AppHandle, urls: Vec<Url>, queue: bool) {
+    if !enabled() {
+        return;
+    }
+    for url in urls {
+        match parse_link(&url) {
+            Ok(action) => {
+                println!("[DeepLink] {}", url);
+                if queue {
+                    if let Ok(mut pending) = PENDING.lock() {
+                        pending.push(action);
+                    }
+                } else {
+                    focus_window(app);
+                    let _ = app.emit("deep-link:open", &action);
+                }
+            }
+            Err(rejection) => {
+                let reason = rejection.reason();
+                eprintln!("[DeepLink] Rejected {}: {}", url, reason);
+                let untrusted_workspace = match rejection {
+                    Rejection::Untrusted(root) => Some(root.to_string_lossy().to_string()),
+                    Rejection::Invalid(_) => None,
+                };
+                let _ = app.emit(
+                    "deep-link:rejected",
+                    RejectedDeepLink {
+                        url: url.to_string(),
+                        reason,
+                        untrusted_workspace,
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Listen for links; called once from setup
+pub fn start(app: &AppHandle) {
+    // Installed bundles register the scheme; dev builds on Linux and Windows must do it at runtime
+    #[cfg(all(debug_assertions, any(target_os = "linux", target_os = "windows")))]
+    if let Err(e) = app.deep_link().register(SCHEME) {
+        eprintln!("[DeepLink] Failed to register {}://: {}", SCHEME, e);
+    }
+
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => handle_urls(app, urls, true),
+        Ok(None) => {}
+        Err(e) => eprintln!("[DeepLink] Failed to read launch URL: {}", e),
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        handle_urls(&handle, event.urls(), false);
+    });
+}
+
+/// Links received before the frontend was listening (e.g. the launch link)
+#[tauri::command]
+pub fn deep_link_take_pending() -> Result<Vec<DeepLinkAction>, String> {
+    let mut pending = PENDING.lock().map_err(|e| e.to_string())?;
+    Ok(std::mem::take(&mut *pending))
+}
+
+/// Validate a `rainy://` URL without opening it (e.g. pasted into the command palette)
+#[tauri::command]
+pub fn deep_link_parse(url: String) -> Result<DeepLinkAction, String> {
+    let url = Url::parse(&url).map_err(|e| format!("Invalid URL: {}", e))?;
+    parse(&url)
+}
+
+/// Trust a folder so links into it open; the user confirms this in the frontend
+#[tauri::command]
+pub fn deep_link_trust_workspace(app: AppHandle, path: String) -> Result<(), String> {
+    let root = existing_path(Some(path))?;
+    if !root.is_dir() {
+        return Err(format!("Not a folder: {}", root.display()));
+    }
+    let mut trusted = trusted_workspaces();
+    if trusted.iter().any(|t| root.starts_with(t)) {
+        return Ok(());
+    }
+    trusted.push(root);
+    let value = serde_json::to_string(&trusted).map_err(|e| e.to_string())?;
+    crate::configuration_manager::set_configuration_value(
+        app,
+        TRUSTED_WORKSPACES_KEY.to_string(),
+        value,
+        "user".to_string(),
+        None,
+    )
+}
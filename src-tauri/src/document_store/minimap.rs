@@ -0,0 +1,271 @@
+//! Minimap data
+//!
+//! Summarizes a document into at most `max_rows` minimap rows so the editor
+//! can draw a minimap of a huge file from a few kilobytes: per row the ink
+//! density, indentation, bracket depth and longest line, plus markers for
+//! lines changed since HEAD. Files with more lines than rows are sampled by
+//! folding consecutive lines into one row.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const DEFAULT_MAX_ROWS: usize = 4000;
+const DEFAULT_TAB_SIZE: usize = 4;
+
+/// Lines longer than this are only measured, not scanned for brackets
+const MAX_SCANNED_LINE: usize = 10_000;
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MinimapOptions {
+    /// Rows available in the minimap (default 4000)
+    pub max_rows: Option<usize>,
+    pub tab_size: Option<usize>,
+    /// Include markers for lines changed since HEAD (default true)
+    pub git_markers: Option<bool>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MarkerKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+/// Lines changed since HEAD; `start_line..=end_line` are 0-based document
+/// lines (a deletion marks the line after the removed text)
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MinimapMarker {
+    pub kind: MarkerKind,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub start_row: usize,
+    pub end_row: usize,
+}
+
+/// Per-row arrays, all `rows` long; row `i` covers lines
+/// `i * lines_per_row .. (i + 1) * lines_per_row`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MinimapData {
+    pub line_count: usize,
+    pub lines_per_row: usize,
+    pub rows: usize,
+    /// Average non-whitespace characters per line (capped at 255)
+    pub density: Vec<u8>,
+    /// Indentation in columns of the row's first non-blank line
+    pub indent: Vec<u16>,
+    /// Smallest bracket nesting depth at the start of the row's lines
+    pub depth: Vec<u16>,
+    /// Longest line in the row, in columns (capped at 65535)
+    pub max_length: Vec<u16>,
+    pub markers: Vec<MinimapMarker>,
+}
+
+/// Accumulates lines into rows
+pub(super) struct MinimapBuilder {
+    tab_size: usize,
+    lines_per_row: usize,
+    line: usize,
+    depth: usize,
+    /// Current row: ink, first indent, min depth, max length
+    ink: usize,
+    row_indent: Option<usize>,
+    row_depth: usize,
+    row_length: usize,
+    data: MinimapData,
+}
+
+impl MinimapBuilder {
+    pub(super) fn new(line_count: usize, options: &MinimapOptions) -> Self {
+        let max_rows = options.max_rows.unwrap_or(DEFAULT_MAX_ROWS).max(1);
+        let lines_per_row = line_count.div_ceil(max_rows).max(1);
+        let rows = line_count.div_ceil(lines_per_row).max(1);
+        Self {
+            tab_size: options.tab_size.unwrap_or(DEFAULT_TAB_SIZE).max(1),
+            lines_per_row,
+            line: 0,
+            depth: 0,
+            ink: 0,
+            row_indent: None,
+            row_depth: usize::MAX,
+            row_length: 0,
+            data: MinimapData {
+                line_count,
+                lines_per_row,
+                rows,
+                density: Vec::with_capacity(rows),
+                indent: Vec::with_capacity(rows),
+                depth: Vec::with_capacity(rows),
+                max_length: Vec::with_capacity(rows),
+                markers: Vec::new(),
+            },
+        }
+    }
+
+    pub(super) fn push_line(&mut self, text: &str) {
+        let mut columns = 0;
+        let mut indent = None;
+        let mut ink = 0;
+        for ch in text.chars() {
+            if ch == '\t' {
+                columns += self.tab_size - columns % self.tab_size;
+            } else {
+                if indent.is_none() && !ch.is_whitespace() {
+                    indent = Some(columns);
+                }
+                if !ch.is_whitespace() {
+                    ink += 1;
+                }
+                columns += 1;
+            }
+        }
+
+        self.ink += ink;
+        if self.row_indent.is_none() {
+            self.row_indent = indent;
+        }
+        self.row_depth = self.row_depth.min(self.depth);
+        self.row_length = self.row_length.max(columns);
+        if text.len() <= MAX_SCANNED_LINE {
+            self.depth = bracket_depth_after(text, self.depth);
+        }
+
+        self.line += 1;
+        if self.line.is_multiple_of(self.lines_per_row) {
+            self.end_row();
+        }
+    }
+
+    fn end_row(&mut self) {
+        let lines = match self.line % self.lines_per_row {
+            0 => self.lines_per_row,
+            partial => partial,
+        };
+        self.data
+            .density
+            .push((self.ink / lines).min(u8::MAX as usize) as u8);
+        self.data
+            .indent
+            .push(self.row_indent.unwrap_or(0).min(u16::MAX as usize) as u16);
+        self.data.depth.push(
+            (if self.row_depth == usize::MAX {
+                self.depth
+            } else {
+                self.row_depth
+            })
+            .min(u16::MAX as usize) as u16,
+        );
+        self.data
+            .max_length
+            .push(self.row_length.min(u16::MAX as usize) as u16);
+        self.ink = 0;
+        self.row_indent = None;
+        self.row_depth = usize::MAX;
+        self.row_length = 0;
+    }
+
+    pub(super) fn finish(mut self, markers: Vec<(MarkerKind, usize, usize)>) -> MinimapData {
+        if !self.line.is_multiple_of(self.lines_per_row) || self.data.density.is_empty() {
+            self.end_row();
+        }
+        let last_row = self.data.density.len().saturating_sub(1);
+        self.data.rows = self.data.density.len();
+        self.data.markers = markers
+            .into_iter()
+            .map(|(kind, start_line, end_line)| MinimapMarker {
+                kind,
+                start_line,
+                end_line,
+                start_row: (start_line / self.lines_per_row).min(last_row),
+                end_row: (end_line / self.lines_per_row).min(last_row),
+            })
+            .collect();
+        self.data
+    }
+}
+
+/// Bracket depth after a line, ignoring brackets in string literals and
+/// after `//`; a heuristic that holds for C-family languages and JSON
+fn bracket_depth_after(text: &str, mut depth: usize) -> usize {
+    let mut quote: Option<char> = None;
+    let mut previous = ' ';
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match quote {
+            Some(q) => {
+                if ch == '\\' {
+                    chars.next();
+                } else if ch == q {
+                    quote = None;
+                }
+            }
+            None => match ch {
+                '"' | '`' => quote = Some(ch),
+                // Not a Rust lifetime (`&'a`, `<'a>`) or an apostrophe in a word
+                '\'' if !(previous.is_alphanumeric() || previous == '&' || previous == '<')
+                    && chars.clone().any(|c| c == '\'') =>
+                {
+                    quote = Some(ch)
+                }
+                '/' if chars.peek() == Some(&'/') => break,
+                '{' | '[' | '(' => depth += 1,
+                '}' | ']' | ')' => depth = depth.saturating_sub(1),
+                _ => {}
+            },
+        }
+        previous = ch;
+    }
+    depth
+}
+
+/// Changed line ranges of `text` against the file's blob at HEAD
+pub(super) fn git_markers(path: &Path, text: &str) -> Vec<(MarkerKind, usize, usize)> {
+    let mut markers = Vec::new();
+    let Ok(repo) = git2::Repository::discover(path) else {
+        return markers;
+    };
+    let Some(relative) = repo
+        .workdir()
+        .and_then(|workdir| path.strip_prefix(workdir).ok())
+    else {
+        return markers;
+    };
+    let head_blob = repo
+        .head()
+        .and_then(|head| head.peel_to_tree())
+        .and_then(|tree| tree.get_path(relative))
+        .and_then(|entry| repo.find_blob(entry.id()));
+    let old: Vec<u8> = match &head_blob {
+        Ok(blob) => blob.content().to_vec(),
+        // Untracked: the whole file is new
+        Err(_) => {
+            let lines = text.lines().count().max(1);
+            return vec![(MarkerKind::Added, 0, lines - 1)];
+        }
+    };
+
+    let mut opts = git2::DiffOptions::new();
+    opts.context_lines(0);
+    let Ok(patch) = git2::Patch::from_buffers(&old, None, text.as_bytes(), None, Some(&mut opts))
+    else {
+        return markers;
+    };
+    for hunk_index in 0..patch.num_hunks() {
+        let Ok((hunk, _)) = patch.hunk(hunk_index) else {
+            continue;
+        };
+        let (old_lines, new_lines) = (hunk.old_lines() as usize, hunk.new_lines() as usize);
+        // new_start is 1-based, or the line before a pure deletion
+        let start = hunk.new_start() as usize;
+        let marker = match (old_lines, new_lines) {
+            (0, _) => (MarkerKind::Added, start - 1, start + new_lines - 2),
+            (_, 0) => (MarkerKind::Deleted, start, start),
+            _ => (MarkerKind::Modified, start - 1, start + new_lines - 2),
+        };
+        markers.push(marker);
+    }
+    markers
+}
@@ -0,0 +1,404 @@
+//! Document Store
+//!
+//! Authoritative in-memory copies of the documents open in the editor, kept
+//! as ropes. The frontend opens a document once and then only sends deltas
+//! (LSP `TextDocumentContentChangeEvent`s), and everything else reads from
+//! here instead of asking the webview for full contents:
+//! - search in open files (`document_search`)
+//! - language servers attached to a document get didOpen/didChange/didClose
+//! - agent read tools see unsaved editor content
+//! - hot exit: unsaved documents are journaled when a window closes and
+//!   offered back on the next launch
+//! - minimap summaries (`document_minimap`), so huge files never round-trip
+
+mod minimap;
+mod rope;
+
+use crate::language_server_manager::LanguageServerManager;
+use crate::project_manager::{FileSearchResult, SearchOptions};
+use lsp_types::{Range, TextDocumentContentChangeEvent};
+use minimap::{MinimapBuilder, MinimapData, MinimapOptions};
+use once_cell::sync::Lazy;
+use rope::Rope;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::State;
+
+struct OpenDocument {
+    rope: Rope,
+    version: i32,
+    language_id: Option<String>,
+    /// Language servers kept in sync with this document
+    language_servers: Vec<String>,
+}
+
+static DOCUMENTS: Lazy<RwLock<HashMap<PathBuf, OpenDocument>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentInfo {
+    pub path: String,
+    pub version: i32,
+    pub language_id: Option<String>,
+    pub line_count: usize,
+    /// Length in UTF-16 code units
+    pub length: usize,
+}
+
+/// An unsaved document recovered after exit
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HotExitEntry {
+    pub path: String,
+    pub language_id: Option<String>,
+    pub content: String,
+}
+
+fn document_key(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn info(path: &Path, doc: &OpenDocument) -> DocumentInfo {
+    DocumentInfo {
+        path: path.to_string_lossy().to_string(),
+        version: doc.version,
+        language_id: doc.language_id.clone(),
+        line_count: doc.rope.line_count(),
+        length: doc.rope.len_utf16(),
+    }
+}
+
+/// Current text of a document if it is open in the editor
+pub(crate) fn open_text(path: &Path) -> Option<String> {
+    DOCUMENTS
+        .read()
+        .ok()?
+        .get(&document_key(path))
+        .map(|doc| doc.rope.to_string())
+}
+
+pub(crate) fn path_to_uri(path: &Path) -> String {
+    let normalized = path.to_string_lossy().replace('\\', "/");
+    let encoded: Vec<String> = normalized
+        .split('/')
+        .map(|segment| {
+            // Keep Windows drive letters (C:) readable
+            if segment.len() == 2 && segment.ends_with(':') {
+                segment.to_string()
+            } else {
+                urlencoding::encode(segment).into_owned()
+            }
+        })
+        .collect();
+    let joined = encoded.join("/");
+    if joined.starts_with('/') {
+        format!("file://{}", joined)
+    } else {
+        format!("file:///{}", joined)
+    }
+}
+
+/// Send an LSP notification to each server, logging failures
+fn notify_servers(
+    lsp: &LanguageServerManager,
+    servers: &[String],
+    method: &str,
+    params: serde_json::Value,
+) {
+    if servers.is_empty() {
+        return;
+    }
+    let message = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": params,
+    })
+    .to_string();
+    for server in servers {
+        if let Err(e) = lsp.send_message(server, &message) {
+            eprintln!(
+                "[DocumentStore] Failed to send {} to {}: {}",
+                method, server, e
+            );
+        }
+    }
+}
+
+fn hot_exit_path() -> Option<PathBuf> {
+    Some(
+        dirs::home_dir()?
+            .join(".rainy-aether")
+            .join("hot-exit.json"),
+    )
+}
+
+/// Journal open documents whose content differs from disk; returns how many were saved
+pub(crate) fn write_hot_exit_journal() -> Result<usize, String> {
+    let path = hot_exit_path().ok_or("Could not determine home directory")?;
+    let entries: Vec<HotExitEntry> = {
+        let documents = DOCUMENTS.read().map_err(|e| e.to_string())?;
+        documents
+            .iter()
+            .filter_map(|(doc_path, doc)| {
+                let content = doc.rope.to_string();
+                let on_disk = std::fs::read_to_string(doc_path).ok();
+                (on_disk.as_deref() != Some(content.as_str())).then(|| HotExitEntry {
+                    path: doc_path.to_string_lossy().to_string(),
+                    language_id: doc.language_id.clone(),
+                    content,
+                })
+            })
+            .collect()
+    };
+
+    if entries.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+        }
+        return Ok(0);
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string(&entries).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())?;
+    println!(
+        "[DocumentStore] Journaled {} unsaved document(s)",
+        entries.len()
+    );
+    Ok(entries.len())
+}
+
+/// Open a document; `content` is the editor's text, defaulting to the file on disk
+#[tauri::command]
+pub fn document_open(
+    path: String,
+    content: Option<String>,
+    version: Option<i32>,
+    language_id: Option<String>,
+    language_servers: Option<Vec<String>>,
+    lsp: State<'_, LanguageServerManager>,
+) -> Result<DocumentInfo, String> {
+    let key = document_key(Path::new(&path));
+    let content = match content {
+        Some(content) => content,
+        None => {
+            std::fs::read_to_string(&key).map_err(|e| format!("Failed to read {}: {}", path, e))?
+        }
+    };
+
+    let doc = OpenDocument {
+        rope: Rope::from_str(&content),
+        version: version.unwrap_or(1),
+        language_id,
+        language_servers: language_servers.unwrap_or_default(),
+    };
+    let result = info(&key, &doc);
+    notify_servers(
+        &lsp,
+        &doc.language_servers,
+        "textDocument/didOpen",
+        serde_json::json!({
+            "textDocument": {
+                "uri": path_to_uri(&key),
+                "languageId": doc.language_id.clone().unwrap_or_else(|| "plaintext".to_string()),
+                "version": doc.version,
+                "text": content,
+            }
+        }),
+    );
+
+    DOCUMENTS
+        .write()
+        .map_err(|e| e.to_string())?
+        .insert(key, doc);
+    Ok(result)
+}
+
+/// Apply editor changes in order; positions are LSP (0-based, UTF-16)
+#[tauri::command]
+pub fn document_apply_delta(
+    path: String,
+    version: i32,
+    changes: Vec<TextDocumentContentChangeEvent>,
+    lsp: State<'_, LanguageServerManager>,
+) -> Result<DocumentInfo, String> {
+    let key = document_key(Path::new(&path));
+    let (result, servers) = {
+        let mut documents = DOCUMENTS.write().map_err(|e| e.to_string())?;
+        let doc = documents
+            .get_mut(&key)
+            .ok_or_else(|| format!("Document is not open: {}", path))?;
+        if version <= doc.version {
+            return Err(format!(
+                "Stale change for {}: version {} is not newer than {}",
+                path, version, doc.version
+            ));
+        }
+
+        for change in &changes {
+            match change.range {
+                Some(range) => {
+                    let start = doc.rope.offset_of(range.start);
+                    let end = doc.rope.offset_of(range.end);
+                    doc.rope.replace(start, end, &change.text);
+                }
+                None => doc.rope = Rope::from_str(&change.text),
+            }
+        }
+        doc.version = version;
+        (info(&key, doc), doc.language_servers.clone())
+    };
+
+    notify_servers(
+        &lsp,
+        &servers,
+        "textDocument/didChange",
+        serde_json::json!({
+            "textDocument": { "uri": path_to_uri(&key), "version": version },
+            "contentChanges": changes,
+        }),
+    );
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn document_close(path: String, lsp: State<'_, LanguageServerManager>) -> Result<(), String> {
+    let key = document_key(Path::new(&path));
+    let removed = DOCUMENTS.write().map_err(|e| e.to_string())?.remove(&key);
+    if let Some(doc) = removed {
+        notify_servers(
+            &lsp,
+            &doc.language_servers,
+            "textDocument/didClose",
+            serde_json::json!({ "textDocument": { "uri": path_to_uri(&key) } }),
+        );
+    }
+    Ok(())
+}
+
+/// Text of an open document, or of a range of it
+#[tauri::command]
+pub fn document_get_text(path: String, range: Option<Range>) -> Result<String, String> {
+    let documents = DOCUMENTS.read().map_err(|e| e.to_string())?;
+    let doc = documents
+        .get(&document_key(Path::new(&path)))
+        .ok_or_else(|| format!("Document is not open: {}", path))?;
+    Ok(match range {
+        Some(range) => doc.rope.slice(
+            doc.rope.offset_of(range.start),
+            doc.rope.offset_of(range.end),
+        ),
+        None => doc.rope.to_string(),
+    })
+}
+
+#[tauri::command]
+pub fn document_list() -> Result<Vec<DocumentInfo>, String> {
+    let documents = DOCUMENTS.read().map_err(|e| e.to_string())?;
+    let mut list: Vec<DocumentInfo> = documents
+        .iter()
+        .map(|(path, doc)| info(path, doc))
+        .collect();
+    list.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(list)
+}
+
+/// Search the unsaved contents of all open documents
+#[tauri::command]
+pub fn document_search(
+    query: String,
+    options: SearchOptions,
+) -> Result<Vec<FileSearchResult>, String> {
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let max_results = options.max_results.unwrap_or(1000);
+    let documents = DOCUMENTS.read().map_err(|e| e.to_string())?;
+
+    let mut results = Vec::new();
+    let mut total = 0;
+    for (path, doc) in documents.iter() {
+        if total >= max_results {
+            break;
+        }
+        let matches =
+            crate::project_manager::search_in_content(&doc.rope.to_string(), &query, &options);
+        if matches.is_empty() {
+            continue;
+        }
+        total += matches.len();
+        results.push(FileSearchResult {
+            path: path.to_string_lossy().to_string(),
+            name: path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            matches,
+        });
+    }
+    results.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(results)
+}
+
+/// Journal unsaved documents now (also done automatically when a window closes)
+#[tauri::command]
+pub fn document_write_hot_exit_journal() -> Result<usize, String> {
+    write_hot_exit_journal()
+}
+
+/// Unsaved documents from the previous session; the journal is cleared once read
+#[tauri::command]
+pub fn document_take_hot_exit_journal() -> Result<Vec<HotExitEntry>, String> {
+    let Some(path) = hot_exit_path().filter(|p| p.exists()) else {
+        return Ok(Vec::new());
+    };
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let entries = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse hot exit journal: {}", e))?;
+    std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+    Ok(entries)
+}
+
+/// Minimap rows and git change markers for a document, sampled to
+/// `options.maxRows`; reads the file from disk when it isn't open
+#[tauri::command]
+pub fn document_minimap(
+    path: String,
+    options: Option<MinimapOptions>,
+) -> Result<MinimapData, String> {
+    let options = options.unwrap_or_default();
+    let key = document_key(Path::new(&path));
+    let with_markers = options.git_markers.unwrap_or(true);
+
+    let open = {
+        let documents = DOCUMENTS.read().map_err(|e| e.to_string())?;
+        documents.get(&key).map(|doc| {
+            let mut builder = MinimapBuilder::new(doc.rope.line_count(), &options);
+            doc.rope.for_each_line(|line| builder.push_line(line));
+            (builder, with_markers.then(|| doc.rope.to_string()))
+        })
+    };
+    let (builder, text) = match open {
+        Some(open) => open,
+        None => {
+            let text = std::fs::read_to_string(&key)
+                .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            let line_count = text.bytes().filter(|&b| b == b'\n').count() + 1;
+            let mut builder = MinimapBuilder::new(line_count, &options);
+            for line in text.split('\n') {
+                builder.push_line(line.strip_suffix('\r').unwrap_or(line));
+            }
+            (builder, with_markers.then_some(text))
+        }
+    };
+
+    let markers = text
+        .map(|text| minimap::git_markers(&key, &text))
+        .unwrap_or_default();
+    Ok(builder.finish(markers))
+}
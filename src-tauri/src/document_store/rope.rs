@@ -0,0 +1,216 @@
+//! Chunked rope
+//!
+//! Text is held in a sequence of chunks of at most `MAX_CHUNK_BYTES`, each
+//! caching its UTF-16 length and newline count. An edit only rewrites the
+//! chunks it touches, and positions are found by skipping whole chunks, so
+//! keystrokes in large files don't copy the whole buffer.
+
+use lsp_types::Position;
+
+const MAX_CHUNK_BYTES: usize = 2048;
+
+/// Edits leaving a chunk smaller than this merge it with its neighbour
+const MIN_CHUNK_BYTES: usize = MAX_CHUNK_BYTES / 4;
+
+#[derive(Debug, Clone, Default)]
+struct Chunk {
+    text: String,
+    utf16_len: usize,
+    newlines: usize,
+}
+
+impl Chunk {
+    fn new(text: String) -> Self {
+        Self {
+            utf16_len: text.encode_utf16().count(),
+            newlines: text.bytes().filter(|&b| b == b'\n').count(),
+            text,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Rope {
+    /// Never empty; an empty document is a single empty chunk
+    chunks: Vec<Chunk>,
+}
+
+/// Split text into chunks on char boundaries
+fn split_chunks(text: &str) -> Vec<Chunk> {
+    let mut chunks = Vec::with_capacity(text.len() / MAX_CHUNK_BYTES + 1);
+    let mut rest = text;
+    while rest.len() > MAX_CHUNK_BYTES {
+        let mut end = MAX_CHUNK_BYTES;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        chunks.push(Chunk::new(rest[..end].to_string()));
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() || chunks.is_empty() {
+        chunks.push(Chunk::new(rest.to_string()));
+    }
+    chunks
+}
+
+impl Rope {
+    pub fn from_str(text: &str) -> Self {
+        Self {
+            chunks: split_chunks(text),
+        }
+    }
+
+    pub fn len_bytes(&self) -> usize {
+        self.chunks.iter().map(|c| c.text.len()).sum()
+    }
+
+    pub fn len_utf16(&self) -> usize {
+        self.chunks.iter().map(|c| c.utf16_len).sum()
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.chunks.iter().map(|c| c.newlines).sum::<usize>() + 1
+    }
+
+    /// Chunk index and offset within it of a byte offset (clamped to the end)
+    fn locate(&self, offset: usize) -> (usize, usize) {
+        let mut start = 0;
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            if offset < start + chunk.text.len() {
+                return (index, offset - start);
+            }
+            start += chunk.text.len();
+        }
+        let last = self.chunks.len() - 1;
+        (last, self.chunks[last].text.len())
+    }
+
+    /// Byte offset of an LSP position (0-based line, UTF-16 character),
+    /// clamped to the end of the line / document like editors do
+    pub fn offset_of(&self, position: Position) -> usize {
+        let line = position.line as usize;
+        let mut offset = 0;
+        let mut newlines_before = 0;
+        let mut index = 0;
+
+        // Skip whole chunks before the line
+        if line > 0 {
+            loop {
+                let Some(chunk) = self.chunks.get(index) else {
+                    return self.len_bytes();
+                };
+                if newlines_before + chunk.newlines >= line {
+                    let needed = line - newlines_before;
+                    let (newline_at, _) = chunk
+                        .text
+                        .match_indices('\n')
+                        .nth(needed - 1)
+                        .unwrap_or((chunk.text.len() - 1, ""));
+                    offset += newline_at + 1;
+                    break;
+                }
+                newlines_before += chunk.newlines;
+                offset += chunk.text.len();
+                index += 1;
+            }
+        }
+
+        // Walk the line counting UTF-16 units
+        let (mut index, mut local) = self.locate(offset);
+        let mut remaining = position.character as usize;
+        while remaining > 0 {
+            let chunk = &self.chunks[index];
+            let Some(ch) = chunk.text[local..].chars().next() else {
+                if index + 1 >= self.chunks.len() {
+                    break;
+                }
+                index += 1;
+                local = 0;
+                continue;
+            };
+            if ch == '\n' || (ch == '\r' && chunk.text[local..].starts_with("\r\n")) {
+                break;
+            }
+            let width = ch.len_utf16();
+            if width > remaining {
+                break;
+            }
+            remaining -= width;
+            local += ch.len_utf8();
+            offset += ch.len_utf8();
+        }
+        offset
+    }
+
+    /// Replace the byte range `start..end` with `text`
+    pub fn replace(&mut self, start: usize, end: usize, text: &str) {
+        let end = end.max(start);
+        let (first, first_local) = self.locate(start);
+        let (mut last, last_local) = self.locate(end);
+
+        let mut combined = String::with_capacity(
+            first_local + text.len() + self.chunks[last].text.len() - last_local,
+        );
+        combined.push_str(&self.chunks[first].text[..first_local]);
+        combined.push_str(text);
+        combined.push_str(&self.chunks[last].text[last_local..]);
+
+        // Keep chunks from shrinking into many tiny pieces
+        if combined.len() < MIN_CHUNK_BYTES && last + 1 < self.chunks.len() {
+            last += 1;
+            combined.push_str(&self.chunks[last].text);
+        }
+
+        self.chunks.splice(first..=last, split_chunks(&combined));
+    }
+
+    /// Call `f` with each line, without its line break
+    pub fn for_each_line(&self, mut f: impl FnMut(&str)) {
+        // A line split across chunks is assembled here
+        let mut pending = String::new();
+        for chunk in &self.chunks {
+            let mut rest = chunk.text.as_str();
+            while let Some(newline) = rest.find('\n') {
+                let line = &rest[..newline];
+                if pending.is_empty() {
+                    f(line.strip_suffix('\r').unwrap_or(line));
+                } else {
+                    pending.push_str(line);
+                    f(pending.strip_suffix('\r').unwrap_or(&pending));
+                    pending.clear();
+                }
+                rest = &rest[newline + 1..];
+            }
+            pending.push_str(rest);
+        }
+        f(&pending);
+    }
+
+    /// Text of the byte range `start..end`
+    pub fn slice(&self, start: usize, end: usize) -> String {
+        let mut result = String::with_capacity(end.saturating_sub(start));
+        let mut chunk_start = 0;
+        for chunk in &self.chunks {
+            let chunk_end = chunk_start + chunk.text.len();
+            if chunk_end > start && chunk_start < end {
+                let from = start.saturating_sub(chunk_start);
+                let to = (end - chunk_start).min(chunk.text.len());
+                result.push_str(&chunk.text[from..to]);
+            }
+            if chunk_end >= end {
+                break;
+            }
+            chunk_start = chunk_end;
+        }
+        result
+    }
+}
+
+impl std::fmt::Display for Rope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for chunk in &self.chunks {
+            f.write_str(&chunk.text)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,345 @@
+//! Explorer Clipboard
+//!
+//! Pastes from the OS clipboard into the workspace: files and folders copied
+//! in the system file manager are copied into the target folder, and image
+//! data (screenshots) is saved as a PNG, `assets/image.png` by default.
+//! Existing names are never overwritten; a " copy" suffix is added instead.
+//!
+//! The clipboard is read with the platform's own tools (osascript,
+//! PowerShell, wl-paste/xclip), like terminal clipboard writes.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tauri::{AppHandle, Emitter};
+
+/// Folder (relative to the workspace root) images go to when no target is given
+const DEFAULT_IMAGE_FOLDER: &str = "assets";
+
+const DEFAULT_IMAGE_NAME: &str = "image.png";
+
+/// What a paste created
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardPasteResult {
+    /// "files", "image" or "none" when the clipboard had nothing to paste
+    pub kind: String,
+    pub target_dir: String,
+    pub created: Vec<String>,
+}
+
+/// Run a clipboard tool and return its stdout if it succeeded with output
+fn capture(program: &str, args: &[&str]) -> Option<Vec<u8>> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    (output.status.success() && !output.stdout.is_empty()).then_some(output.stdout)
+}
+
+/// Paths from a `text/uri-list` (or GNOME copied-files) payload
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn parse_uri_list(content: &str) -> Vec<PathBuf> {
+    content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("file://"))
+        .filter_map(|rest| {
+            // Drop an optional host ("file://localhost/...")
+            let path = &rest[rest.find('/')?..];
+            urlencoding::decode(path)
+                .ok()
+                .map(|p| PathBuf::from(p.into_owned()))
+        })
+        .collect()
+}
+
+/// Paths of files and folders copied in the system file manager
+fn clipboard_file_paths() -> Vec<PathBuf> {
+    #[cfg(target_os = "macos")]
+    let paths: Vec<PathBuf> = capture(
+        "osascript",
+        &[
+            "-l",
+            "JavaScript",
+            "-e",
+            "ObjC.import('AppKit'); var p = $.NSPasteboard.generalPasteboard.propertyListForType('NSFilenamesPboardType'); p.isNil() ? '' : ObjC.deepUnwrap(p).join('\\n')",
+        ],
+    )
+    .map(|out| {
+        String::from_utf8_lossy(&out)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| PathBuf::from(line.trim()))
+            .collect()
+    })
+    .unwrap_or_default();
+
+    #[cfg(target_os = "windows")]
+    let paths: Vec<PathBuf> = capture(
+        "powershell",
+        &[
+            "-NoProfile",
+            "-Command",
+            "Get-Clipboard -Format FileDropList | ForEach-Object { $_.FullName }",
+        ],
+    )
+    .map(|out| {
+        String::from_utf8_lossy(&out)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| PathBuf::from(line.trim()))
+            .collect()
+    })
+    .unwrap_or_default();
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let paths: Vec<PathBuf> = [
+        ("wl-paste", vec!["--no-newline", "--type", "text/uri-list"]),
+        (
+            "wl-paste",
+            vec!["--no-newline", "--type", "x-special/gnome-copied-files"],
+        ),
+        (
+            "xclip",
+            vec!["-selection", "clipboard", "-t", "text/uri-list", "-o"],
+        ),
+        (
+            "xclip",
+            vec![
+                "-selection",
+                "clipboard",
+                "-t",
+                "x-special/gnome-copied-files",
+                "-o",
+            ],
+        ),
+    ]
+    .iter()
+    .filter_map(|(program, args)| capture(program, args))
+    .map(|out| parse_uri_list(&String::from_utf8_lossy(&out)))
+    .find(|paths| !paths.is_empty())
+    .unwrap_or_default();
+
+    paths.into_iter().filter(|path| path.exists()).collect()
+}
+
+/// PNG bytes of an image on the clipboard
+fn clipboard_image_png() -> Option<Vec<u8>> {
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    {
+        // The platform tools can only write the image to a file
+        let temp = std::env::temp_dir().join(format!(
+            "rainy-clipboard-{}.png",
+            uuid::Uuid::new_v4().to_string().replace('-', "")
+        ));
+        let temp_str = temp.to_string_lossy().to_string();
+
+        #[cfg(target_os = "macos")]
+        {
+            let open = format!(
+                "set f to open for access POSIX file \"{}\" with write permission",
+                temp_str.replace('"', "\\\"")
+            );
+            capture(
+                "osascript",
+                &[
+                    "-e",
+                    "try",
+                    "-e",
+                    "set d to the clipboard as «class PNGf»",
+                    "-e",
+                    "on error",
+                    "-e",
+                    "return",
+                    "-e",
+                    "end try",
+                    "-e",
+                    &open,
+                    "-e",
+                    "write d to f",
+                    "-e",
+                    "close access f",
+                ],
+            );
+        }
+        #[cfg(target_os = "windows")]
+        {
+            let script = format!(
+                "Add-Type -AssemblyName System.Windows.Forms; $i = [System.Windows.Forms.Clipboard]::GetImage(); if ($i) {{ $i.Save('{}', [System.Drawing.Imaging.ImageFormat]::Png) }}",
+                temp_str.replace('\'', "''")
+            );
+            capture("powershell", &["-NoProfile", "-STA", "-Command", &script]);
+        }
+
+        let bytes = std::fs::read(&temp).ok();
+        let _ = std::fs::remove_file(&temp);
+        bytes.filter(|b| !b.is_empty())
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        capture("wl-paste", &["--type", "image/png"]).or_else(|| {
+            capture(
+                "xclip",
+                &["-selection", "clipboard", "-t", "image/png", "-o"],
+            )
+        })
+    }
+}
+
+/// A file name given by the caller: a single path component
+fn validate_file_name(name: &str) -> Result<(), String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() || trimmed == "." || trimmed == ".." {
+        return Err(format!("Invalid file name: {:?}", name));
+    }
+    if name.contains(['/', '\\']) || Path::new(name).components().count() != 1 {
+        return Err(format!("File name must not contain a path: {}", name));
+    }
+    Ok(())
+}
+
+/// `dir/name`, or `name copy.ext`, `name copy 2.ext`, ... if that exists
+fn unique_destination(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let path = Path::new(name);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| name.to_string());
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+
+    (1..)
+        .map(|n| {
+            let suffix = if n == 1 {
+                " copy".to_string()
+            } else {
+                format!(" copy {}", n)
+            };
+            dir.join(format!("{}{}{}", stem, suffix, extension))
+        })
+        .find(|candidate| !candidate.exists())
+        .unwrap_or(candidate)
+}
+
+/// Files and folders currently on the clipboard (paths that exist)
+#[tauri::command]
+pub async fn read_clipboard_files() -> Result<Vec<String>, String> {
+    let paths = tokio::task::spawn_blocking(clipboard_file_paths)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(paths
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect())
+}
+
+/// Paste clipboard files or image data into `target_dir`
+///
+/// Files are preferred over image data. Without a target, images go to the
+/// `explorer.pasteImageFolder` setting (default `assets`) under the
+/// workspace root; `image_name` must be a plain file name. Emits `explorer:pasted` so the explorer can refresh.
+#[tauri::command]
+pub async fn paste_clipboard_into(
+    app: AppHandle,
+    target_dir: Option<String>,
+    workspace_root: Option<String>,
+    image_name: Option<String>,
+) -> Result<ClipboardPasteResult, String> {
+    if let Some(name) = &image_name {
+        validate_file_name(name)?;
+    }
+    let files = tokio::task::spawn_blocking(clipboard_file_paths)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let (kind, target, created) = if !files.is_empty() {
+        let target = PathBuf::from(
+            target_dir
+                .or(workspace_root)
+                .ok_or("No target folder to paste into")?,
+        );
+        let mut created = Vec::with_capacity(files.len());
+        for source in &files {
+            let name = source
+                .file_name()
+                .ok_or_else(|| format!("Cannot paste {}", source.display()))?
+                .to_string_lossy()
+                .to_string();
+            if source.is_dir() && target.starts_with(source) {
+                return Err(format!("Cannot paste {} into itself", name));
+            }
+            let destination = unique_destination(&target, &name);
+            if source.is_dir() {
+                crate::file_operations::copy_dir_recursive(source, &destination).await?;
+            } else {
+                tokio::fs::copy(source, &destination)
+                    .await
+                    .map_err(|e| format!("Failed to copy {}: {}", name, e))?;
+            }
+            created.push(destination.to_string_lossy().to_string());
+        }
+        ("files", target, created)
+    } else if let Some(png) = tokio::task::spawn_blocking(clipboard_image_png)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        let target = match target_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => {
+                let root = workspace_root.ok_or("No target folder to paste into")?;
+                let folder = crate::configuration_manager::resolve_setting(
+                    Some(&root),
+                    "explorer.pasteImageFolder",
+                )
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_else(|| DEFAULT_IMAGE_FOLDER.to_string());
+                Path::new(&root).join(folder)
+            }
+        };
+        tokio::fs::create_dir_all(&target)
+            .await
+            .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+        let mut name = image_name.unwrap_or_else(|| DEFAULT_IMAGE_NAME.to_string());
+        if Path::new(&name).extension().is_none() {
+            name.push_str(".png");
+        }
+        let destination = unique_destination(&target, &name);
+        tokio::fs::write(&destination, png)
+            .await
+            .map_err(|e| format!("Failed to write {}: {}", destination.display(), e))?;
+        (
+            "image",
+            target,
+            vec![destination.to_string_lossy().to_string()],
+        )
+    } else {
+        let target = PathBuf::from(target_dir.or(workspace_root).unwrap_or_default());
+        ("none", target, Vec::new())
+    };
+
+    let result = ClipboardPasteResult {
+        kind: kind.to_string(),
+        target_dir: target.to_string_lossy().to_string(),
+        created,
+    };
+    if !result.created.is_empty() {
+        println!(
+            "[ExplorerClipboard] Pasted {} item(s) into {}",
+            result.created.len(),
+            result.target_dir
+        );
+        if let Err(e) = app.emit("explorer:pasted", &result) {
+            eprintln!("[ExplorerClipboard] Failed to emit paste event: {}", e);
+        }
+    }
+    Ok(result)
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::Cursor;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager};
 use zip::ZipArchive;
 
@@ -14,7 +14,16 @@ fn get_rainy_aether_dir(app: &AppHandle) -> Result<PathBuf, String> {
         .path()
         .home_dir()
         .map_err(|e| format!("Failed to get home directory: {}", e))?;
+    ensure_rainy_aether_dir(home_dir)
+}
+
+/// Same as `get_rainy_aether_dir`, for callers without an AppHandle (the CLI)
+fn user_rainy_aether_dir() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or("Failed to get home directory")?;
+    ensure_rainy_aether_dir(home_dir)
+}
 
+fn ensure_rainy_aether_dir(home_dir: PathBuf) -> Result<PathBuf, String> {
     let rainy_dir = home_dir.join(".rainy-aether");
 
     // Ensure .rainy-aether directory exists
@@ -33,7 +42,10 @@ fn get_rainy_aether_dir(app: &AppHandle) -> Result<PathBuf, String> {
 /// Get the extensions directory path and ensure it exists
 /// Extensions are stored in: ~/.rainy-aether/extensions/
 fn get_extensions_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let rainy_dir = get_rainy_aether_dir(app)?;
+    ensure_extensions_dir(get_rainy_aether_dir(app)?)
+}
+
+fn ensure_extensions_dir(rainy_dir: PathBuf) -> Result<PathBuf, String> {
     let extensions_dir = rainy_dir.join("extensions");
 
     // Ensure extensions directory exists
@@ -101,26 +113,57 @@ pub struct ExtensionMetadata {
     pub description: Option<String>,
 }
 
-#[tauri::command]
-pub fn load_installed_extensions(app: AppHandle) -> Result<String, String> {
-    let rainy_dir = get_rainy_aether_dir(&app)?;
-    let extensions_file = rainy_dir.join("installed_extensions.json");
+fn installed_extensions_file(rainy_dir: &Path) -> PathBuf {
+    rainy_dir.join("installed_extensions.json")
+}
+
+fn extensions_manifest_file(extensions_dir: &Path) -> PathBuf {
+    extensions_dir.join("extensions.json")
+}
 
+fn read_installed_file(rainy_dir: &Path) -> Result<String, String> {
+    let extensions_file = installed_extensions_file(rainy_dir);
     if !extensions_file.exists() {
         return Ok("[]".to_string());
     }
-
     fs::read_to_string(&extensions_file)
         .map_err(|e| format!("Failed to read extensions file: {}", e))
 }
 
+fn write_installed_file(rainy_dir: &Path, extensions: &str) -> Result<(), String> {
+    fs::write(installed_extensions_file(rainy_dir), extensions)
+        .map_err(|e| format!("Failed to write extensions file: {}", e))
+}
+
+fn read_manifest_file(extensions_dir: &Path) -> Result<String, String> {
+    let manifest_file = extensions_manifest_file(extensions_dir);
+    if !manifest_file.exists() {
+        // Return empty manifest if file doesn't exist
+        let empty_manifest = ExtensionsManifest { extensions: vec![] };
+        return serde_json::to_string_pretty(&empty_manifest)
+            .map_err(|e| format!("Failed to serialize empty manifest: {}", e));
+    }
+    fs::read_to_string(&manifest_file)
+        .map_err(|e| format!("Failed to read extensions manifest: {}", e))
+}
+
+fn write_manifest_file(extensions_dir: &Path, manifest: &str) -> Result<(), String> {
+    // Validate JSON before writing
+    let _: ExtensionsManifest =
+        serde_json::from_str(manifest).map_err(|e| format!("Invalid manifest JSON: {}", e))?;
+
+    fs::write(extensions_manifest_file(extensions_dir), manifest)
+        .map_err(|e| format!("Failed to write extensions manifest: {}", e))
+}
+
 #[tauri::command]
-pub fn save_installed_extensions(app: AppHandle, extensions: String) -> Result<(), String> {
-    let rainy_dir = get_rainy_aether_dir(&app)?;
-    let extensions_file = rainy_dir.join("installed_extensions.json");
+pub fn load_installed_extensions(app: AppHandle) -> Result<String, String> {
+    read_installed_file(&get_rainy_aether_dir(&app)?)
+}
 
-    fs::write(&extensions_file, extensions)
-        .map_err(|e| format!("Failed to write extensions file: {}", e))
+#[tauri::command]
+pub fn save_installed_extensions(app: AppHandle, extensions: String) -> Result<(), String> {
+    write_installed_file(&get_rainy_aether_dir(&app)?, &extensions)
 }
 
 #[tauri::command]
@@ -129,19 +172,30 @@ pub fn extract_extension(
     vsix_data: Vec<u8>,
     target_path: String,
 ) -> Result<(), String> {
-    let extensions_dir = get_extensions_dir(&app)?;
+    extract_into(&get_extensions_dir(&app)?, vsix_data, &target_path)
+}
 
+fn extract_into(
+    extensions_dir: &Path,
+    vsix_data: Vec<u8>,
+    target_path: &str,
+) -> Result<(), String> {
     // target_path is in VS Code format: "publisher.name-version"
     // Example: "pkief.material-icon-theme-5.28.0"
-    let full_target_path = extensions_dir.join(&target_path);
+    let full_target_path = extensions_dir.join(target_path);
 
     println!(
         "[ExtensionManager] Extracting extension to: {:?}",
         full_target_path
     );
 
+    unpack_vsix(vsix_data, &full_target_path)
+}
+
+/// Extract a VSIX package, dropping its `extension/` prefix
+fn unpack_vsix(vsix_data: Vec<u8>, full_target_path: &Path) -> Result<(), String> {
     // Create target directory
-    fs::create_dir_all(&full_target_path)
+    fs::create_dir_all(full_target_path)
         .map_err(|e| format!("Failed to create extension directory: {}", e))?;
 
     // Extract VSIX (which is a ZIP file)
@@ -279,32 +333,13 @@ pub fn read_extension_file(app: AppHandle, path: String) -> Result<String, Strin
 /// Load the extensions.json manifest file
 #[tauri::command]
 pub fn load_extensions_manifest(app: AppHandle) -> Result<String, String> {
-    let extensions_dir = get_extensions_dir(&app)?;
-    let manifest_file = extensions_dir.join("extensions.json");
-
-    if !manifest_file.exists() {
-        // Return empty manifest if file doesn't exist
-        let empty_manifest = ExtensionsManifest { extensions: vec![] };
-        return serde_json::to_string_pretty(&empty_manifest)
-            .map_err(|e| format!("Failed to serialize empty manifest: {}", e));
-    }
-
-    fs::read_to_string(&manifest_file)
-        .map_err(|e| format!("Failed to read extensions manifest: {}", e))
+    read_manifest_file(&get_extensions_dir(&app)?)
 }
 
 /// Save the extensions.json manifest file
 #[tauri::command]
 pub fn save_extensions_manifest(app: AppHandle, manifest: String) -> Result<(), String> {
-    let extensions_dir = get_extensions_dir(&app)?;
-    let manifest_file = extensions_dir.join("extensions.json");
-
-    // Validate JSON before writing
-    let _: ExtensionsManifest =
-        serde_json::from_str(&manifest).map_err(|e| format!("Invalid manifest JSON: {}", e))?;
-
-    fs::write(&manifest_file, manifest)
-        .map_err(|e| format!("Failed to write extensions manifest: {}", e))
+    write_manifest_file(&get_extensions_dir(&app)?, &manifest)
 }
 
 /// Get the Rainy Aether directory path (for diagnostics)
@@ -321,3 +356,134 @@ pub fn ensure_extensions_directory(app: AppHandle) -> Result<String, String> {
     let extensions_dir = get_extensions_dir(&app)?;
     Ok(extensions_dir.to_string_lossy().to_string())
 }
+
+// ============================================================================
+// HEADLESS INSTALLS (CLI)
+// ============================================================================
+
+/// An installed extension as listed by `--list-extensions`
+pub(crate) struct InstalledExtensionSummary {
+    pub id: String,
+    pub version: String,
+    pub enabled: bool,
+}
+
+fn read_installed(rainy_dir: &Path) -> Result<Vec<serde_json::Value>, String> {
+    serde_json::from_str(&read_installed_file(rainy_dir)?)
+        .map_err(|e| format!("Invalid extensions file: {}", e))
+}
+
+/// Extensions recorded in `installed_extensions.json`
+pub(crate) fn list_installed_extensions() -> Result<Vec<InstalledExtensionSummary>, String> {
+    let installed = read_installed(&user_rainy_aether_dir()?)?;
+    Ok(installed
+        .iter()
+        .map(|ext| InstalledExtensionSummary {
+            id: ext["id"].as_str().unwrap_or_default().to_string(),
+            version: ext["version"].as_str().unwrap_or_default().to_string(),
+            enabled: ext["enabled"].as_bool().unwrap_or(false),
+        })
+        .collect())
+}
+
+/// Install a VSIX package without the GUI, through the same extraction and
+/// files the extension manager commands use: extracted to
+/// `publisher.name-version`, added to `installed_extensions.json` and the
+/// extensions.json manifest. Returns the extension id and version.
+pub(crate) fn install_vsix(vsix_data: Vec<u8>) -> Result<(String, String), String> {
+    let package: serde_json::Value = {
+        let mut archive = ZipArchive::new(Cursor::new(&vsix_data))
+            .map_err(|e| format!("Failed to open VSIX archive: {}", e))?;
+        let file = archive
+            .by_name("extension/package.json")
+            .map_err(|_| "VSIX has no extension/package.json".to_string())?;
+        serde_json::from_reader(file).map_err(|e| format!("Invalid package.json: {}", e))?
+    };
+    let field = |key: &str| package[key].as_str().unwrap_or_default().to_string();
+    let (publisher, name, version) = (field("publisher"), field("name"), field("version"));
+    if publisher.is_empty() || name.is_empty() || version.is_empty() {
+        return Err("package.json must have publisher, name and version".to_string());
+    }
+    let id = format!("{}.{}", publisher, name);
+    let relative_path = format!(
+        "{}.{}-{}",
+        publisher.to_lowercase(),
+        name.to_lowercase(),
+        version
+    );
+
+    let rainy_dir = user_rainy_aether_dir()?;
+    let extensions_dir = ensure_extensions_dir(rainy_dir.clone())?;
+    let full_target_path = extensions_dir.join(&relative_path);
+    if full_target_path.exists() {
+        fs::remove_dir_all(&full_target_path)
+            .map_err(|e| format!("Failed to replace existing extension: {}", e))?;
+    }
+    extract_into(&extensions_dir, vsix_data, &relative_path)?;
+
+    let now = chrono::Utc::now();
+    let display_name = package["displayName"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| name.clone());
+    let description = field("description");
+
+    // Replace any other installed version
+    let mut installed = read_installed(&rainy_dir)?;
+    for previous in installed.iter().filter(|ext| ext["id"] == id.as_str()) {
+        if let Some(path) = previous["path"].as_str().filter(|p| *p != relative_path) {
+            let _ = fs::remove_dir_all(extensions_dir.join(path));
+        }
+    }
+    installed.retain(|ext| ext["id"] != id.as_str());
+    let mut entry = serde_json::json!({
+        "id": id,
+        "publisher": publisher,
+        "name": name,
+        "displayName": display_name,
+        "description": description,
+        "version": version,
+        "state": "enabled",
+        "installedAt": now.to_rfc3339(),
+        "enabled": true,
+        "manifest": package,
+        "path": relative_path,
+    });
+    if let Some(dependencies) = package["extensionDependencies"].as_array() {
+        entry["dependencies"] = serde_json::Value::Array(dependencies.clone());
+    }
+    installed.push(entry);
+    let installed_json = serde_json::to_string(&installed)
+        .map_err(|e| format!("Failed to serialize extensions: {}", e))?;
+    write_installed_file(&rainy_dir, &installed_json)?;
+
+    let mut manifest: ExtensionsManifest =
+        serde_json::from_str(&read_manifest_file(&extensions_dir)?)
+            .unwrap_or(ExtensionsManifest { extensions: vec![] });
+    manifest
+        .extensions
+        .retain(|entry| entry.identifier.id != id);
+    manifest.extensions.push(ExtensionManifestEntry {
+        identifier: ExtensionIdentifier {
+            id: id.clone(),
+            uuid: None,
+        },
+        version: version.clone(),
+        relative_path,
+        metadata: ExtensionMetadata {
+            installed_timestamp: Some(now.timestamp_millis()),
+            is_enabled: true,
+            is_builtin: Some(false),
+            is_system: Some(false),
+            updated_timestamp: Some(now.timestamp_millis()),
+            pre_release_version: Some(false),
+            display_name: Some(display_name),
+            description: Some(description),
+        },
+    });
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    write_manifest_file(&extensions_dir, &manifest_json)?;
+
+    Ok((id, version))
+}
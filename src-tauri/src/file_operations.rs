@@ -3,9 +3,18 @@
  *
  * High-performance file operations for AI agent tools with security controls,
  * batch processing, and efficient I/O.
+ *
+ * Tool paths are confined to the workspace root of the calling agent
+ * session, registered with `tool_set_session_root`; violations are counted
+ * in `tool_sandbox_metrics`.
  */
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
@@ -77,31 +86,565 @@ pub struct BatchReadResult {
     pub errors: Vec<String>,
 }
 
-/// Validate workspace path (security check)
-fn validate_workspace_path(workspace_root: &str, path: &str) -> Result<PathBuf, String> {
-    let root = PathBuf::from(workspace_root);
-    let full_path = root.join(path);
+/// Workspace search request (agent tool)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceSearchRequest {
+    pub query: String,
+    pub regex: Option<bool>,
+    pub case_sensitive: Option<bool>,
+    pub whole_word: Option<bool>,
+    /// Comma-separated glob filters, e.g. "*.rs,*.toml"
+    pub include: Option<String>,
+    /// Comma-separated glob filters, e.g. "*.lock,dist*"
+    pub exclude: Option<String>,
+    pub max_results: Option<usize>,
+    pub max_matches_per_file: Option<usize>,
+}
 
-    // Canonicalize to resolve symlinks and relative paths
-    let canonical_path = full_path
-        .canonicalize()
-        .map_err(|e| format!("Invalid path: {}", e))?;
+/// Chunked view of a large file (agent tool)
+#[derive(Debug, Serialize)]
+pub struct FileChunksResult {
+    pub path: String,
+    pub language: String,
+    pub total_lines: usize,
+    pub total_chunks: usize,
+    /// Every chunk's range and name; content only for the requested chunks
+    pub chunks: Vec<crate::code_chunker::CodeChunk>,
+}
+
+/// Single matching line returned to the agent
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceSearchLine {
+    pub line: usize,
+    pub content: String,
+}
+
+/// Matches for one file, path relative to the workspace root
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceSearchFile {
+    pub path: String,
+    pub total_matches: usize,
+    pub matches: Vec<WorkspaceSearchLine>,
+}
+
+/// Workspace search result (bounded for agent context)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceSearchResult {
+    pub files: Vec<WorkspaceSearchFile>,
+    pub total_matches: usize,
+    pub truncated: bool,
+}
+
+/// Maximum characters of a matching line returned to the agent
+const SEARCH_LINE_MAX_CHARS: usize = 200;
+
+/// Setting that lets a session use paths outside its workspace once approved
+const ALLOW_OUTSIDE_WORKSPACE_SETTING: &str = "agent.files.allowOutsideWorkspace";
+
+/// Paths agents may never read or modify, in addition to `sandbox.denyPaths`.
+/// Unlike the `agent.files.redact` defaults these can't be re-allowed.
+const DEFAULT_SANDBOX_DENY: [&str; 3] = [".git", ".env", ".env.*"];
+
+/// Number of recent violations kept for `tool_sandbox_metrics`
+const MAX_RECENT_VIOLATIONS: usize = 50;
+
+/// Workspace root and approved outside paths of an agent session
+struct SessionSandbox {
+    /// Root as registered (used to resolve workspace settings)
+    root: String,
+    canonical_root: PathBuf,
+    /// Paths outside the root the user approved, including everything below them
+    outside_grants: Vec<PathBuf>,
+}
+
+/// Sandboxes of active agent sessions (session_id -> sandbox)
+static SESSION_SANDBOXES: Lazy<Mutex<HashMap<String, SessionSandbox>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A rejected tool call, as reported by `tool_sandbox_metrics`
+#[derive(Debug, Clone, Serialize)]
+pub struct SandboxViolationRecord {
+    pub policy: String,
+    pub path: String,
+    /// Milliseconds since the epoch
+    pub timestamp: u64,
+}
+
+/// Sandbox checks and violations since startup
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SandboxMetrics {
+    pub checks: u64,
+    pub violations: u64,
+    pub violations_by_policy: HashMap<String, u64>,
+    pub recent_violations: std::collections::VecDeque<SandboxViolationRecord>,
+}
+
+static SANDBOX_METRICS: Lazy<Mutex<SandboxMetrics>> =
+    Lazy::new(|| Mutex::new(SandboxMetrics::default()));
+
+fn with_sandbox_metrics(update: impl FnOnce(&mut SandboxMetrics)) {
+    if let Ok(mut metrics) = SANDBOX_METRICS.lock() {
+        update(&mut metrics);
+    }
+}
+
+fn record_violation(policy: &str, path: &str) {
+    eprintln!("[FileOperations] Sandbox violation ({}): {}", policy, path);
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    with_sandbox_metrics(|m| {
+        m.violations += 1;
+        *m.violations_by_policy
+            .entry(policy.to_string())
+            .or_default() += 1;
+        if m.recent_violations.len() >= MAX_RECENT_VIOLATIONS {
+            m.recent_violations.pop_front();
+        }
+        m.recent_violations.push_back(SandboxViolationRecord {
+            policy: policy.to_string(),
+            path: path.to_string(),
+            timestamp,
+        });
+    });
+}
+
+/// Canonicalize a path that may not exist yet: the deepest existing ancestor
+/// is resolved (following symlinks) and the remaining components appended
+fn canonicalize_lenient(path: &Path) -> Result<PathBuf, String> {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        match existing.canonicalize() {
+            Ok(canonical) => {
+                return Ok(rest
+                    .iter()
+                    .rev()
+                    .fold(canonical, |acc, part| acc.join(part)));
+            }
+            Err(e) => match (existing.parent(), existing.file_name()) {
+                (Some(parent), Some(name)) => {
+                    rest.push(name.to_os_string());
+                    existing = parent;
+                }
+                _ => return Err(format!("Invalid path: {}", e)),
+            },
+        }
+    }
+}
+
+fn allows_outside_workspace(workspace_root: &str) -> bool {
+    crate::configuration_manager::resolve_setting(
+        Some(workspace_root),
+        ALLOW_OUTSIDE_WORKSPACE_SETTING,
+    )
+    .and_then(|v| v.as_bool())
+    .unwrap_or(false)
+}
+
+/// Workspace boundary of one tool call.
+///
+/// Every call names an agent session registered through
+/// `tool_set_session_root`; the caller's `workspace_root` must match the
+/// session's root, so a caller can't widen the sandbox by passing another one.
+/// Every path is canonicalized (symlinks resolved, `..` rejected) and must
+/// stay under the root, unless the session was granted access to it with
+/// `tool_approve_outside_path`.
+struct Sandbox {
+    root: String,
+    canonical_root: PathBuf,
+    outside_grants: Vec<PathBuf>,
+}
 
-    let canonical_root = root
+impl Sandbox {
+    fn open(workspace_root: &str, session_id: &str) -> Result<Self, String> {
+        let canonical_root = PathBuf::from(workspace_root)
+            .canonicalize()
+            .map_err(|e| format!("Invalid workspace root: {}", e))?;
+
+        let sessions = SESSION_SANDBOXES
+            .lock()
+            .map_err(|_| "Sandbox lock poisoned".to_string())?;
+        let Some(session) = sessions.get(session_id) else {
+            record_violation("unregistered_session", workspace_root);
+            return Err(format!(
+                "No workspace root registered for session {}; call tool_set_session_root first",
+                session_id
+            ));
+        };
+        if session.canonical_root != canonical_root {
+            record_violation("session_root", workspace_root);
+            return Err(format!(
+                "Workspace root {} does not match the root of session {}",
+                workspace_root, session_id
+            ));
+        }
+        Ok(Self {
+            root: session.root.clone(),
+            canonical_root: session.canonical_root.clone(),
+            outside_grants: session.outside_grants.clone(),
+        })
+    }
+
+    /// Resolve a tool path (relative to the root, or absolute) to its canonical form
+    fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        with_sandbox_metrics(|m| m.checks += 1);
+
+        // Block traversal attempts
+        if Path::new(path)
+            .components()
+            .any(|c| matches!(c, std::path::Component::ParentDir))
+        {
+            return Err(PolicyViolation {
+                code: "policy_violation".to_string(),
+                policy: "traversal".to_string(),
+                path: path.to_string(),
+                pattern: None,
+                message: "Path traversal not allowed".to_string(),
+            }
+            .into_error());
+        }
+
+        // Canonicalize to resolve symlinks, so links can't point out of the root
+        let canonical_path = canonicalize_lenient(&self.canonical_root.join(path))?;
+        if canonical_path.starts_with(&self.canonical_root)
+            || self
+                .outside_grants
+                .iter()
+                .any(|grant| canonical_path.starts_with(grant))
+        {
+            return Ok(canonical_path);
+        }
+
+        // "needs_approval" tells the agent to ask the user, then call
+        // tool_approve_outside_path and retry
+        let (policy, message) = if allows_outside_workspace(&self.root) {
+            (
+                "needs_approval",
+                format!(
+                    "{} is outside the workspace; it can be used once the user approves access",
+                    path
+                ),
+            )
+        } else {
+            ("outside_workspace", "Path is outside workspace".to_string())
+        };
+        Err(PolicyViolation {
+            code: "policy_violation".to_string(),
+            policy: policy.to_string(),
+            path: path.to_string(),
+            pattern: None,
+            message,
+        }
+        .into_error())
+    }
+
+    fn policy(&self) -> AgentFilePolicy {
+        AgentFilePolicy::load(&self.root, &self.canonical_root)
+    }
+}
+
+/// Register the workspace root agent tools of a session are confined to;
+/// returns the canonical root
+#[tauri::command]
+pub fn tool_set_session_root(session_id: String, workspace_root: String) -> Result<String, String> {
+    if session_id.is_empty() {
+        return Err("Session ID cannot be empty".to_string());
+    }
+    let canonical_root = PathBuf::from(&workspace_root)
         .canonicalize()
         .map_err(|e| format!("Invalid workspace root: {}", e))?;
+    if !canonical_root.is_dir() {
+        return Err(format!(
+            "Workspace root is not a directory: {}",
+            workspace_root
+        ));
+    }
+
+    let mut sessions = SESSION_SANDBOXES
+        .lock()
+        .map_err(|_| "Sandbox lock poisoned".to_string())?;
+    let keep_grants = sessions
+        .get(&session_id)
+        .filter(|session| session.canonical_root == canonical_root)
+        .map(|session| session.outside_grants.clone())
+        .unwrap_or_default();
+    sessions.insert(
+        session_id,
+        SessionSandbox {
+            root: workspace_root,
+            canonical_root: canonical_root.clone(),
+            outside_grants: keep_grants,
+        },
+    );
+    Ok(canonical_root.to_string_lossy().to_string())
+}
+
+/// Forget a session's workspace root and approvals
+#[tauri::command]
+pub fn tool_end_session(session_id: String) -> Result<bool, String> {
+    let mut sessions = SESSION_SANDBOXES
+        .lock()
+        .map_err(|_| "Sandbox lock poisoned".to_string())?;
+    Ok(sessions.remove(&session_id).is_some())
+}
+
+/// Let a session use a path outside its workspace (and everything below it)
+/// after the user approved it. Requires `agent.files.allowOutsideWorkspace`.
+#[tauri::command]
+pub fn tool_approve_outside_path(session_id: String, path: String) -> Result<String, String> {
+    let mut sessions = SESSION_SANDBOXES
+        .lock()
+        .map_err(|_| "Sandbox lock poisoned".to_string())?;
+    let session = sessions
+        .get_mut(&session_id)
+        .ok_or_else(|| format!("No workspace root registered for session {}", session_id))?;
+    if !allows_outside_workspace(&session.root) {
+        return Err(format!(
+            "Access outside the workspace is disabled; enable {} to allow it with approval",
+            ALLOW_OUTSIDE_WORKSPACE_SETTING
+        ));
+    }
+
+    let canonical_path = canonicalize_lenient(&session.canonical_root.join(&path))?;
+    if !session.outside_grants.contains(&canonical_path) {
+        println!(
+            "[FileOperations] Session {} approved for {}",
+            session_id,
+            canonical_path.display()
+        );
+        session.outside_grants.push(canonical_path.clone());
+    }
+    Ok(canonical_path.to_string_lossy().to_string())
+}
+
+/// Sandbox check and violation counts, with the most recent violations
+#[tauri::command]
+pub fn tool_sandbox_metrics() -> Result<SandboxMetrics, String> {
+    SANDBOX_METRICS
+        .lock()
+        .map(|metrics| metrics.clone())
+        .map_err(|_| "Sandbox metrics lock poisoned".to_string())
+}
+
+/// Default maximum size of a file the agent may read
+const DEFAULT_AGENT_MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Files excluded from agent reads by default (large, low-value context)
+const DEFAULT_AGENT_EXCLUDES: [&str; 6] = [
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "yarn.lock",
+    "Cargo.lock",
+    "*.min.js",
+    "*.map",
+];
+
+/// Files treated as secrets by default; the agent can never read or modify them
+const DEFAULT_AGENT_REDACTIONS: [&str; 8] = [
+    ".env",
+    ".env.*",
+    "*.pem",
+    "*.key",
+    "*.p12",
+    "*.pfx",
+    "id_rsa*",
+    "id_ed25519*",
+];
+
+/// Structured error returned when a tool call violates a workspace file policy
+#[derive(Debug, Serialize)]
+pub struct PolicyViolation {
+    pub code: String,
+    /// "max_file_size", "excluded", "redacted", "denied", "read_only",
+    /// "outside_workspace", "needs_approval" or "traversal"
+    pub policy: String,
+    pub path: String,
+    pub pattern: Option<String>,
+    pub message: String,
+}
+
+impl PolicyViolation {
+    fn into_error(self) -> String {
+        record_violation(&self.policy, &self.path);
+        serde_json::to_string(&self).unwrap_or(self.message)
+    }
+}
+
+/// Workspace file-size, exclusion and redaction policies for agent tools.
+///
+/// Configured through the `agent.files.maxFileSize`, `agent.files.exclude` and
+/// `agent.files.redact` settings. Configured patterns extend the defaults and
+/// use gitignore syntax, so `!.env.example` re-allows a single file. The
+/// workspace's `.rainy/agents.json` sandbox adds `denyPaths` and `readOnly`.
+pub(crate) struct AgentFilePolicy {
+    max_file_size: u64,
+    excluded: Gitignore,
+    redacted: Gitignore,
+    denied: Gitignore,
+    read_only: bool,
+}
+
+impl AgentFilePolicy {
+    pub(crate) fn load(workspace_root: &str, canonical_root: &Path) -> Self {
+        use crate::configuration_manager::resolve_setting;
+
+        let max_file_size = resolve_setting(Some(workspace_root), "agent.files.maxFileSize")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_AGENT_MAX_FILE_SIZE);
+
+        let build = |defaults: &[&str], key: &str| {
+            let mut builder = GitignoreBuilder::new(canonical_root);
+            for pattern in defaults {
+                let _ = builder.add_line(None, pattern);
+            }
+            if let Some(serde_json::Value::Array(patterns)) =
+                resolve_setting(Some(workspace_root), key)
+            {
+                for pattern in patterns.iter().filter_map(|p| p.as_str()) {
+                    if let Err(e) = builder.add_line(None, pattern) {
+                        eprintln!(
+                            "[FileOperations] Ignoring invalid pattern in {}: {}",
+                            key, e
+                        );
+                    }
+                }
+            }
+            builder.build().unwrap_or_else(|_| Gitignore::empty())
+        };
+
+        let sandbox = crate::agent_config::load(Path::new(workspace_root)).sandbox;
+        let mut denied = GitignoreBuilder::new(canonical_root);
+        for pattern in DEFAULT_SANDBOX_DENY {
+            let _ = denied.add_line(None, pattern);
+        }
+        for pattern in &sandbox.deny_paths {
+            if let Err(e) = denied.add_line(None, pattern) {
+                eprintln!(
+                    "[FileOperations] Ignoring invalid pattern in sandbox.denyPaths: {}",
+                    e
+                );
+            }
+        }
+
+        Self {
+            max_file_size,
+            excluded: build(&DEFAULT_AGENT_EXCLUDES, "agent.files.exclude"),
+            redacted: build(&DEFAULT_AGENT_REDACTIONS, "agent.files.redact"),
+            denied: denied.build().unwrap_or_else(|_| Gitignore::empty()),
+            read_only: sandbox.read_only,
+        }
+    }
+
+    fn matching_pattern(matcher: &Gitignore, full_path: &Path) -> Option<String> {
+        let matched = if full_path.starts_with(matcher.path()) {
+            matcher.matched_path_or_any_parents(full_path, full_path.is_dir())
+        } else {
+            // Approved path outside the workspace: match it and its parents by name
+            full_path
+                .ancestors()
+                .map(|p| matcher.matched(p, p.is_dir()))
+                .find(|m| !m.is_none())
+                .unwrap_or(ignore::Match::None)
+        };
+        if matched.is_ignore() {
+            matched.inner().map(|glob| glob.original().to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Reject secret and sandbox-denied files (used for every read or modification)
+    fn check_write(&self, full_path: &Path, path: &str) -> Result<(), String> {
+        if let Some(pattern) = Self::matching_pattern(&self.denied, full_path) {
+            return Err(PolicyViolation {
+                code: "policy_violation".to_string(),
+                policy: "denied".to_string(),
+                path: path.to_string(),
+                message: format!(
+                    "Access to {} is denied by the workspace agent sandbox pattern '{}'",
+                    path, pattern
+                ),
+                pattern: Some(pattern),
+            }
+            .into_error());
+        }
+        if let Some(pattern) = Self::matching_pattern(&self.redacted, full_path) {
+            return Err(PolicyViolation {
+                code: "policy_violation".to_string(),
+                policy: "redacted".to_string(),
+                path: path.to_string(),
+                message: format!(
+                    "Access to {} is blocked: it matches secret pattern '{}'",
+                    path, pattern
+                ),
+                pattern: Some(pattern),
+            }
+            .into_error());
+        }
+        Ok(())
+    }
 
-    // Ensure path is within workspace
-    if !canonical_path.starts_with(&canonical_root) {
-        return Err("Path is outside workspace".to_string());
+    /// Reject any change while the workspace sandbox is read-only, then as `check_write`
+    fn check_modify(&self, full_path: &Path, path: &str) -> Result<(), String> {
+        if self.read_only {
+            return Err(PolicyViolation {
+                code: "policy_violation".to_string(),
+                policy: "read_only".to_string(),
+                path: path.to_string(),
+                pattern: None,
+                message: format!(
+                    "Cannot modify {}: the workspace agent sandbox is read-only",
+                    path
+                ),
+            }
+            .into_error());
+        }
+        self.check_write(full_path, path)
     }
 
-    // Block traversal attempts
-    if path.contains("..") {
-        return Err("Path traversal not allowed".to_string());
+    /// Reject secret, excluded and oversized files
+    fn check_read(&self, full_path: &Path, path: &str, size: u64) -> Result<(), String> {
+        self.check_write(full_path, path)?;
+
+        if let Some(pattern) = Self::matching_pattern(&self.excluded, full_path) {
+            return Err(PolicyViolation {
+                code: "policy_violation".to_string(),
+                policy: "excluded".to_string(),
+                path: path.to_string(),
+                message: format!(
+                    "{} is excluded from agent reads by pattern '{}'",
+                    path, pattern
+                ),
+                pattern: Some(pattern),
+            }
+            .into_error());
+        }
+
+        if size > self.max_file_size {
+            return Err(PolicyViolation {
+                code: "policy_violation".to_string(),
+                policy: "max_file_size".to_string(),
+                path: path.to_string(),
+                pattern: None,
+                message: format!(
+                    "{} is {} bytes, above the agent limit of {} bytes; read a line range instead",
+                    path, size, self.max_file_size
+                ),
+            }
+            .into_error());
+        }
+
+        Ok(())
     }
 
-    Ok(canonical_path)
+    /// Whether a file may appear in agent search results or indexes
+    pub(crate) fn allows(&self, full_path: &Path) -> bool {
+        Self::matching_pattern(&self.redacted, full_path).is_none()
+            && Self::matching_pattern(&self.excluded, full_path).is_none()
+            && Self::matching_pattern(&self.denied, full_path).is_none()
+    }
 }
 
 /// Read file with optional line range
@@ -111,9 +654,11 @@ pub async fn tool_read_file(
     path: String,
     start_line: Option<usize>,
     end_line: Option<usize>,
+    session_id: String,
 ) -> Result<FileReadResult, String> {
     // Validate path
-    let full_path = validate_workspace_path(&workspace_root, &path)?;
+    let sandbox = Sandbox::open(&workspace_root, &session_id)?;
+    let full_path = sandbox.resolve(&path)?;
 
     // Check if file exists
     if !full_path.exists() {
@@ -125,16 +670,28 @@ pub async fn tool_read_file(
         return Err(format!("Path is not a file: {}", path));
     }
 
-    // Read file
-    let content = fs::read_to_string(&full_path)
-        .await
-        .map_err(|e| format!("Failed to read file: {}", e))?;
-
     // Get metadata
     let metadata = fs::metadata(&full_path)
         .await
         .map_err(|e| format!("Failed to read file metadata: {}", e))?;
 
+    // Enforce workspace file policies; ranged reads may exceed the size limit
+    let policy = sandbox.policy();
+    let size_for_policy = if start_line.is_some() || end_line.is_some() {
+        0
+    } else {
+        metadata.len()
+    };
+    policy.check_read(&full_path, &path, size_for_policy)?;
+
+    // Unsaved editor content is authoritative for documents open in the editor
+    let content = match crate::document_store::open_text(&full_path) {
+        Some(text) => text,
+        None => fs::read_to_string(&full_path)
+            .await
+            .map_err(|e| format!("Failed to read file: {}", e))?,
+    };
+
     // Apply line filtering if needed
     let filtered_content = match (start_line, end_line) {
         (Some(start), Some(end)) => {
@@ -181,8 +738,11 @@ pub async fn tool_write_file(
     path: String,
     content: String,
     create_dirs: Option<bool>,
+    session_id: String,
 ) -> Result<FileWriteResult, String> {
-    let full_path = validate_workspace_path(&workspace_root, &path)?;
+    let sandbox = Sandbox::open(&workspace_root, &session_id)?;
+    let full_path = sandbox.resolve(&path)?;
+    sandbox.policy().check_modify(&full_path, &path)?;
 
     // Create parent directories if needed
     if create_dirs.unwrap_or(false) {
@@ -215,8 +775,15 @@ pub async fn tool_edit_file(
     workspace_root: String,
     path: String,
     operations: Vec<EditOperation>,
+    session_id: String,
 ) -> Result<FileEditResult, String> {
-    let full_path = validate_workspace_path(&workspace_root, &path)?;
+    let sandbox = Sandbox::open(&workspace_root, &session_id)?;
+    let full_path = sandbox.resolve(&path)?;
+
+    let size = fs::metadata(&full_path).await.map(|m| m.len()).unwrap_or(0);
+    let policy = sandbox.policy();
+    policy.check_modify(&full_path, &path)?;
+    policy.check_read(&full_path, &path, size)?;
 
     // Read current content
     let original_content = fs::read_to_string(&full_path)
@@ -289,8 +856,11 @@ pub async fn tool_delete_file(
     workspace_root: String,
     path: String,
     recursive: Option<bool>,
+    session_id: String,
 ) -> Result<usize, String> {
-    let full_path = validate_workspace_path(&workspace_root, &path)?;
+    let sandbox = Sandbox::open(&workspace_root, &session_id)?;
+    let full_path = sandbox.resolve(&path)?;
+    sandbox.policy().check_modify(&full_path, &path)?;
 
     if !full_path.exists() {
         return Err(format!("Path does not exist: {}", path));
@@ -338,9 +908,15 @@ pub async fn tool_rename_file(
     workspace_root: String,
     old_path: String,
     new_path: String,
+    session_id: String,
 ) -> Result<String, String> {
-    let old_full_path = validate_workspace_path(&workspace_root, &old_path)?;
-    let new_full_path = validate_workspace_path(&workspace_root, &new_path)?;
+    let sandbox = Sandbox::open(&workspace_root, &session_id)?;
+    let old_full_path = sandbox.resolve(&old_path)?;
+    let new_full_path = sandbox.resolve(&new_path)?;
+
+    let policy = sandbox.policy();
+    policy.check_modify(&old_full_path, &old_path)?;
+    policy.check_modify(&new_full_path, &new_path)?;
 
     if !old_full_path.exists() {
         return Err(format!("Source path does not exist: {}", old_path));
@@ -364,9 +940,15 @@ pub async fn tool_copy_file(
     source_path: String,
     dest_path: String,
     overwrite: Option<bool>,
+    session_id: String,
 ) -> Result<usize, String> {
-    let source_full_path = validate_workspace_path(&workspace_root, &source_path)?;
-    let dest_full_path = validate_workspace_path(&workspace_root, &dest_path)?;
+    let sandbox = Sandbox::open(&workspace_root, &session_id)?;
+    let source_full_path = sandbox.resolve(&source_path)?;
+    let dest_full_path = sandbox.resolve(&dest_path)?;
+
+    let policy = sandbox.policy();
+    policy.check_write(&source_full_path, &source_path)?;
+    policy.check_modify(&dest_full_path, &dest_path)?;
 
     if !source_full_path.exists() {
         return Err(format!("Source path does not exist: {}", source_path));
@@ -403,6 +985,7 @@ pub async fn tool_copy_file(
 pub async fn tool_batch_read_files(
     workspace_root: String,
     request: BatchReadRequest,
+    session_id: String,
 ) -> Result<BatchReadResult, String> {
     let mut results = Vec::new();
     let mut errors = Vec::new();
@@ -418,10 +1001,11 @@ pub async fn tool_batch_read_files(
             let sem = semaphore.clone();
             let start = request.start_line;
             let end = request.end_line;
+            let session_id = session_id.clone();
 
             tokio::spawn(async move {
                 let _permit = sem.acquire().await;
-                tool_read_file(workspace, path, start, end).await
+                tool_read_file(workspace, path, start, end, session_id).await
             })
         })
         .collect();
@@ -440,8 +1024,173 @@ pub async fn tool_batch_read_files(
     })
 }
 
+/// Read a file as declaration-aligned chunks
+///
+/// Without `chunk_indices` this returns an outline (ranges, kinds and names of
+/// every chunk) so the agent can pick the parts of a large file it needs; the
+/// listed chunks are returned with their content.
+#[tauri::command]
+pub async fn tool_read_file_chunks(
+    workspace_root: String,
+    path: String,
+    max_tokens: Option<usize>,
+    chunk_indices: Option<Vec<usize>>,
+    session_id: String,
+) -> Result<FileChunksResult, String> {
+    use crate::code_chunker::{chunk_source, ChunkLanguage, DEFAULT_CHUNK_TOKENS};
+
+    let sandbox = Sandbox::open(&workspace_root, &session_id)?;
+    let full_path = sandbox.resolve(&path)?;
+    if !full_path.is_file() {
+        return Err(format!("Path is not a file: {}", path));
+    }
+
+    // Chunked reads are how large files are meant to be consumed, so only
+    // secret and exclusion policies apply here
+    sandbox.policy().check_read(&full_path, &path, 0)?;
+
+    let content = match crate::document_store::open_text(&full_path) {
+        Some(text) => text,
+        None => fs::read_to_string(&full_path)
+            .await
+            .map_err(|e| format!("Failed to read file: {}", e))?,
+    };
+
+    let language = full_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(ChunkLanguage::from_extension)
+        .unwrap_or(ChunkLanguage::Plain);
+
+    let mut chunks = chunk_source(
+        &content,
+        language,
+        max_tokens.unwrap_or(DEFAULT_CHUNK_TOKENS),
+    );
+    let wanted = chunk_indices.unwrap_or_default();
+    for (i, chunk) in chunks.iter_mut().enumerate() {
+        if !wanted.contains(&i) {
+            chunk.content.clear();
+        }
+    }
+
+    Ok(FileChunksResult {
+        path,
+        language: language.name().to_string(),
+        total_lines: content.lines().count(),
+        total_chunks: chunks.len(),
+        chunks,
+    })
+}
+
+/// Search the workspace using the project search engine
+///
+/// Lets agents locate code by text or regex instead of reading directories
+/// file-by-file. Results are capped per file and overall to keep responses small.
+#[tauri::command]
+pub async fn tool_search_workspace(
+    workspace_root: String,
+    request: WorkspaceSearchRequest,
+    session_id: String,
+) -> Result<WorkspaceSearchResult, String> {
+    if request.query.is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+
+    let use_regex = request.regex.unwrap_or(false);
+    let case_sensitive = request.case_sensitive.unwrap_or(false);
+
+    // Surface invalid patterns to the agent instead of silently returning nothing
+    if use_regex {
+        regex::RegexBuilder::new(&request.query)
+            .case_insensitive(!case_sensitive)
+            .build()
+            .map_err(|e| format!("Invalid regex: {}", e))?;
+    }
+
+    let sandbox = Sandbox::open(&workspace_root, &session_id)?;
+    let root = sandbox.resolve(".")?;
+    let max_results = request.max_results.unwrap_or(200).min(1000);
+    let max_per_file = request.max_matches_per_file.unwrap_or(10).max(1);
+
+    let options = crate::project_manager::SearchOptions {
+        case_sensitive,
+        whole_word: request.whole_word.unwrap_or(false),
+        use_regex,
+        include_pattern: request.include,
+        exclude_pattern: request.exclude,
+        max_results: Some(max_results),
+        include_ignored: false,
+    };
+
+    let query = request.query;
+    let search_root = root.clone();
+    let results = tokio::task::spawn_blocking(move || {
+        crate::project_manager::run_workspace_search(&search_root, &query, &options)
+    })
+    .await
+    .map_err(|e| format!("Search task failed: {}", e))??;
+
+    let policy = sandbox.policy();
+    let mut files = Vec::with_capacity(results.len());
+    let mut total_matches = 0;
+    let mut truncated = false;
+
+    for result in results {
+        // Never surface secret or excluded files through search
+        if !policy.allows(Path::new(&result.path)) {
+            continue;
+        }
+
+        let total = result.matches.len();
+        total_matches += total;
+        if total > max_per_file {
+            truncated = true;
+        }
+
+        let relative = Path::new(&result.path)
+            .strip_prefix(&root)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or(result.path);
+
+        let matches = result
+            .matches
+            .into_iter()
+            .take(max_per_file)
+            .map(|m| WorkspaceSearchLine {
+                line: m.line_number,
+                content: truncate_line(m.line_content.trim_end(), SEARCH_LINE_MAX_CHARS),
+            })
+            .collect();
+
+        files.push(WorkspaceSearchFile {
+            path: relative,
+            total_matches: total,
+            matches,
+        });
+    }
+
+    if total_matches >= max_results {
+        truncated = true;
+    }
+
+    Ok(WorkspaceSearchResult {
+        files,
+        total_matches,
+        truncated,
+    })
+}
+
+/// Helper: Truncate a line to a maximum number of characters
+fn truncate_line(line: &str, max_chars: usize) -> String {
+    match line.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}…", &line[..idx]),
+        None => line.to_string(),
+    }
+}
+
 /// Helper: Copy directory recursively
-fn copy_dir_recursive<'a>(
+pub(crate) fn copy_dir_recursive<'a>(
     src: &'a Path,
     dest: &'a Path,
 ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send + 'a>> {
@@ -0,0 +1,424 @@
+//! Focus Mode
+//!
+//! A Pomodoro-style timer cycling through focus blocks and short/long
+//! breaks. The timer lives in the backend so it keeps running while windows
+//! reload and survives restarts (`~/.rainy-aether/focus.json`); the status
+//! bar and tray icon follow it through events:
+//!
+//! - `focus:state-changed` with a `FocusStatus` whenever the timer is
+//!   started, paused, resumed, stopped or moves to another phase
+//! - `focus:tick` every second while running
+//! - `focus:phase-completed` when a block or break runs out
+//!
+//! Durations come from `focus.durations` (`{ focus, shortBreak, longBreak }`
+//! in minutes, plus `longBreakEvery`) unless given to `focus_start`. With
+//! `focus.doNotDisturb` enabled, notifications are silenced during focus
+//! blocks; `focus.autoStartBreaks` / `focus.autoStartFocus` chain phases
+//! without waiting for the user.
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Shortest phase; anything below would let the timer cycle through phases
+/// (and notifications) almost continuously
+const MIN_PHASE_MINUTES: f64 = 1.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum FocusPhase {
+    #[default]
+    Focus,
+    ShortBreak,
+    LongBreak,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum TimerStatus {
+    #[default]
+    Stopped,
+    Running,
+    Paused,
+}
+
+/// Phase lengths in minutes
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FocusDurations {
+    pub focus: f64,
+    pub short_break: f64,
+    pub long_break: f64,
+    /// Focus blocks before a long break
+    pub long_break_every: u32,
+}
+
+impl Default for FocusDurations {
+    fn default() -> Self {
+        Self {
+            focus: 25.0,
+            short_break: 5.0,
+            long_break: 15.0,
+            long_break_every: 4,
+        }
+    }
+}
+
+impl FocusDurations {
+    fn phase_ms(&self, phase: FocusPhase) -> i64 {
+        let minutes = match phase {
+            FocusPhase::Focus => self.focus,
+            FocusPhase::ShortBreak => self.short_break,
+            FocusPhase::LongBreak => self.long_break,
+        };
+        (minutes.max(MIN_PHASE_MINUTES) * 60_000.0) as i64
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (name, minutes) in [
+            ("focus", self.focus),
+            ("shortBreak", self.short_break),
+            ("longBreak", self.long_break),
+        ] {
+            if minutes.is_nan() || minutes < MIN_PHASE_MINUTES {
+                return Err(format!(
+                    "The {} phase must last at least {} minute",
+                    name, MIN_PHASE_MINUTES
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Persisted timer state
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct FocusTimer {
+    phase: FocusPhase,
+    status: TimerStatus,
+    durations: FocusDurations,
+    /// Unix ms at which the running phase ends
+    ends_at: Option<i64>,
+    /// Time left in a paused phase
+    remaining_ms: Option<i64>,
+    /// Focus blocks finished since the timer was started
+    completed_blocks: u32,
+    /// What the user is working on, shown next to the timer
+    label: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusStatus {
+    pub phase: FocusPhase,
+    pub status: TimerStatus,
+    pub remaining_ms: i64,
+    pub phase_duration_ms: i64,
+    pub completed_blocks: u32,
+    pub durations: FocusDurations,
+    pub label: Option<String>,
+    pub do_not_disturb: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct FocusTick {
+    phase: FocusPhase,
+    remaining_ms: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PhaseCompleted {
+    completed: FocusPhase,
+    next: FocusPhase,
+    completed_blocks: u32,
+    /// Whether the next phase started on its own
+    auto_started: bool,
+}
+
+impl FocusTimer {
+    fn remaining_ms(&self, now: i64) -> i64 {
+        match self.status {
+            TimerStatus::Running => self.ends_at.map(|end| (end - now).max(0)).unwrap_or(0),
+            TimerStatus::Paused => self.remaining_ms.unwrap_or(0),
+            TimerStatus::Stopped => self.durations.phase_ms(self.phase),
+        }
+    }
+
+    fn status(&self, now: i64) -> FocusStatus {
+        FocusStatus {
+            phase: self.phase,
+            status: self.status,
+            remaining_ms: self.remaining_ms(now),
+            phase_duration_ms: self.durations.phase_ms(self.phase),
+            completed_blocks: self.completed_blocks,
+            durations: self.durations,
+            label: self.label.clone(),
+            do_not_disturb: self.wants_do_not_disturb(),
+        }
+    }
+
+    fn wants_do_not_disturb(&self) -> bool {
+        self.phase == FocusPhase::Focus
+            && self.status == TimerStatus::Running
+            && setting_bool("focus.doNotDisturb", false)
+    }
+
+    fn run(&mut self, phase: FocusPhase, now: i64) {
+        self.phase = phase;
+        self.status = TimerStatus::Running;
+        self.ends_at = Some(now + self.durations.phase_ms(phase));
+        self.remaining_ms = None;
+    }
+
+    /// Finish the running phase and move to the next one
+    fn advance(&mut self, now: i64) -> PhaseCompleted {
+        let completed = self.phase;
+        let next = match completed {
+            FocusPhase::Focus => {
+                self.completed_blocks += 1;
+                let every = self.durations.long_break_every.max(1);
+                if self.completed_blocks.is_multiple_of(every) {
+                    FocusPhase::LongBreak
+                } else {
+                    FocusPhase::ShortBreak
+                }
+            }
+            FocusPhase::ShortBreak | FocusPhase::LongBreak => FocusPhase::Focus,
+        };
+        let auto_start = match next {
+            FocusPhase::Focus => setting_bool("focus.autoStartFocus", false),
+            _ => setting_bool("focus.autoStartBreaks", true),
+        };
+        if auto_start {
+            self.run(next, now);
+        } else {
+            self.phase = next;
+            self.status = TimerStatus::Stopped;
+            self.ends_at = None;
+            self.remaining_ms = None;
+        }
+        PhaseCompleted {
+            completed,
+            next,
+            completed_blocks: self.completed_blocks,
+            auto_started: auto_start,
+        }
+    }
+}
+
+static FOCUS: Lazy<Mutex<FocusTimer>> = Lazy::new(|| Mutex::new(load()));
+
+fn setting_bool(key: &str, default: bool) -> bool {
+    crate::configuration_manager::resolve_setting(None, key)
+        .and_then(|v| v.as_bool())
+        .unwrap_or(default)
+}
+
+fn durations_from_settings() -> FocusDurations {
+    crate::configuration_manager::resolve_setting(None, "focus.durations")
+        .and_then(|value| serde_json::from_value::<FocusDurations>(value).ok())
+        .filter(|durations| durations.validate().is_ok())
+        .unwrap_or_default()
+}
+
+fn state_path() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join(".rainy-aether").join("focus.json"))
+}
+
+fn load() -> FocusTimer {
+    state_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_else(|| FocusTimer {
+            durations: durations_from_settings(),
+            ..Default::default()
+        })
+}
+
+fn save(timer: &FocusTimer) {
+    let Some(path) = state_path() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    match serde_json::to_string_pretty(timer) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                eprintln!("[FocusMode] Failed to save timer: {}", e);
+            }
+        }
+        Err(e) => eprintln!("[FocusMode] Failed to serialize timer: {}", e),
+    }
+}
+
+fn now_ms() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+/// Apply a change, persist it and announce the new state
+fn update<T>(
+    app: &AppHandle,
+    change: impl FnOnce(&mut FocusTimer, i64) -> Result<T, String>,
+) -> Result<FocusStatus, String> {
+    let mut timer = FOCUS.lock().map_err(|e| e.to_string())?;
+    let now = now_ms();
+    change(&mut timer, now)?;
+    save(&timer);
+    let status = timer.status(now);
+    drop(timer);
+
+    crate::notification_manager::set_focus_do_not_disturb(app, status.do_not_disturb);
+    if let Err(e) = app.emit("focus:state-changed", &status) {
+        eprintln!("[FocusMode] Failed to emit state change: {}", e);
+    }
+    Ok(status)
+}
+
+/// Advance a running phase that has run out; returns the completion, if any
+fn complete_due_phase(app: &AppHandle) -> Option<PhaseCompleted> {
+    let mut completed = None;
+    let result = update(app, |timer, now| {
+        // Phases missed while the app was closed are skipped, not replayed
+        if timer.status == TimerStatus::Running && timer.ends_at.is_some_and(|end| end <= now) {
+            completed = Some(timer.advance(now));
+        }
+        Ok(())
+    });
+    if result.is_err() {
+        return None;
+    }
+    completed
+}
+
+/// Restore the timer and start ticking; called once from setup
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        // Also restores do not disturb for a focus block still running
+        if let Some(completed) = complete_due_phase(&app) {
+            let _ = app.emit("focus:phase-completed", &completed);
+        }
+        loop {
+            std::thread::sleep(TICK_INTERVAL);
+            let tick = match FOCUS.lock() {
+                Ok(timer) if timer.status == TimerStatus::Running => {
+                    let now = now_ms();
+                    FocusTick {
+                        phase: timer.phase,
+                        remaining_ms: timer.remaining_ms(now),
+                    }
+                }
+                _ => continue,
+            };
+            let _ = app.emit("focus:tick", &tick);
+            if tick.remaining_ms == 0 {
+                if let Some(completed) = complete_due_phase(&app) {
+                    println!(
+                        "[FocusMode] {:?} finished, next {:?}",
+                        completed.completed, completed.next
+                    );
+                    let _ = app.emit("focus:phase-completed", &completed);
+                }
+            }
+        }
+    });
+}
+
+/// Start a phase (default: the upcoming one), optionally with new durations
+#[tauri::command]
+pub fn focus_start(
+    app: AppHandle,
+    phase: Option<FocusPhase>,
+    durations: Option<FocusDurations>,
+    label: Option<String>,
+) -> Result<FocusStatus, String> {
+    if let Some(durations) = &durations {
+        durations.validate()?;
+    }
+    update(&app, |timer, now| {
+        if timer.status == TimerStatus::Stopped
+            && timer.phase == FocusPhase::Focus
+            && phase.is_none()
+        {
+            // A fresh session rereads the settings
+            timer.durations = durations_from_settings();
+        }
+        if let Some(durations) = durations {
+            timer.durations = durations;
+        }
+        if label.is_some() {
+            timer.label = label;
+        }
+        let phase = phase.unwrap_or(timer.phase);
+        timer.run(phase, now);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn focus_pause(app: AppHandle) -> Result<FocusStatus, String> {
+    update(&app, |timer, now| {
+        if timer.status != TimerStatus::Running {
+            return Err("Focus timer is not running".to_string());
+        }
+        timer.remaining_ms = Some(timer.remaining_ms(now));
+        timer.ends_at = None;
+        timer.status = TimerStatus::Paused;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn focus_resume(app: AppHandle) -> Result<FocusStatus, String> {
+    update(&app, |timer, now| {
+        if timer.status != TimerStatus::Paused {
+            return Err("Focus timer is not paused".to_string());
+        }
+        timer.ends_at = Some(now + timer.remaining_ms.take().unwrap_or(0));
+        timer.status = TimerStatus::Running;
+        Ok(())
+    })
+}
+
+/// Stop the timer and reset the cycle to a new focus block
+#[tauri::command]
+pub fn focus_stop(app: AppHandle) -> Result<FocusStatus, String> {
+    update(&app, |timer, _| {
+        *timer = FocusTimer {
+            durations: timer.durations,
+            label: timer.label.take(),
+            ..Default::default()
+        };
+        Ok(())
+    })
+}
+
+/// End the current phase early and move on as if it had run out
+#[tauri::command]
+pub fn focus_skip(app: AppHandle) -> Result<FocusStatus, String> {
+    let mut completed = None;
+    let status = update(&app, |timer, now| {
+        completed = Some(timer.advance(now));
+        Ok(())
+    })?;
+    if let Some(completed) = completed {
+        let _ = app.emit("focus:phase-completed", &completed);
+    }
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn focus_get_state() -> Result<FocusStatus, String> {
+    let timer = FOCUS.lock().map_err(|e| e.to_string())?;
+    Ok(timer.status(now_ms()))
+}
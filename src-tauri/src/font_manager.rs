@@ -55,6 +55,125 @@ pub struct FontManifest {
     pub last_updated: i64,
 }
 
+/// Unicode ranges always kept when subsetting (Google Fonts "latin" set)
+const LATIN_UNICODE_RANGES: [&str; 17] = [
+    "U+0000-00FF",
+    "U+0131",
+    "U+0152-0153",
+    "U+02BB-02BC",
+    "U+02C6",
+    "U+02DA",
+    "U+02DC",
+    "U+2000-206F",
+    "U+2074",
+    "U+20AC",
+    "U+2122",
+    "U+2191",
+    "U+2193",
+    "U+2212",
+    "U+2215",
+    "U+FEFF",
+    "U+FFFD",
+];
+
+/// Downloaded fonts smaller than this are used as-is
+const MIN_SUBSET_BYTES: u64 = 256 * 1024;
+
+const SUBSET_TIMEOUT_SECS: u64 = 60;
+
+/// Optional subsetting applied after a download
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FontSubsetOptions {
+    /// Extra ranges on top of Latin, e.g. "U+0400-04FF" or "U+2500-257F"
+    #[serde(default)]
+    pub unicode_ranges: Vec<String>,
+}
+
+/// Result of subsetting a font file
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FontSubsetResult {
+    /// Full-coverage original, kept for fallback
+    pub original_path: String,
+    pub subset_path: String,
+    pub original_size: u64,
+    pub subset_size: u64,
+    pub unicode_ranges: Vec<String>,
+}
+
+/// Latin plus validated extra ranges, in pyftsubset syntax
+fn subset_unicode_ranges(extra: &[String]) -> Result<Vec<String>, String> {
+    let pattern = regex::Regex::new(r"^U\+[0-9A-Fa-f]{1,6}(-[0-9A-Fa-f]{1,6})?$")
+        .map_err(|e| e.to_string())?;
+
+    let mut ranges: Vec<String> = LATIN_UNICODE_RANGES.iter().map(|r| r.to_string()).collect();
+    for range in extra {
+        let range = range.trim().to_uppercase();
+        if !pattern.is_match(&range) {
+            return Err(format!("Invalid unicode range: {}", range));
+        }
+        if !ranges.contains(&range) {
+            ranges.push(range);
+        }
+    }
+    Ok(ranges)
+}
+
+/// Subset a font to the given ranges as woff2 using fontTools (pyftsubset)
+async fn run_font_subset(
+    source: &std::path::Path,
+    output: &std::path::Path,
+    ranges: &[String],
+) -> Result<(), String> {
+    let args = vec![
+        source.to_string_lossy().to_string(),
+        format!("--unicodes={}", ranges.join(",")),
+        "--flavor=woff2".to_string(),
+        "--layout-features=*".to_string(),
+        format!("--output-file={}", output.to_string_lossy()),
+    ];
+
+    // Prefer the pyftsubset entry point, fall back to the python module
+    let candidates: [(&str, Vec<String>); 2] = [
+        ("pyftsubset", args.clone()),
+        (
+            "python3",
+            ["-m".to_string(), "fontTools.subset".to_string()]
+                .into_iter()
+                .chain(args)
+                .collect(),
+        ),
+    ];
+
+    let mut last_error = "fontTools (pyftsubset) is not installed".to_string();
+    for (program, program_args) in candidates {
+        let child = tokio::process::Command::new(program)
+            .args(&program_args)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .output();
+
+        let output =
+            match tokio::time::timeout(std::time::Duration::from_secs(SUBSET_TIMEOUT_SECS), child)
+                .await
+            {
+                Err(_) => return Err("Font subsetting timed out".to_string()),
+                Ok(Err(_)) => continue, // program not found
+                Ok(Ok(output)) => output,
+            };
+
+        if output.status.success() {
+            return Ok(());
+        }
+        last_error = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    }
+
+    Err(format!("Font subsetting failed: {}", last_error))
+}
+
 /// Get fonts directory path
 fn get_fonts_dir(app: &AppHandle) -> Result<PathBuf, String> {
     let home_dir = app
@@ -125,6 +244,7 @@ pub async fn download_font_file(
     url: String,
     font_family: String,
     variant_name: String,
+    subset: Option<FontSubsetOptions>,
 ) -> Result<String, String> {
     let fonts_dir = get_fonts_dir(&app)?;
 
@@ -167,6 +287,17 @@ pub async fn download_font_file(
     // Write to file
     fs::write(&file_path, &bytes).map_err(|e| format!("Failed to write font file: {}", e))?;
 
+    // Large fonts can be subset for the editor; the original stays for fallback
+    if let Some(options) = subset {
+        if bytes.len() as u64 >= MIN_SUBSET_BYTES {
+            let path = file_path.to_string_lossy().to_string();
+            match subset_font_file(path, Some(options.unicode_ranges)).await {
+                Ok(result) => return Ok(result.subset_path),
+                Err(e) => eprintln!("[FontManager] Subsetting skipped: {}", e),
+            }
+        }
+    }
+
     // Return absolute path
     let absolute_path = file_path
         .to_str()
@@ -176,6 +307,48 @@ pub async fn download_font_file(
     Ok(absolute_path)
 }
 
+/// Subset a font file to Latin plus extra unicode ranges as woff2
+/// Writes `<name>.subset.woff2` next to the original, which is left untouched
+#[tauri::command]
+pub async fn subset_font_file(
+    file_path: String,
+    unicode_ranges: Option<Vec<String>>,
+) -> Result<FontSubsetResult, String> {
+    let source = PathBuf::from(&file_path);
+    if !source.exists() {
+        return Err(format!("Font file not found: {}", file_path));
+    }
+
+    let ranges = subset_unicode_ranges(&unicode_ranges.unwrap_or_default())?;
+    let stem = source
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or("Invalid font file name")?;
+    let subset_path = source.with_file_name(format!("{}.subset.woff2", stem));
+
+    run_font_subset(&source, &subset_path, &ranges).await?;
+
+    let original_size = fs::metadata(&source)
+        .map_err(|e| format!("Failed to read metadata: {}", e))?
+        .len();
+    let subset_size = fs::metadata(&subset_path)
+        .map_err(|e| format!("Failed to read subset metadata: {}", e))?
+        .len();
+
+    println!(
+        "[FontManager] Subset {} ({} -> {} bytes)",
+        file_path, original_size, subset_size
+    );
+
+    Ok(FontSubsetResult {
+        original_path: file_path,
+        subset_path: subset_path.to_string_lossy().to_string(),
+        original_size,
+        subset_size,
+        unicode_ranges: ranges,
+    })
+}
+
 /// Read font file as base64
 #[tauri::command]
 pub async fn read_font_file_base64(file_path: String) -> Result<String, String> {
@@ -0,0 +1,275 @@
+//! Git Archive
+//!
+//! Native equivalent of `git archive`: writes the tree of a revision (or one
+//! of its subdirectories) to a zip, tar or tar.gz file straight from the
+//! object database, so the working tree and index are never touched.
+//! Progress is reported with `git:archive-progress` events.
+
+use super::error::GitError;
+use super::types::{ArchiveFormat, ArchiveProgress, ArchiveResult};
+use chrono::{Datelike, TimeZone, Timelike, Utc};
+use git2::{ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use tauri::Emitter;
+
+const MODE_EXECUTABLE: i32 = 0o100755;
+const MODE_SYMLINK: i32 = 0o120000;
+
+/// Blob in the archived tree
+struct ArchiveEntry {
+    path: String,
+    oid: Oid,
+    mode: i32,
+}
+
+/// Entries under `tree`; submodules are left out as `git archive` does
+fn collect_entries(tree: &git2::Tree) -> Result<Vec<ArchiveEntry>, GitError> {
+    let mut entries = Vec::new();
+    tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+        if entry.kind() == Some(ObjectType::Blob) {
+            entries.push(ArchiveEntry {
+                path: format!("{}{}", dir, entry.name().unwrap_or_default()),
+                oid: entry.id(),
+                mode: entry.filemode(),
+            });
+        }
+        TreeWalkResult::Ok
+    })
+    .map_err(GitError::from)?;
+    Ok(entries)
+}
+
+fn format_for(format: Option<ArchiveFormat>, output_path: &str) -> ArchiveFormat {
+    format.unwrap_or_else(|| {
+        let lower = output_path.to_lowercase();
+        if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+            ArchiveFormat::TarGz
+        } else if lower.ends_with(".tar") {
+            ArchiveFormat::Tar
+        } else {
+            ArchiveFormat::Zip
+        }
+    })
+}
+
+fn io_error(e: std::io::Error) -> GitError {
+    GitError::internal(&format!("Failed to write archive: {}", e))
+}
+
+/// Writes entries in one of the supported formats
+enum ArchiveWriter {
+    Zip(Box<zip::ZipWriter<BufWriter<File>>>),
+    Tar(tar::Builder<BufWriter<File>>),
+    TarGz(tar::Builder<flate2::write::GzEncoder<BufWriter<File>>>),
+}
+
+impl ArchiveWriter {
+    fn create(format: ArchiveFormat, output: &Path) -> Result<Self, GitError> {
+        let file = BufWriter::new(File::create(output).map_err(io_error)?);
+        Ok(match format {
+            ArchiveFormat::Zip => Self::Zip(Box::new(zip::ZipWriter::new(file))),
+            ArchiveFormat::Tar => Self::Tar(tar::Builder::new(file)),
+            ArchiveFormat::TarGz => Self::TarGz(tar::Builder::new(flate2::write::GzEncoder::new(
+                file,
+                flate2::Compression::default(),
+            ))),
+        })
+    }
+
+    fn add(&mut self, path: &str, mode: i32, content: &[u8], mtime: i64) -> Result<(), GitError> {
+        let permissions = if mode == MODE_EXECUTABLE {
+            0o755
+        } else {
+            0o644
+        };
+        match self {
+            Self::Zip(zip) => {
+                let options = zip::write::SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .last_modified_time(zip_time(mtime))
+                    .unix_permissions(permissions);
+                if mode == MODE_SYMLINK {
+                    zip.add_symlink(path, String::from_utf8_lossy(content), options)
+                } else {
+                    zip.start_file(path, options)
+                        .and_then(|_| zip.write_all(content).map_err(Into::into))
+                }
+                .map_err(|e| GitError::internal(&format!("Failed to write archive: {}", e)))
+            }
+            Self::Tar(tar) => append_tar(tar, path, mode, permissions, content, mtime),
+            Self::TarGz(tar) => append_tar(tar, path, mode, permissions, content, mtime),
+        }
+    }
+
+    fn finish(self) -> Result<(), GitError> {
+        match self {
+            Self::Zip(zip) => zip
+                .finish()
+                .map_err(|e| GitError::internal(&format!("Failed to write archive: {}", e)))?
+                .flush()
+                .map_err(io_error),
+            Self::Tar(tar) => tar
+                .into_inner()
+                .map_err(io_error)?
+                .flush()
+                .map_err(io_error),
+            Self::TarGz(tar) => tar
+                .into_inner()
+                .and_then(|gz| gz.finish())
+                .map_err(io_error)?
+                .flush()
+                .map_err(io_error),
+        }
+    }
+}
+
+fn append_tar<W: Write>(
+    tar: &mut tar::Builder<W>,
+    path: &str,
+    mode: i32,
+    permissions: u32,
+    content: &[u8],
+    mtime: i64,
+) -> Result<(), GitError> {
+    let mut header = tar::Header::new_gnu();
+    header.set_mtime(mtime.max(0) as u64);
+    if mode == MODE_SYMLINK {
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_mode(0o777);
+        header.set_size(0);
+        let target = String::from_utf8_lossy(content).to_string();
+        tar.append_link(&mut header, path, target)
+    } else {
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_mode(permissions);
+        header.set_size(content.len() as u64);
+        tar.append_data(&mut header, path, content)
+    }
+    .map_err(io_error)
+}
+
+/// Zip timestamps start in 1980
+fn zip_time(secs: i64) -> zip::DateTime {
+    Utc.timestamp_opt(secs, 0)
+        .single()
+        .and_then(|t| {
+            zip::DateTime::from_date_and_time(
+                u16::try_from(t.year()).ok()?,
+                t.month() as u8,
+                t.day() as u8,
+                t.hour() as u8,
+                t.minute() as u8,
+                t.second() as u8,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+/// Export the tree at `rev` (a commit, branch or tag) to an archive.
+///
+/// `format` defaults to the one implied by `output_path`'s extension.
+/// `subdirectory` limits the archive to one folder of the tree, and every
+/// entry is placed under `prefix` (default `<repo>-<short id>/`; pass an
+/// empty string for none).
+#[tauri::command(async)]
+pub fn git_archive(
+    window: tauri::Window,
+    path: String,
+    rev: String,
+    format: Option<ArchiveFormat>,
+    output_path: String,
+    subdirectory: Option<String>,
+    prefix: Option<String>,
+) -> Result<ArchiveResult, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let commit = repo
+        .revparse_single(&rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|_| GitError::not_found(&format!("Revision '{}' not found", rev)))?;
+    let commit_id = commit.id().to_string();
+    let mtime = commit.time().seconds();
+
+    let root = commit.tree().map_err(GitError::from)?;
+    let subdirectory = subdirectory
+        .map(|s| s.trim_matches('/').to_string())
+        .filter(|s| !s.is_empty());
+    let tree = match &subdirectory {
+        Some(dir) => root
+            .get_path(Path::new(dir))
+            .and_then(|entry| entry.to_object(&repo))
+            .and_then(|object| object.peel_to_tree())
+            .map_err(|_| GitError::not_found(&format!("'{}' is not a folder in {}", dir, rev)))?,
+        None => root,
+    };
+
+    let prefix = prefix.unwrap_or_else(|| {
+        let name = repo
+            .workdir()
+            .unwrap_or_else(|| repo.path())
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "archive".to_string());
+        format!("{}-{}/", name, &commit_id[..7])
+    });
+    let prefix = match prefix.trim_matches('/') {
+        "" => String::new(),
+        trimmed => format!("{}/", trimmed),
+    };
+
+    let entries = collect_entries(&tree)?;
+    let total = entries.len();
+    let format = format_for(format, &output_path);
+    let output = Path::new(&output_path);
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(io_error)?;
+    }
+
+    let result = (|| -> Result<u64, GitError> {
+        let mut writer = ArchiveWriter::create(format, output)?;
+        let mut bytes = 0u64;
+        let mut last_percent = None;
+        for (index, entry) in entries.iter().enumerate() {
+            let blob = repo.find_blob(entry.oid).map_err(GitError::from)?;
+            let name = format!("{}{}", prefix, entry.path);
+            writer.add(&name, entry.mode, blob.content(), mtime)?;
+            bytes += blob.size() as u64;
+
+            let percent = ((index + 1) * 100 / total.max(1)) as u32;
+            if last_percent != Some(percent) {
+                last_percent = Some(percent);
+                let _ = window.emit(
+                    "git:archive-progress",
+                    ArchiveProgress {
+                        output_path: output_path.clone(),
+                        current: index + 1,
+                        total,
+                        path: entry.path.clone(),
+                        percent,
+                    },
+                );
+            }
+        }
+        writer.finish()?;
+        Ok(bytes)
+    })();
+
+    let bytes = match result {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let _ = std::fs::remove_file(output);
+            return Err(e.into());
+        }
+    };
+
+    let archive_bytes = std::fs::metadata(output).map(|m| m.len()).unwrap_or(0);
+    Ok(ArchiveResult {
+        output_path,
+        commit_id,
+        files: total,
+        uncompressed_bytes: bytes,
+        archive_bytes,
+    })
+}
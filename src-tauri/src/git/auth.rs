@@ -1,14 +1,19 @@
 //! Git Authentication
 //!
 //! Provides authentication callbacks for remote Git operations using libgit2.
-//! Supports: SSH keys, SSH agent, system git credentials (osxkeychain, credential-manager-core).
+//! Supports: SSH keys (with passphrase prompting), SSH agent, system git credentials
+//! (osxkeychain, credential-manager-core).
 
+use super::ssh::{discover_ssh_keys, host_from_url, PassphrasePrompt, SshKeyInfo};
 use git2::{Cred, CredentialType, FetchOptions, PushOptions, RemoteCallbacks};
 use std::path::Path;
 use std::process::{Command, Stdio};
 
 pub struct AuthCallbacks;
 
+/// Wrong passphrases accepted per key before moving on to the next one
+const MAX_PASSPHRASE_ATTEMPTS: u32 = 3;
+
 /// Try to get credentials from system git credential helper
 fn get_system_credentials(url: &str) -> Option<(String, String)> {
     // Parse the URL to extract protocol and host
@@ -74,188 +79,140 @@ fn get_system_credentials(url: &str) -> Option<(String, String)> {
     None
 }
 
-impl AuthCallbacks {
-    /// Create remote callbacks with authentication support
-    pub fn create_callbacks<'a>() -> RemoteCallbacks<'a> {
-        let mut callbacks = RemoteCallbacks::new();
-        let tried_ssh = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
-        let tried_agent = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
-        let tried_system = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
-        let cached_creds = std::sync::Arc::new(std::sync::Mutex::new(Option::<(String, String)>::None));
-
-        callbacks.credentials(move |url, username, allowed| {
-            // For SSH URLs, try SSH key and agent
-            if allowed.contains(CredentialType::SSH_KEY) {
-                // Try SSH key files
-                if !tried_ssh.load(std::sync::atomic::Ordering::Relaxed) {
-                    tried_ssh.store(true, std::sync::atomic::Ordering::Relaxed);
-                    
-                    let home = std::env::var("HOME")
-                        .or_else(|_| std::env::var("USERPROFILE"))
-                        .unwrap_or_else(|_| ".".to_string());
+/// Credential attempts made so far during one remote operation
+#[derive(Default)]
+struct CredentialAttempts {
+    /// Keys for the remote host, discovered on first use
+    ssh_keys: Option<Vec<SshKeyInfo>>,
+    next_plain_key: usize,
+    tried_agent: bool,
+    next_encrypted_key: usize,
+    passphrase_attempts: u32,
+    tried_system: bool,
+    cached_creds: Option<(String, String)>,
+}
 
-                    let ssh_dir = Path::new(&home).join(".ssh");
-                    let key_names = ["id_ed25519", "id_rsa", "id_ecdsa"];
+fn ssh_key_cred(username: &str, key: &SshKeyInfo, passphrase: Option<&str>) -> Result<Cred, git2::Error> {
+    Cred::ssh_key(
+        username,
+        key.public_key.as_deref().map(Path::new),
+        Path::new(&key.private_key),
+        passphrase,
+    )
+}
 
-                    for key_name in key_names {
-                        let private_key = ssh_dir.join(key_name);
-                        let public_key = ssh_dir.join(format!("{}.pub", key_name));
+/// Credentials callback shared by all remote operations. libgit2 calls it again
+/// after each rejected credential, so every call moves on to the next method:
+/// unencrypted keys, ssh-agent, passphrase-protected keys, then system git credentials.
+fn credentials_handler(
+    prompt: Option<PassphrasePrompt>,
+) -> impl FnMut(&str, Option<&str>, CredentialType) -> Result<Cred, git2::Error> {
+    let mut attempts = CredentialAttempts::default();
+
+    move |url, username, allowed| {
+        let user = username.unwrap_or("git");
+
+        if allowed.contains(CredentialType::SSH_KEY) {
+            let keys = attempts
+                .ssh_keys
+                .get_or_insert_with(|| discover_ssh_keys(host_from_url(url).as_deref()));
+            let plain: Vec<&SshKeyInfo> = keys.iter().filter(|k| !k.encrypted).collect();
+            let encrypted: Vec<&SshKeyInfo> = keys.iter().filter(|k| k.encrypted).collect();
+
+            if let Some(key) = plain.get(attempts.next_plain_key) {
+                attempts.next_plain_key += 1;
+                return ssh_key_cred(user, key, None);
+            }
 
-                        if private_key.exists() {
-                            if let Ok(cred) = Cred::ssh_key(
-                                username.unwrap_or("git"),
-                                if public_key.exists() { Some(&public_key) } else { None },
-                                &private_key,
-                                None,
-                            ) {
-                                return Ok(cred);
-                            }
-                        }
-                    }
+            if !attempts.tried_agent {
+                attempts.tried_agent = true;
+                if let Ok(cred) = Cred::ssh_key_from_agent(user) {
+                    return Ok(cred);
                 }
+            }
 
-                // Try SSH agent
-                if !tried_agent.load(std::sync::atomic::Ordering::Relaxed) {
-                    tried_agent.store(true, std::sync::atomic::Ordering::Relaxed);
-                    if let Ok(cred) = Cred::ssh_key_from_agent(username.unwrap_or("git")) {
-                        return Ok(cred);
+            if let Some(prompt) = &prompt {
+                while let Some(key) = encrypted.get(attempts.next_encrypted_key) {
+                    if attempts.passphrase_attempts >= MAX_PASSPHRASE_ATTEMPTS {
+                        attempts.next_encrypted_key += 1;
+                        attempts.passphrase_attempts = 0;
+                        continue;
                     }
+                    attempts.passphrase_attempts += 1;
+                    let Some(passphrase) =
+                        prompt.request(Path::new(&key.private_key), attempts.passphrase_attempts)
+                    else {
+                        return Err(git2::Error::from_str("Passphrase entry cancelled"));
+                    };
+                    return ssh_key_cred(user, key, Some(&passphrase));
                 }
             }
+        }
 
-            // For HTTPS URLs, use system git credential helper
-            if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
-                if !tried_system.load(std::sync::atomic::Ordering::Relaxed) {
-                    tried_system.store(true, std::sync::atomic::Ordering::Relaxed);
-                    
-                    // Get credentials from system git
-                    if let Some((user, pass)) = get_system_credentials(url) {
-                        let mut cache = cached_creds.lock().unwrap();
-                        *cache = Some((user.clone(), pass.clone()));
-                        
-                        if let Ok(cred) = Cred::userpass_plaintext(&user, &pass) {
-                            return Ok(cred);
-                        }
-                    }
-                }
-                
-                // Try cached credentials on retry
-                let cache = cached_creds.lock().unwrap();
-                if let Some((ref user, ref pass)) = *cache {
-                    if let Ok(cred) = Cred::userpass_plaintext(user, pass) {
+        // For HTTPS URLs, use system git credential helper
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if !attempts.tried_system {
+                attempts.tried_system = true;
+
+                if let Some((user, pass)) = get_system_credentials(url) {
+                    attempts.cached_creds = Some((user.clone(), pass.clone()));
+
+                    if let Ok(cred) = Cred::userpass_plaintext(&user, &pass) {
                         return Ok(cred);
                     }
                 }
             }
 
-            // For username-only auth
-            if allowed.contains(CredentialType::USERNAME) {
-                return Cred::username(username.unwrap_or("git"));
+            // Try cached credentials on retry
+            if let Some((ref user, ref pass)) = attempts.cached_creds {
+                if let Ok(cred) = Cred::userpass_plaintext(user, pass) {
+                    return Ok(cred);
+                }
             }
+        }
 
-            Err(git2::Error::from_str(
-                "Authentication failed. For HTTPS, ensure credentials are stored in macOS Keychain. For SSH, ensure your key is added to ssh-agent.",
-            ))
-        });
+        // For username-only auth
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(user);
+        }
+
+        Err(git2::Error::from_str(
+            "Authentication failed. For HTTPS, ensure credentials are stored in your system credential helper. For SSH, ensure your key is added to ssh-agent or listed as an IdentityFile in ~/.ssh/config.",
+        ))
+    }
+}
 
+impl AuthCallbacks {
+    /// Create remote callbacks with authentication support
+    pub fn create_callbacks<'a>(prompt: Option<PassphrasePrompt>) -> RemoteCallbacks<'a> {
+        let mut callbacks = RemoteCallbacks::new();
+        callbacks.credentials(credentials_handler(prompt));
         callbacks
     }
 
     /// Create fetch options with authentication callbacks
-    pub fn fetch_options<'a>() -> FetchOptions<'a> {
+    pub fn fetch_options<'a>(prompt: Option<PassphrasePrompt>) -> FetchOptions<'a> {
         let mut opts = FetchOptions::new();
-        opts.remote_callbacks(Self::create_callbacks());
+        opts.remote_callbacks(Self::create_callbacks(prompt));
         opts
     }
 
     /// Create push options with authentication callbacks
-    pub fn push_options<'a>() -> PushOptions<'a> {
+    pub fn push_options<'a>(prompt: Option<PassphrasePrompt>) -> PushOptions<'a> {
         let mut opts = PushOptions::new();
-        opts.remote_callbacks(Self::create_callbacks());
+        opts.remote_callbacks(Self::create_callbacks(prompt));
         opts
     }
 
     /// Create fetch options with authentication AND progress callback for clone
-    pub fn fetch_options_with_progress<'a, F>(progress_cb: F) -> FetchOptions<'a>
+    pub fn fetch_options_with_progress<'a, F>(
+        prompt: Option<PassphrasePrompt>,
+        progress_cb: F,
+    ) -> FetchOptions<'a>
     where
         F: FnMut(git2::Progress<'_>) -> bool + 'a,
     {
-        let mut callbacks = RemoteCallbacks::new();
-        let tried_ssh = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
-        let tried_agent = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
-        let tried_system = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
-        let cached_creds = std::sync::Arc::new(std::sync::Mutex::new(Option::<(String, String)>::None));
-
-        // Add authentication callbacks
-        callbacks.credentials(move |url, username, allowed| {
-            // For SSH URLs
-            if allowed.contains(CredentialType::SSH_KEY) {
-                if !tried_ssh.load(std::sync::atomic::Ordering::Relaxed) {
-                    tried_ssh.store(true, std::sync::atomic::Ordering::Relaxed);
-                    
-                    let home = std::env::var("HOME")
-                        .or_else(|_| std::env::var("USERPROFILE"))
-                        .unwrap_or_else(|_| ".".to_string());
-
-                    let ssh_dir = Path::new(&home).join(".ssh");
-                    let key_names = ["id_ed25519", "id_rsa", "id_ecdsa"];
-
-                    for key_name in key_names {
-                        let private_key = ssh_dir.join(key_name);
-                        let public_key = ssh_dir.join(format!("{}.pub", key_name));
-
-                        if private_key.exists() {
-                            if let Ok(cred) = Cred::ssh_key(
-                                username.unwrap_or("git"),
-                                if public_key.exists() { Some(&public_key) } else { None },
-                                &private_key,
-                                None,
-                            ) {
-                                return Ok(cred);
-                            }
-                        }
-                    }
-                }
-
-                if !tried_agent.load(std::sync::atomic::Ordering::Relaxed) {
-                    tried_agent.store(true, std::sync::atomic::Ordering::Relaxed);
-                    if let Ok(cred) = Cred::ssh_key_from_agent(username.unwrap_or("git")) {
-                        return Ok(cred);
-                    }
-                }
-            }
-
-            // For HTTPS - use system git credential
-            if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
-                if !tried_system.load(std::sync::atomic::Ordering::Relaxed) {
-                    tried_system.store(true, std::sync::atomic::Ordering::Relaxed);
-                    
-                    if let Some((user, pass)) = get_system_credentials(url) {
-                        let mut cache = cached_creds.lock().unwrap();
-                        *cache = Some((user.clone(), pass.clone()));
-                        
-                        if let Ok(cred) = Cred::userpass_plaintext(&user, &pass) {
-                            return Ok(cred);
-                        }
-                    }
-                }
-                
-                let cache = cached_creds.lock().unwrap();
-                if let Some((ref user, ref pass)) = *cache {
-                    if let Ok(cred) = Cred::userpass_plaintext(user, pass) {
-                        return Ok(cred);
-                    }
-                }
-            }
-
-            if allowed.contains(CredentialType::USERNAME) {
-                return Cred::username(username.unwrap_or("git"));
-            }
-
-            Err(git2::Error::from_str("No valid authentication method available"))
-        });
-
-        // Add progress callback
+        let mut callbacks = Self::create_callbacks(prompt);
         callbacks.transfer_progress(progress_cb);
 
         let mut opts = FetchOptions::new();
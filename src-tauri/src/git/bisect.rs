@@ -0,0 +1,370 @@
+//! Git Bisect
+//!
+//! libgit2 has no bisect, so the search is done here. State is kept where the
+//! git CLI keeps it (`BISECT_START`, `BISECT_LOG` and `refs/bisect/*`), so a
+//! session started in the IDE can be continued or reset from a terminal and
+//! vice versa.
+
+use super::error::GitError;
+use super::history::commit_info;
+use super::types::BisectStatus;
+use git2::{Oid, Repository, Sort, StatusOptions};
+use std::collections::HashMap;
+use std::fs;
+
+const BAD_REF: &str = "refs/bisect/bad";
+const GOOD_PREFIX: &str = "refs/bisect/good-";
+const SKIP_PREFIX: &str = "refs/bisect/skip-";
+
+/// Above this many candidates the midpoint is picked by topological order
+/// instead of exact ancestor counts
+const MAX_EXACT_CANDIDATES: usize = 4096;
+
+fn is_bisecting(repo: &Repository) -> bool {
+    repo.path().join("BISECT_START").exists()
+}
+
+fn bisect_refs(repo: &Repository, prefix: &str) -> Result<Vec<Oid>, GitError> {
+    let mut oids = Vec::new();
+    for reference in repo
+        .references_glob(&format!("{}*", prefix))
+        .map_err(GitError::from)?
+    {
+        if let Some(oid) = reference.map_err(GitError::from)?.target() {
+            oids.push(oid);
+        }
+    }
+    Ok(oids)
+}
+
+fn resolve_commit(repo: &Repository, rev: Option<&str>) -> Result<Oid, GitError> {
+    let rev = rev.unwrap_or("HEAD");
+    repo.revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map(|commit| commit.id())
+        .map_err(|_| GitError::not_found(&format!("Revision '{}' not found", rev)))
+}
+
+fn append_log(repo: &Repository, term: &str, oid: Oid) {
+    use std::io::Write;
+    let summary = repo
+        .find_commit(oid)
+        .ok()
+        .and_then(|c| c.summary().map(|s| s.to_string()))
+        .unwrap_or_default();
+    if let Ok(mut log) = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(repo.path().join("BISECT_LOG"))
+    {
+        let _ = writeln!(log, "# {}: [{}] {}", term, oid, summary);
+        let _ = writeln!(log, "git bisect {} {}", term, oid);
+    }
+}
+
+fn mark(repo: &Repository, term: &str, oid: Oid) -> Result<(), GitError> {
+    let name = match term {
+        "bad" => BAD_REF.to_string(),
+        "good" => format!("{}{}", GOOD_PREFIX, oid),
+        "skip" => format!("{}{}", SKIP_PREFIX, oid),
+        other => {
+            return Err(GitError::internal(&format!(
+                "Invalid bisect mark: {}. Use good, bad, or skip.",
+                other
+            )))
+        }
+    };
+    repo.reference(&name, oid, true, &format!("bisect: {}", term))
+        .map_err(GitError::from)?;
+    append_log(repo, term, oid);
+    Ok(())
+}
+
+/// Result of one bisection step
+enum Step {
+    /// Not enough marks yet
+    NeedsMarks,
+    Test {
+        next: Oid,
+        remaining: usize,
+    },
+    Found(Oid),
+    /// Only skipped commits are left between good and bad
+    OnlySkipped(Vec<Oid>),
+}
+
+/// Pick the candidate that splits the remaining commits most evenly
+fn next_step(repo: &Repository) -> Result<Step, GitError> {
+    let bad = match repo.refname_to_id(BAD_REF) {
+        Ok(oid) => oid,
+        Err(_) => return Ok(Step::NeedsMarks),
+    };
+    let goods = bisect_refs(repo, GOOD_PREFIX)?;
+    if goods.is_empty() {
+        return Ok(Step::NeedsMarks);
+    }
+    let skipped = bisect_refs(repo, SKIP_PREFIX)?;
+
+    // Commits that may have introduced the change, parents first
+    let mut revwalk = repo.revwalk().map_err(GitError::from)?;
+    revwalk
+        .set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+        .map_err(GitError::from)?;
+    revwalk.push(bad).map_err(GitError::from)?;
+    for good in &goods {
+        revwalk.hide(*good).map_err(GitError::from)?;
+    }
+    let candidates: Vec<Oid> = revwalk.collect::<Result<_, _>>().map_err(GitError::from)?;
+    if candidates.is_empty() {
+        return Err(GitError::conflict(
+            "The bad commit is an ancestor of a good commit",
+        ));
+    }
+
+    let testable: Vec<usize> = (0..candidates.len())
+        .filter(|&i| candidates[i] != bad && !skipped.contains(&candidates[i]))
+        .collect();
+    if testable.is_empty() {
+        let left: Vec<Oid> = candidates
+            .iter()
+            .copied()
+            .filter(|oid| *oid != bad)
+            .collect();
+        return Ok(if left.is_empty() {
+            Step::Found(bad)
+        } else {
+            Step::OnlySkipped(left.into_iter().chain([bad]).collect())
+        });
+    }
+
+    let total = candidates.len();
+    let best = if total <= MAX_EXACT_CANDIDATES {
+        // ancestors[i]: bitset of the candidates reachable from candidate i
+        let index: HashMap<Oid, usize> = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, oid)| (*oid, i))
+            .collect();
+        let words = total.div_ceil(64);
+        let mut ancestors: Vec<Vec<u64>> = Vec::with_capacity(total);
+        for (i, oid) in candidates.iter().enumerate() {
+            let mut bits = vec![0u64; words];
+            bits[i / 64] |= 1 << (i % 64);
+            let commit = repo.find_commit(*oid).map_err(GitError::from)?;
+            for parent in commit.parent_ids() {
+                if let Some(&p) = index.get(&parent) {
+                    for (word, parent_word) in bits.iter_mut().zip(&ancestors[p]) {
+                        *word |= parent_word;
+                    }
+                }
+            }
+            ancestors.push(bits);
+        }
+        testable
+            .iter()
+            .copied()
+            .max_by_key(|&i| {
+                let count: usize = ancestors[i].iter().map(|w| w.count_ones() as usize).sum();
+                count.min(total - count)
+            })
+            .expect("testable is not empty")
+    } else {
+        let middle = total / 2;
+        testable
+            .iter()
+            .copied()
+            .min_by_key(|&i| i.abs_diff(middle))
+            .expect("testable is not empty")
+    };
+
+    Ok(Step::Test {
+        next: candidates[best],
+        remaining: total,
+    })
+}
+
+fn checkout_detached(repo: &Repository, oid: Oid) -> Result<(), GitError> {
+    let commit = repo.find_commit(oid).map_err(GitError::from)?;
+    let mut checkout_opts = git2::build::CheckoutBuilder::new();
+    checkout_opts.safe();
+    repo.checkout_tree(commit.as_object(), Some(&mut checkout_opts))
+        .map_err(GitError::from)?;
+    repo.set_head_detached(oid).map_err(GitError::from)
+}
+
+/// Current state; `step` is computed by the caller when it already has one
+fn status(repo: &Repository, step: Option<Step>) -> Result<BisectStatus, GitError> {
+    let active = is_bisecting(repo);
+    let step = match step {
+        Some(step) => step,
+        None if active => next_step(repo)?,
+        None => Step::NeedsMarks,
+    };
+    let info = |oid: Oid| repo.find_commit(oid).ok().map(|c| commit_info(&c));
+    let hex = |oids: Vec<Oid>| oids.iter().map(Oid::to_string).collect::<Vec<_>>();
+
+    let mut result = BisectStatus {
+        active,
+        original_head: fs::read_to_string(repo.path().join("BISECT_START"))
+            .ok()
+            .map(|s| s.trim().to_string()),
+        bad: repo.refname_to_id(BAD_REF).ok().map(|oid| oid.to_string()),
+        good: hex(bisect_refs(repo, GOOD_PREFIX)?),
+        skipped: hex(bisect_refs(repo, SKIP_PREFIX)?),
+        current: repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .and_then(info),
+        next: None,
+        remaining: 0,
+        steps_left: 0,
+        first_bad: None,
+        candidates: Vec::new(),
+    };
+    match step {
+        Step::NeedsMarks => {}
+        Step::Test { next, remaining } => {
+            result.next = info(next);
+            result.remaining = remaining;
+            result.steps_left = usize::BITS - remaining.leading_zeros();
+        }
+        Step::Found(oid) => result.first_bad = info(oid),
+        Step::OnlySkipped(oids) => result.candidates = hex(oids),
+    }
+    Ok(result)
+}
+
+/// Move to the next commit to test, if there is one
+fn advance(repo: &Repository) -> Result<BisectStatus, GitError> {
+    let step = next_step(repo)?;
+    if let Step::Test { next, .. } = step {
+        checkout_detached(repo, next)?;
+    }
+    status(repo, Some(step))
+}
+
+/// Start bisecting. `bad` defaults to HEAD; `good` can also be marked later.
+#[tauri::command]
+pub fn git_bisect_start(
+    path: String,
+    bad: Option<String>,
+    good: Option<String>,
+) -> Result<BisectStatus, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    if is_bisecting(&repo) {
+        return Err("A bisect is already in progress. Reset it first.".to_string());
+    }
+
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false).include_ignored(false);
+    let dirty = repo
+        .statuses(Some(&mut opts))
+        .map_err(GitError::from)?
+        .iter()
+        .any(|entry| !entry.status().is_empty());
+    if dirty {
+        return Err("Commit or stash your changes before bisecting".to_string());
+    }
+
+    let bad = resolve_commit(&repo, bad.as_deref())?;
+    let good = good
+        .as_deref()
+        .map(|rev| resolve_commit(&repo, Some(rev)))
+        .transpose()?;
+
+    // Where to return on reset: the branch name, or the commit when detached
+    let head = repo.head().map_err(GitError::from)?;
+    let original = match head.shorthand() {
+        Some(name) if head.is_branch() => name.to_string(),
+        _ => head
+            .target()
+            .map(|oid| oid.to_string())
+            .ok_or_else(|| GitError::not_found("HEAD does not point to a commit"))?,
+    };
+    fs::write(repo.path().join("BISECT_START"), format!("{}\n", original))
+        .map_err(|e| format!("Failed to start bisect: {}", e))?;
+    fs::write(repo.path().join("BISECT_TERMS"), "bad\ngood\n")
+        .map_err(|e| format!("Failed to start bisect: {}", e))?;
+    let _ = fs::write(repo.path().join("BISECT_LOG"), "git bisect start\n");
+
+    mark(&repo, "bad", bad)?;
+    if let Some(good) = good {
+        mark(&repo, "good", good)?;
+    }
+    Ok(advance(&repo)?)
+}
+
+/// Mark a commit (default: the one checked out) as good, bad or skip and
+/// check out the next one to test
+#[tauri::command]
+pub fn git_bisect_mark(
+    path: String,
+    term: String,
+    rev: Option<String>,
+) -> Result<BisectStatus, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    if !is_bisecting(&repo) {
+        return Err("No bisect in progress".to_string());
+    }
+    let oid = resolve_commit(&repo, rev.as_deref())?;
+    mark(&repo, &term.to_lowercase(), oid)?;
+    Ok(advance(&repo)?)
+}
+
+/// Bisect state and the next commit to test
+#[tauri::command]
+pub fn git_bisect_status(path: String) -> Result<BisectStatus, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    Ok(status(&repo, None)?)
+}
+
+/// End the bisect and return to the branch (or commit) it started from
+#[tauri::command]
+pub fn git_bisect_reset(path: String) -> Result<String, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let original = fs::read_to_string(repo.path().join("BISECT_START"))
+        .map_err(|_| "No bisect in progress".to_string())?
+        .trim()
+        .to_string();
+
+    let branch_ref = format!("refs/heads/{}", original);
+    let mut checkout_opts = git2::build::CheckoutBuilder::new();
+    checkout_opts.safe();
+    if let Ok(reference) = repo.find_reference(&branch_ref) {
+        let commit = reference.peel_to_commit().map_err(GitError::from)?;
+        repo.checkout_tree(commit.as_object(), Some(&mut checkout_opts))
+            .map_err(GitError::from)?;
+        repo.set_head(&branch_ref).map_err(GitError::from)?;
+    } else {
+        checkout_detached(&repo, resolve_commit(&repo, Some(&original))?)?;
+    }
+
+    let mut refs = Vec::new();
+    for reference in repo
+        .references_glob("refs/bisect/*")
+        .map_err(GitError::from)?
+        .flatten()
+    {
+        if let Some(name) = reference.name() {
+            refs.push(name.to_string());
+        }
+    }
+    for name in refs {
+        if let Ok(mut reference) = repo.find_reference(&name) {
+            let _ = reference.delete();
+        }
+    }
+    for file in [
+        "BISECT_START",
+        "BISECT_TERMS",
+        "BISECT_LOG",
+        "BISECT_EXPECTED_REV",
+        "BISECT_ANCESTORS_OK",
+        "BISECT_NAMES",
+    ] {
+        let _ = fs::remove_file(repo.path().join(file));
+    }
+
+    Ok(format!("Bisect reset, back on {}", original))
+}
@@ -3,12 +3,18 @@
 //! Native libgit2 implementation for branch management.
 
 use super::error::GitError;
+use super::repositories::GitRepoRegistry;
 use super::types::BranchInfo;
 use git2::{BranchType, Repository};
+use tauri::State;
 
 /// List all branches
 #[tauri::command]
-pub fn git_branches(path: String) -> Result<Vec<BranchInfo>, String> {
+pub fn git_branches(
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+) -> Result<Vec<BranchInfo>, String> {
+    let path = registry.resolve(path);
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
     let branches = repo.branches(None).map_err(|e| GitError::from(e))?;
 
@@ -52,7 +58,11 @@ pub fn git_branches(path: String) -> Result<Vec<BranchInfo>, String> {
 
 /// Get current branch name
 #[tauri::command]
-pub fn git_get_current_branch(path: String) -> Result<String, String> {
+pub fn git_get_current_branch(
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+) -> Result<String, String> {
+    let path = registry.resolve(path);
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
     let head = repo.head().map_err(|e| GitError::from(e))?;
 
@@ -66,7 +76,12 @@ pub fn git_get_current_branch(path: String) -> Result<String, String> {
 
 /// Create a new branch
 #[tauri::command]
-pub fn git_create_branch(path: String, branch_name: String) -> Result<String, String> {
+pub fn git_create_branch(
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+    branch_name: String,
+) -> Result<String, String> {
+    let path = registry.resolve(path);
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
     let head = repo.head().map_err(|e| GitError::from(e))?;
     let commit = head.peel_to_commit().map_err(|e| GitError::from(e))?;
@@ -80,10 +95,12 @@ pub fn git_create_branch(path: String, branch_name: String) -> Result<String, St
 /// Delete a branch
 #[tauri::command]
 pub fn git_delete_branch(
+    registry: State<'_, GitRepoRegistry>,
     path: String,
     branch_name: String,
     _force: Option<bool>,
 ) -> Result<String, String> {
+    let path = registry.resolve(path);
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
     let mut branch = repo
         .find_branch(&branch_name, BranchType::Local)
@@ -100,7 +117,12 @@ pub fn git_delete_branch(
 
 /// Checkout/switch to a branch
 #[tauri::command]
-pub fn git_checkout_branch(path: String, branch_name: String) -> Result<String, String> {
+pub fn git_checkout_branch(
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+    branch_name: String,
+) -> Result<String, String> {
+    let path = registry.resolve(path);
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
 
     let branch = repo
@@ -125,10 +147,12 @@ pub fn git_checkout_branch(path: String, branch_name: String) -> Result<String,
 /// Rename a branch
 #[tauri::command]
 pub fn git_rename_branch(
+    registry: State<'_, GitRepoRegistry>,
     path: String,
     old_name: String,
     new_name: String,
 ) -> Result<String, String> {
+    let path = registry.resolve(path);
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
     let mut branch = repo
         .find_branch(&old_name, BranchType::Local)
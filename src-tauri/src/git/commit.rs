@@ -3,17 +3,22 @@
 //! Native libgit2 implementation for commit, amend, reset, and revert.
 
 use super::error::GitError;
+use super::trailers;
 use git2::Repository;
 
 /// Create a commit
 /// If stage_all is true, stages all tracked modified files AND untracked files before committing
+/// `sign_off` and `co_authors` (`Name <email>`) append the matching trailers
 #[tauri::command]
 pub fn git_commit(
     path: String,
     message: String,
     stage_all: Option<bool>,
+    sign_off: Option<bool>,
+    co_authors: Option<Vec<String>>,
 ) -> Result<String, String> {
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
+    let co_author_trailers = trailers::co_author_trailers(&co_authors.unwrap_or_default())?;
 
     // Stage all files if requested (including untracked files)
     if stage_all.unwrap_or(false) {
@@ -68,9 +73,27 @@ pub fn git_commit(
         println!("[GitCommit] Total staged: {} files", staged_count);
     }
 
+    // Refuse to commit secrets when configured to
+    super::secrets::pre_commit_check(&repo)?;
+
     // Get the signature from git config
     let sig = repo.signature().map_err(|e| GitError::from(e))?;
 
+    let mut message_trailers = Vec::new();
+    if sign_off.unwrap_or(false) {
+        message_trailers.push(format!(
+            "Signed-off-by: {} <{}>",
+            sig.name().unwrap_or_default(),
+            sig.email().unwrap_or_default()
+        ));
+    }
+    message_trailers.extend(co_author_trailers);
+    let message = if message_trailers.is_empty() {
+        message
+    } else {
+        trailers::append_trailers(&message, &message_trailers)
+    };
+
     // Re-read the index to get the updated tree
     let mut index = repo.index().map_err(|e| GitError::from(e))?;
     let tree_id = index.write_tree().map_err(|e| GitError::from(e))?;
@@ -105,6 +128,9 @@ pub fn git_amend_commit(path: String, message: Option<String>) -> Result<String,
 
     let sig = repo.signature().map_err(|e| GitError::from(e))?;
 
+    // Refuse to commit secrets when configured to
+    super::secrets::pre_commit_check(&repo)?;
+
     // Get the new tree from index
     let mut index = repo.index().map_err(|e| GitError::from(e))?;
     let tree_id = index.write_tree().map_err(|e| GitError::from(e))?;
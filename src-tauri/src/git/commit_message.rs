@@ -0,0 +1,343 @@
+//! Commit Message Generation
+//!
+//! Summarizes the staged changes (HEAD vs index) and asks the configured AI
+//! provider for a Conventional Commits message, without going through a chat
+//! session. The diff sent to the provider is size-capped: small files are
+//! included in full, the rest are listed with their line counts, and lines
+//! that look like secrets are replaced before anything leaves the machine.
+
+use super::error::GitError;
+use super::types::GeneratedCommitMessage;
+use crate::provider_client::{self, HTTP_CLIENT};
+use git2::{DiffFindOptions, DiffOptions, Patch, Repository};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Duration;
+
+/// Total diff text sent to the provider
+const MAX_DIFF_BYTES: usize = 24 * 1024;
+
+/// Patch lines included per file before it is only summarized
+const MAX_LINES_PER_FILE: usize = 200;
+
+/// Recent subjects included as a style reference
+const RECENT_SUBJECTS: usize = 10;
+
+const MAX_SUBJECT_LENGTH: usize = 72;
+
+const DEFAULT_PROVIDER: &str = "gemini";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Generated files whose diffs say nothing about intent
+const SUMMARY_ONLY_FILES: [&str; 6] = [
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "yarn.lock",
+    "Cargo.lock",
+    "bun.lockb",
+    "go.sum",
+];
+
+const SYSTEM_PROMPT: &str = "You write git commit messages following the Conventional Commits \
+specification. Reply with the commit message only: a subject line `type(optional scope): summary` \
+in the imperative mood, at most 72 characters, using one of feat, fix, docs, style, refactor, perf, \
+test, build, ci, chore or revert; then, only if the change needs explaining, a blank line and a \
+short body wrapped at 72 characters. No code fences, quotes or commentary.";
+
+static CONVENTIONAL_SUBJECT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(feat|fix|docs|style|refactor|perf|test|build|ci|chore|revert)(\([^)]+\))?!?: \S")
+        .expect("invalid conventional commit pattern")
+});
+
+/// Staged changes condensed into a prompt
+struct StagedSummary {
+    files_changed: usize,
+    truncated: bool,
+    prompt: String,
+}
+
+/// Patch text with lines that look like secrets replaced
+fn redacted_patch(patch: &mut Patch) -> Option<(String, usize)> {
+    let buf = patch.to_buf().ok()?;
+    let text = String::from_utf8_lossy(&buf);
+    let lines = text.lines().count();
+    let redacted = text
+        .lines()
+        .map(|line| {
+            if crate::secret_scanner::secret_spans(line).is_empty() {
+                line.to_string()
+            } else {
+                format!("{}[redacted secret]", line.chars().next().unwrap_or(' '))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some((redacted, lines))
+}
+
+fn summarize_staged(repo: &Repository) -> Result<StagedSummary, GitError> {
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let index = repo.index()?;
+    let mut opts = DiffOptions::new();
+    opts.context_lines(2);
+    let mut diff = repo.diff_tree_to_index(head_tree.as_ref(), Some(&index), Some(&mut opts))?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+    let deltas = diff.deltas().len();
+    if deltas == 0 {
+        return Err(GitError::not_found("No staged changes"));
+    }
+
+    let mut overview = Vec::with_capacity(deltas);
+    let mut patches = String::new();
+    let mut truncated = false;
+    for index in 0..deltas {
+        let Some(mut patch) = Patch::from_diff(&diff, index)? else {
+            continue;
+        };
+        let delta = patch.delta();
+        let path = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+        let status = super::history::delta_status_code(delta.status());
+        let binary = delta.new_file().is_binary() || delta.old_file().is_binary();
+        let (_, additions, deletions) = patch.line_stats()?;
+        overview.push(format!(
+            "{} {} (+{} -{})",
+            status, path, additions, deletions
+        ));
+
+        let file_name = Path::new(&path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if binary || SUMMARY_ONLY_FILES.contains(&file_name.as_str()) {
+            continue;
+        }
+        match redacted_patch(&mut patch) {
+            Some((text, lines))
+                if lines <= MAX_LINES_PER_FILE && patches.len() + text.len() <= MAX_DIFF_BYTES =>
+            {
+                patches.push_str(&text);
+                patches.push('\n');
+            }
+            _ => truncated = true,
+        }
+    }
+
+    let mut recent = Vec::new();
+    if let Ok(mut revwalk) = repo.revwalk() {
+        if revwalk.push_head().is_ok() {
+            recent = revwalk
+                .filter_map(|oid| repo.find_commit(oid.ok()?).ok())
+                .filter_map(|commit| commit.summary().map(str::to_string))
+                .take(RECENT_SUBJECTS)
+                .collect();
+        }
+    }
+
+    let mut prompt = format!("Staged files:\n{}\n", overview.join("\n"));
+    if !recent.is_empty() {
+        prompt.push_str(&format!(
+            "\nRecent commit subjects in this repository (match their language and scope naming):\n{}\n",
+            recent.join("\n")
+        ));
+    }
+    if !patches.is_empty() {
+        prompt.push_str(&format!("\nDiff:\n{}", patches));
+    }
+    if truncated {
+        prompt.push_str("\n(Some diffs were omitted for size; use the file list above.)\n");
+    }
+    Ok(StagedSummary {
+        files_changed: overview.len(),
+        truncated,
+        prompt,
+    })
+}
+
+fn default_model(provider: &str) -> &'static str {
+    match provider {
+        "openai" => "gpt-4o-mini",
+        "groq" => "llama-3.3-70b-versatile",
+        "cerebras" => "zai-glm-4.6",
+        "anthropic" => "claude-3-5-haiku-latest",
+        _ => "gemini-2.5-flash-lite",
+    }
+}
+
+/// Run a single-turn completion against a provider
+async fn complete(
+    provider: &str,
+    model: &str,
+    api_key: &str,
+    prompt: &str,
+) -> Result<String, String> {
+    let send = |request: reqwest::RequestBuilder| {
+        provider_client::send(request.timeout(REQUEST_TIMEOUT), provider)
+    };
+    let text = if let Some(endpoint) = provider_client::chat_completions_endpoint(provider) {
+        let body = json!({
+            "model": model,
+            "temperature": 0.2,
+            "messages": [
+                { "role": "system", "content": SYSTEM_PROMPT },
+                { "role": "user", "content": prompt },
+            ],
+        });
+        let response = send(HTTP_CLIENT.post(endpoint).bearer_auth(api_key).json(&body)).await?;
+        response
+            .pointer("/choices/0/message/content")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    } else if provider == "anthropic" {
+        let body = json!({
+            "model": model,
+            "max_tokens": 512,
+            "system": SYSTEM_PROMPT,
+            "messages": [{ "role": "user", "content": prompt }],
+        });
+        let request = HTTP_CLIENT
+            .post("https://api.anthropic.com/v1/messages")
+            .header("x-api-key", api_key)
+            .header("anthropic-version", "2023-06-01")
+            .json(&body);
+        let response = send(request).await?;
+        response
+            .pointer("/content/0/text")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    } else if provider == "gemini" || provider == "google" {
+        let url = format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent",
+            model
+        );
+        let body = json!({
+            "systemInstruction": { "parts": [{ "text": SYSTEM_PROMPT }] },
+            "contents": [{ "role": "user", "parts": [{ "text": prompt }] }],
+            "generationConfig": { "temperature": 0.2 },
+        });
+        let request = HTTP_CLIENT
+            .post(url)
+            .header("x-goog-api-key", api_key)
+            .json(&body);
+        let response = send(request).await?;
+        response
+            .pointer("/candidates/0/content/parts/0/text")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    } else {
+        return Err(format!(
+            "Unsupported provider for commit messages: {}",
+            provider
+        ));
+    };
+    text.filter(|t| !t.trim().is_empty())
+        .ok_or_else(|| format!("{} returned an empty message", provider))
+}
+
+/// Normalize a model reply into a Conventional Commits message
+fn normalize_message(raw: &str) -> (String, Option<String>) {
+    let cleaned: Vec<&str> = raw
+        .trim()
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect();
+    let mut lines = cleaned
+        .iter()
+        .map(|l| l.trim_end())
+        .skip_while(|l| l.trim().is_empty());
+
+    let first = lines.next().unwrap_or_default().trim();
+    let first = first.trim_matches(|c| c == '"' || c == '\'' || c == '`');
+    let mut subject = if CONVENTIONAL_SUBJECT.is_match(first) {
+        first.to_string()
+    } else {
+        format!("chore: {}", first)
+    };
+    if subject.chars().count() > MAX_SUBJECT_LENGTH {
+        subject = subject.chars().take(MAX_SUBJECT_LENGTH).collect::<String>();
+        subject = subject.trim_end().to_string();
+    }
+    let subject = subject.trim_end_matches('.').to_string();
+
+    let body = lines
+        .skip_while(|l| l.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string();
+    (subject, (!body.is_empty()).then_some(body))
+}
+
+/// Generate a commit message for the staged changes
+///
+/// The provider and model default to the `git.commitMessage.provider` and
+/// `git.commitMessage.model` settings, then to the workspace agent config.
+#[tauri::command]
+pub async fn git_generate_commit_message(
+    path: String,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<GeneratedCommitMessage, String> {
+    let repo_path = path.clone();
+    let summary = tokio::task::spawn_blocking(move || -> Result<StagedSummary, String> {
+        let repo = Repository::open(&repo_path).map_err(GitError::from)?;
+        Ok(summarize_staged(&repo)?)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let setting = |key: &str| {
+        crate::configuration_manager::resolve_setting(Some(&path), key)
+            .and_then(|v| v.as_str().map(str::to_string))
+            .filter(|v| !v.is_empty())
+    };
+    let agent_config = crate::agent_config::load(Path::new(&path));
+    let provider = provider
+        .or_else(|| setting("git.commitMessage.provider"))
+        .or_else(|| agent_config.default_provider.clone())
+        .unwrap_or_else(|| DEFAULT_PROVIDER.to_string());
+    // The agent's default model only applies to the agent's default provider
+    let agent_model = agent_config
+        .default_model
+        .filter(|_| agent_config.default_provider.as_deref() == Some(provider.as_str()));
+    let model = model
+        .or_else(|| setting("git.commitMessage.model"))
+        .or(agent_model)
+        .unwrap_or_else(|| default_model(&provider).to_string());
+    let api_key = provider_client::api_key(&provider)?;
+
+    let prompt = crate::agent_redaction::redact_for_run(
+        "git:commit-message",
+        &summary.prompt,
+        Some(&path),
+        crate::agent_redaction::RedactionDirection::Prompt,
+    );
+    let raw = complete(&provider, &model, &api_key, &prompt).await?;
+    let (subject, body) = normalize_message(&raw);
+    let message = match &body {
+        Some(body) => format!("{}\n\n{}", subject, body),
+        None => subject.clone(),
+    };
+
+    println!(
+        "[Git] Generated commit message for {} staged file(s) with {}/{}",
+        summary.files_changed, provider, model
+    );
+    Ok(GeneratedCommitMessage {
+        message,
+        subject,
+        body,
+        provider,
+        model,
+        files_changed: summary.files_changed,
+        truncated: summary.truncated,
+    })
+}
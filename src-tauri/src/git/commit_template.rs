@@ -0,0 +1,394 @@
+//! Commit Message Templates and Validation
+//!
+//! Reads the template the commit box should start from (`commit.template`,
+//! falling back to a `.gitmessage` file in the repository or home folder) and
+//! checks messages against the `git.commitValidation` rules — Conventional
+//! Commits format, subject and body lengths, trailing whitespace — returning
+//! violations with positions so the editor can underline them inline.
+//!
+//! Like git's default cleanup, comment lines and everything below the
+//! scissors line are ignored. Messages git writes itself (merges, reverts,
+//! `fixup!`/`squash!`) are only checked for whitespace.
+
+use super::error::GitError;
+use super::types::{
+    CommitMessageValidation, CommitMessageViolation, CommitTemplate, CommitValidationRules,
+};
+use git2::Repository;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+const GITMESSAGE_FILE: &str = ".gitmessage";
+
+const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+static CONVENTIONAL_HEADER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(?P<type>[A-Za-z]+)(?:\((?P<scope>[^()]*)\))?(?P<breaking>!)?: (?P<description>.*)$",
+    )
+    .expect("invalid conventional header pattern")
+});
+
+/// Subjects of messages git generates
+const GENERATED_PREFIXES: [&str; 5] = ["Merge ", "Revert \"", "fixup! ", "squash! ", "amend! "];
+
+fn comment_char(repo: &Repository) -> char {
+    repo.config()
+        .and_then(|config| config.get_string("core.commentChar"))
+        .ok()
+        .and_then(|value| value.chars().next())
+        .filter(|c| !c.is_whitespace())
+        .unwrap_or('#')
+}
+
+/// Lines git keeps after cleanup, with their 1-based line numbers
+fn message_lines(message: &str, comment: char) -> Vec<(usize, &str)> {
+    let mut lines = Vec::new();
+    for (index, line) in message.lines().enumerate() {
+        if line.starts_with(comment) {
+            if line.contains(SCISSORS) {
+                break;
+            }
+            continue;
+        }
+        lines.push((index + 1, line));
+    }
+    // Leading and trailing blank lines are stripped
+    let first = lines.iter().position(|(_, l)| !l.trim().is_empty());
+    let last = lines.iter().rposition(|(_, l)| !l.trim().is_empty());
+    match (first, last) {
+        (Some(first), Some(last)) => lines[first..=last].to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+fn strip_comments(content: &str, comment: char) -> String {
+    let mut kept: Vec<&str> = Vec::new();
+    for line in content.lines() {
+        if line.starts_with(comment) {
+            if line.contains(SCISSORS) {
+                break;
+            }
+            continue;
+        }
+        kept.push(line);
+    }
+    let text = kept.join("\n");
+    let trimmed = text.trim_matches('\n');
+    if trimmed.trim().is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", trimmed.trim_end())
+    }
+}
+
+/// `commit.template` paths are relative to the working tree, `~` is the home folder
+fn resolve_template_path(repo: &Repository, value: &str) -> PathBuf {
+    if let Some(rest) = value.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    let path = Path::new(value);
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    repo.workdir()
+        .map(|workdir| workdir.join(path))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+fn read_template(path: PathBuf, source: &str, comment: char) -> Option<CommitTemplate> {
+    let content = std::fs::read_to_string(&path).ok()?;
+    Some(CommitTemplate {
+        path: path.to_string_lossy().to_string(),
+        source: source.to_string(),
+        message: strip_comments(&content, comment),
+        content,
+    })
+}
+
+/// The commit message template for a repository, if one is configured
+#[tauri::command]
+pub fn git_get_commit_template(path: String) -> Result<Option<CommitTemplate>, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let comment = comment_char(&repo);
+
+    let configured = repo
+        .config()
+        .and_then(|config| config.get_string("commit.template"))
+        .ok()
+        .filter(|value| !value.trim().is_empty());
+    if let Some(value) = configured {
+        let template_path = resolve_template_path(&repo, value.trim());
+        return match read_template(template_path.clone(), "config", comment) {
+            Some(template) => Ok(Some(template)),
+            None => Err(GitError::not_found(&format!(
+                "Commit template not found: {}",
+                template_path.display()
+            ))
+            .into()),
+        };
+    }
+
+    let candidates = repo
+        .workdir()
+        .map(|workdir| workdir.join(GITMESSAGE_FILE))
+        .into_iter()
+        .chain(dirs::home_dir().map(|home| home.join(GITMESSAGE_FILE)));
+    for candidate in candidates {
+        if candidate.is_file() {
+            return Ok(read_template(candidate, "gitmessage", comment));
+        }
+    }
+    Ok(None)
+}
+
+fn rules_for(path: &str) -> CommitValidationRules {
+    crate::configuration_manager::resolve_setting(Some(path), "git.commitValidation")
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Collects violations; columns are computed from byte offsets
+struct Violations(Vec<CommitMessageViolation>);
+
+impl Violations {
+    fn add(
+        &mut self,
+        rule: &str,
+        severity: &str,
+        message: String,
+        line: usize,
+        text: &str,
+        bytes: std::ops::Range<usize>,
+    ) {
+        let start_column = text[..bytes.start].chars().count() + 1;
+        let end_column = start_column + text[bytes].chars().count().max(1);
+        self.0.push(CommitMessageViolation {
+            rule: rule.to_string(),
+            severity: severity.to_string(),
+            message,
+            line,
+            start_column,
+            end_column,
+        });
+    }
+}
+
+fn check_conventional(
+    rules: &CommitValidationRules,
+    line: usize,
+    subject: &str,
+    violations: &mut Violations,
+) {
+    let Some(captures) = CONVENTIONAL_HEADER.captures(subject) else {
+        violations.add(
+            "conventional-format",
+            "error",
+            "Subject must look like `type(scope): description`".to_string(),
+            line,
+            subject,
+            0..subject.len(),
+        );
+        return;
+    };
+
+    let kind = captures.name("type").expect("type group always matches");
+    if !rules.types.is_empty() && !rules.types.iter().any(|t| t == kind.as_str()) {
+        violations.add(
+            "type-enum",
+            "error",
+            format!(
+                "Unknown type '{}'; use one of {}",
+                kind.as_str(),
+                rules.types.join(", ")
+            ),
+            line,
+            subject,
+            kind.range(),
+        );
+    }
+
+    match captures.name("scope") {
+        Some(scope) if scope.as_str().trim().is_empty() => violations.add(
+            "scope-empty",
+            "error",
+            "Scope must not be empty".to_string(),
+            line,
+            subject,
+            scope.start().saturating_sub(1)..scope.end() + 1,
+        ),
+        Some(scope)
+            if !rules.scopes.is_empty() && !rules.scopes.iter().any(|s| s == scope.as_str()) =>
+        {
+            violations.add(
+                "scope-enum",
+                "error",
+                format!(
+                    "Unknown scope '{}'; use one of {}",
+                    scope.as_str(),
+                    rules.scopes.join(", ")
+                ),
+                line,
+                subject,
+                scope.range(),
+            )
+        }
+        None if rules.require_scope => violations.add(
+            "scope-required",
+            "error",
+            "A scope is required: `type(scope): description`".to_string(),
+            line,
+            subject,
+            kind.range(),
+        ),
+        _ => {}
+    }
+
+    let description = captures
+        .name("description")
+        .expect("description group always matches");
+    if description.as_str().trim().is_empty() {
+        violations.add(
+            "description-empty",
+            "error",
+            "Description must not be empty".to_string(),
+            line,
+            subject,
+            description.start().saturating_sub(1)..subject.len(),
+        );
+    }
+}
+
+/// Check a commit message against the repository's validation rules
+#[tauri::command]
+pub fn git_validate_commit_message(
+    path: String,
+    message: String,
+) -> Result<CommitMessageValidation, String> {
+    let rules = rules_for(&path);
+    let comment = Repository::open(&path)
+        .map(|repo| comment_char(&repo))
+        .unwrap_or('#');
+    let lines = message_lines(&message, comment);
+    let mut violations = Violations(Vec::new());
+
+    let Some(&(subject_line, subject)) = lines.first() else {
+        violations.add(
+            "subject-empty",
+            "error",
+            "Commit message must not be empty".to_string(),
+            1,
+            "",
+            0..0,
+        );
+        return Ok(CommitMessageValidation {
+            valid: false,
+            violations: violations.0,
+        });
+    };
+
+    if rules.no_trailing_whitespace {
+        for &(line, text) in &lines {
+            let trimmed = text.trim_end().len();
+            if trimmed < text.len() && trimmed > 0 {
+                violations.add(
+                    "trailing-whitespace",
+                    "warning",
+                    "Trailing whitespace".to_string(),
+                    line,
+                    text,
+                    trimmed..text.len(),
+                );
+            }
+        }
+    }
+
+    let generated = GENERATED_PREFIXES
+        .iter()
+        .any(|prefix| subject.starts_with(prefix));
+    if !generated {
+        if rules.conventional {
+            check_conventional(&rules, subject_line, subject, &mut violations);
+        }
+
+        let length = subject.chars().count();
+        if rules.max_subject_length > 0 && length > rules.max_subject_length {
+            let start = subject
+                .char_indices()
+                .nth(rules.max_subject_length)
+                .map(|(i, _)| i)
+                .unwrap_or(0);
+            violations.add(
+                "subject-max-length",
+                "warning",
+                format!(
+                    "Subject is {} characters; keep it to {}",
+                    length, rules.max_subject_length
+                ),
+                subject_line,
+                subject,
+                start..subject.len(),
+            );
+        }
+
+        let trimmed = subject.trim_end();
+        if rules.no_subject_period && trimmed.ends_with('.') && !trimmed.ends_with("...") {
+            violations.add(
+                "subject-full-stop",
+                "warning",
+                "Subject should not end with a period".to_string(),
+                subject_line,
+                subject,
+                trimmed.len() - 1..trimmed.len(),
+            );
+        }
+
+        if let Some(&(line, text)) = lines.get(1) {
+            if !text.trim().is_empty() {
+                violations.add(
+                    "body-leading-blank",
+                    "error",
+                    "Separate the subject from the body with a blank line".to_string(),
+                    line,
+                    text,
+                    0..text.len(),
+                );
+            }
+        }
+
+        if rules.max_body_line_length > 0 {
+            for &(line, text) in lines.iter().skip(1) {
+                let length = text.chars().count();
+                // Long URLs cannot be wrapped
+                if length <= rules.max_body_line_length || !text.trim().contains(' ') {
+                    continue;
+                }
+                let start = text
+                    .char_indices()
+                    .nth(rules.max_body_line_length)
+                    .map(|(i, _)| i)
+                    .unwrap_or(0);
+                violations.add(
+                    "body-max-line-length",
+                    "warning",
+                    format!(
+                        "Line is {} characters; wrap at {}",
+                        length, rules.max_body_line_length
+                    ),
+                    line,
+                    text,
+                    start..text.len(),
+                );
+            }
+        }
+    }
+
+    let valid = !violations.0.iter().any(|v| v.severity == "error");
+    Ok(CommitMessageValidation {
+        valid,
+        violations: violations.0,
+    })
+}
@@ -0,0 +1,197 @@
+//! Git Config
+//!
+//! Reads and writes git configuration at local, global or system scope.
+//! Without a scope, reads see the effective value — the one git itself would
+//! use — and writes go to the repository's own config. `git_list_config`
+//! reports every effective value with the scope it comes from.
+//!
+//! `git_get_user_identity` / `git_set_user_identity` cover `user.name` and
+//! `user.email`, so the commit box can ask for them before a commit fails.
+
+use super::error::GitError;
+use super::status::invalidate_status_cache;
+use super::types::{GitConfigEntry, GitIdentity};
+use git2::{Config, ConfigLevel, Repository};
+use std::path::PathBuf;
+
+fn scope_name(level: ConfigLevel) -> &'static str {
+    match level {
+        ConfigLevel::ProgramData => "programdata",
+        ConfigLevel::System => "system",
+        ConfigLevel::XDG => "xdg",
+        ConfigLevel::Global => "global",
+        ConfigLevel::Local => "local",
+        ConfigLevel::Worktree => "worktree",
+        ConfigLevel::App => "app",
+        ConfigLevel::Highest => "highest",
+    }
+}
+
+/// Effective config of a repository, or of the user when `path` isn't one
+fn effective_config(path: &str) -> Result<Config, GitError> {
+    match Repository::open(path) {
+        Ok(repo) => repo.config().map_err(GitError::from),
+        Err(_) => Config::open_default().map_err(GitError::from),
+    }
+}
+
+/// `~/.gitconfig`, or wherever git keeps the global file
+fn global_config_path() -> Result<PathBuf, GitError> {
+    Config::find_global()
+        .ok()
+        .or_else(|| dirs::home_dir().map(|home| home.join(".gitconfig")))
+        .ok_or_else(|| GitError::not_found("Cannot locate the global git config"))
+}
+
+/// The config file of one scope; `None` is the effective config
+/// Global and system files are opened even if they don't exist yet
+fn scoped_config(path: &str, scope: Option<&str>) -> Result<Config, GitError> {
+    match scope {
+        None => effective_config(path),
+        Some("local") => {
+            let repo = Repository::open(path).map_err(GitError::from)?;
+            repo.config()
+                .and_then(|config| config.open_level(ConfigLevel::Local))
+                .map_err(GitError::from)
+        }
+        Some("global") => Config::open(&global_config_path()?).map_err(GitError::from),
+        Some("system") => {
+            let file = Config::find_system()
+                .map_err(|_| GitError::not_found("Cannot locate the system git config"))?;
+            Config::open(&file).map_err(GitError::from)
+        }
+        Some(other) => Err(GitError::invalid(&format!(
+            "Unknown config scope '{}'; use local, global or system",
+            other
+        ))),
+    }
+}
+
+/// Settings like core.autocrlf or status.showUntrackedFiles change status
+fn invalidate_status(path: &str) {
+    if let Ok(repo) = Repository::open(path) {
+        if let Some(workdir) = repo.workdir() {
+            invalidate_status_cache(workdir);
+        }
+    }
+}
+
+fn read_entry(config: &Config, key: &str) -> Option<(String, &'static str)> {
+    let entry = config.get_entry(key).ok()?;
+    let value = entry.value()?.to_string();
+    Some((value, scope_name(entry.level())))
+}
+
+/// Read a config value; `None` when it is not set
+#[tauri::command]
+pub fn git_get_config(
+    path: String,
+    key: String,
+    scope: Option<String>,
+) -> Result<Option<String>, String> {
+    let config = scoped_config(&path, scope.as_deref())?;
+    Ok(read_entry(&config, &key).map(|(value, _)| value))
+}
+
+/// Write a config value; the scope defaults to local
+#[tauri::command]
+pub fn git_set_config(
+    path: String,
+    key: String,
+    value: String,
+    scope: Option<String>,
+) -> Result<(), String> {
+    let scope = scope.unwrap_or_else(|| "local".to_string());
+    let mut config = scoped_config(&path, Some(&scope))?;
+    config.set_str(&key, &value).map_err(GitError::from)?;
+    invalidate_status(&path);
+    println!("[GitConfig] Set {} in {} config", key, scope);
+    Ok(())
+}
+
+/// Remove a config value; the scope defaults to local
+#[tauri::command]
+pub fn git_unset_config(path: String, key: String, scope: Option<String>) -> Result<(), String> {
+    let scope = scope.unwrap_or_else(|| "local".to_string());
+    let mut config = scoped_config(&path, Some(&scope))?;
+    if config.get_entry(&key).is_err() {
+        return Ok(());
+    }
+    config.remove(&key).map_err(GitError::from)?;
+    invalidate_status(&path);
+    println!("[GitConfig] Unset {} in {} config", key, scope);
+    Ok(())
+}
+
+/// Every effective config value with the scope it comes from, by name
+#[tauri::command]
+pub fn git_list_config(path: String) -> Result<Vec<GitConfigEntry>, String> {
+    let config = effective_config(&path)?;
+
+    let mut names: Vec<String> = Vec::new();
+    config
+        .entries(None)
+        .map_err(GitError::from)?
+        .for_each(|entry| {
+            if let Some(name) = entry.name() {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+        })
+        .map_err(GitError::from)?;
+    names.sort();
+
+    Ok(names
+        .into_iter()
+        .filter_map(|name| {
+            let (value, scope) = read_entry(&config, &name)?;
+            Some(GitConfigEntry {
+                name,
+                value,
+                scope: scope.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// The identity commits will be made with, and where each part is set
+#[tauri::command]
+pub fn git_get_user_identity(path: String) -> Result<GitIdentity, String> {
+    let config = effective_config(&path)?;
+    let name = read_entry(&config, "user.name").filter(|(value, _)| !value.trim().is_empty());
+    let email = read_entry(&config, "user.email").filter(|(value, _)| !value.trim().is_empty());
+    Ok(GitIdentity {
+        complete: name.is_some() && email.is_some(),
+        name_scope: name.as_ref().map(|(_, scope)| scope.to_string()),
+        email_scope: email.as_ref().map(|(_, scope)| scope.to_string()),
+        name: name.map(|(value, _)| value),
+        email: email.map(|(value, _)| value),
+    })
+}
+
+/// Set `user.name` and `user.email`; the scope defaults to global
+#[tauri::command]
+pub fn git_set_user_identity(
+    path: String,
+    name: String,
+    email: String,
+    scope: Option<String>,
+) -> Result<GitIdentity, String> {
+    let (name, email) = (name.trim(), email.trim());
+    if name.is_empty() {
+        return Err(GitError::invalid("The name cannot be empty").into());
+    }
+    if !email.contains('@') || email.contains(char::is_whitespace) {
+        return Err(GitError::invalid(&format!("Invalid email address '{}'", email)).into());
+    }
+
+    let scope = scope.unwrap_or_else(|| "global".to_string());
+    let mut config = scoped_config(&path, Some(&scope))?;
+    config.set_str("user.name", name).map_err(GitError::from)?;
+    config
+        .set_str("user.email", email)
+        .map_err(GitError::from)?;
+    println!("[GitConfig] Set user identity in {} config", scope);
+    git_get_user_identity(path)
+}
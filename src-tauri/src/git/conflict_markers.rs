@@ -0,0 +1,358 @@
+//! Conflict Markers
+//!
+//! Structured access to the `<<<<<<<` / `|||||||` / `=======` / `>>>>>>>`
+//! blocks git writes into conflicted files, for the three-way merge editor.
+//! `git_parse_conflict_markers` returns each region with the line range and
+//! text of every side; `git_write_conflict_resolution` replaces chosen
+//! regions and stages the file once no markers are left.
+//!
+//! Markers are git's default size of seven characters. Marker-like lines in
+//! unexpected places (a second `=======`, a `<<<<<<<` inside a region) are
+//! treated as content, as git does.
+
+use super::error::GitError;
+use super::status::invalidate_status_cache;
+use super::types::{
+    ConflictMarkers, ConflictRegion, ConflictRegionResolution, ConflictResolutionResult,
+    ConflictSide,
+};
+use git2::Repository;
+use std::path::{Component, Path, PathBuf};
+
+const MARKER_SIZE: usize = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    Ours,
+    Base,
+    Separator,
+    Theirs,
+}
+
+/// The marker a line starts, with its label
+fn marker(line: &str) -> Option<(Marker, Option<String>)> {
+    let text = line.trim_end_matches(['\r', '\n']);
+    for (c, kind) in [
+        ('<', Marker::Ours),
+        ('|', Marker::Base),
+        ('=', Marker::Separator),
+        ('>', Marker::Theirs),
+    ] {
+        let Some(rest) = text.strip_prefix(c.to_string().repeat(MARKER_SIZE).as_str()) else {
+            continue;
+        };
+        if rest.is_empty() {
+            return Some((kind, None));
+        }
+        if kind != Marker::Separator && rest.starts_with(' ') {
+            let label = rest.trim();
+            return Some((kind, (!label.is_empty()).then(|| label.to_string())));
+        }
+    }
+    None
+}
+
+/// Lines `from..to` (0-based) of the file as one side
+fn side(lines: &[&str], label: Option<String>, from: usize, to: usize) -> ConflictSide {
+    ConflictSide {
+        label,
+        start_line: from + 1,
+        line_count: to - from,
+        content: lines[from..to].concat(),
+    }
+}
+
+struct OpenRegion {
+    start: usize,
+    ours_label: Option<String>,
+    base: Option<(usize, Option<String>)>,
+    separator: Option<usize>,
+}
+
+fn parse_regions(lines: &[&str]) -> Result<Vec<ConflictRegion>, GitError> {
+    let mut regions = Vec::new();
+    let mut open: Option<OpenRegion> = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        let Some((kind, label)) = marker(line) else {
+            continue;
+        };
+        match (kind, open.as_mut()) {
+            (Marker::Ours, None) => {
+                open = Some(OpenRegion {
+                    start: i,
+                    ours_label: label,
+                    base: None,
+                    separator: None,
+                })
+            }
+            (Marker::Base, Some(region)) if region.base.is_none() && region.separator.is_none() => {
+                region.base = Some((i, label));
+            }
+            (Marker::Separator, Some(region)) if region.separator.is_none() => {
+                region.separator = Some(i);
+            }
+            (Marker::Theirs, Some(region)) if region.separator.is_some() => {
+                let separator = region.separator.unwrap_or(i);
+                let ours_end = region.base.as_ref().map_or(separator, |(at, _)| *at);
+                regions.push(ConflictRegion {
+                    index: regions.len(),
+                    start_line: region.start + 1,
+                    end_line: i + 1,
+                    ours: side(lines, region.ours_label.take(), region.start + 1, ours_end),
+                    base: region
+                        .base
+                        .take()
+                        .map(|(at, label)| side(lines, label, at + 1, separator)),
+                    theirs: side(lines, label, separator + 1, i),
+                });
+                open = None;
+            }
+            _ => {}
+        }
+    }
+
+    match open {
+        Some(region) => Err(GitError::invalid(&format!(
+            "Unterminated conflict starting at line {}",
+            region.start + 1
+        ))),
+        None => Ok(regions),
+    }
+}
+
+fn line_ending(content: &str) -> &'static str {
+    if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+/// `file` inside the working tree at `path`; absolute paths and `..` are refused
+fn workdir_file(path: &str, file: &str) -> Result<PathBuf, GitError> {
+    let relative = Path::new(file);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(GitError::invalid(&format!(
+            "{} is not a path inside the repository",
+            file
+        )));
+    }
+    Ok(Path::new(path).join(relative))
+}
+
+fn read_file(path: &str, file: &str) -> Result<String, String> {
+    let full_path = workdir_file(path, file)?;
+    std::fs::read_to_string(&full_path).map_err(|e| format!("Failed to read {}: {}", file, e))
+}
+
+/// Conflict regions of a working-tree file, with the lines of each side
+#[tauri::command]
+pub fn git_parse_conflict_markers(path: String, file: String) -> Result<ConflictMarkers, String> {
+    let content = read_file(&path, &file)?;
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let regions = parse_regions(&lines)?;
+    Ok(ConflictMarkers {
+        path: file,
+        line_ending: line_ending(&content).to_string(),
+        regions,
+        content,
+    })
+}
+
+fn resolved_text(
+    region: &ConflictRegion,
+    resolution: &ConflictRegionResolution,
+    line_ending: &str,
+) -> Result<String, GitError> {
+    let text = match resolution.resolution.as_str() {
+        "ours" => region.ours.content.clone(),
+        "theirs" => region.theirs.content.clone(),
+        "base" => region
+            .base
+            .as_ref()
+            .map(|base| base.content.clone())
+            .ok_or_else(|| {
+                GitError::invalid(&format!(
+                    "Conflict {} has no base section; it was not written in diff3 style",
+                    region.index
+                ))
+            })?,
+        "both" => format!("{}{}", region.ours.content, region.theirs.content),
+        "both_theirs_first" => format!("{}{}", region.theirs.content, region.ours.content),
+        "custom" => {
+            let mut text = resolution.content.clone().unwrap_or_default();
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push_str(line_ending);
+            }
+            text
+        }
+        other => {
+            return Err(GitError::invalid(&format!(
+            "Unknown resolution '{}'; use ours, theirs, base, both, both_theirs_first or custom",
+            other
+        )))
+        }
+    };
+    Ok(text)
+}
+
+/// Replace the given conflict regions and write the file back
+/// Regions not listed keep their markers; once none are left the file is
+/// staged, unless `stage` is false
+#[tauri::command]
+pub fn git_write_conflict_resolution(
+    path: String,
+    file: String,
+    resolved_regions: Vec<ConflictRegionResolution>,
+    stage: Option<bool>,
+) -> Result<ConflictResolutionResult, String> {
+    let content = read_file(&path, &file)?;
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let regions = parse_regions(&lines)?;
+    let ending = line_ending(&content);
+
+    let mut resolutions: Vec<Option<&ConflictRegionResolution>> = vec![None; regions.len()];
+    for resolution in &resolved_regions {
+        let slot = resolutions.get_mut(resolution.index).ok_or_else(|| {
+            GitError::invalid(&format!(
+                "{} has no conflict {}; it has {}",
+                file,
+                resolution.index,
+                regions.len()
+            ))
+        })?;
+        if slot.replace(resolution).is_some() {
+            return Err(GitError::invalid(&format!(
+                "Conflict {} is resolved more than once",
+                resolution.index
+            ))
+            .into());
+        }
+    }
+
+    let mut output = String::with_capacity(content.len());
+    let mut cursor = 0;
+    for (region, resolution) in regions.iter().zip(&resolutions) {
+        let (start, end) = (region.start_line - 1, region.end_line - 1);
+        output.push_str(&lines[cursor..start].concat());
+        match resolution {
+            Some(resolution) => output.push_str(&resolved_text(region, resolution, ending)?),
+            None => output.push_str(&lines[start..=end].concat()),
+        }
+        cursor = end + 1;
+    }
+    output.push_str(&lines[cursor..].concat());
+
+    let full_path = workdir_file(&path, &file)?;
+    std::fs::write(&full_path, output)
+        .map_err(|e| format!("Failed to write resolved file: {}", e))?;
+
+    let resolved = resolutions.iter().filter(|r| r.is_some()).count();
+    let remaining = regions.len() - resolved;
+    let mut staged = false;
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    if remaining == 0 && stage.unwrap_or(true) {
+        let mut index = repo.index().map_err(GitError::from)?;
+        index.add_path(Path::new(&file)).map_err(GitError::from)?;
+        index.write().map_err(GitError::from)?;
+        staged = true;
+    }
+    if let Some(workdir) = repo.workdir() {
+        invalidate_status_cache(workdir);
+    }
+
+    println!(
+        "[GitMerge] Resolved {} conflict(s) in {}, {} remaining",
+        resolved, file, remaining
+    );
+    Ok(ConflictResolutionResult {
+        path: file,
+        resolved,
+        remaining,
+        staged,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regions(text: &str) -> Result<Vec<ConflictRegion>, GitError> {
+        let lines: Vec<&str> = text.split_inclusive('\n').collect();
+        parse_regions(&lines)
+    }
+
+    #[test]
+    fn parses_two_way_conflict() {
+        let text = "a\n<<<<<<< HEAD\nours\n=======\ntheirs 1\ntheirs 2\n>>>>>>> feature\nb\n";
+        let regions = regions(text).expect("parses");
+        assert_eq!(regions.len(), 1);
+        let region = &regions[0];
+        assert_eq!((region.start_line, region.end_line), (2, 7));
+        assert_eq!(region.ours.label.as_deref(), Some("HEAD"));
+        assert_eq!(region.ours.start_line, 3);
+        assert_eq!(region.ours.content, "ours\n");
+        assert!(region.base.is_none());
+        assert_eq!(region.theirs.label.as_deref(), Some("feature"));
+        assert_eq!(region.theirs.start_line, 5);
+        assert_eq!(region.theirs.line_count, 2);
+        assert_eq!(region.theirs.content, "theirs 1\ntheirs 2\n");
+    }
+
+    #[test]
+    fn parses_diff3_base() {
+        let text = "<<<<<<< ours\nx\n||||||| base\ny\n=======\nz\n>>>>>>> theirs\n";
+        let regions = regions(text).expect("parses");
+        let base = regions[0].base.as_ref().expect("has a base");
+        assert_eq!(base.label.as_deref(), Some("base"));
+        assert_eq!(base.content, "y\n");
+        assert_eq!(regions[0].ours.content, "x\n");
+        assert_eq!(regions[0].theirs.content, "z\n");
+    }
+
+    #[test]
+    fn indexes_multiple_regions_and_allows_empty_sides() {
+        let text = "<<<<<<<\n=======\nb\n>>>>>>>\nmid\n<<<<<<< a\nc\n=======\n>>>>>>> b\n";
+        let regions = regions(text).expect("parses");
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].index, 0);
+        assert_eq!(regions[0].ours.label, None);
+        assert_eq!(regions[0].ours.line_count, 0);
+        assert_eq!(regions[1].index, 1);
+        assert_eq!(regions[1].start_line, 6);
+        assert_eq!(regions[1].theirs.line_count, 0);
+    }
+
+    #[test]
+    fn treats_stray_markers_as_content() {
+        let text = "<<<<<<< a\none\n=======\ntwo\n=======\n>>>>>>> b\n=======\n";
+        let regions = regions(text).expect("parses");
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].theirs.content, "two\n=======\n");
+    }
+
+    #[test]
+    fn ignores_longer_marker_runs() {
+        assert!(regions("<<<<<<<< not a marker\n========\n")
+            .expect("parses")
+            .is_empty());
+    }
+
+    #[test]
+    fn rejects_unterminated_conflict() {
+        let error = regions("x\n<<<<<<< HEAD\nours\n=======\n").expect_err("unterminated");
+        assert!(error.message.contains("line 2"));
+    }
+
+    #[test]
+    fn refuses_paths_outside_the_working_tree() {
+        assert!(workdir_file("/repo", "src/main.rs").is_ok());
+        assert!(workdir_file("/repo", "../secret").is_err());
+        assert!(workdir_file("/repo", "src/../../secret").is_err());
+        assert!(workdir_file("/repo", "/etc/passwd").is_err());
+    }
+}
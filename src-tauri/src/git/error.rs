@@ -79,6 +79,15 @@ impl GitError {
             details: None,
         }
     }
+
+    /// Create an invalid input or state error
+    pub fn invalid(message: &str) -> Self {
+        Self {
+            category: ErrorCategory::Invalid,
+            message: message.to_string(),
+            details: None,
+        }
+    }
 }
 
 // Convert GitError to String for Tauri command compatibility
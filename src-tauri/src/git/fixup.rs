@@ -0,0 +1,429 @@
+//! Git Fixup Workflow
+//!
+//! Supports correcting earlier commits from the commit panel: check whether
+//! HEAD can simply be amended, otherwise record the staged changes as a
+//! `fixup!` (or `squash!` / `amend!`) commit for an older one, and later fold
+//! those commits into their targets with an autosquash rebase.
+//!
+//! libgit2 has no interactive rebase, so autosquash replays the todo list in
+//! memory and only moves the branch once every commit applied. A conflict
+//! leaves the branch untouched and reports the commit that failed.
+
+use super::error::GitError;
+use super::sync::has_local_changes;
+use super::types::{AmendStatus, FixupCommit, RebaseProgress, RebaseResult};
+use git2::{BranchType, Commit, Oid, Repository, Sort};
+use tauri::Emitter;
+
+const FIXUP_PREFIXES: [&str; 3] = ["fixup! ", "squash! ", "amend! "];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Pick,
+    Fixup,
+    Squash,
+    Amend,
+}
+
+fn short(oid: Oid) -> String {
+    oid.to_string()[..7].to_string()
+}
+
+fn head_commit(repo: &Repository) -> Result<Commit<'_>, GitError> {
+    repo.head()
+        .and_then(|head| head.peel_to_commit())
+        .map_err(GitError::from)
+}
+
+fn resolve_commit<'r>(repo: &'r Repository, spec: &str) -> Result<Commit<'r>, GitError> {
+    repo.revparse_single(spec)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|_| GitError::not_found(&format!("Cannot resolve '{}'", spec)))
+}
+
+/// Remote branches whose tip contains `oid`
+fn remote_branches_containing(repo: &Repository, oid: Oid) -> Result<Vec<String>, GitError> {
+    let mut names = Vec::new();
+    for branch in repo
+        .branches(Some(BranchType::Remote))
+        .map_err(GitError::from)?
+    {
+        let (branch, _) = branch.map_err(GitError::from)?;
+        let (Some(tip), Ok(Some(name))) = (branch.get().target(), branch.name()) else {
+            continue;
+        };
+        if name.ends_with("/HEAD") {
+            continue;
+        }
+        if tip == oid || repo.graph_descendant_of(tip, oid).unwrap_or(false) {
+            names.push(name.to_string());
+        }
+    }
+    Ok(names)
+}
+
+/// Whether the last commit can be amended, and why not
+#[tauri::command]
+pub fn git_amend_status(path: String) -> Result<AmendStatus, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let Ok(head) = head_commit(&repo) else {
+        return Ok(AmendStatus {
+            can_amend: false,
+            head: None,
+            summary: None,
+            pushed_to: Vec::new(),
+            reason: Some("There is no commit to amend yet".to_string()),
+        });
+    };
+
+    let pushed_to = remote_branches_containing(&repo, head.id())?;
+    let reason = if repo.state() != git2::RepositoryState::Clean {
+        Some(format!("A {:?} is in progress", repo.state()))
+    } else if head.parent_count() > 1 {
+        Some("The last commit is a merge".to_string())
+    } else if !pushed_to.is_empty() {
+        Some(format!(
+            "The last commit is already on {}; amending rewrites published history",
+            pushed_to.join(", ")
+        ))
+    } else {
+        None
+    };
+
+    Ok(AmendStatus {
+        can_amend: reason.is_none(),
+        head: Some(head.id().to_string()),
+        summary: head.summary().map(|s| s.to_string()),
+        pushed_to,
+        reason,
+    })
+}
+
+/// Commit the staged changes as a correction of `target_hash`
+/// `kind` is "fixup" (default), "squash" or "amend"; `message` becomes the body
+/// of a squash or the replacement message of an amend
+#[tauri::command]
+pub fn git_create_fixup_commit(
+    path: String,
+    target_hash: String,
+    kind: Option<String>,
+    message: Option<String>,
+) -> Result<FixupCommit, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let head = head_commit(&repo)?;
+    let target = resolve_commit(&repo, &target_hash)?;
+
+    if target.id() != head.id()
+        && !repo
+            .graph_descendant_of(head.id(), target.id())
+            .unwrap_or(false)
+    {
+        return Err(GitError::invalid(&format!(
+            "{} is not part of the current branch",
+            short(target.id())
+        ))
+        .into());
+    }
+
+    let mut index = repo.index().map_err(GitError::from)?;
+    let tree_id = index.write_tree().map_err(GitError::from)?;
+    let kind = kind.unwrap_or_else(|| "fixup".to_string());
+    if tree_id == head.tree_id() && kind != "amend" {
+        return Err(GitError::invalid("Nothing staged to fix up with").into());
+    }
+
+    // The target's own fixup prefixes are dropped, as `git commit --fixup` does
+    let mut subject = target.summary().unwrap_or_default();
+    while let Some(rest) = FIXUP_PREFIXES.iter().find_map(|p| subject.strip_prefix(p)) {
+        subject = rest;
+    }
+    let commit_message = match kind.as_str() {
+        "fixup" => format!("fixup! {}\n", subject),
+        "squash" => match message.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+            Some(body) => format!("squash! {}\n\n{}\n", subject, body),
+            None => format!("squash! {}\n", subject),
+        },
+        "amend" => {
+            let replacement = message
+                .as_deref()
+                .map(str::trim)
+                .filter(|m| !m.is_empty())
+                .ok_or_else(|| GitError::invalid("An amend! commit needs the new message"))?;
+            format!("amend! {}\n\n{}\n", subject, replacement)
+        }
+        other => {
+            return Err(GitError::invalid(&format!(
+                "Unknown fixup kind '{}'; use fixup, squash or amend",
+                other
+            ))
+            .into())
+        }
+    };
+
+    super::secrets::pre_commit_check(&repo)?;
+
+    let sig = repo.signature().map_err(GitError::from)?;
+    let tree = repo.find_tree(tree_id).map_err(GitError::from)?;
+    let commit_id = repo
+        .commit(Some("HEAD"), &sig, &sig, &commit_message, &tree, &[&head])
+        .map_err(GitError::from)?;
+
+    println!(
+        "[GitFixup] Created {} for {}",
+        short(commit_id),
+        short(target.id())
+    );
+    Ok(FixupCommit {
+        commit_id: commit_id.to_string(),
+        target: target.id().to_string(),
+        message: commit_message,
+    })
+}
+
+/// Action and target subject of a `fixup!`-style commit
+fn fixup_target(summary: &str) -> Option<(Action, &str)> {
+    let (action, mut rest) = if let Some(rest) = summary.strip_prefix("fixup! ") {
+        (Action::Fixup, rest)
+    } else if let Some(rest) = summary.strip_prefix("squash! ") {
+        (Action::Squash, rest)
+    } else if let Some(rest) = summary.strip_prefix("amend! ") {
+        (Action::Amend, rest)
+    } else {
+        return None;
+    };
+    // `fixup! fixup! x` targets x
+    while let Some(inner) = FIXUP_PREFIXES.iter().find_map(|p| rest.strip_prefix(p)) {
+        rest = inner;
+    }
+    Some((action, rest.trim()))
+}
+
+/// Reorder commits so each fixup follows its target, like `rebase --autosquash`
+fn autosquash_todo<'r>(commits: Vec<Commit<'r>>) -> Vec<(Action, Commit<'r>)> {
+    let mut todo: Vec<(Action, Commit<'r>)> = Vec::new();
+    for commit in commits {
+        let summary = commit.summary().unwrap_or_default().to_string();
+        let target = fixup_target(&summary).and_then(|(action, subject)| {
+            // Match the subject first, then a commit id prefix
+            let position = todo
+                .iter()
+                .position(|(a, c)| *a == Action::Pick && c.summary() == Some(subject))
+                .or_else(|| {
+                    if subject.len() < 4 {
+                        return None;
+                    }
+                    todo.iter().position(|(a, c)| {
+                        *a == Action::Pick && c.id().to_string().starts_with(subject)
+                    })
+                })?;
+            Some((action, position))
+        });
+        match target {
+            Some((action, position)) => {
+                // After the target and any fixups already attached to it
+                let mut insert_at = position + 1;
+                while insert_at < todo.len() && todo[insert_at].0 != Action::Pick {
+                    insert_at += 1;
+                }
+                todo.insert(insert_at, (action, commit));
+            }
+            None => todo.push((Action::Pick, commit)),
+        }
+    }
+    todo
+}
+
+/// Message body without its subject line
+fn body_of(message: &str) -> &str {
+    message
+        .split_once('\n')
+        .map(|(_, body)| body.trim_start_matches('\n'))
+        .unwrap_or("")
+}
+
+/// Rebase onto `upstream`, squashing `fixup!`, `squash!` and `amend!` commits
+/// into the commits they name
+#[tauri::command]
+pub fn git_autosquash_rebase(
+    window: tauri::Window,
+    path: String,
+    upstream: String,
+) -> Result<RebaseResult, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    if repo.state() != git2::RepositoryState::Clean {
+        return Err(format!(
+            "Cannot rebase: repository is in {:?} state",
+            repo.state()
+        ));
+    }
+    let head_ref = repo.head().map_err(GitError::from)?;
+    if !head_ref.is_branch() {
+        return Err(GitError::invalid("Cannot autosquash a detached HEAD").into());
+    }
+    let refname = head_ref.name().unwrap_or_default().to_string();
+    if has_local_changes(&repo)? {
+        return Err(GitError::conflict("Commit or stash your changes before rebasing").into());
+    }
+
+    let head = head_commit(&repo)?;
+    let onto = resolve_commit(&repo, &upstream)?;
+    let base = repo
+        .merge_base(head.id(), onto.id())
+        .map_err(GitError::from)?;
+
+    let mut revwalk = repo.revwalk().map_err(GitError::from)?;
+    revwalk
+        .set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+        .map_err(GitError::from)?;
+    revwalk.push(head.id()).map_err(GitError::from)?;
+    revwalk.hide(base).map_err(GitError::from)?;
+    let mut commits = Vec::new();
+    for oid in revwalk {
+        let commit = repo
+            .find_commit(oid.map_err(GitError::from)?)
+            .map_err(GitError::from)?;
+        if commit.parent_count() > 1 {
+            return Err(GitError::invalid(&format!(
+                "Cannot autosquash across merge commit {}",
+                short(commit.id())
+            ))
+            .into());
+        }
+        commits.push(commit);
+    }
+
+    let todo = autosquash_todo(commits);
+    let total = todo.len();
+    let squashed = todo.iter().filter(|(a, _)| *a != Action::Pick).count();
+    let sig = repo.signature().map_err(GitError::from)?;
+
+    // `last` is the newest rewritten commit; fixups replace it
+    let mut last = onto.clone();
+    for (i, (action, commit)) in todo.iter().enumerate() {
+        let _ = window.emit(
+            "git:rebase-progress",
+            RebaseProgress {
+                current: i + 1,
+                total,
+                commit_id: commit.id().to_string(),
+                summary: commit.summary().unwrap_or_default().to_string(),
+            },
+        );
+
+        let mut index = repo
+            .cherrypick_commit(commit, &last, 0, None)
+            .map_err(GitError::from)?;
+        if index.has_conflicts() {
+            let conflicts = index
+                .conflicts()
+                .map_err(GitError::from)?
+                .filter_map(|c| c.ok())
+                .filter_map(|c| {
+                    c.our
+                        .or(c.their)
+                        .and_then(|e| String::from_utf8(e.path).ok())
+                })
+                .collect();
+            return Ok(RebaseResult {
+                status: "conflicts".to_string(),
+                current: i + 1,
+                total,
+                stopped_at: Some(commit.id().to_string()),
+                conflicts,
+                message: format!(
+                    "Conflicts while applying {} ({}); the branch was left unchanged",
+                    short(commit.id()),
+                    commit.summary().unwrap_or_default()
+                ),
+            });
+        }
+        let tree_id = index.write_tree_to(&repo).map_err(GitError::from)?;
+        let tree = repo.find_tree(tree_id).map_err(GitError::from)?;
+
+        let new_id = match action {
+            Action::Pick => {
+                // Unchanged history is kept as is
+                if commit.parent_id(0).ok() == Some(last.id()) {
+                    commit.id()
+                } else {
+                    repo.commit(
+                        None,
+                        &commit.author(),
+                        &sig,
+                        commit.message().unwrap_or_default(),
+                        &tree,
+                        &[&last],
+                    )
+                    .map_err(GitError::from)?
+                }
+            }
+            Action::Fixup | Action::Squash | Action::Amend => {
+                let previous = last.message().unwrap_or_default().to_string();
+                let message = match action {
+                    Action::Squash => {
+                        let body = body_of(commit.message().unwrap_or_default()).trim_end();
+                        if body.is_empty() {
+                            previous
+                        } else {
+                            format!("{}\n\n{}\n", previous.trim_end(), body)
+                        }
+                    }
+                    Action::Amend => {
+                        let body = body_of(commit.message().unwrap_or_default());
+                        if body.trim().is_empty() {
+                            previous
+                        } else {
+                            body.to_string()
+                        }
+                    }
+                    _ => previous,
+                };
+                let parents: Vec<Commit> = last.parents().collect();
+                let parents: Vec<&Commit> = parents.iter().collect();
+                repo.commit(None, &last.author(), &sig, &message, &tree, &parents)
+                    .map_err(GitError::from)?
+            }
+        };
+        last = repo.find_commit(new_id).map_err(GitError::from)?;
+    }
+
+    if last.id() == head.id() {
+        return Ok(RebaseResult {
+            status: "completed".to_string(),
+            current: total,
+            total,
+            stopped_at: None,
+            conflicts: Vec::new(),
+            message: "Nothing to squash".to_string(),
+        });
+    }
+
+    // A safe checkout refuses to overwrite anything that changed meanwhile
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.safe();
+    repo.checkout_tree(last.as_object(), Some(&mut checkout))
+        .map_err(GitError::from)?;
+    repo.reference("ORIG_HEAD", head.id(), true, "autosquash")
+        .map_err(GitError::from)?;
+    repo.find_reference(&refname)
+        .and_then(|mut reference| {
+            reference.set_target(
+                last.id(),
+                &format!("rebase (autosquash): onto {}", onto.id()),
+            )
+        })
+        .map_err(GitError::from)?;
+
+    Ok(RebaseResult {
+        status: "completed".to_string(),
+        current: total,
+        total,
+        stopped_at: None,
+        conflicts: Vec::new(),
+        message: format!(
+            "Rebased {} commit(s), squashing {} fixup(s)",
+            total - squashed,
+            squashed
+        ),
+    })
+}
@@ -3,11 +3,16 @@
 //! Native libgit2 implementation for log, diff, and commit history.
 
 use super::error::GitError;
-use super::types::{CommitInfo, FileDiff};
-use git2::{DiffOptions, Repository, Time};
+use super::types::{
+    CommitInfo, DiffRefsOptions, FileDiff, FileDiffOutput, FileHistoryEntry, IntralineDiff,
+    IntralineMode,
+};
+use super::word_diff::IntralineCollector;
+use git2::{Commit, DiffFindOptions, DiffOptions, Oid, Repository, Time};
+use std::path::Path;
 
 /// Format git time to ISO 8601 format
-fn format_time(time: Time) -> String {
+pub(super) fn format_time(time: Time) -> String {
     use chrono::{FixedOffset, Offset, TimeZone, Utc};
 
     let offset_minutes = time.offset_minutes();
@@ -58,6 +63,223 @@ pub fn git_log(path: String, max_count: Option<u32>) -> Result<Vec<CommitInfo>,
     Ok(commits)
 }
 
+pub(super) fn commit_info(commit: &Commit) -> CommitInfo {
+    let author = commit.author();
+    CommitInfo {
+        hash: commit.id().to_string(),
+        author: author.name().unwrap_or("").to_string(),
+        email: author.email().unwrap_or("").to_string(),
+        date: format_time(author.when()),
+        message: commit
+            .message()
+            .unwrap_or("")
+            .lines()
+            .next()
+            .unwrap_or("")
+            .to_string(),
+    }
+}
+
+/// Blob id of `path` in a commit's tree
+fn blob_at(commit: &Commit, path: &str) -> Option<Oid> {
+    commit
+        .tree()
+        .ok()?
+        .get_path(Path::new(path))
+        .ok()
+        .map(|entry| entry.id())
+}
+
+/// Commits on HEAD that touched a file, newest first, with per-commit line stats.
+/// With `follow_renames` the history continues under the file's previous names.
+#[tauri::command]
+pub fn git_file_history(
+    path: String,
+    file_path: String,
+    follow_renames: Option<bool>,
+    limit: Option<usize>,
+) -> Result<Vec<FileHistoryEntry>, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let follow_renames = follow_renames.unwrap_or(true);
+    let limit = limit.unwrap_or(100);
+
+    // Accept absolute paths inside the working directory
+    let mut current = match repo.workdir() {
+        Some(workdir) => Path::new(&file_path)
+            .strip_prefix(workdir)
+            .unwrap_or(Path::new(&file_path))
+            .to_string_lossy()
+            .replace('\\', "/"),
+        None => file_path.replace('\\', "/"),
+    };
+
+    let mut revwalk = repo.revwalk().map_err(GitError::from)?;
+    revwalk
+        .set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)
+        .map_err(GitError::from)?;
+    if revwalk.push_head().is_err() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    for oid in revwalk {
+        if entries.len() >= limit {
+            break;
+        }
+        let commit = repo
+            .find_commit(oid.map_err(GitError::from)?)
+            .map_err(GitError::from)?;
+
+        let blob = blob_at(&commit, &current);
+        let parents: Vec<Commit> = commit.parents().collect();
+        // Skip commits where the file matches a parent (including merges that took one side)
+        if parents
+            .iter()
+            .any(|parent| blob_at(parent, &current) == blob)
+        {
+            continue;
+        }
+        if blob.is_none() && parents.is_empty() {
+            continue;
+        }
+
+        let tree = commit.tree().map_err(GitError::from)?;
+        let parent_tree = match parents.first() {
+            Some(parent) => Some(parent.tree().map_err(GitError::from)?),
+            None => None,
+        };
+
+        // Renames need the whole diff for similarity detection; otherwise limit to the file
+        let added_here = blob.is_some()
+            && parents
+                .first()
+                .is_some_and(|parent| blob_at(parent, &current).is_none());
+        let mut opts = DiffOptions::new();
+        if !(follow_renames && added_here) {
+            opts.pathspec(&current).disable_pathspec_match(true);
+        }
+        let mut diff = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))
+            .map_err(GitError::from)?;
+        if follow_renames && added_here {
+            let mut find = DiffFindOptions::new();
+            find.renames(true);
+            diff.find_similar(Some(&mut find)).map_err(GitError::from)?;
+        }
+
+        let Some(index) = diff.deltas().position(|delta| {
+            delta.new_file().path().or_else(|| delta.old_file().path()) == Some(Path::new(&current))
+        }) else {
+            continue;
+        };
+        let delta = diff
+            .get_delta(index)
+            .ok_or_else(|| "Delta not found".to_string())?;
+        let status = match delta.status() {
+            git2::Delta::Added => "A",
+            git2::Delta::Deleted => "D",
+            git2::Delta::Renamed => "R",
+            _ => "M",
+        };
+        let old_path = (delta.status() == git2::Delta::Renamed)
+            .then(|| delta.old_file().path())
+            .flatten()
+            .map(|p| p.to_string_lossy().to_string());
+
+        let (additions, deletions) = match git2::Patch::from_diff(&diff, index) {
+            Ok(Some(patch)) => patch
+                .line_stats()
+                .map(|(_, additions, deletions)| (additions, deletions))
+                .unwrap_or((0, 0)),
+            _ => (0, 0),
+        };
+
+        entries.push(FileHistoryEntry {
+            commit: commit_info(&commit),
+            path: current.clone(),
+            old_path: old_path.clone(),
+            status: status.to_string(),
+            additions,
+            deletions,
+        });
+
+        match old_path {
+            // Older commits know the file by its previous name
+            Some(old_path) => current = old_path,
+            // The file was created here; nothing older to follow
+            None if status == "A" => break,
+            None => {}
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Files touched by commits on HEAD since a unix timestamp, with the most
+/// recent commit time and commit count for each (paths relative to the repo root)
+pub(crate) fn recent_commit_files(
+    path: &str,
+    since: i64,
+) -> Result<Vec<(String, i64, usize)>, GitError> {
+    use std::collections::HashMap;
+
+    let repo = Repository::open(path).map_err(GitError::from)?;
+    let mut revwalk = repo.revwalk().map_err(GitError::from)?;
+    revwalk
+        .set_sorting(git2::Sort::TIME)
+        .map_err(GitError::from)?;
+    if revwalk.push_head().is_err() {
+        // Empty repository
+        return Ok(Vec::new());
+    }
+
+    let mut files: HashMap<String, (i64, usize)> = HashMap::new();
+    for oid in revwalk {
+        let commit = repo
+            .find_commit(oid.map_err(GitError::from)?)
+            .map_err(GitError::from)?;
+        let time = commit.time().seconds();
+        if time < since {
+            break;
+        }
+
+        let tree = commit.tree().map_err(GitError::from)?;
+        let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+        let diff = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+            .map_err(GitError::from)?;
+
+        for delta in diff.deltas() {
+            if let Some(file) = delta.new_file().path().or_else(|| delta.old_file().path()) {
+                let entry = files
+                    .entry(file.to_string_lossy().to_string())
+                    .or_insert((time, 0));
+                entry.0 = entry.0.max(time);
+                entry.1 += 1;
+            }
+        }
+    }
+
+    Ok(files
+        .into_iter()
+        .map(|(path, (time, count))| (path, time, count))
+        .collect())
+}
+
+/// Paths with uncommitted changes, including untracked files (relative to the repo root)
+pub(crate) fn uncommitted_files(path: &str) -> Result<Vec<String>, GitError> {
+    let repo = Repository::open(path).map_err(GitError::from)?;
+    let mut options = git2::StatusOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true);
+
+    let statuses = repo.statuses(Some(&mut options)).map_err(GitError::from)?;
+    Ok(statuses
+        .iter()
+        .filter(|entry| !entry.status().is_ignored())
+        .filter_map(|entry| entry.path().map(|p| p.to_string()))
+        .collect())
+}
+
 /// Sync status for status bar - returns ahead/behind counts
 #[derive(serde::Serialize)]
 pub struct SyncStatus {
@@ -307,7 +529,8 @@ pub fn git_diff_file(
     path: String,
     file_path: String,
     staged: Option<bool>,
-) -> Result<String, String> {
+    intraline: Option<IntralineMode>,
+) -> Result<FileDiffOutput, String> {
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
 
     let mut opts = DiffOptions::new();
@@ -326,17 +549,35 @@ pub fn git_diff_file(
     };
 
     let mut diff_text = String::new();
+    let mut collector = intraline.map(IntralineCollector::new);
+    let mut diff_line = 0;
     diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
+        if let Some(collector) = collector.as_mut() {
+            collector.push(&line, diff_line);
+        }
         let origin = line.origin();
         if origin == '+' || origin == '-' || origin == ' ' {
             diff_text.push(origin);
         }
-        diff_text.push_str(&String::from_utf8_lossy(line.content()));
+        let content = String::from_utf8_lossy(line.content());
+        diff_line += content.matches('\n').count();
+        diff_text.push_str(&content);
         true
     })
     .map_err(|e| GitError::from(e))?;
 
-    Ok(diff_text)
+    Ok(diff_output(diff_text, collector))
+}
+
+/// Plain text, or text with highlights when intraline diffing was requested
+fn diff_output(diff: String, collector: Option<IntralineCollector>) -> FileDiffOutput {
+    match collector {
+        Some(collector) => FileDiffOutput::Intraline(IntralineDiff {
+            diff,
+            highlights: collector.finish(),
+        }),
+        None => FileDiffOutput::Text(diff),
+    }
 }
 
 /// Get diff for all files in a commit (with optional metadata-only mode)
@@ -447,6 +688,150 @@ pub fn git_diff_commit(
     Ok(file_diffs)
 }
 
+pub(super) fn delta_status_code(status: git2::Delta) -> &'static str {
+    match status {
+        git2::Delta::Added => "A",
+        git2::Delta::Deleted => "D",
+        git2::Delta::Modified => "M",
+        git2::Delta::Renamed => "R",
+        git2::Delta::Copied => "C",
+        git2::Delta::Typechange => "T",
+        _ => "?",
+    }
+}
+
+/// Compare any two refs (branches, tags, commits or other revspecs)
+/// What `to_ref` changes relative to `from_ref`, like `git diff from..to`
+#[tauri::command]
+pub fn git_diff_refs(
+    path: String,
+    from_ref: String,
+    to_ref: String,
+    options: Option<DiffRefsOptions>,
+) -> Result<Vec<FileDiff>, String> {
+    let options = options.unwrap_or_default();
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let tree_of = |spec: &str| {
+        repo.revparse_single(spec)
+            .and_then(|object| object.peel_to_tree())
+            .map_err(|e| format!("Cannot resolve '{}': {}", spec, e.message()))
+    };
+    let from_tree = tree_of(&from_ref)?;
+    let to_tree = tree_of(&to_ref)?;
+
+    let mut opts = DiffOptions::new();
+    for pathspec in &options.paths {
+        opts.pathspec(pathspec);
+    }
+    let mut diff = repo
+        .diff_tree_to_tree(Some(&from_tree), Some(&to_tree), Some(&mut opts))
+        .map_err(GitError::from)?;
+    if options.detect_renames.unwrap_or(true) {
+        let mut find_opts = DiffFindOptions::new();
+        find_opts.renames(true);
+        diff.find_similar(Some(&mut find_opts))
+            .map_err(GitError::from)?;
+    }
+
+    let max_lines = options.max_lines_per_file.unwrap_or(500);
+    let mut file_diffs = Vec::with_capacity(diff.deltas().len());
+
+    for (i, delta) in diff.deltas().enumerate() {
+        let new_file = delta.new_file();
+        let old_file = delta.old_file();
+        let file_path = new_file
+            .path()
+            .or_else(|| old_file.path())
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let old_path = matches!(delta.status(), git2::Delta::Renamed | git2::Delta::Copied)
+            .then(|| old_file.path().map(|p| p.to_string_lossy().to_string()))
+            .flatten();
+
+        let mut additions = 0;
+        let mut deletions = 0;
+        let mut text = String::new();
+        if !options.metadata_only {
+            if let Some(mut patch) = git2::Patch::from_diff(&diff, i).map_err(GitError::from)? {
+                let (_, adds, dels) = patch.line_stats().map_err(GitError::from)?;
+                additions = adds;
+                deletions = dels;
+
+                let mut line_count = 0;
+                patch
+                    .print(&mut |_delta, _hunk, line| {
+                        if line_count < max_lines {
+                            let origin = line.origin();
+                            if origin == '+' || origin == '-' || origin == ' ' {
+                                text.push(origin);
+                            }
+                            text.push_str(&String::from_utf8_lossy(line.content()));
+                            line_count += 1;
+                        }
+                        true
+                    })
+                    .ok();
+            }
+        }
+
+        file_diffs.push(FileDiff {
+            path: file_path,
+            old_path,
+            status: delta_status_code(delta.status()).to_string(),
+            additions,
+            deletions,
+            diff: text,
+        });
+    }
+
+    Ok(file_diffs)
+}
+
+fn resolve_merge_base(
+    repo: &Repository,
+    branch_a: &str,
+    branch_b: &str,
+) -> Result<git2::Oid, String> {
+    let commit_of = |spec: &str| {
+        repo.revparse_single(spec)
+            .and_then(|object| object.peel_to_commit())
+            .map(|commit| commit.id())
+            .map_err(|e| format!("Cannot resolve '{}': {}", spec, e.message()))
+    };
+    let a = commit_of(branch_a)?;
+    let b = commit_of(branch_b)?;
+    repo.merge_base(a, b).map_err(|_| {
+        GitError::not_found(&format!(
+            "'{}' and '{}' have no common ancestor",
+            branch_a, branch_b
+        ))
+        .into()
+    })
+}
+
+/// Commit where `branch_a` and `branch_b` diverged
+#[tauri::command]
+pub fn git_merge_base(path: String, branch_a: String, branch_b: String) -> Result<String, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    Ok(resolve_merge_base(&repo, &branch_a, &branch_b)?.to_string())
+}
+
+/// What `branch_a` changes since it diverged from `branch_b`, like
+/// `git diff branch_b...branch_a` — the changes a pull request review shows
+#[tauri::command]
+pub fn git_diff_merge_base(
+    path: String,
+    branch_a: String,
+    branch_b: String,
+    options: Option<DiffRefsOptions>,
+) -> Result<Vec<FileDiff>, String> {
+    let base = {
+        let repo = Repository::open(&path).map_err(GitError::from)?;
+        resolve_merge_base(&repo, &branch_a, &branch_b)?
+    };
+    git_diff_refs(path, base.to_string(), branch_a, options)
+}
+
 /// Get diff for a specific file in a commit (lazy loading)
 #[tauri::command]
 pub fn git_diff_commit_file(
@@ -454,7 +839,8 @@ pub fn git_diff_commit_file(
     commit: String,
     file_path: String,
     max_lines: Option<usize>,
-) -> Result<String, String> {
+    intraline: Option<IntralineMode>,
+) -> Result<FileDiffOutput, String> {
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
     let oid = git2::Oid::from_str(&commit).map_err(|e| GitError::from(e))?;
     let commit_obj = repo.find_commit(oid).map_err(|e| GitError::from(e))?;
@@ -482,19 +868,26 @@ pub fn git_diff_commit_file(
     let max = max_lines.unwrap_or(500);
     let mut diff_text = String::new();
     let mut line_count = 0;
+    let mut collector = intraline.map(IntralineCollector::new);
+    let mut diff_line = 0;
 
     diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
         if line_count < max {
+            if let Some(collector) = collector.as_mut() {
+                collector.push(&line, diff_line);
+            }
             let origin = line.origin();
             if origin == '+' || origin == '-' || origin == ' ' {
                 diff_text.push(origin);
             }
-            diff_text.push_str(&String::from_utf8_lossy(line.content()));
+            let content = String::from_utf8_lossy(line.content());
+            diff_line += content.matches('\n').count();
+            diff_text.push_str(&content);
             line_count += 1;
         }
         true
     })
     .map_err(|e| GitError::from(e))?;
 
-    Ok(diff_text)
+    Ok(diff_output(diff_text, collector))
 }
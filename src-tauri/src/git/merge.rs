@@ -3,8 +3,9 @@
 //! Native libgit2 implementation for merge and conflict resolution.
 
 use super::error::GitError;
-use super::types::ConflictContent;
-use git2::{MergeOptions, Repository};
+use super::types::{ConflictContent, MergeConflictPreview, MergePreview};
+use git2::{MergeOptions, Oid, Repository, Tree};
+use std::collections::BTreeSet;
 
 /// Merge a branch into current branch
 #[tauri::command]
@@ -100,6 +101,136 @@ pub fn git_merge(path: String, branch: String, no_ff: Option<bool>) -> Result<St
     Ok(format!("Merged branch '{}'", branch))
 }
 
+/// Paths changed between two trees
+fn changed_paths(repo: &Repository, from: &Tree, to: &Tree) -> Result<BTreeSet<String>, GitError> {
+    let diff = repo.diff_tree_to_tree(Some(from), Some(to), None)?;
+    Ok(diff
+        .deltas()
+        .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+        .flatten()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect())
+}
+
+/// Preview merging a branch into HEAD without touching the index or working tree
+///
+/// The merge is computed in memory with `merge_trees`, so the result shows
+/// which files would conflict and which would merge cleanly.
+#[tauri::command]
+pub fn git_merge_preview(path: String, branch: String) -> Result<MergePreview, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+
+    let their_commit = match repo.find_branch(&branch, git2::BranchType::Local) {
+        Ok(local) => local.get().peel_to_commit(),
+        Err(_) => repo
+            .revparse_single(&branch)
+            .and_then(|object| object.peel_to_commit()),
+    }
+    .map_err(GitError::from)?;
+    let our_commit = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .map_err(GitError::from)?;
+
+    let base_id: Option<Oid> = repo.merge_base(our_commit.id(), their_commit.id()).ok();
+    let up_to_date = base_id == Some(their_commit.id());
+    let fast_forward = !up_to_date && base_id == Some(our_commit.id());
+
+    let mut revwalk = repo.revwalk().map_err(GitError::from)?;
+    revwalk.push(their_commit.id()).map_err(GitError::from)?;
+    revwalk.hide(our_commit.id()).map_err(GitError::from)?;
+    let incoming_commits = revwalk.count();
+
+    let mut preview = MergePreview {
+        up_to_date,
+        fast_forward,
+        merge_base: base_id.map(|id| id.to_string()),
+        incoming_commits,
+        conflicts: Vec::new(),
+        auto_merged: Vec::new(),
+        incoming_files: Vec::new(),
+        dirty_files: Vec::new(),
+    };
+    if up_to_date {
+        return Ok(preview);
+    }
+
+    let our_tree = our_commit.tree().map_err(GitError::from)?;
+    let their_tree = their_commit.tree().map_err(GitError::from)?;
+    // Unrelated histories merge against an empty tree
+    let base_tree = match base_id {
+        Some(id) => repo
+            .find_commit(id)
+            .and_then(|commit| commit.tree())
+            .map_err(GitError::from)?,
+        None => {
+            let empty = repo
+                .treebuilder(None)
+                .and_then(|builder| builder.write())
+                .map_err(GitError::from)?;
+            repo.find_tree(empty).map_err(GitError::from)?
+        }
+    };
+
+    let index = repo
+        .merge_trees(&base_tree, &our_tree, &their_tree, None)
+        .map_err(GitError::from)?;
+
+    let entry_path = |entry: &Option<git2::IndexEntry>| {
+        entry
+            .as_ref()
+            .map(|e| String::from_utf8_lossy(&e.path).to_string())
+    };
+    let mut conflicted = BTreeSet::new();
+    for conflict in index.conflicts().map_err(GitError::from)? {
+        let conflict = conflict.map_err(GitError::from)?;
+        let kind = match (&conflict.ancestor, &conflict.our, &conflict.their) {
+            (None, Some(_), Some(_)) => "both_added",
+            (Some(_), None, Some(_)) => "deleted_by_us",
+            (Some(_), Some(_), None) => "deleted_by_them",
+            _ => "both_modified",
+        };
+        let Some(path) = entry_path(&conflict.our)
+            .or_else(|| entry_path(&conflict.their))
+            .or_else(|| entry_path(&conflict.ancestor))
+        else {
+            continue;
+        };
+        conflicted.insert(path.clone());
+        preview.conflicts.push(MergeConflictPreview {
+            path,
+            kind: kind.to_string(),
+        });
+    }
+
+    let ours_changed = changed_paths(&repo, &base_tree, &our_tree)?;
+    let theirs_changed = changed_paths(&repo, &base_tree, &their_tree)?;
+    for path in &theirs_changed {
+        if conflicted.contains(path) {
+            continue;
+        }
+        if ours_changed.contains(path) {
+            preview.auto_merged.push(path.clone());
+        } else {
+            preview.incoming_files.push(path.clone());
+        }
+    }
+
+    // Uncommitted changes to files the merge would write block or endanger it
+    let mut status_opts = git2::StatusOptions::new();
+    status_opts.include_untracked(true);
+    let statuses = repo
+        .statuses(Some(&mut status_opts))
+        .map_err(GitError::from)?;
+    preview.dirty_files = statuses
+        .iter()
+        .filter_map(|entry| entry.path().map(str::to_string))
+        .filter(|path| theirs_changed.contains(path))
+        .collect();
+
+    Ok(preview)
+}
+
 /// Abort a merge in progress
 #[tauri::command]
 pub fn git_merge_abort(path: String) -> Result<String, String> {
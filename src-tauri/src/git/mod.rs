@@ -6,13 +6,31 @@
 //! - Better performance
 //! - Consistent cross-platform behavior
 
+pub mod archive;
 mod auth;
+pub mod bisect;
 pub mod branch;
 pub mod commit;
+pub mod commit_message;
+pub mod commit_template;
+pub mod config;
+pub mod conflict_markers;
 pub mod error;
+pub mod fixup;
 pub mod history;
 pub mod merge;
+pub mod patch;
+pub mod rebase;
+pub mod reflog;
 pub mod remote;
+pub mod repo_state;
+pub mod repositories;
+pub mod secrets;
+pub mod ssh;
 pub mod stash;
 pub mod status;
+pub mod sync;
+pub mod trailers;
 pub mod types;
+pub mod watcher;
+mod word_diff;
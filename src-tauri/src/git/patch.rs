@@ -0,0 +1,260 @@
+//! Git Patches
+//!
+//! Share changes without a remote: `git_format_patch` writes commits as
+//! numbered mbox `.patch` files the way `git format-patch` does, and
+//! `git_apply_patch` applies a patch (from this app, `git format-patch` or
+//! `git diff`) to the working tree, the index or both.
+//!
+//! Before applying, every file of the patch is checked on its own so the
+//! result can say which files don't apply and why; nothing is written
+//! unless all of them do.
+
+use super::error::GitError;
+use super::status::invalidate_status_cache;
+use super::types::{ApplyPatchResult, PatchFile, PatchFileResult};
+use git2::{
+    ApplyLocation, ApplyOptions, Delta, Diff, DiffFindOptions, Email, EmailCreateOptions,
+    Repository, Sort,
+};
+use std::path::Path;
+
+/// Longest subject slug in a patch file name, as in git
+const MAX_SLUG_LENGTH: usize = 52;
+
+/// File name part derived from a commit subject
+fn slug(subject: &str) -> String {
+    let mut slug = String::new();
+    for c in subject.chars() {
+        if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= MAX_SLUG_LENGTH {
+            break;
+        }
+    }
+    let slug = slug.trim_matches(|c| c == '-' || c == '.').to_string();
+    if slug.is_empty() {
+        "patch".to_string()
+    } else {
+        slug
+    }
+}
+
+/// `a..b` is the commits in b but not a; a single revision means `rev..HEAD`
+fn range_commits(repo: &Repository, range: &str) -> Result<Vec<git2::Oid>, GitError> {
+    let range = range.trim();
+    if range.is_empty() || range.contains("...") {
+        return Err(GitError::invalid(&format!(
+            "Invalid range '{}'; use <since> or <from>..<to>",
+            range
+        )));
+    }
+    let (from, to) = match range.split_once("..") {
+        Some((from, to)) => (
+            if from.is_empty() { "HEAD" } else { from },
+            if to.is_empty() { "HEAD" } else { to },
+        ),
+        None => (range, "HEAD"),
+    };
+    let resolve = |spec: &str| {
+        repo.revparse_single(spec)
+            .and_then(|object| object.peel_to_commit())
+            .map(|commit| commit.id())
+            .map_err(|_| GitError::not_found(&format!("Cannot resolve '{}'", spec)))
+    };
+
+    let mut revwalk = repo.revwalk().map_err(GitError::from)?;
+    revwalk
+        .set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+        .map_err(GitError::from)?;
+    revwalk.push(resolve(to)?).map_err(GitError::from)?;
+    revwalk.hide(resolve(from)?).map_err(GitError::from)?;
+    revwalk
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(GitError::from)
+}
+
+/// Export the commits of `range` as numbered `.patch` files in `out_dir`
+/// Merge commits are skipped, as `git format-patch` does
+#[tauri::command]
+pub fn git_format_patch(
+    path: String,
+    range: String,
+    out_dir: String,
+) -> Result<Vec<PatchFile>, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let mut commits = Vec::new();
+    for oid in range_commits(&repo, &range)? {
+        let commit = repo.find_commit(oid).map_err(GitError::from)?;
+        if commit.parent_count() <= 1 {
+            commits.push(commit);
+        }
+    }
+    if commits.is_empty() {
+        return Err(GitError::not_found(&format!("No commits in {}", range)).into());
+    }
+
+    let out_dir = Path::new(&out_dir);
+    std::fs::create_dir_all(out_dir)
+        .map_err(|e| format!("Failed to create {}: {}", out_dir.display(), e))?;
+
+    let total = commits.len();
+    let mut written = Vec::new();
+    for (index, commit) in commits.iter().enumerate() {
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree().map_err(GitError::from)?),
+            Err(_) => None,
+        };
+        let tree = commit.tree().map_err(GitError::from)?;
+        let mut diff = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+            .map_err(GitError::from)?;
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))
+            .map_err(GitError::from)?;
+
+        let subject = commit.summary().unwrap_or_default().to_string();
+        let message = commit.message().unwrap_or_default();
+        let body = message
+            .split_once('\n')
+            .map(|(_, body)| body.trim())
+            .unwrap_or("");
+        let email = Email::from_diff(
+            &diff,
+            index + 1,
+            total,
+            &commit.id(),
+            subject.as_str(),
+            body,
+            &commit.author(),
+            &mut EmailCreateOptions::new(),
+        )
+        .map_err(GitError::from)?;
+
+        let file = out_dir.join(format!("{:04}-{}.patch", index + 1, slug(&subject)));
+        std::fs::write(&file, email.as_slice())
+            .map_err(|e| format!("Failed to write {}: {}", file.display(), e))?;
+        written.push(PatchFile {
+            path: file.to_string_lossy().to_string(),
+            commit_id: commit.id().to_string(),
+            subject,
+        });
+    }
+
+    println!(
+        "[GitPatch] Wrote {} patch(es) to {}",
+        written.len(),
+        out_dir.display()
+    );
+    Ok(written)
+}
+
+fn change_name(status: Delta) -> &'static str {
+    match status {
+        Delta::Added => "added",
+        Delta::Deleted => "deleted",
+        Delta::Renamed => "renamed",
+        Delta::Copied => "copied",
+        _ => "modified",
+    }
+}
+
+/// Check one file of the patch against the target
+fn check_delta(
+    repo: &Repository,
+    diff: &Diff,
+    index: usize,
+    location: ApplyLocation,
+) -> Result<(), git2::Error> {
+    let mut current = 0;
+    let mut opts = ApplyOptions::new();
+    opts.check(true);
+    // Returning false skips a file
+    opts.delta_callback(move |_| {
+        let selected = current == index;
+        current += 1;
+        selected
+    });
+    repo.apply(diff, location, Some(&mut opts))
+}
+
+/// Apply a patch to the working tree (default), the index or both
+/// With `check_only` nothing is written; the result says which files would apply
+#[tauri::command]
+pub fn git_apply_patch(
+    path: String,
+    patch_content: String,
+    check_only: Option<bool>,
+    location: Option<String>,
+) -> Result<ApplyPatchResult, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let check_only = check_only.unwrap_or(false);
+    let location_name = location.unwrap_or_else(|| "workdir".to_string());
+    let location = match location_name.as_str() {
+        "workdir" => ApplyLocation::WorkDir,
+        "index" => ApplyLocation::Index,
+        "both" => ApplyLocation::Both,
+        other => {
+            return Err(GitError::invalid(&format!(
+                "Unknown location '{}'; use workdir, index or both",
+                other
+            ))
+            .into())
+        }
+    };
+
+    let diff = Diff::from_buffer(patch_content.as_bytes())
+        .map_err(|e| format!("Not a valid patch: {}", e.message()))?;
+    if diff.deltas().len() == 0 {
+        return Err(GitError::invalid("The patch contains no changes").into());
+    }
+
+    let files: Vec<PatchFileResult> = diff
+        .deltas()
+        .enumerate()
+        .map(|(index, delta)| {
+            let file = if delta.status() == Delta::Deleted {
+                delta.old_file()
+            } else {
+                delta.new_file()
+            };
+            let error = check_delta(&repo, &diff, index, location)
+                .err()
+                .map(|e| e.message().to_string());
+            PatchFileResult {
+                path: file
+                    .path()
+                    .map(|p| p.to_string_lossy().replace('\\', "/"))
+                    .unwrap_or_default(),
+                change: change_name(delta.status()).to_string(),
+                applies: error.is_none(),
+                error,
+            }
+        })
+        .collect();
+
+    let failing = files.iter().filter(|f| !f.applies).count();
+    let applies = failing == 0;
+    if applies && !check_only {
+        repo.apply(&diff, location, None).map_err(GitError::from)?;
+        if let Some(workdir) = repo.workdir() {
+            invalidate_status_cache(workdir);
+        }
+    }
+
+    let message = if !applies {
+        format!("{} of {} file(s) do not apply", failing, files.len())
+    } else if check_only {
+        format!("All {} file(s) apply cleanly", files.len())
+    } else {
+        format!("Applied {} file(s)", files.len())
+    };
+    Ok(ApplyPatchResult {
+        applied: applies && !check_only,
+        check_only,
+        location: location_name,
+        files,
+        message,
+    })
+}
@@ -0,0 +1,202 @@
+//! Git Rebase Operations
+//!
+//! Native libgit2 implementation for rebase, continue, skip, and abort.
+//! Progress is emitted as `git:rebase-progress` while commits are replayed.
+
+use super::error::GitError;
+use super::types::{RebaseProgress, RebaseResult};
+use git2::{Rebase, RebaseOptions, Repository, Signature};
+use tauri::Emitter;
+
+/// Resolve a branch name, ref or commit-ish to an annotated commit
+fn annotated_commit<'r>(
+    repo: &'r Repository,
+    spec: &str,
+) -> Result<git2::AnnotatedCommit<'r>, GitError> {
+    let commit = repo
+        .revparse_single(spec)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|_| GitError::not_found(&format!("Cannot resolve '{}'", spec)))?;
+    repo.find_annotated_commit(commit.id())
+        .map_err(GitError::from)
+}
+
+/// Paths with unresolved conflicts in the index
+pub(super) fn conflicted_paths(repo: &Repository) -> Result<Vec<String>, GitError> {
+    let index = repo.index().map_err(GitError::from)?;
+    if !index.has_conflicts() {
+        return Ok(Vec::new());
+    }
+
+    let conflicts = index
+        .conflicts()
+        .map_err(GitError::from)?
+        .filter_map(|c| c.ok())
+        .filter_map(|c| {
+            c.our
+                .or(c.their)
+                .or(c.ancestor)
+                .and_then(|e| std::str::from_utf8(&e.path).ok().map(|s| s.to_string()))
+        })
+        .collect();
+    Ok(conflicts)
+}
+
+/// Commit the current operation; an already-applied (empty) patch is skipped
+fn commit_current(rebase: &mut Rebase, sig: &Signature) -> Result<(), GitError> {
+    match rebase.commit(None, sig, None) {
+        Ok(_) => Ok(()),
+        Err(e) if e.code() == git2::ErrorCode::Applied => Ok(()),
+        Err(e) => Err(GitError::from(e)),
+    }
+}
+
+/// Replay remaining operations until done or a conflict stops the rebase
+pub(super) fn run_rebase(
+    window: &tauri::Window,
+    repo: &Repository,
+    rebase: &mut Rebase,
+    sig: &Signature,
+) -> Result<RebaseResult, GitError> {
+    let total = rebase.len();
+
+    while let Some(op) = rebase.next() {
+        let op = op.map_err(GitError::from)?;
+        let current = rebase.operation_current().map(|i| i + 1).unwrap_or(0);
+        let commit_id = op.id().to_string();
+        let summary = repo
+            .find_commit(op.id())
+            .ok()
+            .and_then(|c| c.summary().map(|s| s.to_string()))
+            .unwrap_or_default();
+
+        let _ = window.emit(
+            "git:rebase-progress",
+            RebaseProgress {
+                current,
+                total,
+                commit_id: commit_id.clone(),
+                summary: summary.clone(),
+            },
+        );
+
+        let conflicts = conflicted_paths(repo)?;
+        if !conflicts.is_empty() {
+            return Ok(RebaseResult {
+                status: "conflicts".to_string(),
+                current,
+                total,
+                stopped_at: Some(commit_id.clone()),
+                message: format!(
+                    "Conflicts while applying {} ({}). Resolve them and continue, skip, or abort.",
+                    &commit_id[..7.min(commit_id.len())],
+                    summary
+                ),
+                conflicts,
+            });
+        }
+
+        commit_current(rebase, sig)?;
+    }
+
+    rebase.finish(Some(sig)).map_err(GitError::from)?;
+
+    Ok(RebaseResult {
+        status: "completed".to_string(),
+        current: total,
+        total,
+        stopped_at: None,
+        conflicts: Vec::new(),
+        message: format!("Successfully rebased {} commit(s)", total),
+    })
+}
+
+/// Rebase the current branch onto `upstream`
+/// When `onto` is given, commits after `upstream` are replayed onto `onto` instead
+#[tauri::command]
+pub fn git_rebase(
+    window: tauri::Window,
+    path: String,
+    upstream: String,
+    onto: Option<String>,
+) -> Result<RebaseResult, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+
+    if repo.state() != git2::RepositoryState::Clean {
+        return Err(format!(
+            "Cannot rebase: repository is in {:?} state",
+            repo.state()
+        ));
+    }
+
+    let upstream_commit = annotated_commit(&repo, &upstream)?;
+    let onto_commit = match onto.as_deref() {
+        Some(spec) => Some(annotated_commit(&repo, spec)?),
+        None => None,
+    };
+
+    let sig = repo.signature().map_err(GitError::from)?;
+    let mut opts = RebaseOptions::new();
+    let mut rebase = repo
+        .rebase(
+            None,
+            Some(&upstream_commit),
+            onto_commit.as_ref(),
+            Some(&mut opts),
+        )
+        .map_err(GitError::from)?;
+
+    Ok(run_rebase(&window, &repo, &mut rebase, &sig)?)
+}
+
+/// Continue a rebase after conflicts have been resolved and staged
+#[tauri::command]
+pub fn git_rebase_continue(window: tauri::Window, path: String) -> Result<RebaseResult, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let mut rebase = repo.open_rebase(None).map_err(GitError::from)?;
+
+    let conflicts = conflicted_paths(&repo)?;
+    if !conflicts.is_empty() {
+        return Err(GitError::conflict(&format!(
+            "Resolve conflicts before continuing: {}",
+            conflicts.join(", ")
+        ))
+        .into());
+    }
+
+    let sig = repo.signature().map_err(GitError::from)?;
+    if rebase.operation_current().is_some() {
+        commit_current(&mut rebase, &sig)?;
+    }
+
+    Ok(run_rebase(&window, &repo, &mut rebase, &sig)?)
+}
+
+/// Skip the commit that stopped the rebase and continue with the next one
+#[tauri::command]
+pub fn git_rebase_skip(window: tauri::Window, path: String) -> Result<RebaseResult, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let mut rebase = repo.open_rebase(None).map_err(GitError::from)?;
+
+    // Drop the partially applied commit from the index and working tree
+    let head = repo
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(GitError::from)?;
+    repo.reset(head.as_object(), git2::ResetType::Hard, None)
+        .map_err(GitError::from)?;
+
+    let sig = repo.signature().map_err(GitError::from)?;
+    Ok(run_rebase(&window, &repo, &mut rebase, &sig)?)
+}
+
+/// Abort a rebase in progress and restore the original branch
+#[tauri::command]
+pub fn git_rebase_abort(path: String) -> Result<String, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let mut rebase = repo.open_rebase(None).map_err(GitError::from)?;
+
+    rebase.abort().map_err(GitError::from)?;
+
+    Ok("Rebase aborted".to_string())
+}
@@ -0,0 +1,160 @@
+//! Git Reflog Operations
+//!
+//! Native libgit2 implementation for reading the reflog and recovering
+//! commits from it (e.g. after a bad reset).
+
+use super::error::GitError;
+use super::history::format_time;
+use super::types::ReflogEntry;
+use git2::Repository;
+
+/// Resolve the commit a reflog entry points to
+fn reflog_commit<'r>(
+    repo: &'r Repository,
+    reference: &str,
+    index: usize,
+) -> Result<git2::Commit<'r>, GitError> {
+    let reflog = repo.reflog(reference).map_err(GitError::from)?;
+    let entry = reflog.get(index).ok_or_else(|| {
+        GitError::not_found(&format!(
+            "Reflog entry {}@{{{}}} does not exist",
+            reference, index
+        ))
+    })?;
+
+    repo.find_commit(entry.id_new()).map_err(|_| {
+        GitError::not_found(&format!(
+            "Commit {} from {}@{{{}}} no longer exists",
+            entry.id_new(),
+            reference,
+            index
+        ))
+    })
+}
+
+/// Get reflog entries for a reference (default HEAD), most recent first
+#[tauri::command]
+pub fn git_reflog(
+    path: String,
+    reference: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<ReflogEntry>, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let reference = reference.unwrap_or_else(|| "HEAD".to_string());
+    let reflog = repo.reflog(&reference).map_err(GitError::from)?;
+
+    let entries = reflog
+        .iter()
+        .take(limit.unwrap_or(100))
+        .enumerate()
+        .map(|(index, entry)| {
+            let committer = entry.committer();
+            ReflogEntry {
+                index,
+                id: entry.id_new().to_string(),
+                old_id: entry.id_old().to_string(),
+                message: entry.message().unwrap_or("").to_string(),
+                committer: committer.name().unwrap_or("").to_string(),
+                email: committer.email().unwrap_or("").to_string(),
+                date: format_time(committer.when()),
+                summary: repo
+                    .find_commit(entry.id_new())
+                    .ok()
+                    .and_then(|c| c.summary().map(|s| s.to_string())),
+            }
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+/// Check out the commit of a reflog entry
+/// With `branch`, a new branch is created there and checked out; otherwise HEAD is detached
+#[tauri::command]
+pub fn git_checkout_reflog_entry(
+    path: String,
+    reference: Option<String>,
+    index: usize,
+    branch: Option<String>,
+) -> Result<String, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let reference = reference.unwrap_or_else(|| "HEAD".to_string());
+    let commit = reflog_commit(&repo, &reference, index)?;
+
+    // Create the branch first: a name that already exists fails before the
+    // working tree changes
+    let mut created = match &branch {
+        Some(name) => Some(repo.branch(name, &commit, false).map_err(GitError::from)?),
+        None => None,
+    };
+
+    let mut checkout_opts = git2::build::CheckoutBuilder::new();
+    checkout_opts.safe();
+    if let Err(e) = repo.checkout_tree(commit.as_object(), Some(&mut checkout_opts)) {
+        if let Some(created) = created.as_mut() {
+            let _ = created.delete();
+        }
+        return Err(GitError::from(e).into());
+    }
+
+    match branch {
+        Some(name) => {
+            repo.set_head(&format!("refs/heads/{}", name))
+                .map_err(GitError::from)?;
+            Ok(format!(
+                "Created branch {} at {}@{{{}}} ({})",
+                name,
+                reference,
+                index,
+                &commit.id().to_string()[..7]
+            ))
+        }
+        None => {
+            repo.set_head_detached(commit.id())
+                .map_err(GitError::from)?;
+            Ok(format!(
+                "HEAD detached at {}@{{{}}} ({})",
+                reference,
+                index,
+                &commit.id().to_string()[..7]
+            ))
+        }
+    }
+}
+
+/// Reset the current branch to the commit of a reflog entry
+#[tauri::command]
+pub fn git_reset_to_reflog_entry(
+    path: String,
+    reference: Option<String>,
+    index: usize,
+    mode: Option<String>,
+) -> Result<String, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let reference = reference.unwrap_or_else(|| "HEAD".to_string());
+    let commit = reflog_commit(&repo, &reference, index)?;
+
+    let mode = mode.unwrap_or_else(|| "mixed".to_string());
+    let reset_type = match mode.to_lowercase().as_str() {
+        "soft" => git2::ResetType::Soft,
+        "mixed" => git2::ResetType::Mixed,
+        "hard" => git2::ResetType::Hard,
+        _ => {
+            return Err(format!(
+                "Invalid reset mode: {}. Use soft, mixed, or hard.",
+                mode
+            ))
+        }
+    };
+
+    repo.reset(commit.as_object(), reset_type, None)
+        .map_err(GitError::from)?;
+
+    Ok(format!(
+        "Reset to {}@{{{}}} ({}, {})",
+        reference,
+        index,
+        &commit.id().to_string()[..7],
+        mode
+    ))
+}
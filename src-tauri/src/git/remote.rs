@@ -4,16 +4,20 @@
 
 use super::auth::AuthCallbacks;
 use super::error::GitError;
+use super::ssh::PassphrasePrompt;
 use super::types::{CloneProgress, RemoteInfo};
 use git2::{AutotagOption, Repository};
 
 /// Push to remote repository
-#[tauri::command]
+/// Runs off the main thread so it can wait for a key passphrase from the frontend
+#[tauri::command(async)]
 pub fn git_push(
+    app: tauri::AppHandle,
     path: String,
     remote_name: Option<String>,
     branch_name: Option<String>,
     force: Option<bool>,
+    operation_id: Option<String>,
 ) -> Result<String, String> {
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
 
@@ -37,7 +41,7 @@ pub fn git_push(
         format!("refs/heads/{}:refs/heads/{}", branch, branch)
     };
 
-    let mut push_opts = AuthCallbacks::push_options();
+    let mut push_opts = AuthCallbacks::push_options(Some(PassphrasePrompt::new(app, operation_id)));
 
     remote
         .push(&[&refspec], Some(&mut push_opts))
@@ -47,11 +51,13 @@ pub fn git_push(
 }
 
 /// Pull from remote repository (fetch + merge)
-#[tauri::command]
+#[tauri::command(async)]
 pub fn git_pull(
+    app: tauri::AppHandle,
     path: String,
     remote_name: Option<String>,
     branch_name: Option<String>,
+    operation_id: Option<String>,
 ) -> Result<String, String> {
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
 
@@ -70,7 +76,8 @@ pub fn git_pull(
     };
 
     // Fetch
-    let mut fetch_opts = AuthCallbacks::fetch_options();
+    let mut fetch_opts =
+        AuthCallbacks::fetch_options(Some(PassphrasePrompt::new(app, operation_id)));
     let refspec = format!(
         "refs/heads/{}:refs/remotes/{}/{}",
         branch, remote_name, branch
@@ -164,8 +171,23 @@ pub fn git_pull(
 }
 
 /// Fetch from remote repository
-#[tauri::command]
-pub fn git_fetch(path: String, remote_name: Option<String>) -> Result<String, String> {
+/// Auto-fetch passes `background`, which makes it a no-op while auto-fetch is paused
+#[tauri::command(async)]
+pub fn git_fetch(
+    app: tauri::AppHandle,
+    path: String,
+    remote_name: Option<String>,
+    operation_id: Option<String>,
+    background: Option<bool>,
+) -> Result<String, String> {
+    if background.unwrap_or(false)
+        && crate::background_pause::is_paused(
+            crate::background_pause::BackgroundSubsystem::AutoFetch,
+        )
+    {
+        return Ok("Auto-fetch is paused".to_string());
+    }
+
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
 
     let remote_name = remote_name.as_deref().unwrap_or("origin");
@@ -173,7 +195,8 @@ pub fn git_fetch(path: String, remote_name: Option<String>) -> Result<String, St
         .find_remote(remote_name)
         .map_err(|e| GitError::from(e))?;
 
-    let mut fetch_opts = AuthCallbacks::fetch_options();
+    let mut fetch_opts =
+        AuthCallbacks::fetch_options(Some(PassphrasePrompt::new(app, operation_id)));
     fetch_opts.download_tags(AutotagOption::All);
 
     remote
@@ -184,21 +207,23 @@ pub fn git_fetch(path: String, remote_name: Option<String>) -> Result<String, St
 }
 
 /// Clone a repository
-#[tauri::command]
+#[tauri::command(async)]
 pub fn git_clone(
     window: tauri::Window,
     url: String,
     destination: String,
     branch: Option<String>,
     _depth: Option<u32>,
+    operation_id: Option<String>,
 ) -> Result<String, String> {
-    use tauri::Emitter;
+    use tauri::{Emitter, Manager};
 
     let mut builder = git2::build::RepoBuilder::new();
 
     // Set up fetch options with BOTH auth and progress callbacks
     let window_clone = window.clone();
-    let fetch_opts = AuthCallbacks::fetch_options_with_progress(move |progress| {
+    let prompt = PassphrasePrompt::new(window.app_handle().clone(), operation_id);
+    let fetch_opts = AuthCallbacks::fetch_options_with_progress(Some(prompt), move |progress| {
         let percent = if progress.total_objects() > 0 {
             ((progress.received_objects() as f64 / progress.total_objects() as f64) * 100.0) as u32
         } else {
@@ -0,0 +1,171 @@
+//! Repository State
+//!
+//! Reports the operation a repository is in the middle of — merge, rebase,
+//! cherry-pick, revert, `git am` or bisect — with the message being composed,
+//! the refs git keeps for it and the rebase position, so the UI can show a
+//! "Rebase in progress — continue / abort" banner instead of letting the next
+//! commit fail. State is read from the same files the git CLI writes, so
+//! operations started in a terminal are picked up too.
+
+use super::error::GitError;
+use super::rebase::conflicted_paths;
+use super::types::{RepoState, RepoStateProgress, RepoStateRef};
+use git2::{Oid, Repository, RepositoryState};
+use std::fs;
+use std::path::Path;
+
+/// Refs describing an operation, in the order they are shown
+const STATE_REFS: [&str; 6] = [
+    "MERGE_HEAD",
+    "CHERRY_PICK_HEAD",
+    "REVERT_HEAD",
+    "REBASE_HEAD",
+    "ORIG_HEAD",
+    "refs/bisect/bad",
+];
+
+fn operation_name(state: RepositoryState) -> &'static str {
+    match state {
+        RepositoryState::Clean => "clean",
+        RepositoryState::Merge => "merge",
+        RepositoryState::Revert => "revert",
+        RepositoryState::RevertSequence => "revert-sequence",
+        RepositoryState::CherryPick => "cherry-pick",
+        RepositoryState::CherryPickSequence => "cherry-pick-sequence",
+        RepositoryState::Bisect => "bisect",
+        RepositoryState::Rebase => "rebase",
+        RepositoryState::RebaseInteractive => "rebase-interactive",
+        RepositoryState::RebaseMerge => "rebase-merge",
+        RepositoryState::ApplyMailbox => "apply-mailbox",
+        RepositoryState::ApplyMailboxOrRebase => "apply-mailbox-or-rebase",
+    }
+}
+
+fn actions_for(state: RepositoryState) -> &'static [&'static str] {
+    match state {
+        RepositoryState::Clean => &[],
+        RepositoryState::Merge => &["commit", "abort"],
+        RepositoryState::Revert
+        | RepositoryState::RevertSequence
+        | RepositoryState::CherryPick
+        | RepositoryState::CherryPickSequence => &["continue", "skip", "abort"],
+        RepositoryState::Bisect => &["reset"],
+        RepositoryState::Rebase
+        | RepositoryState::RebaseInteractive
+        | RepositoryState::RebaseMerge
+        | RepositoryState::ApplyMailbox
+        | RepositoryState::ApplyMailboxOrRebase => &["continue", "skip", "abort"],
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn short_id(id: &str) -> String {
+    id[..7.min(id.len())].to_string()
+}
+
+fn state_ref(repo: &Repository, name: &str, oid: Oid) -> RepoStateRef {
+    RepoStateRef {
+        name: name.to_string(),
+        commit: oid.to_string(),
+        summary: repo
+            .find_commit(oid)
+            .ok()
+            .and_then(|c| c.summary().map(|s| s.to_string()))
+            .unwrap_or_default(),
+    }
+}
+
+fn state_refs(repo: &Repository) -> Vec<RepoStateRef> {
+    let mut refs = Vec::new();
+    for name in STATE_REFS {
+        // MERGE_HEAD lists one commit per merged head
+        if name == "MERGE_HEAD" {
+            let heads = read_trimmed(&repo.path().join(name)).unwrap_or_default();
+            for oid in heads.lines().filter_map(|l| Oid::from_str(l.trim()).ok()) {
+                refs.push(state_ref(repo, name, oid));
+            }
+            continue;
+        }
+        if let Ok(oid) = repo.refname_to_id(name) {
+            refs.push(state_ref(repo, name, oid));
+        }
+    }
+    refs
+}
+
+/// Position of a rebase or `git am`, from `rebase-merge/` or `rebase-apply/`
+fn sequence_progress(repo: &Repository) -> Option<RepoStateProgress> {
+    let (dir, current_file, total_file) = if repo.path().join("rebase-merge").is_dir() {
+        (repo.path().join("rebase-merge"), "msgnum", "end")
+    } else if repo.path().join("rebase-apply").is_dir() {
+        (repo.path().join("rebase-apply"), "next", "last")
+    } else {
+        return None;
+    };
+    let number = |file: &str| {
+        read_trimmed(&dir.join(file))
+            .and_then(|n| n.parse::<usize>().ok())
+            .unwrap_or(0)
+    };
+    Some(RepoStateProgress {
+        head_name: read_trimmed(&dir.join("head-name"))
+            .map(|name| name.trim_start_matches("refs/heads/").to_string()),
+        onto: read_trimmed(&dir.join("onto")).map(|onto| short_id(&onto)),
+        current: number(current_file),
+        total: number(total_file),
+    })
+}
+
+/// Operation in progress, its message, refs and position
+#[tauri::command]
+pub fn git_repo_state(path: String) -> Result<RepoState, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let bisecting = repo.path().join("BISECT_START").exists();
+
+    let mut state = repo.state();
+    // libgit2 only reports a bisect when nothing else is in progress
+    if state == RepositoryState::Clean && bisecting {
+        state = RepositoryState::Bisect;
+    }
+    let in_progress = state != RepositoryState::Clean;
+
+    let detached = repo.head_detached().unwrap_or(false);
+    let head = repo.head().ok().and_then(|head| {
+        if detached {
+            head.target().map(|oid| short_id(&oid.to_string()))
+        } else {
+            head.shorthand().map(|s| s.to_string())
+        }
+    });
+
+    let message = repo
+        .message()
+        .ok()
+        .or_else(|| fs::read_to_string(repo.path().join("SQUASH_MSG")).ok())
+        .filter(|m| !m.trim().is_empty());
+
+    // ORIG_HEAD outlives the operation that wrote it
+    let refs = state_refs(&repo)
+        .into_iter()
+        .filter(|r| in_progress || r.name != "ORIG_HEAD")
+        .collect();
+
+    Ok(RepoState {
+        operation: operation_name(state).to_string(),
+        in_progress,
+        head,
+        detached,
+        message,
+        refs,
+        progress: sequence_progress(&repo),
+        conflicts: conflicted_paths(&repo)?,
+        actions: actions_for(state).iter().map(|a| a.to_string()).collect(),
+        bisecting,
+    })
+}
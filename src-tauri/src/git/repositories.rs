@@ -0,0 +1,317 @@
+//! Git Repository Registry
+//!
+//! Workspaces can hold several repositories. `git_list_repositories` finds
+//! them — the repository containing the workspace, nested clones, submodules
+//! and linked worktrees — and registers each under a stable ID derived from
+//! its path. Status and branch commands accept that ID in place of a path,
+//! and `git-status-changed` deltas carry it, so the source control panel can
+//! show one section per repository.
+//!
+//! The scan depth and skipped folders follow `git.repositoryScanMaxDepth` and
+//! `git.repositoryScanIgnoredFolders`.
+
+use super::error::GitError;
+use super::types::{GitRepository, StatusEntry};
+use git2::Repository;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+const DEFAULT_SCAN_DEPTH: usize = 3;
+
+const DEFAULT_IGNORED_FOLDERS: [&str; 1] = ["node_modules"];
+
+#[derive(Default)]
+struct RegistryInner {
+    repos: HashMap<String, GitRepository>,
+    /// Repository IDs found under each workspace root
+    workspaces: HashMap<PathBuf, Vec<String>>,
+}
+
+/// Repositories discovered in open workspaces, by ID
+#[derive(Default)]
+pub struct GitRepoRegistry {
+    inner: Mutex<RegistryInner>,
+}
+
+impl GitRepoRegistry {
+    /// The working tree of a registered repository ID; anything else is
+    /// returned unchanged, so commands keep accepting plain paths
+    pub fn resolve(&self, path: String) -> String {
+        self.inner
+            .lock()
+            .ok()
+            .and_then(|inner| inner.repos.get(&path).map(|repo| repo.path.clone()))
+            .unwrap_or(path)
+    }
+
+    /// ID of the registered repository with this working tree
+    pub(super) fn id_for_workdir(&self, workdir: &Path) -> Option<String> {
+        let inner = self.inner.lock().ok()?;
+        inner
+            .repos
+            .values()
+            .find(|repo| Path::new(&repo.path) == workdir)
+            .map(|repo| repo.id.clone())
+    }
+
+    fn replace_workspace(&self, root: PathBuf, repos: &[GitRepository]) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        let ids: Vec<String> = repos.iter().map(|repo| repo.id.clone()).collect();
+        if let Some(previous) = inner.workspaces.insert(root, ids) {
+            let still_used: Vec<String> = inner.workspaces.values().flatten().cloned().collect();
+            for id in previous {
+                if !still_used.contains(&id) {
+                    inner.repos.remove(&id);
+                }
+            }
+        }
+        for repo in repos {
+            inner.repos.insert(repo.id.clone(), repo.clone());
+        }
+    }
+
+    fn workspace_repos(&self, root: &Path) -> Option<Vec<GitRepository>> {
+        let inner = self.inner.lock().ok()?;
+        let ids = inner.workspaces.get(root)?;
+        Some(
+            ids.iter()
+                .filter_map(|id| inner.repos.get(id).cloned())
+                .collect(),
+        )
+    }
+}
+
+/// Working trees are canonicalized so IDs don't depend on how a path was spelled
+pub(super) fn canonical(path: &Path) -> PathBuf {
+    plain_canonicalize(path).unwrap_or_else(|| path.to_path_buf())
+}
+
+/// `canonicalize` without the `\\?\` prefix Windows adds
+fn plain_canonicalize(path: &Path) -> Option<PathBuf> {
+    let canonical = path.canonicalize().ok()?;
+    #[cfg(windows)]
+    {
+        let text = canonical.to_string_lossy();
+        if let Some(stripped) = text.strip_prefix(r"\\?\") {
+            if !stripped.starts_with("UNC\\") {
+                return Some(PathBuf::from(stripped));
+            }
+        }
+    }
+    Some(canonical)
+}
+
+fn repo_id(workdir: &Path) -> String {
+    let digest = Sha256::digest(workdir.to_string_lossy().as_bytes());
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("repo-{}", &hex[..12])
+}
+
+/// `to` relative to `from`, walking up with `..` when needed
+fn relative_path(from: &Path, to: &Path) -> String {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().to_string()),
+    );
+    parts.join("/")
+}
+
+fn describe(
+    repo: &Repository,
+    workdir: &Path,
+    root: &Path,
+    kind: &str,
+    parent_id: Option<String>,
+) -> GitRepository {
+    let detached = repo.head_detached().unwrap_or(false);
+    let head = repo.head().ok().and_then(|head| {
+        if detached {
+            head.target().map(|oid| oid.to_string()[..7].to_string())
+        } else {
+            head.shorthand().map(|s| s.to_string())
+        }
+    });
+    GitRepository {
+        id: repo_id(workdir),
+        name: workdir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| workdir.to_string_lossy().to_string()),
+        path: workdir.to_string_lossy().to_string(),
+        relative_path: relative_path(root, workdir),
+        git_dir: repo.path().to_string_lossy().to_string(),
+        kind: kind.to_string(),
+        parent_id,
+        head,
+        detached,
+    }
+}
+
+fn scan_settings(workspace: &str) -> (usize, Vec<String>) {
+    let depth = crate::configuration_manager::resolve_setting(
+        Some(workspace),
+        "git.repositoryScanMaxDepth",
+    )
+    .and_then(|v| v.as_u64())
+    .map(|d| d as usize)
+    .unwrap_or(DEFAULT_SCAN_DEPTH);
+    let ignored = crate::configuration_manager::resolve_setting(
+        Some(workspace),
+        "git.repositoryScanIgnoredFolders",
+    )
+    .and_then(|v| v.as_array().cloned())
+    .map(|folders| {
+        folders
+            .iter()
+            .filter_map(|f| f.as_str().map(str::to_string))
+            .collect()
+    })
+    .unwrap_or_else(|| {
+        DEFAULT_IGNORED_FOLDERS
+            .iter()
+            .map(|f| f.to_string())
+            .collect()
+    });
+    (depth, ignored)
+}
+
+/// Find the repositories of a workspace
+fn discover(workspace: &str) -> Result<(PathBuf, Vec<GitRepository>), GitError> {
+    let root = plain_canonicalize(Path::new(workspace))
+        .ok_or_else(|| GitError::not_found(&format!("Workspace not found: {}", workspace)))?;
+    let (max_depth, ignored) = scan_settings(workspace);
+
+    // Working trees, in discovery order (parents before children)
+    let mut found: Vec<(PathBuf, Repository, &str)> = Vec::new();
+
+    if let Ok(repo) = Repository::discover(&root) {
+        if let Some(workdir) = repo.workdir().map(canonical) {
+            let kind = if workdir == root { "root" } else { "parent" };
+            found.push((workdir, repo, kind));
+        }
+    }
+
+    let walker = walkdir::WalkDir::new(&root)
+        .min_depth(1)
+        .max_depth(max_depth)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0
+                || entry.file_type().is_dir()
+                    && !name.starts_with('.')
+                    && !ignored.iter().any(|folder| *folder == name)
+        });
+    for entry in walker.filter_map(|e| e.ok()) {
+        if !entry.path().join(".git").exists() {
+            continue;
+        }
+        let Ok(repo) = Repository::open(entry.path()) else {
+            continue;
+        };
+        let Some(workdir) = repo.workdir().map(canonical) else {
+            continue;
+        };
+        if found.iter().any(|(existing, _, _)| *existing == workdir) {
+            continue;
+        }
+        let kind = if repo.is_worktree() {
+            "worktree"
+        } else {
+            "nested"
+        };
+        found.push((workdir, repo, kind));
+    }
+
+    // Submodules are nested repositories their parent knows about
+    let mut submodules: Vec<PathBuf> = Vec::new();
+    for (workdir, repo, _) in &found {
+        if let Ok(modules) = repo.submodules() {
+            submodules.extend(modules.iter().map(|m| canonical(&workdir.join(m.path()))));
+        }
+    }
+
+    let mut repos = Vec::new();
+    for (workdir, repo, kind) in &found {
+        let kind = if *kind == "nested" && submodules.contains(workdir) {
+            "submodule"
+        } else {
+            kind
+        };
+        let parent_id = found
+            .iter()
+            .filter(|(other, _, _)| other != workdir && workdir.starts_with(other))
+            .max_by_key(|(other, _, _)| other.components().count())
+            .map(|(other, _, _)| repo_id(other));
+        repos.push(describe(repo, workdir, &root, kind, parent_id));
+    }
+    Ok((root, repos))
+}
+
+/// Repositories in a workspace: the one containing it, nested clones,
+/// submodules and worktrees. Results are cached until `rescan` is set.
+#[tauri::command]
+pub fn git_list_repositories(
+    registry: State<'_, GitRepoRegistry>,
+    workspace: String,
+    rescan: Option<bool>,
+) -> Result<Vec<GitRepository>, String> {
+    if !rescan.unwrap_or(false) {
+        let root = canonical(Path::new(&workspace));
+        if let Some(repos) = registry.workspace_repos(&root) {
+            return Ok(repos);
+        }
+    }
+    let (root, repos) = discover(&workspace)?;
+    println!(
+        "[GitRepositories] {} repositories in {}",
+        repos.len(),
+        root.display()
+    );
+    registry.replace_workspace(root, &repos);
+    Ok(repos)
+}
+
+/// Watch every repository of a workspace; returns each baseline by repository ID
+#[tauri::command]
+pub fn git_watch_repositories(
+    app: AppHandle,
+    registry: State<'_, GitRepoRegistry>,
+    workspace: String,
+) -> Result<HashMap<String, Vec<StatusEntry>>, String> {
+    let repos = git_list_repositories(registry.clone(), workspace, None)?;
+    let mut baselines = HashMap::new();
+    for repo in repos {
+        match super::watcher::git_watch_repository(app.clone(), registry.clone(), repo.path.clone())
+        {
+            Ok(baseline) => {
+                baselines.insert(repo.id, baseline);
+            }
+            Err(e) => eprintln!("[GitRepositories] Failed to watch {}: {}", repo.path, e),
+        }
+    }
+    Ok(baselines)
+}
+
+/// Stop watching the repositories of a workspace
+#[tauri::command]
+pub fn git_unwatch_repositories(
+    registry: State<'_, GitRepoRegistry>,
+    workspace: String,
+) -> Result<(), String> {
+    let root = canonical(Path::new(&workspace));
+    for repo in registry.workspace_repos(&root).unwrap_or_default() {
+        super::watcher::git_unwatch_repository(registry.clone(), repo.path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+//! Git Secret Checks
+//!
+//! Scans the lines a commit would add (HEAD vs index) for secrets using the
+//! shared secret scanner. `git_commit` runs this as a pre-commit check and
+//! refuses to commit only when `secrets.blockCommits` is enabled.
+
+use super::error::GitError;
+use crate::secret_scanner::{self, SecretFinding};
+use git2::{DiffOptions, Repository};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Staged blobs larger than this are not scanned
+const MAX_SCANNED_BLOB_SIZE: u64 = 1024 * 1024;
+
+/// Secrets in lines added by the staged changes, before baseline filtering
+fn scan_staged(repo: &Repository) -> Result<Vec<SecretFinding>, GitError> {
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let index = repo.index().map_err(GitError::from)?;
+
+    let mut opts = DiffOptions::new();
+    opts.context_lines(0);
+    let diff = repo
+        .diff_tree_to_index(head_tree.as_ref(), Some(&index), Some(&mut opts))
+        .map_err(GitError::from)?;
+
+    // Added lines per file, collected first so each file is scanned once
+    let added: RefCell<HashMap<String, Vec<(u32, String)>>> = RefCell::new(HashMap::new());
+    diff.foreach(
+        &mut |_, _| true,
+        Some(&mut |delta, _| !delta.new_file().is_binary()),
+        None,
+        Some(&mut |delta, _, line| {
+            if line.origin() != '+' || delta.new_file().size() > MAX_SCANNED_BLOB_SIZE {
+                return true;
+            }
+            let (Some(path), Some(line_number)) = (delta.new_file().path(), line.new_lineno())
+            else {
+                return true;
+            };
+            let content = String::from_utf8_lossy(line.content());
+            added
+                .borrow_mut()
+                .entry(path.to_string_lossy().replace('\\', "/"))
+                .or_default()
+                .push((
+                    line_number,
+                    content.trim_end_matches(['\n', '\r']).to_string(),
+                ));
+            true
+        }),
+    )
+    .map_err(GitError::from)?;
+
+    let findings = added
+        .into_inner()
+        .iter()
+        .flat_map(|(path, lines)| {
+            secret_scanner::scan_lines(path, lines.iter().map(|(n, l)| (*n, l.as_str())))
+        })
+        .collect();
+    Ok(findings)
+}
+
+fn workspace_root(repo: &Repository) -> Result<&Path, GitError> {
+    repo.workdir()
+        .ok_or_else(|| GitError::internal("Secret scanning requires a working directory"))
+}
+
+/// Staged findings that are not baselined or ignored
+pub(crate) fn staged_findings(repo: &Repository) -> Result<Vec<SecretFinding>, GitError> {
+    let findings = scan_staged(repo)?;
+    Ok(secret_scanner::filter_findings(
+        workspace_root(repo)?,
+        findings,
+    ))
+}
+
+/// Fail when staged changes contain secrets and commits are configured to be blocked
+pub(crate) fn pre_commit_check(repo: &Repository) -> Result<(), GitError> {
+    if !secret_scanner::blocks_commits(workspace_root(repo)?) {
+        return Ok(());
+    }
+
+    let findings = staged_findings(repo)?;
+    if findings.is_empty() {
+        return Ok(());
+    }
+
+    let summary: Vec<String> = findings
+        .iter()
+        .map(|f| format!("{}:{} {} ({})", f.path, f.line, f.description, f.redacted))
+        .collect();
+    let mut error = GitError::conflict(&format!(
+        "Commit blocked: {} potential secret(s) in staged changes. Remove them or add them to the secrets baseline.",
+        findings.len()
+    ));
+    error.details = Some(summary.join("\n"));
+    Err(error)
+}
+
+/// Scan staged changes for secrets
+#[tauri::command]
+pub fn git_scan_staged_secrets(path: String) -> Result<Vec<SecretFinding>, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    Ok(staged_findings(&repo)?)
+}
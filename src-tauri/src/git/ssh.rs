@@ -0,0 +1,264 @@
+//! SSH key discovery and passphrase prompting
+//!
+//! Finds candidate private keys for a host (IdentityFile entries from
+//! `~/.ssh/config`, then the default key names) and lets remote operations
+//! ask the frontend for a key passphrase: the operation emits
+//! `git:passphrase-required` and blocks until `git_provide_passphrase` is
+//! called with the same operation id. The IDE answers with its passphrase
+//! dialog (PassphraseDialog); closing it cancels the prompt.
+
+use base64::Engine;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Default key names tried after config-file identities
+const DEFAULT_KEY_NAMES: [&str; 3] = ["id_ed25519", "id_ecdsa", "id_rsa"];
+
+/// How long an operation waits for the user to enter a passphrase
+const PASSPHRASE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Operations waiting for a passphrase; `None` means the user cancelled
+static PENDING_PASSPHRASES: Lazy<Mutex<HashMap<String, Sender<Option<String>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A private key that may be used for a host
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SshKeyInfo {
+    pub private_key: String,
+    pub public_key: Option<String>,
+    /// Key is protected by a passphrase
+    pub encrypted: bool,
+    /// "config" (IdentityFile) or "default"
+    pub source: String,
+}
+
+/// Payload of the `git:passphrase-required` event
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PassphraseRequest {
+    pub operation_id: String,
+    pub key_path: String,
+    /// 1 for the first prompt, higher after a wrong passphrase
+    pub attempt: u32,
+}
+
+/// Lets a remote operation prompt the frontend for passphrases
+#[derive(Clone)]
+pub struct PassphrasePrompt {
+    app: AppHandle,
+    operation_id: String,
+}
+
+impl PassphrasePrompt {
+    pub fn new(app: AppHandle, operation_id: Option<String>) -> Self {
+        Self {
+            app,
+            operation_id: operation_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        }
+    }
+
+    /// Ask for the passphrase of `key_path`, blocking until answered, cancelled or timed out
+    pub fn request(&self, key_path: &Path, attempt: u32) -> Option<String> {
+        let (tx, rx) = mpsc::channel();
+        PENDING_PASSPHRASES
+            .lock()
+            .ok()?
+            .insert(self.operation_id.clone(), tx);
+
+        let request = PassphraseRequest {
+            operation_id: self.operation_id.clone(),
+            key_path: key_path.to_string_lossy().to_string(),
+            attempt,
+        };
+        // Nobody can answer a prompt that was never delivered
+        let answer = match self.app.emit("git:passphrase-required", &request) {
+            Ok(()) => rx.recv_timeout(PASSPHRASE_TIMEOUT).ok().flatten(),
+            Err(e) => {
+                eprintln!("[Git] Failed to request passphrase: {}", e);
+                None
+            }
+        };
+        if let Ok(mut pending) = PENDING_PASSPHRASES.lock() {
+            pending.remove(&self.operation_id);
+        }
+        answer
+    }
+}
+
+fn home_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn expand_tilde(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => home_dir().join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Host part of an ssh:// or scp-style (`git@host:path`) URL
+pub fn host_from_url(url: &str) -> Option<String> {
+    let rest = match url.strip_prefix("ssh://") {
+        Some(rest) => rest.split('/').next()?,
+        None if !url.contains("://") => url.split(':').next()?,
+        None => return None,
+    };
+    let host = rest.rsplit('@').next()?;
+    // ssh://host:port
+    let host = host.split(':').next()?;
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+/// Match an ssh_config Host pattern (`*` and `?` wildcards)
+fn host_pattern_matches(pattern: &str, host: &str) -> bool {
+    fn matches(p: &[u8], h: &[u8]) -> bool {
+        match (p.first(), h.first()) {
+            (None, None) => true,
+            (Some(b'*'), _) => matches(&p[1..], h) || (!h.is_empty() && matches(p, &h[1..])),
+            (Some(b'?'), Some(_)) => matches(&p[1..], &h[1..]),
+            (Some(a), Some(b)) if a.eq_ignore_ascii_case(b) => matches(&p[1..], &h[1..]),
+            _ => false,
+        }
+    }
+    matches(pattern.as_bytes(), host.as_bytes())
+}
+
+/// IdentityFile entries of `~/.ssh/config` that apply to `host`
+fn config_identity_files(host: Option<&str>) -> Vec<PathBuf> {
+    let Ok(content) = std::fs::read_to_string(home_dir().join(".ssh").join("config")) else {
+        return Vec::new();
+    };
+
+    let mut identities = Vec::new();
+    // Lines before the first Host/Match block apply to every host
+    let mut active = true;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (keyword, value) = match line.split_once(|c: char| c.is_whitespace() || c == '=') {
+            Some((k, v)) => (
+                k,
+                v.trim_start_matches(|c: char| c.is_whitespace() || c == '='),
+            ),
+            None => continue,
+        };
+
+        match keyword.to_ascii_lowercase().as_str() {
+            "host" => {
+                active = host.is_some_and(|host| {
+                    let patterns: Vec<&str> = value.split_whitespace().collect();
+                    let negated = patterns.iter().any(|p| {
+                        p.strip_prefix('!')
+                            .is_some_and(|p| host_pattern_matches(p, host))
+                    });
+                    !negated
+                        && patterns
+                            .iter()
+                            .any(|p| !p.starts_with('!') && host_pattern_matches(p, host))
+                });
+            }
+            // Match conditions aren't evaluated; skip those blocks
+            "match" => active = false,
+            "identityfile" if active => {
+                identities.push(expand_tilde(value.trim_matches('"')));
+            }
+            _ => {}
+        }
+    }
+    identities
+}
+
+/// Whether a private key file needs a passphrase
+fn is_key_encrypted(path: &Path) -> bool {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return false;
+    };
+    // Legacy PEM keys
+    if content.contains("ENCRYPTED") {
+        return true;
+    }
+
+    // OpenSSH keys: "openssh-key-v1\0" followed by the length-prefixed cipher name
+    let body: String = content
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(body.trim()) else {
+        return false;
+    };
+    let magic = b"openssh-key-v1\0";
+    if !bytes.starts_with(magic) || bytes.len() < magic.len() + 4 {
+        return false;
+    }
+    let len_bytes: [u8; 4] = bytes[magic.len()..magic.len() + 4]
+        .try_into()
+        .unwrap_or_default();
+    let start = magic.len() + 4;
+    let end = start + u32::from_be_bytes(len_bytes) as usize;
+    bytes
+        .get(start..end)
+        .is_some_and(|cipher| cipher != b"none")
+}
+
+/// Candidate private keys for `host`, config-file identities first
+pub fn discover_ssh_keys(host: Option<&str>) -> Vec<SshKeyInfo> {
+    let ssh_dir = home_dir().join(".ssh");
+    let candidates = config_identity_files(host)
+        .into_iter()
+        .map(|path| (path, "config"))
+        .chain(
+            DEFAULT_KEY_NAMES
+                .iter()
+                .map(|name| (ssh_dir.join(name), "default")),
+        );
+
+    let mut keys: Vec<SshKeyInfo> = Vec::new();
+    for (path, source) in candidates {
+        let private_key = path.to_string_lossy().to_string();
+        if !path.is_file() || keys.iter().any(|k| k.private_key == private_key) {
+            continue;
+        }
+        let public_key = PathBuf::from(format!("{}.pub", private_key));
+        keys.push(SshKeyInfo {
+            encrypted: is_key_encrypted(&path),
+            public_key: public_key
+                .is_file()
+                .then(|| public_key.to_string_lossy().to_string()),
+            private_key,
+            source: source.to_string(),
+        });
+    }
+    keys
+}
+
+/// List the SSH keys that would be tried for a remote URL or host
+#[tauri::command]
+pub fn git_list_ssh_keys(url: Option<String>) -> Result<Vec<SshKeyInfo>, String> {
+    let host = url.as_deref().and_then(host_from_url);
+    Ok(discover_ssh_keys(host.as_deref()))
+}
+
+/// Answer a `git:passphrase-required` prompt; `None` cancels it
+#[tauri::command]
+pub fn git_provide_passphrase(
+    operation_id: String,
+    passphrase: Option<String>,
+) -> Result<(), String> {
+    let sender = PENDING_PASSPHRASES
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&operation_id)
+        .ok_or_else(|| format!("No passphrase request pending for {}", operation_id))?;
+    sender
+        .send(passphrase)
+        .map_err(|_| "Operation is no longer waiting for a passphrase".to_string())
+}
@@ -3,7 +3,7 @@
 //! Native libgit2 implementation for stash management.
 
 use super::error::GitError;
-use super::types::StashEntry;
+use super::types::{FileDiff, StashDetails, StashEntry};
 use git2::Repository;
 
 /// List stashes
@@ -27,16 +27,42 @@ pub fn git_stash_list(path: String) -> Result<Vec<StashEntry>, String> {
 }
 
 /// Create a stash
+/// `paths` limits the stash to matching files; `keep_index` leaves staged
+/// changes in place and `include_untracked` stashes untracked files too
 #[tauri::command]
-pub fn git_stash_push(path: String, message: Option<String>) -> Result<String, String> {
+pub fn git_stash_push(
+    path: String,
+    message: Option<String>,
+    paths: Option<Vec<String>>,
+    keep_index: Option<bool>,
+    include_untracked: Option<bool>,
+) -> Result<String, String> {
     let mut repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
     let sig = repo.signature().map_err(|e| GitError::from(e))?;
 
     let msg = message.as_deref();
 
-    let oid = repo
-        .stash_save(&sig, msg.unwrap_or("WIP"), None)
-        .map_err(|e| GitError::from(e))?;
+    let mut flags = git2::StashFlags::DEFAULT;
+    if keep_index.unwrap_or(false) {
+        flags |= git2::StashFlags::KEEP_INDEX;
+    }
+    if include_untracked.unwrap_or(false) {
+        flags |= git2::StashFlags::INCLUDE_UNTRACKED;
+    }
+
+    let oid = match paths.filter(|paths| !paths.is_empty()) {
+        // libgit2's path-limited stash can't take a message; it records the default one
+        Some(paths) => {
+            let mut opts = git2::StashSaveOptions::new(sig);
+            opts.flags(Some(flags));
+            for pathspec in &paths {
+                opts.pathspec(pathspec.as_str());
+            }
+            repo.stash_save_ext(Some(&mut opts))
+        }
+        None => repo.stash_save2(&sig, Some(msg.unwrap_or("WIP")), Some(flags)),
+    }
+    .map_err(GitError::from)?;
 
     Ok(format!("Created stash: {}", oid))
 }
@@ -57,3 +83,205 @@ pub fn git_stash_pop(path: String, index: Option<usize>) -> Result<String, Strin
 
     Ok(format!("Applied and dropped stash@{{{}}}", idx))
 }
+
+/// Commit id of stash@{index}
+fn stash_oid(repo: &mut Repository, index: usize) -> Result<git2::Oid, GitError> {
+    let mut found = None;
+    repo.stash_foreach(|i, _, oid| {
+        if i == index {
+            found = Some(*oid);
+            false
+        } else {
+            true
+        }
+    })
+    .map_err(GitError::from)?;
+    found.ok_or_else(|| GitError::not_found(&format!("stash@{{{}}} does not exist", index)))
+}
+
+/// Apply a stash without removing it
+/// With `reinstate_index`, changes that were staged are staged again
+#[tauri::command]
+pub fn git_stash_apply(
+    path: String,
+    index: Option<usize>,
+    reinstate_index: Option<bool>,
+) -> Result<String, String> {
+    let mut repo = Repository::open(&path).map_err(GitError::from)?;
+    let idx = index.unwrap_or(0);
+
+    let mut opts = git2::StashApplyOptions::new();
+    if reinstate_index.unwrap_or(false) {
+        opts.reinstantiate_index();
+    }
+    repo.stash_apply(idx, Some(&mut opts))
+        .map_err(GitError::from)?;
+
+    Ok(format!("Applied stash@{{{}}}", idx))
+}
+
+/// Remove a stash without applying it
+#[tauri::command]
+pub fn git_stash_drop(path: String, index: usize) -> Result<String, String> {
+    let mut repo = Repository::open(&path).map_err(GitError::from)?;
+    repo.stash_drop(index).map_err(GitError::from)?;
+    Ok(format!("Dropped stash@{{{}}}", index))
+}
+
+/// Changed files of one diff, with per-file stats and patch text
+fn diff_files(diff: &git2::Diff, max_lines: usize) -> Result<Vec<FileDiff>, GitError> {
+    let mut files = Vec::new();
+    for i in 0..diff.deltas().len() {
+        let Some(delta) = diff.get_delta(i) else {
+            continue;
+        };
+        let path = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let old_path = (delta.status() == git2::Delta::Renamed)
+            .then(|| delta.old_file().path())
+            .flatten()
+            .map(|p| p.to_string_lossy().to_string());
+        let status = match delta.status() {
+            git2::Delta::Added | git2::Delta::Untracked => "A",
+            git2::Delta::Deleted => "D",
+            git2::Delta::Modified => "M",
+            git2::Delta::Renamed => "R",
+            git2::Delta::Copied => "C",
+            _ => "?",
+        }
+        .to_string();
+
+        let (mut additions, mut deletions, mut text) = (0, 0, String::new());
+        if let Some(mut patch) = git2::Patch::from_diff(diff, i).map_err(GitError::from)? {
+            let (_, adds, dels) = patch.line_stats().map_err(GitError::from)?;
+            additions = adds;
+            deletions = dels;
+
+            let mut line_count = 0;
+            patch
+                .print(&mut |_, _, line| {
+                    if line_count < max_lines {
+                        let origin = line.origin();
+                        if origin == '+' || origin == '-' || origin == ' ' {
+                            text.push(origin);
+                        }
+                        text.push_str(&String::from_utf8_lossy(line.content()));
+                        line_count += 1;
+                    }
+                    true
+                })
+                .map_err(GitError::from)?;
+        }
+
+        files.push(FileDiff {
+            path,
+            old_path,
+            status,
+            additions,
+            deletions,
+            diff: text,
+        });
+    }
+    Ok(files)
+}
+
+/// Show the files and diff recorded in a stash, including stashed untracked files
+#[tauri::command]
+pub fn git_stash_show(
+    path: String,
+    index: Option<usize>,
+    max_lines_per_file: Option<usize>,
+) -> Result<StashDetails, String> {
+    let mut repo = Repository::open(&path).map_err(GitError::from)?;
+    let idx = index.unwrap_or(0);
+    let oid = stash_oid(&mut repo, idx)?;
+    let max_lines = max_lines_per_file.unwrap_or(500);
+
+    let stash = repo.find_commit(oid).map_err(GitError::from)?;
+    let base = stash.parent(0).map_err(GitError::from)?;
+    let diff = repo
+        .diff_tree_to_tree(
+            Some(&base.tree().map_err(GitError::from)?),
+            Some(&stash.tree().map_err(GitError::from)?),
+            None,
+        )
+        .map_err(GitError::from)?;
+    let mut files = diff_files(&diff, max_lines)?;
+
+    // The third parent holds untracked files stashed with --include-untracked
+    if let Ok(untracked) = stash.parent(2) {
+        let diff = repo
+            .diff_tree_to_tree(None, Some(&untracked.tree().map_err(GitError::from)?), None)
+            .map_err(GitError::from)?;
+        files.extend(diff_files(&diff, max_lines)?);
+    }
+
+    Ok(StashDetails {
+        index: idx,
+        message: stash.message().unwrap_or("").to_string(),
+        hash: oid.to_string(),
+        base_commit: base.id().to_string(),
+        files,
+    })
+}
+
+/// Create a branch at the commit a stash was made from, check it out, and
+/// apply the stash there (dropping it if it applies cleanly)
+#[tauri::command]
+pub fn git_stash_branch(
+    path: String,
+    index: Option<usize>,
+    branch_name: String,
+) -> Result<String, String> {
+    let mut repo = Repository::open(&path).map_err(GitError::from)?;
+    let idx = index.unwrap_or(0);
+    let oid = stash_oid(&mut repo, idx)?;
+
+    {
+        let base = repo
+            .find_commit(oid)
+            .and_then(|stash| stash.parent(0))
+            .map_err(GitError::from)?;
+        let branch = repo
+            .branch(&branch_name, &base, false)
+            .map_err(GitError::from)?;
+        let refname = branch
+            .get()
+            .name()
+            .ok_or_else(|| GitError::internal("Invalid branch name"))?
+            .to_string();
+
+        repo.checkout_tree(
+            base.as_object(),
+            Some(git2::build::CheckoutBuilder::new().safe()),
+        )
+        .map_err(GitError::from)?;
+        repo.set_head(&refname).map_err(GitError::from)?;
+    }
+
+    let mut opts = git2::StashApplyOptions::new();
+    opts.reinstantiate_index();
+    repo.stash_apply(idx, Some(&mut opts))
+        .map_err(GitError::from)?;
+
+    let has_conflicts = repo
+        .index()
+        .map(|index| index.has_conflicts())
+        .unwrap_or(false);
+    if has_conflicts {
+        return Ok(format!(
+            "Switched to {} and applied stash@{{{}}} with conflicts; the stash was kept",
+            branch_name, idx
+        ));
+    }
+
+    repo.stash_drop(idx).map_err(GitError::from)?;
+    Ok(format!(
+        "Switched to {} and applied stash@{{{}}}",
+        branch_name, idx
+    ))
+}
@@ -1,10 +1,20 @@
 //! Git Status Operations
 //!
-//! Native libgit2 implementation for status, staging, and discard operations.
+//! Native libgit2 implementation for status, staging, discard, restore and
+//! clean operations.
 
 use super::error::GitError;
-use super::types::StatusEntry;
+use super::repositories::GitRepoRegistry;
+use super::types::{
+    CleanFailure, CleanOptions, CleanResult, LineRange, StatusEntry, StatusQueryOptions,
+};
 use git2::{Repository, Status, StatusOptions};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::State;
 
 /// Check if a path is a git repository
 #[tauri::command]
@@ -51,34 +61,145 @@ pub fn git_delete_repo(path: String, force: Option<bool>) -> Result<String, Stri
     Ok("Repository deleted successfully".to_string())
 }
 
-/// Get git status using native libgit2
-#[tauri::command]
-pub fn git_status(path: String) -> Result<Vec<StatusEntry>, String> {
-    let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
+/// How long a cached status stays valid for repositories nobody watches
+const STATUS_CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// Cached results kept per repository
+const MAX_CACHED_QUERIES: usize = 8;
 
+struct CachedStatus {
+    options: StatusQueryOptions,
+    index_mtime: Option<SystemTime>,
+    head: Option<git2::Oid>,
+    computed_at: Instant,
+    entries: Vec<StatusEntry>,
+}
+
+#[derive(Default)]
+struct RepoStatusCache {
+    /// Bumped on every invalidation; a result computed under an older
+    /// generation may predate a worktree change and is not stored
+    generation: u64,
+    entries: Vec<CachedStatus>,
+}
+
+/// Status results keyed by repository workdir
+///
+/// An entry is reused while the index and HEAD are unchanged and the
+/// worktree hasn't been touched: repositories watched by `git_watch_repository`
+/// are invalidated on file events and otherwise cached indefinitely, others
+/// only for `STATUS_CACHE_TTL`.
+static STATUS_CACHE: Lazy<Mutex<HashMap<PathBuf, RepoStatusCache>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn index_mtime(repo: &Repository) -> Option<SystemTime> {
+    std::fs::metadata(repo.path().join("index"))
+        .and_then(|m| m.modified())
+        .ok()
+}
+
+fn cache_key(repo: &Repository) -> PathBuf {
+    repo.workdir().unwrap_or_else(|| repo.path()).to_path_buf()
+}
+
+/// Drop cached status for a repository after its worktree changed
+pub(super) fn invalidate_status_cache(workdir: &Path) {
+    if let Ok(mut cache) = STATUS_CACHE.lock() {
+        let repo_cache = cache.entry(workdir.to_path_buf()).or_default();
+        repo_cache.generation += 1;
+        repo_cache.entries.clear();
+    }
+}
+
+fn compute_status(
+    repo: &Repository,
+    options: &StatusQueryOptions,
+) -> Result<Vec<StatusEntry>, GitError> {
     let mut opts = StatusOptions::new();
-    opts.include_untracked(true)
-        .recurse_untracked_dirs(true)
+    opts.include_untracked(!options.exclude_untracked)
+        .recurse_untracked_dirs(!options.exclude_untracked && !options.untracked_dirs_only)
         .include_ignored(false);
+    for pathspec in &options.paths {
+        opts.pathspec(pathspec);
+    }
 
-    let statuses = repo
-        .statuses(Some(&mut opts))
-        .map_err(|e| GitError::from(e))?;
-
-    let entries: Vec<StatusEntry> = statuses
+    let statuses = repo.statuses(Some(&mut opts))?;
+    Ok(statuses
         .iter()
         .map(|entry| {
             let path = entry.path().unwrap_or("").to_string();
             let code = status_to_porcelain_code(entry.status());
             StatusEntry { path, code }
         })
-        .collect();
+        .collect())
+}
 
+/// Get git status using native libgit2
+///
+/// `options` restricts the status to pathspecs and controls how untracked
+/// files are reported; repeated calls are served from a cache while nothing
+/// has changed.
+#[tauri::command]
+pub fn git_status(
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+    options: Option<StatusQueryOptions>,
+) -> Result<Vec<StatusEntry>, String> {
+    let path = registry.resolve(path);
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let mut options = options.unwrap_or_default();
+    let use_cache = !options.no_cache;
+    options.no_cache = false;
+
+    let key = cache_key(&repo);
+    let index_mtime = index_mtime(&repo);
+    let head = repo.head().ok().and_then(|h| h.target());
+    let watched = super::watcher::is_watched(&key);
+
+    let generation = {
+        let cache = STATUS_CACHE.lock().map_err(|e| e.to_string())?;
+        let repo_cache = cache.get(&key);
+        if use_cache {
+            let hit = repo_cache.and_then(|repo_cache| {
+                repo_cache.entries.iter().find(|cached| {
+                    cached.options == options
+                        && cached.index_mtime == index_mtime
+                        && cached.head == head
+                        && (watched || cached.computed_at.elapsed() < STATUS_CACHE_TTL)
+                })
+            });
+            if let Some(cached) = hit {
+                return Ok(cached.entries.clone());
+            }
+        }
+        repo_cache.map_or(0, |repo_cache| repo_cache.generation)
+    };
+
+    let entries = compute_status(&repo, &options)?;
+
+    let mut cache = STATUS_CACHE.lock().map_err(|e| e.to_string())?;
+    let repo_cache = cache.entry(key).or_default();
+    if repo_cache.generation != generation {
+        // The worktree changed while computing; serve the result uncached
+        return Ok(entries);
+    }
+    let cached = &mut repo_cache.entries;
+    cached.retain(|c| c.options != options);
+    if cached.len() >= MAX_CACHED_QUERIES {
+        cached.remove(0);
+    }
+    cached.push(CachedStatus {
+        options,
+        index_mtime,
+        head,
+        computed_at: Instant::now(),
+        entries: entries.clone(),
+    });
     Ok(entries)
 }
 
 /// Convert git2::Status to two-letter porcelain code (e.g., "M ", " M", "A ", "??")
-fn status_to_porcelain_code(status: Status) -> String {
+pub(super) fn status_to_porcelain_code(status: Status) -> String {
     let index_char = if status.contains(Status::INDEX_NEW) {
         'A'
     } else if status.contains(Status::INDEX_MODIFIED) {
@@ -119,7 +240,12 @@ fn status_to_porcelain_code(status: Status) -> String {
 
 /// Stage a single file
 #[tauri::command]
-pub fn git_stage_file(path: String, file_path: String) -> Result<String, String> {
+pub fn git_stage_file(
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+    file_path: String,
+) -> Result<String, String> {
+    let path = registry.resolve(path);
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
     let mut index = repo.index().map_err(|e| GitError::from(e))?;
 
@@ -131,9 +257,177 @@ pub fn git_stage_file(path: String, file_path: String) -> Result<String, String>
     Ok(format!("Staged: {}", file_path))
 }
 
+/// Build a patch against the index containing only the selected lines.
+/// Unselected additions are dropped and unselected deletions become context;
+/// a deletion is selected when a range covers the working tree line it precedes.
+/// Returns the patch text and the number of selected changed lines.
+fn build_partial_patch(
+    file_path: &str,
+    old_blob: Option<&git2::Blob>,
+    new_content: &[u8],
+    ranges: &[LineRange],
+) -> Result<(String, usize), GitError> {
+    let selected = |line: u32| ranges.iter().any(|r| r.start <= line && line <= r.end);
+
+    let mut opts = git2::DiffOptions::new();
+    opts.context_lines(3);
+    let patch = git2::Patch::from_buffers(
+        old_blob.map_or(&[][..], |blob| blob.content()),
+        Some(std::path::Path::new(file_path)),
+        new_content,
+        Some(std::path::Path::new(file_path)),
+        Some(&mut opts),
+    )
+    .map_err(GitError::from)?;
+
+    let mut body = String::new();
+    let mut selected_count = 0;
+    // New-side delta of the synthetic patch so far, for hunk headers
+    let mut delta: i64 = 0;
+
+    for hunk_idx in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(hunk_idx).map_err(GitError::from)?;
+
+        let mut lines = String::new();
+        let mut old_count: i64 = 0;
+        let mut new_count: i64 = 0;
+        let mut changed = false;
+        let mut last_emitted = false;
+        // Working tree line the next deletion would precede
+        let mut new_cursor = hunk.new_start().max(1);
+
+        for line_idx in 0..line_count {
+            let line = patch
+                .line_in_hunk(hunk_idx, line_idx)
+                .map_err(GitError::from)?;
+            let text = String::from_utf8_lossy(line.content());
+
+            match line.origin() {
+                ' ' => {
+                    lines.push(' ');
+                    lines.push_str(&text);
+                    old_count += 1;
+                    new_count += 1;
+                    new_cursor = line.new_lineno().map_or(new_cursor, |n| n + 1);
+                    last_emitted = true;
+                }
+                '-' => {
+                    if selected(new_cursor) {
+                        lines.push('-');
+                        changed = true;
+                        selected_count += 1;
+                    } else {
+                        lines.push(' ');
+                        new_count += 1;
+                    }
+                    lines.push_str(&text);
+                    old_count += 1;
+                    last_emitted = true;
+                }
+                '+' => {
+                    let lineno = line.new_lineno().unwrap_or(new_cursor);
+                    new_cursor = lineno + 1;
+                    last_emitted = selected(lineno);
+                    if last_emitted {
+                        lines.push('+');
+                        lines.push_str(&text);
+                        new_count += 1;
+                        changed = true;
+                        selected_count += 1;
+                    }
+                }
+                // End-of-file newline markers apply to the preceding line
+                '=' | '>' | '<' if last_emitted => {
+                    if !lines.ends_with('\n') {
+                        lines.push('\n');
+                    }
+                    lines.push_str("\\ No newline at end of file\n");
+                }
+                _ => {}
+            }
+        }
+
+        if !changed {
+            continue;
+        }
+
+        let old_start = hunk.old_start() as i64;
+        let new_start = if new_count == 0 {
+            old_start + delta - 1
+        } else if old_count == 0 {
+            old_start + delta + 1
+        } else {
+            old_start + delta
+        };
+        body.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start,
+            old_count,
+            new_start.max(0),
+            new_count
+        ));
+        body.push_str(&lines);
+        delta += new_count - old_count;
+    }
+
+    let header = if old_blob.is_some() {
+        format!("diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n", file_path)
+    } else {
+        format!(
+            "diff --git a/{0} b/{0}\nnew file mode 100644\n--- /dev/null\n+++ b/{0}\n",
+            file_path
+        )
+    };
+
+    Ok((format!("{}{}", header, body), selected_count))
+}
+
+/// Stage only the selected lines of a file
+/// `line_ranges` refer to the working tree version of the file
+#[tauri::command]
+pub fn git_stage_lines(
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+    file: String,
+    line_ranges: Vec<LineRange>,
+) -> Result<String, String> {
+    let path = registry.resolve(path);
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let file = file.replace('\\', "/");
+
+    let full_path = std::path::Path::new(&path).join(&file);
+    if !full_path.exists() {
+        return Err(format!(
+            "{} was deleted; stage the whole file instead",
+            file
+        ));
+    }
+    let new_content =
+        std::fs::read(&full_path).map_err(|e| format!("Failed to read {}: {}", file, e))?;
+
+    let index = repo.index().map_err(GitError::from)?;
+    let old_blob = match index.get_path(std::path::Path::new(&file), 0) {
+        Some(entry) => Some(repo.find_blob(entry.id).map_err(GitError::from)?),
+        None => None,
+    };
+
+    let (patch_text, selected) =
+        build_partial_patch(&file, old_blob.as_ref(), &new_content, &line_ranges)?;
+    if selected == 0 {
+        return Ok(format!("No changes selected in {}", file));
+    }
+
+    let diff = git2::Diff::from_buffer(patch_text.as_bytes()).map_err(GitError::from)?;
+    repo.apply(&diff, git2::ApplyLocation::Index, None)
+        .map_err(GitError::from)?;
+
+    Ok(format!("Staged {} line(s) in {}", selected, file))
+}
+
 /// Stage all changes
 #[tauri::command]
-pub fn git_stage_all(path: String) -> Result<String, String> {
+pub fn git_stage_all(registry: State<'_, GitRepoRegistry>, path: String) -> Result<String, String> {
+    let path = registry.resolve(path);
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
     let mut index = repo.index().map_err(|e| GitError::from(e))?;
 
@@ -147,7 +441,12 @@ pub fn git_stage_all(path: String) -> Result<String, String> {
 
 /// Unstage a single file
 #[tauri::command]
-pub fn git_unstage_file(path: String, file_path: String) -> Result<String, String> {
+pub fn git_unstage_file(
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+    file_path: String,
+) -> Result<String, String> {
+    let path = registry.resolve(path);
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
 
     // Get HEAD commit
@@ -164,7 +463,11 @@ pub fn git_unstage_file(path: String, file_path: String) -> Result<String, Strin
 
 /// Unstage all changes
 #[tauri::command]
-pub fn git_unstage_all(path: String) -> Result<String, String> {
+pub fn git_unstage_all(
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+) -> Result<String, String> {
+    let path = registry.resolve(path);
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
 
     let head = repo.head().map_err(|e| GitError::from(e))?;
@@ -179,7 +482,12 @@ pub fn git_unstage_all(path: String) -> Result<String, String> {
 
 /// Discard changes to a file (restore to HEAD)
 #[tauri::command]
-pub fn git_discard_changes(path: String, file_path: String) -> Result<String, String> {
+pub fn git_discard_changes(
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+    file_path: String,
+) -> Result<String, String> {
+    let path = registry.resolve(path);
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
 
     // Check if file is untracked (new file)
@@ -219,7 +527,12 @@ pub fn git_discard_changes(path: String, file_path: String) -> Result<String, St
 
 /// Discard changes to multiple files
 #[tauri::command]
-pub fn git_discard_files(path: String, file_paths: Vec<String>) -> Result<String, String> {
+pub fn git_discard_files(
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+    file_paths: Vec<String>,
+) -> Result<String, String> {
+    let path = registry.resolve(path);
     let repo = Repository::open(&path).map_err(|e| GitError::from(e))?;
 
     let head = repo.head().map_err(|e| GitError::from(e))?;
@@ -256,3 +569,223 @@ pub fn git_discard_files(path: String, file_paths: Vec<String>) -> Result<String
 
     Ok(format!("Discarded changes to {} files", file_paths.len()))
 }
+
+/// Restore a file's content from any revision (default HEAD), like
+/// `git restore --source=<rev> <file>`.
+///
+/// A file that doesn't exist at `rev` is deleted. With `staged` the index
+/// entry is updated to match as well.
+#[tauri::command]
+pub fn git_restore_file(
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+    file: String,
+    rev: Option<String>,
+    staged: Option<bool>,
+) -> Result<String, String> {
+    let path = registry.resolve(path);
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| GitError::internal("Repository has no working tree"))?
+        .to_path_buf();
+    let rev = rev.unwrap_or_else(|| "HEAD".to_string());
+    let tree = repo
+        .revparse_single(&rev)
+        .and_then(|object| object.peel_to_tree())
+        .map_err(|_| GitError::not_found(&format!("Revision '{}' not found", rev)))?;
+
+    let relative = Path::new(&file);
+    let full_path = workdir_path(&workdir, relative)?;
+    let staged = staged.unwrap_or(false);
+    let mut index = repo.index().map_err(GitError::from)?;
+
+    let entry = match tree.get_path(relative) {
+        Ok(entry) => entry,
+        Err(_) => {
+            // Only files git knows about are removed; untracked files are
+            // not the restore's to delete
+            let in_head = repo
+                .head()
+                .and_then(|head| head.peel_to_tree())
+                .is_ok_and(|head_tree| head_tree.get_path(relative).is_ok());
+            if !in_head && index.get_path(relative, 0).is_none() {
+                return Err(GitError::not_found(&format!(
+                    "'{}' is not tracked and not in {}",
+                    file, rev
+                ))
+                .into());
+            }
+            if full_path.is_file() || full_path.is_symlink() {
+                std::fs::remove_file(&full_path)
+                    .map_err(|e| format!("Failed to remove {}: {}", file, e))?;
+            }
+            if staged {
+                index.remove_path(relative).map_err(GitError::from)?;
+                index.write().map_err(GitError::from)?;
+            }
+            invalidate_status_cache(&workdir);
+            return Ok(format!("Removed {} (not in {})", file, rev));
+        }
+    };
+    let blob = entry
+        .to_object(&repo)
+        .and_then(|object| object.peel_to_blob())
+        .map_err(|_| GitError::not_found(&format!("'{}' is not a file in {}", file, rev)))?;
+
+    if let Some(parent) = full_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create folder: {}", e))?;
+    }
+    if full_path.is_symlink() {
+        let _ = std::fs::remove_file(&full_path);
+    }
+    write_blob(&full_path, entry.filemode(), blob.content())
+        .map_err(|e| format!("Failed to restore {}: {}", file, e))?;
+
+    if staged {
+        index.add_path(relative).map_err(GitError::from)?;
+        index.write().map_err(GitError::from)?;
+    }
+    invalidate_status_cache(&workdir);
+    Ok(format!("Restored {} from {}", file, rev))
+}
+
+/// `relative` inside the working tree. Rejects absolute paths, `..` and
+/// paths whose existing part resolves outside the working tree (symlinks).
+fn workdir_path(workdir: &Path, relative: &Path) -> Result<PathBuf, String> {
+    let plain = relative
+        .components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)));
+    if !plain || relative.as_os_str().is_empty() {
+        return Err(format!(
+            "'{}' is not a path inside the repository",
+            relative.display()
+        ));
+    }
+
+    let full_path = workdir.join(relative);
+    let root = workdir
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve working tree: {}", e))?;
+    // The file itself may not exist yet; check its nearest existing ancestor
+    let existing = full_path
+        .parent()
+        .into_iter()
+        .flat_map(Path::ancestors)
+        .find(|p| p.exists())
+        .unwrap_or(workdir);
+    let resolved = existing
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", relative.display(), e))?;
+    if !resolved.starts_with(&root) {
+        return Err(format!(
+            "'{}' resolves outside the repository",
+            relative.display()
+        ));
+    }
+    Ok(full_path)
+}
+
+/// Write blob content with the file mode recorded in the tree
+fn write_blob(path: &Path, mode: i32, content: &[u8]) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if mode == i32::from(git2::FileMode::Link) {
+            let target = String::from_utf8_lossy(content).to_string();
+            let _ = std::fs::remove_file(path);
+            return std::os::unix::fs::symlink(target, path);
+        }
+        std::fs::write(path, content)?;
+        let permissions = if mode == i32::from(git2::FileMode::BlobExecutable) {
+            0o755
+        } else {
+            0o644
+        };
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(permissions))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = mode;
+        std::fs::write(path, content)
+    }
+}
+
+/// Remove untracked files, like `git clean -f`.
+///
+/// Untracked directories are only removed with `directories` and nested
+/// repositories are never removed. Use `dry_run` to list what would go.
+#[tauri::command]
+pub fn git_clean(
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+    options: Option<CleanOptions>,
+) -> Result<CleanResult, String> {
+    let path = registry.resolve(path);
+    let options = options.unwrap_or_default();
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| GitError::internal("Repository has no working tree"))?
+        .to_path_buf();
+
+    let mut opts = StatusOptions::new();
+    // Untracked directories come back as one `dir/` entry
+    opts.include_untracked(!options.only_ignored)
+        .recurse_untracked_dirs(false)
+        .include_ignored(options.ignored || options.only_ignored)
+        .recurse_ignored_dirs(false)
+        .exclude_submodules(true);
+    for pathspec in &options.paths {
+        opts.pathspec(pathspec);
+    }
+    let statuses = repo.statuses(Some(&mut opts)).map_err(GitError::from)?;
+
+    let mut result = CleanResult {
+        dry_run: options.dry_run,
+        removed: Vec::new(),
+        skipped: Vec::new(),
+        failed: Vec::new(),
+    };
+    for entry in statuses.iter() {
+        let status = entry.status();
+        let wanted = if options.only_ignored {
+            status.contains(Status::IGNORED)
+        } else {
+            status.contains(Status::WT_NEW) || (options.ignored && status.contains(Status::IGNORED))
+        };
+        let Some(relative) = entry.path().filter(|_| wanted) else {
+            continue;
+        };
+        let relative = relative.to_string();
+        let full_path = workdir.join(&relative);
+        let is_dir = relative.ends_with('/');
+        if is_dir && !options.directories {
+            continue;
+        }
+        if is_dir && full_path.join(".git").exists() {
+            result.skipped.push(relative);
+            continue;
+        }
+        if !options.dry_run {
+            let removed = if is_dir {
+                std::fs::remove_dir_all(&full_path)
+            } else {
+                std::fs::remove_file(&full_path)
+            };
+            if let Err(e) = removed {
+                result.failed.push(CleanFailure {
+                    path: relative,
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        }
+        result.removed.push(relative);
+    }
+
+    if !options.dry_run {
+        invalidate_status_cache(&workdir);
+    }
+    Ok(result)
+}
@@ -0,0 +1,415 @@
+//! Git Sync
+//!
+//! The status bar's "Sync Changes": fetch the current branch's upstream,
+//! integrate it (rebase, merge or fast-forward only) and push local commits.
+//! Each step is emitted as `git:sync-progress` when it starts and ends, and
+//! a rebase additionally reports `git:rebase-progress`.
+//!
+//! Conflicts stop the sync cleanly: the rebase or merge is left in progress
+//! and the result carries `status: "conflicts"`, the operation (`state`) and
+//! the conflicted paths, so the UI can hand over to the rebase and merge
+//! conflict commands. Nothing is pushed in that case.
+
+use super::auth::AuthCallbacks;
+use super::error::GitError;
+use super::rebase::{conflicted_paths, run_rebase};
+use super::ssh::PassphrasePrompt;
+use super::status::invalidate_status_cache;
+use super::types::{SyncResult, SyncStep};
+use git2::{BranchType, Oid, RebaseOptions, Repository, Status, StatusOptions};
+use tauri::{Emitter, Manager};
+
+const STEP_FETCH: &str = "fetch";
+const STEP_INTEGRATE: &str = "integrate";
+const STEP_PUSH: &str = "push";
+
+/// Emits steps and keeps them for the result
+struct Progress<'w> {
+    window: &'w tauri::Window,
+    operation_id: String,
+    steps: Vec<SyncStep>,
+}
+
+impl Progress<'_> {
+    fn report(&mut self, step: &str, status: &str, message: String) {
+        let step = SyncStep {
+            operation_id: self.operation_id.clone(),
+            step: step.to_string(),
+            status: status.to_string(),
+            message,
+        };
+        let _ = self.window.emit("git:sync-progress", &step);
+        if status != "started" {
+            self.steps.push(step);
+        }
+    }
+
+    /// Report a failed step and pass the error on
+    fn fail(&mut self, step: &str, error: GitError) -> GitError {
+        self.report(step, "failed", error.message.clone());
+        error
+    }
+}
+
+/// Strategy from the argument, else from `pull.ff` / `pull.rebase` like `git pull`
+fn resolve_strategy(repo: &Repository, strategy: Option<String>) -> Result<String, GitError> {
+    if let Some(strategy) = strategy {
+        return match strategy.as_str() {
+            "rebase" | "merge" | "ff-only" => Ok(strategy),
+            other => Err(GitError::invalid(&format!(
+                "Unknown sync strategy '{}'; use rebase, merge or ff-only",
+                other
+            ))),
+        };
+    }
+    let config = repo.config().map_err(GitError::from)?;
+    if config.get_string("pull.ff").is_ok_and(|ff| ff == "only") {
+        return Ok("ff-only".to_string());
+    }
+    let rebase = config
+        .get_string("pull.rebase")
+        .is_ok_and(|value| !matches!(value.as_str(), "false" | "no" | "off" | "0"));
+    Ok(if rebase { "rebase" } else { "merge" }.to_string())
+}
+
+/// Tracked files with changes; rebasing or merging over them could lose work
+pub(super) fn has_local_changes(repo: &Repository) -> Result<bool, GitError> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false).include_ignored(false);
+    let statuses = repo.statuses(Some(&mut opts)).map_err(GitError::from)?;
+    Ok(statuses
+        .iter()
+        .any(|entry| entry.status() != Status::CURRENT && entry.status() != Status::IGNORED))
+}
+
+fn ahead_behind(repo: &Repository, local: Oid, upstream: Oid) -> Result<(usize, usize), GitError> {
+    repo.graph_ahead_behind(local, upstream)
+        .map_err(GitError::from)
+}
+
+fn fast_forward(repo: &Repository, refname: &str, target: Oid) -> Result<(), GitError> {
+    let commit = repo.find_commit(target).map_err(GitError::from)?;
+    // A safe checkout refuses to overwrite local changes instead of losing them
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.safe();
+    repo.checkout_tree(commit.as_object(), Some(&mut checkout))
+        .map_err(GitError::from)?;
+    repo.find_reference(refname)
+        .and_then(|mut reference| reference.set_target(target, "sync: fast-forward"))
+        .map_err(GitError::from)?;
+    Ok(())
+}
+
+/// Merge the upstream; returns the conflicted paths if it stopped
+fn merge_upstream(
+    repo: &Repository,
+    upstream_name: &str,
+    upstream: Oid,
+) -> Result<Vec<String>, GitError> {
+    let annotated = repo
+        .find_annotated_commit(upstream)
+        .map_err(GitError::from)?;
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.safe();
+    repo.merge(&[&annotated], None, Some(&mut checkout))
+        .map_err(GitError::from)?;
+
+    let conflicts = conflicted_paths(repo)?;
+    if !conflicts.is_empty() {
+        return Ok(conflicts);
+    }
+
+    let sig = repo.signature().map_err(GitError::from)?;
+    let mut index = repo.index().map_err(GitError::from)?;
+    let tree_id = index.write_tree_to(repo).map_err(GitError::from)?;
+    let tree = repo.find_tree(tree_id).map_err(GitError::from)?;
+    let head_commit = repo
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(GitError::from)?;
+    let upstream_commit = repo.find_commit(upstream).map_err(GitError::from)?;
+    let branch = repo
+        .head()
+        .ok()
+        .and_then(|h| h.shorthand().map(str::to_string))
+        .unwrap_or_default();
+    repo.commit(
+        Some("HEAD"),
+        &sig,
+        &sig,
+        &format!(
+            "Merge remote-tracking branch '{}' into {}",
+            upstream_name, branch
+        ),
+        &tree,
+        &[&head_commit, &upstream_commit],
+    )
+    .map_err(GitError::from)?;
+    repo.cleanup_state().map_err(GitError::from)?;
+    Ok(Vec::new())
+}
+
+/// Fetch, rebase or merge onto the upstream, and push
+/// `strategy` is "rebase", "merge" or "ff-only"; defaults follow `pull.rebase` and `pull.ff`
+#[tauri::command(async)]
+pub fn git_sync(
+    window: tauri::Window,
+    path: String,
+    strategy: Option<String>,
+    operation_id: Option<String>,
+) -> Result<SyncResult, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let operation_id = operation_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let strategy = resolve_strategy(&repo, strategy)?;
+
+    if repo.state() != git2::RepositoryState::Clean {
+        return Err(GitError::conflict(&format!(
+            "Cannot sync: repository is in {:?} state",
+            repo.state()
+        ))
+        .into());
+    }
+
+    let head = repo.head().map_err(GitError::from)?;
+    if !head.is_branch() {
+        return Err(GitError::invalid("Cannot sync a detached HEAD; check out a branch").into());
+    }
+    let refname = head.name().unwrap_or_default().to_string();
+    let branch_name = head.shorthand().unwrap_or_default().to_string();
+    let branch = repo
+        .find_branch(&branch_name, BranchType::Local)
+        .map_err(GitError::from)?;
+    let upstream_branch = branch.upstream().map_err(|_| {
+        GitError::not_found(&format!(
+            "Branch '{}' has no upstream; publish it first",
+            branch_name
+        ))
+    })?;
+    let upstream_name = upstream_branch
+        .name()
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+        .to_string();
+    let upstream_refname = upstream_branch.get().name().unwrap_or_default().to_string();
+    let remote_name = repo
+        .branch_upstream_remote(&refname)
+        .map_err(GitError::from)?
+        .as_str()
+        .unwrap_or("origin")
+        .to_string();
+    let merge_ref = repo
+        .branch_upstream_merge(&refname)
+        .map_err(GitError::from)?
+        .as_str()
+        .unwrap_or(&refname)
+        .to_string();
+    drop(upstream_branch);
+    drop(branch);
+    drop(head);
+
+    let mut progress = Progress {
+        window: &window,
+        operation_id: operation_id.clone(),
+        steps: Vec::new(),
+    };
+    let result = |progress: Progress, status: &str, pulled, pushed, message: String| SyncResult {
+        operation_id: progress.operation_id,
+        status: status.to_string(),
+        strategy: strategy.clone(),
+        branch: branch_name.clone(),
+        upstream: upstream_name.clone(),
+        pulled,
+        pushed,
+        state: None,
+        conflicts: Vec::new(),
+        steps: progress.steps,
+        message,
+    };
+
+    // Fetch
+    progress.report(
+        STEP_FETCH,
+        "started",
+        format!("Fetching from {}", remote_name),
+    );
+    let fetched = repo.find_remote(&remote_name).and_then(|mut remote| {
+        let prompt = PassphrasePrompt::new(window.app_handle().clone(), Some(operation_id.clone()));
+        let mut fetch_opts = AuthCallbacks::fetch_options(Some(prompt));
+        remote.fetch::<&str>(&[], Some(&mut fetch_opts), None)
+    });
+    if let Err(e) = fetched {
+        return Err(progress.fail(STEP_FETCH, GitError::from(e)).into());
+    }
+    progress.report(STEP_FETCH, "completed", format!("Fetched {}", remote_name));
+
+    let local_oid = repo
+        .refname_to_id(&refname)
+        .map_err(|e| progress.fail(STEP_INTEGRATE, GitError::from(e)))?;
+    let upstream_oid = repo
+        .refname_to_id(&upstream_refname)
+        .map_err(|e| progress.fail(STEP_INTEGRATE, GitError::from(e)))?;
+    let (ahead, behind) = ahead_behind(&repo, local_oid, upstream_oid)
+        .map_err(|e| progress.fail(STEP_INTEGRATE, e))?;
+
+    // Integrate
+    if behind == 0 {
+        progress.report(
+            STEP_INTEGRATE,
+            "skipped",
+            format!("Already up to date with {}", upstream_name),
+        );
+    } else {
+        progress.report(
+            STEP_INTEGRATE,
+            "started",
+            format!("Integrating {} commit(s) from {}", behind, upstream_name),
+        );
+        if ahead == 0 {
+            fast_forward(&repo, &refname, upstream_oid)
+                .map_err(|e| progress.fail(STEP_INTEGRATE, e))?;
+            progress.report(
+                STEP_INTEGRATE,
+                "completed",
+                format!("Fast-forwarded to {}", upstream_name),
+            );
+        } else if strategy == "ff-only" {
+            return Err(progress
+                .fail(
+                    STEP_INTEGRATE,
+                    GitError::conflict(&format!(
+                        "'{}' has diverged from {} ({} ahead, {} behind); cannot fast-forward",
+                        branch_name, upstream_name, ahead, behind
+                    )),
+                )
+                .into());
+        } else if has_local_changes(&repo).map_err(|e| progress.fail(STEP_INTEGRATE, e))? {
+            return Err(progress
+                .fail(
+                    STEP_INTEGRATE,
+                    GitError::conflict("Commit or stash your changes before syncing"),
+                )
+                .into());
+        } else {
+            let conflicts = if strategy == "rebase" {
+                let upstream_commit = repo
+                    .find_annotated_commit(upstream_oid)
+                    .map_err(|e| progress.fail(STEP_INTEGRATE, GitError::from(e)))?;
+                let sig = repo
+                    .signature()
+                    .map_err(|e| progress.fail(STEP_INTEGRATE, GitError::from(e)))?;
+                let mut opts = RebaseOptions::new();
+                let mut rebase = repo
+                    .rebase(None, Some(&upstream_commit), None, Some(&mut opts))
+                    .map_err(|e| progress.fail(STEP_INTEGRATE, GitError::from(e)))?;
+                run_rebase(&window, &repo, &mut rebase, &sig)
+                    .map_err(|e| progress.fail(STEP_INTEGRATE, e))?
+                    .conflicts
+            } else {
+                merge_upstream(&repo, &upstream_name, upstream_oid)
+                    .map_err(|e| progress.fail(STEP_INTEGRATE, e))?
+            };
+
+            if !conflicts.is_empty() {
+                if let Some(workdir) = repo.workdir() {
+                    invalidate_status_cache(workdir);
+                }
+                let message = format!(
+                    "Conflicts while {} {}; resolve them to finish the sync",
+                    if strategy == "rebase" {
+                        "rebasing onto"
+                    } else {
+                        "merging"
+                    },
+                    upstream_name
+                );
+                progress.report(STEP_INTEGRATE, "conflicts", message.clone());
+                let mut stopped = result(progress, "conflicts", behind, 0, message);
+                stopped.state = Some(strategy.clone());
+                stopped.conflicts = conflicts;
+                return Ok(stopped);
+            }
+            progress.report(
+                STEP_INTEGRATE,
+                "completed",
+                if strategy == "rebase" {
+                    format!("Rebased {} commit(s) onto {}", ahead, upstream_name)
+                } else {
+                    format!("Merged {}", upstream_name)
+                },
+            );
+        }
+    }
+    if let Some(workdir) = repo.workdir() {
+        invalidate_status_cache(workdir);
+    }
+
+    // Push
+    let local_oid = repo
+        .refname_to_id(&refname)
+        .map_err(|e| progress.fail(STEP_PUSH, GitError::from(e)))?;
+    let (to_push, _) =
+        ahead_behind(&repo, local_oid, upstream_oid).map_err(|e| progress.fail(STEP_PUSH, e))?;
+    if to_push == 0 {
+        progress.report(STEP_PUSH, "skipped", "Nothing to push".to_string());
+        let status = if behind == 0 {
+            "up_to_date"
+        } else {
+            "completed"
+        };
+        let message = if behind == 0 {
+            "Already up to date".to_string()
+        } else {
+            format!("Pulled {} commit(s)", behind)
+        };
+        return Ok(result(progress, status, behind, 0, message));
+    }
+
+    progress.report(
+        STEP_PUSH,
+        "started",
+        format!("Pushing {} commit(s) to {}", to_push, upstream_name),
+    );
+    let mut rejected: Option<String> = None;
+    let pushed = repo.find_remote(&remote_name).and_then(|mut remote| {
+        let prompt = PassphrasePrompt::new(window.app_handle().clone(), Some(operation_id.clone()));
+        let mut callbacks = AuthCallbacks::create_callbacks(Some(prompt));
+        callbacks.push_update_reference(|_, status| {
+            if let Some(status) = status {
+                rejected = Some(status.to_string());
+            }
+            Ok(())
+        });
+        let mut push_opts = git2::PushOptions::new();
+        push_opts.remote_callbacks(callbacks);
+        remote.push(
+            &[&format!("{}:{}", refname, merge_ref)],
+            Some(&mut push_opts),
+        )
+    });
+    if let Err(e) = pushed {
+        return Err(progress.fail(STEP_PUSH, GitError::from(e)).into());
+    }
+    if let Some(reason) = rejected {
+        return Err(progress
+            .fail(
+                STEP_PUSH,
+                GitError::conflict(&format!("Push rejected by {}: {}", remote_name, reason)),
+            )
+            .into());
+    }
+    // Match what a fetch would record, so ahead/behind reads 0/0 right away
+    let _ = repo.reference(&upstream_refname, local_oid, true, "sync: update by push");
+    progress.report(
+        STEP_PUSH,
+        "completed",
+        format!("Pushed {} commit(s) to {}", to_push, upstream_name),
+    );
+
+    let message = if behind == 0 {
+        format!("Pushed {} commit(s)", to_push)
+    } else {
+        format!("Pulled {} and pushed {} commit(s)", behind, to_push)
+    };
+    Ok(result(progress, "completed", behind, to_push, message))
+}
@@ -0,0 +1,159 @@
+//! Commit Trailers
+//!
+//! `Signed-off-by` and `Co-authored-by` lines `git_commit` can append to a
+//! message, and `git_get_recent_coauthors`, which looks through recent
+//! history for people to suggest as co-authors.
+//!
+//! Trailers join the message's existing trailer block when it ends with one
+//! and start a new paragraph otherwise; a trailer already present is not
+//! added twice.
+
+use super::error::GitError;
+use super::types::CoauthorSuggestion;
+use git2::{Repository, Sort};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Commits looked at when suggesting co-authors
+const HISTORY_DEPTH: usize = 500;
+
+const DEFAULT_SUGGESTION_LIMIT: usize = 10;
+
+static TRAILER_LINE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Za-z0-9-]+: ").expect("invalid trailer line pattern"));
+
+static IDENTITY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?P<name>[^<>]+?)\s*<(?P<email>[^<>\s]+@[^<>\s]+)>\s*$")
+        .expect("invalid identity pattern")
+});
+
+/// Split `Name <email>` into its parts
+fn parse_identity(value: &str) -> Option<(String, String)> {
+    let captures = IDENTITY.captures(value)?;
+    Some((captures["name"].to_string(), captures["email"].to_string()))
+}
+
+/// `Co-authored-by` values, normalized to `Name <email>`
+/// Entries that are not in that form are rejected
+pub(super) fn co_author_trailers(co_authors: &[String]) -> Result<Vec<String>, GitError> {
+    co_authors
+        .iter()
+        .map(|value| {
+            parse_identity(value)
+                .map(|(name, email)| format!("Co-authored-by: {} <{}>", name, email))
+                .ok_or_else(|| {
+                    GitError::invalid(&format!("Invalid co-author '{}'; use Name <email>", value))
+                })
+        })
+        .collect()
+}
+
+/// Append trailers to a commit message, skipping those it already has
+pub(super) fn append_trailers(message: &str, trailers: &[String]) -> String {
+    let message = message.trim_end();
+    let existing: Vec<String> = git2::message_trailers_strs(message)
+        .map(|found| {
+            found
+                .iter()
+                .map(|(key, value)| format!("{}: {}", key, value).to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut added: Vec<&String> = Vec::new();
+    for trailer in trailers {
+        let key = trailer.to_lowercase();
+        if !existing.contains(&key) && !added.iter().any(|a| a.to_lowercase() == key) {
+            added.push(trailer);
+        }
+    }
+    if added.is_empty() {
+        return format!("{}\n", message);
+    }
+
+    // A subject line alone is never a trailer block
+    let last_paragraph = message.rsplit("\n\n").next().unwrap_or("");
+    let ends_with_trailers = message.contains("\n\n")
+        && last_paragraph
+            .lines()
+            .all(|line| TRAILER_LINE.is_match(line));
+
+    let mut result = message.to_string();
+    if !message.is_empty() {
+        result.push_str(if ends_with_trailers { "\n" } else { "\n\n" });
+    }
+    for trailer in added {
+        result.push_str(trailer);
+        result.push('\n');
+    }
+    result
+}
+
+/// People who authored or co-authored recent commits, most frequent first
+/// The configured identity is left out
+#[tauri::command]
+pub fn git_get_recent_coauthors(
+    path: String,
+    limit: Option<usize>,
+) -> Result<Vec<CoauthorSuggestion>, String> {
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let own_email = repo
+        .signature()
+        .ok()
+        .and_then(|sig| sig.email().map(|e| e.to_lowercase()));
+
+    let mut revwalk = repo.revwalk().map_err(GitError::from)?;
+    if revwalk.push_head().is_err() {
+        // Unborn branch: no history yet
+        return Ok(Vec::new());
+    }
+    revwalk.set_sorting(Sort::TIME).map_err(GitError::from)?;
+
+    let mut people: HashMap<String, CoauthorSuggestion> = HashMap::new();
+    let mut record = |name: &str, email: &str, time: i64| {
+        let key = email.to_lowercase();
+        if name.ends_with("[bot]") || own_email.as_deref() == Some(key.as_str()) {
+            return;
+        }
+        let entry = people.entry(key).or_insert_with(|| CoauthorSuggestion {
+            name: name.to_string(),
+            email: email.to_string(),
+            trailer: format!("{} <{}>", name, email),
+            commits: 0,
+            last_seen: time,
+        });
+        entry.commits += 1;
+        entry.last_seen = entry.last_seen.max(time);
+    };
+
+    for oid in revwalk.take(HISTORY_DEPTH).filter_map(|oid| oid.ok()) {
+        let Ok(commit) = repo.find_commit(oid) else {
+            continue;
+        };
+        let time = commit.time().seconds();
+        let author = commit.author();
+        if let (Some(name), Some(email)) = (author.name(), author.email()) {
+            record(name, email, time);
+        }
+        let Ok(trailers) = git2::message_trailers_strs(commit.message().unwrap_or_default()) else {
+            continue;
+        };
+        for (key, value) in trailers.iter() {
+            if key.eq_ignore_ascii_case("Co-authored-by") {
+                if let Some((name, email)) = parse_identity(value) {
+                    record(&name, &email, time);
+                }
+            }
+        }
+    }
+
+    let mut suggestions: Vec<CoauthorSuggestion> = people.into_values().collect();
+    suggestions.sort_by(|a, b| {
+        b.commits
+            .cmp(&a.commits)
+            .then(b.last_seen.cmp(&a.last_seen))
+    });
+    suggestions.truncate(limit.unwrap_or(DEFAULT_SUGGESTION_LIMIT));
+    Ok(suggestions)
+}
@@ -2,7 +2,7 @@
 //!
 //! Shared data structures used across Git operations.
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// Status entry for a file in the working tree
 #[derive(Serialize, Debug, Clone)]
@@ -45,6 +45,31 @@ pub struct StashEntry {
     pub hash: String,
 }
 
+/// Contents of a stash entry
+#[derive(Serialize, Debug, Clone)]
+pub struct StashDetails {
+    pub index: usize,
+    pub message: String,
+    pub hash: String,
+    /// Commit the stash was created on top of
+    pub base_commit: String,
+    pub files: Vec<FileDiff>,
+}
+
+/// A commit in a file's history
+#[derive(Serialize, Debug, Clone)]
+pub struct FileHistoryEntry {
+    pub commit: CommitInfo,
+    /// Path of the file in this commit
+    pub path: String,
+    /// Previous path when this commit renamed the file
+    pub old_path: Option<String>,
+    /// "A", "M", "D" or "R"
+    pub status: String,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
 /// File diff information
 #[derive(Serialize, Debug, Clone)]
 pub struct FileDiff {
@@ -56,6 +81,126 @@ pub struct FileDiff {
     pub diff: String,
 }
 
+/// Options for comparing two refs; camelCase like the commands' own arguments
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiffRefsOptions {
+    /// Only list changed files, without diff text or line counts
+    pub metadata_only: bool,
+    /// Defaults to 500
+    pub max_lines_per_file: Option<usize>,
+    /// Defaults to true
+    pub detect_renames: Option<bool>,
+    /// Limit the comparison to these paths
+    pub paths: Vec<String>,
+}
+
+/// Options for `git_status` on large repositories
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(default)]
+pub struct StatusQueryOptions {
+    /// Only report paths matching these pathspecs
+    pub paths: Vec<String>,
+    /// Report untracked directories as a single `dir/` entry instead of
+    /// listing every file inside them
+    pub untracked_dirs_only: bool,
+    /// Skip untracked files entirely
+    pub exclude_untracked: bool,
+    /// Bypass the status cache
+    pub no_cache: bool,
+}
+
+/// Options for `git_clean`
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct CleanOptions {
+    /// Only report what would be removed
+    pub dry_run: bool,
+    /// Also remove untracked directories (`git clean -d`)
+    pub directories: bool,
+    /// Also remove ignored files (`git clean -x`)
+    pub ignored: bool,
+    /// Remove only ignored files (`git clean -X`)
+    pub only_ignored: bool,
+    /// Limit cleaning to these pathspecs
+    pub paths: Vec<String>,
+}
+
+/// Result of `git_clean`
+#[derive(Serialize, Debug, Clone)]
+pub struct CleanResult {
+    pub dry_run: bool,
+    /// Removed (or, in a dry run, removable) paths; directories end with `/`
+    pub removed: Vec<String>,
+    /// Nested repositories left in place, as `git clean` does
+    pub skipped: Vec<String>,
+    pub failed: Vec<CleanFailure>,
+}
+
+/// A path `git_clean` could not remove
+#[derive(Serialize, Debug, Clone)]
+pub struct CleanFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// A generated commit message for the staged changes
+#[derive(Serialize, Debug, Clone)]
+pub struct GeneratedCommitMessage {
+    /// Subject and body, ready to commit
+    pub message: String,
+    pub subject: String,
+    pub body: Option<String>,
+    pub provider: String,
+    pub model: String,
+    pub files_changed: usize,
+    /// Some file diffs were left out of the prompt for size
+    pub truncated: bool,
+}
+
+/// Granularity of intraline diff highlights
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IntralineMode {
+    Word,
+    Char,
+}
+
+/// Changed span within a diff line, in UTF-16 code units of the line content
+/// (excluding the leading `+`/`-`); `end` is exclusive
+#[derive(Serialize, Debug, Clone)]
+pub struct HighlightRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Intraline highlights for one changed line
+#[derive(Serialize, Debug, Clone)]
+pub struct LineHighlight {
+    /// 0-based line in the diff text
+    pub diff_line: usize,
+    /// "+" or "-"
+    pub origin: String,
+    pub old_lineno: Option<u32>,
+    pub new_lineno: Option<u32>,
+    pub ranges: Vec<HighlightRange>,
+}
+
+/// Diff text with intraline highlights
+#[derive(Serialize, Debug, Clone)]
+pub struct IntralineDiff {
+    pub diff: String,
+    pub highlights: Vec<LineHighlight>,
+}
+
+/// Plain diff text, or diff text with highlights when requested
+#[derive(Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum FileDiffOutput {
+    Text(String),
+    Intraline(IntralineDiff),
+}
+
 /// Clone progress information
 #[derive(Serialize, Debug, Clone)]
 pub struct CloneProgress {
@@ -75,3 +220,434 @@ pub struct ConflictContent {
     pub theirs: String,
     pub base: String,
 }
+
+/// One side of a conflict region; lines are 1-based
+#[derive(Serialize, Debug, Clone)]
+pub struct ConflictSide {
+    /// Text after the marker, e.g. "HEAD" or a branch name
+    pub label: Option<String>,
+    /// First content line, after the marker
+    pub start_line: usize,
+    pub line_count: usize,
+    pub content: String,
+}
+
+/// A `<<<<<<<` ... `>>>>>>>` block in a conflicted file
+#[derive(Serialize, Debug, Clone)]
+pub struct ConflictRegion {
+    pub index: usize,
+    /// Line of the `<<<<<<<` marker
+    pub start_line: usize,
+    /// Line of the `>>>>>>>` marker
+    pub end_line: usize,
+    pub ours: ConflictSide,
+    /// Only present for diff3/zdiff3 style conflicts
+    pub base: Option<ConflictSide>,
+    pub theirs: ConflictSide,
+}
+
+/// Conflict regions of a working-tree file
+#[derive(Serialize, Debug, Clone)]
+pub struct ConflictMarkers {
+    pub path: String,
+    pub content: String,
+    pub line_ending: String,
+    pub regions: Vec<ConflictRegion>,
+}
+
+/// How to resolve one conflict region
+#[derive(Deserialize, Debug, Clone)]
+pub struct ConflictRegionResolution {
+    pub index: usize,
+    /// "ours", "theirs", "base", "both" (ours then theirs), "both_theirs_first" or "custom"
+    pub resolution: String,
+    /// Replacement text for "custom"
+    pub content: Option<String>,
+}
+
+/// Result of `git_write_conflict_resolution`
+#[derive(Serialize, Debug, Clone)]
+pub struct ConflictResolutionResult {
+    pub path: String,
+    pub resolved: usize,
+    /// Regions still marked in the file
+    pub remaining: usize,
+    /// Whether the file was staged (only once no conflicts remain)
+    pub staged: bool,
+}
+
+/// A file that would conflict in a merge
+#[derive(Serialize, Debug, Clone)]
+pub struct MergeConflictPreview {
+    pub path: String,
+    /// "both_modified", "both_added", "deleted_by_us" or "deleted_by_them"
+    pub kind: String,
+}
+
+/// Outcome of a merge computed in memory
+#[derive(Serialize, Debug, Clone)]
+pub struct MergePreview {
+    pub up_to_date: bool,
+    pub fast_forward: bool,
+    pub merge_base: Option<String>,
+    /// Commits on the branch that are not on HEAD
+    pub incoming_commits: usize,
+    pub conflicts: Vec<MergeConflictPreview>,
+    /// Changed on both sides and merged cleanly
+    pub auto_merged: Vec<String>,
+    /// Changed only on the branch
+    pub incoming_files: Vec<String>,
+    /// Files touched by the merge that have uncommitted changes
+    pub dirty_files: Vec<String>,
+}
+
+/// Rebase progress, emitted as each commit is replayed
+#[derive(Serialize, Debug, Clone)]
+pub struct RebaseProgress {
+    /// 1-based index of the commit being replayed
+    pub current: usize,
+    pub total: usize,
+    pub commit_id: String,
+    pub summary: String,
+}
+
+/// Outcome of a rebase step
+#[derive(Serialize, Debug, Clone)]
+pub struct RebaseResult {
+    /// "completed" or "conflicts"
+    pub status: String,
+    pub current: usize,
+    pub total: usize,
+    /// Commit being replayed when stopped on conflicts
+    pub stopped_at: Option<String>,
+    pub conflicts: Vec<String>,
+    pub message: String,
+}
+
+/// Patch file written by `git_format_patch`
+#[derive(Serialize, Debug, Clone)]
+pub struct PatchFile {
+    pub path: String,
+    pub commit_id: String,
+    pub subject: String,
+}
+
+/// How one file of a patch applies
+#[derive(Serialize, Debug, Clone)]
+pub struct PatchFileResult {
+    pub path: String,
+    /// "added", "deleted", "modified", "renamed" or "copied"
+    pub change: String,
+    pub applies: bool,
+    /// Why the file does not apply
+    pub error: Option<String>,
+}
+
+/// Outcome of `git_apply_patch`
+#[derive(Serialize, Debug, Clone)]
+pub struct ApplyPatchResult {
+    /// False when checking only or when any file does not apply
+    pub applied: bool,
+    pub check_only: bool,
+    /// "workdir", "index" or "both"
+    pub location: String,
+    pub files: Vec<PatchFileResult>,
+    pub message: String,
+}
+
+/// Whether HEAD can be amended without rewriting published history
+#[derive(Serialize, Debug, Clone)]
+pub struct AmendStatus {
+    pub can_amend: bool,
+    pub head: Option<String>,
+    pub summary: Option<String>,
+    /// Remote branches that already contain HEAD
+    pub pushed_to: Vec<String>,
+    /// Why amending is refused or discouraged
+    pub reason: Option<String>,
+}
+
+/// Commit created by `git_create_fixup_commit`
+#[derive(Serialize, Debug, Clone)]
+pub struct FixupCommit {
+    pub commit_id: String,
+    pub target: String,
+    pub message: String,
+}
+
+/// Someone from recent history to offer as a `Co-authored-by` trailer
+#[derive(Serialize, Debug, Clone)]
+pub struct CoauthorSuggestion {
+    pub name: String,
+    pub email: String,
+    /// Trailer value, `Name <email>`
+    pub trailer: String,
+    /// Recent commits they authored or co-authored
+    pub commits: usize,
+    /// Time of their most recent commit (seconds since epoch)
+    pub last_seen: i64,
+}
+
+/// An effective git config value and the scope it comes from
+#[derive(Serialize, Debug, Clone)]
+pub struct GitConfigEntry {
+    pub name: String,
+    pub value: String,
+    /// "system", "xdg", "global", "local", "worktree", ...
+    pub scope: String,
+}
+
+/// The `user.name` / `user.email` commits will be made with
+#[derive(Serialize, Debug, Clone)]
+pub struct GitIdentity {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub name_scope: Option<String>,
+    pub email_scope: Option<String>,
+    /// Both are set, so committing won't fail for lack of an identity
+    pub complete: bool,
+}
+
+/// One step of a sync, emitted as `git:sync-progress`
+#[derive(Serialize, Debug, Clone)]
+pub struct SyncStep {
+    pub operation_id: String,
+    /// "fetch", "integrate" or "push"
+    pub step: String,
+    /// "started", "completed", "skipped", "conflicts" or "failed"
+    pub status: String,
+    pub message: String,
+}
+
+/// Outcome of a sync
+#[derive(Serialize, Debug, Clone)]
+pub struct SyncResult {
+    pub operation_id: String,
+    /// "completed", "up_to_date" or "conflicts"
+    pub status: String,
+    /// "rebase", "merge" or "ff-only"
+    pub strategy: String,
+    pub branch: String,
+    pub upstream: String,
+    /// Upstream commits brought in
+    pub pulled: usize,
+    /// Local commits pushed
+    pub pushed: usize,
+    /// Operation left in progress when stopped on conflicts ("rebase" or "merge")
+    pub state: Option<String>,
+    pub conflicts: Vec<String>,
+    pub steps: Vec<SyncStep>,
+    pub message: String,
+}
+
+/// Inclusive 1-based line range in the working tree version of a file
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct LineRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+/// Reflog entry (index 0 is the most recent)
+#[derive(Serialize, Debug, Clone)]
+pub struct ReflogEntry {
+    pub index: usize,
+    pub id: String,
+    pub old_id: String,
+    pub message: String,
+    pub committer: String,
+    pub email: String,
+    pub date: String,
+    /// Summary of the commit the entry points to, if it still exists
+    pub summary: Option<String>,
+}
+
+/// Archive file format
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "tar")]
+    Tar,
+    #[serde(rename = "tar.gz", alias = "tgz")]
+    TarGz,
+}
+
+/// Archive progress information
+#[derive(Serialize, Debug, Clone)]
+pub struct ArchiveProgress {
+    pub output_path: String,
+    pub current: usize,
+    pub total: usize,
+    /// Path of the file just written, relative to the archived folder
+    pub path: String,
+    pub percent: u32,
+}
+
+/// Archive written by `git_archive`
+#[derive(Serialize, Debug, Clone)]
+pub struct ArchiveResult {
+    pub output_path: String,
+    pub commit_id: String,
+    pub files: usize,
+    pub uncompressed_bytes: u64,
+    pub archive_bytes: u64,
+}
+
+/// State of a bisect session
+#[derive(Serialize, Debug, Clone)]
+pub struct BisectStatus {
+    pub active: bool,
+    /// Branch (or commit) checked out when the bisect started
+    pub original_head: Option<String>,
+    pub bad: Option<String>,
+    pub good: Vec<String>,
+    pub skipped: Vec<String>,
+    /// Commit currently checked out
+    pub current: Option<CommitInfo>,
+    /// Commit to test next (checked out by start/mark)
+    pub next: Option<CommitInfo>,
+    /// Commits still between good and bad
+    pub remaining: usize,
+    /// Roughly how many more marks are needed
+    pub steps_left: u32,
+    /// Set once the first bad commit is known
+    pub first_bad: Option<CommitInfo>,
+    /// When only skipped commits are left: the commits that may be first bad
+    pub candidates: Vec<String>,
+}
+
+/// A repository found in a workspace by `git_list_repositories`
+#[derive(Serialize, Debug, Clone)]
+pub struct GitRepository {
+    /// Stable ID, accepted wherever status and branch commands take a path
+    pub id: String,
+    /// Folder name
+    pub name: String,
+    /// Working tree
+    pub path: String,
+    /// Working tree relative to the workspace root ("" for the root, ".." segments
+    /// when the workspace is inside the repository)
+    pub relative_path: String,
+    pub git_dir: String,
+    /// "root", "parent" (contains the workspace), "nested", "submodule" or "worktree"
+    pub kind: String,
+    /// Closest repository containing this one
+    pub parent_id: Option<String>,
+    /// Branch checked out, or the short commit id when detached
+    pub head: Option<String>,
+    pub detached: bool,
+}
+
+/// A ref that describes an operation in progress (`MERGE_HEAD`, `ORIG_HEAD`, ...)
+#[derive(Serialize, Debug, Clone)]
+pub struct RepoStateRef {
+    pub name: String,
+    pub commit: String,
+    pub summary: String,
+}
+
+/// Progress of a rebase or `git am` in progress
+#[derive(Serialize, Debug, Clone)]
+pub struct RepoStateProgress {
+    /// Branch being rebased
+    pub head_name: Option<String>,
+    pub onto: Option<String>,
+    /// 1-based index of the step being applied
+    pub current: usize,
+    pub total: usize,
+}
+
+/// Operation in progress in a repository
+#[derive(Serialize, Debug, Clone)]
+pub struct RepoState {
+    /// "clean", "merge", "revert", "revert-sequence", "cherry-pick",
+    /// "cherry-pick-sequence", "rebase", "rebase-interactive", "rebase-merge",
+    /// "apply-mailbox", "apply-mailbox-or-rebase" or "bisect"
+    pub operation: String,
+    pub in_progress: bool,
+    /// Branch checked out, or the short commit id when detached
+    pub head: Option<String>,
+    pub detached: bool,
+    /// Message being composed (`MERGE_MSG`, or `SQUASH_MSG` after a squash merge)
+    pub message: Option<String>,
+    pub refs: Vec<RepoStateRef>,
+    pub progress: Option<RepoStateProgress>,
+    pub conflicts: Vec<String>,
+    /// What can be done next: "commit", "continue", "skip", "abort" or "reset"
+    pub actions: Vec<String>,
+    /// A bisect can run alongside other operations
+    pub bisecting: bool,
+}
+
+/// Commit message template configured for a repository
+#[derive(Serialize, Debug, Clone)]
+pub struct CommitTemplate {
+    /// File the template was read from
+    pub path: String,
+    /// "config" (`commit.template`) or "gitmessage" (a `.gitmessage` file)
+    pub source: String,
+    pub content: String,
+    /// The content without comment lines, ready to prefill the message box
+    pub message: String,
+}
+
+/// Rules applied by `git_validate_commit_message`, read from the
+/// `git.commitValidation` setting
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CommitValidationRules {
+    /// Require `type(scope)!: description` subjects
+    pub conventional: bool,
+    /// Allowed conventional types
+    pub types: Vec<String>,
+    /// Allowed scopes; empty allows any
+    pub scopes: Vec<String>,
+    pub require_scope: bool,
+    /// 0 disables the check
+    pub max_subject_length: usize,
+    /// 0 disables the check
+    pub max_body_line_length: usize,
+    pub no_trailing_whitespace: bool,
+    pub no_subject_period: bool,
+}
+
+impl Default for CommitValidationRules {
+    fn default() -> Self {
+        Self {
+            conventional: false,
+            types: [
+                "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore",
+                "revert",
+            ]
+            .iter()
+            .map(|t| t.to_string())
+            .collect(),
+            scopes: Vec::new(),
+            require_scope: false,
+            max_subject_length: 72,
+            max_body_line_length: 100,
+            no_trailing_whitespace: true,
+            no_subject_period: true,
+        }
+    }
+}
+
+/// A rule a commit message breaks; positions are 1-based and columns count characters
+#[derive(Serialize, Debug, Clone)]
+pub struct CommitMessageViolation {
+    pub rule: String,
+    /// "error" blocks the commit, "warning" does not
+    pub severity: String,
+    pub message: String,
+    pub line: usize,
+    pub start_column: usize,
+    pub end_column: usize,
+}
+
+/// Result of `git_validate_commit_message`
+#[derive(Serialize, Debug, Clone)]
+pub struct CommitMessageValidation {
+    /// No error-level violations
+    pub valid: bool,
+    pub violations: Vec<CommitMessageViolation>,
+}
@@ -0,0 +1,345 @@
+//! Git Repository Watcher
+//!
+//! Watches a repository's worktree and `.git` directory and emits
+//! `git-status-changed` with only the entries whose status changed, so the
+//! frontend doesn't have to re-run `git_status` after every file event.
+//!
+//! Events are debounced. Worktree changes are rechecked file by file with
+//! `status_file`; changes to HEAD, the index, refs or `.gitignore` (and
+//! large bursts) trigger a full status recompute that is diffed against the
+//! previous snapshot.
+
+use super::error::GitError;
+use super::repositories::{canonical, GitRepoRegistry};
+use super::status::{invalidate_status_cache, status_to_porcelain_code};
+use super::types::StatusEntry;
+use crate::background_pause::BackgroundSubsystem;
+use git2::{Repository, Status, StatusOptions};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Quiet period before changes are processed
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Above this many changed paths, a full recompute is cheaper
+const MAX_INCREMENTAL_PATHS: usize = 256;
+
+/// Files under `.git` whose changes can alter the status of any path
+const GIT_STATE_FILES: [&str; 7] = [
+    "HEAD",
+    "index",
+    "packed-refs",
+    "MERGE_HEAD",
+    "CHERRY_PICK_HEAD",
+    "REVERT_HEAD",
+    "info/exclude",
+];
+
+/// Payload of the `git-status-changed` event
+#[derive(Serialize, Debug, Clone)]
+pub struct StatusDelta {
+    pub repo_path: String,
+    /// ID from the repository registry, when the repository was listed there
+    pub repo_id: Option<String>,
+    /// New or changed entries
+    pub changed: Vec<StatusEntry>,
+    /// Paths that are now clean (or gone)
+    pub removed: Vec<String>,
+    /// Whether this came from a full recompute
+    pub full: bool,
+}
+
+struct RepoWatch {
+    _watcher: RecommendedWatcher,
+    workdir: PathBuf,
+}
+
+static WATCHES: Lazy<Mutex<HashMap<PathBuf, RepoWatch>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Whether file events for this workdir reach the status cache
+pub(super) fn is_watched(workdir: &Path) -> bool {
+    WATCHES
+        .lock()
+        .map(|watches| watches.values().any(|w| w.workdir == workdir))
+        .unwrap_or(false)
+}
+
+/// What a batch of file events requires
+#[derive(Default)]
+struct PendingChanges {
+    full: bool,
+    paths: BTreeSet<String>,
+}
+
+impl PendingChanges {
+    fn add(&mut self, workdir: &Path, git_dir: &Path, path: &Path) {
+        if let Ok(inside_git) = path.strip_prefix(git_dir) {
+            let relative = inside_git.to_string_lossy().replace('\\', "/");
+            if GIT_STATE_FILES.contains(&relative.as_str()) || relative.starts_with("refs/") {
+                self.full = true;
+            }
+            // Objects, logs, lock files and the like don't affect status
+            return;
+        }
+        let Ok(relative) = path.strip_prefix(workdir) else {
+            return;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        if relative.is_empty() {
+            return;
+        }
+        if relative == ".gitignore" || relative.ends_with("/.gitignore") || path.is_dir() {
+            self.full = true;
+        }
+        self.paths.insert(relative);
+    }
+}
+
+fn full_status(repo: &Repository) -> Result<HashMap<String, String>, GitError> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    let statuses = repo.statuses(Some(&mut opts))?;
+    Ok(statuses
+        .iter()
+        .filter_map(|entry| {
+            let path = entry.path()?.to_string();
+            Some((path, status_to_porcelain_code(entry.status())))
+        })
+        .collect())
+}
+
+/// Status code of one path, `None` when it is clean, ignored or gone
+fn path_status(repo: &Repository, path: &str) -> Option<String> {
+    let status = repo.status_file(Path::new(path)).ok()?;
+    if status.is_empty() || status.contains(Status::IGNORED) || status == Status::CURRENT {
+        return None;
+    }
+    Some(status_to_porcelain_code(status))
+}
+
+/// Process file events; `snapshot` is the baseline handed to the frontend,
+/// taken after the watcher was registered so no change falls in between
+fn watch_loop(
+    app: AppHandle,
+    repo_path: String,
+    events: Receiver<notify::Result<notify::Event>>,
+    mut snapshot: HashMap<String, String>,
+) {
+    let repo = match Repository::open(&repo_path) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("[GitWatcher] Failed to open {}: {}", repo_path, e);
+            return;
+        }
+    };
+    let Some(workdir) = repo.workdir().map(Path::to_path_buf) else {
+        return;
+    };
+    let git_dir = repo.path().to_path_buf();
+    let canonical_workdir = canonical(&workdir);
+
+    loop {
+        // Block for the first event, then collect until things go quiet
+        let mut pending = PendingChanges::default();
+        match events.recv() {
+            Ok(Ok(event)) => {
+                // Cached status is stale from the first event on
+                invalidate_status_cache(&workdir);
+                event
+                    .paths
+                    .iter()
+                    .for_each(|p| pending.add(&workdir, &git_dir, p))
+            }
+            Ok(Err(_)) => continue,
+            Err(_) => return,
+        }
+        let quiet = DEBOUNCE.max(Duration::from_millis(
+            crate::power_manager::current_budget().watcher_flush_ms,
+        ));
+        loop {
+            match events.recv_timeout(quiet) {
+                Ok(Ok(event)) => event
+                    .paths
+                    .iter()
+                    .for_each(|p| pending.add(&workdir, &git_dir, p)),
+                Ok(Err(_)) => {}
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+        // While watchers are paused keep collecting; the changes go out on resume
+        while crate::background_pause::is_paused(BackgroundSubsystem::Watchers) {
+            match events.recv_timeout(DEBOUNCE) {
+                Ok(Ok(event)) => event
+                    .paths
+                    .iter()
+                    .for_each(|p| pending.add(&workdir, &git_dir, p)),
+                Ok(Err(_)) | Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+        // Drop anything computed while events were still arriving
+        invalidate_status_cache(&workdir);
+        // Build output and dependencies churn without affecting status
+        pending
+            .paths
+            .retain(|path| !repo.is_path_ignored(path).unwrap_or(false));
+        if !pending.full && pending.paths.is_empty() {
+            continue;
+        }
+
+        let full = pending.full || pending.paths.len() > MAX_INCREMENTAL_PATHS;
+        let mut changed = Vec::new();
+        let mut removed = Vec::new();
+        if full {
+            let current = match full_status(&repo) {
+                Ok(current) => current,
+                Err(e) => {
+                    eprintln!(
+                        "[GitWatcher] Status failed for {}: {}",
+                        repo_path, e.message
+                    );
+                    continue;
+                }
+            };
+            for (path, code) in &current {
+                if snapshot.get(path) != Some(code) {
+                    changed.push(StatusEntry {
+                        path: path.clone(),
+                        code: code.clone(),
+                    });
+                }
+            }
+            removed.extend(
+                snapshot
+                    .keys()
+                    .filter(|path| !current.contains_key(*path))
+                    .cloned(),
+            );
+            snapshot = current;
+        } else {
+            for path in pending.paths {
+                match path_status(&repo, &path) {
+                    Some(code) => {
+                        if snapshot.get(&path) != Some(&code) {
+                            snapshot.insert(path.clone(), code.clone());
+                            changed.push(StatusEntry { path, code });
+                        }
+                    }
+                    None => {
+                        if snapshot.remove(&path).is_some() {
+                            removed.push(path);
+                        }
+                    }
+                }
+            }
+        }
+
+        if changed.is_empty() && removed.is_empty() {
+            continue;
+        }
+        changed.sort_by(|a, b| a.path.cmp(&b.path));
+        removed.sort();
+        let delta = StatusDelta {
+            repo_path: repo_path.clone(),
+            repo_id: app
+                .try_state::<GitRepoRegistry>()
+                .and_then(|registry| registry.id_for_workdir(&canonical_workdir)),
+            changed,
+            removed,
+            full,
+        };
+        if let Err(e) = app.emit("git-status-changed", &delta) {
+            eprintln!("[GitWatcher] Failed to emit status delta: {}", e);
+        }
+    }
+}
+
+/// Start watching a repository; returns the current status as the baseline
+/// that later `git-status-changed` deltas apply to
+#[tauri::command]
+pub fn git_watch_repository(
+    app: AppHandle,
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+) -> Result<Vec<StatusEntry>, String> {
+    let path = registry.resolve(path);
+    let repo = Repository::open(&path).map_err(GitError::from)?;
+    let workdir = repo
+        .workdir()
+        .ok_or("Cannot watch a bare repository")?
+        .to_path_buf();
+    let to_entries = |status: HashMap<String, String>| {
+        let mut entries: Vec<StatusEntry> = status
+            .into_iter()
+            .map(|(path, code)| StatusEntry { path, code })
+            .collect();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        entries
+    };
+
+    let key = PathBuf::from(&path);
+    let mut watches = WATCHES.lock().map_err(|e| e.to_string())?;
+    if watches.contains_key(&key) {
+        return Ok(to_entries(full_status(&repo)?));
+    }
+
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+    watcher
+        .watch(&workdir, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", workdir.display(), e))?;
+    // A .git outside the worktree (worktrees, separate git dirs)
+    if !repo.path().starts_with(&workdir) {
+        watcher
+            .watch(repo.path(), RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to watch {}: {}", repo.path().display(), e))?;
+    }
+
+    // Changes from here on queue up as events and are diffed against this
+    let snapshot = full_status(&repo)?;
+    let baseline = to_entries(snapshot.clone());
+
+    let repo_path = path.clone();
+    std::thread::Builder::new()
+        .name("git-watcher".to_string())
+        .spawn(move || watch_loop(app, repo_path, rx, snapshot))
+        .map_err(|e| format!("Failed to start git watcher: {}", e))?;
+
+    watches.insert(
+        key,
+        RepoWatch {
+            _watcher: watcher,
+            workdir,
+        },
+    );
+    println!("[GitWatcher] Watching {}", path);
+    Ok(baseline)
+}
+
+/// Stop watching a repository
+#[tauri::command]
+pub fn git_unwatch_repository(
+    registry: State<'_, GitRepoRegistry>,
+    path: String,
+) -> Result<(), String> {
+    let path = registry.resolve(path);
+    // Dropping the watcher closes the channel, which ends the worker thread
+    WATCHES
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(Path::new(&path));
+    Ok(())
+}
@@ -0,0 +1,222 @@
+//! Intraline (word-level) diff highlights
+//!
+//! Pairs the removed and added lines of each change block in a patch and
+//! marks the words (or characters) that differ, like `git diff --word-diff`,
+//! so the diff viewer can highlight them without diffing again in JS.
+//! Ranges are UTF-16 offsets into the line content, without the `+`/`-`.
+
+use super::types::{HighlightRange, IntralineMode, LineHighlight};
+
+/// Token pairs above this are only trimmed by common prefix/suffix
+const MAX_LCS_CELLS: usize = 200_000;
+
+/// Lines sharing less than this fraction of their text aren't highlighted;
+/// the whole line is changed
+const MIN_SIMILARITY: f64 = 0.3;
+
+struct BlockLine {
+    diff_line: usize,
+    lineno: Option<u32>,
+    content: String,
+}
+
+/// Collects patch lines from `Diff::print` and computes highlights per block
+pub(super) struct IntralineCollector {
+    mode: IntralineMode,
+    removed: Vec<BlockLine>,
+    added: Vec<BlockLine>,
+    highlights: Vec<LineHighlight>,
+}
+
+/// Byte range of each token
+fn tokenize(text: &str, mode: IntralineMode) -> Vec<(usize, usize)> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        if mode == IntralineMode::Word {
+            let word = |ch: char| ch.is_alphanumeric() || ch == '_';
+            if word(c) || c.is_whitespace() {
+                let same_class = |ch: char| {
+                    if word(c) {
+                        word(ch)
+                    } else {
+                        ch.is_whitespace()
+                    }
+                };
+                while let Some(&(i, next)) = chars.peek() {
+                    if !same_class(next) {
+                        break;
+                    }
+                    end = i + next.len_utf8();
+                    chars.next();
+                }
+            }
+        }
+        tokens.push((start, end));
+    }
+    tokens
+}
+
+/// Which tokens of `a` and `b` are part of their longest common subsequence
+fn common_tokens(a: &[&str], b: &[&str]) -> (Vec<bool>, Vec<bool>) {
+    let mut keep_a = vec![false; a.len()];
+    let mut keep_b = vec![false; b.len()];
+
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    for i in 0..prefix {
+        keep_a[i] = true;
+        keep_b[i] = true;
+    }
+    for i in 0..suffix {
+        keep_a[a.len() - 1 - i] = true;
+        keep_b[b.len() - 1 - i] = true;
+    }
+
+    let (mid_a, mid_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    let (n, m) = (mid_a.len(), mid_b.len());
+    if n == 0 || m == 0 || n * m > MAX_LCS_CELLS {
+        return (keep_a, keep_b);
+    }
+
+    // lengths[i][j] = LCS of mid_a[i..] and mid_b[j..]
+    let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i * (m + 1) + j] = if mid_a[i] == mid_b[j] {
+                lengths[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                lengths[(i + 1) * (m + 1) + j].max(lengths[i * (m + 1) + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if mid_a[i] == mid_b[j] {
+            keep_a[prefix + i] = true;
+            keep_b[prefix + j] = true;
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * (m + 1) + j] >= lengths[i * (m + 1) + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    (keep_a, keep_b)
+}
+
+/// Merge the changed tokens into UTF-16 ranges
+fn changed_ranges(text: &str, tokens: &[(usize, usize)], keep: &[bool]) -> Vec<HighlightRange> {
+    let mut ranges: Vec<HighlightRange> = Vec::new();
+    let mut offset = 0;
+    for (&(start, end), &kept) in tokens.iter().zip(keep) {
+        let width = text[start..end].encode_utf16().count();
+        if !kept {
+            match ranges.last_mut() {
+                Some(last) if last.end == offset => last.end += width,
+                _ => ranges.push(HighlightRange {
+                    start: offset,
+                    end: offset + width,
+                }),
+            }
+        }
+        offset += width;
+    }
+    ranges
+}
+
+impl IntralineCollector {
+    pub(super) fn new(mode: IntralineMode) -> Self {
+        Self {
+            mode,
+            removed: Vec::new(),
+            added: Vec::new(),
+            highlights: Vec::new(),
+        }
+    }
+
+    /// Feed one patch line; `diff_line` is its 0-based line in the diff text
+    pub(super) fn push(&mut self, line: &git2::DiffLine, diff_line: usize) {
+        let content = String::from_utf8_lossy(line.content())
+            .trim_end_matches(['\n', '\r'])
+            .to_string();
+        match line.origin() {
+            '-' => {
+                // A removal after additions starts a new block
+                if !self.added.is_empty() {
+                    self.flush();
+                }
+                self.removed.push(BlockLine {
+                    diff_line,
+                    lineno: line.old_lineno(),
+                    content,
+                });
+            }
+            '+' => self.added.push(BlockLine {
+                diff_line,
+                lineno: line.new_lineno(),
+                content,
+            }),
+            // "No newline at end of file" markers sit inside a block
+            '=' | '>' | '<' => {}
+            _ => self.flush(),
+        }
+    }
+
+    fn flush(&mut self) {
+        let removed = std::mem::take(&mut self.removed);
+        let added = std::mem::take(&mut self.added);
+        for (old, new) in removed.iter().zip(&added) {
+            let old_tokens = tokenize(&old.content, self.mode);
+            let new_tokens = tokenize(&new.content, self.mode);
+            let a: Vec<&str> = old_tokens
+                .iter()
+                .map(|&(s, e)| &old.content[s..e])
+                .collect();
+            let b: Vec<&str> = new_tokens
+                .iter()
+                .map(|&(s, e)| &new.content[s..e])
+                .collect();
+            let (keep_a, keep_b) = common_tokens(&a, &b);
+
+            let common: usize = a
+                .iter()
+                .zip(&keep_a)
+                .filter(|(_, kept)| **kept)
+                .map(|(t, _)| t.len())
+                .sum();
+            let longest = old.content.len().max(new.content.len()).max(1);
+            if (common as f64) / (longest as f64) < MIN_SIMILARITY {
+                continue;
+            }
+
+            self.highlights.push(LineHighlight {
+                diff_line: old.diff_line,
+                origin: "-".to_string(),
+                old_lineno: old.lineno,
+                new_lineno: None,
+                ranges: changed_ranges(&old.content, &old_tokens, &keep_a),
+            });
+            self.highlights.push(LineHighlight {
+                diff_line: new.diff_line,
+                origin: "+".to_string(),
+                old_lineno: None,
+                new_lineno: new.lineno,
+                ranges: changed_ranges(&new.content, &new_tokens, &keep_b),
+            });
+        }
+    }
+
+    pub(super) fn finish(mut self) -> Vec<LineHighlight> {
+        self.flush();
+        self.highlights.sort_by_key(|h| h.diff_line);
+        self.highlights
+    }
+}
@@ -458,3 +458,224 @@ pub struct Command {
     pub category: String,
     pub keybinding: Option<String>,
 }
+
+/// A binding from the effective keybinding map (defaults merged with user overrides)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeybindingEntry {
+    pub command: String,
+    /// Chord, or space-separated chord sequence (e.g. "Ctrl+K Ctrl+S")
+    pub key: String,
+    pub when: Option<String>,
+    /// "mac", "windows" or "linux"; None applies to all platforms
+    pub platform: Option<String>,
+}
+
+/// Bindings that cannot all fire for the same key press
+#[derive(Debug, Clone, Serialize)]
+pub struct KeybindingConflict {
+    pub platform: String,
+    pub key: String,
+    pub when: Option<String>,
+    /// "duplicate" (same chord) or "prefix" (one chord starts the other's sequence)
+    pub kind: String,
+    pub commands: Vec<String>,
+    /// Unused chords for the same key, best first
+    pub suggestions: Vec<String>,
+}
+
+const KEYBINDING_PLATFORMS: [&str; 3] = ["mac", "windows", "linux"];
+
+/// Normalize one chord: canonical modifier names and order, title-cased key
+fn normalize_chord(chord: &str) -> String {
+    let mut modifiers = [false; 4]; // Ctrl, Shift, Alt, Cmd
+    let mut key = String::new();
+
+    for part in chord.split('+').map(str::trim).filter(|p| !p.is_empty()) {
+        match part.to_lowercase().as_str() {
+            "ctrl" | "control" => modifiers[0] = true,
+            "shift" => modifiers[1] = true,
+            "alt" | "option" | "opt" => modifiers[2] = true,
+            "cmd" | "command" | "meta" | "super" | "win" => modifiers[3] = true,
+            _ => {
+                let mut chars = part.chars();
+                key = match chars.next() {
+                    Some(first) => first
+                        .to_uppercase()
+                        .chain(chars.flat_map(char::to_lowercase))
+                        .collect(),
+                    None => String::new(),
+                };
+            }
+        }
+    }
+    // "Ctrl++" splits into empty parts around the plus key
+    if key.is_empty() && chord.ends_with('+') {
+        key = "+".to_string();
+    }
+
+    let mut parts: Vec<&str> = ["Ctrl", "Shift", "Alt", "Cmd"]
+        .iter()
+        .zip(modifiers)
+        .filter(|(_, on)| *on)
+        .map(|(name, _)| *name)
+        .collect();
+    parts.push(&key);
+    parts.join("+")
+}
+
+/// Normalize a chord sequence
+fn normalize_key_sequence(key: &str) -> Vec<String> {
+    key.split_whitespace().map(normalize_chord).collect()
+}
+
+/// Whether two `when` clauses can be true at the same time
+/// Clauses are compared textually; a missing clause is active everywhere
+fn contexts_overlap(a: &Option<String>, b: &Option<String>) -> bool {
+    let normalize = |w: &str| w.split_whitespace().collect::<String>();
+    match (a, b) {
+        (Some(a), Some(b)) => normalize(a) == normalize(b),
+        _ => true,
+    }
+}
+
+/// Expand the default shortcut list into per-platform entries
+/// Keys containing Cmd are macOS; the others apply to Windows/Linux, or to
+/// every platform when the shortcut has no macOS-specific key
+fn default_keybinding_entries() -> Result<Vec<KeybindingEntry>, String> {
+    let mut entries = Vec::new();
+    for shortcut in get_keyboard_shortcuts()? {
+        let has_mac_key = shortcut.keys.iter().any(|k| k.contains("Cmd"));
+        for key in &shortcut.keys {
+            let platforms: Vec<Option<String>> = if key.contains("Cmd") {
+                vec![Some("mac".to_string())]
+            } else if has_mac_key {
+                vec![Some("windows".to_string()), Some("linux".to_string())]
+            } else {
+                vec![None]
+            };
+            for platform in platforms {
+                entries.push(KeybindingEntry {
+                    command: shortcut.id.clone(),
+                    key: key.clone(),
+                    when: shortcut.when.clone(),
+                    platform,
+                });
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Free chords for the same key on a platform and context
+fn suggest_free_chords(
+    last_chord: &str,
+    platform: &str,
+    when: &Option<String>,
+    bindings: &[(Vec<String>, &KeybindingEntry)],
+) -> Vec<String> {
+    let key = last_chord.rsplit('+').next().unwrap_or(last_chord);
+    let primary = if platform == "mac" { "Cmd" } else { "Ctrl" };
+    let candidates = [
+        format!("{primary}+{key}"),
+        format!("{primary}+Shift+{key}"),
+        format!("{primary}+Alt+{key}"),
+        format!("{primary}+Shift+Alt+{key}"),
+        format!("Alt+{key}"),
+        format!("Shift+Alt+{key}"),
+        format!("{primary}+K {primary}+{key}"),
+    ];
+
+    candidates
+        .iter()
+        .filter(|candidate| {
+            let sequence = normalize_key_sequence(candidate);
+            !bindings.iter().any(|(other, entry)| {
+                let shared = sequence.len().min(other.len());
+                sequence[..shared] == other[..shared] && contexts_overlap(when, &entry.when)
+            })
+        })
+        .map(|candidate| normalize_key_sequence(candidate).join(" "))
+        .take(3)
+        .collect()
+}
+
+/// Detect conflicting keybindings per platform and context
+/// Checks `bindings` (the effective keybinding map), or the default shortcuts
+/// when omitted; `platform` limits the check to one platform
+#[tauri::command]
+pub fn detect_keybinding_conflicts(
+    bindings: Option<Vec<KeybindingEntry>>,
+    platform: Option<String>,
+) -> Result<Vec<KeybindingConflict>, String> {
+    let bindings = match bindings {
+        Some(bindings) => bindings,
+        None => default_keybinding_entries()?,
+    };
+    let platforms: Vec<&str> = match platform.as_deref() {
+        Some(p) if KEYBINDING_PLATFORMS.contains(&p) => vec![p],
+        Some(p) => return Err(format!("Unknown platform: {}", p)),
+        None => KEYBINDING_PLATFORMS.to_vec(),
+    };
+
+    let mut conflicts = Vec::new();
+    for platform in platforms {
+        let active: Vec<(Vec<String>, &KeybindingEntry)> = bindings
+            .iter()
+            .filter(|b| b.platform.as_deref().is_none_or(|p| p == platform))
+            .map(|b| (normalize_key_sequence(&b.key), b))
+            .filter(|(sequence, _)| !sequence.is_empty())
+            .collect();
+
+        // Group bindings that collide with each other, keyed by the shorter sequence
+        let mut groups: Vec<KeybindingConflict> = Vec::new();
+        for (i, (sequence, entry)) in active.iter().enumerate() {
+            for (other_sequence, other) in active.iter().skip(i + 1) {
+                if entry.command == other.command || !contexts_overlap(&entry.when, &other.when) {
+                    continue;
+                }
+
+                let shared = sequence.len().min(other_sequence.len());
+                if sequence[..shared] != other_sequence[..shared] {
+                    continue;
+                }
+
+                let kind = if sequence.len() == other_sequence.len() {
+                    "duplicate"
+                } else {
+                    "prefix"
+                };
+                let key = sequence[..shared].join(" ");
+                let when = entry.when.clone().or_else(|| other.when.clone());
+
+                match groups
+                    .iter_mut()
+                    .find(|g| g.key == key && g.kind == kind && g.when == when)
+                {
+                    Some(group) => {
+                        for command in [&entry.command, &other.command] {
+                            if !group.commands.contains(command) {
+                                group.commands.push(command.clone());
+                            }
+                        }
+                    }
+                    None => groups.push(KeybindingConflict {
+                        platform: platform.to_string(),
+                        key,
+                        when,
+                        kind: kind.to_string(),
+                        commands: vec![entry.command.clone(), other.command.clone()],
+                        suggestions: Vec::new(),
+                    }),
+                }
+            }
+        }
+
+        for group in &mut groups {
+            let last_chord = group.key.rsplit(' ').next().unwrap_or(&group.key);
+            group.suggestions = suggest_free_chords(last_chord, platform, &group.when, &active);
+        }
+        conflicts.extend(groups);
+    }
+
+    Ok(conflicts)
+}
@@ -9,6 +9,9 @@
  * - Pre-computing lookup tables for file extensions, file names, and folder names
  * - Caching loaded icon content (SVG as base64 data URLs)
  * - Batch icon resolution for multiple files
+ * - Light / high contrast variants, following the active UI theme kind
+ * - Serving icon files over the `rainy-icon` URI scheme so the webview can
+ *   cache them instead of receiving base64 strings through IPC
  */
 use base64::{engine::general_purpose::STANDARD, Engine};
 use lru::LruCache;
@@ -17,8 +20,12 @@ use std::collections::HashMap;
 use std::fs;
 use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
-use std::sync::RwLock;
-use tauri::State;
+use std::sync::{Arc, RwLock};
+use tauri::http::{Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, State};
+
+/// URI scheme serving icon files from loaded themes
+pub const ICON_PROTOCOL: &str = "rainy-icon";
 
 /// Icon definition from theme manifest
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -81,16 +88,68 @@ pub struct IconThemeManifest {
     /// Root folder name to icon ID mapping (expanded)
     #[serde(default)]
     pub root_folder_names_expanded: HashMap<String, String>,
-    /// Light theme variant (parsed but not yet used)
-    #[allow(dead_code)]
+    /// Light theme variant (overrides associations in light UI themes)
     #[serde(default)]
     pub light: Option<Box<IconThemeManifest>>,
-    /// High contrast theme variant (parsed but not yet used)
-    #[allow(dead_code)]
+    /// High contrast theme variant (overrides associations in high contrast UI themes)
     #[serde(default)]
     pub high_contrast: Option<Box<IconThemeManifest>>,
 }
 
+/// Kind of the active UI theme, selecting which icon variant applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IconThemeKind {
+    #[default]
+    Dark,
+    Light,
+    #[serde(rename = "hc", alias = "highContrast")]
+    HighContrast,
+}
+
+/// Pre-computed lookups of a light/high contrast variant (lowercase keys)
+/// Entries here take precedence over the base theme; anything missing falls back
+#[derive(Debug, Default)]
+pub struct IconVariantLookups {
+    pub file_extensions: HashMap<String, String>,
+    pub file_names: HashMap<String, String>,
+    pub language_ids: HashMap<String, String>,
+    pub folder_names: HashMap<String, String>,
+    pub folder_names_expanded: HashMap<String, String>,
+    pub root_folder_names: HashMap<String, String>,
+    pub root_folder_names_expanded: HashMap<String, String>,
+    pub default_file: Option<String>,
+    pub default_folder: Option<String>,
+    pub default_folder_expanded: Option<String>,
+    pub root_folder: Option<String>,
+    pub root_folder_expanded: Option<String>,
+}
+
+fn lowercase_keys(map: &HashMap<String, String>) -> HashMap<String, String> {
+    map.iter()
+        .map(|(k, v)| (k.to_lowercase(), v.clone()))
+        .collect()
+}
+
+impl IconVariantLookups {
+    fn from_manifest(manifest: &IconThemeManifest) -> Self {
+        Self {
+            file_extensions: lowercase_keys(&manifest.file_extensions),
+            file_names: lowercase_keys(&manifest.file_names),
+            language_ids: lowercase_keys(&manifest.language_ids),
+            folder_names: lowercase_keys(&manifest.folder_names),
+            folder_names_expanded: lowercase_keys(&manifest.folder_names_expanded),
+            root_folder_names: lowercase_keys(&manifest.root_folder_names),
+            root_folder_names_expanded: lowercase_keys(&manifest.root_folder_names_expanded),
+            default_file: manifest.file.clone(),
+            default_folder: manifest.folder.clone(),
+            default_folder_expanded: manifest.folder_expanded.clone(),
+            root_folder: manifest.root_folder.clone(),
+            root_folder_expanded: manifest.root_folder_expanded.clone(),
+        }
+    }
+}
+
 /// Loaded icon theme with pre-computed lookups and cached icons
 #[derive(Debug)]
 pub struct LoadedIconTheme {
@@ -128,6 +187,21 @@ pub struct LoadedIconTheme {
     pub root_folder: Option<String>,
     /// Root folder expanded icon
     pub root_folder_expanded: Option<String>,
+    /// Light variant lookups
+    pub light: Option<IconVariantLookups>,
+    /// High contrast variant lookups
+    pub high_contrast: Option<IconVariantLookups>,
+}
+
+impl LoadedIconTheme {
+    /// Variant consulted before the base lookups for a theme kind
+    fn variant(&self, kind: IconThemeKind) -> Option<&IconVariantLookups> {
+        match kind {
+            IconThemeKind::Dark => None,
+            IconThemeKind::Light => self.light.as_ref(),
+            IconThemeKind::HighContrast => self.high_contrast.as_ref(),
+        }
+    }
 }
 
 /// Resolved icon result returned to frontend
@@ -158,6 +232,9 @@ pub struct IconThemeInfo {
     pub file_name_count: usize,
 }
 
+/// Raw icon file contents and MIME type
+type IconAsset = (Arc<Vec<u8>>, &'static str);
+
 /// Manager state holding all loaded themes and cache
 pub struct IconThemeManagerState {
     /// All loaded icon themes
@@ -166,6 +243,12 @@ pub struct IconThemeManagerState {
     active_theme_id: RwLock<Option<String>>,
     /// LRU cache for loaded icon content (icon_id -> base64 data URL)
     icon_cache: RwLock<LruCache<String, String>>,
+    /// Kind of the active UI theme (dark, light, high contrast)
+    theme_kind: RwLock<IconThemeKind>,
+    /// Return protocol URLs instead of base64 data URLs in `icon_path`
+    deliver_as_url: RwLock<bool>,
+    /// LRU cache of raw icon files served over the protocol ("theme/icon" -> bytes, mime)
+    asset_cache: RwLock<LruCache<String, IconAsset>>,
 }
 
 impl IconThemeManagerState {
@@ -175,8 +258,15 @@ impl IconThemeManagerState {
             active_theme_id: RwLock::new(None),
             // Cache up to 1000 icons in memory
             icon_cache: RwLock::new(LruCache::new(NonZeroUsize::new(1000).unwrap())),
+            theme_kind: RwLock::new(IconThemeKind::default()),
+            deliver_as_url: RwLock::new(false),
+            asset_cache: RwLock::new(LruCache::new(NonZeroUsize::new(1000).unwrap())),
         }
     }
+
+    fn kind(&self) -> IconThemeKind {
+        self.theme_kind.read().map(|k| *k).unwrap_or_default()
+    }
 }
 
 impl Default for IconThemeManagerState {
@@ -186,7 +276,7 @@ impl Default for IconThemeManagerState {
 }
 
 /// Strip JSONC comments from content
-fn strip_json_comments(content: &str) -> String {
+pub(crate) fn strip_json_comments(content: &str) -> String {
     let mut result = String::with_capacity(content.len());
     let mut chars = content.chars().peekable();
     let mut in_string = false;
@@ -293,26 +383,30 @@ fn resolve_icon_path(base_path: &Path, icon_path: &str) -> PathBuf {
     }
 }
 
-/// Load icon content and convert to base64 data URL
-fn load_icon_as_data_url(icon_path: &Path) -> Result<String, String> {
-    let content = fs::read(icon_path)
-        .map_err(|e| format!("Failed to read icon file {:?}: {}", icon_path, e))?;
-
+/// MIME type of an icon file from its extension
+fn icon_mime_type(icon_path: &Path) -> &'static str {
     let extension = icon_path
         .extension()
         .and_then(|e| e.to_str())
         .unwrap_or("")
         .to_lowercase();
 
-    let mime_type = match extension.as_str() {
+    match extension.as_str() {
         "svg" => "image/svg+xml",
         "png" => "image/png",
         "jpg" | "jpeg" => "image/jpeg",
         "gif" => "image/gif",
         "webp" => "image/webp",
         _ => "image/svg+xml", // Default to SVG
-    };
+    }
+}
 
+/// Load icon content and convert to base64 data URL
+fn load_icon_as_data_url(icon_path: &Path) -> Result<String, String> {
+    let content = fs::read(icon_path)
+        .map_err(|e| format!("Failed to read icon file {:?}: {}", icon_path, e))?;
+
+    let mime_type = icon_mime_type(icon_path);
     let base64_content = STANDARD.encode(&content);
     Ok(format!("data:{};base64,{}", mime_type, base64_content))
 }
@@ -422,12 +516,25 @@ pub fn load_icon_theme(
         default_folder_expanded: manifest.folder_expanded,
         root_folder: manifest.root_folder,
         root_folder_expanded: manifest.root_folder_expanded,
+        light: manifest
+            .light
+            .as_deref()
+            .map(IconVariantLookups::from_manifest),
+        high_contrast: manifest
+            .high_contrast
+            .as_deref()
+            .map(IconVariantLookups::from_manifest),
     };
 
     // Store the loaded theme
     let mut themes = state.themes.write().map_err(|e| e.to_string())?;
     themes.insert(theme_id, loaded_theme);
 
+    // A reloaded theme may point at different files
+    if let Ok(mut assets) = state.asset_cache.write() {
+        assets.clear();
+    }
+
     Ok(theme_info)
 }
 
@@ -447,6 +554,124 @@ pub fn set_active_icon_theme(
     Ok(())
 }
 
+/// Set the kind of the active UI theme ("dark", "light" or "hc")
+/// Icons resolve through the matching variant, so the cache is invalidated on change
+#[tauri::command]
+pub fn set_icon_theme_kind(
+    state: State<'_, IconThemeManagerState>,
+    kind: IconThemeKind,
+) -> Result<(), String> {
+    let mut current = state.theme_kind.write().map_err(|e| e.to_string())?;
+    if *current == kind {
+        return Ok(());
+    }
+    *current = kind;
+
+    let mut cache = state.icon_cache.write().map_err(|e| e.to_string())?;
+    cache.clear();
+
+    Ok(())
+}
+
+/// Get the kind of the active UI theme
+#[tauri::command]
+pub fn get_icon_theme_kind(
+    state: State<'_, IconThemeManagerState>,
+) -> Result<IconThemeKind, String> {
+    Ok(state.kind())
+}
+
+/// Choose how icon files are delivered in `icon_path`:
+/// "dataUrl" (base64, default) or "url" (`rainy-icon` protocol URL)
+#[tauri::command]
+pub fn set_icon_delivery_mode(
+    state: State<'_, IconThemeManagerState>,
+    mode: String,
+) -> Result<(), String> {
+    let as_url = match mode.as_str() {
+        "dataUrl" => false,
+        "url" => true,
+        other => return Err(format!("Unknown icon delivery mode: {}", other)),
+    };
+
+    *state.deliver_as_url.write().map_err(|e| e.to_string())? = as_url;
+
+    // Cached entries hold the previous representation
+    let mut cache = state.icon_cache.write().map_err(|e| e.to_string())?;
+    cache.clear();
+
+    Ok(())
+}
+
+/// Protocol URL for an icon; Windows and Android webviews expose custom
+/// schemes as http://<scheme>.localhost
+fn icon_url(theme_id: &str, icon_id: &str) -> String {
+    let base = if cfg!(any(windows, target_os = "android")) {
+        format!("http://{}.localhost", ICON_PROTOCOL)
+    } else {
+        format!("{}://localhost", ICON_PROTOCOL)
+    };
+    format!(
+        "{}/{}/{}",
+        base,
+        urlencoding::encode(theme_id),
+        urlencoding::encode(icon_id)
+    )
+}
+
+/// Read an icon file through the asset cache
+fn load_icon_asset(
+    state: &IconThemeManagerState,
+    theme_id: &str,
+    icon_id: &str,
+) -> Option<IconAsset> {
+    let key = format!("{}/{}", theme_id, icon_id);
+    if let Some(hit) = state.asset_cache.write().ok()?.get(&key) {
+        return Some(hit.clone());
+    }
+
+    let full_path = {
+        let themes = state.themes.read().ok()?;
+        let theme = themes.get(theme_id)?;
+        let icon_path = theme.icon_definitions.get(icon_id)?.icon_path.as_ref()?;
+        resolve_icon_path(&theme.base_path, icon_path)
+    };
+
+    let bytes = Arc::new(fs::read(&full_path).ok()?);
+    let asset = (bytes, icon_mime_type(&full_path));
+    state.asset_cache.write().ok()?.put(key, asset.clone());
+    Some(asset)
+}
+
+/// Handle a `rainy-icon://localhost/<theme_id>/<icon_id>` request
+pub fn handle_icon_protocol(app: &AppHandle, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let state = app.state::<IconThemeManagerState>();
+
+    let mut segments = request.uri().path().trim_start_matches('/').splitn(2, '/');
+    let asset = match (segments.next(), segments.next()) {
+        (Some(theme_id), Some(icon_id)) => {
+            let theme_id = urlencoding::decode(theme_id).unwrap_or_default();
+            let icon_id = urlencoding::decode(icon_id).unwrap_or_default();
+            load_icon_asset(&state, &theme_id, &icon_id)
+        }
+        _ => None,
+    };
+
+    match asset {
+        Some((bytes, mime_type)) => Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", mime_type)
+            .header("Cache-Control", "public, max-age=3600")
+            .header("Access-Control-Allow-Origin", "*")
+            .body(bytes.as_ref().clone())
+            .unwrap_or_default(),
+        None => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Vec::new())
+            .unwrap_or_default(),
+    }
+}
+
 /// Get the currently active icon theme ID
 #[tauri::command]
 pub fn get_active_icon_theme(
@@ -475,36 +700,10 @@ pub fn get_file_icon(
         None => return Ok(None),
     };
 
-    let file_name_lower = file_name.to_lowercase();
-
-    // 1. Check exact file name match
-    if let Some(icon_id) = theme.file_names.get(&file_name_lower) {
-        return resolve_icon(&state, theme, icon_id);
+    match match_file_icon_id(theme, state.kind(), &file_name, language_id.as_deref()) {
+        Some(icon_id) => resolve_icon(&state, theme, icon_id),
+        None => Ok(None),
     }
-
-    // 2. Check file extension matches (try multi-part extensions first)
-    let parts: Vec<&str> = file_name_lower.split('.').collect();
-    for i in 1..parts.len() {
-        let ext = parts[i..].join(".");
-        if let Some(icon_id) = theme.file_extensions.get(&ext) {
-            return resolve_icon(&state, theme, icon_id);
-        }
-    }
-
-    // 3. Check language ID
-    if let Some(lang_id) = language_id {
-        let lang_id_lower = lang_id.to_lowercase();
-        if let Some(icon_id) = theme.language_ids.get(&lang_id_lower) {
-            return resolve_icon(&state, theme, icon_id);
-        }
-    }
-
-    // 4. Fall back to default file icon
-    if let Some(ref icon_id) = theme.default_file {
-        return resolve_icon(&state, theme, icon_id);
-    }
-
-    Ok(None)
 }
 
 /// Get icon for a folder by name
@@ -527,55 +726,10 @@ pub fn get_folder_icon(
         None => return Ok(None),
     };
 
-    let folder_name_lower = folder_name.to_lowercase();
-
-    // 1. Check root folder name match
-    if is_root {
-        let root_map = if is_expanded {
-            &theme.root_folder_names_expanded
-        } else {
-            &theme.root_folder_names
-        };
-
-        if let Some(icon_id) = root_map.get(&folder_name_lower) {
-            return resolve_icon(&state, theme, icon_id);
-        }
-
-        // Fall back to root folder default
-        let default_root = if is_expanded {
-            &theme.root_folder_expanded
-        } else {
-            &theme.root_folder
-        };
-
-        if let Some(ref icon_id) = default_root {
-            return resolve_icon(&state, theme, icon_id);
-        }
-    }
-
-    // 2. Check folder name match
-    let folder_map = if is_expanded {
-        &theme.folder_names_expanded
-    } else {
-        &theme.folder_names
-    };
-
-    if let Some(icon_id) = folder_map.get(&folder_name_lower) {
-        return resolve_icon(&state, theme, icon_id);
-    }
-
-    // 3. Fall back to default folder icon
-    let default_folder = if is_expanded {
-        &theme.default_folder_expanded
-    } else {
-        &theme.default_folder
-    };
-
-    if let Some(ref icon_id) = default_folder {
-        return resolve_icon(&state, theme, icon_id);
+    match match_folder_icon_id(theme, state.kind(), &folder_name, is_expanded, is_root) {
+        Some(icon_id) => resolve_icon(&state, theme, icon_id),
+        None => Ok(None),
     }
-
-    Ok(None)
 }
 
 /// Resolve icons for multiple files in a single batch call (for performance)
@@ -609,38 +763,133 @@ pub fn get_icons_batch(
         }
     };
 
+    let kind = state.kind();
     for file_name in files {
-        let file_name_lower = file_name.to_lowercase();
-        let mut icon: Option<ResolvedIcon> = None;
+        let icon = match_file_icon_id(theme, kind, &file_name, None)
+            .and_then(|icon_id| resolve_icon(&state, theme, icon_id).ok().flatten());
+        results.insert(file_name, icon);
+    }
 
-        // 1. Check exact file name match
-        if let Some(icon_id) = theme.file_names.get(&file_name_lower) {
-            icon = resolve_icon(&state, theme, icon_id).ok().flatten();
-        }
+    Ok(results)
+}
 
-        // 2. Check file extension matches
-        if icon.is_none() {
-            let parts: Vec<&str> = file_name_lower.split('.').collect();
-            for i in 1..parts.len() {
-                let ext = parts[i..].join(".");
-                if let Some(icon_id) = theme.file_extensions.get(&ext) {
-                    icon = resolve_icon(&state, theme, icon_id).ok().flatten();
-                    break;
-                }
-            }
-        }
+/// An explorer row to resolve in a batch
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplorerIconRequest {
+    pub name: String,
+    #[serde(default)]
+    pub is_dir: bool,
+    #[serde(default)]
+    pub is_expanded: bool,
+    #[serde(default)]
+    pub is_root: bool,
+    /// Two-letter porcelain status code (e.g. " M", "A ", "??", "UU")
+    #[serde(default)]
+    pub git_status: Option<String>,
+    #[serde(default)]
+    pub language_id: Option<String>,
+}
 
-        // 3. Fall back to default
-        if icon.is_none() {
-            if let Some(ref icon_id) = theme.default_file {
-                icon = resolve_icon(&state, theme, icon_id).ok().flatten();
-            }
+/// Git decoration for an explorer row
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplorerDecoration {
+    /// Single-letter badge (files only)
+    pub badge: Option<String>,
+    /// Theme color ID for the label
+    pub color: String,
+    pub tooltip: String,
+    pub strike_through: bool,
+    pub faded: bool,
+}
+
+/// Icon and decoration for one explorer row
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplorerIconResult {
+    pub name: String,
+    pub icon: Option<ResolvedIcon>,
+    pub decoration: Option<ExplorerDecoration>,
+}
+
+/// Map a porcelain status code to an explorer decoration
+/// Folders get the color only, as they aggregate their children's status
+fn git_decoration(code: &str, is_dir: bool) -> Option<ExplorerDecoration> {
+    let (badge, color, tooltip) = match code {
+        "" | "  " => return None,
+        "!!" => ("I", "gitDecoration.ignoredResourceForeground", "Ignored"),
+        "??" => (
+            "U",
+            "gitDecoration.untrackedResourceForeground",
+            "Untracked",
+        ),
+        "UU" | "AA" | "DD" | "AU" | "UA" | "DU" | "UD" => (
+            "!",
+            "gitDecoration.conflictingResourceForeground",
+            "Conflict",
+        ),
+        c if c.contains('D') => ("D", "gitDecoration.deletedResourceForeground", "Deleted"),
+        c if c.contains('A') => ("A", "gitDecoration.addedResourceForeground", "Added"),
+        c if c.contains('R') => ("R", "gitDecoration.renamedResourceForeground", "Renamed"),
+        c if c.contains('M') || c.contains('T') => {
+            ("M", "gitDecoration.modifiedResourceForeground", "Modified")
         }
+        _ => return None,
+    };
 
-        results.insert(file_name, icon);
-    }
+    Some(ExplorerDecoration {
+        badge: (!is_dir).then(|| badge.to_string()),
+        color: color.to_string(),
+        tooltip: if is_dir {
+            format!("Contains {} files", tooltip.to_lowercase())
+        } else {
+            tooltip.to_string()
+        },
+        strike_through: !is_dir && badge == "D",
+        faded: badge == "I",
+    })
+}
 
-    Ok(results)
+/// Resolve icons and git decorations for explorer rows in one call
+/// Results are returned in request order
+#[tauri::command]
+pub fn get_explorer_icons_batch(
+    state: State<'_, IconThemeManagerState>,
+    entries: Vec<ExplorerIconRequest>,
+) -> Result<Vec<ExplorerIconResult>, String> {
+    let active = state
+        .active_theme_id
+        .read()
+        .map_err(|e| e.to_string())?
+        .clone();
+    let themes = state.themes.read().map_err(|e| e.to_string())?;
+    let theme = active.as_ref().and_then(|id| themes.get(id));
+    let kind = state.kind();
+
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let icon = theme.and_then(|theme| {
+                let icon_id = if entry.is_dir {
+                    match_folder_icon_id(theme, kind, &entry.name, entry.is_expanded, entry.is_root)
+                } else {
+                    match_file_icon_id(theme, kind, &entry.name, entry.language_id.as_deref())
+                };
+                icon_id.and_then(|id| resolve_icon(&state, theme, id).ok().flatten())
+            });
+            let decoration = entry
+                .git_status
+                .as_deref()
+                .and_then(|code| git_decoration(code, entry.is_dir));
+
+            ExplorerIconResult {
+                name: entry.name,
+                icon,
+                decoration,
+            }
+        })
+        .collect())
 }
 
 /// Unregister an icon theme
@@ -652,6 +901,10 @@ pub fn unregister_icon_theme(
     let mut themes = state.themes.write().map_err(|e| e.to_string())?;
     themes.remove(&theme_id);
 
+    if let Ok(mut assets) = state.asset_cache.write() {
+        assets.clear();
+    }
+
     // If this was the active theme, clear it
     let mut active = state.active_theme_id.write().map_err(|e| e.to_string())?;
     if active.as_ref() == Some(&theme_id) {
@@ -681,6 +934,144 @@ pub fn get_loaded_icon_themes(
         .collect())
 }
 
+/// Look a key up in the variant map first, then in the base map
+fn lookup<'t>(
+    variant: Option<&'t HashMap<String, String>>,
+    base: &'t HashMap<String, String>,
+    key: &str,
+) -> Option<&'t str> {
+    variant
+        .and_then(|map| map.get(key))
+        .or_else(|| base.get(key))
+        .map(|id| id.as_str())
+}
+
+/// Find the icon ID for a file: exact name, extension (longest first), language, default
+/// Each step consults the variant for `kind` before the base theme
+fn match_file_icon_id<'t>(
+    theme: &'t LoadedIconTheme,
+    kind: IconThemeKind,
+    file_name: &str,
+    language_id: Option<&str>,
+) -> Option<&'t str> {
+    let variant = theme.variant(kind);
+    let file_name_lower = file_name.to_lowercase();
+
+    // 1. Check exact file name match
+    if let Some(icon_id) = lookup(
+        variant.map(|v| &v.file_names),
+        &theme.file_names,
+        &file_name_lower,
+    ) {
+        return Some(icon_id);
+    }
+
+    // 2. Check file extension matches (try multi-part extensions first)
+    let parts: Vec<&str> = file_name_lower.split('.').collect();
+    for i in 1..parts.len() {
+        let ext = parts[i..].join(".");
+        if let Some(icon_id) = lookup(
+            variant.map(|v| &v.file_extensions),
+            &theme.file_extensions,
+            &ext,
+        ) {
+            return Some(icon_id);
+        }
+    }
+
+    // 3. Check language ID
+    if let Some(lang_id) = language_id {
+        if let Some(icon_id) = lookup(
+            variant.map(|v| &v.language_ids),
+            &theme.language_ids,
+            &lang_id.to_lowercase(),
+        ) {
+            return Some(icon_id);
+        }
+    }
+
+    // 4. Fall back to default file icon
+    variant
+        .and_then(|v| v.default_file.as_deref())
+        .or(theme.default_file.as_deref())
+}
+
+/// Find the icon ID for a folder: root names/default, folder names, default
+/// Each step consults the variant for `kind` before the base theme
+fn match_folder_icon_id<'t>(
+    theme: &'t LoadedIconTheme,
+    kind: IconThemeKind,
+    folder_name: &str,
+    is_expanded: bool,
+    is_root: bool,
+) -> Option<&'t str> {
+    let variant = theme.variant(kind);
+    let folder_name_lower = folder_name.to_lowercase();
+
+    // 1. Check root folder name match
+    if is_root {
+        let icon_id = if is_expanded {
+            lookup(
+                variant.map(|v| &v.root_folder_names_expanded),
+                &theme.root_folder_names_expanded,
+                &folder_name_lower,
+            )
+        } else {
+            lookup(
+                variant.map(|v| &v.root_folder_names),
+                &theme.root_folder_names,
+                &folder_name_lower,
+            )
+        };
+        if icon_id.is_some() {
+            return icon_id;
+        }
+
+        // Fall back to root folder default
+        let default_root = if is_expanded {
+            variant
+                .and_then(|v| v.root_folder_expanded.as_deref())
+                .or(theme.root_folder_expanded.as_deref())
+        } else {
+            variant
+                .and_then(|v| v.root_folder.as_deref())
+                .or(theme.root_folder.as_deref())
+        };
+        if default_root.is_some() {
+            return default_root;
+        }
+    }
+
+    // 2. Check folder name match
+    let icon_id = if is_expanded {
+        lookup(
+            variant.map(|v| &v.folder_names_expanded),
+            &theme.folder_names_expanded,
+            &folder_name_lower,
+        )
+    } else {
+        lookup(
+            variant.map(|v| &v.folder_names),
+            &theme.folder_names,
+            &folder_name_lower,
+        )
+    };
+    if icon_id.is_some() {
+        return icon_id;
+    }
+
+    // 3. Fall back to default folder icon
+    if is_expanded {
+        variant
+            .and_then(|v| v.default_folder_expanded.as_deref())
+            .or(theme.default_folder_expanded.as_deref())
+    } else {
+        variant
+            .and_then(|v| v.default_folder.as_deref())
+            .or(theme.default_folder.as_deref())
+    }
+}
+
 /// Helper function to resolve an icon ID to its content
 fn resolve_icon(
     state: &State<'_, IconThemeManagerState>,
@@ -718,6 +1109,17 @@ fn resolve_icon(
         }));
     }
 
+    // In URL mode the webview fetches (and caches) the file over the protocol
+    if icon_def.icon_path.is_some() && state.deliver_as_url.read().map(|d| *d).unwrap_or(false) {
+        return Ok(Some(ResolvedIcon {
+            icon_id: icon_id.to_string(),
+            icon_path: Some(icon_url(&theme.id, icon_id)),
+            font_character: None,
+            font_color: None,
+            font_id: None,
+        }));
+    }
+
     // Load icon file and convert to data URL
     if let Some(ref icon_path) = icon_def.icon_path {
         let full_path = resolve_icon_path(&theme.base_path, icon_path);
@@ -0,0 +1,705 @@
+//! Indentation
+//!
+//! Indent services that behave the same for every language instead of
+//! depending on each language server's formatter:
+//! - `indentation_detect` guesses whether a text indents with tabs or spaces
+//! - `indentation_convert` switches between tabs and spaces or resizes the
+//!   indent unit
+//! - `indentation_reindent_paste` fits a pasted block to where it lands
+//!
+//! Structure comes from tree-sitter. A line's nesting level is the number of
+//! bracket pairs (`{}`, `()`, `[]`) open around it, which holds for every
+//! brace language; Python and languages without a grammar keep the block's
+//! relative indentation, converted to the target unit. Lines inside
+//! multi-line strings are never touched, and block comment lines move with
+//! the line that starts the comment. `editor.reindentOnPaste` turns
+//! re-indentation off, globally (`false`) or per language
+//! (`{ "python": false }`).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use tree_sitter::{Node, Parser, Tree};
+
+/// Larger documents are not parsed; the cursor line's indent is used instead
+const MAX_PARSE_BYTES: usize = 2 * 1024 * 1024;
+
+const DEFAULT_TAB_SIZE: usize = 4;
+
+/// Largest indent unit `detect` will guess
+const MAX_DETECTED_UNIT: usize = 8;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IndentStyle {
+    pub insert_spaces: bool,
+    /// Columns per indent level (the tab width when indenting with tabs)
+    pub tab_size: usize,
+}
+
+impl Default for IndentStyle {
+    fn default() -> Self {
+        Self {
+            insert_spaces: true,
+            tab_size: DEFAULT_TAB_SIZE,
+        }
+    }
+}
+
+impl IndentStyle {
+    fn normalized(self) -> Self {
+        Self {
+            insert_spaces: self.insert_spaces,
+            tab_size: self.tab_size.max(1),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IndentConversion {
+    /// Current style; detected from the text when omitted
+    pub from: Option<IndentStyle>,
+    pub to: IndentStyle,
+    /// Enables skipping lines inside multi-line strings
+    pub language_id: Option<String>,
+    pub path: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertedIndentation {
+    pub text: String,
+    pub from: IndentStyle,
+    pub changed_lines: usize,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PasteRequest {
+    /// Document path; its editor content is used when the document is open
+    pub path: Option<String>,
+    /// Document text, needed when `path` isn't open
+    pub text: Option<String>,
+    pub language_id: Option<String>,
+    /// 1-based cursor position, column in UTF-16 code units (as in Monaco)
+    pub line: usize,
+    pub column: usize,
+    pub pasted: String,
+    /// Target style; detected from the document when omitted
+    pub style: Option<IndentStyle>,
+    pub workspace_root: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReindentedPaste {
+    pub text: String,
+    /// 1-based range on the cursor line to replace with `text`; it starts at
+    /// column 1 when the whitespace before the cursor is re-indented too
+    pub line: usize,
+    pub start_column: usize,
+    pub end_column: usize,
+    /// False when the paste was left as is (single line, or disabled for
+    /// the language)
+    pub applied: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Grammar {
+    Rust,
+    TypeScript,
+    Tsx,
+    JavaScript,
+    Python,
+    Go,
+    Json,
+    C,
+    Cpp,
+    Java,
+    Css,
+}
+
+impl Grammar {
+    fn from_language_id(id: &str) -> Option<Self> {
+        match id.to_lowercase().as_str() {
+            "rust" => Some(Self::Rust),
+            "typescript" => Some(Self::TypeScript),
+            "typescriptreact" | "tsx" => Some(Self::Tsx),
+            "javascript" | "javascriptreact" | "jsx" => Some(Self::JavaScript),
+            "python" => Some(Self::Python),
+            "go" => Some(Self::Go),
+            "json" | "jsonc" => Some(Self::Json),
+            "c" => Some(Self::C),
+            "cpp" => Some(Self::Cpp),
+            "java" => Some(Self::Java),
+            "css" | "scss" | "less" => Some(Self::Css),
+            _ => None,
+        }
+    }
+
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "rs" => Some(Self::Rust),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            "tsx" => Some(Self::Tsx),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Self::JavaScript),
+            "py" | "pyi" => Some(Self::Python),
+            "go" => Some(Self::Go),
+            "json" | "jsonc" => Some(Self::Json),
+            "c" | "h" => Some(Self::C),
+            "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => Some(Self::Cpp),
+            "java" => Some(Self::Java),
+            "css" | "scss" | "less" => Some(Self::Css),
+            _ => None,
+        }
+    }
+
+    fn resolve(language_id: Option<&str>, path: Option<&str>) -> Option<Self> {
+        language_id
+            .and_then(Self::from_language_id)
+            .or_else(|| path.and_then(|p| Self::from_path(Path::new(p))))
+    }
+
+    /// Editor language id, used for per-language settings
+    fn id(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::TypeScript => "typescript",
+            Self::Tsx => "typescriptreact",
+            Self::JavaScript => "javascript",
+            Self::Python => "python",
+            Self::Go => "go",
+            Self::Json => "json",
+            Self::C => "c",
+            Self::Cpp => "cpp",
+            Self::Java => "java",
+            Self::Css => "css",
+        }
+    }
+
+    fn language(self) -> tree_sitter::Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+            Self::Json => tree_sitter_json::LANGUAGE.into(),
+            Self::C => tree_sitter_c::LANGUAGE.into(),
+            Self::Cpp => tree_sitter_cpp::LANGUAGE.into(),
+            Self::Java => tree_sitter_java::LANGUAGE.into(),
+            Self::Css => tree_sitter_css::LANGUAGE.into(),
+        }
+    }
+
+    /// Indentation is the syntax, so brackets don't describe the structure
+    fn indentation_sensitive(self) -> bool {
+        self == Self::Python
+    }
+}
+
+fn parse(grammar: Grammar, text: &str) -> Option<Tree> {
+    if text.len() > MAX_PARSE_BYTES {
+        return None;
+    }
+    let mut parser = Parser::new();
+    parser.set_language(&grammar.language()).ok()?;
+    parser.parse(text, None)
+}
+
+fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// Width of leading whitespace in columns
+fn columns(whitespace: &str, tab_size: usize) -> usize {
+    whitespace.chars().fold(0, |cols, ch| {
+        if ch == '\t' {
+            cols + tab_size - cols % tab_size
+        } else {
+            cols + 1
+        }
+    })
+}
+
+fn render(cols: usize, style: IndentStyle) -> String {
+    if style.insert_spaces {
+        " ".repeat(cols)
+    } else {
+        format!(
+            "{}{}",
+            "\t".repeat(cols / style.tab_size),
+            " ".repeat(cols % style.tab_size)
+        )
+    }
+}
+
+/// How a line relates to tokens spanning several lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    Code,
+    /// Starts inside a string, so its whitespace is content
+    InString,
+    /// Continues a block comment that starts on the given line
+    InComment(usize),
+}
+
+/// Per-line structure of a parsed text
+struct Structure {
+    /// Bracket nesting at the start of each line; negative after closers
+    /// without an opener (a pasted block that ends a scope)
+    levels: Vec<i64>,
+    kinds: Vec<LineKind>,
+    /// Byte ranges between matched brackets; `None` runs to the end
+    pairs: Vec<(usize, Option<usize>)>,
+}
+
+impl Structure {
+    /// Brackets enclosing a byte offset
+    fn depth_at(&self, offset: usize) -> i64 {
+        self.pairs
+            .iter()
+            .filter(|(open, close)| *open <= offset && close.is_none_or(|close| offset <= close))
+            .count() as i64
+    }
+}
+
+fn closer_for(open: &str) -> &'static str {
+    match open {
+        "{" => "}",
+        "(" => ")",
+        _ => "]",
+    }
+}
+
+/// Whether a token is the first thing on its line
+fn leads_line(node: &Node, lines: &[&str]) -> bool {
+    let position = node.start_position();
+    lines
+        .get(position.row)
+        .is_some_and(|line| leading_whitespace(line).len() == position.column)
+}
+
+/// Count the lines between a bracket pair as one level deeper; a closer that
+/// starts its line sits at the outer level
+fn add_scope(diff: &mut [i64], lines: &[&str], open: &Node, close: Option<&Node>) {
+    let rows = lines.len();
+    let from = open.start_position().row + 1;
+    let to = match close {
+        Some(close) if leads_line(close, lines) => close.start_position().row,
+        Some(close) => close.start_position().row + 1,
+        None => rows,
+    };
+    if from < to {
+        diff[from] += 1;
+        diff[to] -= 1;
+    }
+}
+
+fn analyze(tree: &Tree, lines: &[&str]) -> Structure {
+    let rows = lines.len();
+    let mut diff = vec![0i64; rows + 1];
+    let mut kinds = vec![LineKind::Code; rows];
+    let mut pairs = Vec::new();
+
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let kind = node.kind();
+        let (start, end) = (node.start_position(), node.end_position());
+        if node.is_named()
+            && start.row < end.row
+            && (kind.contains("string") || kind.contains("comment"))
+        {
+            // A node ending at column 0 stops before that line
+            let last = if end.column > 0 { end.row } else { end.row - 1 };
+            let line_kind = if kind.contains("comment") {
+                LineKind::InComment(start.row)
+            } else {
+                LineKind::InString
+            };
+            let last = last.min(rows.saturating_sub(1));
+            if start.row < last {
+                kinds[start.row + 1..=last].fill(line_kind);
+            }
+            continue;
+        }
+
+        let mut cursor = node.walk();
+        let mut open: Vec<Node> = Vec::new();
+        for child in node.children(&mut cursor) {
+            if child.child_count() > 0 {
+                stack.push(child);
+                continue;
+            }
+            match child.kind() {
+                "{" | "(" | "[" if !child.is_named() => open.push(child),
+                "}" | ")" | "]" if !child.is_named() && !child.is_missing() => {
+                    match open
+                        .iter()
+                        .rposition(|o| closer_for(o.kind()) == child.kind())
+                    {
+                        Some(index) => {
+                            let opener = open.remove(index);
+                            add_scope(&mut diff, lines, &opener, Some(&child));
+                            pairs.push((opener.end_byte(), Some(child.start_byte())));
+                        }
+                        None => {
+                            let row = child.start_position().row;
+                            let from = if leads_line(&child, lines) {
+                                row
+                            } else {
+                                row + 1
+                            };
+                            diff[from.min(rows)] -= 1;
+                            diff[rows] += 1;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        for opener in open {
+            add_scope(&mut diff, lines, &opener, None);
+            pairs.push((opener.end_byte(), None));
+        }
+    }
+
+    let mut level = 0;
+    let levels = diff[..rows]
+        .iter()
+        .map(|delta| {
+            level += delta;
+            level
+        })
+        .collect();
+    Structure {
+        levels,
+        kinds,
+        pairs,
+    }
+}
+
+/// Guess the indent style of a text; None when no line is indented
+pub(crate) fn detect(text: &str) -> Option<IndentStyle> {
+    let mut tab_lines = 0;
+    let mut space_lines = 0;
+    let mut deltas = [0usize; MAX_DETECTED_UNIT + 1];
+    let mut previous = 0usize;
+    for line in text.lines() {
+        let whitespace = leading_whitespace(line);
+        let rest = &line[whitespace.len()..];
+        // Blank lines and block comment continuations say nothing
+        if rest.trim().is_empty() || rest.starts_with('*') {
+            continue;
+        }
+        if whitespace.starts_with('\t') {
+            tab_lines += 1;
+            continue;
+        }
+        if !whitespace.is_empty() {
+            space_lines += 1;
+        }
+        if whitespace.contains('\t') {
+            continue;
+        }
+        let delta = whitespace.len().abs_diff(previous);
+        if (2..=MAX_DETECTED_UNIT).contains(&delta) {
+            deltas[delta] += 1;
+        }
+        previous = whitespace.len();
+    }
+
+    if tab_lines == 0 && space_lines == 0 {
+        return None;
+    }
+    if tab_lines > space_lines {
+        return Some(IndentStyle {
+            insert_spaces: false,
+            tab_size: DEFAULT_TAB_SIZE,
+        });
+    }
+    // Most common step; ties go to the smaller unit, which divides the larger
+    let unit = (2..=MAX_DETECTED_UNIT)
+        .filter(|&d| deltas[d] > 0)
+        .max_by_key(|&d| (deltas[d], std::cmp::Reverse(d)))
+        .unwrap_or(DEFAULT_TAB_SIZE);
+    Some(IndentStyle {
+        insert_spaces: true,
+        tab_size: unit,
+    })
+}
+
+/// Re-indent `pasted` for insertion at byte `offset` of `document`.
+/// Returns the new text and whether it replaces the whitespace before the
+/// cursor as well.
+fn reindent(
+    grammar: Option<Grammar>,
+    document: &str,
+    offset: usize,
+    pasted: &str,
+    style: IndentStyle,
+) -> (String, bool) {
+    let line_start = document[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let prefix = &document[line_start..offset];
+    let mid_line = !prefix.trim().is_empty();
+
+    let lines: Vec<&str> = pasted
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect();
+    if lines.len() < 2 {
+        return (pasted.to_string(), false);
+    }
+    let normalized = lines.join("\n");
+    let unit = style.tab_size as i64;
+
+    let structure = grammar
+        .and_then(|g| parse(g, &normalized))
+        .map(|tree| analyze(&tree, &lines));
+    let structural = grammar.is_some_and(|g| !g.indentation_sensitive()) && structure.is_some();
+    let kinds: Vec<LineKind> = structure
+        .as_ref()
+        .map(|s| s.kinds.clone())
+        .unwrap_or_else(|| vec![LineKind::Code; lines.len()]);
+
+    // Levels relative to the cursor line when pasting after text on it,
+    // otherwise to the enclosing scope
+    let rel: Vec<i64> = match (&structure, structural) {
+        (Some(structure), true) => {
+            let anchor = if mid_line { structure.levels[0] } else { 0 };
+            structure
+                .levels
+                .iter()
+                .map(|level| level - anchor)
+                .collect()
+        }
+        _ => vec![0; lines.len()],
+    };
+
+    let base = if mid_line {
+        columns(leading_whitespace(prefix), style.tab_size) as i64
+    } else {
+        let depth = grammar
+            .filter(|_| structural)
+            .and_then(|g| parse(g, document))
+            .map(|tree| {
+                let document_lines: Vec<&str> = document.split('\n').collect();
+                analyze(&tree, &document_lines).depth_at(offset)
+            });
+        match depth {
+            Some(depth) => depth * unit,
+            None => columns(prefix, style.tab_size) as i64,
+        }
+    };
+
+    let mut cols: Vec<i64> = lines
+        .iter()
+        .map(|line| columns(leading_whitespace(line), style.tab_size) as i64)
+        .collect();
+    let code_lines: Vec<usize> = (1..lines.len())
+        .filter(|&i| kinds[i] == LineKind::Code && !lines[i].trim().is_empty())
+        .collect();
+    // The block's own indent unit, for scaling relative indentation
+    let code_text: Vec<&str> = code_lines.iter().map(|&i| lines[i]).collect();
+    let source_unit = detect(&code_text.join("\n"))
+        .filter(|s| s.insert_spaces)
+        .map_or(unit, |s| s.tab_size as i64);
+
+    // Original column of level 0; a first line copied without its indent
+    // has it estimated from the lines below
+    let origin = if !leading_whitespace(lines[0]).is_empty() || code_lines.is_empty() {
+        cols[0] - rel[0] * source_unit
+    } else if structural {
+        let lowest = code_lines
+            .iter()
+            .map(|&i| cols[i] - rel[i] * source_unit)
+            .min()
+            .unwrap_or(0);
+        // After an unfinished first line (`x.iter()`), a line at the same
+        // level is a continuation, indented one unit further
+        let first = lines[0].trim_end();
+        let finished = first.ends_with([';', '}', ',', ']']) || first.starts_with("//");
+        let next = lines[code_lines[0]].trim_start();
+        let continued =
+            !finished && !next.starts_with(['{', '}', ')', ']']) && rel[code_lines[0]] == rel[0];
+        (lowest - if continued { source_unit } else { 0 }).max(0)
+    } else {
+        let opens_block = lines[0].trim_end().ends_with([':', '{', '(', '[']);
+        (cols[code_lines[0]] - if opens_block { source_unit } else { 0 }).max(0)
+    };
+    if leading_whitespace(lines[0]).is_empty() {
+        cols[0] = origin + rel[0] * source_unit;
+    }
+
+    let scale = |value: i64| value * unit / source_unit.max(1);
+    let mut new_cols = vec![0i64; lines.len()];
+    let mut output = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        let content = line.trim_start_matches([' ', '\t']);
+        if i == 0 && mid_line {
+            new_cols[0] = base;
+            output.push(line.to_string());
+            continue;
+        }
+        if kinds[i] == LineKind::InString {
+            output.push(line.to_string());
+            continue;
+        }
+        if content.is_empty() {
+            output.push(String::new());
+            continue;
+        }
+        let target = match kinds[i] {
+            LineKind::InComment(start) => cols[i] + new_cols[start] - cols[start],
+            _ if structural => {
+                let extra = (cols[i] - origin - rel[i] * source_unit).max(0);
+                base + rel[i] * unit + scale(extra)
+            }
+            _ => base + scale(cols[i] - origin),
+        }
+        .max(0);
+        new_cols[i] = target;
+        output.push(format!("{}{}", render(target as usize, style), content));
+    }
+
+    let newline = if pasted.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    (output.join(newline), !mid_line)
+}
+
+/// Byte offset of a 1-based line and UTF-16 column
+fn offset_of(text: &str, line: usize, column: usize) -> usize {
+    let mut start = 0;
+    for _ in 1..line {
+        match text[start..].find('\n') {
+            Some(i) => start += i + 1,
+            None => return text.len(),
+        }
+    }
+    let end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+    let mut units = 0;
+    for (i, ch) in text[start..end].char_indices() {
+        if units >= column.saturating_sub(1) {
+            return start + i;
+        }
+        units += ch.len_utf16();
+    }
+    end
+}
+
+fn reindent_enabled(workspace_root: Option<&str>, language_id: Option<&str>) -> bool {
+    match crate::configuration_manager::resolve_setting(workspace_root, "editor.reindentOnPaste") {
+        Some(Value::Bool(enabled)) => enabled,
+        Some(Value::Object(languages)) => language_id
+            .and_then(|id| languages.get(id))
+            .or_else(|| languages.get("*"))
+            .and_then(Value::as_bool)
+            .unwrap_or(true),
+        _ => true,
+    }
+}
+
+/// Guess the indent style of a text
+#[tauri::command]
+pub fn indentation_detect(text: String) -> Result<Option<IndentStyle>, String> {
+    Ok(detect(&text))
+}
+
+/// Convert leading indentation between tabs and spaces or to another unit
+/// size; partial levels (alignment) are kept as is
+#[tauri::command]
+pub fn indentation_convert(
+    text: String,
+    options: IndentConversion,
+) -> Result<ConvertedIndentation, String> {
+    let to = options.to.normalized();
+    let from = options
+        .from
+        .or_else(|| detect(&text))
+        .unwrap_or(to)
+        .normalized();
+
+    let lines: Vec<&str> = text.split('\n').collect();
+    let kinds = Grammar::resolve(options.language_id.as_deref(), options.path.as_deref())
+        .and_then(|g| parse(g, &text))
+        .map(|tree| analyze(&tree, &lines).kinds)
+        .unwrap_or_else(|| vec![LineKind::Code; lines.len()]);
+
+    let mut changed_lines = 0;
+    let converted: Vec<String> = lines
+        .iter()
+        .zip(kinds)
+        .map(|(line, kind)| {
+            let whitespace = leading_whitespace(line);
+            if whitespace.is_empty() || kind == LineKind::InString {
+                return line.to_string();
+            }
+            let cols = columns(whitespace, from.tab_size);
+            let target = cols / from.tab_size * to.tab_size + cols % from.tab_size;
+            let indent = render(target, to);
+            if indent == whitespace {
+                return line.to_string();
+            }
+            changed_lines += 1;
+            format!("{}{}", indent, &line[whitespace.len()..])
+        })
+        .collect();
+
+    Ok(ConvertedIndentation {
+        text: converted.join("\n"),
+        from,
+        changed_lines,
+    })
+}
+
+/// Re-indent a block being pasted so it fits the structure at the cursor
+#[tauri::command]
+pub fn indentation_reindent_paste(request: PasteRequest) -> Result<ReindentedPaste, String> {
+    let document = match (&request.text, &request.path) {
+        (Some(text), _) => text.clone(),
+        (None, Some(path)) => crate::document_store::open_text(Path::new(path))
+            .map(Ok)
+            .unwrap_or_else(|| std::fs::read_to_string(path))
+            .map_err(|e| format!("Failed to read {}: {}", path, e))?,
+        (None, None) => return Err("Either text or path is required".to_string()),
+    };
+    let grammar = Grammar::resolve(request.language_id.as_deref(), request.path.as_deref());
+    let language_id = request
+        .language_id
+        .as_deref()
+        .or_else(|| grammar.map(Grammar::id));
+    let unchanged = ReindentedPaste {
+        text: request.pasted.clone(),
+        line: request.line,
+        start_column: request.column,
+        end_column: request.column,
+        applied: false,
+    };
+    if !reindent_enabled(request.workspace_root.as_deref(), language_id) {
+        return Ok(unchanged);
+    }
+
+    let style = request
+        .style
+        .or_else(|| detect(&document))
+        .unwrap_or_default()
+        .normalized();
+    let offset = offset_of(&document, request.line, request.column);
+    let (text, replaces_prefix) = reindent(grammar, &document, offset, &request.pasted, style);
+    if text == request.pasted && !replaces_prefix {
+        return Ok(unchanged);
+    }
+    Ok(ReindentedPaste {
+        text,
+        line: request.line,
+        start_column: if replaces_prefix { 1 } else { request.column },
+        end_column: request.column,
+        applied: true,
+    })
+}
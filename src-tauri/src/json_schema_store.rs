@@ -0,0 +1,1553 @@
+//! JSON Schema Store
+//!
+//! Maps JSON files to schemas and validates documents against them for editor
+//! diagnostics. Associations come from, in order of precedence:
+//! 1. the `json.schemas` setting (`[{ fileMatch, url | schema }]`)
+//! 2. extensions (`register_json_schemas`)
+//! 3. schemas bundled with the app (`.rainy/*.json`, package.json, tsconfig.json)
+//! 4. the SchemaStore catalog, unless `json.schemaDownload.enable` is false
+//!
+//! Relative schema paths resolve against the contributing extension's folder,
+//! or the workspace root for settings. Remote schemas are cached in memory and
+//! under `~/.rainy-aether/cache/json-schemas/`; local ones in memory until the
+//! file changes. Only `$ref`s within the schema itself are followed, others are
+//! reported as warnings. Documents may contain comments and
+//! trailing commas (JSONC); diagnostics carry 1-based line/column ranges in
+//! UTF-16 units, as Monaco markers expect.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::State;
+
+/// SchemaStore catalog listing schemas and the files they apply to
+const SCHEMASTORE_CATALOG_URL: &str = "https://www.schemastore.org/api/json/catalog.json";
+
+/// Downloaded schemas are refreshed after this long
+const SCHEMA_CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// After a failed catalog download, validation goes without the catalog
+/// for this long before trying again
+const CATALOG_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Guards against `$ref` cycles in schemas
+const MAX_VALIDATION_DEPTH: usize = 64;
+
+/// Deepest array/object nesting a document may have; deeper documents are
+/// reported as a parse error instead of exhausting the stack
+const MAX_NESTING_DEPTH: usize = 128;
+
+/// A file pattern to schema mapping
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaAssociation {
+    /// Glob patterns; patterns without `/` match the file name, others the
+    /// workspace-relative path
+    pub file_match: Vec<String>,
+    pub url: Option<String>,
+    /// Inline schema, used instead of `url`
+    pub schema: Option<Value>,
+}
+
+/// The schema chosen for a file
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaInfo {
+    pub url: Option<String>,
+    /// "settings", "extension:<id>", "bundled" or "schemastore"
+    pub source: String,
+}
+
+/// A problem found in a JSON document
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonDiagnostic {
+    pub message: String,
+    /// "error" or "warning"
+    pub severity: String,
+    /// JSON pointer of the offending value
+    pub pointer: String,
+    pub start_line: u32,
+    pub start_column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonValidationResult {
+    pub schema: Option<SchemaInfo>,
+    pub diagnostics: Vec<JsonDiagnostic>,
+}
+
+/// A loaded schema and, for local files, the modification time it was read at
+struct CachedSchema {
+    schema: Arc<Value>,
+    modified: Option<SystemTime>,
+}
+
+#[derive(Default)]
+pub struct JsonSchemaStoreState {
+    /// Extension id -> contributed associations, with local paths made absolute
+    contributed: Mutex<HashMap<String, Vec<SchemaAssociation>>>,
+    /// Loaded schemas by URL, or by resolved path for local files
+    schemas: Mutex<HashMap<String, CachedSchema>>,
+    /// SchemaStore catalog associations, loaded on first use
+    catalog: Mutex<Option<Arc<Vec<SchemaAssociation>>>>,
+    /// When the last catalog download failed
+    catalog_failed_at: Mutex<Option<Instant>>,
+}
+
+// ============================================================================
+// Bundled schemas
+// ============================================================================
+
+fn tasks_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "version": { "type": "string", "enum": ["2.0.0"] },
+            "tasks": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["label"],
+                    "properties": {
+                        "label": { "type": "string", "minLength": 1 },
+                        "type": { "type": "string", "enum": ["shell", "process"] },
+                        "command": { "type": "string" },
+                        "args": { "type": "array", "items": { "type": "string" } },
+                        "cwd": { "type": "string" },
+                        "env": { "type": "object", "additionalProperties": { "type": "string" } },
+                        "group": {
+                            "anyOf": [
+                                { "type": "string", "enum": ["build", "test", "none"] },
+                                {
+                                    "type": "object",
+                                    "required": ["kind"],
+                                    "properties": {
+                                        "kind": { "type": "string", "enum": ["build", "test", "none"] },
+                                        "isDefault": { "type": "boolean" }
+                                    }
+                                }
+                            ]
+                        },
+                        "dependsOn": {
+                            "anyOf": [
+                                { "type": "string" },
+                                { "type": "array", "items": { "type": "string" } }
+                            ]
+                        },
+                        "problemMatcher": {
+                            "anyOf": [
+                                { "type": "string" },
+                                { "type": "array" },
+                                { "type": "object" }
+                            ]
+                        },
+                        "isBackground": { "type": "boolean" }
+                    }
+                }
+            }
+        }
+    })
+}
+
+fn settings_schema() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": true
+    })
+}
+
+fn agents_schema() -> Value {
+    let strings = json!({ "type": "array", "items": { "type": "string" } });
+    json!({
+        "type": "object",
+        "properties": {
+            "defaultProvider": { "type": "string" },
+            "defaultModel": { "type": "string" },
+            "systemPrompt": { "type": "string" },
+            "enabledTools": strings,
+            "disabledTools": strings,
+            "sandbox": {
+                "type": "object",
+                "properties": {
+                    "readOnly": { "type": "boolean" },
+                    "denyPaths": strings,
+                    "allowedCommands": strings,
+                    "deniedCommands": strings
+                }
+            }
+        }
+    })
+}
+
+fn bundled_associations() -> Vec<SchemaAssociation> {
+    let inline = |pattern: &str, schema: Value| SchemaAssociation {
+        file_match: vec![pattern.to_string()],
+        url: None,
+        schema: Some(schema),
+    };
+    let remote = |pattern: &str, url: &str| SchemaAssociation {
+        file_match: vec![pattern.to_string()],
+        url: Some(url.to_string()),
+        schema: None,
+    };
+
+    vec![
+        inline(".rainy/tasks.json", tasks_schema()),
+        inline(".rainy/settings.json", settings_schema()),
+        inline(".rainy/agents.json", agents_schema()),
+        remote("package.json", "https://json.schemastore.org/package.json"),
+        remote(
+            "tsconfig.json",
+            "https://json.schemastore.org/tsconfig.json",
+        ),
+        remote(
+            "tsconfig.*.json",
+            "https://json.schemastore.org/tsconfig.json",
+        ),
+        remote(
+            "jsconfig.json",
+            "https://json.schemastore.org/jsconfig.json",
+        ),
+    ]
+}
+
+// ============================================================================
+// File matching
+// ============================================================================
+
+fn glob_to_regex(pattern: &str) -> Option<regex::Regex> {
+    let mut re = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // "**/" also matches zero directories
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    re.push_str("(?:.*/)?");
+                } else {
+                    re.push_str(".*");
+                }
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+    regex::Regex::new(&re).ok()
+}
+
+fn association_matches(
+    association: &SchemaAssociation,
+    file_path: &Path,
+    workspace_root: Option<&str>,
+) -> bool {
+    let file_name = file_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let relative = workspace_root
+        .and_then(|root| file_path.strip_prefix(root).ok())
+        .unwrap_or(file_path)
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    association.file_match.iter().any(|pattern| {
+        let Some(re) = glob_to_regex(pattern.trim_start_matches('/')) else {
+            return false;
+        };
+        if pattern.contains('/') {
+            re.is_match(&relative)
+        } else {
+            re.is_match(&file_name)
+        }
+    })
+}
+
+// ============================================================================
+// Schema loading
+// ============================================================================
+
+fn schema_cache_dir() -> Option<PathBuf> {
+    Some(
+        dirs::home_dir()?
+            .join(".rainy-aether")
+            .join("cache")
+            .join("json-schemas"),
+    )
+}
+
+fn cache_file_for(url: &str) -> Option<PathBuf> {
+    let hash = format!("{:x}", Sha256::digest(url.as_bytes()));
+    Some(schema_cache_dir()?.join(format!("{}.json", &hash[..32])))
+}
+
+/// Download a schema, using the disk cache when fresh (or when offline)
+async fn fetch_remote_json(url: &str) -> Result<Value, String> {
+    let cache_file = cache_file_for(url);
+    let cached = cache_file.as_ref().and_then(|path| {
+        let age = std::fs::metadata(path)
+            .ok()?
+            .modified()
+            .ok()?
+            .elapsed()
+            .ok()?;
+        let value: Value = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+        Some((value, age < SCHEMA_CACHE_TTL))
+    });
+    if let Some((value, true)) = &cached {
+        return Ok(value.clone());
+    }
+
+    let downloaded = async {
+        let response = reqwest::get(url).await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+        response.json::<Value>().await.map_err(|e| e.to_string())
+    }
+    .await;
+
+    match downloaded {
+        Ok(value) => {
+            if let Some(path) = cache_file {
+                if let Some(parent) = path.parent() {
+                    let _ = std::fs::create_dir_all(parent);
+                }
+                let _ = std::fs::write(&path, value.to_string());
+            }
+            Ok(value)
+        }
+        Err(e) => match cached {
+            Some((value, _)) => {
+                eprintln!("[JsonSchemaStore] Using stale cache for {}: {}", url, e);
+                Ok(value)
+            }
+            None => Err(format!("Failed to download schema {}: {}", url, e)),
+        },
+    }
+}
+
+fn is_remote(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// Path of a local schema URL, relative ones joined to `base`
+fn local_schema_path(url: &str, base: Option<&Path>) -> PathBuf {
+    let path = Path::new(url.strip_prefix("file://").unwrap_or(url));
+    match base {
+        Some(base) if path.is_relative() => base.join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+async fn load_schema(
+    state: &JsonSchemaStoreState,
+    url: &str,
+    workspace_root: Option<&str>,
+) -> Result<Arc<Value>, String> {
+    if is_remote(url) {
+        if let Some(cached) = state.schemas.lock().map_err(|e| e.to_string())?.get(url) {
+            return Ok(Arc::clone(&cached.schema));
+        }
+        let schema = Arc::new(fetch_remote_json(url).await?);
+        state.schemas.lock().map_err(|e| e.to_string())?.insert(
+            url.to_string(),
+            CachedSchema {
+                schema: Arc::clone(&schema),
+                modified: None,
+            },
+        );
+        return Ok(schema);
+    }
+
+    let path = local_schema_path(url, workspace_root.map(Path::new));
+    let path = path.canonicalize().unwrap_or(path);
+    let key = path.to_string_lossy().to_string();
+    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+    if let Some(cached) = state.schemas.lock().map_err(|e| e.to_string())?.get(&key) {
+        if cached.modified.is_some() && cached.modified == modified {
+            return Ok(Arc::clone(&cached.schema));
+        }
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read schema {:?}: {}", path, e))?;
+    let schema: Value =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid schema {:?}: {}", path, e))?;
+    let schema = Arc::new(schema);
+    state.schemas.lock().map_err(|e| e.to_string())?.insert(
+        key,
+        CachedSchema {
+            schema: Arc::clone(&schema),
+            modified,
+        },
+    );
+    Ok(schema)
+}
+
+async fn catalog_associations(state: &JsonSchemaStoreState) -> Arc<Vec<SchemaAssociation>> {
+    if let Some(catalog) = state.catalog.lock().ok().and_then(|c| c.clone()) {
+        return catalog;
+    }
+    let recently_failed = state
+        .catalog_failed_at
+        .lock()
+        .ok()
+        .and_then(|failed| *failed)
+        .is_some_and(|at| at.elapsed() < CATALOG_RETRY_DELAY);
+    if recently_failed {
+        return Arc::new(Vec::new());
+    }
+
+    let associations: Vec<SchemaAssociation> =
+        match fetch_remote_json(SCHEMASTORE_CATALOG_URL).await {
+            Ok(catalog) => catalog
+                .get("schemas")
+                .and_then(Value::as_array)
+                .map(|schemas| {
+                    schemas
+                        .iter()
+                        .filter_map(|entry| {
+                            let file_match: Vec<String> = entry
+                                .get("fileMatch")?
+                                .as_array()?
+                                .iter()
+                                .filter_map(|p| p.as_str().map(String::from))
+                                .collect();
+                            Some(SchemaAssociation {
+                                file_match,
+                                url: Some(entry.get("url")?.as_str()?.to_string()),
+                                schema: None,
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            Err(e) => {
+                // Not cached, so the catalog is picked up once the network is back
+                eprintln!("[JsonSchemaStore] SchemaStore catalog unavailable: {}", e);
+                if let Ok(mut failed) = state.catalog_failed_at.lock() {
+                    *failed = Some(Instant::now());
+                }
+                return Arc::new(Vec::new());
+            }
+        };
+
+    let associations = Arc::new(associations);
+    if let Ok(mut catalog) = state.catalog.lock() {
+        *catalog = Some(Arc::clone(&associations));
+    }
+    associations
+}
+
+fn setting(workspace_root: Option<&str>, key: &str) -> Option<Value> {
+    crate::configuration_manager::resolve_setting(workspace_root, key)
+}
+
+/// Find the association for a file, highest precedence first
+async fn find_association(
+    state: &JsonSchemaStoreState,
+    file_path: &Path,
+    workspace_root: Option<&str>,
+) -> Result<Option<(SchemaAssociation, String)>, String> {
+    let from_settings: Vec<SchemaAssociation> = setting(workspace_root, "json.schemas")
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default();
+    if let Some(found) = from_settings
+        .into_iter()
+        .find(|a| association_matches(a, file_path, workspace_root))
+    {
+        return Ok(Some((found, "settings".to_string())));
+    }
+
+    {
+        let contributed = state.contributed.lock().map_err(|e| e.to_string())?;
+        for (extension_id, associations) in contributed.iter() {
+            if let Some(found) = associations
+                .iter()
+                .find(|a| association_matches(a, file_path, workspace_root))
+            {
+                return Ok(Some((found.clone(), format!("extension:{}", extension_id))));
+            }
+        }
+    }
+
+    if let Some(found) = bundled_associations()
+        .into_iter()
+        .find(|a| association_matches(a, file_path, workspace_root))
+    {
+        return Ok(Some((found, "bundled".to_string())));
+    }
+
+    let download_enabled = setting(workspace_root, "json.schemaDownload.enable")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    if download_enabled {
+        let catalog = catalog_associations(state).await;
+        if let Some(found) = catalog
+            .iter()
+            .find(|a| association_matches(a, file_path, workspace_root))
+        {
+            return Ok(Some((found.clone(), "schemastore".to_string())));
+        }
+    }
+
+    Ok(None)
+}
+
+// ============================================================================
+// JSONC parsing with positions
+// ============================================================================
+
+/// 1-based line/column (UTF-16 units)
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    line: u32,
+    column: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    start: Position,
+    end: Position,
+}
+
+/// Where a value (and its property key, if any) appears in the document
+#[derive(Debug, Clone, Copy)]
+struct NodeLocation {
+    value: Span,
+    key: Option<Span>,
+}
+
+#[derive(Debug)]
+struct ParseError {
+    message: String,
+    at: Position,
+}
+
+struct JsoncParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: u32,
+    column: u32,
+    /// Arrays and objects currently open
+    depth: usize,
+    locations: HashMap<String, NodeLocation>,
+}
+
+fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+impl<'a> JsoncParser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.chars().peekable(),
+            line: 1,
+            column: 1,
+            depth: 0,
+            locations: HashMap::new(),
+        }
+    }
+
+    fn position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, ParseError> {
+        Err(ParseError {
+            message: message.to_string(),
+            at: self.position(),
+        })
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += c.len_utf16() as u32;
+        }
+        Some(c)
+    }
+
+    fn skip_trivia(&mut self) -> Result<(), ParseError> {
+        loop {
+            match self.chars.peek() {
+                Some(c) if c.is_whitespace() || *c == '\u{feff}' => {
+                    self.bump();
+                }
+                Some('/') => {
+                    self.bump();
+                    match self.bump() {
+                        Some('/') => {
+                            while !matches!(self.chars.peek(), None | Some('\n')) {
+                                self.bump();
+                            }
+                        }
+                        Some('*') => loop {
+                            match self.bump() {
+                                Some('*') if self.chars.peek() == Some(&'/') => {
+                                    self.bump();
+                                    break;
+                                }
+                                Some(_) => {}
+                                None => return self.error("Unterminated comment"),
+                            }
+                        },
+                        _ => return self.error("Unexpected character '/'"),
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, ParseError> {
+        self.bump(); // opening quote
+        let mut result = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(result),
+                Some('\\') => match self.bump() {
+                    Some('"') => result.push('"'),
+                    Some('\\') => result.push('\\'),
+                    Some('/') => result.push('/'),
+                    Some('b') => result.push('\u{8}'),
+                    Some('f') => result.push('\u{c}'),
+                    Some('n') => result.push('\n'),
+                    Some('r') => result.push('\r'),
+                    Some('t') => result.push('\t'),
+                    Some('u') => {
+                        let unit = self.parse_hex4()?;
+                        let code = if (0xD800..0xDC00).contains(&unit)
+                            && self.chars.peek() == Some(&'\\')
+                        {
+                            self.bump();
+                            if self.bump() != Some('u') {
+                                return self.error("Invalid unicode escape");
+                            }
+                            let low = self.parse_hex4()?;
+                            0x10000 + ((unit - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF)
+                        } else {
+                            unit
+                        };
+                        result.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                    }
+                    _ => return self.error("Invalid escape sequence"),
+                },
+                Some('\n') | None => return self.error("Unterminated string"),
+                Some(c) => result.push(c),
+            }
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, ParseError> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let Some(digit) = self.bump().and_then(|c| c.to_digit(16)) else {
+                return self.error("Invalid unicode escape");
+            };
+            value = value * 16 + digit;
+        }
+        Ok(value)
+    }
+
+    fn parse_literal(&mut self) -> Result<Value, ParseError> {
+        let mut token = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_alphanumeric() || matches!(c, '-' | '+' | '.') {
+                token.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        match token.as_str() {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            "null" => Ok(Value::Null),
+            _ => serde_json::from_str::<serde_json::Number>(&token)
+                .map(Value::Number)
+                .or_else(|_| self.error(&format!("Unexpected token '{}'", token))),
+        }
+    }
+
+    fn parse_value(&mut self, pointer: &str, key: Option<Span>) -> Result<Value, ParseError> {
+        self.skip_trivia()?;
+        let start = self.position();
+        if matches!(self.chars.peek(), Some('{' | '[')) && self.depth >= MAX_NESTING_DEPTH {
+            return self.error(&format!(
+                "Document is nested more than {} levels deep",
+                MAX_NESTING_DEPTH
+            ));
+        }
+        let value = match self.chars.peek() {
+            Some('{') => {
+                self.depth += 1;
+                let object = self.parse_object(pointer);
+                self.depth -= 1;
+                object?
+            }
+            Some('[') => {
+                self.depth += 1;
+                let array = self.parse_array(pointer);
+                self.depth -= 1;
+                array?
+            }
+            Some('"') => Value::String(self.parse_string()?),
+            Some(_) => self.parse_literal()?,
+            None => return self.error("Unexpected end of document"),
+        };
+        self.locations.insert(
+            pointer.to_string(),
+            NodeLocation {
+                value: Span {
+                    start,
+                    end: self.position(),
+                },
+                key,
+            },
+        );
+        Ok(value)
+    }
+
+    fn parse_object(&mut self, pointer: &str) -> Result<Value, ParseError> {
+        self.bump(); // {
+        let mut object = Map::new();
+        loop {
+            self.skip_trivia()?;
+            match self.chars.peek() {
+                Some('}') => {
+                    self.bump();
+                    return Ok(Value::Object(object));
+                }
+                Some('"') => {}
+                _ => return self.error("Expected property name or '}'"),
+            }
+
+            let key_start = self.position();
+            let key = self.parse_string()?;
+            let key_span = Span {
+                start: key_start,
+                end: self.position(),
+            };
+            self.skip_trivia()?;
+            if self.bump() != Some(':') {
+                return self.error("Expected ':'");
+            }
+            let child = format!("{}/{}", pointer, escape_pointer_token(&key));
+            let value = self.parse_value(&child, Some(key_span))?;
+            object.insert(key, value);
+
+            self.skip_trivia()?;
+            match self.bump() {
+                Some(',') => {}
+                Some('}') => return Ok(Value::Object(object)),
+                _ => return self.error("Expected ',' or '}'"),
+            }
+        }
+    }
+
+    fn parse_array(&mut self, pointer: &str) -> Result<Value, ParseError> {
+        self.bump(); // [
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.chars.peek() == Some(&']') {
+                self.bump();
+                return Ok(Value::Array(items));
+            }
+            let child = format!("{}/{}", pointer, items.len());
+            items.push(self.parse_value(&child, None)?);
+
+            self.skip_trivia()?;
+            match self.bump() {
+                Some(',') => {}
+                Some(']') => return Ok(Value::Array(items)),
+                _ => return self.error("Expected ',' or ']'"),
+            }
+        }
+    }
+
+    fn parse_document(mut self) -> Result<(Value, HashMap<String, NodeLocation>), ParseError> {
+        let value = self.parse_value("", None)?;
+        self.skip_trivia()?;
+        if self.chars.peek().is_some() {
+            return self.error("Unexpected content after the end of the document");
+        }
+        Ok((value, self.locations))
+    }
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+struct SchemaError {
+    pointer: String,
+    message: String,
+    warning: bool,
+    /// Point at the property name instead of its value
+    on_key: bool,
+}
+
+struct SchemaValidator<'a> {
+    root: &'a Value,
+    errors: Vec<SchemaError>,
+}
+
+/// Whether a branch validated; warnings alone do not fail it
+fn passes(errors: &[SchemaError]) -> bool {
+    errors.iter().all(|error| error.warning)
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn matches_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "integer" => value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+impl<'a> SchemaValidator<'a> {
+    fn new(root: &'a Value) -> Self {
+        Self {
+            root,
+            errors: Vec::new(),
+        }
+    }
+
+    fn error(&mut self, pointer: &str, message: String) {
+        self.errors.push(SchemaError {
+            pointer: pointer.to_string(),
+            message,
+            warning: false,
+            on_key: false,
+        });
+    }
+
+    /// Validate against a subschema without recording errors; returns them instead
+    fn try_branch(
+        &self,
+        schema: &Value,
+        value: &Value,
+        pointer: &str,
+        depth: usize,
+    ) -> Vec<SchemaError> {
+        let mut branch = SchemaValidator::new(self.root);
+        branch.validate(schema, value, pointer, depth + 1);
+        branch.errors
+    }
+
+    fn validate(&mut self, schema: &Value, value: &Value, pointer: &str, depth: usize) {
+        if depth > MAX_VALIDATION_DEPTH {
+            return;
+        }
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                self.error(pointer, "Value is not allowed".to_string());
+                return;
+            }
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            // Only local references can be resolved
+            match reference
+                .strip_prefix('#')
+                .and_then(|fragment| self.root.pointer(fragment))
+            {
+                Some(target) => self.validate(target, value, pointer, depth + 1),
+                None => self.errors.push(SchemaError {
+                    pointer: pointer.to_string(),
+                    message: if reference.starts_with('#') {
+                        format!("Schema reference {} does not exist", reference)
+                    } else {
+                        format!(
+                            "Schema reference {} is not loaded; this value was not fully checked",
+                            reference
+                        )
+                    },
+                    warning: true,
+                    on_key: false,
+                }),
+            }
+        }
+
+        if let Some(message) = schema.get("deprecationMessage").and_then(Value::as_str) {
+            self.errors.push(SchemaError {
+                pointer: pointer.to_string(),
+                message: message.to_string(),
+                warning: true,
+                on_key: true,
+            });
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let types: Vec<&str> = match expected {
+                Value::String(t) => vec![t.as_str()],
+                Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !types.is_empty() && !types.iter().any(|t| matches_type(value, t)) {
+                self.error(
+                    pointer,
+                    format!(
+                        "Incorrect type. Expected \"{}\", got \"{}\"",
+                        types.join(" | "),
+                        type_name(value)
+                    ),
+                );
+                return;
+            }
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+            if !allowed.contains(value) {
+                let options: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+                self.error(
+                    pointer,
+                    format!(
+                        "Value is not accepted. Valid values: {}",
+                        options.join(", ")
+                    ),
+                );
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if expected != value {
+                self.error(pointer, format!("Value must be {}", expected));
+            }
+        }
+
+        self.validate_combinators(schema, value, pointer, depth);
+
+        match value {
+            Value::String(s) => self.validate_string(schema, s, pointer),
+            Value::Number(_) => {
+                self.validate_number(schema, value.as_f64().unwrap_or_default(), pointer)
+            }
+            Value::Array(items) => self.validate_array(schema, items, pointer, depth),
+            Value::Object(object) => self.validate_object(schema, object, pointer, depth),
+            _ => {}
+        }
+    }
+
+    fn validate_combinators(
+        &mut self,
+        schema: &Map<String, Value>,
+        value: &Value,
+        pointer: &str,
+        depth: usize,
+    ) {
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            for sub in all {
+                self.validate(sub, value, pointer, depth + 1);
+            }
+        }
+
+        for keyword in ["anyOf", "oneOf"] {
+            let Some(branches) = schema.get(keyword).and_then(Value::as_array) else {
+                continue;
+            };
+            let results: Vec<Vec<SchemaError>> = branches
+                .iter()
+                .map(|branch| self.try_branch(branch, value, pointer, depth))
+                .collect();
+            let matching = results.iter().filter(|errors| passes(errors)).count();
+
+            if matching == 0 {
+                // Report the errors of the closest branch
+                if let Some(best) = results.into_iter().min_by_key(|errors| errors.len()) {
+                    self.errors.extend(best);
+                }
+            } else if keyword == "oneOf" && matching > 1 {
+                self.error(
+                    pointer,
+                    "Matches multiple schemas when only one must validate".to_string(),
+                );
+            }
+        }
+
+        if let Some(not) = schema.get("not") {
+            if passes(&self.try_branch(not, value, pointer, depth)) {
+                self.error(
+                    pointer,
+                    "Value matches a schema that is not allowed".to_string(),
+                );
+            }
+        }
+
+        if let Some(condition) = schema.get("if") {
+            let branch = if passes(&self.try_branch(condition, value, pointer, depth)) {
+                schema.get("then")
+            } else {
+                schema.get("else")
+            };
+            if let Some(branch) = branch {
+                self.validate(branch, value, pointer, depth + 1);
+            }
+        }
+    }
+
+    fn validate_string(&mut self, schema: &Map<String, Value>, s: &str, pointer: &str) {
+        let length = s.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+            if length < min {
+                self.error(
+                    pointer,
+                    format!("String is shorter than the minimum length of {}", min),
+                );
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+            if length > max {
+                self.error(
+                    pointer,
+                    format!("String is longer than the maximum length of {}", max),
+                );
+            }
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+            if let Ok(re) = regex::Regex::new(pattern) {
+                if !re.is_match(s) {
+                    let message = schema
+                        .get("patternErrorMessage")
+                        .and_then(Value::as_str)
+                        .map(String::from)
+                        .unwrap_or_else(|| {
+                            format!("String does not match the pattern of \"{}\"", pattern)
+                        });
+                    self.error(pointer, message);
+                }
+            }
+        }
+    }
+
+    fn validate_number(&mut self, schema: &Map<String, Value>, n: f64, pointer: &str) {
+        let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+        if let Some(min) = bound("minimum") {
+            if n < min {
+                self.error(pointer, format!("Value is below the minimum of {}", min));
+            }
+        }
+        if let Some(max) = bound("maximum") {
+            if n > max {
+                self.error(pointer, format!("Value is above the maximum of {}", max));
+            }
+        }
+        if let Some(min) = bound("exclusiveMinimum") {
+            if n <= min {
+                self.error(pointer, format!("Value must be greater than {}", min));
+            }
+        }
+        if let Some(max) = bound("exclusiveMaximum") {
+            if n >= max {
+                self.error(pointer, format!("Value must be less than {}", max));
+            }
+        }
+    }
+
+    fn validate_array(
+        &mut self,
+        schema: &Map<String, Value>,
+        items: &[Value],
+        pointer: &str,
+        depth: usize,
+    ) {
+        match schema.get("items") {
+            Some(Value::Array(tuple)) => {
+                for (i, (item, item_schema)) in items.iter().zip(tuple).enumerate() {
+                    self.validate(item_schema, item, &format!("{}/{}", pointer, i), depth + 1);
+                }
+            }
+            Some(item_schema) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.validate(item_schema, item, &format!("{}/{}", pointer, i), depth + 1);
+                }
+            }
+            None => {}
+        }
+
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+            if (items.len() as u64) < min {
+                self.error(
+                    pointer,
+                    format!("Array has too few items. Expected {} or more", min),
+                );
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+            if items.len() as u64 > max {
+                self.error(
+                    pointer,
+                    format!("Array has too many items. Expected {} or fewer", max),
+                );
+            }
+        }
+        if schema.get("uniqueItems").and_then(Value::as_bool) == Some(true) {
+            for (i, item) in items.iter().enumerate() {
+                if items[..i].contains(item) {
+                    self.error(
+                        &format!("{}/{}", pointer, i),
+                        "Array has duplicate items".to_string(),
+                    );
+                }
+            }
+        }
+    }
+
+    fn validate_object(
+        &mut self,
+        schema: &Map<String, Value>,
+        object: &Map<String, Value>,
+        pointer: &str,
+        depth: usize,
+    ) {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    self.error(pointer, format!("Missing property \"{}\"", name));
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let pattern_properties: Vec<(regex::Regex, &Value)> = schema
+            .get("patternProperties")
+            .and_then(Value::as_object)
+            .map(|patterns| {
+                patterns
+                    .iter()
+                    .filter_map(|(p, s)| Some((regex::Regex::new(p).ok()?, s)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for (key, child) in object {
+            let child_pointer = format!("{}/{}", pointer, escape_pointer_token(key));
+            let mut matched = false;
+
+            if let Some(property_schema) = properties.and_then(|p| p.get(key)) {
+                matched = true;
+                self.validate(property_schema, child, &child_pointer, depth + 1);
+            }
+            for (re, pattern_schema) in &pattern_properties {
+                if re.is_match(key) {
+                    matched = true;
+                    self.validate(pattern_schema, child, &child_pointer, depth + 1);
+                }
+            }
+
+            if !matched {
+                match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => self.errors.push(SchemaError {
+                        pointer: child_pointer,
+                        message: format!("Property {} is not allowed", key),
+                        warning: false,
+                        on_key: true,
+                    }),
+                    Some(additional @ Value::Object(_)) => {
+                        self.validate(additional, child, &child_pointer, depth + 1)
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if let Some(min) = schema.get("minProperties").and_then(Value::as_u64) {
+            if (object.len() as u64) < min {
+                self.error(pointer, format!("Object has fewer than {} properties", min));
+            }
+        }
+        if let Some(max) = schema.get("maxProperties").and_then(Value::as_u64) {
+            if object.len() as u64 > max {
+                self.error(pointer, format!("Object has more than {} properties", max));
+            }
+        }
+    }
+}
+
+fn locate(locations: &HashMap<String, NodeLocation>, pointer: &str, on_key: bool) -> Span {
+    let fallback = Span {
+        start: Position { line: 1, column: 1 },
+        end: Position { line: 1, column: 1 },
+    };
+    let Some(location) = locations.get(pointer) else {
+        return fallback;
+    };
+    match (on_key, location.key) {
+        (true, Some(key)) => key,
+        // Point at the opening line of large containers rather than the whole block
+        _ if location.value.start.line != location.value.end.line => Span {
+            start: location.value.start,
+            end: Position {
+                line: location.value.start.line,
+                column: location.value.start.column + 1,
+            },
+        },
+        _ => location.value,
+    }
+}
+
+fn diagnostic(message: String, severity: &str, pointer: String, span: Span) -> JsonDiagnostic {
+    JsonDiagnostic {
+        message,
+        severity: severity.to_string(),
+        pointer,
+        start_line: span.start.line,
+        start_column: span.start.column,
+        end_line: span.end.line,
+        end_column: span.end.column,
+    }
+}
+
+/// Parse a JSONC document and validate it against `schema`
+fn validate_document(content: &str, schema: Option<&Value>) -> Vec<JsonDiagnostic> {
+    let (value, locations) = match JsoncParser::new(content).parse_document() {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            let span = Span {
+                start: e.at,
+                end: Position {
+                    line: e.at.line,
+                    column: e.at.column + 1,
+                },
+            };
+            return vec![diagnostic(e.message, "error", String::new(), span)];
+        }
+    };
+    let Some(schema) = schema else {
+        return Vec::new();
+    };
+
+    let mut validator = SchemaValidator::new(schema);
+    validator.validate(schema, &value, "", 0);
+    validator
+        .errors
+        .into_iter()
+        .map(|error| {
+            let span = locate(&locations, &error.pointer, error.on_key);
+            let severity = if error.warning { "warning" } else { "error" };
+            diagnostic(error.message, severity, error.pointer, span)
+        })
+        .collect()
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Register schema associations contributed by an extension (replaces earlier ones)
+/// Relative schema paths resolve against `extension_path`, the extension's folder
+#[tauri::command]
+pub fn register_json_schemas(
+    extension_id: String,
+    extension_path: Option<String>,
+    mut associations: Vec<SchemaAssociation>,
+    state: State<'_, JsonSchemaStoreState>,
+) -> Result<(), String> {
+    for association in &mut associations {
+        if association.url.is_none() && association.schema.is_none() {
+            return Err(format!(
+                "Schema association for {:?} needs a url or an inline schema",
+                association.file_match
+            ));
+        }
+        let Some(url) = association.url.as_mut().filter(|url| !is_remote(url)) else {
+            continue;
+        };
+        let base = extension_path.as_deref().map(Path::new);
+        let path = local_schema_path(url, base);
+        if path.is_relative() {
+            return Err(format!(
+                "Schema {} of {} is relative, but no extension path was given",
+                url, extension_id
+            ));
+        }
+        *url = path.to_string_lossy().to_string();
+    }
+    state
+        .contributed
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(extension_id, associations);
+    Ok(())
+}
+
+/// Remove the schema associations of an extension
+#[tauri::command]
+pub fn unregister_json_schemas(
+    extension_id: String,
+    state: State<'_, JsonSchemaStoreState>,
+) -> Result<(), String> {
+    state
+        .contributed
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&extension_id);
+    Ok(())
+}
+
+/// Which schema applies to a file
+#[tauri::command]
+pub async fn get_json_schema_for_file(
+    file_path: String,
+    workspace_root: Option<String>,
+    state: State<'_, JsonSchemaStoreState>,
+) -> Result<Option<SchemaInfo>, String> {
+    let found = find_association(&state, Path::new(&file_path), workspace_root.as_deref()).await?;
+    Ok(found.map(|(association, source)| SchemaInfo {
+        url: association.url,
+        source,
+    }))
+}
+
+/// Validate a JSON document against the schema associated with its path
+/// Syntax errors are reported even when no schema applies
+#[tauri::command]
+pub async fn validate_json_document(
+    file_path: String,
+    content: String,
+    workspace_root: Option<String>,
+    state: State<'_, JsonSchemaStoreState>,
+) -> Result<JsonValidationResult, String> {
+    let workspace_root = workspace_root.as_deref();
+    let found = find_association(&state, Path::new(&file_path), workspace_root).await?;
+
+    let (schema, info) = match found {
+        Some((association, source)) => {
+            let schema = match (&association.schema, &association.url) {
+                (Some(inline), _) => Some(Arc::new(inline.clone())),
+                (None, Some(url)) => match load_schema(&state, url, workspace_root).await {
+                    Ok(schema) => Some(schema),
+                    Err(e) => {
+                        eprintln!("[JsonSchemaStore] {}", e);
+                        None
+                    }
+                },
+                (None, None) => None,
+            };
+            let info = SchemaInfo {
+                url: association.url,
+                source,
+            };
+            (schema, Some(info))
+        }
+        None => (None, None),
+    };
+
+    Ok(JsonValidationResult {
+        schema: info,
+        diagnostics: validate_document(&content, schema.as_deref()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<(Value, HashMap<String, NodeLocation>), ParseError> {
+        JsoncParser::new(text).parse_document()
+    }
+
+    fn messages(content: &str, schema: &Value) -> Vec<String> {
+        validate_document(content, Some(schema))
+            .into_iter()
+            .map(|d| d.message)
+            .collect()
+    }
+
+    #[test]
+    fn parses_comments_and_trailing_commas() {
+        let (value, _) =
+            parse("// leading\n{\n  \"a\": 1, /* inline */\n  \"b\": [true, null,],\n}\n")
+                .expect("document parses");
+        assert_eq!(value, json!({ "a": 1, "b": [true, null] }));
+    }
+
+    #[test]
+    fn records_value_and_key_positions() {
+        let (_, locations) =
+            parse("{\n  \"name\": \"x\",\n  \"list\": [1, 2]\n}").expect("document parses");
+
+        let name = locations["/name"];
+        assert_eq!((name.value.start.line, name.value.start.column), (2, 11));
+        let key = name.key.expect("property has a key span");
+        assert_eq!((key.start.line, key.start.column), (2, 3));
+
+        let item = locations["/list/1"];
+        assert_eq!((item.value.start.line, item.value.start.column), (3, 15));
+        assert!(item.key.is_none());
+    }
+
+    #[test]
+    fn counts_columns_in_utf16_units() {
+        let (_, locations) = parse("{\"😀\": 1}").expect("document parses");
+        // The emoji is two UTF-16 units
+        assert_eq!(locations["/😀"].value.start.column, 8);
+    }
+
+    #[test]
+    fn escapes_pointer_tokens() {
+        let (_, locations) = parse("{\"a/b\": {\"c~d\": 1}}").expect("document parses");
+        assert!(locations.contains_key("/a~1b/c~0d"));
+    }
+
+    #[test]
+    fn decodes_string_escapes() {
+        let (value, _) = parse(r#""tab\there é 😀""#).expect("document parses");
+        assert_eq!(value, json!("tab\there é 😀"));
+    }
+
+    #[test]
+    fn reports_syntax_errors_with_position() {
+        let error = parse("{\n  \"a\" 1\n}").expect_err("missing colon");
+        assert_eq!(error.message, "Expected ':'");
+        assert_eq!(error.at.line, 2);
+
+        assert!(parse("[1, 2").is_err());
+        assert!(parse("\"open").is_err());
+        assert!(parse("/* open").is_err());
+        assert!(parse("1 2").is_err());
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let deep = "[".repeat(MAX_NESTING_DEPTH + 1) + &"]".repeat(MAX_NESTING_DEPTH + 1);
+        let error = parse(&deep).expect_err("too deep");
+        assert!(error.message.contains("nested"));
+    }
+
+    #[test]
+    fn validates_types_required_and_additional_properties() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": { "type": "string" },
+                "port": { "type": "integer", "minimum": 1 }
+            },
+            "additionalProperties": false
+        });
+        let found = messages(r#"{ "port": 0, "extra": true }"#, &schema);
+        assert!(found.contains(&"Missing property \"name\"".to_string()));
+        assert!(found.contains(&"Value is below the minimum of 1".to_string()));
+        assert!(found.contains(&"Property extra is not allowed".to_string()));
+
+        let found = messages(r#"{ "name": "x", "port": "80" }"#, &schema);
+        assert_eq!(
+            found,
+            vec!["Incorrect type. Expected \"integer\", got \"string\"".to_string()]
+        );
+    }
+
+    #[test]
+    fn places_diagnostics_on_the_offending_node() {
+        let schema = json!({ "additionalProperties": false });
+        let diagnostics = validate_document("{\n  \"extra\": 1\n}", Some(&schema));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].pointer, "/extra");
+        assert_eq!(
+            (diagnostics[0].start_line, diagnostics[0].start_column),
+            (2, 3)
+        );
+    }
+
+    #[test]
+    fn validates_combinators() {
+        let schema = json!({
+            "oneOf": [{ "type": "string" }, { "type": "number" }]
+        });
+        assert!(messages("\"x\"", &schema).is_empty());
+        assert!(!messages("true", &schema).is_empty());
+
+        let schema = json!({ "oneOf": [{ "type": "number" }, { "minimum": 0 }] });
+        assert_eq!(
+            messages("1", &schema),
+            vec!["Matches multiple schemas when only one must validate".to_string()]
+        );
+
+        let schema = json!({ "not": { "type": "null" } });
+        assert!(!messages("null", &schema).is_empty());
+    }
+
+    #[test]
+    fn follows_local_references() {
+        let schema = json!({
+            "definitions": { "name": { "type": "string" } },
+            "properties": { "name": { "$ref": "#/definitions/name" } }
+        });
+        assert_eq!(
+            messages(r#"{ "name": 1 }"#, &schema),
+            vec!["Incorrect type. Expected \"string\", got \"number\"".to_string()]
+        );
+    }
+
+    #[test]
+    fn warns_about_references_it_cannot_follow() {
+        let schema = json!({
+            "properties": {
+                "remote": { "$ref": "https://example.com/schema.json" },
+                "missing": { "$ref": "#/definitions/nope" }
+            }
+        });
+        let diagnostics = validate_document(r#"{ "remote": 1, "missing": 2 }"#, Some(&schema));
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|d| d.severity == "warning"));
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.pointer == "/remote" && d.message.contains("not loaded")));
+    }
+
+    #[test]
+    fn warnings_do_not_fail_a_branch() {
+        let schema = json!({
+            "anyOf": [{ "$ref": "https://example.com/schema.json" }, { "type": "string" }]
+        });
+        let diagnostics = validate_document("1", Some(&schema));
+        assert!(diagnostics.iter().all(|d| d.severity == "warning"));
+    }
+
+    #[test]
+    fn resolves_relative_schema_paths_against_a_base() {
+        let base = Path::new("/ext/my-extension");
+        assert_eq!(
+            local_schema_path("schemas/config.json", Some(base)),
+            base.join("schemas/config.json")
+        );
+        assert_eq!(
+            local_schema_path("file:///abs/schema.json", Some(base)),
+            PathBuf::from("/abs/schema.json")
+        );
+        assert!(local_schema_path("schema.json", None).is_relative());
+    }
+}
@@ -12,24 +12,37 @@
  * - etc.
  */
 
+use crate::process_supervisor::{ProcessSpec, StdioMode, SupervisedProcess};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Write};
-use std::process::{Child, Command, Stdio};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
+use tokio::sync::oneshot;
 
 /// Atomic session ID counter for thread-safe ID generation
 static SESSION_COUNTER: AtomicU32 = AtomicU32::new(1);
 
+/// ID prefix of requests sent by the backend itself rather than the editor
+const BACKEND_REQUEST_PREFIX: &str = "rainy-backend-";
+
+static BACKEND_REQUEST_COUNTER: AtomicU32 = AtomicU32::new(1);
+
+/// Backend requests awaiting a response, by request ID; their responses are
+/// not forwarded to the frontend
+static BACKEND_REQUESTS: Lazy<Mutex<HashMap<String, oneshot::Sender<serde_json::Value>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
 /// Language server process information
 #[derive(Debug)]
 struct LanguageServerProcess {
-    /// Process handle
-    child: Child,
+    /// Supervised process handle
+    process: Arc<SupervisedProcess>,
     /// Server ID (stored for debugging/logging purposes)
     #[allow(dead_code)]
     server_id: String,
@@ -190,27 +203,20 @@ impl LanguageServerManager {
         // Resolve command for cross-platform compatibility (Windows .cmd extension)
         let resolved_command = resolve_command_path(&params.command);
 
-        // Build command with proper error handling
-        let mut cmd = Command::new(&resolved_command);
-        cmd.args(&params.args);
-
-        // Set working directory
-        if let Some(cwd) = &params.cwd {
-            cmd.current_dir(cwd);
-        }
-
-        // Set environment variables
-        for (key, value) in &params.env {
-            cmd.env(key, value);
-        }
-
-        // Configure stdio with proper buffering
-        cmd.stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+        // Build process spec; stdio is left to the LSP framing readers below
+        let spec = ProcessSpec {
+            args: params.args.clone(),
+            env: params.env.clone(),
+            cwd: params.cwd.as_ref().map(PathBuf::from),
+            stdin_piped: true,
+            stdout: StdioMode::Piped,
+            stderr: StdioMode::Piped,
+            shutdown_timeout: Duration::from_secs(5),
+            ..ProcessSpec::new(&format!("lsp:{}", server_id), &resolved_command)
+        };
 
         // Spawn the process with improved error handling
-        let mut child = match cmd.spawn() {
+        let process = match SupervisedProcess::spawn(spec, None) {
             Ok(c) => c,
             Err(e) => {
                 // Check if this is a "not found" error
@@ -229,9 +235,9 @@ impl LanguageServerManager {
         };
 
         // Get handles with validation
-        let stdin = child.stdin.take();
-        let stdout = child.stdout.take().ok_or(LSPError::StdioCaptureFailed)?;
-        let stderr = child.stderr.take().ok_or(LSPError::StdioCaptureFailed)?;
+        let stdin = process.take_stdin();
+        let stdout = process.take_stdout().ok_or(LSPError::StdioCaptureFailed)?;
+        let stderr = process.take_stderr().ok_or(LSPError::StdioCaptureFailed)?;
 
         // Store process info
         {
@@ -242,7 +248,7 @@ impl LanguageServerManager {
             servers.insert(
                 server_id.clone(),
                 LanguageServerProcess {
-                    child,
+                    process,
                     server_id: server_id.clone(),
                     session_id,
                     stdin,
@@ -359,6 +365,14 @@ impl LanguageServerManager {
                 // Convert to string and emit
                 match String::from_utf8(content_buf) {
                     Ok(message) => {
+                        if message.contains(BACKEND_REQUEST_PREFIX)
+                            && Self::route_backend_response(&message)
+                        {
+                            if let Ok(mut s) = stats.lock() {
+                                s.total_messages_received += 1;
+                            }
+                            continue;
+                        }
                         let event_name = format!("lsp-message-{}", session_id);
                         if let Err(e) = app_handle.emit(
                             &event_name,
@@ -396,6 +410,28 @@ impl LanguageServerManager {
         let _ = app_handle.emit(&event_name, ());
     }
 
+    /// Hand a response to the backend request waiting for it
+    fn route_backend_response(message: &str) -> bool {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(message) else {
+            return false;
+        };
+        // Requests from the server carry a method; only responses are routed
+        if value.get("method").is_some() {
+            return false;
+        }
+        let Some(id) = value.get("id").and_then(|id| id.as_str()) else {
+            return false;
+        };
+        let sender = BACKEND_REQUESTS.lock().ok().and_then(|mut p| p.remove(id));
+        match sender {
+            Some(sender) => {
+                let _ = sender.send(value);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Stderr reader for logging
     fn read_stderr(
         session_id: u32,
@@ -436,29 +472,16 @@ impl LanguageServerManager {
     pub fn stop_server(&self, server_id: &str) -> Result<(), LSPError> {
         println!("[LSP] Stopping language server: {}", server_id);
 
-        let mut servers = self
+        // Take the server out first so shutdown doesn't block other servers
+        let removed = self
             .servers
             .lock()
-            .map_err(|_| LSPError::LockAcquisitionFailed)?;
-
-        if let Some(mut server_process) = servers.remove(server_id) {
-            // Try graceful shutdown first
-            let _ = server_process.child.kill();
-
-            // Wait with timeout
-            let timeout = Duration::from_secs(5);
-            let start = Instant::now();
-
-            while start.elapsed() < timeout {
-                if let Ok(Some(_)) = server_process.child.try_wait() {
-                    break;
-                }
-                thread::sleep(Duration::from_millis(100));
-            }
+            .map_err(|_| LSPError::LockAcquisitionFailed)?
+            .remove(server_id);
 
-            // Force kill if still running
-            let _ = server_process.child.kill();
-            let _ = server_process.child.wait();
+        if let Some(server_process) = removed {
+            // Graceful shutdown with timeout, force kill if still running
+            server_process.process.shutdown();
 
             // Update stats
             if let Ok(mut stats) = self.stats.lock() {
@@ -523,6 +546,83 @@ impl LanguageServerManager {
         }
     }
 
+    /// Send a request on the backend's behalf and wait for the result
+    ///
+    /// The server must already be initialized by the editor's LSP client.
+    pub async fn request(
+        &self,
+        server_id: &str,
+        method: &str,
+        params: serde_json::Value,
+        timeout: Duration,
+    ) -> Result<serde_json::Value, String> {
+        let id = format!(
+            "{}{}",
+            BACKEND_REQUEST_PREFIX,
+            BACKEND_REQUEST_COUNTER.fetch_add(1, Ordering::SeqCst)
+        );
+        let (tx, rx) = oneshot::channel();
+        BACKEND_REQUESTS
+            .lock()
+            .map_err(|_| LSPError::LockAcquisitionFailed)?
+            .insert(id.clone(), tx);
+
+        let message = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+        let response = match self.send_message(server_id, &message.to_string()) {
+            Ok(()) => tokio::time::timeout(timeout, rx).await,
+            Err(e) => {
+                if let Ok(mut pending) = BACKEND_REQUESTS.lock() {
+                    pending.remove(&id);
+                }
+                return Err(e.to_string());
+            }
+        };
+        let response = match response {
+            Ok(Ok(response)) => response,
+            _ => {
+                if let Ok(mut pending) = BACKEND_REQUESTS.lock() {
+                    pending.remove(&id);
+                }
+                return Err(format!("{} did not answer {}", server_id, method));
+            }
+        };
+        if let Some(error) = response.get("error") {
+            return Err(format!(
+                "{} failed on {}: {}",
+                method,
+                server_id,
+                error
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("unknown error")
+            ));
+        }
+        Ok(response
+            .get("result")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null))
+    }
+
+    /// Send a notification on the backend's behalf
+    pub fn notify(
+        &self,
+        server_id: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<(), LSPError> {
+        let message = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        });
+        self.send_message(server_id, &message.to_string())
+    }
+
     /// Check if a server is running
     #[allow(dead_code)]
     pub fn is_server_running(&self, server_id: &str) -> bool {
@@ -537,7 +637,6 @@ impl LanguageServerManager {
     }
 
     /// Get list of running servers
-    #[allow(dead_code)]
     pub fn get_running_servers(&self) -> Vec<String> {
         let servers = match self.servers.lock() {
             Ok(guard) => guard,
@@ -564,22 +663,18 @@ impl LanguageServerManager {
     pub fn stop_all_servers(&self) {
         println!("[LSP] Stopping all language servers");
 
-        let mut servers = match self.servers.lock() {
-            Ok(guard) => guard,
+        // Drain the map, then shut the servers down with the lock released
+        let servers: Vec<_> = match self.servers.lock() {
+            Ok(mut guard) => guard.drain().collect(),
             Err(poisoned) => {
                 eprintln!("[LSP] Mutex poisoned, recovering...");
-                poisoned.into_inner()
+                poisoned.into_inner().drain().collect()
             }
         };
 
-        let server_ids: Vec<String> = servers.keys().cloned().collect();
-
-        for server_id in server_ids {
-            if let Some(mut server_process) = servers.remove(&server_id) {
-                let _ = server_process.child.kill();
-                let _ = server_process.child.wait();
-                println!("[LSP] Stopped server: {}", server_id);
-            }
+        for (server_id, server_process) in servers {
+            server_process.process.shutdown();
+            println!("[LSP] Stopped server: {}", server_id);
         }
 
         // Reset stats
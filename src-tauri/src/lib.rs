@@ -1,23 +1,51 @@
+mod activity_tracker; // Opt-in local time tracking and timesheet export
+mod agent_config; // Per-workspace agent policies from .rainy/agents.json
+mod agent_redaction; // Secret redaction for agent prompts, responses and logs
 mod agent_server_manager;
+mod background_pause; // Global pause switch for background subsystems
 mod browser_manager; // Integrated browser preview
+mod cli; // Command-line arguments handled before and by the GUI
+mod code_chunker; // Declaration-aware source chunking for agents and indexers
+mod collaboration; // Live-share sessions with CRDT buffer sync
+mod command_broker; // Schema-validated command execution shared by tasks, agents and extensions
+mod completions; // Inline (ghost text) completions with debouncing and caching
 mod configuration_manager;
 mod credential_manager;
+mod deep_link; // rainy:// URLs from browsers, terminals and CI
+mod document_store; // Rope-backed copies of open editor documents
+mod explorer_clipboard; // Paste files and images from the OS clipboard into the workspace
 mod extension_manager;
 mod extension_registry;
 mod file_operations;
+mod focus_mode; // Pomodoro-style focus timer
 mod font_manager;
 mod git; // Modular native Git implementation
 mod help_manager;
 mod icon_theme_manager; // High-performance icon theme management
+mod indentation; // Indent detection, conversion and structure-aware paste
+mod json_schema_store; // Schema-based validation of JSON config files
 mod language_server_manager;
 #[cfg(target_os = "macos")]
 mod menu_manager; // Native macOS menu support
+mod notification_manager; // OS notifications with action callbacks
+mod opener_resolver; // Classifies clicked/typed/dropped strings into open actions
+mod power_manager; // Efficiency mode when idle or on battery
+mod problem_matcher; // Structured diagnostics from compiler/linter output
+mod process_supervisor; // Shared spawn/restart/shutdown logic for child processes
 mod project_manager;
+mod prompt_templates; // Shareable system-prompt templates for agent sessions
+mod provider_client; // API keys and HTTP plumbing for direct provider calls
+mod secret_scanner; // Credential detection on save and before commits
+mod snapshot_manager; // Content-addressed snapshots for risky operations
+mod startup_profiler; // Startup phase timing for slow-start reports
 mod state_manager; // Session state management (Rust-based persistence)
 mod terminal_manager;
 mod theme_manager; // Core Rust theme management
 mod update_manager;
 mod window_manager; // Inngest/AgentKit sidecar manager
+mod workspace_bundle; // Shareable export/import of .rainy workspace configuration
+mod workspace_edit; // Atomic application of LSP workspace edits
+mod workspace_warmup; // Prioritized, cancellable indexing after a workspace opens
 
 #[tauri::command]
 fn open_windows_terminal(app: tauri::AppHandle, cwd: Option<String>) -> Result<(), String> {
@@ -75,10 +103,45 @@ fn set_menu_mode(_app: tauri::AppHandle, _mode: String) -> Result<(), String> {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let mut builder = tauri::Builder::default()
-        .manage(project_manager::WatcherState {
-            watcher: std::sync::Arc::new(std::sync::Mutex::new(None)),
-        })
+    // Headless commands (--list-extensions, --install-extension, --wait) exit here
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    if let Some(code) = cli::handle_before_gui() {
+        std::process::exit(code);
+    }
+
+    startup_profiler::begin();
+    let builder_started = std::time::Instant::now();
+
+    let mut builder = tauri::Builder::default();
+
+    // Must be the first plugin: a second launch hands its arguments (and deep link) to this instance
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            let has_link = argv.iter().skip(1).any(|arg| deep_link::is_link_arg(arg));
+            if cli::handle_args(app, argv, std::path::Path::new(&cwd), false) || has_link {
+                return;
+            }
+            // A plain launch still gets a window of its own, as without single-instance
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = window_manager::window_open_new(app).await {
+                    eprintln!("[Startup] Failed to open window for second launch: {}", e);
+                }
+            });
+        }));
+    }
+
+    builder = builder
+        .manage(project_manager::WatcherState::default())
+        .manage(project_manager::OpenFilesState::default())
+        .manage(project_manager::ReplaceUndoState::default())
+        .manage(project_manager::RecentChangesState::default())
+        .manage(git::repositories::GitRepoRegistry::default())
+        .manage(command_broker::CommandBrokerState::default())
+        .manage(notification_manager::NotificationState::default())
+        .manage(collaboration::CollaborationState::default())
+        .manage(json_schema_store::JsonSchemaStoreState::default())
         .manage(terminal_manager::TerminalState::default())
         .manage(language_server_manager::LanguageServerManager::new())
         .manage(agent_server_manager::AgentServerState::default())
@@ -86,24 +149,50 @@ pub fn run() {
         .manage(icon_theme_manager::IconThemeManagerState::new())
         .manage(theme_manager::ThemeManagerState::new())
         .manage(state_manager::SessionStateManager::new())
+        .register_uri_scheme_protocol(icon_theme_manager::ICON_PROTOCOL, |ctx, request| {
+            icon_theme_manager::handle_icon_protocol(ctx.app_handle(), &request)
+        })
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_pty::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_store::Builder::default().build())
-        .plugin(tauri_plugin_updater::Builder::new().build());
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .on_window_event(|window, event| {
+            power_manager::handle_window_event(window, event);
+            project_manager::handle_window_event(window, event);
+            if let tauri::WindowEvent::CloseRequested { .. } = event {
+                if let Err(e) = document_store::write_hot_exit_journal() {
+                    eprintln!("[DocumentStore] Failed to write hot exit journal: {}", e);
+                }
+                activity_tracker::flush();
+            }
+        });
+    startup_profiler::phase_since("builder.configure", builder_started);
 
     // Desktop-only: register global shortcuts and emit events to frontend
     #[cfg(not(any(target_os = "android", target_os = "ios")))]
     {
         use tauri::Emitter;
-        builder = builder.setup(|app| {
+        let plugins_started = std::time::Instant::now();
+        builder = builder.setup(move |app| {
+            startup_profiler::phase_since("plugins.init", plugins_started);
+            let setup_started = std::time::Instant::now();
+
+            power_manager::start(app.handle());
+            focus_mode::start(app.handle());
+            deep_link::start(app.handle());
+            cli::start(app.handle());
+
             // macOS-only: Set up native application menu (starts with minimal startup menu)
             #[cfg(target_os = "macos")]
             {
                 // Start with startup (minimal) menu - will switch to full menu when project opens
-                match menu_manager::build_startup_menu(app.handle()) {
+                match startup_profiler::phase("menu.build", || {
+                    menu_manager::build_startup_menu(app.handle())
+                }) {
                     Ok(menu) => {
                         if let Err(e) = app.set_menu(menu) {
                             eprintln!("Failed to set macOS menu: {}", e);
@@ -238,6 +327,7 @@ pub fn run() {
                     .build(),
             )?;
 
+            startup_profiler::phase_since("setup", setup_started);
             Ok(())
         });
     }
@@ -271,6 +361,7 @@ pub fn run() {
         window_manager::open_external_url,
         // Help and documentation
         help_manager::get_keyboard_shortcuts,
+        help_manager::detect_keybinding_conflicts,
         help_manager::get_documentation_links,
         help_manager::get_app_info,
         help_manager::get_available_commands,
@@ -278,20 +369,185 @@ pub fn run() {
         project_manager::open_project_dialog,
         project_manager::load_project_structure,
         project_manager::load_directory_children,
+        project_manager::resolve_symlink,
+        project_manager::workspace_paths::to_workspace_relative,
+        project_manager::workspace_paths::from_workspace_relative,
+        project_manager::structure_stream::stream_project_structure,
+        project_manager::structure_stream::cancel_project_structure_stream,
+        project_manager::file_index::build_file_index,
+        project_manager::file_index::fuzzy_find_files,
+        project_manager::file_index::get_file_index_status,
+        project_manager::semantic_index::build_semantic_index,
+        project_manager::semantic_index::semantic_search,
+        project_manager::semantic_index::get_semantic_index_status,
         project_manager::list_directory,
         project_manager::get_file_content,
+        project_manager::binary_content::get_file_binary,
+        project_manager::large_file::read_file_range,
+        project_manager::large_file::read_file_lines,
+        project_manager::dir_stats::compute_directory_stats,
         project_manager::save_file_content,
+        project_manager::track_open_file,
+        project_manager::untrack_open_file,
+        project_manager::ignore_pattern_for_path,
+        project_manager::add_ignore_pattern,
+        project_manager::remove_ignore_pattern,
+        project_manager::check_path_ignored,
+        project_manager::get_recently_changed_files,
+        project_manager::recent_files_changed,
+        project_manager::file_hash::hash_file,
+        project_manager::file_hash::hash_files,
         project_manager::watch_project_changes,
+        project_manager::unwatch_project_changes,
+        project_manager::watcher_pause,
+        project_manager::watcher_resume,
         project_manager::create_file,
         project_manager::create_folder,
         project_manager::rename_path,
         project_manager::delete_path,
+        project_manager::batch_ops::batch_file_operation,
+        project_manager::copy_ops::copy_path,
+        project_manager::trash::delete_to_trash,
+        project_manager::trash::restore_from_trash,
         project_manager::get_temp_dir,
         project_manager::search_in_workspace,
         project_manager::replace_in_file,
-        project_manager::execute_command,
+        project_manager::replace_in_workspace,
+        project_manager::undo_last_replace,
+        project_manager::command_exec::execute_command,
+        project_manager::command_exec::kill_command,
+        workspace_edit::apply_workspace_edit,
+        // Collaboration sessions
+        collaboration::collab_host_session,
+        collaboration::collab_join_session,
+        collaboration::collab_leave_session,
+        collaboration::collab_get_session,
+        collaboration::collab_list_directory,
+        collaboration::collab_open_document,
+        collaboration::collab_close_document,
+        collaboration::collab_apply_edit,
+        collaboration::collab_update_presence,
+        collaboration::collab_save_document,
+        // Open document buffers
+        document_store::document_open,
+        document_store::document_apply_delta,
+        document_store::document_close,
+        document_store::document_get_text,
+        document_store::document_list,
+        document_store::document_search,
+        document_store::document_minimap,
+        document_store::document_write_hot_exit_journal,
+        document_store::document_take_hot_exit_journal,
+        // Open anything
+        opener_resolver::resolve_opener,
+        // Workspace agent configuration
+        agent_config::get_agent_config,
+        agent_config::agent_enabled_tools,
+        agent_config::agent_command_allowed,
+        agent_config::watch_agent_config,
+        agent_config::unwatch_agent_config,
+        prompt_templates::list_prompt_templates,
+        prompt_templates::get_prompt_template,
+        prompt_templates::save_prompt_template,
+        prompt_templates::delete_prompt_template,
+        prompt_templates::render_prompt_template,
+        // Workspace warmup
+        workspace_warmup::start_workspace_warmup,
+        workspace_warmup::cancel_workspace_warmup,
+        workspace_warmup::get_workspace_warmup_status,
+        workspace_warmup::search_workspace_symbols,
+        // Explorer clipboard paste
+        explorer_clipboard::read_clipboard_files,
+        explorer_clipboard::paste_clipboard_into,
+        // Workspace bundles
+        workspace_bundle::export_workspace_bundle,
+        workspace_bundle::preview_workspace_bundle,
+        workspace_bundle::import_workspace_bundle,
+        // Virtual file systems
+        project_manager::vfs::vfs_list_providers,
+        project_manager::vfs::vfs_mount,
+        project_manager::vfs::vfs_unmount,
+        project_manager::vfs::vfs_list_mounts,
+        project_manager::vfs::vfs_stat,
+        project_manager::vfs::vfs_read_dir,
+        project_manager::vfs::vfs_read_file,
+        project_manager::vfs::vfs_write_file,
+        project_manager::vfs::vfs_delete,
+        project_manager::vfs::vfs_create_dir,
+        project_manager::vfs::vfs_watch,
+        project_manager::vfs::vfs_unwatch,
+        // Problem matchers
+        problem_matcher::problem_matchers_list,
+        problem_matcher::problem_matchers_register,
+        problem_matcher::problem_matchers_unregister,
+        problem_matcher::match_problems,
+        // Inline completions
+        completions::inline_completion_request,
+        completions::inline_completion_cancel,
+        completions::inline_completion_accept,
+        completions::inline_completion_reject,
+        completions::inline_completion_metrics,
+        // Activity tracking
+        activity_tracker::activity_record,
+        activity_tracker::activity_get_summaries,
+        activity_tracker::activity_export,
+        activity_tracker::activity_clear,
+        activity_tracker::activity_list_workspaces,
+        // Deep links
+        deep_link::deep_link_take_pending,
+        deep_link::deep_link_parse,
+        deep_link::deep_link_trust_workspace,
+        // Agent redaction
+        agent_redaction::agent_redact,
+        agent_redaction::agent_redaction_report,
+        agent_redaction::agent_redaction_reports,
+        agent_redaction::agent_redaction_clear,
+        // Indentation
+        indentation::indentation_detect,
+        indentation::indentation_convert,
+        indentation::indentation_reindent_paste,
+        // CLI
+        cli::cli_take_pending,
+        cli::cli_wait_done,
+        // Notifications
+        notification_manager::show_notification,
+        notification_manager::invoke_notification_action,
+        notification_manager::dismiss_notification,
+        notification_manager::set_do_not_disturb,
+        notification_manager::get_do_not_disturb,
+        // Focus mode
+        focus_mode::focus_start,
+        focus_mode::focus_pause,
+        focus_mode::focus_resume,
+        focus_mode::focus_stop,
+        focus_mode::focus_skip,
+        focus_mode::focus_get_state,
+        // JSON schema validation
+        json_schema_store::register_json_schemas,
+        json_schema_store::unregister_json_schemas,
+        json_schema_store::get_json_schema_for_file,
+        json_schema_store::validate_json_document,
+        // Secret scanning
+        secret_scanner::scan_file_for_secrets,
+        secret_scanner::add_secrets_to_baseline,
+        git::secrets::git_scan_staged_secrets,
+        // Power mode
+        power_manager::get_power_status,
+        power_manager::set_power_override,
+        power_manager::refresh_power_status,
+        // Background pause
+        background_pause::background_pause,
+        background_pause::background_resume,
+        background_pause::get_background_pause_status,
+        command_broker::broker_register_command,
+        command_broker::broker_unregister_command,
+        command_broker::broker_list_commands,
+        command_broker::broker_run_command,
         terminal_manager::terminal_create,
         terminal_manager::terminal_write,
+        terminal_manager::terminal_paste,
+        terminal_manager::terminal_set_problem_matchers,
+        terminal_manager::terminal_resolve_clipboard_request,
         terminal_manager::terminal_resize,
         terminal_manager::terminal_kill,
         terminal_manager::terminal_change_directory,
@@ -299,27 +555,48 @@ pub fn run() {
         terminal_manager::terminal_list_sessions,
         terminal_manager::terminal_get_profiles,
         terminal_manager::terminal_init_profiles,
+        terminal_manager::terminal_import_profiles,
         // Git integration - Native libgit2 implementation
         // Status operations
         git::status::git_is_repo,
+        git::watcher::git_watch_repository,
+        git::watcher::git_unwatch_repository,
         git::status::git_init,
         git::status::git_delete_repo,
         git::status::git_status,
+        git::repo_state::git_repo_state,
+        git::repositories::git_list_repositories,
+        git::repositories::git_watch_repositories,
+        git::repositories::git_unwatch_repositories,
         git::status::git_stage_file,
+        git::status::git_stage_lines,
         git::status::git_stage_all,
         git::status::git_unstage_file,
         git::status::git_unstage_all,
         git::status::git_discard_changes,
         git::status::git_discard_files,
+        git::status::git_restore_file,
+        git::status::git_clean,
         // History operations
         git::history::git_log,
+        git::history::git_file_history,
         git::history::git_show_files,
         git::history::git_diff,
         git::history::git_diff_file,
         git::history::git_diff_commit,
         git::history::git_diff_commit_file,
+        git::history::git_diff_refs,
+        git::history::git_merge_base,
+        git::history::git_diff_merge_base,
         git::history::git_unpushed,
         git::history::git_sync_status,
+        // Config
+        git::config::git_get_config,
+        git::config::git_set_config,
+        git::config::git_unset_config,
+        git::config::git_list_config,
+        git::config::git_get_user_identity,
+        git::config::git_set_user_identity,
         // Branch operations
         git::branch::git_branches,
         git::branch::git_get_current_branch,
@@ -330,6 +607,13 @@ pub fn run() {
         // Commit operations
         git::commit::git_commit,
         git::commit::git_amend_commit,
+        git::trailers::git_get_recent_coauthors,
+        git::fixup::git_amend_status,
+        git::fixup::git_create_fixup_commit,
+        git::fixup::git_autosquash_rebase,
+        git::commit_message::git_generate_commit_message,
+        git::commit_template::git_get_commit_template,
+        git::commit_template::git_validate_commit_message,
         git::commit::git_reset,
         git::commit::git_revert,
         git::commit::git_cherry_pick,
@@ -338,22 +622,51 @@ pub fn run() {
         git::remote::git_pull,
         git::remote::git_fetch,
         git::remote::git_clone,
+        git::ssh::git_list_ssh_keys,
+        git::ssh::git_provide_passphrase,
         git::remote::git_list_remotes,
         git::remote::git_add_remote,
         git::remote::git_remove_remote,
         git::remote::git_set_remote_url,
+        git::sync::git_sync,
         // Stash operations
         git::stash::git_stash_list,
         git::stash::git_stash_push,
         git::stash::git_stash_pop,
+        git::stash::git_stash_apply,
+        git::stash::git_stash_drop,
+        git::stash::git_stash_show,
+        git::stash::git_stash_branch,
         // Merge & Conflict operations
         git::merge::git_merge,
+        git::merge::git_merge_preview,
         git::merge::git_merge_abort,
         git::merge::git_list_conflicts,
         git::merge::git_get_conflict_content,
         git::merge::git_resolve_conflict,
         git::merge::git_accept_ours,
         git::merge::git_accept_theirs,
+        git::conflict_markers::git_parse_conflict_markers,
+        git::conflict_markers::git_write_conflict_resolution,
+        // Rebase operations
+        git::rebase::git_rebase,
+        git::rebase::git_rebase_continue,
+        git::rebase::git_rebase_skip,
+        git::rebase::git_rebase_abort,
+        // Reflog & recovery
+        git::reflog::git_reflog,
+        git::reflog::git_checkout_reflog_entry,
+        git::reflog::git_reset_to_reflog_entry,
+        // Archive
+        git::archive::git_archive,
+        // Patches
+        git::patch::git_format_patch,
+        git::patch::git_apply_patch,
+        // Bisect
+        git::bisect::git_bisect_start,
+        git::bisect::git_bisect_mark,
+        git::bisect::git_bisect_status,
+        git::bisect::git_bisect_reset,
         // Agent credential management
         credential_manager::agent_store_credential,
         credential_manager::agent_get_credential,
@@ -367,6 +680,12 @@ pub fn run() {
         file_operations::tool_rename_file,
         file_operations::tool_copy_file,
         file_operations::tool_batch_read_files,
+        file_operations::tool_search_workspace,
+        file_operations::tool_read_file_chunks,
+        file_operations::tool_set_session_root,
+        file_operations::tool_end_session,
+        file_operations::tool_approve_outside_path,
+        file_operations::tool_sandbox_metrics,
         // Extension management
         extension_manager::load_installed_extensions,
         extension_manager::save_installed_extensions,
@@ -413,6 +732,7 @@ pub fn run() {
         font_manager::load_font_manifest,
         font_manager::save_font_manifest,
         font_manager::download_font_file,
+        font_manager::subset_font_file,
         font_manager::read_font_file_base64,
         font_manager::import_custom_font_file,
         font_manager::delete_font_file,
@@ -424,6 +744,8 @@ pub fn run() {
         agent_server_manager::agent_server_status,
         agent_server_manager::agent_server_health,
         agent_server_manager::agent_server_health,
+        agent_server_manager::agent_server_metrics,
+        agent_server_manager::agent_server_logs,
         // Theme management (Rust backend)
         theme_manager::set_backend_theme,
         theme_manager::get_backend_theme,
@@ -446,14 +768,30 @@ pub fn run() {
         icon_theme_manager::get_file_icon,
         icon_theme_manager::get_folder_icon,
         icon_theme_manager::get_icons_batch,
+        icon_theme_manager::get_explorer_icons_batch,
+        icon_theme_manager::set_icon_theme_kind,
+        icon_theme_manager::get_icon_theme_kind,
+        icon_theme_manager::set_icon_delivery_mode,
         icon_theme_manager::unregister_icon_theme,
         icon_theme_manager::get_loaded_icon_themes,
         // Session state management (Rust-based persistence)
         state_manager::get_session_state,
         state_manager::save_session_state,
         state_manager::clear_session_state,
+        state_manager::capture_session_environment,
+        state_manager::get_session_environment,
+        state_manager::diff_session_environment,
+        // Snapshot commands
+        snapshot_manager::snapshot_create,
+        snapshot_manager::snapshot_list,
+        snapshot_manager::snapshot_diff,
+        snapshot_manager::snapshot_restore,
+        snapshot_manager::snapshot_delete,
         // Menu mode switching (cross-platform, macOS has real implementation)
         set_menu_mode,
+        // Startup profiling
+        startup_profiler::record_startup_mark,
+        startup_profiler::get_startup_profile,
     ]);
 
     if let Err(error) = builder.run(tauri::generate_context!()) {
@@ -0,0 +1,473 @@
+//! Notification Manager
+//!
+//! Shows OS notifications with action buttons ("Build failed — [Open log]
+//! [Rerun]") and routes clicked actions back to the frontend as
+//! `notification:action` events carrying the command to run.
+//!
+//! Native delivery uses whatever the platform offers without extra
+//! dependencies: `notify-send --wait` on Linux, `terminal-notifier` (or plain
+//! `osascript` without buttons) on macOS, and a PowerShell toast on Windows.
+//! When buttons can't be shown natively the notification is also emitted as
+//! `notification:in-app` so the action can be taken from the notification
+//! center.
+//!
+//! Per-source settings and quiet hours are read from settings.json:
+//! `notifications.enabled`, `notifications.sources.<source>.enabled` /
+//! `.native`, and `notifications.quietHours` (`{ enabled, start, end }` in
+//! local "HH:MM"). Critical notifications ignore quiet hours.
+//!
+//! Do not disturb silences notifications the same way. It is toggled by the
+//! user or, during focus blocks, by the focus timer.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, State};
+
+/// Application name shown by the OS
+const APP_NAME: &str = "Rainy Aether";
+
+/// Do not disturb, as set by the user and by the focus timer
+static MANUAL_DND: AtomicBool = AtomicBool::new(false);
+static FOCUS_DND: AtomicBool = AtomicBool::new(false);
+
+/// A button on a notification
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationAction {
+    pub id: String,
+    pub label: String,
+    /// Command the frontend should run when the action is clicked
+    pub command: Option<String>,
+    pub args: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRequest {
+    /// Originating subsystem ("build", "git", "extensions", ...) used for per-source settings
+    pub source: String,
+    pub title: String,
+    pub body: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<NotificationAction>,
+    /// "low", "normal" (default) or "critical"
+    pub urgency: Option<String>,
+    pub workspace_path: Option<String>,
+}
+
+/// A notification as sent to the frontend
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationPayload {
+    pub id: String,
+    #[serde(flatten)]
+    pub request: NotificationRequest,
+    /// True when shown during quiet hours or do not disturb (no sound/popup expected)
+    pub silent: bool,
+}
+
+/// Payload of the `notification:action` event
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationActionEvent {
+    pub notification_id: String,
+    pub source: String,
+    pub action_id: String,
+    pub command: Option<String>,
+    pub args: Option<Value>,
+}
+
+/// How a notification was delivered
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationResult {
+    pub id: String,
+    /// "native", "inApp" or "suppressed"
+    pub delivery: String,
+    pub reason: Option<String>,
+}
+
+/// Notifications whose actions can still be invoked
+#[derive(Default)]
+pub struct NotificationState {
+    pending: Arc<Mutex<HashMap<String, NotificationRequest>>>,
+}
+
+/// Payload of the `notification:do-not-disturb-changed` event
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DoNotDisturbStatus {
+    pub enabled: bool,
+    /// Turned on by the user
+    pub manual: bool,
+    /// Turned on for a focus block
+    pub focus: bool,
+}
+
+/// Effective settings for one notification source
+struct SourcePolicy {
+    enabled: bool,
+    native: bool,
+    /// Why the notification is silenced, if it is
+    quiet: Option<&'static str>,
+}
+
+fn setting(workspace_path: Option<&str>, key: &str) -> Option<Value> {
+    crate::configuration_manager::resolve_setting(workspace_path, key)
+}
+
+fn parse_hhmm(value: &str) -> Option<chrono::NaiveTime> {
+    chrono::NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+/// Whether `now` falls in quiet hours; ranges may wrap past midnight
+fn in_quiet_hours(config: &Value, now: chrono::NaiveTime) -> bool {
+    if !config
+        .get("enabled")
+        .and_then(Value::as_bool)
+        .unwrap_or(true)
+    {
+        return false;
+    }
+    let (Some(start), Some(end)) = (
+        config
+            .get("start")
+            .and_then(Value::as_str)
+            .and_then(parse_hhmm),
+        config
+            .get("end")
+            .and_then(Value::as_str)
+            .and_then(parse_hhmm),
+    ) else {
+        return false;
+    };
+
+    if start <= end {
+        now >= start && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+fn source_policy(request: &NotificationRequest) -> SourcePolicy {
+    let ws = request.workspace_path.as_deref();
+    let global_enabled = setting(ws, "notifications.enabled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let source = setting(ws, "notifications.sources")
+        .and_then(|sources| sources.get(&request.source).cloned())
+        .unwrap_or(Value::Null);
+
+    let critical = request.urgency.as_deref() == Some("critical");
+    let quiet = if critical {
+        None
+    } else if do_not_disturb_status().enabled {
+        Some("Do not disturb")
+    } else if setting(ws, "notifications.quietHours")
+        .map(|config| in_quiet_hours(&config, chrono::Local::now().time()))
+        .unwrap_or(false)
+    {
+        Some("Quiet hours")
+    } else {
+        None
+    };
+
+    SourcePolicy {
+        enabled: global_enabled
+            && source
+                .get("enabled")
+                .and_then(Value::as_bool)
+                .unwrap_or(true),
+        native: source
+            .get("native")
+            .and_then(Value::as_bool)
+            .unwrap_or(true),
+        quiet,
+    }
+}
+
+fn do_not_disturb_status() -> DoNotDisturbStatus {
+    let manual = MANUAL_DND.load(Ordering::Relaxed);
+    let focus = FOCUS_DND.load(Ordering::Relaxed);
+    DoNotDisturbStatus {
+        enabled: manual || focus,
+        manual,
+        focus,
+    }
+}
+
+fn emit_do_not_disturb(app: &AppHandle) {
+    let _ = app.emit(
+        "notification:do-not-disturb-changed",
+        do_not_disturb_status(),
+    );
+}
+
+/// Silence notifications for the duration of a focus block
+pub(crate) fn set_focus_do_not_disturb(app: &AppHandle, enabled: bool) {
+    if FOCUS_DND.swap(enabled, Ordering::Relaxed) != enabled {
+        emit_do_not_disturb(app);
+    }
+}
+
+/// Route a clicked action to the frontend and retire the notification
+fn dispatch_action(
+    app: &AppHandle,
+    pending: &Mutex<HashMap<String, NotificationRequest>>,
+    notification_id: &str,
+    action_id: &str,
+) -> Result<(), String> {
+    let request = pending
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(notification_id)
+        .ok_or_else(|| format!("Notification not found: {}", notification_id))?;
+    let action = request
+        .actions
+        .iter()
+        .find(|a| a.id == action_id)
+        .ok_or_else(|| format!("Unknown action: {}", action_id))?;
+
+    app.emit(
+        "notification:action",
+        NotificationActionEvent {
+            notification_id: notification_id.to_string(),
+            source: request.source.clone(),
+            action_id: action.id.clone(),
+            command: action.command.clone(),
+            args: action.args.clone(),
+        },
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Build the native notifier command; the bool is whether it reports clicked actions on stdout
+#[cfg(target_os = "linux")]
+fn native_command(request: &NotificationRequest) -> Option<(Command, bool)> {
+    which::which("notify-send").ok()?;
+    let mut cmd = Command::new("notify-send");
+    cmd.arg(format!("--app-name={}", APP_NAME));
+    if let Some(urgency) = request.urgency.as_deref() {
+        cmd.arg(format!("--urgency={}", urgency));
+    }
+    for action in &request.actions {
+        cmd.arg(format!("--action={}={}", action.id, action.label));
+    }
+    let with_actions = !request.actions.is_empty();
+    if with_actions {
+        cmd.arg("--wait");
+    }
+    cmd.arg(&request.title)
+        .arg(request.body.as_deref().unwrap_or(""));
+    Some((cmd, with_actions))
+}
+
+#[cfg(target_os = "macos")]
+fn native_command(request: &NotificationRequest) -> Option<(Command, bool)> {
+    if which::which("terminal-notifier").is_ok() {
+        let mut cmd = Command::new("terminal-notifier");
+        cmd.args(["-title", APP_NAME, "-subtitle", &request.title]);
+        cmd.args(["-message", request.body.as_deref().unwrap_or(" ")]);
+        let with_actions = !request.actions.is_empty();
+        if with_actions {
+            let labels: Vec<&str> = request.actions.iter().map(|a| a.label.as_str()).collect();
+            cmd.args(["-actions", &labels.join(",")]);
+        }
+        return Some((cmd, with_actions));
+    }
+
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!(
+        "display notification \"{}\" with title \"{}\" subtitle \"{}\"",
+        escape(request.body.as_deref().unwrap_or("")),
+        APP_NAME,
+        escape(&request.title)
+    );
+    let mut cmd = Command::new("osascript");
+    cmd.args(["-e", &script]);
+    Some((cmd, false))
+}
+
+#[cfg(target_os = "windows")]
+fn native_command(request: &NotificationRequest) -> Option<(Command, bool)> {
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('\'', "''")
+    };
+    let script = format!(
+        "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; \
+         $xml = New-Object Windows.Data.Xml.Dom.XmlDocument; \
+         $xml.LoadXml('<toast><visual><binding template=\"ToastGeneric\"><text>{}</text><text>{}</text></binding></visual></toast>'); \
+         [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('{}').Show([Windows.UI.Notifications.ToastNotification]::new($xml))",
+        escape(&request.title),
+        escape(request.body.as_deref().unwrap_or("")),
+        APP_NAME
+    );
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+    Some((cmd, false))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn native_command(_request: &NotificationRequest) -> Option<(Command, bool)> {
+    None
+}
+
+/// Show the notification natively, watching for clicked actions in the background
+fn show_native(
+    app: &AppHandle,
+    pending: &Arc<Mutex<HashMap<String, NotificationRequest>>>,
+    id: &str,
+    request: &NotificationRequest,
+) -> Result<bool, String> {
+    let Some((mut cmd, reports_actions)) = native_command(request) else {
+        return Err("No native notifier available".to_string());
+    };
+
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to show notification: {}", e))?;
+
+    let app = app.clone();
+    let pending = Arc::clone(pending);
+    let id = id.to_string();
+    let actions = request.actions.clone();
+    std::thread::spawn(move || {
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                let clicked = line.trim();
+                // notify-send prints the action id, terminal-notifier the label
+                if let Some(action) = actions
+                    .iter()
+                    .find(|a| a.id == clicked || a.label == clicked)
+                {
+                    if let Err(e) = dispatch_action(&app, &pending, &id, &action.id) {
+                        eprintln!("[Notifications] Failed to route action: {}", e);
+                    }
+                    break;
+                }
+            }
+        }
+        let _ = child.wait();
+    });
+
+    Ok(reports_actions)
+}
+
+/// Show a notification, honoring per-source settings and quiet hours
+#[tauri::command]
+pub fn show_notification(
+    app: AppHandle,
+    request: NotificationRequest,
+    state: State<'_, NotificationState>,
+) -> Result<NotificationResult, String> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let policy = source_policy(&request);
+
+    if !policy.enabled {
+        return Ok(NotificationResult {
+            id,
+            delivery: "suppressed".to_string(),
+            reason: Some(format!(
+                "Notifications from '{}' are disabled",
+                request.source
+            )),
+        });
+    }
+
+    if !request.actions.is_empty() {
+        state
+            .pending
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(id.clone(), request.clone());
+    }
+
+    let mut reason = None;
+    let mut native_actions = false;
+    let native = if let Some(quiet) = policy.quiet {
+        reason = Some(quiet.to_string());
+        false
+    } else if !policy.native {
+        false
+    } else {
+        match show_native(&app, &state.pending, &id, &request) {
+            Ok(reports_actions) => {
+                native_actions = reports_actions;
+                true
+            }
+            Err(e) => {
+                eprintln!("[Notifications] {}", e);
+                reason = Some(e);
+                false
+            }
+        }
+    };
+
+    // In-app copy whenever the native popup can't carry the notification or its buttons
+    if !native || (!request.actions.is_empty() && !native_actions) {
+        let _ = app.emit(
+            "notification:in-app",
+            NotificationPayload {
+                id: id.clone(),
+                request,
+                silent: policy.quiet.is_some(),
+            },
+        );
+    }
+
+    Ok(NotificationResult {
+        id,
+        delivery: if native { "native" } else { "inApp" }.to_string(),
+        reason,
+    })
+}
+
+/// Invoke a notification action from the in-app notification center
+#[tauri::command]
+pub fn invoke_notification_action(
+    app: AppHandle,
+    notification_id: String,
+    action_id: String,
+    state: State<'_, NotificationState>,
+) -> Result<(), String> {
+    dispatch_action(&app, &state.pending, &notification_id, &action_id)
+}
+
+/// Forget a notification without running any of its actions
+#[tauri::command]
+pub fn dismiss_notification(
+    notification_id: String,
+    state: State<'_, NotificationState>,
+) -> Result<(), String> {
+    state
+        .pending
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&notification_id);
+    Ok(())
+}
+
+/// Turn do not disturb on or off; focus blocks may still hold it on
+#[tauri::command]
+pub fn set_do_not_disturb(app: AppHandle, enabled: bool) -> DoNotDisturbStatus {
+    if MANUAL_DND.swap(enabled, Ordering::Relaxed) != enabled {
+        emit_do_not_disturb(&app);
+    }
+    do_not_disturb_status()
+}
+
+#[tauri::command]
+pub fn get_do_not_disturb() -> DoNotDisturbStatus {
+    do_not_disturb_status()
+}
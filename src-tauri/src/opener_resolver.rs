@@ -0,0 +1,304 @@
+//! Opener Resolver
+//!
+//! Classifies arbitrary strings (terminal link clicks, command palette input,
+//! dropped text) into what opening them should do: a file at a line/column,
+//! a URL, a commit, or an issue/PR reference. Keeping this in one place means
+//! `src/foo.rs:12:5`, `src/foo.rs(12,5)` and `file:///…/foo.rs#L12` all
+//! resolve the same way everywhere.
+
+use git2::Repository;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// `path:line[:col]`, `path(line[,col])`, `path#Lline[Ccol]` and Python's
+/// `File "path", line N, in fn`
+static PATH_WITH_LOCATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"^(?:File ")?(?P<path>.+?)(?::(?P<line>\d+)(?::(?P<col>\d+))?|\((?P<pline>\d+)(?:,\s*(?P<pcol>\d+))?\)|#L(?P<hline>\d+)(?:C(?P<hcol>\d+))?|",? line (?P<qline>\d+)(?:, in .*)?)"?:?$"#,
+    )
+    .expect("invalid path pattern")
+});
+
+/// `#123`, `GH-123`, `owner/repo#123`
+static ISSUE_REFERENCE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:(?P<repo>[\w.-]+/[\w.-]+)#|#|GH-)(?P<number>\d+)$")
+        .expect("invalid issue pattern")
+});
+
+static COMMIT_HASH: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[0-9a-fA-F]{7,40}$").expect("invalid hash pattern"));
+
+static URL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:https?|ftp|mailto|vscode|rainy)://\S+$|^mailto:\S+$|^www\.\S+\.\S+$")
+        .expect("invalid url pattern")
+});
+
+/// What opening the input should do
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum OpenerAction {
+    /// 1-based line and column
+    File {
+        path: String,
+        line: Option<u32>,
+        column: Option<u32>,
+        exists: bool,
+        is_directory: bool,
+    },
+    Url {
+        url: String,
+    },
+    Commit {
+        hash: String,
+        /// Repository the commit was found in
+        repository: Option<String>,
+    },
+    Issue {
+        number: u64,
+        /// `owner/repo` when given explicitly
+        repository: Option<String>,
+        /// Web URL when the hosting provider is known
+        url: Option<String>,
+    },
+    Unknown {
+        input: String,
+    },
+}
+
+/// Strip wrapping quotes/brackets and trailing punctuation picked up from prose or logs
+fn clean_input(input: &str) -> &str {
+    let mut s = input.trim();
+    loop {
+        let trimmed = s
+            .strip_prefix(['"', '\'', '`', '<', '['])
+            .and_then(|inner| inner.strip_suffix(['"', '\'', '`', '>', ']']))
+            .unwrap_or(s)
+            .trim_end_matches(['.', ',', ';'])
+            .trim();
+        if trimmed == s {
+            return s;
+        }
+        s = trimmed;
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Resolve a path against the terminal cwd, then the workspace root
+fn resolve_path(path: &str, bases: &[&Path]) -> PathBuf {
+    let candidate = expand_home(path);
+    if candidate.is_absolute() {
+        return candidate;
+    }
+    // Diff-style prefixes ("a/src/x.rs") from git output
+    let stripped = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .map(PathBuf::from);
+
+    for base in bases {
+        if base.join(&candidate).exists() {
+            return base.join(&candidate);
+        }
+        if let Some(stripped) = &stripped {
+            if base.join(stripped).exists() {
+                return base.join(stripped);
+            }
+        }
+    }
+    bases
+        .first()
+        .map(|base| base.join(&candidate))
+        .unwrap_or(candidate)
+}
+
+fn file_action(path: PathBuf, line: Option<u32>, column: Option<u32>) -> OpenerAction {
+    OpenerAction::File {
+        exists: path.exists(),
+        is_directory: path.is_dir(),
+        path: path.to_string_lossy().to_string(),
+        line,
+        column,
+    }
+}
+
+fn looks_like_path(input: &str) -> bool {
+    !input.contains(char::is_whitespace) && (input.contains(['/', '\\']) || input.contains('.'))
+        || input.starts_with('~')
+}
+
+/// Web base URL (`https://host/owner/repo`) of the workspace's origin remote
+fn remote_web_url(workspace: &Path) -> Option<String> {
+    let repo = Repository::discover(workspace).ok()?;
+    let remote = repo.find_remote("origin").ok()?;
+    let url = remote.url()?.trim_end_matches(".git").to_string();
+
+    let web = if let Some(rest) = url.strip_prefix("git@") {
+        // git@host:owner/repo
+        let (host, path) = rest.split_once(':')?;
+        format!("https://{}/{}", host, path)
+    } else if let Some(rest) = url.strip_prefix("ssh://") {
+        let rest = rest.rsplit('@').next()?;
+        let (host, path) = rest.split_once('/')?;
+        format!("https://{}/{}", host.split(':').next()?, path)
+    } else if url.starts_with("https://") || url.starts_with("http://") {
+        // Drop embedded credentials
+        match url.split_once("://").and_then(|(scheme, rest)| {
+            rest.split_once('@')
+                .filter(|(userinfo, _)| !userinfo.contains('/'))
+                .map(|(_, host)| format!("{}://{}", scheme, host))
+        }) {
+            Some(clean) => clean,
+            None => url,
+        }
+    } else {
+        return None;
+    };
+    Some(web)
+}
+
+fn issue_url(base: &str, number: u64) -> String {
+    if base.contains("gitlab") {
+        format!("{}/-/issues/{}", base, number)
+    } else {
+        // GitHub and most forges redirect /issues/N to the PR when N is one
+        format!("{}/issues/{}", base, number)
+    }
+}
+
+/// Repository containing the commit, if any
+fn find_commit(hash: &str, workspace: Option<&Path>) -> Option<String> {
+    let repo = Repository::discover(workspace?).ok()?;
+    let object = repo.revparse_single(hash).ok()?;
+    object.peel_to_commit().ok()?;
+    repo.workdir()
+        .map(|dir| dir.to_string_lossy().trim_end_matches('/').to_string())
+}
+
+/// Classify a string
+pub fn resolve(input: &str, workspace_root: Option<&Path>, cwd: Option<&Path>) -> OpenerAction {
+    let input = clean_input(input);
+    if input.is_empty() {
+        return OpenerAction::Unknown {
+            input: input.to_string(),
+        };
+    }
+    let bases: Vec<&Path> = cwd.into_iter().chain(workspace_root).collect();
+
+    // file:// URLs are files, not web links
+    if let Some(rest) = input.strip_prefix("file://") {
+        let decoded = urlencoding::decode(rest)
+            .map(|d| d.into_owned())
+            .unwrap_or_else(|_| rest.to_string());
+        let decoded = if cfg!(windows) {
+            decoded.trim_start_matches('/').to_string()
+        } else {
+            decoded
+        };
+        return match PATH_WITH_LOCATION.captures(&decoded) {
+            Some(captures) => {
+                let (line, column) = location(&captures);
+                file_action(PathBuf::from(&captures["path"]), line, column)
+            }
+            None => file_action(PathBuf::from(decoded), None, None),
+        };
+    }
+
+    if URL.is_match(input) {
+        let url = if input.starts_with("www.") {
+            format!("https://{}", input)
+        } else {
+            input.to_string()
+        };
+        return OpenerAction::Url { url };
+    }
+
+    if let Some(captures) = ISSUE_REFERENCE.captures(input) {
+        let number: u64 = captures["number"].parse().unwrap_or_default();
+        let repository = captures.name("repo").map(|m| m.as_str().to_string());
+        let url = match &repository {
+            Some(repo) => Some(issue_url(&format!("https://github.com/{}", repo), number)),
+            None => workspace_root
+                .and_then(remote_web_url)
+                .map(|base| issue_url(&base, number)),
+        };
+        return OpenerAction::Issue {
+            number,
+            repository,
+            url,
+        };
+    }
+
+    if let Some(captures) = PATH_WITH_LOCATION.captures(input) {
+        let path = &captures["path"];
+        // "12:30" is a time, not a file
+        if !path.chars().all(|c| c.is_ascii_digit()) {
+            let (line, column) = location(&captures);
+            return file_action(resolve_path(path, &bases), line, column);
+        }
+    }
+
+    if COMMIT_HASH.is_match(input) {
+        let repository = find_commit(input, workspace_root.or(cwd));
+        // Without a repository to check, all-letter words like "deadbeef" stay ambiguous
+        let has_digit = input.chars().any(|c| c.is_ascii_digit());
+        let path_exists = bases.iter().any(|base| base.join(input).exists());
+        if !path_exists && (repository.is_some() || has_digit) {
+            return OpenerAction::Commit {
+                hash: input.to_string(),
+                repository,
+            };
+        }
+    }
+
+    let path = resolve_path(input, &bases);
+    if path.exists() || looks_like_path(input) {
+        return file_action(path, None, None);
+    }
+
+    OpenerAction::Unknown {
+        input: input.to_string(),
+    }
+}
+
+fn location(captures: &regex::Captures) -> (Option<u32>, Option<u32>) {
+    let number = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| captures.name(name))
+            .and_then(|m| m.as_str().parse().ok())
+    };
+    (
+        number(&["line", "pline", "hline", "qline"]),
+        number(&["col", "pcol", "hcol"]),
+    )
+}
+
+/// Classify a string clicked, typed or dropped by the user. Relative paths
+/// are resolved against `cwd` (e.g. the terminal's directory) first, then
+/// `workspace_root`.
+#[tauri::command]
+pub fn resolve_opener(
+    input: String,
+    workspace_root: Option<String>,
+    cwd: Option<String>,
+) -> Result<OpenerAction, String> {
+    Ok(resolve(
+        &input,
+        workspace_root.as_deref().map(Path::new),
+        cwd.as_deref().map(Path::new),
+    ))
+}
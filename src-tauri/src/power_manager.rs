@@ -0,0 +1,303 @@
+//! Power Manager
+//!
+//! Switches the app into an efficiency mode when no window is in use (all
+//! minimized or unfocused) or the device is running on battery, and back when
+//! that stops being true. Background work reads the current
+//! `BackgroundBudget` to decide how often to run: the project watcher batches
+//! its events with it, and the frontend schedulers (auto-fetch, indexers,
+//! metrics sampling) receive it through the `power:mode-changed` event.
+//!
+//! The automatic choice can be overridden from settings or commands.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Window, WindowEvent};
+
+/// How often the battery state is polled
+const BATTERY_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PowerMode {
+    Normal,
+    Efficiency,
+}
+
+/// User override of the automatic mode
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PowerOverride {
+    #[default]
+    Auto,
+    Normal,
+    Efficiency,
+}
+
+/// Intervals background work should use in the current mode
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct BackgroundBudget {
+    /// Delay before batched watcher events are flushed (0 = immediately)
+    pub watcher_flush_ms: u64,
+    pub auto_fetch_interval_secs: u64,
+    /// Maximum worker threads indexers should use
+    pub indexer_threads: usize,
+    pub metrics_sample_secs: u64,
+}
+
+impl BackgroundBudget {
+    fn for_mode(mode: PowerMode) -> Self {
+        match mode {
+            PowerMode::Normal => Self {
+                watcher_flush_ms: 0,
+                auto_fetch_interval_secs: 180,
+                indexer_threads: num_cpus::get().max(1),
+                metrics_sample_secs: 5,
+            },
+            PowerMode::Efficiency => Self {
+                watcher_flush_ms: 2000,
+                auto_fetch_interval_secs: 1800,
+                indexer_threads: 1,
+                metrics_sample_secs: 60,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerStatus {
+    pub mode: PowerMode,
+    pub override_mode: PowerOverride,
+    /// None when the battery state can't be determined
+    pub on_battery: Option<bool>,
+    pub windows_active: bool,
+    pub budget: BackgroundBudget,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct WindowActivity {
+    focused: bool,
+    minimized: bool,
+}
+
+#[derive(Default)]
+struct PowerController {
+    windows: HashMap<String, WindowActivity>,
+    on_battery: Option<bool>,
+    override_mode: PowerOverride,
+    battery_poller_started: bool,
+}
+
+impl PowerController {
+    fn windows_active(&self) -> bool {
+        // Before any window reports in, assume the user is working
+        self.windows.is_empty() || self.windows.values().any(|w| w.focused && !w.minimized)
+    }
+
+    fn mode(&self) -> PowerMode {
+        match self.override_mode {
+            PowerOverride::Normal => PowerMode::Normal,
+            PowerOverride::Efficiency => PowerMode::Efficiency,
+            PowerOverride::Auto => {
+                if !self.windows_active() || self.on_battery == Some(true) {
+                    PowerMode::Efficiency
+                } else {
+                    PowerMode::Normal
+                }
+            }
+        }
+    }
+
+    fn status(&self) -> PowerStatus {
+        let mode = self.mode();
+        PowerStatus {
+            mode,
+            override_mode: self.override_mode,
+            on_battery: self.on_battery,
+            windows_active: self.windows_active(),
+            budget: BackgroundBudget::for_mode(mode),
+        }
+    }
+}
+
+static POWER: Lazy<Mutex<PowerController>> = Lazy::new(|| Mutex::new(PowerController::default()));
+
+/// Current power mode
+pub(crate) fn current_mode() -> PowerMode {
+    POWER.lock().map(|p| p.mode()).unwrap_or(PowerMode::Normal)
+}
+
+/// Background budget for the current power mode
+pub(crate) fn current_budget() -> BackgroundBudget {
+    BackgroundBudget::for_mode(current_mode())
+}
+
+/// Apply a change to the controller and announce the new mode if it changed
+fn update(app: &AppHandle, change: impl FnOnce(&mut PowerController)) {
+    let Ok(mut power) = POWER.lock() else {
+        return;
+    };
+    let before = power.mode();
+    change(&mut power);
+    let status = power.status();
+    drop(power);
+
+    if status.mode != before {
+        println!("[PowerManager] Switched to {:?} mode", status.mode);
+        if let Err(e) = app.emit("power:mode-changed", &status) {
+            eprintln!("[PowerManager] Failed to emit mode change: {}", e);
+        }
+    }
+}
+
+/// Query the OS for whether the device is running on battery
+#[cfg(target_os = "linux")]
+fn query_on_battery() -> Option<bool> {
+    let supplies = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let mut has_battery = false;
+    for supply in supplies.flatten() {
+        let path = supply.path();
+        let kind = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+        match kind.trim() {
+            "Mains" | "USB" => {
+                let online = std::fs::read_to_string(path.join("online")).unwrap_or_default();
+                if online.trim() == "1" {
+                    return Some(false);
+                }
+            }
+            "Battery" => has_battery = true,
+            _ => {}
+        }
+    }
+    // A battery with no online adapter means we're discharging; no battery means desktop
+    Some(has_battery)
+}
+
+#[cfg(target_os = "macos")]
+fn query_on_battery() -> Option<bool> {
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    if text.contains("'Battery Power'") {
+        Some(true)
+    } else if text.contains("'AC Power'") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn query_on_battery() -> Option<bool> {
+    // BatteryStatus 1 = discharging; no Win32_Battery instance means a desktop
+    let output = std::process::Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "(Get-CimInstance Win32_Battery | Select-Object -First 1).BatteryStatus",
+        ])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    match text.trim() {
+        "" => Some(false),
+        status => status.parse::<u16>().ok().map(|s| s == 1),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn query_on_battery() -> Option<bool> {
+    None
+}
+
+fn override_from_settings() -> Option<PowerOverride> {
+    crate::configuration_manager::resolve_setting(None, "power.mode")
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+/// Start polling the battery state; called once from setup
+pub fn start(app: &AppHandle) {
+    {
+        let Ok(mut power) = POWER.lock() else {
+            return;
+        };
+        if power.battery_poller_started {
+            return;
+        }
+        power.battery_poller_started = true;
+        if let Some(override_mode) = override_from_settings() {
+            power.override_mode = override_mode;
+        }
+    }
+
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        let on_battery = query_on_battery();
+        update(&app, |power| power.on_battery = on_battery);
+        std::thread::sleep(BATTERY_POLL_INTERVAL);
+    });
+}
+
+/// Track focus/minimize state of every window
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    let label = window.label().to_string();
+    let app = window.app_handle();
+    match event {
+        WindowEvent::Focused(focused) => {
+            let minimized = window.is_minimized().unwrap_or(false);
+            update(app, |power| {
+                power.windows.insert(
+                    label,
+                    WindowActivity {
+                        focused: *focused,
+                        minimized,
+                    },
+                );
+            });
+        }
+        WindowEvent::Resized(_) => {
+            let minimized = window.is_minimized().unwrap_or(false);
+            update(app, |power| {
+                let entry = power.windows.entry(label).or_insert(WindowActivity {
+                    focused: false,
+                    minimized,
+                });
+                entry.minimized = minimized;
+            });
+        }
+        WindowEvent::Destroyed => {
+            update(app, |power| {
+                power.windows.remove(&label);
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Current power mode, its inputs and the resulting background budget
+#[tauri::command]
+pub fn get_power_status() -> Result<PowerStatus, String> {
+    POWER.lock().map(|p| p.status()).map_err(|e| e.to_string())
+}
+
+/// Force normal or efficiency mode, or return to automatic switching
+#[tauri::command]
+pub fn set_power_override(app: AppHandle, mode: PowerOverride) -> Result<PowerStatus, String> {
+    update(&app, |power| power.override_mode = mode);
+    get_power_status()
+}
+
+/// Re-query the battery state immediately
+#[tauri::command]
+pub fn refresh_power_status(app: AppHandle) -> Result<PowerStatus, String> {
+    let on_battery = query_on_battery();
+    update(&app, |power| power.on_battery = on_battery);
+    get_power_status()
+}
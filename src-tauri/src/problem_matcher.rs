@@ -0,0 +1,614 @@
+//! Problem Matchers
+//!
+//! Turns compiler and linter output into structured diagnostics. Matchers
+//! follow the VS Code model: a sequence of line patterns whose capture groups
+//! name the file, position, severity, code and message. The last pattern may
+//! `loop` to match every subsequent line (eslint's stylish format lists many
+//! problems under one file name).
+//!
+//! Built-in matchers cover rustc/cargo, tsc, eslint and gcc/clang; users can
+//! register their own. The engine is fed raw output incrementally, so it
+//! serves both brokered task runs and live terminal output.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Longest partial line kept between chunks
+const MAX_PENDING_LINE: usize = 64 * 1024;
+
+static ANSI_ESCAPE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[()][A-Za-z0-9]")
+        .expect("invalid ANSI pattern")
+});
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ProblemSeverity {
+    #[default]
+    Error,
+    Warning,
+    Info,
+}
+
+impl ProblemSeverity {
+    fn parse(text: &str) -> Option<Self> {
+        match text.to_ascii_lowercase().as_str() {
+            "error" | "fatal" | "fatal error" | "e" => Some(Self::Error),
+            "warning" | "warn" | "w" => Some(Self::Warning),
+            "info" | "note" | "help" | "hint" | "i" => Some(Self::Info),
+            _ => None,
+        }
+    }
+}
+
+/// How relative file names are resolved
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum FileLocation {
+    Absolute,
+    Relative,
+    /// Absolute paths as-is, others relative to the working directory
+    #[default]
+    AutoDetect,
+}
+
+/// One line pattern; fields are capture group indexes (0 = unused)
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProblemPattern {
+    pub regexp: String,
+    pub file: usize,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub severity: usize,
+    pub code: usize,
+    pub message: usize,
+    /// Keep matching this (last) pattern on following lines
+    #[serde(rename = "loop")]
+    pub repeat: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProblemMatcher {
+    /// Referenced as `$name`
+    pub name: String,
+    /// Diagnostics collection the problems belong to (e.g. "rustc")
+    pub owner: String,
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Used when the pattern captures no severity
+    #[serde(default)]
+    pub severity: ProblemSeverity,
+    #[serde(default)]
+    pub file_location: FileLocation,
+    pub patterns: Vec<ProblemPattern>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub owner: String,
+    pub source: Option<String>,
+    pub file: String,
+    /// 1-based; 1 when the tool reports none
+    pub line: u32,
+    pub column: u32,
+    pub end_line: Option<u32>,
+    pub end_column: Option<u32>,
+    pub severity: ProblemSeverity,
+    pub code: Option<String>,
+    pub message: String,
+}
+
+fn pattern(regexp: &str, fields: &[(&str, usize)], repeat: bool) -> ProblemPattern {
+    let mut p = ProblemPattern {
+        regexp: regexp.to_string(),
+        repeat,
+        ..Default::default()
+    };
+    for (field, group) in fields {
+        match *field {
+            "file" => p.file = *group,
+            "line" => p.line = *group,
+            "column" => p.column = *group,
+            "severity" => p.severity = *group,
+            "code" => p.code = *group,
+            "message" => p.message = *group,
+            _ => {}
+        }
+    }
+    p
+}
+
+fn builtin(name: &str, owner: &str, patterns: Vec<ProblemPattern>) -> ProblemMatcher {
+    ProblemMatcher {
+        name: name.to_string(),
+        owner: owner.to_string(),
+        source: Some(owner.to_string()),
+        severity: ProblemSeverity::Error,
+        file_location: FileLocation::AutoDetect,
+        patterns,
+    }
+}
+
+fn builtin_matchers() -> Vec<ProblemMatcher> {
+    vec![
+        // error[E0308]: mismatched types
+        //   --> src/main.rs:4:18
+        builtin(
+            "rustc",
+            "rustc",
+            vec![
+                pattern(
+                    r"^(error|warning)(?:\[(\w+)\])?: (.*)$",
+                    &[("severity", 1), ("code", 2), ("message", 3)],
+                    false,
+                ),
+                pattern(
+                    r"^\s*--> (.*?):(\d+):(\d+)$",
+                    &[("file", 1), ("line", 2), ("column", 3)],
+                    false,
+                ),
+            ],
+        ),
+        // src/a.ts(3,5): error TS2322: ...  or  src/a.ts:3:5 - error TS2322: ...
+        builtin(
+            "tsc",
+            "typescript",
+            vec![pattern(
+                r"^(.+?)[(:](\d+)[,:](\d+)\)?(?::| -) (error|warning|info) (TS\d+)\s*: (.*)$",
+                &[
+                    ("file", 1),
+                    ("line", 2),
+                    ("column", 3),
+                    ("severity", 4),
+                    ("code", 5),
+                    ("message", 6),
+                ],
+                false,
+            )],
+        ),
+        // /path/file.js
+        //   3:5  error  'x' is not defined  no-undef
+        builtin(
+            "eslint-stylish",
+            "eslint",
+            vec![
+                pattern(r"^([^\s].*)$", &[("file", 1)], false),
+                pattern(
+                    r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.*?)(?:\s\s+(\S+))?$",
+                    &[
+                        ("line", 1),
+                        ("column", 2),
+                        ("severity", 3),
+                        ("message", 4),
+                        ("code", 5),
+                    ],
+                    true,
+                ),
+            ],
+        ),
+        builtin(
+            "eslint-compact",
+            "eslint",
+            vec![pattern(
+                r"^(.+):\sline\s(\d+),\scol\s(\d+),\s(Error|Warning|Info)\s-\s(.+)\s\((.+)\)$",
+                &[
+                    ("file", 1),
+                    ("line", 2),
+                    ("column", 3),
+                    ("severity", 4),
+                    ("message", 5),
+                    ("code", 6),
+                ],
+                false,
+            )],
+        ),
+        // main.c:3:5: error: ...
+        builtin(
+            "gcc",
+            "cpp",
+            vec![pattern(
+                r"^(.*?):(\d+):(\d*):?\s+(?:fatal\s+)?(warning|error|note):\s+(.*)$",
+                &[
+                    ("file", 1),
+                    ("line", 2),
+                    ("column", 3),
+                    ("severity", 4),
+                    ("message", 5),
+                ],
+                false,
+            )],
+        ),
+    ]
+}
+
+/// User-registered matchers by name
+static USER_MATCHERS: Lazy<RwLock<HashMap<String, ProblemMatcher>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Look up matchers by name (with or without the leading `$`)
+pub(crate) fn resolve_matchers(names: &[String]) -> Result<Vec<ProblemMatcher>, String> {
+    let builtins = builtin_matchers();
+    let user = USER_MATCHERS.read().map_err(|e| e.to_string())?;
+    names
+        .iter()
+        .map(|name| {
+            let name = name.trim_start_matches('$');
+            user.get(name)
+                .cloned()
+                .or_else(|| builtins.iter().find(|m| m.name == name).cloned())
+                .ok_or_else(|| format!("Unknown problem matcher: ${}", name))
+        })
+        .collect()
+}
+
+/// All built-in matchers, used for problem detection in terminals
+pub(crate) fn default_matchers() -> Vec<ProblemMatcher> {
+    builtin_matchers()
+}
+
+struct CompiledMatcher {
+    matcher: ProblemMatcher,
+    regexes: Vec<Regex>,
+    /// Index of the next pattern to match in a multi-line sequence
+    next: usize,
+    captured: HashMap<&'static str, String>,
+}
+
+impl CompiledMatcher {
+    fn new(matcher: ProblemMatcher) -> Result<Self, String> {
+        if matcher.patterns.is_empty() {
+            return Err(format!(
+                "Problem matcher '{}' has no patterns",
+                matcher.name
+            ));
+        }
+        let regexes = matcher
+            .patterns
+            .iter()
+            .map(|p| {
+                Regex::new(&p.regexp)
+                    .map_err(|e| format!("Invalid pattern in '{}': {}", matcher.name, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            matcher,
+            regexes,
+            next: 0,
+            captured: HashMap::new(),
+        })
+    }
+
+    fn capture(&mut self, index: usize, line: &str) -> bool {
+        let Some(captures) = self.regexes[index].captures(line) else {
+            return false;
+        };
+        let pattern = &self.matcher.patterns[index];
+        for (field, group) in [
+            ("file", pattern.file),
+            ("line", pattern.line),
+            ("column", pattern.column),
+            ("endLine", pattern.end_line),
+            ("endColumn", pattern.end_column),
+            ("severity", pattern.severity),
+            ("code", pattern.code),
+            ("message", pattern.message),
+        ] {
+            if group == 0 {
+                continue;
+            }
+            if let Some(value) = captures.get(group).filter(|m| !m.as_str().is_empty()) {
+                self.captured.insert(field, value.as_str().to_string());
+            } else {
+                self.captured.remove(field);
+            }
+        }
+        true
+    }
+
+    fn diagnostic(&self, cwd: Option<&Path>) -> Option<Diagnostic> {
+        let file = self.captured.get("file")?;
+        let message = self.captured.get("message")?;
+        let number = |field: &str| self.captured.get(field).and_then(|v| v.parse::<u32>().ok());
+        let path = PathBuf::from(file);
+        let resolved = match (self.matcher.file_location, cwd) {
+            (FileLocation::Absolute, _) | (_, None) => path,
+            (FileLocation::AutoDetect, Some(_)) if path.is_absolute() => path,
+            (_, Some(cwd)) => cwd.join(path),
+        };
+        Some(Diagnostic {
+            owner: self.matcher.owner.clone(),
+            source: self.matcher.source.clone(),
+            file: resolved.to_string_lossy().to_string(),
+            line: number("line").unwrap_or(1).max(1),
+            column: number("column").unwrap_or(1).max(1),
+            end_line: number("endLine"),
+            end_column: number("endColumn"),
+            severity: self
+                .captured
+                .get("severity")
+                .and_then(|s| ProblemSeverity::parse(s))
+                .unwrap_or(self.matcher.severity),
+            code: self.captured.get("code").cloned(),
+            message: message.trim().to_string(),
+        })
+    }
+
+    /// Feed one line; returns a diagnostic when a sequence completes
+    fn process(&mut self, line: &str, cwd: Option<&Path>) -> Option<Diagnostic> {
+        let last = self.regexes.len() - 1;
+
+        // A looping last pattern keeps matching until a line doesn't fit
+        if self.next > last {
+            if self.capture(last, line) {
+                return self.diagnostic(cwd);
+            }
+            self.next = 0;
+        }
+
+        if self.next > 0 {
+            if self.capture(self.next, line) {
+                self.next += 1;
+                if self.next > last {
+                    let diagnostic = self.diagnostic(cwd);
+                    if !self.matcher.patterns[last].repeat {
+                        self.next = 0;
+                    }
+                    return diagnostic;
+                }
+                return None;
+            }
+            // Sequence broken: the line may start a new one
+            self.next = 0;
+        }
+
+        self.captured.clear();
+        if !self.capture(0, line) {
+            return None;
+        }
+        if last == 0 {
+            return self.diagnostic(cwd);
+        }
+        self.next = 1;
+        None
+    }
+}
+
+/// Incremental matcher over a stream of output
+pub struct ProblemMatcherEngine {
+    matchers: Vec<CompiledMatcher>,
+    cwd: Option<PathBuf>,
+    pending: String,
+}
+
+impl ProblemMatcherEngine {
+    pub fn new(matchers: Vec<ProblemMatcher>, cwd: Option<PathBuf>) -> Result<Self, String> {
+        Ok(Self {
+            matchers: matchers
+                .into_iter()
+                .map(CompiledMatcher::new)
+                .collect::<Result<_, _>>()?,
+            cwd,
+            pending: String::new(),
+        })
+    }
+
+    fn process_line(&mut self, line: &str) -> Vec<Diagnostic> {
+        let line = ANSI_ESCAPE.replace_all(line, "");
+        // Carriage returns redraw the line; keep what is finally shown
+        let line = line.rsplit('\r').find(|s| !s.is_empty()).unwrap_or("");
+        let cwd = self.cwd.as_deref();
+        self.matchers
+            .iter_mut()
+            .filter_map(|matcher| matcher.process(line, cwd))
+            .collect()
+    }
+
+    /// Feed a chunk of output; complete lines are matched immediately
+    pub fn feed(&mut self, chunk: &str) -> Vec<Diagnostic> {
+        self.pending.push_str(chunk);
+        let mut diagnostics = Vec::new();
+        while let Some(index) = self.pending.find('\n') {
+            let line: String = self.pending.drain(..=index).collect();
+            diagnostics.extend(self.process_line(line.trim_end_matches(['\n', '\r'])));
+        }
+        if self.pending.len() > MAX_PENDING_LINE {
+            self.pending.clear();
+        }
+        diagnostics
+    }
+
+    /// Match the trailing partial line at the end of the stream
+    pub fn finish(&mut self) -> Vec<Diagnostic> {
+        let line = std::mem::take(&mut self.pending);
+        if line.is_empty() {
+            Vec::new()
+        } else {
+            self.process_line(&line)
+        }
+    }
+}
+
+/// Match complete output in one go
+pub(crate) fn match_output(
+    output: &str,
+    matchers: Vec<ProblemMatcher>,
+    cwd: Option<&Path>,
+) -> Result<Vec<Diagnostic>, String> {
+    let mut engine = ProblemMatcherEngine::new(matchers, cwd.map(Path::to_path_buf))?;
+    let mut diagnostics = engine.feed(output);
+    diagnostics.extend(engine.finish());
+    Ok(diagnostics)
+}
+
+/// Built-in and user-registered problem matchers
+#[tauri::command]
+pub fn problem_matchers_list() -> Result<Vec<ProblemMatcher>, String> {
+    let mut matchers = builtin_matchers();
+    let user = USER_MATCHERS.read().map_err(|e| e.to_string())?;
+    matchers.retain(|m| !user.contains_key(&m.name));
+    matchers.extend(user.values().cloned());
+    matchers.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(matchers)
+}
+
+/// Register (or replace) a user-defined matcher; a built-in of the same name is shadowed
+#[tauri::command]
+pub fn problem_matchers_register(matcher: ProblemMatcher) -> Result<(), String> {
+    let mut matcher = matcher;
+    matcher.name = matcher.name.trim_start_matches('$').to_string();
+    if matcher.name.is_empty() {
+        return Err("Problem matcher name cannot be empty".to_string());
+    }
+    // Validate the patterns before accepting the matcher
+    CompiledMatcher::new(matcher.clone())?;
+    USER_MATCHERS
+        .write()
+        .map_err(|e| e.to_string())?
+        .insert(matcher.name.clone(), matcher);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn problem_matchers_unregister(name: String) -> Result<(), String> {
+    USER_MATCHERS
+        .write()
+        .map_err(|e| e.to_string())?
+        .remove(name.trim_start_matches('$'));
+    Ok(())
+}
+
+/// Run matchers (by name; all built-ins when omitted) over captured output
+#[tauri::command]
+pub fn match_problems(
+    output: String,
+    matchers: Option<Vec<String>>,
+    cwd: Option<String>,
+) -> Result<Vec<Diagnostic>, String> {
+    let matchers = match matchers {
+        Some(names) => resolve_matchers(&names)?,
+        None => default_matchers(),
+    };
+    match_output(&output, matchers, cwd.as_deref().map(Path::new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matchers(names: &[&str]) -> Vec<ProblemMatcher> {
+        builtin_matchers()
+            .into_iter()
+            .filter(|m| names.contains(&m.name.as_str()))
+            .collect()
+    }
+
+    fn run(output: &str, names: &[&str]) -> Vec<Diagnostic> {
+        match_output(output, matchers(names), Some(Path::new("/work"))).expect("matches")
+    }
+
+    #[test]
+    fn matches_rustc_two_line_errors() {
+        let output = "error[E0308]: mismatched types\n  --> src/main.rs:4:18\n   |\n";
+        let diagnostics = run(output, &["rustc"]);
+        assert_eq!(diagnostics.len(), 1);
+        let d = &diagnostics[0];
+        assert_eq!(
+            d.file,
+            Path::new("/work").join("src/main.rs").to_string_lossy()
+        );
+        assert_eq!((d.line, d.column), (4, 18));
+        assert_eq!(d.severity, ProblemSeverity::Error);
+        assert_eq!(d.code.as_deref(), Some("E0308"));
+        assert_eq!(d.message, "mismatched types");
+    }
+
+    #[test]
+    fn rustc_warning_without_code() {
+        let output = "warning: unused variable: `x`\n --> src/lib.rs:2:9\n";
+        let d = &run(output, &["rustc"])[0];
+        assert_eq!(d.severity, ProblemSeverity::Warning);
+        assert_eq!(d.code, None);
+    }
+
+    #[test]
+    fn broken_sequence_yields_nothing() {
+        let output = "error: aborting\nsomething else\n  --> src/main.rs:1:1\n";
+        assert!(run(output, &["rustc"]).is_empty());
+    }
+
+    #[test]
+    fn matches_both_tsc_formats() {
+        let output = "src/a.ts(3,5): error TS2322: Type 'x' is not assignable.\n\
+                      src/b.ts:7:1 - warning TS6133: 'y' is declared but never used.\n";
+        let diagnostics = run(output, &["tsc"]);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (3, 5));
+        assert_eq!(diagnostics[0].code.as_deref(), Some("TS2322"));
+        assert_eq!(diagnostics[1].severity, ProblemSeverity::Warning);
+        assert!(diagnostics[1].file.ends_with("b.ts"));
+    }
+
+    #[test]
+    fn eslint_stylish_loops_over_problems_for_one_file() {
+        let output = "/abs/file.js\n  3:5  error  'x' is not defined  no-undef\n  \
+                      9:1  warning  Unexpected console statement  no-console\n\n";
+        let diagnostics = run(output, &["eslint-stylish"]);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|d| d.file == "/abs/file.js"));
+        assert_eq!(diagnostics[0].code.as_deref(), Some("no-undef"));
+        assert_eq!(diagnostics[0].message, "'x' is not defined");
+        assert_eq!(diagnostics[1].line, 9);
+        assert_eq!(diagnostics[1].severity, ProblemSeverity::Warning);
+    }
+
+    #[test]
+    fn gcc_missing_column_defaults_to_one() {
+        let d = &run("main.c:12:: fatal error: stdio.h: No such file\n", &["gcc"])[0];
+        assert_eq!((d.line, d.column), (12, 1));
+        assert_eq!(d.severity, ProblemSeverity::Error);
+        assert_eq!(d.message, "stdio.h: No such file");
+    }
+
+    #[test]
+    fn feed_joins_lines_split_across_chunks_and_strips_ansi() {
+        let mut engine = ProblemMatcherEngine::new(matchers(&["gcc"]), None).expect("compiles");
+        assert!(engine.feed("\x1b[1mmain.c:3:5: \x1b[31mwarn").is_empty());
+        let diagnostics = engine.feed("ing:\x1b[0m unused\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file, "main.c");
+        assert_eq!(diagnostics[0].severity, ProblemSeverity::Warning);
+    }
+
+    #[test]
+    fn finish_matches_trailing_partial_line() {
+        let mut engine = ProblemMatcherEngine::new(matchers(&["gcc"]), None).expect("compiles");
+        assert!(engine.feed("a.c:1:2: error: boom").is_empty());
+        assert_eq!(engine.finish().len(), 1);
+        assert!(engine.finish().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_matchers() {
+        let mut matcher = matchers(&["gcc"]).remove(0);
+        matcher.patterns[0].regexp = "(".to_string();
+        assert!(CompiledMatcher::new(matcher.clone()).is_err());
+        matcher.patterns.clear();
+        assert!(CompiledMatcher::new(matcher).is_err());
+    }
+
+    #[test]
+    fn resolves_names_with_or_without_dollar() {
+        let resolved = resolve_matchers(&["$rustc".to_string(), "tsc".to_string()]).expect("ok");
+        assert_eq!(resolved.len(), 2);
+        assert!(resolve_matchers(&["$nope".to_string()]).is_err());
+    }
+}
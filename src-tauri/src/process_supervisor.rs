@@ -0,0 +1,535 @@
+//! Process Supervisor
+//!
+//! Shared spawn/monitor logic for long-running child processes such as the
+//! agent server sidecar and language servers. A supervised process is spawned
+//! from a `ProcessSpec` (program, args, env, cwd), optionally has its output
+//! captured line by line, is restarted according to its `RestartPolicy`, and
+//! is shut down gracefully (terminate, wait, then kill).
+//!
+//! Lifecycle changes and captured output are reported through an optional
+//! event handler so each manager can forward them to the frontend its own way.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Interval at which the monitor thread polls the child for exit
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Maximum captured output lines kept per process
+const MAX_CAPTURED_LINES: usize = 1000;
+
+/// What to do when a supervised process exits without being asked to
+#[derive(Debug, Clone)]
+pub enum RestartPolicy {
+    Never,
+    /// Restart with exponential backoff, giving up after `max_restarts` attempts.
+    /// A run lasting at least `stable_after` resets the attempt counter.
+    OnFailure {
+        max_restarts: u32,
+        base_delay: Duration,
+        max_delay: Duration,
+        stable_after: Duration,
+    },
+}
+
+/// How a standard stream of the child is handled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StdioMode {
+    /// Read line by line by the supervisor and reported as output events
+    Capture,
+    /// Left to the caller (see `take_stdout` / `take_stderr`)
+    Piped,
+}
+
+/// Health check run on demand against a supervised process
+#[derive(Debug, Clone)]
+pub enum HealthCheck {
+    /// The process is healthy when it accepts TCP connections on this port
+    Tcp { port: u16, timeout: Duration },
+}
+
+/// Description of a process to supervise
+#[derive(Debug, Clone)]
+pub struct ProcessSpec {
+    /// Name used in log messages
+    pub name: String,
+    pub program: String,
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub cwd: Option<PathBuf>,
+    pub stdin_piped: bool,
+    pub stdout: StdioMode,
+    pub stderr: StdioMode,
+    pub restart: RestartPolicy,
+    pub health_check: Option<HealthCheck>,
+    /// Time allowed for a graceful exit before the process is killed
+    pub shutdown_timeout: Duration,
+    /// Run the process in its own process group (Unix), so children it leaves
+    /// behind (e.g. a dev server under a package manager) are signalled with it
+    pub process_group: bool,
+}
+
+impl ProcessSpec {
+    pub fn new(name: &str, program: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            program: program.to_string(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            cwd: None,
+            stdin_piped: false,
+            stdout: StdioMode::Capture,
+            stderr: StdioMode::Capture,
+            restart: RestartPolicy::Never,
+            health_check: None,
+            shutdown_timeout: Duration::from_secs(5),
+            process_group: false,
+        }
+    }
+
+    /// Build a spec from a configured `std::process::Command`
+    pub fn from_command(name: &str, command: &Command) -> Self {
+        let mut spec = Self::new(name, &command.get_program().to_string_lossy());
+        spec.args = command
+            .get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect();
+        spec.env = command
+            .get_envs()
+            .filter_map(|(k, v)| {
+                Some((
+                    k.to_string_lossy().to_string(),
+                    v?.to_string_lossy().to_string(),
+                ))
+            })
+            .collect();
+        spec.cwd = command.get_current_dir().map(|d| d.to_path_buf());
+        spec
+    }
+
+    fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args)
+            .envs(&self.env)
+            .stdin(if self.stdin_piped {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(cwd) = &self.cwd {
+            cmd.current_dir(cwd);
+        }
+        #[cfg(unix)]
+        if self.process_group {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        cmd
+    }
+}
+
+/// Lifecycle and output notifications from a supervised process
+#[derive(Debug, Clone)]
+pub enum ProcessEvent {
+    Started { pid: u32 },
+    Output { stream: &'static str, line: String },
+    Exited { code: Option<i32>, uptime: Duration },
+    Restarting { attempt: u32, delay: Duration },
+    GaveUp { restarts: u32 },
+    Stopped,
+}
+
+pub type EventHandler = Arc<dyn Fn(&ProcessEvent) + Send + Sync>;
+
+/// Captured output line
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CapturedLine {
+    pub stream: String,
+    pub line: String,
+    pub timestamp: i64,
+}
+
+struct Inner {
+    child: Option<Child>,
+    pid: Option<u32>,
+    started_at: Option<Instant>,
+    /// Restart attempts since the last stable run
+    restarts: u32,
+    total_exits: u32,
+    last_exit_code: Option<i32>,
+    stop_requested: bool,
+    stdin: Option<ChildStdin>,
+    stdout: Option<ChildStdout>,
+    stderr: Option<ChildStderr>,
+}
+
+/// A running process under supervision
+pub struct SupervisedProcess {
+    spec: ProcessSpec,
+    inner: Mutex<Inner>,
+    output: Mutex<VecDeque<CapturedLine>>,
+    handler: Option<EventHandler>,
+}
+
+impl std::fmt::Debug for SupervisedProcess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SupervisedProcess")
+            .field("name", &self.spec.name)
+            .field("pid", &self.pid())
+            .finish()
+    }
+}
+
+impl SupervisedProcess {
+    /// Spawn a process and start supervising it
+    pub fn spawn(
+        spec: ProcessSpec,
+        handler: Option<EventHandler>,
+    ) -> Result<Arc<SupervisedProcess>, std::io::Error> {
+        let process = Arc::new(SupervisedProcess {
+            spec,
+            inner: Mutex::new(Inner {
+                child: None,
+                pid: None,
+                started_at: None,
+                restarts: 0,
+                total_exits: 0,
+                last_exit_code: None,
+                stop_requested: false,
+                stdin: None,
+                stdout: None,
+                stderr: None,
+            }),
+            output: Mutex::new(VecDeque::new()),
+            handler,
+        });
+
+        process.start_child()?;
+
+        let monitor = Arc::clone(&process);
+        thread::spawn(move || monitor.monitor());
+
+        Ok(process)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        match self.inner.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn emit(&self, event: ProcessEvent) {
+        if let Some(handler) = &self.handler {
+            handler(&event);
+        }
+    }
+
+    fn record_output(&self, stream: &'static str, line: String) {
+        if let Ok(mut output) = self.output.lock() {
+            if output.len() >= MAX_CAPTURED_LINES {
+                output.pop_front();
+            }
+            output.push_back(CapturedLine {
+                stream: stream.to_string(),
+                line: line.clone(),
+                timestamp: chrono::Utc::now().timestamp_millis(),
+            });
+        }
+        self.emit(ProcessEvent::Output { stream, line });
+    }
+
+    fn start_child(self: &Arc<Self>) -> Result<(), std::io::Error> {
+        let mut child = self.spec.command().spawn()?;
+        let pid = child.id();
+
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        {
+            let mut inner = self.lock();
+            inner.stdin = child.stdin.take();
+            inner.stdout = None;
+            inner.stderr = None;
+
+            match (self.spec.stdout, stdout) {
+                (StdioMode::Capture, Some(stream)) => self.capture(stream, "stdout"),
+                (StdioMode::Piped, stream) => inner.stdout = stream,
+                (StdioMode::Capture, None) => {}
+            }
+            match (self.spec.stderr, stderr) {
+                (StdioMode::Capture, Some(stream)) => self.capture(stream, "stderr"),
+                (StdioMode::Piped, stream) => inner.stderr = stream,
+                (StdioMode::Capture, None) => {}
+            }
+
+            inner.child = Some(child);
+            inner.pid = Some(pid);
+            inner.started_at = Some(Instant::now());
+        }
+
+        println!("[Supervisor] Started {} (pid {})", self.spec.name, pid);
+        self.emit(ProcessEvent::Started { pid });
+        Ok(())
+    }
+
+    fn capture<R: Read + Send + 'static>(self: &Arc<Self>, stream: R, name: &'static str) {
+        let process = Arc::clone(self);
+        thread::spawn(move || {
+            let reader = BufReader::new(stream);
+            for line in reader.lines() {
+                match line {
+                    Ok(line) => process.record_output(name, line),
+                    Err(_) => break,
+                }
+            }
+        });
+    }
+
+    /// Wait for exits and apply the restart policy
+    fn monitor(self: Arc<Self>) {
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let (code, uptime, pid) = {
+                let mut inner = self.lock();
+                if inner.stop_requested {
+                    return;
+                }
+                let Some(child) = inner.child.as_mut() else {
+                    return;
+                };
+                let status = match child.try_wait() {
+                    Ok(Some(status)) => status,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        eprintln!("[Supervisor] Failed to poll {}: {}", self.spec.name, e);
+                        continue;
+                    }
+                };
+
+                let uptime = inner.started_at.map(|s| s.elapsed()).unwrap_or_default();
+                let pid = inner.pid.take();
+                inner.child = None;
+                inner.total_exits += 1;
+                inner.last_exit_code = status.code();
+                (status.code(), uptime, pid)
+            };
+
+            eprintln!(
+                "[Supervisor] {} exited with code {:?} after {}s",
+                self.spec.name,
+                code,
+                uptime.as_secs()
+            );
+            self.emit(ProcessEvent::Exited { code, uptime });
+
+            let RestartPolicy::OnFailure {
+                max_restarts,
+                base_delay,
+                max_delay,
+                stable_after,
+            } = self.spec.restart.clone()
+            else {
+                return;
+            };
+
+            let attempt = {
+                let mut inner = self.lock();
+                if uptime >= stable_after {
+                    inner.restarts = 0;
+                }
+                if inner.restarts >= max_restarts {
+                    None
+                } else {
+                    inner.restarts += 1;
+                    Some(inner.restarts)
+                }
+            };
+
+            let Some(attempt) = attempt else {
+                eprintln!(
+                    "[Supervisor] Giving up on {} after {} restarts",
+                    self.spec.name, max_restarts
+                );
+                self.emit(ProcessEvent::GaveUp {
+                    restarts: max_restarts,
+                });
+                return;
+            };
+
+            let delay = base_delay
+                .saturating_mul(1 << (attempt - 1).min(16))
+                .min(max_delay);
+            self.emit(ProcessEvent::Restarting { attempt, delay });
+
+            if let Some(pid) = pid {
+                self.release(pid);
+            }
+
+            // Sleep in small steps so a shutdown during backoff is honoured
+            let deadline = Instant::now() + delay;
+            while Instant::now() < deadline {
+                if self.lock().stop_requested {
+                    return;
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+
+            if let Err(e) = self.start_child() {
+                eprintln!("[Supervisor] Failed to restart {}: {}", self.spec.name, e);
+                self.emit(ProcessEvent::GaveUp { restarts: attempt });
+                return;
+            }
+        }
+    }
+
+    /// Send a signal to the process, or to its whole group when it has one
+    #[cfg(unix)]
+    fn signal(&self, pid: u32, signal: libc::c_int) {
+        let target = if self.spec.process_group {
+            -(pid as libc::pid_t)
+        } else {
+            pid as libc::pid_t
+        };
+        unsafe {
+            libc::kill(target, signal);
+        }
+    }
+
+    /// Free what an exited run still holds before it is restarted: kill the
+    /// children left in its process group, then wait (up to the shutdown
+    /// timeout) for the health-check port to be released, so the new run can
+    /// bind it.
+    fn release(&self, pid: u32) {
+        #[cfg(unix)]
+        if self.spec.process_group {
+            self.signal(pid, libc::SIGKILL);
+        }
+        #[cfg(not(unix))]
+        let _ = pid;
+
+        let Some(HealthCheck::Tcp { port, .. }) = &self.spec.health_check else {
+            return;
+        };
+        let start = Instant::now();
+        while std::net::TcpListener::bind(("127.0.0.1", *port)).is_err() {
+            if start.elapsed() >= self.spec.shutdown_timeout {
+                eprintln!(
+                    "[Supervisor] Port {} is still in use after {} exited",
+                    port, self.spec.name
+                );
+                return;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Stop the process: ask it to terminate, wait for the shutdown timeout, then kill
+    pub fn shutdown(&self) {
+        let child = {
+            let mut inner = self.lock();
+            inner.stop_requested = true;
+            inner.stdin = None;
+            inner.pid = None;
+            inner.child.take()
+        };
+
+        if let Some(mut child) = child {
+            #[cfg(unix)]
+            self.signal(child.id(), libc::SIGTERM);
+
+            let start = Instant::now();
+            while start.elapsed() < self.spec.shutdown_timeout {
+                if let Ok(Some(_)) = child.try_wait() {
+                    break;
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+
+            // Force kill if still running
+            #[cfg(unix)]
+            if self.spec.process_group {
+                self.signal(child.id(), libc::SIGKILL);
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+
+        println!("[Supervisor] Stopped {}", self.spec.name);
+        self.emit(ProcessEvent::Stopped);
+    }
+
+    /// Take the child's stdin (only available when `stdin_piped` is set)
+    pub fn take_stdin(&self) -> Option<ChildStdin> {
+        self.lock().stdin.take()
+    }
+
+    /// Take the child's stdout (only available in `StdioMode::Piped`)
+    pub fn take_stdout(&self) -> Option<ChildStdout> {
+        self.lock().stdout.take()
+    }
+
+    /// Take the child's stderr (only available in `StdioMode::Piped`)
+    pub fn take_stderr(&self) -> Option<ChildStderr> {
+        self.lock().stderr.take()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.lock().child.is_some()
+    }
+
+    pub fn pid(&self) -> Option<u32> {
+        self.lock().pid
+    }
+
+    pub fn uptime(&self) -> Option<Duration> {
+        let inner = self.lock();
+        inner.child.as_ref()?;
+        inner.started_at.map(|s| s.elapsed())
+    }
+
+    /// Restart attempts since the last stable run
+    pub fn restarts(&self) -> u32 {
+        self.lock().restarts
+    }
+
+    pub fn total_exits(&self) -> u32 {
+        self.lock().total_exits
+    }
+
+    pub fn last_exit_code(&self) -> Option<i32> {
+        self.lock().last_exit_code
+    }
+
+    /// Most recent captured output lines, oldest first
+    pub fn recent_output(&self, limit: usize) -> Vec<CapturedLine> {
+        self.output
+            .lock()
+            .map(|output| {
+                let skip = output.len().saturating_sub(limit);
+                output.iter().skip(skip).cloned().collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Run the configured health check (None when no check is configured)
+    pub fn check_health(&self) -> Option<bool> {
+        match self.spec.health_check.as_ref()? {
+            HealthCheck::Tcp { port, timeout } => Some(
+                std::net::TcpStream::connect_timeout(
+                    &std::net::SocketAddr::from(([127, 0, 0, 1], *port)),
+                    *timeout,
+                )
+                .is_ok(),
+            ),
+        }
+    }
+}
@@ -1,21 +1,33 @@
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use tauri::Emitter;
 use tauri::State;
+use crate::language_server_manager::LanguageServerManager;
 use tokio::fs as async_fs;
-use ignore::gitignore::{Gitignore, GitignoreBuilder};
-
-// Helper function to create a gitignore matcher for a given directory
-fn create_gitignore_matcher(path: &Path) -> Gitignore {
-    let mut builder = GitignoreBuilder::new(path);
-    builder.add(".gitignore"); // Look for .gitignore in the given path
-    // Also include global gitignore if desired, though usually project-specific is enough
-    builder.build().unwrap_or_else(|_| Gitignore::empty())
-}
+use ignore::gitignore::GitignoreBuilder;
+
+pub mod batch_ops; // Multi-item file operations with rollback
+pub mod binary_content; // Raw file bytes for image previews and the hex view
+pub mod command_exec; // Shell commands with streamed output and cancellation
+pub mod copy_ops; // Copy and duplicate with conflict strategies
+pub mod dir_stats; // Folder size and disk usage breakdown
+pub mod file_hash; // Content checksums for dirty detection
+pub mod file_index; // In-memory file list and fuzzy matching for Quick Open
+mod import_updates; // Import path rewriting when files move
+pub mod large_file; // Ranged and line-based reads for files too big to load whole
+pub mod semantic_index; // Embedded code chunks for searching by meaning
+pub mod structure_stream; // Recursive file tree loading in event batches
+pub mod trash; // Recoverable deletes through the system trash
+pub mod vfs; // Pluggable remote file system providers
+pub mod workspace_paths; // Absolute <-> workspace-relative path conversion
+mod watch_events; // Debounced, typed watcher events
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FileNode {
@@ -27,6 +39,14 @@ pub struct FileNode {
     modified: Option<u64>,
     // New field to indicate if children are loaded
     children_loaded: bool,
+    #[serde(default)]
+    is_symlink: bool,
+    // Where the link points, as written in the link
+    #[serde(default)]
+    symlink_target: Option<String>,
+    // Link to a folder containing the link itself; never expanded
+    #[serde(default)]
+    symlink_loop: bool,
 }
 
 // Directories and files to ignore during scanning (hardcoded)
@@ -57,43 +77,86 @@ fn is_hardcoded_ignored(name: &str) -> bool {
     )
 }
 
-// Check if a path should be ignored, using gitignore rules and hardcoded ignores
-fn should_ignore(matcher: &Gitignore, path: &Path, is_directory: bool) -> bool {
-    let file_name_str = path
-        .file_name()
-        .map(|s| s.to_string_lossy().to_string())
-        .unwrap_or_default();
-
-    if is_hardcoded_ignored(&file_name_str) {
-        return true;
+// Walker honoring .gitignore (including those of parent folders), .ignore,
+// .rainyignore, .git/info/exclude and the global excludes file, plus the
+// hardcoded ignores. With `include_ignored` every entry is walked except the
+// .git folder itself.
+fn ignore_walker(dir: &Path, include_ignored: bool) -> ignore::WalkBuilder {
+    let mut builder = ignore::WalkBuilder::new(dir);
+    if !include_ignored {
+        builder.add_custom_ignore_filename(".rainyignore");
     }
+    builder
+        .hidden(false)
+        .parents(!include_ignored)
+        .ignore(!include_ignored)
+        .git_ignore(!include_ignored)
+        .git_global(!include_ignored)
+        .git_exclude(!include_ignored)
+        .require_git(false)
+        .filter_entry(move |entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0
+                || if include_ignored {
+                    name != ".git"
+                } else {
+                    !is_hardcoded_ignored(&name)
+                }
+        });
+    builder
+}
 
-    // Check against gitignore rules
-    // `matched` returns a `Match` enum. `is_ignore()` tells us if it was ignored.
-    // We pass `is_directory` to `matched` so it can correctly handle directory-specific patterns.
-    matcher.matched(path, is_directory).is_ignore()
+// Immediate entries of a directory that are not ignored
+fn list_unignored_entries(dir: &Path, include_ignored: bool) -> Vec<PathBuf> {
+    ignore_walker(dir, include_ignored)
+        .max_depth(Some(1))
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.depth() == 1)
+        .map(|entry| entry.into_path())
+        .collect()
 }
 
 
+// Whether a symlink points to a folder containing the link, so following it
+// would expand forever
+fn is_symlink_loop(path: &Path) -> bool {
+    let (Ok(target), Some(parent)) = (fs::canonicalize(path), path.parent()) else {
+        return false;
+    };
+    fs::canonicalize(parent)
+        .map(|parent| parent.starts_with(&target))
+        .unwrap_or(false)
+}
+
 // Read directory with depth limit and ignore patterns (NON-RECURSIVE for top level)
 fn read_directory_shallow(
     path: &Path,
     max_depth: usize,
     current_depth: usize,
-    matcher: &Gitignore,
+    include_ignored: bool,
 ) -> Result<FileNode, String> {
-    let metadata = fs::metadata(path).map_err(|e| e.to_string())?;
+    let link_metadata = fs::symlink_metadata(path).map_err(|e| e.to_string())?;
+    let is_symlink = link_metadata.file_type().is_symlink();
+    let symlink_target = if is_symlink {
+        fs::read_link(path)
+            .ok()
+            .map(|target| target.to_string_lossy().to_string())
+    } else {
+        None
+    };
+    // Broken links are listed as files
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) if is_symlink => link_metadata,
+        Err(e) => return Err(e.to_string()),
+    };
     let name = path
         .file_name()
         .unwrap_or_default()
         .to_string_lossy()
         .to_string();
 
-    // Check if this directory should be ignored
-    if should_ignore(matcher, path, metadata.is_dir()) && current_depth > 0 {
-        return Err("Ignored directory".to_string());
-    }
-
     let modified_time = metadata
         .modified()
         .ok()
@@ -101,18 +164,16 @@ fn read_directory_shallow(
         .map(|d| d.as_secs());
 
     if metadata.is_dir() {
+        let symlink_loop = is_symlink && is_symlink_loop(path);
         // For directories, only load immediate children if within depth limit
-        let children = if current_depth < max_depth {
-            let mut child_nodes: Vec<FileNode> = fs::read_dir(path)
-                .map_err(|e| e.to_string())?
-                .filter_map(|entry| entry.ok())
-                .filter_map(|entry| {
-                    let entry_path = entry.path();
-                    // Skip ignored entries using the new should_ignore
-                    if should_ignore(matcher, &entry_path, entry_path.is_dir()) {
-                        return None;
-                    }
-                    read_directory_shallow(&entry_path, max_depth, current_depth + 1, matcher).ok()
+        let children = if symlink_loop {
+            Some(Vec::new())
+        } else if current_depth < max_depth {
+            let mut child_nodes: Vec<FileNode> = list_unignored_entries(path, include_ignored)
+                .iter()
+                .filter_map(|entry_path| {
+                    read_directory_shallow(entry_path, max_depth, current_depth + 1, include_ignored)
+                        .ok()
                 })
                 .collect();
 
@@ -135,7 +196,10 @@ fn read_directory_shallow(
             children,
             size: Some(metadata.len()),
             modified: modified_time,
-            children_loaded: current_depth < max_depth,
+            children_loaded: symlink_loop || current_depth < max_depth,
+            is_symlink,
+            symlink_target,
+            symlink_loop,
         })
     } else {
         Ok(FileNode {
@@ -146,12 +210,347 @@ fn read_directory_shallow(
             size: Some(metadata.len()),
             modified: modified_time,
             children_loaded: false,
+            is_symlink,
+            symlink_target,
+            symlink_loop: false,
         })
     }
 }
 
+/// A window's project watcher
+pub struct ProjectWatcher {
+    root: PathBuf,
+    _watcher: RecommendedWatcher,
+    /// Held by the shared flusher through weak references, so dropping the
+    /// watcher also unregisters it
+    _pending: Arc<Mutex<watch_events::PendingChanges>>,
+    /// Nested `watcher_pause` calls; events are held while above zero
+    pauses: Arc<std::sync::atomic::AtomicUsize>,
+}
+
+/// Project watchers by window label, so each window watches its own workspace
+#[derive(Default)]
 pub struct WatcherState {
-    pub watcher: Arc<Mutex<Option<RecommendedWatcher>>>,
+    pub watchers: Arc<Mutex<HashMap<String, ProjectWatcher>>>,
+}
+
+/// Stop watching for a window that has been closed
+pub fn handle_window_event(window: &tauri::Window, event: &tauri::WindowEvent) {
+    use tauri::Manager;
+    if let tauri::WindowEvent::Destroyed = event {
+        let state = window.state::<WatcherState>();
+        if let Ok(mut watchers) = state.watchers.lock() {
+            watchers.remove(window.label());
+        };
+    }
+}
+
+/// Maximum number of watcher events remembered for the recent changes feed
+const MAX_RECENT_EVENTS: usize = 5000;
+
+/// Last watcher event time (unix seconds) per path, indexed by time so the
+/// oldest entries can be evicted without sorting
+#[derive(Default)]
+struct RecentEvents {
+    by_path: HashMap<PathBuf, i64>,
+    by_time: BTreeSet<(i64, PathBuf)>,
+}
+
+/// Watcher events for the recent changes feed
+#[derive(Default)]
+pub struct RecentChangesState {
+    events: Arc<Mutex<RecentEvents>>,
+}
+
+fn record_recent_changes(events: &Mutex<RecentEvents>, paths: &[&PathBuf]) {
+    let Ok(mut events) = events.lock() else {
+        return;
+    };
+    let events = &mut *events;
+    let now = chrono::Utc::now().timestamp();
+    for path in paths {
+        if let Some(previous) = events.by_path.insert((*path).clone(), now) {
+            events.by_time.remove(&(previous, (*path).clone()));
+        }
+        events.by_time.insert((now, (*path).clone()));
+    }
+
+    // Drop the oldest entries once over the cap
+    while events.by_path.len() > MAX_RECENT_EVENTS {
+        let Some((_, path)) = events.by_time.pop_first() else {
+            break;
+        };
+        events.by_path.remove(&path);
+    }
+}
+
+/// Files larger than this are tracked by hash only (no diff summary)
+const OPEN_FILE_CONTENT_LIMIT: u64 = 2 * 1024 * 1024;
+
+/// On-disk version of a file the editor has open
+#[derive(Debug, Clone)]
+struct OpenFileSnapshot {
+    /// Path the editor opened the file by, reported in change events
+    opened_as: String,
+    modified: u64,
+    hash: String,
+    content: Option<String>,
+    /// Hash of the last external version we already notified about
+    notified_hash: Option<String>,
+}
+
+impl OpenFileSnapshot {
+    /// Re-read the file from disk, keeping the path it was opened by
+    fn reload(&mut self, path: &Path) {
+        if let Ok(snapshot) = snapshot_file(path) {
+            *self = OpenFileSnapshot {
+                opened_as: std::mem::take(&mut self.opened_as),
+                ..snapshot
+            };
+        }
+    }
+}
+
+/// Files declared open by the frontend, used for external change detection
+#[derive(Default)]
+pub struct OpenFilesState {
+    files: Arc<Mutex<HashMap<PathBuf, OpenFileSnapshot>>>,
+}
+
+/// Key of a tracked file: its canonical path, so one file opened through
+/// different paths (symlinks, `..`, relative) is tracked once. A file that
+/// no longer exists is keyed by its canonical parent directory.
+fn open_file_key(path: &Path) -> PathBuf {
+    if let Ok(canonical) = fs::canonicalize(path) {
+        return canonical;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => fs::canonicalize(parent)
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
+impl OpenFilesState {
+    /// Re-read a tracked file after the app itself changed it on disk,
+    /// so the change is not reported as external
+    pub(crate) fn refresh(&self, path: &Path) {
+        let Ok(mut files) = self.files.lock() else {
+            return;
+        };
+        if let Some(tracked) = files.get_mut(&open_file_key(path)) {
+            tracked.reload(path);
+        }
+    }
+}
+
+/// Tracked file information returned to the frontend
+#[derive(Serialize, Debug, Clone)]
+pub struct OpenFileInfo {
+    pub path: String,
+    pub modified: u64,
+    pub hash: String,
+}
+
+/// Payload of the file-changed-externally event
+#[derive(Serialize, Debug, Clone)]
+pub struct ExternalChangeEvent {
+    pub path: String,
+    pub deleted: bool,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    pub first_changed_line: Option<usize>,
+}
+
+fn hash_bytes(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
+}
+
+fn modified_millis(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Write a file through a temporary sibling and a rename, so a crash mid-save
+/// leaves either the old or the new content, never a truncated file.
+/// Symlinks are written through, and an existing file keeps its permissions.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    use std::io::Write;
+
+    let target = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => {
+            fs::canonicalize(path).map_err(|e| e.to_string())?
+        }
+        _ => path.to_path_buf(),
+    };
+    let permissions = fs::metadata(&target).ok().map(|m| m.permissions());
+    let dir = target.parent().unwrap_or_else(|| Path::new("."));
+    let name = target
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    let mut attempt = 0;
+    let (temp_path, mut file) = loop {
+        let candidate = dir.join(format!(".{}.{}-{}.tmp", name, std::process::id(), attempt));
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&candidate)
+        {
+            Ok(file) => break (candidate, file),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && attempt < 100 => {
+                attempt += 1;
+            }
+            Err(e) => return Err(format!("Failed to create temporary file: {}", e)),
+        }
+    };
+
+    let mut written = file.write_all(bytes).and_then(|_| file.sync_all());
+    drop(file);
+    if let (Ok(()), Some(permissions)) = (&written, permissions) {
+        written = fs::set_permissions(&temp_path, permissions);
+    }
+    if written.is_ok() {
+        written = fs::rename(&temp_path, &target);
+    }
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("Failed to save {}: {}", path.display(), e));
+    }
+    Ok(())
+}
+
+/// Read the current on-disk version of a file
+fn snapshot_file(path: &Path) -> Result<OpenFileSnapshot, String> {
+    let metadata = fs::metadata(path).map_err(|e| e.to_string())?;
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let content = if metadata.len() <= OPEN_FILE_CONTENT_LIMIT {
+        String::from_utf8(bytes.clone()).ok()
+    } else {
+        None
+    };
+
+    Ok(OpenFileSnapshot {
+        opened_as: path.to_string_lossy().to_string(),
+        modified: modified_millis(&metadata),
+        hash: hash_bytes(&bytes),
+        content,
+        notified_hash: None,
+    })
+}
+
+/// Summarize a change by trimming the common leading and trailing lines
+fn summarize_line_changes(old: &str, new: &str) -> (usize, usize, Option<usize>) {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    let prefix = old_lines
+        .iter()
+        .zip(new_lines.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let removed = old_lines.len() - prefix - suffix;
+    let added = new_lines.len() - prefix - suffix;
+    let first_changed = if added == 0 && removed == 0 {
+        None
+    } else {
+        Some(prefix + 1)
+    };
+
+    (added, removed, first_changed)
+}
+
+/// Check a tracked file after a watcher event, returning an event if it changed externally
+fn detect_external_change(
+    files: &Mutex<HashMap<PathBuf, OpenFileSnapshot>>,
+    path: &Path,
+) -> Option<ExternalChangeEvent> {
+    let mut files = files.lock().ok()?;
+    let tracked = files.get_mut(&open_file_key(path))?;
+
+    if !path.exists() {
+        if tracked.notified_hash.as_deref() == Some("") {
+            return None;
+        }
+        tracked.notified_hash = Some(String::new());
+        return Some(ExternalChangeEvent {
+            path: tracked.opened_as.clone(),
+            deleted: true,
+            lines_added: 0,
+            lines_removed: 0,
+            first_changed_line: None,
+        });
+    }
+
+    let current = snapshot_file(path).ok()?;
+    if current.hash == tracked.hash || tracked.notified_hash.as_ref() == Some(&current.hash) {
+        return None;
+    }
+    tracked.notified_hash = Some(current.hash.clone());
+
+    let (lines_added, lines_removed, first_changed_line) =
+        match (&tracked.content, &current.content) {
+            (Some(old), Some(new)) => summarize_line_changes(old, new),
+            _ => (0, 0, None),
+        };
+
+    Some(ExternalChangeEvent {
+        path: tracked.opened_as.clone(),
+        deleted: false,
+        lines_added,
+        lines_removed,
+        first_changed_line,
+    })
+}
+
+/// Start tracking a file the editor has opened
+#[tauri::command]
+pub fn track_open_file(path: String, state: State<'_, OpenFilesState>) -> Result<OpenFileInfo, String> {
+    let file_path = open_file_key(Path::new(&path));
+    let snapshot = OpenFileSnapshot {
+        opened_as: path.clone(),
+        ..snapshot_file(&file_path)?
+    };
+    let info = OpenFileInfo {
+        path,
+        modified: snapshot.modified,
+        hash: snapshot.hash.clone(),
+    };
+
+    state
+        .files
+        .lock()
+        .map_err(|e| format!("Failed to acquire open files lock: {}", e))?
+        .insert(file_path, snapshot);
+
+    Ok(info)
+}
+
+/// Stop tracking a file the editor has closed
+#[tauri::command]
+pub fn untrack_open_file(path: String, state: State<'_, OpenFilesState>) -> Result<(), String> {
+    state
+        .files
+        .lock()
+        .map_err(|e| format!("Failed to acquire open files lock: {}", e))?
+        .remove(&open_file_key(Path::new(&path)));
+    Ok(())
 }
 
 #[tauri::command]
@@ -168,37 +567,38 @@ pub fn open_project_dialog() {
 }
 
 #[tauri::command]
-pub async fn load_project_structure(path: String) -> Result<FileNode, String> {
+pub async fn load_project_structure(
+    path: String,
+    include_ignored: Option<bool>,
+) -> Result<FileNode, String> {
     let dir_path = PathBuf::from(&path);
-    let matcher = create_gitignore_matcher(&dir_path);
     // Load only 1 level deep initially for maximum performance
     // Frontend can request more levels on-demand by expanding folders
-    read_directory_shallow(&dir_path, 1, 0, &matcher)
+    read_directory_shallow(&dir_path, 1, 0, include_ignored.unwrap_or(false))
 }
 
 // New command to load children of a specific directory on-demand
 #[tauri::command]
-pub async fn load_directory_children(path: String) -> Result<Vec<FileNode>, String> {
+pub async fn load_directory_children(
+    path: String,
+    include_ignored: Option<bool>,
+) -> Result<Vec<FileNode>, String> {
     let dir_path = PathBuf::from(&path);
     let metadata = fs::metadata(&dir_path).map_err(|e| e.to_string())?;
 
     if !metadata.is_dir() {
         return Err("Path is not a directory".to_string());
     }
+    if is_symlink_loop(&dir_path) {
+        return Ok(Vec::new());
+    }
 
-    let matcher = create_gitignore_matcher(&dir_path); // Create matcher for the current directory
-
-    let mut children: Vec<FileNode> = fs::read_dir(&dir_path)
-        .map_err(|e| e.to_string())?
-        .filter_map(|entry| entry.ok())
-        .filter_map(|entry| {
-            let entry_path = entry.path();
-            // Use the new should_ignore with the matcher
-            if should_ignore(&matcher, &entry_path, entry_path.is_dir()) {
-                return None;
-            }
-            // Load only immediate children (depth 1) and pass the matcher
-            read_directory_shallow(&entry_path, 1, 0, &matcher).ok()
+    let include_ignored = include_ignored.unwrap_or(false);
+    let mut children: Vec<FileNode> = list_unignored_entries(&dir_path, include_ignored)
+        .iter()
+        .filter_map(|entry_path| {
+            // Load only immediate children (depth 1)
+            read_directory_shallow(entry_path, 1, 0, include_ignored).ok()
         })
         .collect();
 
@@ -212,16 +612,73 @@ pub async fn load_directory_children(path: String) -> Result<Vec<FileNode>, Stri
     Ok(children)
 }
 
+/// Where a symlink leads
+#[derive(Serialize, Debug, Clone)]
+pub struct SymlinkInfo {
+    pub path: String,
+    pub is_symlink: bool,
+    /// The target as written in the link
+    pub target: Option<String>,
+    /// Fully resolved path; None when the link is broken
+    pub resolved_path: Option<String>,
+    pub is_directory: bool,
+    pub broken: bool,
+    /// Points to a folder containing the link itself
+    pub is_loop: bool,
+}
+
+#[tauri::command]
+pub async fn resolve_symlink(path: String) -> Result<SymlinkInfo, String> {
+    let link = PathBuf::from(&path);
+    let link_metadata = fs::symlink_metadata(&link).map_err(|e| e.to_string())?;
+    let is_symlink = link_metadata.file_type().is_symlink();
+    let resolved = fs::canonicalize(&link).ok();
+    Ok(SymlinkInfo {
+        is_symlink,
+        target: if is_symlink {
+            fs::read_link(&link)
+                .ok()
+                .map(|target| target.to_string_lossy().to_string())
+        } else {
+            None
+        },
+        is_directory: resolved.as_ref().is_some_and(|p| p.is_dir()),
+        broken: resolved.is_none(),
+        is_loop: is_symlink && is_symlink_loop(&link),
+        resolved_path: resolved.map(|p| p.to_string_lossy().to_string()),
+        path,
+    })
+}
+
+/// A file's text and the last-modified time (ms) it was read at, the value
+/// `save_file_content` expects back as `expected_modified`
+#[derive(Serialize, Debug, Clone)]
+pub struct FileContent {
+    pub content: String,
+    pub modified: u64,
+}
+
+/// Result of `save_file_content`
+#[derive(Serialize, Debug, Clone)]
+pub struct SaveFileResult {
+    /// Last-modified time (ms) of the written file, for the next save
+    pub modified: u64,
+    pub report: Option<SavePipelineReport>,
+}
+
 #[tauri::command]
-pub async fn get_file_content(path: String) -> Result<String, String> {
+pub async fn get_file_content(path: String) -> Result<FileContent, String> {
     use std::io::Read;
 
     let file_path = PathBuf::from(&path);
-    let metadata = fs::metadata(&file_path).map_err(|e| e.to_string())?;
+    // Read the time from the handle before the content: a write racing the
+    // read then shows up as a newer time on the next save
+    let mut file = fs::File::open(&file_path).map_err(|e| e.to_string())?;
+    let metadata = file.metadata().map_err(|e| e.to_string())?;
+    let modified = modified_millis(&metadata);
 
     // If file is larger than 5MB, load only the first 100KB as a preview
     if metadata.len() > 5 * 1024 * 1024 {
-        let file = fs::File::open(&file_path).map_err(|e| e.to_string())?;
         let reader = std::io::BufReader::new(file);
         let mut buffer = String::new();
 
@@ -237,14 +694,31 @@ pub async fn get_file_content(path: String) -> Result<String, String> {
         buffer.push_str("   Only the first 100 KB are shown above.\n");
         buffer.push_str("   ======================================== */");
 
-        return Ok(buffer);
+        return Ok(FileContent {
+            content: buffer,
+            modified,
+        });
     }
 
-    fs::read_to_string(&file_path).map_err(|e| e.to_string())
+    let mut content = String::new();
+    file.read_to_string(&mut content).map_err(|e| e.to_string())?;
+    Ok(FileContent { content, modified })
 }
 
+/// Save a file atomically. `expected_modified` is the last-modified time (ms)
+/// the editor last saw; if the file on disk differs the save is refused with
+/// a "modified on disk" error unless `force` is set. Returns the new
+/// last-modified time to send with the next save.
 #[tauri::command]
-pub async fn save_file_content(path: String, content: String) -> Result<(), String> {
+pub async fn save_file_content(
+    path: String,
+    content: String,
+    pipeline: Option<Vec<SaveAction>>,
+    force: Option<bool>,
+    expected_modified: Option<u64>,
+    open_files: State<'_, OpenFilesState>,
+    lsp: State<'_, LanguageServerManager>,
+) -> Result<SaveFileResult, String> {
     let p = PathBuf::from(&path);
     // Asegurar que el directorio padre exista
     if let Some(parent) = p.parent() {
@@ -252,7 +726,341 @@ pub async fn save_file_content(path: String, content: String) -> Result<(), Stri
             fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
     }
-    fs::write(&p, content).map_err(|e| e.to_string())
+
+    // Refuse to overwrite a version changed on disk since it was loaded
+    if !force.unwrap_or(false) && p.exists() {
+        if let Some(expected) = expected_modified {
+            let metadata = fs::metadata(&p).map_err(|e| e.to_string())?;
+            if modified_millis(&metadata) != expected {
+                return Err(format!(
+                    "File has been modified on disk since it was opened: {}",
+                    path
+                ));
+            }
+        }
+        let files = open_files
+            .files
+            .lock()
+            .map_err(|e| format!("Failed to acquire open files lock: {}", e))?;
+        if let Some(tracked) = files.get(&open_file_key(&p)) {
+            let on_disk = fs::read(&p).map_err(|e| e.to_string())?;
+            if hash_bytes(&on_disk) != tracked.hash {
+                return Err(format!(
+                    "File has been modified on disk since it was opened: {}",
+                    path
+                ));
+            }
+        }
+    }
+
+    let report = match pipeline {
+        Some(actions) => {
+            let report = run_save_pipeline(&p, content, &actions, &lsp).await;
+            write_atomic(&p, report.content.as_bytes())?;
+            Some(report)
+        }
+        None => {
+            write_atomic(&p, content.as_bytes())?;
+            None
+        }
+    };
+
+    // Record the saved version so the watcher doesn't report our own write
+    let mut files = open_files
+        .files
+        .lock()
+        .map_err(|e| format!("Failed to acquire open files lock: {}", e))?;
+    if let Some(tracked) = files.get_mut(&open_file_key(&p)) {
+        tracked.reload(&p);
+    }
+
+    let metadata = fs::metadata(&p).map_err(|e| e.to_string())?;
+    Ok(SaveFileResult {
+        modified: modified_millis(&metadata),
+        report,
+    })
+}
+
+/// Action applied to file content before it is written to disk
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SaveAction {
+    TrimTrailingWhitespace,
+    InsertFinalNewline,
+    TrimFinalNewlines,
+    /// External formatter reading the document on stdin and writing it to stdout
+    Format {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        timeout_ms: Option<u64>,
+    },
+    /// The language server's `source.organizeImports` code action. Servers
+    /// compute it on the editor's document, so it must run before any action
+    /// that changes the content.
+    OrganizeImports,
+}
+
+impl SaveAction {
+    fn name(&self) -> &'static str {
+        match self {
+            SaveAction::TrimTrailingWhitespace => "trim_trailing_whitespace",
+            SaveAction::InsertFinalNewline => "insert_final_newline",
+            SaveAction::TrimFinalNewlines => "trim_final_newlines",
+            SaveAction::Format { .. } => "format",
+            SaveAction::OrganizeImports => "organize_imports",
+        }
+    }
+}
+
+/// Outcome of a single save action
+#[derive(Serialize, Debug, Clone)]
+pub struct SaveActionReport {
+    pub action: String,
+    /// "applied", "unchanged", "skipped" or "failed"
+    pub status: String,
+    pub message: Option<String>,
+}
+
+/// Result of running the save pipeline
+#[derive(Serialize, Debug, Clone)]
+pub struct SavePipelineReport {
+    pub content: String,
+    pub changed: bool,
+    pub actions: Vec<SaveActionReport>,
+}
+
+/// Run save actions in order. A failing action leaves the content untouched
+/// and the remaining actions still run, so a broken formatter never blocks a save.
+async fn run_save_pipeline(
+    path: &Path,
+    original: String,
+    actions: &[SaveAction],
+    lsp: &LanguageServerManager,
+) -> SavePipelineReport {
+    let mut content = original.clone();
+    let mut reports = Vec::with_capacity(actions.len());
+
+    for action in actions {
+        let result = match action {
+            SaveAction::TrimTrailingWhitespace => Ok(Some(trim_trailing_whitespace(&content))),
+            SaveAction::InsertFinalNewline => {
+                if content.is_empty() || content.ends_with('\n') {
+                    Ok(None)
+                } else {
+                    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+                    Ok(Some(format!("{}{}", content, newline)))
+                }
+            }
+            SaveAction::TrimFinalNewlines => {
+                let trimmed = content.trim_end_matches(['\r', '\n']);
+                let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+                if trimmed.len() == content.len() {
+                    Ok(None)
+                } else {
+                    Ok(Some(format!("{}{}", trimmed, newline)))
+                }
+            }
+            SaveAction::Format {
+                command,
+                args,
+                timeout_ms,
+            } => run_formatter(path, &content, command, args, timeout_ms.unwrap_or(10000)).await,
+            SaveAction::OrganizeImports if content != original => Err((
+                "skipped",
+                "Organize imports must run before actions that change the content".to_string(),
+            )),
+            SaveAction::OrganizeImports => organize_imports(lsp, path, &content).await,
+        };
+
+        let report = match result {
+            Ok(Some(next)) if next != content => {
+                content = next;
+                SaveActionReport {
+                    action: action.name().to_string(),
+                    status: "applied".to_string(),
+                    message: None,
+                }
+            }
+            Ok(_) => SaveActionReport {
+                action: action.name().to_string(),
+                status: "unchanged".to_string(),
+                message: None,
+            },
+            Err((status, message)) => SaveActionReport {
+                action: action.name().to_string(),
+                status: status.to_string(),
+                message: Some(message),
+            },
+        };
+        reports.push(report);
+    }
+
+    SavePipelineReport {
+        changed: content != original,
+        content,
+        actions: reports,
+    }
+}
+
+/// Strip trailing spaces and tabs from every line, preserving line endings
+fn trim_trailing_whitespace(content: &str) -> String {
+    content
+        .split_inclusive('\n')
+        .map(|line| {
+            let (body, ending) = if let Some(stripped) = line.strip_suffix("\r\n") {
+                (stripped, "\r\n")
+            } else if let Some(stripped) = line.strip_suffix('\n') {
+                (stripped, "\n")
+            } else {
+                (line, "")
+            };
+            format!("{}{}", body.trim_end_matches([' ', '\t']), ending)
+        })
+        .collect()
+}
+
+/// Apply the first `source.organizeImports` code action a running language
+/// server offers for the document
+async fn organize_imports(
+    lsp: &LanguageServerManager,
+    path: &Path,
+    content: &str,
+) -> Result<Option<String>, (&'static str, String)> {
+    let params = serde_json::json!({
+        "textDocument": { "uri": crate::document_store::path_to_uri(path) },
+        "range": {
+            "start": { "line": 0, "character": 0 },
+            "end": { "line": content.lines().count(), "character": 0 },
+        },
+        "context": { "diagnostics": [], "only": ["source.organizeImports"] },
+    });
+
+    let mut command_only = false;
+    for server in lsp.get_running_servers() {
+        let result = match lsp
+            .request(
+                &server,
+                "textDocument/codeAction",
+                params.clone(),
+                std::time::Duration::from_secs(3),
+            )
+            .await
+        {
+            Ok(serde_json::Value::Null) => continue,
+            Ok(result) => result,
+            // Servers that don't handle this document answer with an error
+            Err(_) => continue,
+        };
+        let Ok(actions) = serde_json::from_value::<Vec<lsp_types::CodeActionOrCommand>>(result)
+        else {
+            continue;
+        };
+        for action in actions {
+            let lsp_types::CodeActionOrCommand::CodeAction(action) = action else {
+                command_only = true;
+                continue;
+            };
+            let Some(edit) = action.edit else {
+                command_only |= action.command.is_some();
+                continue;
+            };
+            let edits = document_text_edits(edit, path);
+            if edits.is_empty() {
+                continue;
+            }
+            return crate::workspace_edit::apply_text_edits(content, &edits)
+                .map(Some)
+                .map_err(|e| ("failed", e));
+        }
+    }
+
+    if command_only {
+        Err((
+            "skipped",
+            "The language server organizes imports through a command; run it in the editor"
+                .to_string(),
+        ))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Text edits of a workspace edit that target `path`
+fn document_text_edits(edit: lsp_types::WorkspaceEdit, path: &Path) -> Vec<lsp_types::TextEdit> {
+    let targets = |uri: &lsp_types::Uri| {
+        crate::workspace_edit::uri_to_path(uri).is_ok_and(|target| target == path)
+    };
+    edit_operations(edit)
+        .into_iter()
+        .filter_map(|operation| match operation {
+            lsp_types::DocumentChangeOperation::Edit(edit) if targets(&edit.text_document.uri) => {
+                Some(edit.edits)
+            }
+            _ => None,
+        })
+        .flatten()
+        .map(|edit| match edit {
+            lsp_types::OneOf::Left(edit) => edit,
+            lsp_types::OneOf::Right(annotated) => annotated.text_edit,
+        })
+        .collect()
+}
+
+/// Pipe content through an external formatter and return its stdout
+async fn run_formatter(
+    path: &Path,
+    content: &str,
+    command: &str,
+    args: &[String],
+    timeout_ms: u64,
+) -> Result<Option<String>, (&'static str, String)> {
+    use std::process::Stdio;
+    use tokio::io::AsyncWriteExt;
+    use tokio::time::{timeout as tokio_timeout, Duration};
+
+    let mut cmd = tokio::process::Command::new(command);
+    cmd.args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(parent) = path.parent() {
+        cmd.current_dir(parent);
+    }
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| ("failed", format!("Failed to spawn formatter '{}': {}", command, e)))?;
+
+    // Feed stdin from a separate task so a large output can't deadlock the pipe
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = content.to_string();
+        tokio::spawn(async move {
+            let _ = stdin.write_all(input.as_bytes()).await;
+        });
+    }
+
+    let output = tokio_timeout(Duration::from_millis(timeout_ms), child.wait_with_output())
+        .await
+        .map_err(|_| ("failed", format!("Formatter timed out after {}ms", timeout_ms)))?
+        .map_err(|e| ("failed", format!("Failed to wait for formatter: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err((
+            "failed",
+            format!(
+                "Formatter exited with code {}: {}",
+                output.status.code().unwrap_or(-1),
+                stderr.trim()
+            ),
+        ));
+    }
+
+    String::from_utf8(output.stdout)
+        .map(Some)
+        .map_err(|_| ("failed", "Formatter produced invalid UTF-8".to_string()))
 }
 
 #[tauri::command]
@@ -274,11 +1082,190 @@ pub async fn create_folder(path: String) -> Result<(), String> {
         .map_err(|e| e.to_string())
 }
 
+/// Result of `rename_path`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameResult {
+    /// Files whose imports were updated (at their paths after the move)
+    pub modified_files: Vec<String>,
+    /// "none", "lsp" (language server edits) or "references" (import rewriting)
+    pub method: String,
+}
+
+/// Import updates for a move from the running language servers
+async fn lsp_rename_edits(
+    lsp: &LanguageServerManager,
+    old: &Path,
+    new: &Path,
+) -> Vec<lsp_types::WorkspaceEdit> {
+    let params = serde_json::json!({
+        "files": [{
+            "oldUri": crate::document_store::path_to_uri(old),
+            "newUri": crate::document_store::path_to_uri(new),
+        }]
+    });
+    let mut edits = Vec::new();
+    for server in lsp.get_running_servers() {
+        match lsp
+            .request(
+                &server,
+                "workspace/willRenameFiles",
+                params.clone(),
+                std::time::Duration::from_secs(3),
+            )
+            .await
+        {
+            Ok(serde_json::Value::Null) => {}
+            Ok(result) => match serde_json::from_value::<lsp_types::WorkspaceEdit>(result) {
+                Ok(edit) => edits.push(edit),
+                Err(e) => eprintln!("[Rename] Invalid edit from {}: {}", server, e),
+            },
+            Err(e) => println!("[Rename] {}", e),
+        }
+    }
+    edits
+}
+
+/// The steps of a workspace edit as `documentChanges` operations
+fn edit_operations(edit: lsp_types::WorkspaceEdit) -> Vec<lsp_types::DocumentChangeOperation> {
+    use lsp_types::{DocumentChangeOperation, DocumentChanges, OneOf};
+    match edit.document_changes {
+        Some(DocumentChanges::Edits(edits)) => edits
+            .into_iter()
+            .map(DocumentChangeOperation::Edit)
+            .collect(),
+        Some(DocumentChanges::Operations(ops)) => ops,
+        None => edit
+            .changes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(uri, edits)| {
+                DocumentChangeOperation::Edit(lsp_types::TextDocumentEdit {
+                    text_document: lsp_types::OptionalVersionedTextDocumentIdentifier {
+                        uri,
+                        version: None,
+                    },
+                    edits: edits.into_iter().map(OneOf::Left).collect(),
+                })
+            })
+            .collect(),
+    }
+}
+
+fn file_uri(path: &Path) -> Result<lsp_types::Uri, String> {
+    crate::document_store::path_to_uri(path)
+        .parse()
+        .map_err(|e| format!("Invalid path {}: {}", path.display(), e))
+}
+
+/// Rename or move a file or folder
+///
+/// With `update_imports`, references to the moved file(s) are updated along
+/// with the move: through the language servers' `workspace/willRenameFiles`
+/// when any of them returns edits, otherwise by rewriting relative imports
+/// under `workspace_root`. The updates and the move are applied as one
+/// workspace edit, so if any part fails none of it is kept.
 #[tauri::command]
-pub async fn rename_path(old_path: String, new_path: String) -> Result<(), String> {
-    async_fs::rename(&old_path, &new_path)
-        .await
-        .map_err(|e| e.to_string())
+pub async fn rename_path(
+    old_path: String,
+    new_path: String,
+    update_imports: Option<bool>,
+    workspace_root: Option<String>,
+    lsp: State<'_, LanguageServerManager>,
+    open_files: State<'_, OpenFilesState>,
+) -> Result<RenameResult, String> {
+    let old = PathBuf::from(&old_path);
+    let new = PathBuf::from(&new_path);
+    let mut result = RenameResult {
+        modified_files: Vec::new(),
+        method: "none".to_string(),
+    };
+
+    if update_imports.unwrap_or(false) {
+        let mut operations = Vec::new();
+        let edits = lsp_rename_edits(&lsp, &old, &new).await;
+        if !edits.is_empty() {
+            operations.extend(edits.into_iter().flat_map(edit_operations));
+            result.method = "lsp".to_string();
+        } else if let Some(root) = workspace_root {
+            let (old_clone, new_clone) = (old.clone(), new.clone());
+            let updates = tokio::task::spawn_blocking(move || {
+                import_updates::plan_import_updates(Path::new(&root), &old_clone, &new_clone)
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+            for (path, content) in updates {
+                // Replaces the whole file: the end position clamps to its end
+                let whole_file = lsp_types::Range::new(
+                    lsp_types::Position::new(0, 0),
+                    lsp_types::Position::new(u32::MAX, 0),
+                );
+                operations.push(lsp_types::DocumentChangeOperation::Edit(
+                    lsp_types::TextDocumentEdit {
+                        text_document: lsp_types::OptionalVersionedTextDocumentIdentifier {
+                            uri: file_uri(&path)?,
+                            version: None,
+                        },
+                        edits: vec![lsp_types::OneOf::Left(lsp_types::TextEdit::new(
+                            whole_file, content,
+                        ))],
+                    },
+                ));
+            }
+            result.method = "references".to_string();
+        }
+        operations.push(lsp_types::DocumentChangeOperation::Op(
+            lsp_types::ResourceOp::Rename(lsp_types::RenameFile {
+                old_uri: file_uri(&old)?,
+                new_uri: file_uri(&new)?,
+                options: None,
+                annotation_id: None,
+            }),
+        ));
+
+        let edit = lsp_types::WorkspaceEdit {
+            document_changes: Some(lsp_types::DocumentChanges::Operations(operations)),
+            ..Default::default()
+        };
+        let summary = crate::workspace_edit::apply_edit(edit, &HashMap::new(), &open_files)?;
+        result.modified_files = summary.files_changed;
+    } else {
+        async_fs::rename(&old, &new)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    if update_imports.unwrap_or(false) {
+        let params = serde_json::json!({
+            "files": [{
+                "oldUri": crate::document_store::path_to_uri(&old),
+                "newUri": crate::document_store::path_to_uri(&new),
+            }]
+        });
+        for server in lsp.get_running_servers() {
+            let _ = lsp.notify(&server, "workspace/didRenameFiles", params.clone());
+        }
+        // Report edited files that moved at their new location
+        for path in result.modified_files.iter_mut() {
+            if let Ok(relative) = Path::new(path.as_str()).strip_prefix(&old) {
+                *path = if relative.as_os_str().is_empty() {
+                    new_path.clone()
+                } else {
+                    new.join(relative).to_string_lossy().to_string()
+                };
+            }
+        }
+        result.modified_files.sort();
+        result.modified_files.dedup();
+        println!(
+            "[Rename] Moved {} -> {}, updated {} file(s) via {}",
+            old_path,
+            new_path,
+            result.modified_files.len(),
+            result.method
+        );
+    }
+    Ok(result)
 }
 
 #[tauri::command]
@@ -294,23 +1281,45 @@ pub async fn delete_path(path: String) -> Result<(), String> {
     }
 }
 
+/// Watch a workspace for this window, replacing the window's previous watcher
+/// Changes are delivered debounced to this window only, as `file-changes`
+/// (typed) and `file-change` (paths) events. `include` / `exclude` are
+/// gitignore-style globs relative to the workspace limiting which changes
+/// are delivered.
 #[tauri::command]
 pub async fn watch_project_changes(
     window: tauri::Window,
     path: String,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
     state: State<'_, WatcherState>,
+    open_files: State<'_, OpenFilesState>,
+    recent_changes: State<'_, RecentChangesState>,
 ) -> Result<(), String> {
-    let mut watcher_guard = state
-        .watcher
+    let filter = watch_events::WatchFilter::new(
+        Path::new(&path),
+        &include.unwrap_or_default(),
+        &exclude.unwrap_or_default(),
+    )?;
+    let mut watchers = state
+        .watchers
         .lock()
         .map_err(|e| format!("Failed to acquire watcher lock: {}", e))?;
 
-    if watcher_guard.is_some() {
-        // We are already watching a directory. Stop the previous watcher.
-        *watcher_guard = None;
-    }
-
-    let window = window.clone();
+    // Stop this window's previous watcher
+    watchers.remove(window.label());
+
+    let tracked_files = open_files.files.clone();
+    let recent_events = recent_changes.events.clone();
+    let pending: Arc<Mutex<watch_events::PendingChanges>> = Arc::default();
+    let pauses: Arc<std::sync::atomic::AtomicUsize> = Arc::default();
+    watch_events::register_flusher(
+        window.clone(),
+        Arc::downgrade(&pending),
+        Arc::downgrade(&pauses),
+    );
+    let event_window = window.clone();
+    let event_pending = pending.clone();
     let mut watcher =
         notify::recommended_watcher(move |res: Result<notify::Event, notify::Error>| {
             match res {
@@ -335,8 +1344,37 @@ pub async fn watch_project_changes(
                         .collect();
 
                     if !relevant_paths.is_empty() {
-                        if let Err(e) = window.emit("file-change", &relevant_paths) {
-                            eprintln!("Failed to emit file-change event: {:?}", e);
+                        // Coalesced and delivered by the flusher thread
+                        let delivered: Vec<&PathBuf> = relevant_paths
+                            .iter()
+                            .copied()
+                            .filter(|path| filter.allows(path))
+                            .collect();
+                        if !delivered.is_empty() {
+                            if let Ok(mut pending) = event_pending.lock() {
+                                // The flusher only needs waking for a new burst
+                                let new_burst = pending.is_idle();
+                                pending.record_event(&event.kind, &delivered);
+                                if new_burst {
+                                    watch_events::wake_flusher();
+                                }
+                            }
+                        }
+                        record_recent_changes(&recent_events, &relevant_paths);
+                        file_index::apply_changes(&relevant_paths);
+                        semantic_index::apply_changes(&relevant_paths);
+                    }
+
+                    // Notify about external modifications to open files
+                    for path in &relevant_paths {
+                        if let Some(change) = detect_external_change(&tracked_files, path) {
+                            if let Err(e) = event_window.emit_to(
+                                event_window.label(),
+                                "file-changed-externally",
+                                &change,
+                            ) {
+                                eprintln!("Failed to emit file-changed-externally event: {:?}", e);
+                            }
                         }
                     }
                 }
@@ -349,11 +1387,443 @@ pub async fn watch_project_changes(
         .watch(path.as_ref(), RecursiveMode::Recursive)
         .map_err(|e| e.to_string())?;
 
-    *watcher_guard = Some(watcher);
+    watchers.insert(
+        window.label().to_string(),
+        ProjectWatcher {
+            root: PathBuf::from(&path),
+            _watcher: watcher,
+            _pending: pending,
+            pauses,
+        },
+    );
 
     Ok(())
 }
 
+/// Stop watching this window's workspace; false if it wasn't watching
+#[tauri::command]
+pub fn unwatch_project_changes(
+    window: tauri::Window,
+    state: State<'_, WatcherState>,
+) -> Result<bool, String> {
+    let mut watchers = state
+        .watchers
+        .lock()
+        .map_err(|e| format!("Failed to acquire watcher lock: {}", e))?;
+    Ok(watchers.remove(window.label()).is_some())
+}
+
+/// Hold this window's watcher events, e.g. around a checkout or a bulk
+/// operation; they are delivered coalesced by `watcher_resume`
+/// Pauses nest; returns how many are active
+#[tauri::command]
+pub fn watcher_pause(
+    window: tauri::Window,
+    state: State<'_, WatcherState>,
+) -> Result<usize, String> {
+    let watchers = state
+        .watchers
+        .lock()
+        .map_err(|e| format!("Failed to acquire watcher lock: {}", e))?;
+    let watcher = watchers
+        .get(window.label())
+        .ok_or("This window is not watching a workspace")?;
+    let depth = watcher.pauses.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+    println!(
+        "[Watcher] Paused {} (depth {})",
+        watcher.root.display(),
+        depth
+    );
+    Ok(depth)
+}
+
+/// Undo one `watcher_pause`; returns how many pauses are still active
+#[tauri::command]
+pub fn watcher_resume(
+    window: tauri::Window,
+    state: State<'_, WatcherState>,
+) -> Result<usize, String> {
+    let watchers = state
+        .watchers
+        .lock()
+        .map_err(|e| format!("Failed to acquire watcher lock: {}", e))?;
+    let Some(watcher) = watchers.get(window.label()) else {
+        return Ok(0);
+    };
+    let depth = watcher
+        .pauses
+        .fetch_update(
+            std::sync::atomic::Ordering::Relaxed,
+            std::sync::atomic::Ordering::Relaxed,
+            |depth| Some(depth.saturating_sub(1)),
+        )
+        .unwrap_or(0)
+        .saturating_sub(1);
+    if depth == 0 {
+        watch_events::wake_flusher();
+        println!("[Watcher] Resumed {}", watcher.root.display());
+    }
+    Ok(depth)
+}
+
+/// Ignore files that can be edited from the explorer
+const EDITABLE_IGNORE_FILES: [&str; 2] = [".gitignore", ".rainyignore"];
+
+/// Which ignore file and pattern decided a path's ignore status
+#[derive(Serialize, Debug, Clone)]
+pub struct IgnoreMatchInfo {
+    pub ignored: bool,
+    /// True when a negated pattern (`!pattern`) re-includes the path
+    pub whitelisted: bool,
+    pub pattern: Option<String>,
+    pub source_file: Option<String>,
+    pub line: Option<usize>,
+}
+
+fn resolve_ignore_file(workspace_root: &str, file: Option<String>) -> Result<PathBuf, String> {
+    let name = file.unwrap_or_else(|| ".gitignore".to_string());
+    if !EDITABLE_IGNORE_FILES.contains(&name.as_str()) {
+        return Err(format!(
+            "Unsupported ignore file: {} (expected one of {})",
+            name,
+            EDITABLE_IGNORE_FILES.join(", ")
+        ));
+    }
+    Ok(Path::new(workspace_root).join(name))
+}
+
+/// Build an anchored pattern that matches exactly one workspace path
+#[tauri::command]
+pub fn ignore_pattern_for_path(workspace_root: String, path: String) -> Result<String, String> {
+    let root = Path::new(&workspace_root);
+    let target = Path::new(&path);
+    let relative = target
+        .strip_prefix(root)
+        .map_err(|_| format!("Path is outside the workspace: {}", path))?;
+
+    let mut pattern = String::from("/");
+    for (i, component) in relative.components().enumerate() {
+        if i > 0 {
+            pattern.push('/');
+        }
+        for (j, ch) in component.as_os_str().to_string_lossy().chars().enumerate() {
+            let needs_escape = matches!(ch, '*' | '?' | '[' | ']' | '\\')
+                || (j == 0 && i == 0 && matches!(ch, '!' | '#'));
+            if needs_escape {
+                pattern.push('\\');
+            }
+            pattern.push(ch);
+        }
+    }
+
+    if pattern == "/" {
+        return Err("Cannot ignore the workspace root".to_string());
+    }
+    if target.is_dir() {
+        pattern.push('/');
+    }
+
+    Ok(pattern)
+}
+
+/// Append a pattern to .gitignore or .rainyignore unless it is already present
+#[tauri::command]
+pub async fn add_ignore_pattern(
+    workspace_root: String,
+    pattern: String,
+    file: Option<String>,
+) -> Result<bool, String> {
+    let pattern = pattern.trim().to_string();
+    if pattern.is_empty() {
+        return Err("Pattern cannot be empty".to_string());
+    }
+
+    let ignore_path = resolve_ignore_file(&workspace_root, file)?;
+    let mut content = if ignore_path.exists() {
+        fs::read_to_string(&ignore_path).map_err(|e| e.to_string())?
+    } else {
+        String::new()
+    };
+
+    if content.lines().any(|line| line.trim() == pattern) {
+        return Ok(false);
+    }
+
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&pattern);
+    content.push('\n');
+
+    fs::write(&ignore_path, content).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Remove every line matching a pattern from .gitignore or .rainyignore
+#[tauri::command]
+pub async fn remove_ignore_pattern(
+    workspace_root: String,
+    pattern: String,
+    file: Option<String>,
+) -> Result<bool, String> {
+    let pattern = pattern.trim();
+    let ignore_path = resolve_ignore_file(&workspace_root, file)?;
+    if !ignore_path.exists() {
+        return Ok(false);
+    }
+
+    let content = fs::read_to_string(&ignore_path).map_err(|e| e.to_string())?;
+    let kept: Vec<&str> = content
+        .lines()
+        .filter(|line| line.trim() != pattern)
+        .collect();
+
+    if kept.len() == content.lines().count() {
+        return Ok(false);
+    }
+
+    let mut updated = kept.join("\n");
+    if !updated.is_empty() {
+        updated.push('\n');
+    }
+    fs::write(&ignore_path, updated).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Report whether a path is ignored and which ignore file and pattern decided it.
+/// Nested ignore files are consulted from the workspace root down, deepest match wins.
+#[tauri::command]
+pub async fn check_path_ignored(workspace_root: String, path: String) -> Result<IgnoreMatchInfo, String> {
+    let root = Path::new(&workspace_root);
+    let target = Path::new(&path);
+    let relative = target
+        .strip_prefix(root)
+        .map_err(|_| format!("Path is outside the workspace: {}", path))?;
+    let is_dir = target.is_dir();
+
+    let mut result = IgnoreMatchInfo {
+        ignored: false,
+        whitelisted: false,
+        pattern: None,
+        source_file: None,
+        line: None,
+    };
+
+    // Directories from the root down to the target's parent
+    let mut dirs = vec![root.to_path_buf()];
+    if let Some(parent) = relative.parent() {
+        let mut current = root.to_path_buf();
+        for component in parent.components() {
+            current.push(component);
+            dirs.push(current.clone());
+        }
+    }
+
+    for dir in dirs {
+        for name in EDITABLE_IGNORE_FILES {
+            let ignore_path = dir.join(name);
+            if !ignore_path.is_file() {
+                continue;
+            }
+
+            let mut builder = GitignoreBuilder::new(&dir);
+            if builder.add(&ignore_path).is_some() {
+                continue;
+            }
+            let Ok(matcher) = builder.build() else {
+                continue;
+            };
+
+            let matched = matcher.matched_path_or_any_parents(target, is_dir);
+            if let Some(glob) = matched.inner() {
+                let original = glob.original().to_string();
+                let line = fs::read_to_string(&ignore_path).ok().and_then(|content| {
+                    content
+                        .lines()
+                        .position(|l| l.trim() == original)
+                        .map(|i| i + 1)
+                });
+
+                result = IgnoreMatchInfo {
+                    ignored: matched.is_ignore(),
+                    whitelisted: matched.is_whitelist(),
+                    pattern: Some(original),
+                    source_file: Some(ignore_path.to_string_lossy().to_string()),
+                    line,
+                };
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// A recently changed workspace file
+#[derive(Serialize, Debug, Clone)]
+pub struct RecentFile {
+    /// Path relative to the workspace root
+    pub path: String,
+    pub absolute_path: String,
+    /// Unix timestamp (seconds) of the most recent change
+    pub last_changed: i64,
+    /// Where the change was seen: "commit", "uncommitted", "watcher" or "mtime"
+    pub sources: Vec<String>,
+    /// Number of recent commits touching the file
+    pub commit_count: usize,
+}
+
+/// Files changed in the last N hours (default 24), combining git history,
+/// uncommitted changes and watcher events, most recent first
+#[tauri::command]
+pub async fn get_recently_changed_files(
+    path: String,
+    hours: Option<u64>,
+    limit: Option<usize>,
+    recent_changes: State<'_, RecentChangesState>,
+) -> Result<Vec<RecentFile>, String> {
+    let root = PathBuf::from(&path);
+    if !root.is_dir() {
+        return Err("Invalid workspace path".to_string());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let hours = i64::try_from(hours.unwrap_or(24)).unwrap_or(i64::MAX);
+    let cutoff = now.saturating_sub(hours.saturating_mul(3600));
+    let mut files: HashMap<String, RecentFile> = HashMap::new();
+
+    let mut add = |relative: String, time: i64, source: &str, commits: usize| {
+        let relative = relative.replace('\\', "/");
+        if relative.split('/').any(is_hardcoded_ignored) {
+            return;
+        }
+        let entry = files.entry(relative.clone()).or_insert_with(|| RecentFile {
+            absolute_path: root.join(&relative).to_string_lossy().to_string(),
+            path: relative,
+            last_changed: time,
+            sources: Vec::new(),
+            commit_count: 0,
+        });
+        entry.last_changed = entry.last_changed.max(time);
+        entry.commit_count += commits;
+        if !entry.sources.iter().any(|s| s == source) {
+            entry.sources.push(source.to_string());
+        }
+    };
+
+    // Git history and working tree (not being a repository is fine)
+    if let Ok(commit_files) = crate::git::history::recent_commit_files(&path, cutoff) {
+        for (file, time, count) in commit_files {
+            add(file, time, "commit", count);
+        }
+    }
+    if let Ok(dirty) = crate::git::history::uncommitted_files(&path) {
+        for file in dirty {
+            let time = fs::metadata(root.join(&file))
+                .map(|m| (modified_millis(&m) / 1000) as i64)
+                .unwrap_or(now);
+            if time >= cutoff {
+                add(file, time, "uncommitted", 0);
+            }
+        }
+    }
+
+    // Watcher events under this workspace
+    let events: Vec<(PathBuf, i64)> = recent_changes
+        .events
+        .lock()
+        .map_err(|e| format!("Failed to acquire recent changes lock: {}", e))?
+        .by_time
+        .range((cutoff, PathBuf::new())..)
+        .map(|(t, p)| (p.clone(), *t))
+        .collect();
+    for (event_path, time) in events {
+        if let Ok(relative) = event_path.strip_prefix(&root) {
+            if event_path.is_file() || !event_path.exists() {
+                add(relative.to_string_lossy().to_string(), time, "watcher", 0);
+            }
+        }
+    }
+
+    let mut result: Vec<RecentFile> = files.into_values().collect();
+    result.sort_by(|a, b| {
+        b.last_changed
+            .cmp(&a.last_changed)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    result.truncate(limit.unwrap_or(50));
+    Ok(result)
+}
+
+/// Most recently modified workspace files for Quick Open, newest first.
+/// Walks the workspace (skipping ignored folders) for modification times and
+/// also counts watcher events, which catch changes that keep the old mtime.
+#[tauri::command]
+pub async fn recent_files_changed(
+    workspace: String,
+    limit: Option<usize>,
+    recent_changes: State<'_, RecentChangesState>,
+) -> Result<Vec<RecentFile>, String> {
+    let root = PathBuf::from(&workspace);
+    if !root.is_dir() {
+        return Err("Invalid workspace path".to_string());
+    }
+    let limit = limit.unwrap_or(20).max(1);
+
+    // Watcher events under this workspace
+    let events: HashMap<PathBuf, i64> = recent_changes
+        .events
+        .lock()
+        .map_err(|e| format!("Failed to acquire recent changes lock: {}", e))?
+        .by_path
+        .iter()
+        .filter(|(path, _)| path.starts_with(&root))
+        .map(|(path, time)| (path.clone(), *time))
+        .collect();
+
+    tokio::task::spawn_blocking(move || {
+        // Min-heap of the newest `limit` files seen so far
+        let mut newest: BinaryHeap<Reverse<(i64, PathBuf, bool)>> = BinaryHeap::new();
+        for entry in ignore_walker(&root, false).build().flatten() {
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let modified = (modified_millis(&metadata) / 1000) as i64;
+            let watched = events.get(entry.path()).copied();
+            let time = modified.max(watched.unwrap_or(0));
+            let oldest_kept = newest.peek().map(|Reverse((t, _, _))| *t);
+            if newest.len() >= limit && oldest_kept.is_some_and(|t| t >= time) {
+                continue;
+            }
+            let from_watcher = watched.is_some_and(|w| w >= modified);
+            newest.push(Reverse((time, entry.into_path(), from_watcher)));
+            if newest.len() > limit {
+                newest.pop();
+            }
+        }
+
+        newest
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((time, path, from_watcher))| RecentFile {
+                path: path
+                    .strip_prefix(&root)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .replace('\\', "/"),
+                absolute_path: path.to_string_lossy().to_string(),
+                last_changed: time,
+                sources: vec![if from_watcher { "watcher" } else { "mtime" }.to_string()],
+                commit_count: 0,
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Recent files task failed: {}", e))
+}
+
 /// Get system temporary directory
 #[tauri::command]
 pub fn get_temp_dir() -> Result<String, String> {
@@ -382,7 +1852,7 @@ pub struct FileSearchResult {
 }
 
 /// Search options
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct SearchOptions {
     pub case_sensitive: bool,
     pub whole_word: bool,
@@ -390,6 +1860,9 @@ pub struct SearchOptions {
     pub include_pattern: Option<String>,
     pub exclude_pattern: Option<String>,
     pub max_results: Option<usize>,
+    /// Also search files excluded by .gitignore, .ignore and global excludes
+    #[serde(default)]
+    pub include_ignored: bool,
 }
 
 /// Check if file should be searched based on include/exclude patterns
@@ -470,28 +1943,21 @@ fn search_in_directory(
     dir: &Path,
     query: &str,
     options: &SearchOptions,
-    matcher: &Gitignore, // New parameter for gitignore rules
     results: &Arc<Mutex<Vec<FileSearchResult>>>,
     current_count: &Arc<Mutex<usize>>,
     max_results: usize,
 ) -> Result<(), String> {
-    // Check if we've reached the max results limit
-    {
-        let count = current_count.lock().unwrap();
-        if *count >= max_results {
-            return Ok(());
-        }
-    }
-
-    // Collect all entries first
-    let entries: Vec<_> = fs::read_dir(dir)
-        .map_err(|e| e.to_string())?
+    // Collect all files that are not ignored first
+    let files: Vec<PathBuf> = ignore_walker(dir, options.include_ignored)
+        .build()
         .filter_map(|e| e.ok())
+        .map(|e| e.into_path())
+        .filter(|path| path.is_file())
         .collect();
 
-    // Parallel processing of entries
-    entries.par_iter().try_for_each(|entry| {
-        // Check limit again
+    // Parallel processing of files
+    files.par_iter().try_for_each(|path| {
+        // Check if we've reached the max results limit
         {
             let count = current_count.lock().unwrap();
             if *count >= max_results {
@@ -499,54 +1965,42 @@ fn search_in_directory(
             }
         }
 
-        let path = entry.path();
-        
-        // Skip ignored directories/files using the new should_ignore
-        if should_ignore(matcher, &path, path.is_dir()) {
+        // Check if we should search this file
+        if !should_search_file(path, &options.include_pattern, &options.exclude_pattern) {
             return Ok(());
         }
 
-        if path.is_dir() {
-            // Recurse into subdirectory (this will also use parallel processing)
-            search_in_directory(&path, query, options, matcher, results, current_count, max_results)?;
-        } else if path.is_file() {
-            // Check if we should search this file
-            if !should_search_file(&path, &options.include_pattern, &options.exclude_pattern) {
-                return Ok(());
-            }
+        // Skip binary files
+        if is_binary_file(path) {
+            return Ok(());
+        }
 
-            // Skip binary files
-            if is_binary_file(&path) {
+        // Skip files larger than 1MB
+        if let Ok(metadata) = fs::metadata(path) {
+            if metadata.len() > 1024 * 1024 {
                 return Ok(());
             }
+        }
 
-            // Skip files larger than 1MB
-            if let Ok(metadata) = fs::metadata(&path) {
-                if metadata.len() > 1024 * 1024 {
-                    return Ok(());
-                }
-            }
-
-            // Search in file
-            if let Ok(content) = fs::read_to_string(&path) {
-                let matches = search_in_content(&content, query, options);
+        // Search in file
+        if let Ok(content) = fs::read_to_string(path) {
+            let matches = search_in_content(&content, query, options);
 
-                if !matches.is_empty() {
-                    // Acquire locks and update shared state
-                    let mut results_guard = results.lock().unwrap();
-                    let mut count_guard = current_count.lock().unwrap();
+            if !matches.is_empty() {
+                // Acquire locks and update shared state
+                let mut results_guard = results.lock().unwrap();
+                let mut count_guard = current_count.lock().unwrap();
 
-                    // Double-check we haven't exceeded limit while waiting for lock
-                    if *count_guard < max_results {
-                        *count_guard += matches.len();
+                // Double-check we haven't exceeded limit while waiting for lock
+                if *count_guard < max_results {
+                    *count_guard += matches.len();
 
-                        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
-                        results_guard.push(FileSearchResult {
-                            path: path.to_string_lossy().to_string(),
-                            name,
-                            matches,
-                        });
-                    }
+                    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                    results_guard.push(FileSearchResult {
+                        path: path.to_string_lossy().to_string(),
+                        name,
+                        matches,
+                    });
                 }
             }
         }
@@ -556,7 +2010,7 @@ fn search_in_directory(
 }
 
 /// Search for matches in file content
-fn search_in_content(content: &str, query: &str, options: &SearchOptions) -> Vec<SearchMatch> {
+pub(crate) fn search_in_content(content: &str, query: &str, options: &SearchOptions) -> Vec<SearchMatch> {
     let mut matches = Vec::new();
 
     if options.use_regex {
@@ -691,14 +2145,23 @@ pub async fn search_in_workspace(
         return Err("Invalid workspace path".to_string());
     }
 
+    run_workspace_search(&dir_path, &query, &options)
+}
+
+/// Run the parallel workspace search engine and return results sorted by path.
+/// Shared by the search panel command and the agent `tool_search_workspace` tool.
+pub(crate) fn run_workspace_search(
+    dir_path: &Path,
+    query: &str,
+    options: &SearchOptions,
+) -> Result<Vec<FileSearchResult>, String> {
     let max_results = options.max_results.unwrap_or(1000);
-    let matcher = create_gitignore_matcher(&dir_path); // Create matcher for the workspace root
 
     // Wrap results and count in Arc<Mutex<>> for thread-safe parallel processing
     let results_shared = Arc::new(Mutex::new(Vec::new()));
     let count_shared = Arc::new(Mutex::new(0usize));
 
-    search_in_directory(&dir_path, &query, &options, &matcher, &results_shared, &count_shared, max_results)?;
+    search_in_directory(dir_path, query, options, &results_shared, &count_shared, max_results)?;
 
     // Extract results from Arc<Mutex<>> and sort
     let results = Arc::try_unwrap(results_shared)
@@ -711,43 +2174,146 @@ pub async fn search_in_workspace(
     Ok(sorted_results)
 }
 
-/// Replace text in a single file
-#[tauri::command]
-pub async fn replace_in_file(
-    path: String,
-    search: String,
-    replace: String,
-    options: SearchOptions,
-) -> Result<usize, String> {
-    let file_path = PathBuf::from(&path);
-    let content = fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
+/// Maximum number of replace operations that can be undone
+const MAX_UNDO_BUNDLES: usize = 10;
+
+/// Reverse patch restoring one file to its content before a replace
+#[derive(Debug, Clone)]
+struct ReversePatch {
+    path: PathBuf,
+    /// Byte range in the replaced content that differs from the original
+    start: usize,
+    end: usize,
+    /// Original text for that range
+    original: String,
+    /// Hash of the file right after the replace, used to detect later edits
+    replaced_hash: String,
+}
+
+/// All file changes made by one replace operation
+#[derive(Debug, Clone)]
+struct UndoBundle {
+    id: String,
+    patches: Vec<ReversePatch>,
+}
+
+/// Undo history for search/replace operations
+#[derive(Default)]
+pub struct ReplaceUndoState {
+    bundles: Mutex<Vec<UndoBundle>>,
+}
+
+impl ReplaceUndoState {
+    fn push(&self, patches: Vec<ReversePatch>) -> Result<Option<String>, String> {
+        if patches.is_empty() {
+            return Ok(None);
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let mut bundles = self
+            .bundles
+            .lock()
+            .map_err(|e| format!("Failed to acquire undo lock: {}", e))?;
+        bundles.push(UndoBundle {
+            id: id.clone(),
+            patches,
+        });
+        if bundles.len() > MAX_UNDO_BUNDLES {
+            bundles.remove(0);
+        }
+        Ok(Some(id))
+    }
+}
+
+/// Result of a workspace-wide replace
+#[derive(Serialize, Debug, Clone)]
+pub struct ReplaceSummary {
+    /// Undo bundle ID (None when nothing changed)
+    pub bundle_id: Option<String>,
+    pub files_changed: usize,
+    pub total_replacements: usize,
+    /// Files that could not be processed, with the reason
+    pub errors: Vec<String>,
+}
+
+/// Result of undoing a replace operation
+#[derive(Serialize, Debug, Clone)]
+pub struct UndoReplaceResult {
+    pub bundle_id: String,
+    pub restored: Vec<String>,
+    /// Files edited after the replace, left untouched
+    pub conflicts: Vec<String>,
+}
+
+/// Build a reverse patch from the smallest range that differs between two versions
+fn build_reverse_patch(path: &Path, original: &str, replaced: &str) -> ReversePatch {
+    // Compared char by char so the range always falls on char boundaries
+    // (e.g. "é" and "è" share their first byte)
+    let prefix: usize = original
+        .chars()
+        .zip(replaced.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+    let suffix: usize = original[prefix..]
+        .chars()
+        .rev()
+        .zip(replaced[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+
+    ReversePatch {
+        path: path.to_path_buf(),
+        start: prefix,
+        end: replaced.len() - suffix,
+        original: original[prefix..original.len() - suffix].to_string(),
+        replaced_hash: hash_bytes(replaced.as_bytes()),
+    }
+}
+
+/// Apply a search/replace to content, returning the new content and replacement count
+fn apply_replacement(
+    content: &str,
+    search: &str,
+    replace: &str,
+    options: &SearchOptions,
+) -> Result<(String, usize), String> {
+    if options.use_regex || options.whole_word {
+        let source = if options.use_regex {
+            search.to_string()
+        } else {
+            regex::escape(search)
+        };
+        let source = if options.whole_word {
+            format!(r"\b(?:{})\b", source)
+        } else {
+            source
+        };
 
-    let (new_content, count) = if options.use_regex {
         let pattern = if options.case_sensitive {
-            regex::Regex::new(&search)
+            regex::Regex::new(&source)
         } else {
-            regex::RegexBuilder::new(&search)
+            regex::RegexBuilder::new(&source)
                 .case_insensitive(true)
                 .build()
         };
 
         match pattern {
             Ok(re) => {
-                let count = re.find_iter(&content).count();
-                let new_content = re.replace_all(&content, replace.as_str()).to_string();
-                (new_content, count)
+                let count = re.find_iter(content).count();
+                let new_content = re.replace_all(content, replace).to_string();
+                Ok((new_content, count))
             }
-            Err(e) => return Err(format!("Invalid regex: {}", e)),
+            Err(e) => Err(format!("Invalid regex: {}", e)),
         }
     } else {
-        let mut new_content = content.clone();
+        let mut new_content = content.to_string();
         let mut count = 0;
 
         if options.case_sensitive {
-            while new_content.contains(&search) {
-                new_content = new_content.replacen(&search, &replace, 1);
-                count += 1;
-            }
+            count = new_content.matches(search).count();
+            new_content = new_content.replace(search, replace);
         } else {
             let search_lower = search.to_lowercase();
             let mut result = String::new();
@@ -755,7 +2321,7 @@ pub async fn replace_in_file(
 
             while let Some(pos) = remaining.to_lowercase().find(&search_lower) {
                 result.push_str(&remaining[..pos]);
-                result.push_str(&replace);
+                result.push_str(replace);
                 remaining = &remaining[pos + search.len()..];
                 count += 1;
             }
@@ -763,76 +2329,178 @@ pub async fn replace_in_file(
             new_content = result;
         }
 
-        (new_content, count)
-    };
+        Ok((new_content, count))
+    }
+}
 
-    fs::write(&file_path, new_content).map_err(|e| e.to_string())?;
+/// Replace text in a single file
+#[tauri::command]
+pub async fn replace_in_file(
+    path: String,
+    search: String,
+    replace: String,
+    options: SearchOptions,
+    undo: State<'_, ReplaceUndoState>,
+) -> Result<usize, String> {
+    let file_path = PathBuf::from(&path);
+    let content = fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
 
-    Ok(count)
-}
+    let (new_content, count) = apply_replacement(&content, &search, &replace, &options)?;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct CommandResult {
-    pub stdout: String,
-    pub stderr: String,
-    pub exit_code: i32,
+    fs::write(&file_path, &new_content).map_err(|e| e.to_string())?;
+
+    if new_content != content {
+        undo.push(vec![build_reverse_patch(&file_path, &content, &new_content)])?;
+    }
+
+    Ok(count)
 }
 
+/// Replace text across every matching file in a workspace as one undoable operation
 #[tauri::command]
-pub async fn execute_command(
-    command: String,
-    cwd: Option<String>,
-    timeout: Option<u64>,
-) -> Result<CommandResult, String> {
-    use std::process::Stdio;
-    use tokio::io::AsyncReadExt;
-    use tokio::time::{timeout as tokio_timeout, Duration};
+pub async fn replace_in_workspace(
+    path: String,
+    search: String,
+    replace: String,
+    options: SearchOptions,
+    files: Option<Vec<String>>,
+    undo: State<'_, ReplaceUndoState>,
+) -> Result<ReplaceSummary, String> {
+    if search.is_empty() {
+        return Err("Search text cannot be empty".to_string());
+    }
 
-    let mut cmd = if cfg!(target_os = "windows") {
-        let mut c = tokio::process::Command::new("cmd");
-        c.args(["/C", &command]);
-        c
-    } else {
-        let mut c = tokio::process::Command::new("sh");
-        c.args(["-c", &command]);
-        c
+    let dir_path = PathBuf::from(&path);
+    if !dir_path.is_dir() {
+        return Err("Invalid workspace path".to_string());
+    }
+
+    // Limit to the given files, otherwise every file with a match
+    let targets: Vec<PathBuf> = match files {
+        Some(files) => files.into_iter().map(PathBuf::from).collect(),
+        None => {
+            let search_options = SearchOptions {
+                max_results: Some(usize::MAX),
+                ..options.clone()
+            };
+            run_workspace_search(&dir_path, &search, &search_options)?
+                .into_iter()
+                .map(|result| PathBuf::from(result.path))
+                .collect()
+        }
     };
 
-    if let Some(dir) = cwd {
-        cmd.current_dir(dir);
-    }
+    let mut patches = Vec::new();
+    let mut summary = ReplaceSummary {
+        bundle_id: None,
+        files_changed: 0,
+        total_replacements: 0,
+        errors: Vec::new(),
+    };
 
-    cmd.stdout(Stdio::piped());
-    cmd.stderr(Stdio::piped());
+    for file_path in targets {
+        let content = match fs::read_to_string(&file_path) {
+            Ok(content) => content,
+            Err(e) => {
+                summary.errors.push(format!("{}: {}", file_path.display(), e));
+                continue;
+            }
+        };
 
-    let mut child = cmd.spawn().map_err(|e| format!("Failed to spawn command: {}", e))?;
+        let (new_content, count) = apply_replacement(&content, &search, &replace, &options)?;
+        if new_content == content {
+            continue;
+        }
 
-    let timeout_duration = Duration::from_millis(timeout.unwrap_or(30000));
+        if let Err(e) = fs::write(&file_path, &new_content) {
+            summary.errors.push(format!("{}: {}", file_path.display(), e));
+            continue;
+        }
 
-    let output_result = tokio_timeout(timeout_duration, child.wait()).await;
+        patches.push(build_reverse_patch(&file_path, &content, &new_content));
+        summary.files_changed += 1;
+        summary.total_replacements += count;
+    }
 
-    match output_result {
-        Ok(Ok(status)) => {
-            let mut stdout = String::new();
-            let mut stderr = String::new();
+    summary.bundle_id = undo.push(patches)?;
+    Ok(summary)
+}
 
-            if let Some(mut out) = child.stdout {
-                let _ = out.read_to_string(&mut stdout).await;
-            }
-            if let Some(mut err) = child.stderr {
-                let _ = err.read_to_string(&mut stderr).await;
+/// Revert the most recent replace operation
+#[tauri::command]
+pub async fn undo_last_replace(
+    undo: State<'_, ReplaceUndoState>,
+) -> Result<Option<UndoReplaceResult>, String> {
+    let bundle = undo
+        .bundles
+        .lock()
+        .map_err(|e| format!("Failed to acquire undo lock: {}", e))?
+        .pop();
+
+    let Some(bundle) = bundle else {
+        return Ok(None);
+    };
+
+    let mut result = UndoReplaceResult {
+        bundle_id: bundle.id,
+        restored: Vec::new(),
+        conflicts: Vec::new(),
+    };
+
+    for patch in bundle.patches {
+        let display = patch.path.to_string_lossy().to_string();
+        let current = match fs::read_to_string(&patch.path) {
+            Ok(current) if hash_bytes(current.as_bytes()) == patch.replaced_hash => current,
+            _ => {
+                result.conflicts.push(display);
+                continue;
             }
+        };
 
-            Ok(CommandResult {
-                stdout,
-                stderr,
-                exit_code: status.code().unwrap_or(-1),
-            })
-        }
-        Ok(Err(e)) => Err(format!("Failed to wait for command: {}", e)),
-        Err(_) => {
-            let _ = child.kill().await;
-            Err("Command timed out".to_string())
+        let restored = format!(
+            "{}{}{}",
+            &current[..patch.start],
+            patch.original,
+            &current[patch.end..]
+        );
+        match fs::write(&patch.path, restored) {
+            Ok(()) => result.restored.push(display),
+            Err(_) => result.conflicts.push(display),
         }
     }
+
+    Ok(Some(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn undo(patch: &ReversePatch, replaced: &str) -> String {
+        format!(
+            "{}{}{}",
+            &replaced[..patch.start],
+            patch.original,
+            &replaced[patch.end..]
+        )
+    }
+
+    #[test]
+    fn test_reverse_patch_multibyte_replacement() {
+        let original = "caf\u{e9} cr\u{e8}me";
+        let replaced = "caf\u{e8} cr\u{e8}me";
+        let patch = build_reverse_patch(Path::new("menu.txt"), original, replaced);
+        assert_eq!(patch.original, "\u{e9}");
+        assert_eq!(&replaced[patch.start..patch.end], "\u{e8}");
+        assert_eq!(undo(&patch, replaced), original);
+    }
+
+    #[test]
+    fn test_reverse_patch_length_change() {
+        let original = "let \u{1f600} = 1;";
+        let replaced = "let smile = 1;";
+        let patch = build_reverse_patch(Path::new("lib.rs"), original, replaced);
+        assert_eq!(patch.original, "\u{1f600}");
+        assert_eq!(undo(&patch, replaced), original);
+    }
 }
@@ -0,0 +1,351 @@
+//! Batch File Operations
+//!
+//! Drag-and-drop of several items used to send one command per item, and a
+//! failure halfway left the tree half moved. `batch_file_operation` runs a
+//! list of create / rename / move / copy / delete operations in order and
+//! reports the outcome of each.
+//!
+//! With `atomic`, the batch stops at the first failure and undoes the steps
+//! already done, newest first. To make deletes undoable they first move the
+//! item to a hidden sibling, which is only removed once the whole batch has
+//! succeeded. Without `atomic`, every operation is attempted and failures are
+//! reported per item.
+//!
+//! Operations never overwrite: a destination that already exists fails.
+
+use super::copy_ops::{copy_tree, CopyCounts};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_STAGING_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum FileOperation {
+    CreateFile {
+        path: String,
+        #[serde(default)]
+        content: Option<String>,
+    },
+    CreateFolder {
+        path: String,
+    },
+    Rename {
+        from: String,
+        to: String,
+    },
+    /// Like rename, but also works across file systems
+    Move {
+        from: String,
+        to: String,
+    },
+    Copy {
+        from: String,
+        to: String,
+    },
+    Delete {
+        path: String,
+    },
+}
+
+impl FileOperation {
+    fn name(&self) -> &'static str {
+        match self {
+            FileOperation::CreateFile { .. } => "create_file",
+            FileOperation::CreateFolder { .. } => "create_folder",
+            FileOperation::Rename { .. } => "rename",
+            FileOperation::Move { .. } => "move",
+            FileOperation::Copy { .. } => "copy",
+            FileOperation::Delete { .. } => "delete",
+        }
+    }
+}
+
+/// Outcome of a single operation
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileOperationResult {
+    pub index: usize,
+    pub op: String,
+    /// "done", "failed", "skipped" or "rolled_back"
+    pub status: String,
+    pub error: Option<String>,
+}
+
+/// Result of `batch_file_operation`
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchOperationResult {
+    pub results: Vec<FileOperationResult>,
+    /// Every operation succeeded
+    pub succeeded: bool,
+    /// An atomic batch failed and its completed steps were undone
+    pub rolled_back: bool,
+}
+
+/// How to undo a completed operation
+enum Undo {
+    /// Done for good (deletes and moves outside an atomic batch)
+    Permanent,
+    RemoveFile(PathBuf),
+    RemoveTree(PathBuf),
+    /// Move `to` back to `from`
+    MoveBack {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    /// A delete staged at `staged`, restored to `original` on rollback and
+    /// removed for good on commit
+    Unstage {
+        original: PathBuf,
+        staged: PathBuf,
+    },
+    /// A cross-device move: remove the copy and restore the staged source
+    RemoveCopyAndUnstage {
+        copy: PathBuf,
+        original: PathBuf,
+        staged: PathBuf,
+    },
+}
+
+fn ensure_absent(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", path.display()),
+        ));
+    }
+    Ok(())
+}
+
+fn remove_any(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Copy to a destination that must not exist yet, cleaning up a partial copy
+fn copy_new(from: &Path, to: &Path) -> io::Result<()> {
+    ensure_absent(to)?;
+    if to.starts_with(from) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Cannot copy {} into itself", from.display()),
+        ));
+    }
+    copy_tree(from, to, &mut CopyCounts::default(), &mut |_| {}).inspect_err(|_| {
+        let _ = remove_any(to);
+    })
+}
+
+/// Move `path` to a hidden sibling so it can be restored
+fn stage_delete(path: &Path) -> io::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Path has no name"))?
+        .to_string_lossy()
+        .to_string();
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    let staged = parent.join(format!(
+        ".{}.rainy-delete-{}-{}",
+        name,
+        std::process::id(),
+        NEXT_STAGING_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::rename(path, &staged)?;
+    Ok(staged)
+}
+
+fn is_cross_device(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::CrossesDevices
+}
+
+fn move_path(from: &Path, to: &Path, atomic: bool, cross_device: bool) -> io::Result<Undo> {
+    fs::symlink_metadata(from)?;
+    ensure_absent(to)?;
+    match fs::rename(from, to) {
+        Ok(()) => Ok(Undo::MoveBack {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        }),
+        Err(e) if cross_device && is_cross_device(&e) => {
+            copy_new(from, to)?;
+            if atomic {
+                let staged = stage_delete(from).inspect_err(|_| {
+                    let _ = remove_any(to);
+                })?;
+                Ok(Undo::RemoveCopyAndUnstage {
+                    copy: to.to_path_buf(),
+                    original: from.to_path_buf(),
+                    staged,
+                })
+            } else {
+                remove_any(from)?;
+                Ok(Undo::Permanent)
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn run_operation(operation: &FileOperation, atomic: bool) -> io::Result<Undo> {
+    match operation {
+        FileOperation::CreateFile { path, content } => {
+            let path = PathBuf::from(path);
+            let mut file = fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)?;
+            if let Some(content) = content {
+                io::Write::write_all(&mut file, content.as_bytes())?;
+            }
+            Ok(Undo::RemoveFile(path))
+        }
+        FileOperation::CreateFolder { path } => {
+            let path = PathBuf::from(path);
+            ensure_absent(&path)?;
+            // Undo removes the outermost folder this created
+            let mut created = path.clone();
+            while let Some(parent) = created.parent() {
+                if parent.as_os_str().is_empty() || parent.exists() {
+                    break;
+                }
+                created = parent.to_path_buf();
+            }
+            fs::create_dir_all(&path)?;
+            Ok(Undo::RemoveTree(created))
+        }
+        FileOperation::Rename { from, to } => {
+            move_path(Path::new(from), Path::new(to), atomic, false)
+        }
+        FileOperation::Move { from, to } => move_path(Path::new(from), Path::new(to), atomic, true),
+        FileOperation::Copy { from, to } => {
+            let to = PathBuf::from(to);
+            copy_new(Path::new(from), &to)?;
+            Ok(Undo::RemoveTree(to))
+        }
+        FileOperation::Delete { path } => {
+            let path = PathBuf::from(path);
+            if atomic {
+                let staged = stage_delete(&path)?;
+                Ok(Undo::Unstage {
+                    original: path,
+                    staged,
+                })
+            } else {
+                remove_any(&path)?;
+                Ok(Undo::Permanent)
+            }
+        }
+    }
+}
+
+fn rollback(undo: Undo) -> io::Result<()> {
+    match undo {
+        Undo::Permanent => Err(io::Error::other("This step cannot be undone")),
+        Undo::RemoveFile(path) => fs::remove_file(path),
+        Undo::RemoveTree(path) => remove_any(&path),
+        Undo::MoveBack { from, to } => fs::rename(to, from),
+        Undo::Unstage { original, staged } => fs::rename(staged, original),
+        Undo::RemoveCopyAndUnstage {
+            copy,
+            original,
+            staged,
+        } => {
+            remove_any(&copy)?;
+            fs::rename(staged, original)
+        }
+    }
+}
+
+/// Finish an operation that succeeded as part of the batch
+fn commit(undo: Undo) {
+    if let Undo::Unstage { staged, .. } | Undo::RemoveCopyAndUnstage { staged, .. } = undo {
+        if let Err(e) = remove_any(&staged) {
+            eprintln!(
+                "[BatchOps] Failed to remove staged delete {}: {}",
+                staged.display(),
+                e
+            );
+        }
+    }
+}
+
+fn run_batch(operations: Vec<FileOperation>, atomic: bool) -> BatchOperationResult {
+    let mut results: Vec<FileOperationResult> = operations
+        .iter()
+        .enumerate()
+        .map(|(index, operation)| FileOperationResult {
+            index,
+            op: operation.name().to_string(),
+            status: "skipped".to_string(),
+            error: None,
+        })
+        .collect();
+    let mut done: Vec<(usize, Undo)> = Vec::new();
+    let mut failed = false;
+
+    for (index, operation) in operations.iter().enumerate() {
+        match run_operation(operation, atomic) {
+            Ok(undo) => {
+                results[index].status = "done".to_string();
+                done.push((index, undo));
+            }
+            Err(e) => {
+                results[index].status = "failed".to_string();
+                results[index].error = Some(e.to_string());
+                failed = true;
+                if atomic {
+                    break;
+                }
+            }
+        }
+    }
+
+    let rolled_back = atomic && failed;
+    if rolled_back {
+        for (index, undo) in done.into_iter().rev() {
+            match rollback(undo) {
+                Ok(()) => results[index].status = "rolled_back".to_string(),
+                Err(e) => {
+                    results[index].error = Some(format!("Rollback failed: {}", e));
+                }
+            }
+        }
+    } else {
+        for (_, undo) in done {
+            commit(undo);
+        }
+    }
+
+    println!(
+        "[BatchOps] Ran {} operation(s){}{}",
+        operations.len(),
+        if failed { ", some failed" } else { "" },
+        if rolled_back { ", rolled back" } else { "" }
+    );
+    BatchOperationResult {
+        results,
+        succeeded: !failed,
+        rolled_back,
+    }
+}
+
+/// Run file operations in order, reporting the outcome of each
+/// With `atomic`, the first failure stops the batch and undoes the steps
+/// already done
+#[tauri::command]
+pub async fn batch_file_operation(
+    ops: Vec<FileOperation>,
+    atomic: Option<bool>,
+) -> Result<BatchOperationResult, String> {
+    let atomic = atomic.unwrap_or(false);
+    tokio::task::spawn_blocking(move || run_batch(ops, atomic))
+        .await
+        .map_err(|e| format!("Batch task failed: {}", e))
+}
@@ -0,0 +1,192 @@
+//! Binary File Content
+//!
+//! `get_file_content` is for text: it decodes UTF-8 and truncates large files
+//! with a marker. Image previews and the hex view need the raw bytes instead,
+//! so `get_file_binary` returns them base64 encoded together with the MIME
+//! type (sniffed from the content, falling back to the extension) and, for
+//! images, the pixel dimensions read from the file header.
+
+use base64::Engine;
+use serde::Serialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Default cap on the bytes returned; callers can ask for more or less
+const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// How much of the file is checked for NUL bytes to tell text from binary
+const TEXT_SNIFF_BYTES: usize = 8 * 1024;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileBinaryContent {
+    pub path: String,
+    /// Base64 of the first `bytesRead` bytes
+    pub base64: String,
+    pub mime_type: String,
+    /// Size of the whole file
+    pub size: u64,
+    pub bytes_read: u64,
+    pub truncated: bool,
+    pub is_image: bool,
+    /// Whether the content looks like something other than text
+    pub is_binary: bool,
+    /// Pixel size, for images whose header we can read
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// MIME types for formats content sniffing can't recognize
+fn mime_from_extension(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    Some(match extension.as_str() {
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" | "cjs" => "text/javascript",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "txt" => "text/plain",
+        "wasm" => "application/wasm",
+        _ => return None,
+    })
+}
+
+fn be_u16(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u16::from_be_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as u32)
+}
+
+fn le_u16(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as u32)
+}
+
+fn be_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le_u24(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 3)?;
+    Some(b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16)
+}
+
+fn le_i32(bytes: &[u8], at: usize) -> Option<i32> {
+    Some(i32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Walk JPEG segments up to the first start-of-frame marker
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut at = 2;
+    while at + 4 <= bytes.len() {
+        if bytes[at] != 0xFF {
+            return None;
+        }
+        let marker = bytes[at + 1];
+        // Fill bytes and markers without a length
+        if marker == 0xFF {
+            at += 1;
+            continue;
+        }
+        if marker == 0xD8 || marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            at += 2;
+            continue;
+        }
+        let length = be_u16(bytes, at + 2)? as usize;
+        let is_frame = matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+        if is_frame {
+            return Some((be_u16(bytes, at + 7)?, be_u16(bytes, at + 5)?));
+        }
+        at += 2 + length;
+    }
+    None
+}
+
+fn webp_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    match bytes.get(12..16)? {
+        b"VP8 " => Some((le_u16(bytes, 26)? & 0x3FFF, le_u16(bytes, 28)? & 0x3FFF)),
+        b"VP8L" => {
+            let bits = u32::from_le_bytes(bytes.get(21..25)?.try_into().ok()?);
+            Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+        }
+        b"VP8X" => Some((le_u24(bytes, 24)? + 1, le_u24(bytes, 27)? + 1)),
+        _ => None,
+    }
+}
+
+/// Width and height from the header of PNG, GIF, JPEG, BMP and WebP images
+fn image_dimensions(mime_type: &str, bytes: &[u8]) -> Option<(u32, u32)> {
+    match mime_type {
+        "image/png" => Some((be_u32(bytes, 16)?, be_u32(bytes, 20)?)),
+        "image/gif" => Some((le_u16(bytes, 6)?, le_u16(bytes, 8)?)),
+        "image/jpeg" => jpeg_dimensions(bytes),
+        "image/bmp" => Some((
+            le_i32(bytes, 18)?.unsigned_abs(),
+            le_i32(bytes, 22)?.unsigned_abs(),
+        )),
+        "image/webp" => webp_dimensions(bytes),
+        _ => None,
+    }
+}
+
+fn read_binary(path: &Path, max_bytes: u64) -> Result<FileBinaryContent, String> {
+    let metadata = std::fs::metadata(path).map_err(|e| e.to_string())?;
+    if metadata.is_dir() {
+        return Err("Path is a directory".to_string());
+    }
+
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut bytes = Vec::with_capacity(metadata.len().min(max_bytes) as usize);
+    file.take(max_bytes)
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+
+    let sniffed = infer::get(&bytes);
+    let is_binary = match sniffed {
+        Some(kind) => kind.matcher_type() != infer::MatcherType::Text,
+        None => bytes[..bytes.len().min(TEXT_SNIFF_BYTES)].contains(&0),
+    };
+    let mime_type = sniffed
+        .map(|kind| kind.mime_type())
+        .or_else(|| mime_from_extension(path))
+        .unwrap_or(if is_binary {
+            "application/octet-stream"
+        } else {
+            "text/plain"
+        })
+        .to_string();
+    let is_image = mime_type.starts_with("image/");
+    let dimensions = if is_image {
+        image_dimensions(&mime_type, &bytes)
+    } else {
+        None
+    };
+
+    Ok(FileBinaryContent {
+        path: path.to_string_lossy().to_string(),
+        base64: base64::engine::general_purpose::STANDARD.encode(&bytes),
+        mime_type,
+        size: metadata.len(),
+        bytes_read: bytes.len() as u64,
+        truncated: (bytes.len() as u64) < metadata.len(),
+        is_image,
+        is_binary,
+        width: dimensions.map(|(width, _)| width),
+        height: dimensions.map(|(_, height)| height),
+    })
+}
+
+/// Read a file as base64 with its MIME type, for image previews and the hex
+/// view; at most `max_bytes` (10 MB by default) are returned
+#[tauri::command]
+pub async fn get_file_binary(
+    path: String,
+    max_bytes: Option<u64>,
+) -> Result<FileBinaryContent, String> {
+    let file_path = PathBuf::from(&path);
+    let max_bytes = max_bytes.unwrap_or(DEFAULT_MAX_BYTES);
+    tokio::task::spawn_blocking(move || read_binary(&file_path, max_bytes))
+        .await
+        .map_err(|e| format!("Read task failed: {}", e))?
+}
@@ -0,0 +1,306 @@
+//! Shell Command Execution
+//!
+//! `execute_command` runs a shell command string for workspace tasks and
+//! agent tools. Output is streamed while the command runs, as
+//! `command-output` events tagged with the command ID, so long builds don't
+//! look hung; the collected output (capped per stream) and exit code are
+//! still returned at the end. Reading stops shortly after the command exits,
+//! so a background process it leaves holding the pipes can't hold up the
+//! result.
+//!
+//! A running command can be stopped with `kill_command`. On Unix the command
+//! gets its own process group and the whole group is killed, so children the
+//! shell started (dev servers, watchers) don't outlive it; on Windows the
+//! process tree is killed with `taskkill /T`. Timeouts kill the same way.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::{watch, Notify};
+
+/// Output cap per stream for execute_command
+const EXECUTE_COMMAND_MAX_OUTPUT: usize = 10 * 1024 * 1024;
+
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+
+/// Kill signals of running commands, by command ID
+static RUNNING: Lazy<Mutex<HashMap<String, Arc<Notify>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static NEXT_COMMAND_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandResult {
+    pub command_id: String,
+    pub stdout: String,
+    pub stderr: String,
+    /// -1 when the command was killed or ended by a signal
+    pub exit_code: i32,
+    pub stdout_truncated: bool,
+    pub stderr_truncated: bool,
+    pub timed_out: bool,
+    /// Stopped with `kill_command`
+    pub killed: bool,
+    pub duration_ms: u64,
+}
+
+/// A chunk of output from a running command
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandOutput {
+    pub command_id: String,
+    /// "stdout" or "stderr"
+    pub stream: String,
+    pub data: String,
+}
+
+/// Length of the prefix of `bytes` that can be decoded now; an incomplete
+/// UTF-8 sequence at the end waits for the next chunk
+fn decodable_len(bytes: &[u8]) -> usize {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => bytes.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => bytes.len(),
+    }
+}
+
+/// Forward a stream as `command-output` events, keeping at most `limit` bytes,
+/// until it ends or `stop` is set
+async fn stream_output<R: AsyncRead + Unpin>(
+    mut reader: R,
+    window: tauri::Window,
+    command_id: String,
+    stream: &'static str,
+    limit: usize,
+    mut stop: watch::Receiver<bool>,
+) -> (Vec<u8>, bool) {
+    let mut kept = Vec::new();
+    let mut truncated = false;
+    let mut pending: Vec<u8> = Vec::new();
+    let mut buf = [0u8; 8192];
+
+    loop {
+        let n = tokio::select! {
+            read = reader.read(&mut buf) => read.unwrap_or(0),
+            _ = stop.wait_for(|stop| *stop) => 0,
+        };
+        let room = limit.saturating_sub(kept.len());
+        if n > room {
+            truncated = true;
+        }
+        kept.extend_from_slice(&buf[..n.min(room)]);
+
+        pending.extend_from_slice(&buf[..n]);
+        let ready = if n == 0 {
+            pending.len()
+        } else {
+            decodable_len(&pending)
+        };
+        if ready > 0 {
+            let chunk: Vec<u8> = pending.drain(..ready).collect();
+            let output = CommandOutput {
+                command_id: command_id.clone(),
+                stream: stream.to_string(),
+                data: String::from_utf8_lossy(&chunk).to_string(),
+            };
+            if let Err(e) = window.emit("command-output", &output) {
+                eprintln!("[Command] Failed to emit output: {}", e);
+            }
+        }
+        if n == 0 {
+            break;
+        }
+    }
+    (kept, truncated)
+}
+
+/// Kill the command and everything it started
+fn kill_tree(child: &mut tokio::process::Child) {
+    let Some(pid) = child.id() else {
+        return;
+    };
+    #[cfg(unix)]
+    unsafe {
+        // The child leads its own process group
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+    #[cfg(windows)]
+    {
+        let _ = std::process::Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+    let _ = child.start_kill();
+}
+
+/// Run a shell command string, streaming its output as `command-output`
+/// events. `env` entries are added to (or override) the inherited
+/// environment; `timeout` is in milliseconds (default 30s, 0 for none).
+/// Prefer the command broker (`broker_run_command`) for anything with
+/// arguments from untrusted input; this runs whatever it is given
+#[tauri::command]
+pub async fn execute_command(
+    window: tauri::Window,
+    command: String,
+    cwd: Option<String>,
+    env: Option<HashMap<String, String>>,
+    timeout: Option<u64>,
+    command_id: Option<String>,
+) -> Result<CommandResult, String> {
+    let command_id = command_id
+        .unwrap_or_else(|| format!("cmd-{}", NEXT_COMMAND_ID.fetch_add(1, Ordering::Relaxed)));
+
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut c = tokio::process::Command::new("cmd");
+        c.args(["/C", &command]);
+        c
+    } else {
+        let mut c = tokio::process::Command::new("sh");
+        c.args(["-c", &command]);
+        c
+    };
+    if let Some(dir) = cwd {
+        cmd.current_dir(dir);
+    }
+    if let Some(env) = env {
+        cmd.envs(env);
+    }
+    #[cfg(unix)]
+    cmd.process_group(0);
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let kill = Arc::new(Notify::new());
+    {
+        let mut running = RUNNING
+            .lock()
+            .map_err(|e| format!("Failed to acquire command lock: {}", e))?;
+        if running.contains_key(&command_id) {
+            return Err(format!("Command {} is already running", command_id));
+        }
+        running.insert(command_id.clone(), kill.clone());
+    }
+    let unregister = |id: &str| {
+        if let Ok(mut running) = RUNNING.lock() {
+            running.remove(id);
+        }
+    };
+
+    let started = Instant::now();
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            unregister(&command_id);
+            return Err(format!("Failed to spawn command: {}", e));
+        }
+    };
+    let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        kill_tree(&mut child);
+        unregister(&command_id);
+        return Err("Failed to capture command output".to_string());
+    };
+    let (stop_output, stop) = watch::channel(false);
+    let stdout_task = tokio::spawn(stream_output(
+        stdout,
+        window.clone(),
+        command_id.clone(),
+        "stdout",
+        EXECUTE_COMMAND_MAX_OUTPUT,
+        stop.clone(),
+    ));
+    let stderr_task = tokio::spawn(stream_output(
+        stderr,
+        window,
+        command_id.clone(),
+        "stderr",
+        EXECUTE_COMMAND_MAX_OUTPUT,
+        stop,
+    ));
+
+    let timeout_ms = timeout.unwrap_or(DEFAULT_TIMEOUT_MS);
+    let deadline = async {
+        if timeout_ms == 0 {
+            std::future::pending::<()>().await
+        } else {
+            tokio::time::sleep(Duration::from_millis(timeout_ms)).await
+        }
+    };
+
+    let mut timed_out = false;
+    let mut killed = false;
+    let status = tokio::select! {
+        status = child.wait() => Some(status),
+        _ = deadline => {
+            timed_out = true;
+            None
+        }
+        _ = kill.notified() => {
+            killed = true;
+            None
+        }
+    };
+    let exit_code = match status {
+        Some(Ok(status)) => status.code(),
+        Some(Err(e)) => {
+            unregister(&command_id);
+            return Err(format!("Failed to wait for command: {}", e));
+        }
+        None => {
+            kill_tree(&mut child);
+            let _ = child.wait().await;
+            None
+        }
+    };
+    unregister(&command_id);
+
+    let ((stdout, stdout_truncated), (stderr, stderr_truncated)) =
+        crate::command_broker::collect_output(stdout_task, stderr_task, stop_output).await;
+    let duration_ms = started.elapsed().as_millis() as u64;
+    if timed_out || killed {
+        println!(
+            "[Command] {} {} after {}ms",
+            command_id,
+            if killed { "killed" } else { "timed out" },
+            duration_ms
+        );
+    }
+
+    Ok(CommandResult {
+        command_id,
+        stdout: String::from_utf8_lossy(&stdout).to_string(),
+        stderr: String::from_utf8_lossy(&stderr).to_string(),
+        exit_code: exit_code.unwrap_or(-1),
+        stdout_truncated,
+        stderr_truncated,
+        timed_out,
+        killed,
+        duration_ms,
+    })
+}
+
+/// Kill a command started with `execute_command`, including the processes
+/// it started; returns false if no such command is running
+#[tauri::command]
+pub fn kill_command(command_id: String) -> Result<bool, String> {
+    let running = RUNNING
+        .lock()
+        .map_err(|e| format!("Failed to acquire command lock: {}", e))?;
+    match running.get(&command_id) {
+        Some(kill) => {
+            kill.notify_one();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
@@ -0,0 +1,291 @@
+//! Copy and Duplicate
+//!
+//! `copy_path` copies a file or a whole folder. When the destination already
+//! exists the strategy decides what happens:
+//! - `fail` (default): return an error
+//! - `overwrite`: replace the destination. The copy is made next to it
+//!   first and only moved into place once complete, so a failed copy leaves
+//!   the destination untouched
+//! - `auto_rename`: pick the next free "name (2).ext", which also makes
+//!   copying an item onto itself a duplicate
+//!
+//! Files keep their permission bits, and so do folders once their contents
+//! are in place. Symlinks are copied as links on Unix. Copying a folder
+//! reports `copy-path-progress` events with file and byte counts.
+
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+static NEXT_COPY_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyProgress {
+    pub copy_id: String,
+    pub source: String,
+    pub destination: String,
+    pub files_copied: u64,
+    pub total_files: u64,
+    pub bytes_copied: u64,
+    pub total_bytes: u64,
+    pub done: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyResult {
+    pub copy_id: String,
+    pub source: String,
+    /// Where the copy ended up, which differs from the requested destination
+    /// after an auto-rename
+    pub destination: String,
+    pub files_copied: u64,
+    pub bytes_copied: u64,
+    pub renamed: bool,
+}
+
+/// Files and bytes copied so far
+#[derive(Default, Clone, Copy)]
+pub(super) struct CopyCounts {
+    pub files: u64,
+    pub bytes: u64,
+}
+
+/// Copy a file, folder or symlink to a path that doesn't exist yet, calling
+/// `progress` after each file
+pub(super) fn copy_tree(
+    from: &Path,
+    to: &Path,
+    counts: &mut CopyCounts,
+    progress: &mut dyn FnMut(CopyCounts),
+) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(from)?;
+    #[cfg(unix)]
+    if metadata.file_type().is_symlink() {
+        std::os::unix::fs::symlink(fs::read_link(from)?, to)?;
+        counts.files += 1;
+        progress(*counts);
+        return Ok(());
+    }
+    if fs::metadata(from)?.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_tree(&entry.path(), &to.join(entry.file_name()), counts, progress)?;
+        }
+        fs::set_permissions(to, metadata.permissions())
+    } else {
+        // Also copies the permission bits
+        counts.bytes += fs::copy(from, to)?;
+        counts.files += 1;
+        progress(*counts);
+        Ok(())
+    }
+}
+
+/// Files and bytes below `path`, for progress totals
+fn measure(path: &Path) -> CopyCounts {
+    let mut counts = CopyCounts::default();
+    for entry in walkdir::WalkDir::new(path).into_iter().flatten() {
+        if !entry.file_type().is_dir() {
+            counts.files += 1;
+            counts.bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+        }
+    }
+    counts
+}
+
+/// "name (2).ext", "name (3).ext", ... next to `path`, the first that's free
+fn auto_renamed(path: &Path) -> PathBuf {
+    let parent = path.parent().unwrap_or_else(|| Path::new(""));
+    let is_dir = path.is_dir();
+    let (stem, extension) = match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(extension)) if !is_dir => (
+            stem.to_string_lossy().to_string(),
+            format!(".{}", extension.to_string_lossy()),
+        ),
+        _ => (
+            path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            String::new(),
+        ),
+    };
+    (2..)
+        .map(|n| parent.join(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+fn remove_existing(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Hidden path next to `path` for an in-progress copy or a replaced item
+fn sibling_path(path: &Path, copy_id: &str, purpose: &str) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    // Copy ids come from the caller and may contain separators
+    let id: String = copy_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    path.with_file_name(format!(".{}.{}-{}", name, purpose, id))
+}
+
+/// Move a finished copy over `target`. The existing item is set aside first,
+/// as a folder can't be renamed over, and put back if the move fails.
+fn replace_with(staged: &Path, target: &Path, copy_id: &str) -> io::Result<()> {
+    let replaced = sibling_path(target, copy_id, "replaced");
+    fs::rename(target, &replaced)?;
+    if let Err(e) = fs::rename(staged, target) {
+        let _ = fs::rename(&replaced, target);
+        return Err(e);
+    }
+    if let Err(e) = remove_existing(&replaced) {
+        eprintln!(
+            "[Copy] Failed to remove replaced {}: {}",
+            replaced.display(),
+            e
+        );
+    }
+    Ok(())
+}
+
+fn copy_blocking(
+    window: &tauri::Window,
+    copy_id: &str,
+    source: &Path,
+    destination: &Path,
+    strategy: &str,
+) -> Result<CopyResult, String> {
+    let source_metadata = fs::symlink_metadata(source).map_err(|e| e.to_string())?;
+    let mut target = destination.to_path_buf();
+    let mut renamed = false;
+    let mut overwrite = false;
+
+    if fs::symlink_metadata(&target).is_ok() {
+        match strategy {
+            "fail" => return Err(format!("{} already exists", target.display())),
+            "auto_rename" => {
+                target = auto_renamed(&target);
+                renamed = true;
+            }
+            "overwrite" => {
+                let same = fs::canonicalize(source).ok() == fs::canonicalize(&target).ok();
+                if same || source.starts_with(&target) || target.starts_with(source) {
+                    return Err("Cannot overwrite the item being copied".to_string());
+                }
+                overwrite = true;
+            }
+            other => {
+                return Err(format!(
+                    "Unknown conflict strategy '{}'; use fail, overwrite or auto_rename",
+                    other
+                ))
+            }
+        }
+    }
+    if source_metadata.is_dir() && target.starts_with(source) {
+        return Err(format!("Cannot copy {} into itself", source.display()));
+    }
+
+    let total = if source_metadata.is_dir() {
+        measure(source)
+    } else {
+        CopyCounts {
+            files: 1,
+            bytes: source_metadata.len(),
+        }
+    };
+    let progress_event = |counts: CopyCounts, done: bool| CopyProgress {
+        copy_id: copy_id.to_string(),
+        source: source.to_string_lossy().to_string(),
+        destination: target.to_string_lossy().to_string(),
+        files_copied: counts.files,
+        total_files: total.files,
+        bytes_copied: counts.bytes,
+        total_bytes: total.bytes,
+        done,
+    };
+
+    let staged = if overwrite {
+        sibling_path(&target, copy_id, "copy")
+    } else {
+        target.clone()
+    };
+    let mut last_progress = Instant::now();
+    let mut counts = CopyCounts::default();
+    let copied = copy_tree(source, &staged, &mut counts, &mut |counts| {
+        if source_metadata.is_dir() && last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            let _ = window.emit("copy-path-progress", progress_event(counts, false));
+        }
+    });
+    if let Err(e) = copied {
+        let _ = remove_existing(&staged);
+        return Err(format!("Failed to copy {}: {}", source.display(), e));
+    }
+    if overwrite {
+        if let Err(e) = replace_with(&staged, &target, copy_id) {
+            let _ = remove_existing(&staged);
+            return Err(format!("Failed to replace {}: {}", target.display(), e));
+        }
+    }
+    if source_metadata.is_dir() {
+        let _ = window.emit("copy-path-progress", progress_event(counts, true));
+    }
+
+    println!(
+        "[Copy] {} -> {} ({} files, {} bytes)",
+        source.display(),
+        target.display(),
+        counts.files,
+        counts.bytes
+    );
+    Ok(CopyResult {
+        copy_id: copy_id.to_string(),
+        source: source.to_string_lossy().to_string(),
+        destination: target.to_string_lossy().to_string(),
+        files_copied: counts.files,
+        bytes_copied: counts.bytes,
+        renamed,
+    })
+}
+
+/// Copy a file or folder; `strategy` ("fail", "overwrite" or "auto_rename")
+/// decides what happens when `dest` exists
+#[tauri::command]
+pub async fn copy_path(
+    window: tauri::Window,
+    src: String,
+    dest: String,
+    strategy: Option<String>,
+    copy_id: Option<String>,
+) -> Result<CopyResult, String> {
+    let copy_id =
+        copy_id.unwrap_or_else(|| format!("copy-{}", NEXT_COPY_ID.fetch_add(1, Ordering::Relaxed)));
+    let strategy = strategy.unwrap_or_else(|| "fail".to_string());
+    tokio::task::spawn_blocking(move || {
+        copy_blocking(
+            &window,
+            &copy_id,
+            Path::new(&src),
+            Path::new(&dest),
+            &strategy,
+        )
+    })
+    .await
+    .map_err(|e| format!("Copy task failed: {}", e))?
+}
@@ -0,0 +1,280 @@
+//! Directory Statistics
+//!
+//! `compute_directory_stats` backs the folder properties dialog and the disk
+//! usage view: total size, file and folder counts, the largest files, the
+//! largest immediate subfolders and a per-extension breakdown.
+//!
+//! Unlike the file tree this counts everything, ignored folders included,
+//! since those are usually where the space goes. Subfolders are walked in
+//! parallel on the rayon pool; symlinks are counted but not followed, and
+//! sizes are apparent sizes. While the walk runs, `directory-stats-progress`
+//! events report the running totals to the requesting window.
+
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+const DEFAULT_LARGEST_COUNT: usize = 20;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SizedEntry {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionStats {
+    /// Lowercase, without the dot; empty for files without one
+    pub extension: String,
+    pub file_count: u64,
+    pub total_size: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryStats {
+    pub path: String,
+    pub total_size: u64,
+    pub file_count: u64,
+    /// Folders below the root
+    pub directory_count: u64,
+    pub symlink_count: u64,
+    pub largest_files: Vec<SizedEntry>,
+    /// Immediate subfolders by total size
+    pub largest_directories: Vec<SizedEntry>,
+    /// By total size, largest first
+    pub extensions: Vec<ExtensionStats>,
+    /// Entries that could not be read
+    pub unreadable_count: u64,
+    pub duration_ms: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryStatsProgress {
+    pub path: String,
+    pub file_count: u64,
+    pub directory_count: u64,
+    pub total_size: u64,
+}
+
+/// Totals of one subtree, merged upwards
+#[derive(Default)]
+struct Totals {
+    size: u64,
+    files: u64,
+    directories: u64,
+    symlinks: u64,
+    unreadable: u64,
+    /// Largest first, at most `largest_count` long
+    largest: Vec<(PathBuf, u64)>,
+    extensions: HashMap<String, (u64, u64)>,
+}
+
+impl Totals {
+    fn add_largest(&mut self, path: PathBuf, size: u64, limit: usize) {
+        if self.largest.len() >= limit && self.largest.last().is_some_and(|(_, s)| *s >= size) {
+            return;
+        }
+        let at = self.largest.partition_point(|(_, s)| *s >= size);
+        self.largest.insert(at, (path, size));
+        self.largest.truncate(limit);
+    }
+
+    fn merge(mut self, other: Totals, limit: usize) -> Totals {
+        self.size += other.size;
+        self.files += other.files;
+        self.directories += other.directories;
+        self.symlinks += other.symlinks;
+        self.unreadable += other.unreadable;
+        for (path, size) in other.largest {
+            self.add_largest(path, size, limit);
+        }
+        for (extension, (count, size)) in other.extensions {
+            let entry = self.extensions.entry(extension).or_default();
+            entry.0 += count;
+            entry.1 += size;
+        }
+        self
+    }
+}
+
+struct Walk {
+    window: tauri::Window,
+    root: String,
+    largest_count: usize,
+    files: AtomicU64,
+    directories: AtomicU64,
+    size: AtomicU64,
+    last_progress: Mutex<Instant>,
+}
+
+impl Walk {
+    fn report_progress(&self) {
+        let Ok(mut last) = self.last_progress.try_lock() else {
+            return;
+        };
+        if last.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+        *last = Instant::now();
+        let progress = DirectoryStatsProgress {
+            path: self.root.clone(),
+            file_count: self.files.load(Ordering::Relaxed),
+            directory_count: self.directories.load(Ordering::Relaxed),
+            total_size: self.size.load(Ordering::Relaxed),
+        };
+        if let Err(e) = self.window.emit("directory-stats-progress", &progress) {
+            eprintln!("[DirectoryStats] Failed to emit progress: {}", e);
+        }
+    }
+
+    /// Totals of `dir`, and of each of its subfolders
+    fn walk(&self, dir: &Path) -> (Totals, Vec<(PathBuf, u64)>) {
+        let mut totals = Totals::default();
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => {
+                totals.unreadable += 1;
+                return (totals, Vec::new());
+            }
+        };
+
+        let mut subdirectories = Vec::new();
+        for entry in entries {
+            let Ok(entry) = entry else {
+                totals.unreadable += 1;
+                continue;
+            };
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                totals.unreadable += 1;
+                continue;
+            };
+            if file_type.is_symlink() {
+                totals.symlinks += 1;
+            } else if file_type.is_dir() {
+                subdirectories.push(path);
+            } else {
+                let Ok(metadata) = entry.metadata() else {
+                    totals.unreadable += 1;
+                    continue;
+                };
+                let size = metadata.len();
+                let extension = path
+                    .extension()
+                    .map(|e| e.to_string_lossy().to_lowercase())
+                    .unwrap_or_default();
+                let stats = totals.extensions.entry(extension).or_default();
+                stats.0 += 1;
+                stats.1 += size;
+                totals.size += size;
+                totals.files += 1;
+                totals.add_largest(path, size, self.largest_count);
+                self.files.fetch_add(1, Ordering::Relaxed);
+                self.size.fetch_add(size, Ordering::Relaxed);
+            }
+        }
+        self.report_progress();
+
+        let children: Vec<(PathBuf, Totals)> = subdirectories
+            .into_par_iter()
+            .map(|subdirectory| {
+                self.directories.fetch_add(1, Ordering::Relaxed);
+                let (mut subtotals, _) = self.walk(&subdirectory);
+                subtotals.directories += 1;
+                (subdirectory, subtotals)
+            })
+            .collect();
+
+        let mut sizes = Vec::with_capacity(children.len());
+        for (subdirectory, subtotals) in children {
+            sizes.push((subdirectory, subtotals.size));
+            totals = totals.merge(subtotals, self.largest_count);
+        }
+        (totals, sizes)
+    }
+}
+
+fn sized_entries(entries: Vec<(PathBuf, u64)>) -> Vec<SizedEntry> {
+    entries
+        .into_iter()
+        .map(|(path, size)| SizedEntry {
+            path: path.to_string_lossy().to_string(),
+            size,
+        })
+        .collect()
+}
+
+/// Size, counts, largest entries and per-extension totals of a folder,
+/// reporting `directory-stats-progress` events while it walks
+#[tauri::command]
+pub async fn compute_directory_stats(
+    window: tauri::Window,
+    path: String,
+    largest_count: Option<usize>,
+) -> Result<DirectoryStats, String> {
+    let root = PathBuf::from(&path);
+    if !fs::metadata(&root).map_err(|e| e.to_string())?.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+
+    let started = Instant::now();
+    let walk = Walk {
+        window,
+        root: path.clone(),
+        largest_count: largest_count.unwrap_or(DEFAULT_LARGEST_COUNT).max(1),
+        files: AtomicU64::new(0),
+        directories: AtomicU64::new(0),
+        size: AtomicU64::new(0),
+        last_progress: Mutex::new(Instant::now()),
+    };
+    let (totals, mut directories) = tokio::task::spawn_blocking(move || walk.walk(&root))
+        .await
+        .map_err(|e| format!("Directory stats task failed: {}", e))?;
+
+    directories.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+    directories.truncate(largest_count.unwrap_or(DEFAULT_LARGEST_COUNT).max(1));
+    let mut extensions: Vec<ExtensionStats> = totals
+        .extensions
+        .into_iter()
+        .map(|(extension, (file_count, total_size))| ExtensionStats {
+            extension,
+            file_count,
+            total_size,
+        })
+        .collect();
+    extensions.sort_by(|a, b| {
+        b.total_size
+            .cmp(&a.total_size)
+            .then_with(|| a.extension.cmp(&b.extension))
+    });
+
+    let duration_ms = started.elapsed().as_millis() as u64;
+    println!(
+        "[DirectoryStats] {}: {} files, {} bytes in {}ms",
+        path, totals.files, totals.size, duration_ms
+    );
+    Ok(DirectoryStats {
+        path,
+        total_size: totals.size,
+        file_count: totals.files,
+        directory_count: totals.directories,
+        symlink_count: totals.symlinks,
+        largest_files: sized_entries(totals.largest),
+        largest_directories: sized_entries(directories),
+        extensions,
+        unreadable_count: totals.unreadable,
+        duration_ms,
+    })
+}
@@ -0,0 +1,141 @@
+//! File Checksums
+//!
+//! After the window regains focus the editor needs to know which open buffers
+//! no longer match the disk. Reading every file into JS to compare is slow
+//! for large workspaces; `hash_file` / `hash_files` stream the files here and
+//! return a digest to compare against the hash of the buffered content.
+//!
+//! `blake3` (default) is the fast choice; `sha256` matches the hashes the
+//! open files tracker already stores.
+
+use rayon::prelude::*;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileHash {
+    pub path: String,
+    pub algorithm: String,
+    /// Lowercase hex digest; `None` when the file could not be read
+    pub hash: Option<String>,
+    pub size: u64,
+    /// Modification time in milliseconds since the epoch
+    pub modified: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+enum Algorithm {
+    Blake3,
+    Sha256,
+}
+
+impl Algorithm {
+    fn parse(name: Option<&str>) -> Result<Self, String> {
+        match name.map(|n| n.to_ascii_lowercase()).as_deref() {
+            None | Some("blake3") => Ok(Algorithm::Blake3),
+            Some("sha256") | Some("sha-256") => Ok(Algorithm::Sha256),
+            Some(other) => Err(format!(
+                "Unknown hash algorithm '{}'; use blake3 or sha256",
+                other
+            )),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Algorithm::Blake3 => "blake3",
+            Algorithm::Sha256 => "sha256",
+        }
+    }
+}
+
+fn digest(path: &Path, algorithm: Algorithm) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut buf = vec![0u8; READ_BUFFER_SIZE];
+    match algorithm {
+        Algorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            loop {
+                let n = file.read(&mut buf)?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buf[..n]);
+            }
+            Ok(hasher.finalize().to_hex().to_string())
+        }
+        Algorithm::Sha256 => {
+            let mut hasher = Sha256::new();
+            loop {
+                let n = file.read(&mut buf)?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buf[..n]);
+            }
+            Ok(format!("{:x}", hasher.finalize()))
+        }
+    }
+}
+
+fn hash_one(path: &str, algorithm: Algorithm) -> FileHash {
+    let mut result = FileHash {
+        path: path.to_string(),
+        algorithm: algorithm.name().to_string(),
+        hash: None,
+        size: 0,
+        modified: 0,
+        error: None,
+    };
+    let hashed = fs::metadata(path).and_then(|metadata| {
+        if metadata.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Path is a directory",
+            ));
+        }
+        result.size = metadata.len();
+        result.modified = super::modified_millis(&metadata);
+        digest(Path::new(path), algorithm)
+    });
+    match hashed {
+        Ok(hash) => result.hash = Some(hash),
+        Err(e) => result.error = Some(e.to_string()),
+    }
+    result
+}
+
+/// Checksum of a file's content; `algo` is "blake3" (default) or "sha256"
+#[tauri::command]
+pub async fn hash_file(path: String, algo: Option<String>) -> Result<FileHash, String> {
+    let algorithm = Algorithm::parse(algo.as_deref())?;
+    let result = tokio::task::spawn_blocking(move || hash_one(&path, algorithm))
+        .await
+        .map_err(|e| format!("Hash task failed: {}", e))?;
+    match result.error {
+        Some(error) => Err(error),
+        None => Ok(result),
+    }
+}
+
+/// Checksums of several files, hashed in parallel; unreadable files are
+/// reported with `error` set instead of failing the whole call
+#[tauri::command]
+pub async fn hash_files(paths: Vec<String>, algo: Option<String>) -> Result<Vec<FileHash>, String> {
+    let algorithm = Algorithm::parse(algo.as_deref())?;
+    tokio::task::spawn_blocking(move || {
+        paths
+            .par_iter()
+            .map(|path| hash_one(path, algorithm))
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Hash task failed: {}", e))
+}
@@ -0,0 +1,448 @@
+//! Workspace File Index
+//!
+//! Quick Open needs every file of the workspace on each keystroke. The index
+//! is built once per workspace with the same ignore rules as the file tree,
+//! then kept current from the project watcher's events, so
+//! `fuzzy_find_files` only has to score paths in memory.
+//!
+//! Scoring follows fzf: matched characters score, gaps cost, and matches at
+//! the start of a path segment, after `_`/`-`/`.` or at a camelCase hump get a
+//! bonus, with the first query character counting double. Matches that lie
+//! entirely in the file name rank above ones spread over folders. A query
+//! with an uppercase letter is case-sensitive; spaces are ignored.
+
+use super::{ignore_walker, list_unignored_entries};
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Files indexed per workspace at most
+const MAX_INDEXED_FILES: usize = 500_000;
+
+const DEFAULT_LIMIT: usize = 50;
+
+const SCORE_MATCH: i32 = 16;
+const PENALTY_GAP_START: i32 = -3;
+const PENALTY_GAP_EXTENSION: i32 = -1;
+const BONUS_PATH_SEGMENT: i32 = 10;
+const BONUS_BOUNDARY: i32 = 8;
+const BONUS_CAMEL: i32 = 7;
+const BONUS_CONSECUTIVE: i32 = 4;
+const BONUS_FIRST_CHAR_MULTIPLIER: i32 = 2;
+const BONUS_FILE_NAME: i32 = 16;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileIndexStatus {
+    pub root: String,
+    pub file_count: usize,
+    /// Unix timestamp (ms) of the last full build
+    pub built_at: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FuzzyFileMatch {
+    pub path: String,
+    pub relative_path: String,
+    pub name: String,
+    pub root: String,
+    pub score: i32,
+    /// Character indices of the matched characters in `relative_path`
+    pub positions: Vec<usize>,
+}
+
+struct FileIndex {
+    root: PathBuf,
+    /// The root as it was given, which watcher events may use
+    alias: PathBuf,
+    /// Relative path (with `/`) to its lowercase form
+    files: HashMap<String, String>,
+    /// Relative paths of the folders walked; "" is the root
+    dirs: HashSet<String>,
+    built_at: i64,
+}
+
+impl FileIndex {
+    fn build(root: PathBuf, alias: PathBuf) -> FileIndex {
+        let mut index = FileIndex {
+            root,
+            alias,
+            files: HashMap::new(),
+            dirs: HashSet::from([String::new()]),
+            built_at: chrono::Utc::now().timestamp_millis(),
+        };
+        let root = index.root.clone();
+        index.add_tree(&root);
+        index
+    }
+
+    fn relative(&self, path: &Path) -> Option<String> {
+        let relative = path
+            .strip_prefix(&self.root)
+            .or_else(|_| path.strip_prefix(&self.alias))
+            .ok()?;
+        Some(relative.to_string_lossy().replace('\\', "/"))
+    }
+
+    /// Add every file under a folder that is not ignored
+    fn add_tree(&mut self, dir: &Path) {
+        for entry in ignore_walker(dir, false).build().filter_map(|e| e.ok()) {
+            let Some(relative) = self.relative(entry.path()) else {
+                continue;
+            };
+            let is_dir = match entry.file_type() {
+                Some(file_type) if file_type.is_symlink() => entry.path().is_dir(),
+                Some(file_type) => file_type.is_dir(),
+                None => continue,
+            };
+            if is_dir {
+                self.dirs.insert(relative);
+            } else if self.files.len() < MAX_INDEXED_FILES {
+                self.insert_file(relative);
+            }
+        }
+    }
+
+    fn insert_file(&mut self, relative: String) {
+        let lower = relative.to_lowercase();
+        self.files.insert(relative, lower);
+    }
+
+    /// Remove a file, or a folder and everything under it
+    fn remove(&mut self, relative: &str) {
+        if self.files.remove(relative).is_some() || !self.dirs.remove(relative) {
+            return;
+        }
+        let prefix = format!("{}/", relative);
+        self.files.retain(|path, _| !path.starts_with(&prefix));
+        self.dirs.retain(|path| !path.starts_with(&prefix));
+    }
+
+    fn status(&self) -> FileIndexStatus {
+        FileIndexStatus {
+            root: self.root.to_string_lossy().to_string(),
+            file_count: self.files.len(),
+            built_at: self.built_at,
+        }
+    }
+}
+
+static FILE_INDEXES: Lazy<RwLock<HashMap<PathBuf, FileIndex>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+fn canonical_root(workspace_root: &str) -> PathBuf {
+    PathBuf::from(workspace_root)
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(workspace_root))
+}
+
+fn build_index(workspace_root: &str) -> Result<FileIndexStatus, String> {
+    let root = canonical_root(workspace_root);
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", workspace_root));
+    }
+    let started = std::time::Instant::now();
+    let index = FileIndex::build(root.clone(), PathBuf::from(workspace_root));
+    let status = index.status();
+    println!(
+        "[FileIndex] Indexed {} files in {} ({} ms)",
+        status.file_count,
+        root.display(),
+        started.elapsed().as_millis()
+    );
+    FILE_INDEXES
+        .write()
+        .map_err(|e| e.to_string())?
+        .insert(root, index);
+    Ok(status)
+}
+
+/// Apply watcher events to the indexes containing the changed paths
+/// Each changed entry is checked against a fresh listing of its folder, so
+/// ignore rules apply and files under ignored folders never get in
+pub(super) fn apply_changes(paths: &[&PathBuf]) {
+    let Ok(mut indexes) = FILE_INDEXES.write() else {
+        return;
+    };
+    for index in indexes.values_mut() {
+        // Changed entries by the folder containing them
+        let mut by_parent: HashMap<String, Vec<(String, PathBuf)>> = HashMap::new();
+        for path in paths {
+            let Some(relative) = index.relative(path) else {
+                continue;
+            };
+            if relative.is_empty() {
+                continue;
+            }
+            let parent = relative
+                .rsplit_once('/')
+                .map(|(parent, _)| parent.to_string())
+                .unwrap_or_default();
+            by_parent
+                .entry(parent)
+                .or_default()
+                .push((relative, (*path).clone()));
+        }
+
+        for (parent, changed) in by_parent {
+            if !index.dirs.contains(&parent) {
+                continue;
+            }
+            let listed: HashSet<std::ffi::OsString> =
+                list_unignored_entries(&index.root.join(&parent), false)
+                    .into_iter()
+                    .filter_map(|path| path.file_name().map(|name| name.to_os_string()))
+                    .collect();
+            for (relative, path) in changed {
+                let present = path.file_name().is_some_and(|name| listed.contains(name));
+                if !present {
+                    index.remove(&relative);
+                } else if path.is_dir() {
+                    if !index.dirs.contains(&relative) {
+                        index.add_tree(&index.root.join(&relative));
+                    }
+                } else if !index.files.contains_key(&relative)
+                    && index.files.len() < MAX_INDEXED_FILES
+                {
+                    index.insert_file(relative);
+                }
+            }
+        }
+    }
+}
+
+fn lower(c: char) -> char {
+    if c.is_ascii() {
+        c.to_ascii_lowercase()
+    } else {
+        c.to_lowercase().next().unwrap_or(c)
+    }
+}
+
+/// Whether the query characters appear in order; `text` has the query's case
+fn contains_in_order(text: &str, query: &[char]) -> bool {
+    let mut remaining = query.iter().peekable();
+    for c in text.chars() {
+        if remaining.peek() == Some(&&c) {
+            remaining.next();
+        }
+    }
+    remaining.peek().is_none()
+}
+
+fn bonus_at(text: &[char], i: usize) -> i32 {
+    if i == 0 {
+        return BONUS_PATH_SEGMENT;
+    }
+    let (prev, current) = (text[i - 1], text[i]);
+    match prev {
+        '/' | '\\' => BONUS_PATH_SEGMENT,
+        '_' | '-' | '.' | ' ' => BONUS_BOUNDARY,
+        _ if prev.is_lowercase() && current.is_uppercase() => BONUS_CAMEL,
+        _ if !prev.is_ascii_digit() && current.is_ascii_digit() => BONUS_CAMEL,
+        _ => 0,
+    }
+}
+
+/// Score a path against the query, with the indices of the matched characters
+fn fuzzy_score(text: &str, query: &[char], case_sensitive: bool) -> Option<(i32, Vec<usize>)> {
+    let chars: Vec<char> = text.chars().collect();
+    let matches = |c: char, q: char| {
+        if case_sensitive {
+            c == q
+        } else {
+            lower(c) == q
+        }
+    };
+
+    // Forward pass: where the first complete match ends
+    let mut qi = 0;
+    let mut end = 0;
+    for (i, &c) in chars.iter().enumerate() {
+        if matches(c, query[qi]) {
+            qi += 1;
+            if qi == query.len() {
+                end = i;
+                break;
+            }
+        }
+    }
+    if qi < query.len() {
+        return None;
+    }
+
+    // Backward pass: the latest start, for the tightest window
+    let mut qi = query.len() - 1;
+    let mut start = end;
+    for i in (0..=end).rev() {
+        if matches(chars[i], query[qi]) {
+            if qi == 0 {
+                start = i;
+                break;
+            }
+            qi -= 1;
+        }
+    }
+
+    let mut score = 0;
+    let mut positions = Vec::with_capacity(query.len());
+    let mut qi = 0;
+    let mut in_gap = false;
+    let mut consecutive = 0;
+    let mut first_bonus = 0;
+    for i in start..=end {
+        if qi < query.len() && matches(chars[i], query[qi]) {
+            let mut bonus = bonus_at(&chars, i);
+            if consecutive == 0 {
+                first_bonus = bonus;
+            } else {
+                // A run keeps the bonus of the boundary it started at
+                if bonus >= BONUS_BOUNDARY && bonus > first_bonus {
+                    first_bonus = bonus;
+                }
+                bonus = bonus.max(first_bonus).max(BONUS_CONSECUTIVE);
+            }
+            score += SCORE_MATCH
+                + if qi == 0 {
+                    bonus * BONUS_FIRST_CHAR_MULTIPLIER
+                } else {
+                    bonus
+                };
+            positions.push(i);
+            in_gap = false;
+            consecutive += 1;
+            qi += 1;
+        } else {
+            score += if in_gap {
+                PENALTY_GAP_EXTENSION
+            } else {
+                PENALTY_GAP_START
+            };
+            in_gap = true;
+            consecutive = 0;
+        }
+    }
+
+    let name_start = chars
+        .iter()
+        .rposition(|&c| c == '/')
+        .map_or(0, |slash| slash + 1);
+    if start >= name_start {
+        score += BONUS_FILE_NAME;
+    }
+    Some((score, positions))
+}
+
+/// Build (or rebuild) the file index of a workspace
+#[tauri::command]
+pub async fn build_file_index(workspace_root: String) -> Result<FileIndexStatus, String> {
+    tokio::task::spawn_blocking(move || build_index(&workspace_root))
+        .await
+        .map_err(|e| format!("File indexing failed: {}", e))?
+}
+
+/// Rank indexed files against a fuzzy query, best first
+/// Searches the given workspace (building its index on first use) or every
+/// indexed workspace
+#[tauri::command]
+pub async fn fuzzy_find_files(
+    query: String,
+    limit: Option<usize>,
+    workspace_root: Option<String>,
+) -> Result<Vec<FuzzyFileMatch>, String> {
+    tokio::task::spawn_blocking(move || find_files(&query, limit, workspace_root.as_deref()))
+        .await
+        .map_err(|e| format!("File search failed: {}", e))?
+}
+
+fn find_files(
+    query: &str,
+    limit: Option<usize>,
+    workspace_root: Option<&str>,
+) -> Result<Vec<FuzzyFileMatch>, String> {
+    let root = workspace_root.map(canonical_root);
+    if let (Some(root), Some(workspace_root)) = (&root, workspace_root) {
+        let indexed = FILE_INDEXES
+            .read()
+            .map_err(|e| e.to_string())?
+            .contains_key(root);
+        if !indexed {
+            build_index(workspace_root)?;
+        }
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    let case_sensitive = query.iter().any(|c| c.is_uppercase());
+    let query: Vec<char> = if case_sensitive {
+        query
+    } else {
+        query.into_iter().map(lower).collect()
+    };
+
+    let indexes = FILE_INDEXES.read().map_err(|e| e.to_string())?;
+    let mut matches: Vec<FuzzyFileMatch> = Vec::new();
+    for index in indexes.values() {
+        if root.as_ref().is_some_and(|root| *root != index.root) {
+            continue;
+        }
+        let to_match = |relative: &String, score: i32, positions: Vec<usize>| FuzzyFileMatch {
+            path: index.root.join(relative).to_string_lossy().to_string(),
+            name: relative
+                .rsplit_once('/')
+                .map_or(relative.as_str(), |(_, name)| name)
+                .to_string(),
+            relative_path: relative.clone(),
+            root: index.root.to_string_lossy().to_string(),
+            score,
+            positions,
+        };
+
+        if query.is_empty() {
+            matches.extend(
+                index
+                    .files
+                    .keys()
+                    .map(|relative| to_match(relative, 0, Vec::new())),
+            );
+            continue;
+        }
+        let found: Vec<FuzzyFileMatch> = index
+            .files
+            .par_iter()
+            .filter_map(|(relative, lowercase)| {
+                let candidate = if case_sensitive { relative } else { lowercase };
+                if !contains_in_order(candidate, &query) {
+                    return None;
+                }
+                let (score, positions) = fuzzy_score(relative, &query, case_sensitive)?;
+                Some(to_match(relative, score, positions))
+            })
+            .collect();
+        matches.extend(found);
+    }
+
+    matches.sort_unstable_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.relative_path.len().cmp(&b.relative_path.len()))
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    matches.truncate(limit);
+    Ok(matches)
+}
+
+/// Index status of a workspace, if it has been indexed
+#[tauri::command]
+pub fn get_file_index_status(workspace_root: String) -> Result<Option<FileIndexStatus>, String> {
+    let root = canonical_root(&workspace_root);
+    Ok(FILE_INDEXES
+        .read()
+        .map_err(|e| e.to_string())?
+        .get(&root)
+        .map(FileIndex::status))
+}
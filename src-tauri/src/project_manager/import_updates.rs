@@ -0,0 +1,277 @@
+//! Import path updates for file moves
+//!
+//! Fallback for when no language server answers `workspace/willRenameFiles`:
+//! finds relative module specifiers (`import … from './x'`, `import('./x')`,
+//! `require('./x')`, `export … from`, CSS `@import`) in JS/TS-family sources
+//! and rewrites those that point at a moved file, plus the moved files' own
+//! relative imports. The specifier's style is kept: an omitted extension or
+//! `/index` stays omitted.
+//!
+//! Specifiers are found with tree-sitter, so strings and comments that merely
+//! look like imports are left alone. Vue and Svelte components and SCSS have
+//! no grammar here and are matched with a pattern instead.
+
+use ignore::WalkBuilder;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use tree_sitter::{Node, Parser};
+
+/// Sources scanned for imports
+const SOURCE_EXTENSIONS: [&str; 12] = [
+    "ts", "tsx", "js", "jsx", "mjs", "cjs", "mts", "cts", "vue", "svelte", "css", "scss",
+];
+
+/// Extensions tried when a specifier omits one
+const RESOLVE_EXTENSIONS: [&str; 8] = ["ts", "tsx", "js", "jsx", "mjs", "cjs", "mts", "cts"];
+
+const MAX_SOURCE_SIZE: u64 = 1024 * 1024;
+
+/// Import pattern for sources without a grammar
+static RELATIVE_SPECIFIER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:\bfrom|\bimport\s*\(?|\brequire\s*\()\s*['"](\.{1,2}/[^'"\n]*)['"]"#)
+        .expect("invalid import pattern")
+});
+
+fn grammar(path: &Path) -> Option<tree_sitter::Language> {
+    match path.extension()?.to_str()? {
+        "ts" | "mts" | "cts" => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
+        "tsx" => Some(tree_sitter_typescript::LANGUAGE_TSX.into()),
+        "js" | "jsx" | "mjs" | "cjs" => Some(tree_sitter_javascript::LANGUAGE.into()),
+        "css" => Some(tree_sitter_css::LANGUAGE.into()),
+        _ => None,
+    }
+}
+
+/// The string node holding the module specifier of an import node
+fn specifier_node<'a>(node: Node<'a>, content: &str) -> Option<Node<'a>> {
+    match node.kind() {
+        "import_statement" | "export_statement" | "import_require_clause" => {
+            node.child_by_field_name("source").or_else(|| {
+                // CSS `@import "x.css"`
+                (0..node.named_child_count())
+                    .filter_map(|i| node.named_child(i))
+                    .find(|child| child.kind() == "string_value")
+            })
+        }
+        // `import('./x')` and `require('./x')`
+        "call_expression" => {
+            let function = node.child_by_field_name("function")?;
+            let is_loader = function.kind() == "import"
+                || (function.kind() == "identifier"
+                    && &content[function.byte_range()] == "require");
+            if !is_loader {
+                return None;
+            }
+            node.child_by_field_name("arguments")?
+                .named_child(0)
+                .filter(|argument| argument.kind() == "string")
+        }
+        _ => None,
+    }
+}
+
+/// Byte ranges of the relative module specifiers in a source, without quotes
+fn relative_specifiers(path: &Path, content: &str) -> Vec<Range<usize>> {
+    let Some(language) = grammar(path) else {
+        return RELATIVE_SPECIFIER
+            .captures_iter(content)
+            .filter_map(|captures| captures.get(1))
+            .map(|specifier| specifier.range())
+            .collect();
+    };
+    let mut parser = Parser::new();
+    if parser.set_language(&language).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(content, None) else {
+        return Vec::new();
+    };
+
+    let mut ranges = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if let Some(string) = specifier_node(node, content) {
+            let range = string.byte_range();
+            if range.len() >= 2 {
+                let inner = range.start + 1..range.end - 1;
+                let specifier = &content[inner.clone()];
+                if (specifier.starts_with("./") || specifier.starts_with("../"))
+                    && !specifier.contains(['\\', '\n'])
+                {
+                    ranges.push(inner);
+                }
+            }
+        }
+        stack.extend((0..node.named_child_count()).filter_map(|i| node.named_child(i)));
+    }
+    ranges.sort_by_key(|range| range.start);
+    ranges
+}
+
+/// Resolve `.` and `..` without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                result.pop();
+            }
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+/// `to` relative to the directory `from`, always starting with `./` or `../`
+fn relative_specifier(from: &Path, to: &Path) -> String {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().to_string()),
+    );
+    let joined = parts.join("/");
+    if joined.starts_with("..") {
+        joined
+    } else {
+        format!("./{}", joined)
+    }
+}
+
+/// The file a specifier refers to, as the workspace is before the move
+fn resolve(base: &Path) -> Option<PathBuf> {
+    if base.is_file() {
+        return Some(base.to_path_buf());
+    }
+    let name = base.file_name()?.to_string_lossy().to_string();
+    for extension in RESOLVE_EXTENSIONS {
+        let candidate = base.with_file_name(format!("{}.{}", name, extension));
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+    }
+    for extension in RESOLVE_EXTENSIONS {
+        let candidate = base.join(format!("index.{}", extension));
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+    }
+    // TypeScript ESM imports name the compiled `.js` file
+    if let Some(stem) = name.strip_suffix(".js") {
+        for extension in ["ts", "tsx"] {
+            let candidate = base.with_file_name(format!("{}.{}", stem, extension));
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+    }
+    None
+}
+
+/// Specifier for `target_new` seen from `from_dir`, in the style of the
+/// original specifier that resolved `base` to `target_old`
+fn rewrite_specifier(from_dir: &Path, base: &Path, target_old: &Path, target_new: &Path) -> String {
+    let base_str = base.to_string_lossy();
+    let target_old_str = target_old.to_string_lossy();
+    let full = relative_specifier(from_dir, target_new);
+    match target_old_str.strip_prefix(base_str.as_ref()) {
+        // Omitted extension or `/index.ts`
+        Some(omitted) => full
+            .strip_suffix(&omitted.replace('\\', "/"))
+            .map(str::to_string)
+            .unwrap_or(full),
+        // `.js` specifier for a `.ts` file
+        None => match (full.rfind('.'), base.extension()) {
+            (Some(dot), Some(extension)) => {
+                format!("{}.{}", &full[..dot], extension.to_string_lossy())
+            }
+            _ => full,
+        },
+    }
+}
+
+fn is_source(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| SOURCE_EXTENSIONS.contains(&e))
+}
+
+/// Files affected by moving `old` to `new` (a file or a directory)
+fn moved_files(old: &Path, new: &Path) -> HashMap<PathBuf, PathBuf> {
+    if old.is_file() {
+        return HashMap::from([(normalize(old), normalize(new))]);
+    }
+    WalkBuilder::new(old)
+        .hidden(false)
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(old).ok()?;
+            Some((normalize(entry.path()), normalize(&new.join(relative))))
+        })
+        .collect()
+}
+
+/// New contents of every source file whose imports change, keyed by the
+/// file's path before the move
+pub(crate) fn plan_import_updates(
+    workspace_root: &Path,
+    old: &Path,
+    new: &Path,
+) -> HashMap<PathBuf, String> {
+    let moved = moved_files(old, new);
+    let mut updates = HashMap::new();
+
+    let sources = WalkBuilder::new(workspace_root)
+        .filter_entry(|entry| entry.file_name() != "node_modules")
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()) && is_source(entry.path()))
+        .filter(|entry| entry.metadata().is_ok_and(|m| m.len() <= MAX_SOURCE_SIZE));
+
+    for entry in sources {
+        let file_old = normalize(entry.path());
+        let Ok(content) = std::fs::read_to_string(&file_old) else {
+            continue;
+        };
+        let file_new = moved.get(&file_old).cloned().unwrap_or(file_old.clone());
+        let (Some(dir_old), Some(dir_new)) = (file_old.parent(), file_new.parent()) else {
+            continue;
+        };
+
+        let mut rewritten = String::with_capacity(content.len());
+        let mut last = 0;
+        let mut changed = false;
+        for specifier in relative_specifiers(&file_old, &content) {
+            let base = normalize(&dir_old.join(&content[specifier.clone()]));
+            let Some(target_old) = resolve(&base).map(|t| normalize(&t)) else {
+                continue;
+            };
+            let target_new = moved.get(&target_old).unwrap_or(&target_old);
+            if target_new == &target_old && file_new == file_old {
+                continue;
+            }
+            let replacement = rewrite_specifier(dir_new, &base, &target_old, target_new);
+            if replacement == content[specifier.clone()] {
+                continue;
+            }
+            rewritten.push_str(&content[last..specifier.start]);
+            rewritten.push_str(&replacement);
+            last = specifier.end;
+            changed = true;
+        }
+        if changed {
+            rewritten.push_str(&content[last..]);
+            updates.insert(file_old, rewritten);
+        }
+    }
+    updates
+}
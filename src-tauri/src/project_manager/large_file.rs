@@ -0,0 +1,253 @@
+//! Large File Reading
+//!
+//! `get_file_content` truncates anything over 5 MB, which makes big logs and
+//! dumps unreadable. These commands read a window of a file instead, so the
+//! editor can virtualize scrolling over the whole thing:
+//! - `read_file_range` reads bytes at an offset
+//! - `read_file_lines` reads lines by number, through an index of line start
+//!   offsets built on first use and cached per file
+//!
+//! A cached index is reused while the file's size and modification time are
+//! unchanged. When a file only grew and the bytes before the old end are the
+//! same (a log being appended to), the index is extended instead of rebuilt.
+
+use lru::LruCache;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Files whose line index is kept in memory
+const INDEX_CACHE_SIZE: usize = 8;
+
+/// Largest window a single call returns
+const MAX_RANGE_BYTES: u64 = 16 * 1024 * 1024;
+
+const MAX_LINES: usize = 100_000;
+
+const SCAN_CHUNK: usize = 1024 * 1024;
+
+/// Bytes before the old end compared to tell an append from a rewrite
+const TAIL_CHECK_BYTES: u64 = 4096;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileRange {
+    pub path: String,
+    pub offset: u64,
+    /// Bytes actually read
+    pub length: u64,
+    /// Size of the whole file
+    pub size: u64,
+    /// The bytes as UTF-8; a multi-byte character cut by the range edges
+    /// shows up as a replacement character
+    pub content: String,
+    pub eof: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileLines {
+    pub path: String,
+    /// 0-based number of the first returned line
+    pub start_line: usize,
+    /// Lines without their line endings
+    pub lines: Vec<String>,
+    pub total_lines: usize,
+    pub size: u64,
+    /// Byte offset where the first returned line starts
+    pub start_offset: u64,
+    pub eof: bool,
+}
+
+struct LineIndex {
+    size: u64,
+    modified: Option<std::time::SystemTime>,
+    /// Start offset of every line; a file ending in a newline also has its
+    /// size as the last entry
+    offsets: Vec<u64>,
+    tail_hash: String,
+}
+
+impl LineIndex {
+    fn line_count(&self) -> usize {
+        match self.offsets.last() {
+            Some(&last) if last == self.size => self.offsets.len() - 1,
+            _ => self.offsets.len(),
+        }
+    }
+
+    fn line_end(&self, line: usize) -> u64 {
+        self.offsets.get(line + 1).copied().unwrap_or(self.size)
+    }
+}
+
+static LINE_INDEXES: Lazy<Mutex<LruCache<PathBuf, Arc<LineIndex>>>> =
+    Lazy::new(|| Mutex::new(LruCache::new(NonZeroUsize::new(INDEX_CACHE_SIZE).unwrap())));
+
+/// Hash of the `TAIL_CHECK_BYTES` before `end`
+fn tail_hash(file: &mut File, end: u64) -> std::io::Result<String> {
+    let start = end.saturating_sub(TAIL_CHECK_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::with_capacity((end - start) as usize);
+    file.by_ref().take(end - start).read_to_end(&mut bytes)?;
+    let mut hasher = Sha256::new();
+    hasher.update(&bytes);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Add the line starts found between `from` and the end of the file
+fn scan_offsets(file: &mut File, from: u64, offsets: &mut Vec<u64>) -> std::io::Result<u64> {
+    file.seek(SeekFrom::Start(from))?;
+    let mut buffer = vec![0u8; SCAN_CHUNK];
+    let mut position = from;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(position);
+        }
+        offsets.extend(
+            buffer[..read]
+                .iter()
+                .enumerate()
+                .filter(|(_, byte)| **byte == b'\n')
+                .map(|(i, _)| position + i as u64 + 1),
+        );
+        position += read as u64;
+    }
+}
+
+fn build_index(path: &Path, previous: Option<Arc<LineIndex>>) -> Result<Arc<LineIndex>, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let metadata = file.metadata().map_err(|e| e.to_string())?;
+    let modified = metadata.modified().ok();
+
+    if let Some(previous) = &previous {
+        if previous.size == metadata.len() && previous.modified == modified {
+            return Ok(previous.clone());
+        }
+    }
+
+    // Appended to since the last scan: only the new bytes need scanning
+    let appended_from = match &previous {
+        Some(previous) if metadata.len() > previous.size => {
+            let hash = tail_hash(&mut file, previous.size).map_err(|e| e.to_string())?;
+            (hash == previous.tail_hash).then(|| (previous.offsets.clone(), previous.size))
+        }
+        _ => None,
+    };
+    let (mut offsets, from) = appended_from.unwrap_or_else(|| (vec![0], 0));
+
+    let size = scan_offsets(&mut file, from, &mut offsets).map_err(|e| e.to_string())?;
+    let index = LineIndex {
+        size,
+        modified,
+        offsets,
+        tail_hash: tail_hash(&mut file, size).map_err(|e| e.to_string())?,
+    };
+    Ok(Arc::new(index))
+}
+
+fn line_index(path: &Path) -> Result<Arc<LineIndex>, String> {
+    let key = path.to_path_buf();
+    let previous = LINE_INDEXES
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get(&key)
+        .cloned();
+    let index = build_index(path, previous)?;
+    LINE_INDEXES
+        .lock()
+        .map_err(|e| e.to_string())?
+        .put(key, index.clone());
+    Ok(index)
+}
+
+fn read_at(file: &mut File, offset: u64, length: u64) -> std::io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut bytes = Vec::with_capacity(length as usize);
+    file.take(length).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_range(path: &Path, byte_offset: u64, length: u64) -> Result<FileRange, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+    let offset = byte_offset.min(size);
+    let bytes =
+        read_at(&mut file, offset, length.min(MAX_RANGE_BYTES)).map_err(|e| e.to_string())?;
+    let length = bytes.len() as u64;
+    Ok(FileRange {
+        path: path.to_string_lossy().to_string(),
+        offset,
+        length,
+        size,
+        content: String::from_utf8_lossy(&bytes).to_string(),
+        eof: offset + length >= size,
+    })
+}
+
+fn read_lines(path: &Path, start_line: usize, count: usize) -> Result<FileLines, String> {
+    let index = line_index(path)?;
+    let total_lines = index.line_count();
+    let start_line = start_line.min(total_lines);
+    let end_line = (start_line + count.min(MAX_LINES)).min(total_lines);
+
+    let start_offset = index.offsets.get(start_line).copied().unwrap_or(index.size);
+    let mut lines = Vec::with_capacity(end_line - start_line);
+    if end_line > start_line {
+        let end_offset = index.line_end(end_line - 1);
+        let mut file = File::open(path).map_err(|e| e.to_string())?;
+        let bytes = read_at(&mut file, start_offset, end_offset - start_offset)
+            .map_err(|e| e.to_string())?;
+        for line in start_line..end_line {
+            let from = (index.offsets[line] - start_offset) as usize;
+            let to = ((index.line_end(line) - start_offset) as usize).min(bytes.len());
+            let text = &bytes[from.min(to)..to];
+            let text = text.strip_suffix(b"\n").unwrap_or(text);
+            let text = text.strip_suffix(b"\r").unwrap_or(text);
+            lines.push(String::from_utf8_lossy(text).to_string());
+        }
+    }
+
+    Ok(FileLines {
+        path: path.to_string_lossy().to_string(),
+        start_line,
+        lines,
+        total_lines,
+        size: index.size,
+        start_offset,
+        eof: end_line >= total_lines,
+    })
+}
+
+/// Read `length` bytes (at most 16 MB) starting at `byte_offset`
+#[tauri::command]
+pub async fn read_file_range(
+    path: String,
+    byte_offset: u64,
+    length: u64,
+) -> Result<FileRange, String> {
+    let file_path = PathBuf::from(&path);
+    tokio::task::spawn_blocking(move || read_range(&file_path, byte_offset, length))
+        .await
+        .map_err(|e| format!("Read task failed: {}", e))?
+}
+
+/// Read `count` lines (at most 100,000) starting at the 0-based `start_line`,
+/// with the file's total line count
+#[tauri::command]
+pub async fn read_file_lines(
+    path: String,
+    start_line: usize,
+    count: usize,
+) -> Result<FileLines, String> {
+    let file_path = PathBuf::from(&path);
+    tokio::task::spawn_blocking(move || read_lines(&file_path, start_line, count))
+        .await
+        .map_err(|e| format!("Read task failed: {}", e))?
+}
@@ -0,0 +1,1011 @@
+//! Semantic Code Index
+//!
+//! Lets agents and the search panel find code by meaning rather than by
+//! exact text. Workspace files are split with the code chunker, each chunk
+//! is embedded, and the vectors are stored in a SQLite database (turso)
+//! under the app data dir so reopening a workspace only re-embeds files
+//! that changed. The index is then kept current from the project watcher's
+//! events, like the file index.
+//!
+//! Embeddings come from the provider set in `agent.semanticSearch.provider`:
+//! - `local` (default): hashed identifier and trigram features, no network
+//! - `openai`: `/embeddings` of the OpenAI API or a compatible endpoint
+//! - `ollama`: `/api/embed` of a local Ollama server
+//!
+//! Files agents may not read (`agent.files.exclude`, `agent.files.redact`
+//! and the sandbox's `denyPaths`) are never chunked or sent to a provider.
+//!
+//! A remote provider without an API key falls back to `local`. Vectors are
+//! normalized and searched exactly in memory; at workspace scale a parallel
+//! scan takes milliseconds, so no approximate index is needed.
+
+use super::{ignore_walker, list_unignored_entries};
+use crate::background_pause::BackgroundSubsystem;
+use crate::code_chunker::{self, ChunkLanguage};
+use crate::credential_manager::CredentialManager;
+use crate::file_operations::AgentFilePolicy;
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Files indexed per workspace at most
+const MAX_INDEXED_FILES: usize = 20_000;
+
+/// Larger files are usually generated or data
+const MAX_FILE_BYTES: u64 = 256 * 1024;
+
+/// Token budget per chunk; smaller than the chunker default so results
+/// point at a single declaration
+const CHUNK_TOKENS: usize = 400;
+
+/// Chunks embedded per provider request
+const EMBED_BATCH_SIZE: usize = 32;
+
+/// Dimensions of the local embedding
+const LOCAL_DIMENSIONS: usize = 512;
+
+const DEFAULT_K: usize = 10;
+const MAX_K: usize = 50;
+
+/// Watcher events are collected this long before the changed files are re-embedded
+const UPDATE_DELAY: Duration = Duration::from_secs(2);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+const DEFAULT_OPENAI_ENDPOINT: &str = "https://api.openai.com/v1";
+const DEFAULT_OLLAMA_ENDPOINT: &str = "http://localhost:11434";
+
+/// Text files without declaration patterns that are still worth indexing
+const PLAIN_EXTENSIONS: [&str; 16] = [
+    "md", "mdx", "txt", "toml", "yaml", "yml", "html", "css", "scss", "vue", "svelte", "sql", "sh",
+    "rb", "php", "lua",
+];
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+CREATE TABLE IF NOT EXISTS chunks (
+    path TEXT NOT NULL,
+    modified INTEGER NOT NULL,
+    start_line INTEGER NOT NULL,
+    end_line INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    name TEXT,
+    content TEXT NOT NULL,
+    embedding BLOB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS chunks_path ON chunks (path);
+";
+
+static HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+});
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticIndexStatus {
+    pub root: String,
+    /// Provider and model the vectors were computed with, e.g. "openai:text-embedding-3-small"
+    pub embedder: String,
+    pub file_count: usize,
+    pub chunk_count: usize,
+    /// Unix timestamp (ms) of the last full build
+    pub built_at: i64,
+    /// Changed files waiting to be re-embedded
+    pub pending: usize,
+    /// Error of the last update from watcher events
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticSearchMatch {
+    pub path: String,
+    pub relative_path: String,
+    /// 1-based, inclusive
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Declaration keyword ("fn", "class", ...) or "block"
+    pub kind: String,
+    pub name: Option<String>,
+    /// Cosine similarity, higher is closer
+    pub score: f32,
+    pub content: String,
+}
+
+// ============================================================================
+// Embedding providers
+// ============================================================================
+
+#[derive(Debug, Clone)]
+enum Embedder {
+    Local,
+    OpenAi {
+        model: String,
+        endpoint: String,
+        api_key: String,
+    },
+    Ollama {
+        model: String,
+        endpoint: String,
+    },
+}
+
+impl Embedder {
+    fn from_settings(workspace_root: &str) -> Embedder {
+        let string = |key: &str| {
+            crate::configuration_manager::resolve_setting(Some(workspace_root), key)
+                .and_then(|v| v.as_str().map(str::to_string))
+                .filter(|v| !v.is_empty())
+        };
+        let endpoint = |default: &str| {
+            string("agent.semanticSearch.endpoint")
+                .unwrap_or_else(|| default.to_string())
+                .trim_end_matches('/')
+                .to_string()
+        };
+        let provider = string("agent.semanticSearch.provider");
+        let model = string("agent.semanticSearch.model");
+        match provider.as_deref() {
+            Some("openai") => match CredentialManager::get_credential("openai_api_key") {
+                Ok(api_key) => Embedder::OpenAi {
+                    model: model.unwrap_or_else(|| "text-embedding-3-small".to_string()),
+                    endpoint: endpoint(DEFAULT_OPENAI_ENDPOINT),
+                    api_key,
+                },
+                Err(_) => {
+                    eprintln!(
+                        "[SemanticIndex] No OpenAI API key configured, using local embeddings"
+                    );
+                    Embedder::Local
+                }
+            },
+            Some("ollama") => Embedder::Ollama {
+                model: model.unwrap_or_else(|| "nomic-embed-text".to_string()),
+                endpoint: endpoint(DEFAULT_OLLAMA_ENDPOINT),
+            },
+            Some("local") | None => Embedder::Local,
+            Some(other) => {
+                eprintln!(
+                    "[SemanticIndex] Unknown embedding provider '{}', using local embeddings",
+                    other
+                );
+                Embedder::Local
+            }
+        }
+    }
+
+    /// Vectors of different embedders are not comparable; an index built
+    /// with another one is rebuilt
+    fn id(&self) -> String {
+        match self {
+            Embedder::Local => format!("local:hash-{}", LOCAL_DIMENSIONS),
+            Embedder::OpenAi { model, .. } => format!("openai:{}", model),
+            Embedder::Ollama { model, .. } => format!("ollama:{}", model),
+        }
+    }
+
+    /// Normalized embeddings of the texts, in order
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        let vectors = match self {
+            Embedder::Local => texts.iter().map(|text| local_embedding(text)).collect(),
+            Embedder::OpenAi {
+                model,
+                endpoint,
+                api_key,
+            } => {
+                let request = HTTP_CLIENT
+                    .post(format!("{}/embeddings", endpoint))
+                    .bearer_auth(api_key)
+                    .json(&json!({ "model": model, "input": texts }));
+                let body = send(request, "OpenAI").await?;
+                let mut data: Vec<(u64, Vec<f32>)> = body
+                    .get("data")
+                    .and_then(Value::as_array)
+                    .ok_or("OpenAI returned no embeddings")?
+                    .iter()
+                    .map(|item| {
+                        let index = item.get("index").and_then(Value::as_u64).unwrap_or(0);
+                        (index, parse_vector(item.get("embedding")))
+                    })
+                    .collect();
+                data.sort_by_key(|(index, _)| *index);
+                data.into_iter().map(|(_, vector)| vector).collect()
+            }
+            Embedder::Ollama { model, endpoint } => {
+                let request = HTTP_CLIENT
+                    .post(format!("{}/api/embed", endpoint))
+                    .json(&json!({ "model": model, "input": texts }));
+                let body = send(request, "Ollama").await?;
+                body.get("embeddings")
+                    .and_then(Value::as_array)
+                    .ok_or("Ollama returned no embeddings")?
+                    .iter()
+                    .map(|vector| parse_vector(Some(vector)))
+                    .collect::<Vec<_>>()
+            }
+        };
+        if vectors.len() != texts.len() || vectors.iter().any(Vec::is_empty) {
+            return Err(format!(
+                "Embedding provider returned {} vectors for {} inputs",
+                vectors.len(),
+                texts.len()
+            ));
+        }
+        Ok(vectors.into_iter().map(normalize).collect())
+    }
+}
+
+async fn send(request: reqwest::RequestBuilder, provider: &str) -> Result<Value, String> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", provider, e))?;
+    let status = response.status();
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from {}: {}", provider, e))?;
+    if !status.is_success() {
+        let message = body
+            .pointer("/error/message")
+            .or_else(|| body.get("error"))
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        return Err(format!("{} returned {}: {}", provider, status, message));
+    }
+    Ok(body)
+}
+
+fn parse_vector(value: Option<&Value>) -> Vec<f32> {
+    value
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_f64)
+                .map(|v| v as f32)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+/// FNV-1a, stable across runs unlike the std hasher
+fn feature_hash(feature: &str) -> u64 {
+    feature.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Split identifiers at `_`, `-`, digits and camelCase humps
+fn identifier_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for token in text.split(|c: char| !c.is_alphanumeric()) {
+        let mut word = String::new();
+        let mut prev_lower = false;
+        for c in token.chars() {
+            if (c.is_uppercase() && prev_lower) || c.is_ascii_digit() {
+                if word.len() > 1 {
+                    words.push(std::mem::take(&mut word));
+                }
+                word.clear();
+            }
+            if !c.is_ascii_digit() {
+                word.extend(c.to_lowercase());
+            }
+            prev_lower = c.is_lowercase();
+        }
+        if word.len() > 1 {
+            words.push(word);
+        }
+    }
+    words
+}
+
+/// Feature-hashed bag of words and character trigrams with sublinear term
+/// weights. Catches shared vocabulary ("parse config" finds `parseConfig`
+/// and `config_parser`) but not synonyms; remote models do better.
+fn local_embedding(text: &str) -> Vec<f32> {
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for word in identifier_words(text) {
+        let padded: Vec<char> = format!(" {} ", word).chars().collect();
+        for trigram in padded.windows(3) {
+            let trigram: String = trigram.iter().collect();
+            *counts.entry(format!("#{}", trigram)).or_default() += 1;
+        }
+        *counts.entry(word).or_default() += 1;
+    }
+
+    let mut vector = vec![0.0f32; LOCAL_DIMENSIONS];
+    for (feature, count) in counts {
+        let hash = feature_hash(&feature);
+        let slot = (hash % LOCAL_DIMENSIONS as u64) as usize;
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        // Whole words weigh more than their trigrams
+        let weight = if feature.starts_with('#') { 1.0 } else { 2.0 };
+        vector[slot] += sign * weight * (1.0 + (count as f32).ln());
+    }
+    normalize(vector)
+}
+
+// ============================================================================
+// Index
+// ============================================================================
+
+struct IndexedChunk {
+    relative_path: String,
+    start_line: usize,
+    end_line: usize,
+    kind: String,
+    name: Option<String>,
+    content: String,
+    vector: Vec<f32>,
+}
+
+struct SemanticIndex {
+    root: PathBuf,
+    /// The root as it was given, which watcher events may use
+    alias: PathBuf,
+    db_path: PathBuf,
+    embedder: Embedder,
+    /// Relative path (with `/`) to its modification time when embedded
+    files: HashMap<String, u64>,
+    chunks: Vec<IndexedChunk>,
+    built_at: i64,
+    /// Paths changed since the last update from watcher events
+    pending: HashSet<String>,
+    update_scheduled: bool,
+    last_error: Option<String>,
+}
+
+impl SemanticIndex {
+    fn relative(&self, path: &Path) -> Option<String> {
+        let relative = path
+            .strip_prefix(&self.root)
+            .or_else(|_| path.strip_prefix(&self.alias))
+            .ok()?;
+        Some(relative.to_string_lossy().replace('\\', "/"))
+    }
+
+    fn status(&self) -> SemanticIndexStatus {
+        SemanticIndexStatus {
+            root: self.root.to_string_lossy().to_string(),
+            embedder: self.embedder.id(),
+            file_count: self.files.len(),
+            chunk_count: self.chunks.len(),
+            built_at: self.built_at,
+            pending: self.pending.len(),
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+static SEMANTIC_INDEXES: Lazy<RwLock<HashMap<PathBuf, SemanticIndex>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Serializes database access; builds and watcher updates may overlap.
+/// Held only around queries, never while embedding.
+static DB_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+fn canonical_root(workspace_root: &str) -> PathBuf {
+    PathBuf::from(workspace_root)
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(workspace_root))
+}
+
+fn db_path(app: &AppHandle, root: &Path) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("semantic-index");
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create semantic index directory: {}", e))?;
+    let key = blake3::hash(root.to_string_lossy().as_bytes()).to_hex();
+    Ok(dir.join(format!("{}.db", &key[..16])))
+}
+
+fn is_indexable(path: &Path) -> bool {
+    let Some(extension) = path.extension().and_then(|e| e.to_str()) else {
+        return false;
+    };
+    ChunkLanguage::from_extension(extension) != ChunkLanguage::Plain
+        || PLAIN_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+}
+
+fn indexable_modified(path: &Path) -> Option<u64> {
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_FILE_BYTES {
+        return None;
+    }
+    Some(super::modified_millis(&metadata))
+}
+
+/// Indexable files under a folder that are not ignored, with their modification times
+fn walk_files(root: &Path, dir: &Path) -> Vec<(String, u64)> {
+    ignore_walker(dir, false)
+        .build()
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter(|entry| is_indexable(entry.path()))
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(root).ok()?;
+            let modified = indexable_modified(entry.path())?;
+            Some((relative.to_string_lossy().replace('\\', "/"), modified))
+        })
+        .take(MAX_INDEXED_FILES)
+        .collect()
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+async fn open_db(path: &Path) -> Result<turso::Connection, String> {
+    let db = turso::Builder::new_local(&path.to_string_lossy())
+        .build()
+        .await
+        .map_err(|e| format!("Failed to open semantic index: {}", e))?;
+    let conn = db
+        .connect()
+        .map_err(|e| format!("Failed to connect to semantic index: {}", e))?;
+    conn.execute_batch(SCHEMA)
+        .await
+        .map_err(|e| format!("Failed to create semantic index schema: {}", e))?;
+    Ok(conn)
+}
+
+fn vector_to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn blob_to_vector(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+fn text_value(value: turso::Value) -> Option<String> {
+    match value {
+        turso::Value::Text(text) => Some(text),
+        _ => None,
+    }
+}
+
+fn int_value(value: turso::Value) -> i64 {
+    match value {
+        turso::Value::Integer(n) => n,
+        _ => 0,
+    }
+}
+
+/// Stored chunks and file times, or nothing when they were computed with
+/// another embedder (the stale rows are dropped)
+async fn load_stored(
+    conn: &turso::Connection,
+    embedder_id: &str,
+) -> Result<(HashMap<String, u64>, Vec<IndexedChunk>), String> {
+    let db_err = |e: turso::Error| format!("Semantic index query failed: {}", e);
+    let mut files = HashMap::new();
+    let mut chunks = Vec::new();
+
+    let mut rows = conn
+        .query("SELECT value FROM meta WHERE key = 'embedder'", ())
+        .await
+        .map_err(db_err)?;
+    let stored_embedder = match rows.next().await.map_err(db_err)? {
+        Some(row) => row.get_value(0).ok().and_then(text_value),
+        None => None,
+    };
+    drop(rows);
+    if stored_embedder.as_deref() != Some(embedder_id) {
+        conn.execute("DELETE FROM chunks", ())
+            .await
+            .map_err(db_err)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('embedder', ?1)",
+            [embedder_id],
+        )
+        .await
+        .map_err(db_err)?;
+        return Ok((files, chunks));
+    }
+
+    let mut rows = conn
+        .query(
+            "SELECT path, modified, start_line, end_line, kind, name, content, embedding FROM chunks",
+            (),
+        )
+        .await
+        .map_err(db_err)?;
+    while let Some(row) = rows.next().await.map_err(db_err)? {
+        let value = |i: usize| row.get_value(i).unwrap_or(turso::Value::Null);
+        let Some(relative_path) = text_value(value(0)) else {
+            continue;
+        };
+        let vector = match value(7) {
+            turso::Value::Blob(blob) => blob_to_vector(&blob),
+            _ => continue,
+        };
+        files.insert(relative_path.clone(), int_value(value(1)) as u64);
+        chunks.push(IndexedChunk {
+            relative_path,
+            start_line: int_value(value(2)) as usize,
+            end_line: int_value(value(3)) as usize,
+            kind: text_value(value(4)).unwrap_or_default(),
+            name: text_value(value(5)),
+            content: text_value(value(6)).unwrap_or_default(),
+            vector,
+        });
+    }
+    Ok((files, chunks))
+}
+
+/// Replace the stored chunks of one file; `chunks` is empty for removed files
+async fn store_file(
+    conn: &turso::Connection,
+    relative_path: &str,
+    modified: u64,
+    chunks: &[IndexedChunk],
+) -> Result<(), String> {
+    let db_err = |e: turso::Error| format!("Failed to update semantic index: {}", e);
+    conn.execute("BEGIN", ()).await.map_err(db_err)?;
+    let result = async {
+        conn.execute("DELETE FROM chunks WHERE path = ?1", [relative_path])
+            .await?;
+        for chunk in chunks {
+            conn.execute(
+                "INSERT INTO chunks (path, modified, start_line, end_line, kind, name, content, embedding)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                vec![
+                    turso::Value::from(relative_path),
+                    turso::Value::Integer(modified as i64),
+                    turso::Value::Integer(chunk.start_line as i64),
+                    turso::Value::Integer(chunk.end_line as i64),
+                    turso::Value::from(chunk.kind.as_str()),
+                    turso::Value::from(chunk.name.clone()),
+                    turso::Value::from(chunk.content.as_str()),
+                    turso::Value::Blob(vector_to_blob(&chunk.vector)),
+                ],
+            )
+            .await?;
+        }
+        Ok::<(), turso::Error>(())
+    }
+    .await;
+    match result {
+        Ok(()) => conn.execute("COMMIT", ()).await.map(|_| ()).map_err(db_err),
+        Err(e) => {
+            let _ = conn.execute("ROLLBACK", ()).await;
+            Err(db_err(e))
+        }
+    }
+}
+
+// ============================================================================
+// Updates
+// ============================================================================
+
+/// Chunk and embed one file. Batches the provider fails on are logged and
+/// skipped; the error of the last one is returned with the chunks that were
+/// embedded.
+async fn embed_file(
+    embedder: &Embedder,
+    root: &Path,
+    relative_path: &str,
+) -> (Vec<IndexedChunk>, Option<String>) {
+    let path = root.join(relative_path);
+    // Non-UTF-8 files are treated as binary and skipped
+    let Ok(content) = tokio::fs::read_to_string(&path).await else {
+        return (Vec::new(), None);
+    };
+    let language = ChunkLanguage::from_extension(
+        path.extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default(),
+    );
+    let chunks: Vec<code_chunker::CodeChunk> =
+        code_chunker::chunk_source(&content, language, CHUNK_TOKENS)
+            .into_iter()
+            .filter(|chunk| !chunk.content.trim().is_empty())
+            .collect();
+
+    let mut indexed = Vec::with_capacity(chunks.len());
+    let mut failure = None;
+    for batch in chunks.chunks(EMBED_BATCH_SIZE) {
+        // The path and name give short chunks something to match on
+        let texts: Vec<String> = batch
+            .iter()
+            .map(|chunk| match &chunk.name {
+                Some(name) => format!(
+                    "{}\n{} {}\n{}",
+                    relative_path, chunk.kind, name, chunk.content
+                ),
+                None => format!("{}\n{}", relative_path, chunk.content),
+            })
+            .collect();
+        let vectors = match embedder.embed(&texts).await {
+            Ok(vectors) => vectors,
+            Err(e) => {
+                eprintln!(
+                    "[SemanticIndex] Skipping lines {}-{} of {}: {}",
+                    batch[0].start_line,
+                    batch[batch.len() - 1].end_line,
+                    relative_path,
+                    e
+                );
+                failure = Some(e);
+                continue;
+            }
+        };
+        indexed.extend(
+            batch
+                .iter()
+                .zip(vectors)
+                .map(|(chunk, vector)| IndexedChunk {
+                    relative_path: relative_path.to_string(),
+                    start_line: chunk.start_line,
+                    end_line: chunk.end_line,
+                    kind: chunk.kind.clone(),
+                    name: chunk.name.clone(),
+                    content: chunk.content.clone(),
+                    vector,
+                }),
+        );
+    }
+    (indexed, failure)
+}
+
+/// Bring the given files up to date: `Some(modified)` for files that exist,
+/// `None` for removed ones. Unchanged files are skipped, and files agent
+/// policies hide are dropped from the index. Returns the number of files
+/// re-embedded or removed.
+///
+/// A file with batches that failed to embed keeps the chunks that succeeded
+/// but is stored without its modification time, so the next build retries
+/// it; the failure is kept as the index's `last_error`.
+async fn update_files(root: &Path, changes: Vec<(String, Option<u64>)>) -> Result<usize, String> {
+    let (db_path, embedder, alias, indexed) = {
+        let indexes = SEMANTIC_INDEXES.read().map_err(|e| e.to_string())?;
+        let index = indexes
+            .get(root)
+            .ok_or_else(|| format!("Workspace is not indexed: {}", root.display()))?;
+        (
+            index.db_path.clone(),
+            index.embedder.clone(),
+            index.alias.clone(),
+            index.files.clone(),
+        )
+    };
+    let policy = AgentFilePolicy::load(&alias.to_string_lossy(), root);
+    let stale: Vec<(String, Option<u64>)> = changes
+        .into_iter()
+        .map(|(relative, modified)| {
+            let allowed = modified.is_some() && policy.allows(&root.join(&relative));
+            (relative, modified.filter(|_| allowed))
+        })
+        .filter(|(relative, modified)| indexed.get(relative).copied() != *modified)
+        .collect();
+    if stale.is_empty() {
+        return Ok(0);
+    }
+
+    let conn = {
+        let _guard = DB_LOCK.lock().await;
+        open_db(&db_path).await?
+    };
+    let mut updated = 0;
+    let mut last_failure = None;
+    for (relative, modified) in stale {
+        let (chunks, failure) = match modified {
+            Some(_) => embed_file(&embedder, root, &relative).await,
+            None => (Vec::new(), None),
+        };
+        // Stored as unmodified so the file is embedded again next time
+        let recorded = modified.filter(|_| failure.is_none());
+        if failure.is_some() {
+            last_failure = failure;
+        }
+        {
+            let _guard = DB_LOCK.lock().await;
+            store_file(&conn, &relative, recorded.unwrap_or_default(), &chunks).await?;
+        }
+
+        let mut indexes = SEMANTIC_INDEXES.write().map_err(|e| e.to_string())?;
+        let Some(index) = indexes.get_mut(root) else {
+            break;
+        };
+        index.chunks.retain(|chunk| chunk.relative_path != relative);
+        index.chunks.extend(chunks);
+        match recorded {
+            Some(modified) => {
+                index.files.insert(relative, modified);
+            }
+            None => {
+                index.files.remove(&relative);
+            }
+        }
+        updated += 1;
+    }
+
+    if let Ok(mut indexes) = SEMANTIC_INDEXES.write() {
+        if let Some(index) = indexes.get_mut(root) {
+            index.last_error = last_failure;
+        }
+    }
+    Ok(updated)
+}
+
+/// Load the stored index of a workspace and embed what changed since
+async fn build_index(app: &AppHandle, workspace_root: &str) -> Result<SemanticIndexStatus, String> {
+    let root = canonical_root(workspace_root);
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", workspace_root));
+    }
+    let started = std::time::Instant::now();
+    let embedder = Embedder::from_settings(workspace_root);
+    let db_path = db_path(app, &root)?;
+
+    let (files, chunks) = {
+        let _guard = DB_LOCK.lock().await;
+        let conn = open_db(&db_path).await?;
+        load_stored(&conn, &embedder.id()).await?
+    };
+
+    let walk_root = root.clone();
+    let present = tokio::task::spawn_blocking(move || walk_files(&walk_root, &walk_root))
+        .await
+        .map_err(|e| format!("Semantic indexing failed: {}", e))?;
+    let present_paths: HashSet<&String> = present.iter().map(|(path, _)| path).collect();
+    let mut changes: Vec<(String, Option<u64>)> = files
+        .keys()
+        .filter(|path| !present_paths.contains(path))
+        .map(|path| (path.clone(), None))
+        .collect();
+    changes.extend(
+        present
+            .iter()
+            .map(|(path, modified)| (path.clone(), Some(*modified))),
+    );
+
+    {
+        let mut indexes = SEMANTIC_INDEXES.write().map_err(|e| e.to_string())?;
+        let pending = indexes
+            .remove(&root)
+            .map(|previous| previous.pending)
+            .unwrap_or_default();
+        indexes.insert(
+            root.clone(),
+            SemanticIndex {
+                root: root.clone(),
+                alias: PathBuf::from(workspace_root),
+                db_path,
+                embedder,
+                files,
+                chunks,
+                built_at: chrono::Utc::now().timestamp_millis(),
+                pending,
+                update_scheduled: false,
+                last_error: None,
+            },
+        );
+    }
+
+    let updated = update_files(&root, changes).await?;
+    println!(
+        "[SemanticIndex] Indexed {} ({} files updated, {} ms)",
+        root.display(),
+        updated,
+        started.elapsed().as_millis()
+    );
+
+    SEMANTIC_INDEXES
+        .read()
+        .map_err(|e| e.to_string())?
+        .get(&root)
+        .map(SemanticIndex::status)
+        .ok_or_else(|| "Semantic index was removed while building".to_string())
+}
+
+/// Queue watcher events for the indexes containing the changed paths; the
+/// files are re-embedded in the background after a short delay
+pub(super) fn apply_changes(paths: &[&PathBuf]) {
+    let Ok(mut indexes) = SEMANTIC_INDEXES.write() else {
+        return;
+    };
+    for index in indexes.values_mut() {
+        let before = index.pending.len();
+        for path in paths {
+            if let Some(relative) = index.relative(path).filter(|r| !r.is_empty()) {
+                index.pending.insert(relative);
+            }
+        }
+        if index.pending.len() > before && !index.update_scheduled {
+            index.update_scheduled = true;
+            let root = index.root.clone();
+            tauri::async_runtime::spawn(flush_pending(root));
+        }
+    }
+}
+
+/// Resolve queued paths to file changes: existing files that are not ignored,
+/// folders by walking them, and removed entries with everything under them
+fn resolve_pending(
+    root: &Path,
+    pending: HashSet<String>,
+    indexed: &HashMap<String, u64>,
+) -> Vec<(String, Option<u64>)> {
+    let mut changes = Vec::new();
+    for relative in pending {
+        let path = root.join(&relative);
+        let listed = path
+            .parent()
+            .is_some_and(|parent| list_unignored_entries(parent, false).contains(&path));
+        if listed && path.is_dir() {
+            changes.extend(
+                walk_files(root, &path)
+                    .into_iter()
+                    .map(|(path, modified)| (path, Some(modified))),
+            );
+        } else if listed && is_indexable(&path) {
+            changes.push((relative, indexable_modified(&path)));
+        } else {
+            let prefix = format!("{}/", relative);
+            changes.extend(
+                indexed
+                    .keys()
+                    .filter(|path| **path == relative || path.starts_with(&prefix))
+                    .map(|path| (path.clone(), None)),
+            );
+        }
+    }
+    changes
+}
+
+async fn flush_pending(root: PathBuf) {
+    loop {
+        tokio::time::sleep(UPDATE_DELAY).await;
+        if crate::background_pause::is_paused(BackgroundSubsystem::Indexers) {
+            continue;
+        }
+
+        let (pending, indexed) = {
+            let Ok(mut indexes) = SEMANTIC_INDEXES.write() else {
+                return;
+            };
+            let Some(index) = indexes.get_mut(&root) else {
+                return;
+            };
+            if index.pending.is_empty() {
+                index.update_scheduled = false;
+                return;
+            }
+            (std::mem::take(&mut index.pending), index.files.clone())
+        };
+
+        let resolve_root = root.clone();
+        let changes =
+            tokio::task::spawn_blocking(move || resolve_pending(&resolve_root, pending, &indexed))
+                .await
+                .unwrap_or_default();
+        if let Err(e) = update_files(&root, changes).await {
+            eprintln!("[SemanticIndex] Update of {} failed: {}", root.display(), e);
+            if let Ok(mut indexes) = SEMANTIC_INDEXES.write() {
+                if let Some(index) = indexes.get_mut(&root) {
+                    index.last_error = Some(e);
+                }
+            }
+        }
+    }
+}
+
+fn search(root: &Path, query_vector: &[f32], k: usize) -> Result<Vec<SemanticSearchMatch>, String> {
+    let indexes = SEMANTIC_INDEXES.read().map_err(|e| e.to_string())?;
+    let index = indexes
+        .get(root)
+        .ok_or_else(|| format!("Workspace is not indexed: {}", root.display()))?;
+
+    let mut scored: Vec<(f32, &IndexedChunk)> = index
+        .chunks
+        .par_iter()
+        .filter(|chunk| chunk.vector.len() == query_vector.len())
+        .map(|chunk| {
+            let score = chunk
+                .vector
+                .iter()
+                .zip(query_vector)
+                .map(|(a, b)| a * b)
+                .sum::<f32>();
+            (score, chunk)
+        })
+        .collect();
+    scored.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+    scored.truncate(k);
+
+    Ok(scored
+        .into_iter()
+        .map(|(score, chunk)| SemanticSearchMatch {
+            path: index
+                .root
+                .join(&chunk.relative_path)
+                .to_string_lossy()
+                .to_string(),
+            relative_path: chunk.relative_path.clone(),
+            start_line: chunk.start_line,
+            end_line: chunk.end_line,
+            kind: chunk.kind.clone(),
+            name: chunk.name.clone(),
+            score,
+            content: chunk.content.clone(),
+        })
+        .collect())
+}
+
+/// Build (or refresh) the semantic index of a workspace
+#[tauri::command]
+pub async fn build_semantic_index(
+    app: AppHandle,
+    workspace_root: String,
+) -> Result<SemanticIndexStatus, String> {
+    build_index(&app, &workspace_root).await
+}
+
+/// The `k` chunks closest in meaning to the query, best first
+/// The workspace is indexed on first use, which can take a while
+#[tauri::command]
+pub async fn semantic_search(
+    app: AppHandle,
+    workspace_root: String,
+    query: String,
+    k: Option<usize>,
+) -> Result<Vec<SemanticSearchMatch>, String> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let root = canonical_root(&workspace_root);
+    let embedder = {
+        let indexes = SEMANTIC_INDEXES.read().map_err(|e| e.to_string())?;
+        indexes.get(&root).map(|index| index.embedder.clone())
+    };
+    let configured = Embedder::from_settings(&workspace_root);
+    // Not indexed yet, or the embedding settings changed
+    if embedder.is_none_or(|embedder| embedder.id() != configured.id()) {
+        build_index(&app, &workspace_root).await?;
+    }
+    let embedder = configured;
+    let query_vector = embedder.embed(&[query]).await?.pop().unwrap_or_default();
+    let k = k.unwrap_or(DEFAULT_K).clamp(1, MAX_K);
+    tokio::task::spawn_blocking(move || search(&root, &query_vector, k))
+        .await
+        .map_err(|e| format!("Semantic search failed: {}", e))?
+}
+
+/// Semantic index status of a workspace, if it has been indexed
+#[tauri::command]
+pub fn get_semantic_index_status(
+    workspace_root: String,
+) -> Result<Option<SemanticIndexStatus>, String> {
+    let root = canonical_root(&workspace_root);
+    Ok(SEMANTIC_INDEXES
+        .read()
+        .map_err(|e| e.to_string())?
+        .get(&root)
+        .map(SemanticIndex::status))
+}
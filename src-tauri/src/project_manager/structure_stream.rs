@@ -0,0 +1,291 @@
+//! Project Structure Streaming
+//!
+//! `load_project_structure` returns a single level, so restoring an expanded
+//! tree used to take one round trip per folder. `stream_project_structure`
+//! walks the tree on a background thread instead — breadth first, so the top
+//! levels arrive first — and emits the nodes to the requesting window as
+//! `project-structure-chunk` events in batches. Nodes are flat and carry their
+//! parent path; the last chunk has `done` set.
+//!
+//! The walk skips the same entries as the file tree, stops at `maxDepth` and
+//! can be limited to the folders the user has expanded. A folder reached
+//! twice through symlinks is only walked the first time. A running stream is
+//! stopped with `cancel_project_structure_stream`.
+
+use super::list_unignored_entries;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::Emitter;
+
+const DEFAULT_BATCH_SIZE: usize = 500;
+
+const DEFAULT_MAX_DEPTH: usize = 8;
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StructureStreamOptions {
+    /// Levels below the root to load; defaults to 8
+    pub max_depth: Option<usize>,
+    /// Nodes per event; defaults to 500
+    pub batch_size: Option<usize>,
+    /// Only descend into these folders (the root is always read)
+    pub expanded: Option<Vec<String>>,
+    /// Also list entries excluded by .gitignore and other ignore files
+    pub include_ignored: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StructureNode {
+    pub name: String,
+    pub path: String,
+    pub parent: Option<String>,
+    pub is_directory: bool,
+    pub size: Option<u64>,
+    pub modified: Option<u64>,
+    /// 0 for the root, 1 for its entries, ...
+    pub depth: usize,
+    /// Whether this folder's entries are part of the stream
+    pub children_loaded: bool,
+    pub is_symlink: bool,
+    pub symlink_target: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StructureChunk {
+    pub stream_id: String,
+    pub root: String,
+    pub nodes: Vec<StructureNode>,
+    /// Nodes sent so far, including this chunk
+    pub sent: usize,
+    pub done: bool,
+    pub cancelled: bool,
+}
+
+static STREAMS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static NEXT_STREAM_ID: AtomicU64 = AtomicU64::new(1);
+
+fn structure_node(
+    path: &Path,
+    metadata: &fs::Metadata,
+    parent: Option<&Path>,
+    depth: usize,
+    children_loaded: bool,
+) -> StructureNode {
+    StructureNode {
+        name: path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+        path: path.to_string_lossy().to_string(),
+        parent: parent.map(|p| p.to_string_lossy().to_string()),
+        is_directory: metadata.is_dir(),
+        size: Some(metadata.len()),
+        modified: metadata
+            .modified()
+            .ok()
+            .and_then(|st| st.duration_since(std::time::SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs()),
+        depth,
+        children_loaded,
+        is_symlink: fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink()),
+        symlink_target: fs::read_link(path)
+            .ok()
+            .map(|target| target.to_string_lossy().to_string()),
+    }
+}
+
+/// Entries of a folder that are not ignored, folders first then by name
+fn sorted_entries(dir: &Path, include_ignored: bool) -> Vec<(PathBuf, fs::Metadata)> {
+    let mut entries: Vec<(PathBuf, fs::Metadata)> = list_unignored_entries(dir, include_ignored)
+        .into_iter()
+        .filter_map(|path| {
+            // Broken links are listed as files
+            let metadata = fs::metadata(&path)
+                .or_else(|_| fs::symlink_metadata(&path))
+                .ok()?;
+            Some((path, metadata))
+        })
+        .collect();
+    entries.sort_by(
+        |(a, a_meta), (b, b_meta)| match (a_meta.is_dir(), b_meta.is_dir()) {
+            (true, false) => std::cmp::Ordering::Less,
+            (false, true) => std::cmp::Ordering::Greater,
+            _ => a
+                .file_name()
+                .map(|n| n.to_string_lossy().to_lowercase())
+                .cmp(&b.file_name().map(|n| n.to_string_lossy().to_lowercase())),
+        },
+    );
+    entries
+}
+
+struct Stream {
+    window: tauri::Window,
+    id: String,
+    root: PathBuf,
+    cancelled: Arc<AtomicBool>,
+    batch: Vec<StructureNode>,
+    batch_size: usize,
+    sent: usize,
+}
+
+impl Stream {
+    fn push(&mut self, node: StructureNode) {
+        self.batch.push(node);
+        if self.batch.len() >= self.batch_size {
+            self.flush(false);
+        }
+    }
+
+    fn flush(&mut self, done: bool) {
+        let nodes = std::mem::take(&mut self.batch);
+        self.sent += nodes.len();
+        let chunk = StructureChunk {
+            stream_id: self.id.clone(),
+            root: self.root.to_string_lossy().to_string(),
+            nodes,
+            sent: self.sent,
+            done,
+            cancelled: done && self.cancelled.load(Ordering::Relaxed),
+        };
+        if let Err(e) = self.window.emit("project-structure-chunk", &chunk) {
+            eprintln!("[ProjectStructure] Failed to emit chunk: {}", e);
+        }
+    }
+}
+
+fn run_stream(mut stream: Stream, root_metadata: fs::Metadata, options: StructureStreamOptions) {
+    let max_depth = options.max_depth.unwrap_or(DEFAULT_MAX_DEPTH);
+    let expanded: Option<HashSet<PathBuf>> = options
+        .expanded
+        .map(|paths| paths.into_iter().map(PathBuf::from).collect());
+
+    let root = stream.root.clone();
+    stream.push(structure_node(
+        &root,
+        &root_metadata,
+        None,
+        0,
+        max_depth > 0,
+    ));
+
+    // Folders already walked, so symlink cycles are only followed once
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    visited.extend(fs::canonicalize(&root).ok());
+    let mut queue: VecDeque<(PathBuf, usize)> = VecDeque::new();
+    if max_depth > 0 {
+        queue.push_back((root, 0));
+    }
+    while let Some((dir, depth)) = queue.pop_front() {
+        if stream.cancelled.load(Ordering::Relaxed) {
+            break;
+        }
+        for (path, metadata) in sorted_entries(&dir, options.include_ignored) {
+            let descend = metadata.is_dir()
+                && depth + 1 < max_depth
+                && expanded.as_ref().is_none_or(|set| set.contains(&path))
+                && fs::canonicalize(&path).is_ok_and(|real| visited.insert(real));
+            stream.push(structure_node(
+                &path,
+                &metadata,
+                Some(&dir),
+                depth + 1,
+                descend,
+            ));
+            if descend {
+                queue.push_back((path, depth + 1));
+            }
+        }
+    }
+
+    stream.flush(true);
+    println!(
+        "[ProjectStructure] Stream {} sent {} nodes{}",
+        stream.id,
+        stream.sent,
+        if stream.cancelled.load(Ordering::Relaxed) {
+            " (cancelled)"
+        } else {
+            ""
+        }
+    );
+    if let Ok(mut streams) = STREAMS.lock() {
+        if streams
+            .get(&stream.id)
+            .is_some_and(|current| Arc::ptr_eq(current, &stream.cancelled))
+        {
+            streams.remove(&stream.id);
+        }
+    }
+}
+
+/// Walk a project tree in the background, emitting `project-structure-chunk`
+/// events to this window; returns the stream ID the events carry
+/// Pass `stream_id` to know the ID before the first event arrives
+#[tauri::command]
+pub fn stream_project_structure(
+    window: tauri::Window,
+    path: String,
+    stream_id: Option<String>,
+    options: Option<StructureStreamOptions>,
+) -> Result<String, String> {
+    let root = PathBuf::from(&path);
+    let root_metadata = fs::metadata(&root).map_err(|e| e.to_string())?;
+    if !root_metadata.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+
+    let id = stream_id.unwrap_or_else(|| {
+        format!(
+            "structure-{}",
+            NEXT_STREAM_ID.fetch_add(1, Ordering::Relaxed)
+        )
+    });
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let mut streams = STREAMS.lock().map_err(|e| e.to_string())?;
+        // Reusing an ID replaces the stream that had it
+        if let Some(previous) = streams.insert(id.clone(), cancelled.clone()) {
+            previous.store(true, Ordering::Relaxed);
+        }
+    }
+
+    let options = options.unwrap_or_default();
+    let stream = Stream {
+        window,
+        id: id.clone(),
+        root,
+        cancelled,
+        batch: Vec::new(),
+        batch_size: options.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1),
+        sent: 0,
+    };
+    std::thread::Builder::new()
+        .name("project-structure".to_string())
+        .spawn(move || run_stream(stream, root_metadata, options))
+        .map_err(|e| format!("Failed to start structure stream: {}", e))?;
+    Ok(id)
+}
+
+/// Stop a running structure stream; its last chunk has `cancelled` set
+#[tauri::command]
+pub fn cancel_project_structure_stream(stream_id: String) -> Result<bool, String> {
+    let streams = STREAMS.lock().map_err(|e| e.to_string())?;
+    Ok(match streams.get(&stream_id) {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    })
+}
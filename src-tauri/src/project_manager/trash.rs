@@ -0,0 +1,130 @@
+//! Trash
+//!
+//! Deleting from the explorer moves entries to the system trash (through the
+//! `trash` crate: the freedesktop.org trash, the macOS Trash or the Windows
+//! Recycle Bin) instead of removing them, so a mistaken delete can be undone.
+//!
+//! On Linux and Windows the trashed item is looked up afterwards and its id
+//! returned as `trashId`; `restore_from_trash` only restores an item that is
+//! still in the trash under that id. macOS doesn't let us list the Trash, so
+//! undo there goes through Finder.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::fs as async_fs;
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedItem {
+    pub original_path: String,
+    /// Where the item went, when the platform tells us
+    pub trash_id: Option<String>,
+    /// Unix timestamp (ms)
+    pub trashed_at: i64,
+    /// Whether `restore_from_trash` can bring it back
+    pub can_restore: bool,
+    /// Deleted for good instead of trashed
+    pub permanent: bool,
+}
+
+/// `path` the way the trash records it: absolute, with its folder resolved
+fn recorded_path(path: &Path) -> PathBuf {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => parent
+            .canonicalize()
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+mod platform {
+    use std::path::Path;
+    use trash::os_limited;
+
+    /// Id of the item most recently trashed from `original`
+    pub fn trashed_id(original: &Path) -> Option<String> {
+        os_limited::list()
+            .ok()?
+            .into_iter()
+            .filter(|item| item.original_path() == original)
+            .max_by_key(|item| item.time_deleted)
+            .map(|item| item.id.to_string_lossy().into_owned())
+    }
+
+    pub fn restore(id: &str) -> Result<(), String> {
+        let item = os_limited::list()
+            .map_err(|e| format!("Failed to read the trash: {}", e))?
+            .into_iter()
+            .find(|item| item.id.to_string_lossy() == id)
+            .ok_or("The item is no longer in the trash")?;
+        os_limited::restore_all([item]).map_err(|e| format!("Failed to restore: {}", e))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::path::Path;
+
+    pub fn trashed_id(_original: &Path) -> Option<String> {
+        None
+    }
+
+    pub fn restore(_id: &str) -> Result<(), String> {
+        Err("Restore the item from the Trash in Finder".to_string())
+    }
+}
+
+/// Move a file or folder to the system trash; with `permanent` it is deleted
+/// for good instead
+#[tauri::command]
+pub async fn delete_to_trash(path: String, permanent: Option<bool>) -> Result<TrashedItem, String> {
+    let target = PathBuf::from(&path);
+    let trashed_at = chrono::Utc::now().timestamp_millis();
+
+    if permanent.unwrap_or(false) {
+        super::delete_path(path.clone()).await?;
+        println!("[Trash] Permanently deleted {}", path);
+        return Ok(TrashedItem {
+            original_path: path,
+            trash_id: None,
+            trashed_at,
+            can_restore: false,
+            permanent: true,
+        });
+    }
+
+    async_fs::symlink_metadata(&target)
+        .await
+        .map_err(|e| e.to_string())?;
+    let trash_id = tokio::task::spawn_blocking(move || {
+        let recorded = recorded_path(&std::path::absolute(&target).map_err(|e| e.to_string())?);
+        trash::delete(&target).map_err(|e| format!("Failed to move to trash: {}", e))?;
+        Ok::<_, String>(platform::trashed_id(&recorded))
+    })
+    .await
+    .map_err(|e| format!("Trash task failed: {}", e))??;
+
+    println!("[Trash] Moved {} to the trash", path);
+    Ok(TrashedItem {
+        original_path: path,
+        can_restore: trash_id.is_some(),
+        trash_id,
+        trashed_at,
+        permanent: false,
+    })
+}
+
+/// Undo `delete_to_trash`: move a trashed item back to where it was. Only
+/// items still in the trash can be restored.
+#[tauri::command]
+pub async fn restore_from_trash(trash_id: String, original_path: String) -> Result<String, String> {
+    if async_fs::symlink_metadata(&original_path).await.is_ok() {
+        return Err(format!("{} already exists", original_path));
+    }
+    tokio::task::spawn_blocking(move || platform::restore(&trash_id))
+        .await
+        .map_err(|e| format!("Restore task failed: {}", e))??;
+    println!("[Trash] Restored {}", original_path);
+    Ok(original_path)
+}
@@ -0,0 +1,866 @@
+//! Virtual File Systems
+//!
+//! Lets remote locations be opened as read-mostly workspaces without
+//! mirroring them locally. A provider implements `FileSystemProvider` for a
+//! URI scheme; the user mounts a location (a folder on a WebDAV server)
+//! and everything under it is addressed as `<scheme>://<mount-id>/<path>`,
+//! so credentials never appear in URIs or in the frontend.
+//!
+//! Built in are `local` (a folder on disk, mostly a reference provider) and
+//! `webdav`. Watching is done by polling directory listings, which works for any
+//! provider.
+
+use crate::credential_manager::CredentialManager;
+use once_cell::sync::Lazy;
+use quick_xml::events::Event;
+use quick_xml::name::{Namespace, ResolveResult};
+use quick_xml::NsReader;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+pub type VfsFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 10;
+
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct VfsCapabilities {
+    pub writable: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VfsEntry {
+    pub name: String,
+    pub uri: String,
+    pub is_dir: bool,
+    pub size: Option<u64>,
+    /// Unix timestamp (ms)
+    pub modified: Option<i64>,
+    pub etag: Option<String>,
+}
+
+/// A mounted remote location
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VfsMount {
+    pub id: String,
+    pub scheme: String,
+    /// Provider-specific location, e.g. the WebDAV folder URL
+    pub root: String,
+    pub label: String,
+    pub read_only: bool,
+    pub username: Option<String>,
+    #[serde(skip)]
+    password: Option<String>,
+}
+
+impl VfsMount {
+    /// URI of a mount-relative path ("/a/b")
+    pub fn uri_for(&self, path: &str) -> String {
+        format!(
+            "{}://{}/{}",
+            self.scheme,
+            self.id,
+            path.trim_start_matches('/')
+        )
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VfsMountOptions {
+    pub label: Option<String>,
+    pub read_only: bool,
+    pub username: Option<String>,
+    /// Credential store ID holding the password or secret key
+    pub credential_id: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VfsProviderInfo {
+    pub scheme: String,
+    pub display_name: String,
+    pub capabilities: VfsCapabilities,
+}
+
+/// Payload of the `vfs:change` event
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VfsChangeEvent {
+    /// The watched directory
+    pub uri: String,
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// A file system reachable under a URI scheme
+///
+/// Paths are mount-relative and `/`-separated, starting with `/`.
+pub trait FileSystemProvider: Send + Sync {
+    fn scheme(&self) -> &'static str;
+    fn display_name(&self) -> &'static str;
+    fn capabilities(&self) -> VfsCapabilities;
+    fn stat<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, VfsEntry>;
+    fn read_dir<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, Vec<VfsEntry>>;
+    fn read_file<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, Vec<u8>>;
+    fn write_file<'a>(
+        &'a self,
+        mount: &'a VfsMount,
+        path: &'a str,
+        content: Vec<u8>,
+    ) -> VfsFuture<'a, ()>;
+    fn delete<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, ()>;
+    fn create_dir<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, ()>;
+}
+
+static PROVIDERS: Lazy<RwLock<HashMap<&'static str, Arc<dyn FileSystemProvider>>>> =
+    Lazy::new(|| {
+        let mut providers: HashMap<&'static str, Arc<dyn FileSystemProvider>> = HashMap::new();
+        for provider in [
+            Arc::new(LocalProvider) as Arc<dyn FileSystemProvider>,
+            Arc::new(WebDavProvider),
+        ] {
+            providers.insert(provider.scheme(), provider);
+        }
+        RwLock::new(providers)
+    });
+
+static MOUNTS: Lazy<RwLock<HashMap<String, VfsMount>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+static WATCHES: Lazy<RwLock<HashMap<String, Arc<AtomicBool>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Split `<scheme>://<mount>/<path>` and look up its provider and mount
+fn resolve(uri: &str) -> Result<(Arc<dyn FileSystemProvider>, VfsMount, String), String> {
+    let (scheme, rest) = uri
+        .split_once("://")
+        .ok_or_else(|| format!("Not a virtual file system URI: {}", uri))?;
+    let (mount_id, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    if path.split('/').any(|segment| segment == "..") {
+        return Err("Paths may not contain '..'".to_string());
+    }
+
+    let mount = MOUNTS
+        .read()
+        .map_err(|e| e.to_string())?
+        .get(mount_id)
+        .cloned()
+        .ok_or_else(|| format!("Unknown mount '{}'", mount_id))?;
+    if mount.scheme != scheme {
+        return Err(format!(
+            "Mount '{}' is a {} mount, not {}",
+            mount_id, mount.scheme, scheme
+        ));
+    }
+    let provider = PROVIDERS
+        .read()
+        .map_err(|e| e.to_string())?
+        .get(scheme)
+        .cloned()
+        .ok_or_else(|| format!("No file system provider for '{}'", scheme))?;
+    Ok((provider, mount, path.to_string()))
+}
+
+fn resolve_writable(uri: &str) -> Result<(Arc<dyn FileSystemProvider>, VfsMount, String), String> {
+    let resolved = resolve(uri)?;
+    if resolved.1.read_only || !resolved.0.capabilities().writable {
+        return Err(format!("{} is read-only", resolved.1.label));
+    }
+    Ok(resolved)
+}
+
+fn file_name(path: &str) -> String {
+    path.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+// ============================================================================
+// Local provider
+// ============================================================================
+
+/// A folder on this machine
+struct LocalProvider;
+
+impl LocalProvider {
+    /// Path on disk of a mount path. Symlinks may not lead outside the
+    /// mount root: the part of the path that exists is canonicalized and
+    /// must stay under the canonical root.
+    fn full_path(mount: &VfsMount, path: &str) -> Result<PathBuf, String> {
+        let root = std::fs::canonicalize(&mount.root)
+            .map_err(|e| format!("Mount root {} is unavailable: {}", mount.root, e))?;
+        let full = root.join(path.trim_start_matches('/'));
+
+        let mut existing: &Path = &full;
+        let resolved = loop {
+            match std::fs::canonicalize(existing) {
+                Ok(resolved) => break resolved,
+                Err(_) => match existing.parent() {
+                    Some(parent) => existing = parent,
+                    None => return Err(format!("{} is outside the mount", path)),
+                },
+            }
+        };
+        if !resolved.starts_with(&root) {
+            return Err(format!("{} resolves outside the mount", path));
+        }
+        Ok(full)
+    }
+
+    fn entry(mount: &VfsMount, path: &str, metadata: &std::fs::Metadata) -> VfsEntry {
+        VfsEntry {
+            name: file_name(path),
+            uri: mount.uri_for(path),
+            is_dir: metadata.is_dir(),
+            size: metadata.is_file().then_some(metadata.len()),
+            modified: metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as i64),
+            etag: None,
+        }
+    }
+}
+
+impl FileSystemProvider for LocalProvider {
+    fn scheme(&self) -> &'static str {
+        "local"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Local Folder"
+    }
+
+    fn capabilities(&self) -> VfsCapabilities {
+        VfsCapabilities { writable: true }
+    }
+
+    fn stat<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, VfsEntry> {
+        Box::pin(async move {
+            let metadata = tokio::fs::metadata(Self::full_path(mount, path)?)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(Self::entry(mount, path, &metadata))
+        })
+    }
+
+    fn read_dir<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, Vec<VfsEntry>> {
+        Box::pin(async move {
+            let mut entries = Vec::new();
+            let mut dir = tokio::fs::read_dir(Self::full_path(mount, path)?)
+                .await
+                .map_err(|e| e.to_string())?;
+            while let Some(child) = dir.next_entry().await.map_err(|e| e.to_string())? {
+                let Ok(metadata) = child.metadata().await else {
+                    continue;
+                };
+                let child_path = format!(
+                    "{}/{}",
+                    path.trim_end_matches('/'),
+                    child.file_name().to_string_lossy()
+                );
+                entries.push(Self::entry(mount, &child_path, &metadata));
+            }
+            Ok(entries)
+        })
+    }
+
+    fn read_file<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            tokio::fs::read(Self::full_path(mount, path)?)
+                .await
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    fn write_file<'a>(
+        &'a self,
+        mount: &'a VfsMount,
+        path: &'a str,
+        content: Vec<u8>,
+    ) -> VfsFuture<'a, ()> {
+        Box::pin(async move {
+            tokio::fs::write(Self::full_path(mount, path)?, content)
+                .await
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    fn delete<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, ()> {
+        Box::pin(async move {
+            let full_path = Self::full_path(mount, path)?;
+            if full_path.is_dir() {
+                tokio::fs::remove_dir_all(full_path).await
+            } else {
+                tokio::fs::remove_file(full_path).await
+            }
+            .map_err(|e| e.to_string())
+        })
+    }
+
+    fn create_dir<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, ()> {
+        Box::pin(async move {
+            tokio::fs::create_dir_all(Self::full_path(mount, path)?)
+                .await
+                .map_err(|e| e.to_string())
+        })
+    }
+}
+
+// ============================================================================
+// WebDAV provider
+// ============================================================================
+
+static HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()
+        .unwrap_or_default()
+});
+
+const DAV_NAMESPACE: &[u8] = b"DAV:";
+
+/// `DAV:` elements of a response whose text is kept
+const DAV_TEXT_PROPERTIES: [&[u8]; 4] =
+    [b"href", b"getcontentlength", b"getlastmodified", b"getetag"];
+
+/// One `<D:response>` of a PROPFIND multistatus
+#[derive(Default)]
+struct DavResponse {
+    href: Option<String>,
+    is_collection: bool,
+    content_length: Option<String>,
+    last_modified: Option<String>,
+    etag: Option<String>,
+}
+
+impl DavResponse {
+    fn field(&mut self, name: &[u8]) -> Option<&mut Option<String>> {
+        match name {
+            b"href" => Some(&mut self.href),
+            b"getcontentlength" => Some(&mut self.content_length),
+            b"getlastmodified" => Some(&mut self.last_modified),
+            b"getetag" => Some(&mut self.etag),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a `207 Multi-Status` body; elements are matched by namespace, not
+/// by prefix, since servers pick their own prefixes for `DAV:`
+fn parse_multistatus(xml: &str) -> Result<Vec<DavResponse>, String> {
+    let invalid = |e: &dyn std::fmt::Display| format!("Invalid WebDAV response: {}", e);
+    let mut reader = NsReader::from_str(xml);
+    let mut responses = Vec::new();
+    let mut current: Option<DavResponse> = None;
+    // Text property being read: its local name and the text so far
+    let mut property: Option<(Vec<u8>, String)> = None;
+
+    loop {
+        let (namespace, event) = reader.read_resolved_event().map_err(|e| invalid(&e))?;
+        let in_dav = matches!(namespace, ResolveResult::Bound(Namespace(DAV_NAMESPACE)));
+        match event {
+            Event::Start(element) if in_dav => {
+                let name = element.local_name();
+                match (name.as_ref(), current.as_mut()) {
+                    (b"response", _) => current = Some(DavResponse::default()),
+                    (b"collection", Some(response)) => response.is_collection = true,
+                    (name, Some(_)) if DAV_TEXT_PROPERTIES.contains(&name) => {
+                        property = Some((name.to_vec(), String::new()));
+                    }
+                    _ => {}
+                }
+            }
+            Event::Empty(element) if in_dav => {
+                if let (b"collection", Some(response)) =
+                    (element.local_name().as_ref(), current.as_mut())
+                {
+                    response.is_collection = true;
+                }
+            }
+            Event::Text(text) => {
+                if let Some((_, value)) = property.as_mut() {
+                    value.push_str(&text.decode().map_err(|e| invalid(&e))?);
+                }
+            }
+            Event::CData(text) => {
+                if let Some((_, value)) = property.as_mut() {
+                    value.push_str(&text.decode().map_err(|e| invalid(&e))?);
+                }
+            }
+            Event::GeneralRef(reference) => {
+                if let Some((_, value)) = property.as_mut() {
+                    if let Some(c) = reference.resolve_char_ref().map_err(|e| invalid(&e))? {
+                        value.push(c);
+                    } else {
+                        let name = reference.decode().map_err(|e| invalid(&e))?;
+                        let resolved = quick_xml::escape::resolve_predefined_entity(&name)
+                            .ok_or_else(|| invalid(&format!("unknown entity &{};", name)))?;
+                        value.push_str(resolved);
+                    }
+                }
+            }
+            Event::End(element) if in_dav => {
+                let name = element.local_name();
+                if name.as_ref() == b"response" {
+                    responses.extend(current.take());
+                } else if property.as_ref().is_some_and(|(n, _)| n == name.as_ref()) {
+                    let (name, value) = property.take().unwrap_or_default();
+                    let value = value.trim();
+                    if let Some(field) = current.as_mut().and_then(|r| r.field(&name)) {
+                        *field = (!value.is_empty()).then(|| value.to_string());
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(responses)
+}
+
+/// Folder on a WebDAV server (Nextcloud, ownCloud, Apache mod_dav, ...)
+struct WebDavProvider;
+
+impl WebDavProvider {
+    fn url(mount: &VfsMount, path: &str) -> String {
+        let encoded: Vec<String> = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| urlencoding::encode(segment).into_owned())
+            .collect();
+        let mut url = format!("{}/{}", mount.root.trim_end_matches('/'), encoded.join("/"));
+        if path.ends_with('/') && !url.ends_with('/') {
+            url.push('/');
+        }
+        url
+    }
+
+    /// Path component of the mount root, to turn hrefs back into mount paths
+    fn root_path(mount: &VfsMount) -> String {
+        let after_scheme = mount
+            .root
+            .split_once("://")
+            .map_or(mount.root.as_str(), |(_, rest)| rest);
+        let path = after_scheme
+            .find('/')
+            .map_or("/", |index| &after_scheme[index..]);
+        urlencoding::decode(path.trim_end_matches('/'))
+            .map(|p| p.into_owned())
+            .unwrap_or_else(|_| path.to_string())
+    }
+
+    /// Basic auth sends the password in the clear, so it only goes over
+    /// HTTPS or to this machine
+    fn protects_credentials(root: &str) -> bool {
+        let Ok(url) = reqwest::Url::parse(root) else {
+            return false;
+        };
+        if url.scheme() == "https" {
+            return true;
+        }
+        let host = url.host_str().unwrap_or_default();
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        host.eq_ignore_ascii_case("localhost")
+            || host
+                .parse::<std::net::IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback())
+    }
+
+    fn request(
+        mount: &VfsMount,
+        method: &str,
+        path: &str,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        let method = reqwest::Method::from_bytes(method.as_bytes()).map_err(|e| e.to_string())?;
+        let mut request = HTTP_CLIENT.request(method, Self::url(mount, path));
+        if let Some(username) = &mount.username {
+            if !Self::protects_credentials(&mount.root) {
+                return Err(format!(
+                    "Refusing to send credentials to {} over unencrypted HTTP; use https://",
+                    mount.root
+                ));
+            }
+            request = request.basic_auth(username, mount.password.as_deref());
+        }
+        Ok(request)
+    }
+
+    async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, String> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("WebDAV server returned {}", response.status()));
+        }
+        Ok(response)
+    }
+
+    async fn propfind(
+        mount: &VfsMount,
+        path: &str,
+        depth: u8,
+    ) -> Result<Vec<(String, VfsEntry)>, String> {
+        let body = r#"<?xml version="1.0" encoding="utf-8"?><d:propfind xmlns:d="DAV:"><d:prop><d:resourcetype/><d:getcontentlength/><d:getlastmodified/><d:getetag/></d:prop></d:propfind>"#;
+        let request = Self::request(mount, "PROPFIND", path)?
+            .header("Depth", depth.to_string())
+            .header("Content-Type", "application/xml")
+            .body(body);
+        let xml = Self::send(request)
+            .await?
+            .text()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let root_path = Self::root_path(mount);
+        Ok(parse_multistatus(&xml)?
+            .into_iter()
+            .filter_map(|response| {
+                let href = response.href?;
+                // Hrefs may be absolute URLs or absolute paths
+                let href_path = match href.split_once("://") {
+                    Some((_, rest)) => rest.find('/').map_or("/", |i| &rest[i..]).to_string(),
+                    None => href,
+                };
+                let decoded = urlencoding::decode(&href_path).ok()?.into_owned();
+                let relative = decoded.strip_prefix(&root_path)?;
+                let relative = format!("/{}", relative.trim_matches('/'));
+
+                Some((
+                    relative.clone(),
+                    VfsEntry {
+                        name: file_name(&relative),
+                        uri: mount.uri_for(&relative),
+                        is_dir: response.is_collection,
+                        size: response.content_length.and_then(|s| s.parse().ok()),
+                        modified: response
+                            .last_modified
+                            .and_then(|s| chrono::DateTime::parse_from_rfc2822(&s).ok())
+                            .map(|t| t.timestamp_millis()),
+                        etag: response.etag,
+                    },
+                ))
+            })
+            .collect())
+    }
+}
+
+impl FileSystemProvider for WebDavProvider {
+    fn scheme(&self) -> &'static str {
+        "webdav"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "WebDAV"
+    }
+
+    fn capabilities(&self) -> VfsCapabilities {
+        VfsCapabilities { writable: true }
+    }
+
+    fn stat<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, VfsEntry> {
+        Box::pin(async move {
+            Self::propfind(mount, path, 0)
+                .await?
+                .into_iter()
+                .next()
+                .map(|(_, entry)| entry)
+                .ok_or_else(|| format!("{} not found", path))
+        })
+    }
+
+    fn read_dir<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, Vec<VfsEntry>> {
+        Box::pin(async move {
+            let dir = format!("/{}", path.trim_matches('/'));
+            let dir_url_path = format!("{}/", dir.trim_end_matches('/'));
+            Ok(Self::propfind(mount, &dir_url_path, 1)
+                .await?
+                .into_iter()
+                // The folder itself is part of a Depth: 1 listing
+                .filter(|(relative, _)| *relative != dir)
+                .map(|(_, entry)| entry)
+                .collect())
+        })
+    }
+
+    fn read_file<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            let response = Self::send(Self::request(mount, "GET", path)?).await?;
+            response
+                .bytes()
+                .await
+                .map(|b| b.to_vec())
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    fn write_file<'a>(
+        &'a self,
+        mount: &'a VfsMount,
+        path: &'a str,
+        content: Vec<u8>,
+    ) -> VfsFuture<'a, ()> {
+        Box::pin(async move {
+            Self::send(Self::request(mount, "PUT", path)?.body(content)).await?;
+            Ok(())
+        })
+    }
+
+    fn delete<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, ()> {
+        Box::pin(async move {
+            Self::send(Self::request(mount, "DELETE", path)?).await?;
+            Ok(())
+        })
+    }
+
+    fn create_dir<'a>(&'a self, mount: &'a VfsMount, path: &'a str) -> VfsFuture<'a, ()> {
+        Box::pin(async move {
+            Self::send(Self::request(mount, "MKCOL", path)?).await?;
+            Ok(())
+        })
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn vfs_list_providers() -> Result<Vec<VfsProviderInfo>, String> {
+    let providers = PROVIDERS.read().map_err(|e| e.to_string())?;
+    let mut list: Vec<VfsProviderInfo> = providers
+        .values()
+        .map(|provider| VfsProviderInfo {
+            scheme: provider.scheme().to_string(),
+            display_name: provider.display_name().to_string(),
+            capabilities: provider.capabilities(),
+        })
+        .collect();
+    list.sort_by(|a, b| a.scheme.cmp(&b.scheme));
+    Ok(list)
+}
+
+/// Mount a remote location; its contents are then available at the returned mount's URIs
+#[tauri::command]
+pub async fn vfs_mount(
+    scheme: String,
+    root: String,
+    options: Option<VfsMountOptions>,
+) -> Result<VfsMount, String> {
+    let options = options.unwrap_or_default();
+    let provider = PROVIDERS
+        .read()
+        .map_err(|e| e.to_string())?
+        .get(scheme.as_str())
+        .cloned()
+        .ok_or_else(|| format!("No file system provider for '{}'", scheme))?;
+    let password = match &options.credential_id {
+        Some(id) => Some(CredentialManager::get_credential(id)?),
+        None => None,
+    };
+
+    let mount = VfsMount {
+        id: uuid::Uuid::new_v4().to_string().replace('-', "")[..12].to_string(),
+        label: options.label.unwrap_or_else(|| root.clone()),
+        scheme,
+        root,
+        read_only: options.read_only || !provider.capabilities().writable,
+        username: options.username,
+        password,
+    };
+    // Fail now rather than on first use if the location is unreachable
+    provider.stat(&mount, "/").await?;
+
+    MOUNTS
+        .write()
+        .map_err(|e| e.to_string())?
+        .insert(mount.id.clone(), mount.clone());
+    println!("[VFS] Mounted {} as {}", mount.label, mount.uri_for("/"));
+    Ok(mount)
+}
+
+#[tauri::command]
+pub fn vfs_unmount(mount_id: String) -> Result<(), String> {
+    let removed = MOUNTS.write().map_err(|e| e.to_string())?.remove(&mount_id);
+    if let Some(mount) = removed {
+        let prefix = mount.uri_for("/");
+        if let Ok(mut watches) = WATCHES.write() {
+            watches.retain(|uri, cancelled| {
+                let keep = !uri.starts_with(&prefix);
+                if !keep {
+                    cancelled.store(true, Ordering::Relaxed);
+                }
+                keep
+            });
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn vfs_list_mounts() -> Result<Vec<VfsMount>, String> {
+    let mut mounts: Vec<VfsMount> = MOUNTS
+        .read()
+        .map_err(|e| e.to_string())?
+        .values()
+        .cloned()
+        .collect();
+    mounts.sort_by(|a, b| a.label.cmp(&b.label));
+    Ok(mounts)
+}
+
+#[tauri::command]
+pub async fn vfs_stat(uri: String) -> Result<VfsEntry, String> {
+    let (provider, mount, path) = resolve(&uri)?;
+    provider.stat(&mount, &path).await
+}
+
+/// List a directory, folders first
+#[tauri::command]
+pub async fn vfs_read_dir(uri: String) -> Result<Vec<VfsEntry>, String> {
+    let (provider, mount, path) = resolve(&uri)?;
+    let mut entries = provider.read_dir(&mount, &path).await?;
+    entries.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    Ok(entries)
+}
+
+#[tauri::command]
+pub async fn vfs_read_file(uri: String) -> Result<String, String> {
+    let (provider, mount, path) = resolve(&uri)?;
+    let bytes = provider.read_file(&mount, &path).await?;
+    String::from_utf8(bytes).map_err(|_| format!("{} is not a text file", uri))
+}
+
+#[tauri::command]
+pub async fn vfs_write_file(uri: String, content: String) -> Result<(), String> {
+    let (provider, mount, path) = resolve_writable(&uri)?;
+    provider
+        .write_file(&mount, &path, content.into_bytes())
+        .await
+}
+
+#[tauri::command]
+pub async fn vfs_delete(uri: String) -> Result<(), String> {
+    let (provider, mount, path) = resolve_writable(&uri)?;
+    provider.delete(&mount, &path).await
+}
+
+#[tauri::command]
+pub async fn vfs_create_dir(uri: String) -> Result<(), String> {
+    let (provider, mount, path) = resolve_writable(&uri)?;
+    provider.create_dir(&mount, &path).await
+}
+
+/// Poll a directory and emit `vfs:change` when its entries change
+#[tauri::command]
+pub fn vfs_watch(app: AppHandle, uri: String, interval_secs: Option<u64>) -> Result<(), String> {
+    resolve(&uri)?;
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let mut watches = WATCHES.write().map_err(|e| e.to_string())?;
+        if watches.contains_key(&uri) {
+            return Ok(());
+        }
+        watches.insert(uri.clone(), cancelled.clone());
+    }
+    let interval = interval_secs.unwrap_or(DEFAULT_POLL_INTERVAL_SECS).max(1);
+
+    tauri::async_runtime::spawn(async move {
+        let mut previous: Option<HashMap<String, VfsEntry>> = None;
+        while !cancelled.load(Ordering::Relaxed) {
+            // The mount may have been removed since the last poll
+            let Ok((provider, mount, path)) = resolve(&uri) else {
+                break;
+            };
+            match provider.read_dir(&mount, &path).await {
+                Ok(entries) => {
+                    let current: HashMap<String, VfsEntry> = entries
+                        .into_iter()
+                        .map(|entry| (entry.uri.clone(), entry))
+                        .collect();
+                    if let Some(previous) = &previous {
+                        let event = VfsChangeEvent {
+                            uri: uri.clone(),
+                            added: current
+                                .keys()
+                                .filter(|key| !previous.contains_key(*key))
+                                .cloned()
+                                .collect(),
+                            changed: current
+                                .iter()
+                                .filter(|(key, entry)| {
+                                    previous.get(*key).is_some_and(|old| old != *entry)
+                                })
+                                .map(|(key, _)| key.clone())
+                                .collect(),
+                            removed: previous
+                                .keys()
+                                .filter(|key| !current.contains_key(*key))
+                                .cloned()
+                                .collect(),
+                        };
+                        if !(event.added.is_empty()
+                            && event.changed.is_empty()
+                            && event.removed.is_empty())
+                        {
+                            if let Err(e) = app.emit("vfs:change", &event) {
+                                eprintln!("[VFS] Failed to emit change event: {}", e);
+                            }
+                        }
+                    }
+                    previous = Some(current);
+                }
+                Err(e) => eprintln!("[VFS] Polling {} failed: {}", uri, e),
+            }
+
+            // Poll less often while the app is in efficiency mode
+            let factor = match crate::power_manager::current_mode() {
+                crate::power_manager::PowerMode::Efficiency => 4,
+                crate::power_manager::PowerMode::Normal => 1,
+            };
+            tokio::time::sleep(Duration::from_secs(interval * factor)).await;
+            crate::background_pause::wait_while_paused_async(
+                crate::background_pause::BackgroundSubsystem::Watchers,
+            )
+            .await;
+        }
+        if let Ok(mut watches) = WATCHES.write() {
+            if watches
+                .get(&uri)
+                .is_some_and(|flag| Arc::ptr_eq(flag, &cancelled))
+            {
+                watches.remove(&uri);
+            }
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn vfs_unwatch(uri: String) -> Result<(), String> {
+    if let Some(cancelled) = WATCHES.write().map_err(|e| e.to_string())?.remove(&uri) {
+        cancelled.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
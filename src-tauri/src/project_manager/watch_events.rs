@@ -0,0 +1,421 @@
+//! Watcher Event Coalescing
+//!
+//! Raw notify events arrive in bursts: a save is often a create, a few
+//! modifies and a rename; a checkout touches thousands of files. Project
+//! watchers record them here instead of emitting each one, and one flusher
+//! thread shared by all watchers delivers the net effect per path once a
+//! burst goes quiet:
+//! - created then modified is still created; created then deleted is nothing
+//! - deleted then created is modified
+//! - a rename is one `renamed` change carrying the old path
+//!
+//! Each flush emits `file-changes` (typed changes) and `file-change` (the
+//! plain list of touched paths, for older listeners) to the owning window.
+//! Nothing is delivered while the watcher is paused, or while watchers are
+//! paused globally; held changes arrive coalesced on resume. The flusher
+//! sleeps until the next burst is due or a watcher wakes it.
+//!
+//! A watcher can also be given include/exclude globs (gitignore syntax,
+//! relative to the workspace), so build output doesn't reach the frontend.
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::event::{ModifyKind, RenameMode};
+use notify::EventKind;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, Once, Weak};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+/// Quiet time after the last event before a burst is delivered
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// A continuous stream of events is still delivered this often
+const MAX_DELAY: Duration = Duration::from_millis(1000);
+
+/// How often held changes are rechecked while their watcher is paused
+const PAUSED_RECHECK: Duration = Duration::from_millis(250);
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChangeKind {
+    Created,
+    Modified,
+    Deleted,
+    Renamed,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChange {
+    pub path: String,
+    pub kind: FileChangeKind,
+    /// For renames, where the file was before
+    pub old_path: Option<String>,
+}
+
+struct Change {
+    kind: FileChangeKind,
+    old_path: Option<PathBuf>,
+    /// Arrival order, so changes are delivered in the order they happened
+    order: u64,
+}
+
+/// Include/exclude globs of a project watcher
+pub(super) struct WatchFilter {
+    root: PathBuf,
+    include: Option<Gitignore>,
+    exclude: Option<Gitignore>,
+}
+
+fn build_globs(root: &Path, globs: &[String]) -> Result<Option<Gitignore>, String> {
+    let globs: Vec<&str> = globs
+        .iter()
+        .map(|glob| glob.trim())
+        .filter(|glob| !glob.is_empty())
+        .collect();
+    if globs.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GitignoreBuilder::new(root);
+    for glob in globs {
+        builder
+            .add_line(None, glob)
+            .map_err(|e| format!("Invalid watch filter '{}': {}", glob, e))?;
+    }
+    builder.build().map(Some).map_err(|e| e.to_string())
+}
+
+impl WatchFilter {
+    pub(super) fn new(root: &Path, include: &[String], exclude: &[String]) -> Result<Self, String> {
+        Ok(WatchFilter {
+            root: root.to_path_buf(),
+            include: build_globs(root, include)?,
+            exclude: build_globs(root, exclude)?,
+        })
+    }
+
+    /// Whether changes to `path` reach the frontend: not excluded, and
+    /// included when there are include globs. A glob matching a folder
+    /// covers everything in it.
+    pub(super) fn allows(&self, path: &Path) -> bool {
+        if !path.starts_with(&self.root) {
+            return true;
+        }
+        let is_dir = path.is_dir();
+        let matches =
+            |globs: &Gitignore| globs.matched_path_or_any_parents(path, is_dir).is_ignore();
+        !self.exclude.as_ref().is_some_and(matches) && self.include.as_ref().is_none_or(matches)
+    }
+}
+
+/// Changes recorded since the last flush, one per path
+#[derive(Default)]
+pub(super) struct PendingChanges {
+    changes: HashMap<PathBuf, Change>,
+    next_order: u64,
+    first_at: Option<Instant>,
+    last_at: Option<Instant>,
+}
+
+impl PendingChanges {
+    /// Whether nothing was recorded since the last flush
+    pub(super) fn is_idle(&self) -> bool {
+        self.first_at.is_none()
+    }
+
+    fn touch(&mut self) -> u64 {
+        let now = Instant::now();
+        self.first_at.get_or_insert(now);
+        self.last_at = Some(now);
+        self.next_order += 1;
+        self.next_order
+    }
+
+    fn insert(&mut self, path: PathBuf, kind: FileChangeKind, old_path: Option<PathBuf>) {
+        let order = self.touch();
+        self.changes.insert(
+            path,
+            Change {
+                kind,
+                old_path,
+                order,
+            },
+        );
+    }
+
+    fn record(&mut self, path: &Path, kind: FileChangeKind) {
+        use FileChangeKind::*;
+        // Renamed then deleted: all that's left is the original going away
+        if kind == Deleted {
+            let original = self
+                .changes
+                .get(path)
+                .filter(|change| change.kind == Renamed)
+                .and_then(|change| change.old_path.clone());
+            if let Some(original) = original {
+                self.changes.remove(path);
+                self.insert(original, Deleted, None);
+                return;
+            }
+        }
+        let merged = match (self.changes.get(path).map(|c| c.kind), kind) {
+            (None, kind) => Some(kind),
+            (Some(Created), Modified) => Some(Created),
+            (Some(Created), Deleted) => None,
+            (Some(Deleted), Created) => Some(Modified),
+            (Some(Renamed), Modified) => Some(Renamed),
+            (Some(_), kind) => Some(kind),
+        };
+        match merged {
+            Some(kind) => {
+                let old_path = self
+                    .changes
+                    .get(path)
+                    .filter(|_| kind == Renamed)
+                    .and_then(|c| c.old_path.clone());
+                self.insert(path.to_path_buf(), kind, old_path);
+            }
+            None => {
+                self.changes.remove(path);
+                self.touch();
+            }
+        }
+    }
+
+    fn record_rename(&mut self, from: &Path, to: &Path) {
+        // Replaces the deleted/created pair reported for the two halves
+        let previous = self.changes.remove(from);
+        self.changes.remove(to);
+        match previous.map(|c| (c.kind, c.old_path)) {
+            // Created and renamed within the burst
+            Some((FileChangeKind::Created, _)) => {
+                self.insert(to.to_path_buf(), FileChangeKind::Created, None)
+            }
+            // Renamed twice: report the whole move
+            Some((FileChangeKind::Renamed, Some(original))) => {
+                self.insert(to.to_path_buf(), FileChangeKind::Renamed, Some(original))
+            }
+            _ => self.insert(
+                to.to_path_buf(),
+                FileChangeKind::Renamed,
+                Some(from.to_path_buf()),
+            ),
+        }
+    }
+
+    /// Record a notify event for the paths that passed the watcher's filter
+    pub(super) fn record_event(&mut self, kind: &EventKind, paths: &[&PathBuf]) {
+        match kind {
+            EventKind::Create(_) => {
+                for path in paths {
+                    self.record(path, FileChangeKind::Created);
+                }
+            }
+            EventKind::Remove(_) => {
+                for path in paths {
+                    self.record(path, FileChangeKind::Deleted);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+                self.record_rename(paths[0], paths[1]);
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                for path in paths {
+                    self.record(path, FileChangeKind::Deleted);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                for path in paths {
+                    self.record(path, FileChangeKind::Created);
+                }
+            }
+            EventKind::Access(_) => {}
+            // Renames some platforms report one path at a time: decide by
+            // whether the path is still there
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                for path in paths {
+                    let kind = if path.exists() {
+                        FileChangeKind::Created
+                    } else {
+                        FileChangeKind::Deleted
+                    };
+                    self.record(path, kind);
+                }
+            }
+            EventKind::Any | EventKind::Other => {
+                for path in paths {
+                    let kind = if path.exists() {
+                        FileChangeKind::Modified
+                    } else {
+                        FileChangeKind::Deleted
+                    };
+                    self.record(path, kind);
+                }
+            }
+            EventKind::Modify(_) => {
+                for path in paths {
+                    self.record(path, FileChangeKind::Modified);
+                }
+            }
+        }
+    }
+
+    /// Time until the recorded burst is due (zero once it is), `None` when
+    /// nothing was recorded
+    fn due_in(&self, debounce: Duration) -> Option<Duration> {
+        let (first, last) = (self.first_at?, self.last_at?);
+        let quiet = debounce.saturating_sub(last.elapsed());
+        let capped = MAX_DELAY.max(debounce).saturating_sub(first.elapsed());
+        Some(quiet.min(capped))
+    }
+
+    fn take(&mut self) -> Vec<FileChange> {
+        self.first_at = None;
+        self.last_at = None;
+        let mut changes: Vec<(PathBuf, Change)> = self.changes.drain().collect();
+        changes.sort_by_key(|(_, change)| change.order);
+        changes
+            .into_iter()
+            .map(|(path, change)| FileChange {
+                path: path.to_string_lossy().to_string(),
+                kind: change.kind,
+                old_path: change.old_path.map(|p| p.to_string_lossy().to_string()),
+            })
+            .collect()
+    }
+}
+
+/// A watcher's pending changes and the window they go to
+struct FlushTarget {
+    window: tauri::Window,
+    pending: Weak<Mutex<PendingChanges>>,
+    pauses: Weak<AtomicUsize>,
+}
+
+#[derive(Default)]
+struct Flusher {
+    targets: Mutex<Vec<FlushTarget>>,
+    woken: Mutex<bool>,
+    wake: Condvar,
+}
+
+static FLUSHER: Lazy<Flusher> = Lazy::new(Flusher::default);
+
+static FLUSHER_STARTED: Once = Once::new();
+
+/// Deliver coalesced changes to `window` until the watcher owning `pending`
+/// is dropped
+pub(super) fn register_flusher(
+    window: tauri::Window,
+    pending: Weak<Mutex<PendingChanges>>,
+    pauses: Weak<AtomicUsize>,
+) {
+    if let Ok(mut targets) = FLUSHER.targets.lock() {
+        targets.push(FlushTarget {
+            window,
+            pending,
+            pauses,
+        });
+    }
+    FLUSHER_STARTED.call_once(|| {
+        if let Err(e) = std::thread::Builder::new()
+            .name("watch-flusher".to_string())
+            .spawn(flush_loop)
+        {
+            eprintln!("Failed to start watcher flusher: {}", e);
+        }
+    });
+}
+
+/// Have the flusher look at pending changes again, after events were
+/// recorded or a watcher resumed
+pub(super) fn wake_flusher() {
+    if let Ok(mut woken) = FLUSHER.woken.lock() {
+        *woken = true;
+        FLUSHER.wake.notify_one();
+    }
+}
+
+fn flush_loop() {
+    loop {
+        // In power-efficiency mode events are batched for longer
+        let debounce = DEBOUNCE.max(Duration::from_millis(
+            crate::power_manager::current_budget().watcher_flush_ms,
+        ));
+        let globally_paused = crate::background_pause::is_paused(
+            crate::background_pause::BackgroundSubsystem::Watchers,
+        );
+
+        let mut next_wake: Option<Duration> = None;
+        let mut deliveries = Vec::new();
+        if let Ok(mut targets) = FLUSHER.targets.lock() {
+            // Dropping a watcher unregisters it
+            targets.retain(|target| target.pending.strong_count() > 0);
+            for target in targets.iter() {
+                let (Some(pending), Some(pauses)) =
+                    (target.pending.upgrade(), target.pauses.upgrade())
+                else {
+                    continue;
+                };
+                let Ok(mut pending) = pending.lock() else {
+                    continue;
+                };
+                let Some(due_in) = pending.due_in(debounce) else {
+                    continue;
+                };
+                let wait = if globally_paused || pauses.load(Ordering::Relaxed) > 0 {
+                    PAUSED_RECHECK
+                } else if due_in.is_zero() {
+                    deliveries.push((target.window.clone(), pending.take()));
+                    continue;
+                } else {
+                    due_in
+                };
+                next_wake = Some(next_wake.map_or(wait, |next| next.min(wait)));
+            }
+        }
+
+        for (window, changes) in deliveries {
+            emit_changes(&window, &changes);
+        }
+
+        let Ok(mut woken) = FLUSHER.woken.lock() else {
+            return;
+        };
+        if !*woken {
+            woken = match next_wake {
+                Some(timeout) => match FLUSHER.wake.wait_timeout(woken, timeout) {
+                    Ok((woken, _)) => woken,
+                    Err(_) => return,
+                },
+                None => match FLUSHER.wake.wait(woken) {
+                    Ok(woken) => woken,
+                    Err(_) => return,
+                },
+            };
+        }
+        *woken = false;
+    }
+}
+
+fn emit_changes(window: &tauri::Window, changes: &[FileChange]) {
+    if changes.is_empty() {
+        return;
+    }
+    let mut paths: Vec<&str> = Vec::with_capacity(changes.len());
+    for change in changes {
+        paths.extend(change.old_path.as_deref());
+        paths.push(&change.path);
+    }
+    let label = window.label();
+    if let Err(e) = window.emit_to(label, "file-changes", changes) {
+        eprintln!("Failed to emit file-changes event: {:?}", e);
+    }
+    if let Err(e) = window.emit_to(label, "file-change", &paths) {
+        eprintln!("Failed to emit file-change event: {:?}", e);
+    }
+}
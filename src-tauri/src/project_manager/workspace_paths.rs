@@ -0,0 +1,144 @@
+//! Workspace-Relative Paths
+//!
+//! Converting between absolute and workspace-relative paths by string prefix
+//! breaks as soon as the same folder is reached two ways: through a symlink,
+//! as `\\?\C:\...` versus `C:\...`, or with a differently cased drive letter
+//! on Windows. Both sides are normalized here before comparing:
+//! - resolved through symlinks, as far as the path exists (a deleted file is
+//!   resolved through its nearest existing parent)
+//! - Windows verbatim prefixes dropped (`\\?\C:\x` becomes `C:\x`,
+//!   `\\?\UNC\server\share` becomes `\\server\share`)
+//! - compared case-insensitively on Windows
+//!
+//! A `..` that climbs above the root (or above the start of a relative path)
+//! makes a path invalid rather than being dropped or kept.
+//!
+//! Relative paths always use `/`.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// `\\?\C:\x` -> `C:\x`, `\\?\UNC\server\share\x` -> `\\server\share\x`
+#[cfg(windows)]
+fn strip_verbatim(path: PathBuf) -> PathBuf {
+    let text = path.to_string_lossy();
+    if let Some(rest) = text.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", rest))
+    } else if let Some(rest) = text.strip_prefix(r"\\?\") {
+        PathBuf::from(rest)
+    } else {
+        path
+    }
+}
+
+#[cfg(not(windows))]
+fn strip_verbatim(path: PathBuf) -> PathBuf {
+    path
+}
+
+/// Resolve `.` and `..` without touching the file system; `None` when a
+/// `..` has no directory left to leave
+fn lexical_normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                _ => return None,
+            },
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    Some(normalized)
+}
+
+/// Canonical form of `path`, resolving symlinks through the longest part of
+/// it that exists
+pub(crate) fn normalize_path(path: &Path) -> Option<PathBuf> {
+    let path = lexical_normalize(path)?;
+    let mut existing = path.as_path();
+    let mut rest: Vec<&std::ffi::OsStr> = Vec::new();
+    loop {
+        if let Ok(canonical) = fs::canonicalize(existing) {
+            let mut resolved = strip_verbatim(canonical);
+            resolved.extend(rest.iter().rev());
+            return Some(resolved);
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                existing = parent;
+            }
+            _ => return Some(strip_verbatim(path.clone())),
+        }
+    }
+}
+
+#[cfg(windows)]
+fn same_component(a: &std::ffi::OsStr, b: &std::ffi::OsStr) -> bool {
+    a.to_string_lossy().to_lowercase() == b.to_string_lossy().to_lowercase()
+}
+
+#[cfg(not(windows))]
+fn same_component(a: &std::ffi::OsStr, b: &std::ffi::OsStr) -> bool {
+    a == b
+}
+
+/// `path` relative to `root`, component by component
+fn strip_root(root: &Path, path: &Path) -> Option<PathBuf> {
+    let mut root_components = root.components();
+    let mut path_components = path.components();
+    for root_component in root_components.by_ref() {
+        let path_component = path_components.next()?;
+        if !same_component(root_component.as_os_str(), path_component.as_os_str()) {
+            return None;
+        }
+    }
+    Some(path_components.as_path().to_path_buf())
+}
+
+fn to_slashes(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// `path` relative to the workspace, with `/` separators; `None` when it is
+/// outside the workspace
+pub(crate) fn workspace_relative(workspace: &Path, path: &Path) -> Option<String> {
+    // As given first, so paths under a symlinked folder keep their names
+    let as_given = |path: &Path| lexical_normalize(path).map(strip_verbatim);
+    let relative = strip_root(&as_given(workspace)?, &as_given(path)?)
+        .or_else(|| strip_root(&normalize_path(workspace)?, &normalize_path(path)?))?;
+    Some(to_slashes(&relative))
+}
+
+/// Path relative to the workspace (with `/` separators, "" for the workspace
+/// itself); `None` when it is outside the workspace
+#[tauri::command]
+pub fn to_workspace_relative(workspace: String, path: String) -> Result<Option<String>, String> {
+    Ok(workspace_relative(Path::new(&workspace), Path::new(&path)))
+}
+
+/// Absolute path of a workspace-relative one; paths leading outside the
+/// workspace are rejected
+#[tauri::command]
+pub fn from_workspace_relative(workspace: String, relative: String) -> Result<String, String> {
+    let relative = Path::new(&relative);
+    if relative.is_absolute() || relative.has_root() {
+        return Err(format!("{} is not a relative path", relative.display()));
+    }
+    let normalized = lexical_normalize(relative)
+        .ok_or_else(|| format!("{} points outside the workspace", relative.display()))?;
+    let workspace_path = lexical_normalize(Path::new(&workspace))
+        .ok_or_else(|| format!("{} is not a valid workspace path", workspace))?;
+    // The workspace as given, so results match the paths in the file tree
+    Ok(strip_verbatim(workspace_path)
+        .join(normalized)
+        .to_string_lossy()
+        .to_string())
+}
@@ -0,0 +1,226 @@
+//! Prompt Templates
+//!
+//! Named system-prompt templates (agent personas), stored one per file as
+//! `~/.rainy-aether/prompt-templates/<name>.json` so a team can share them
+//! by copying the files or keeping the folder in a repository. Every save
+//! bumps the template's `version`; a save made against an older version is
+//! rejected instead of overwriting someone else's edit.
+//!
+//! Content references variables as `{{name}}`. When an agent session is
+//! created from a template the frontend passes `workspaceName`,
+//! `workspaceRoot`, `language` and `openFile`; variables without a value are
+//! left in place so a missing one is easy to spot.
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const MAX_NAME_LEN: usize = 64;
+
+static VARIABLE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_.]*)\s*\}\}").unwrap());
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// System prompt text with `{{variable}}` placeholders
+    pub content: String,
+    /// Starts at 1 and increases with every save
+    #[serde(default = "first_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub updated_at: i64,
+    /// Variables referenced by the content, in order of first use
+    #[serde(default)]
+    pub variables: Vec<String>,
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplateInput {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub content: String,
+    /// Version the edit started from; the save fails if the stored template
+    /// has moved on. Unset overwrites unconditionally.
+    #[serde(default)]
+    pub base_version: Option<u32>,
+}
+
+fn templates_dir() -> Option<PathBuf> {
+    Some(
+        dirs::home_dir()?
+            .join(".rainy-aether")
+            .join("prompt-templates"),
+    )
+}
+
+/// Names double as file names, so they are restricted to a safe character set
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(format!(
+            "Template name must be 1 to {} characters",
+            MAX_NAME_LEN
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid template name '{}': use letters, digits, '-' and '_'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+fn template_path(name: &str) -> Result<PathBuf, String> {
+    validate_name(name)?;
+    let dir = templates_dir().ok_or("Could not determine home directory")?;
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+/// Variable names referenced by `content`, without duplicates
+fn variables_in(content: &str) -> Vec<String> {
+    let mut variables: Vec<String> = Vec::new();
+    for captures in VARIABLE.captures_iter(content) {
+        let name = &captures[1];
+        if !variables.iter().any(|v| v == name) {
+            variables.push(name.to_string());
+        }
+    }
+    variables
+}
+
+/// Replace `{{name}}` placeholders that have a value
+fn interpolate(content: &str, values: &HashMap<String, String>) -> String {
+    VARIABLE
+        .replace_all(content, |captures: &regex::Captures| {
+            values
+                .get(&captures[1])
+                .cloned()
+                .unwrap_or_else(|| captures[0].to_string())
+        })
+        .into_owned()
+}
+
+fn read_template(path: &std::path::Path) -> Result<PromptTemplate, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut template: PromptTemplate = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid template {}: {}", path.display(), e))?;
+    // The file may have been edited by hand or copied under another name
+    if let Some(stem) = path.file_stem() {
+        template.name = stem.to_string_lossy().to_string();
+    }
+    template.variables = variables_in(&template.content);
+    Ok(template)
+}
+
+fn load(name: &str) -> Result<Option<PromptTemplate>, String> {
+    let path = template_path(name)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    read_template(&path).map(Some)
+}
+
+/// All templates, sorted by name. Unreadable files are skipped.
+#[tauri::command]
+pub fn list_prompt_templates() -> Result<Vec<PromptTemplate>, String> {
+    let Some(dir) = templates_dir() else {
+        return Ok(Vec::new());
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+
+    let mut templates: Vec<PromptTemplate> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| match read_template(&path) {
+            Ok(template) => Some(template),
+            Err(e) => {
+                eprintln!("[PromptTemplates] {}", e);
+                None
+            }
+        })
+        .collect();
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(templates)
+}
+
+#[tauri::command]
+pub fn get_prompt_template(name: String) -> Result<Option<PromptTemplate>, String> {
+    load(&name)
+}
+
+/// Create or update a template, bumping its version
+#[tauri::command]
+pub fn save_prompt_template(template: PromptTemplateInput) -> Result<PromptTemplate, String> {
+    let path = template_path(&template.name)?;
+    let existing = load(&template.name)?;
+    if let (Some(existing), Some(base)) = (&existing, template.base_version) {
+        if existing.version != base {
+            return Err(format!(
+                "Template '{}' was changed elsewhere (now version {}, edited from version {})",
+                template.name, existing.version, base
+            ));
+        }
+    }
+
+    let now = Utc::now().timestamp_millis();
+    let saved = PromptTemplate {
+        variables: variables_in(&template.content),
+        version: existing.as_ref().map_or(1, |t| t.version + 1),
+        created_at: existing.as_ref().map_or(now, |t| t.created_at),
+        updated_at: now,
+        name: template.name,
+        description: template.description.filter(|d| !d.trim().is_empty()),
+        content: template.content,
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create templates directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&saved).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write template: {}", e))?;
+    Ok(saved)
+}
+
+/// Delete a template; returns false if it did not exist
+#[tauri::command]
+pub fn delete_prompt_template(name: String) -> Result<bool, String> {
+    let path = template_path(&name)?;
+    if !path.exists() {
+        return Ok(false);
+    }
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to delete template: {}", e))?;
+    Ok(true)
+}
+
+/// A template's content with its variables filled in
+#[tauri::command]
+pub fn render_prompt_template(
+    name: String,
+    variables: HashMap<String, String>,
+) -> Result<String, String> {
+    let template = load(&name)?.ok_or_else(|| format!("Prompt template '{}' not found", name))?;
+    Ok(interpolate(&template.content, &variables))
+}
@@ -0,0 +1,62 @@
+//! Provider Client
+//!
+//! Plumbing shared by the backend features that call AI providers directly
+//! rather than through an agent session (commit messages, inline
+//! completions): API keys from the credential manager, the HTTP client and
+//! error handling for JSON responses.
+//!
+//! API keys are stored under the same `<provider>_api_key` names the agent
+//! settings use, so a key entered there works everywhere.
+
+use crate::credential_manager::CredentialManager;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+
+pub(crate) static HTTP_CLIENT: Lazy<reqwest::Client> =
+    Lazy::new(|| reqwest::Client::builder().build().unwrap_or_default());
+
+/// API key of a provider; `google` shares the `gemini` key
+pub(crate) fn api_key(provider: &str) -> Result<String, String> {
+    let id = match provider {
+        "google" => "gemini",
+        other => other,
+    };
+    CredentialManager::get_credential(&format!("{}_api_key", id))
+        .map_err(|_| format!("No API key configured for {}", provider))
+}
+
+/// OpenAI-compatible chat completions endpoint of a provider
+pub(crate) fn chat_completions_endpoint(provider: &str) -> Option<&'static str> {
+    match provider {
+        "openai" => Some("https://api.openai.com/v1/chat/completions"),
+        "groq" => Some("https://api.groq.com/openai/v1/chat/completions"),
+        "cerebras" => Some("https://api.cerebras.ai/v1/chat/completions"),
+        _ => None,
+    }
+}
+
+/// Send a request and parse its JSON body, turning error statuses into the
+/// provider's error message
+pub(crate) async fn send(
+    request: reqwest::RequestBuilder,
+    provider: &str,
+) -> Result<Value, String> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", provider, e))?;
+    let status = response.status();
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from {}: {}", provider, e))?;
+    if !status.is_success() {
+        let message = body
+            .pointer("/error/message")
+            .or_else(|| body.get("error"))
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        return Err(format!("{} returned {}: {}", provider, status, message));
+    }
+    Ok(body)
+}
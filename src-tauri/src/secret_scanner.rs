@@ -0,0 +1,561 @@
+//! Secret Scanner
+//!
+//! Finds credentials in text: well-known token formats (cloud keys, VCS and
+//! chat tokens, private key blocks, JWTs) plus high-entropy values assigned to
+//! secret-looking names. Used on save from the editor and as a pre-commit
+//! check by the git module.
+//!
+//! Findings can be silenced three ways:
+//! - a `rainy-secrets-ignore` (or `pragma: allowlist secret`) comment on the line
+//! - the `secrets.ignorePaths` setting (gitignore syntax)
+//! - the workspace baseline in `.rainy/secrets-baseline.json`, which records
+//!   fingerprints of accepted findings
+
+use ignore::gitignore::GitignoreBuilder;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Markers that suppress findings on the same line
+const INLINE_IGNORE_MARKERS: [&str; 2] = ["rainy-secrets-ignore", "pragma: allowlist secret"];
+
+/// Minimum Shannon entropy (bits per char) for generic secret assignments
+const GENERIC_ENTROPY_THRESHOLD: f64 = 3.5;
+
+/// Long lines (minified bundles, data blobs) are scanned in windows of this
+/// many bytes, so a line costs time linear in its length
+const SCAN_WINDOW: usize = 4096;
+
+/// Bytes consecutive windows share, so a secret on a boundary is still found
+/// whole in one of them
+const SCAN_WINDOW_OVERLAP: usize = 512;
+
+/// Values that look like secrets but are placeholders
+const PLACEHOLDER_HINTS: [&str; 8] = [
+    "example",
+    "xxxx",
+    "changeme",
+    "placeholder",
+    "your_",
+    "your-",
+    "${",
+    "<",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretFinding {
+    pub rule_id: String,
+    pub description: String,
+    /// Workspace-relative path
+    pub path: String,
+    /// 1-based
+    pub line: u32,
+    /// 1-based, in characters
+    pub column: u32,
+    /// The matched text with most of the secret masked
+    pub redacted: String,
+    /// Stable id of the secret used by the baseline
+    pub fingerprint: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct BaselineEntry {
+    fingerprint: String,
+    rule_id: String,
+    path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct SecretsBaseline {
+    version: u32,
+    entries: Vec<BaselineEntry>,
+}
+
+struct SecretRule {
+    id: &'static str,
+    description: &'static str,
+    pattern: Regex,
+    /// Capture group holding the secret value (0 = whole match)
+    group: usize,
+    /// Require the value to pass the entropy/placeholder checks
+    entropy_check: bool,
+}
+
+static RULES: Lazy<Vec<SecretRule>> = Lazy::new(|| {
+    let rule = |id, description, pattern: &str, group, entropy_check| SecretRule {
+        id,
+        description,
+        pattern: Regex::new(pattern).expect("invalid secret rule"),
+        group,
+        entropy_check,
+    };
+    vec![
+        rule(
+            "private-key",
+            "Private key",
+            r"-----BEGIN (?:RSA |EC |DSA |OPENSSH |PGP |ENCRYPTED )?PRIVATE KEY(?: BLOCK)?-----",
+            0,
+            false,
+        ),
+        rule(
+            "aws-access-key-id",
+            "AWS access key ID",
+            r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b",
+            0,
+            false,
+        ),
+        rule(
+            "github-token",
+            "GitHub token",
+            r"\b(?:gh[pousr]_[A-Za-z0-9]{36,}|github_pat_[A-Za-z0-9_]{60,})\b",
+            0,
+            false,
+        ),
+        rule(
+            "gitlab-token",
+            "GitLab personal access token",
+            r"\bglpat-[A-Za-z0-9_-]{20,}\b",
+            0,
+            false,
+        ),
+        rule(
+            "slack-token",
+            "Slack token",
+            r"\bxox[baprs]-[A-Za-z0-9-]{10,}\b",
+            0,
+            false,
+        ),
+        rule(
+            "stripe-live-key",
+            "Stripe live key",
+            r"\b[sr]k_live_[A-Za-z0-9]{20,}\b",
+            0,
+            false,
+        ),
+        rule(
+            "google-api-key",
+            "Google API key",
+            r"\bAIza[0-9A-Za-z_-]{35}\b",
+            0,
+            false,
+        ),
+        rule(
+            "anthropic-api-key",
+            "Anthropic API key",
+            r"\bsk-ant-[A-Za-z0-9_-]{20,}",
+            0,
+            false,
+        ),
+        rule(
+            "openai-api-key",
+            "OpenAI API key",
+            r"\bsk-(?:proj-)?[A-Za-z0-9_-]{32,}",
+            0,
+            true,
+        ),
+        rule(
+            "jwt",
+            "JSON Web Token",
+            r"\beyJ[A-Za-z0-9_-]{10,}\.eyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}",
+            0,
+            false,
+        ),
+        rule(
+            "generic-secret",
+            "High-entropy value assigned to a secret-like name",
+            r#"(?i)(?:api[_-]?key|secret|token|passw(?:or)?d|auth[_-]?key|access[_-]?key|client[_-]?secret)[A-Za-z0-9_-]*["']?\s*[:=]\s*["']([^"'\s]{12,})["']"#,
+            1,
+            true,
+        ),
+    ]
+});
+
+/// Shannon entropy in bits per character
+fn shannon_entropy(value: &str) -> f64 {
+    let mut counts = std::collections::HashMap::new();
+    for c in value.chars() {
+        *counts.entry(c).or_insert(0usize) += 1;
+    }
+    let len = value.chars().count() as f64;
+    counts
+        .values()
+        .map(|&n| {
+            let p = n as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn looks_like_secret(value: &str) -> bool {
+    let lower = value.to_lowercase();
+    !PLACEHOLDER_HINTS.iter().any(|hint| lower.contains(hint))
+        && shannon_entropy(value) >= GENERIC_ENTROPY_THRESHOLD
+}
+
+fn redact(value: &str) -> String {
+    let visible: String = value.chars().take(4).collect();
+    format!(
+        "{}{}",
+        visible,
+        "*".repeat(value.chars().count().saturating_sub(4).min(12))
+    )
+}
+
+fn fingerprint(rule_id: &str, path: &str, secret: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(rule_id.as_bytes());
+    hasher.update([0]);
+    hasher.update(path.as_bytes());
+    hasher.update([0]);
+    hasher.update(secret.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// A secret found in a single line
+pub(crate) struct SecretSpan {
+    pub rule_id: &'static str,
+    pub description: &'static str,
+    /// Byte range of the secret within the line
+    pub range: std::ops::Range<usize>,
+}
+
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Whether a line carries an inline ignore marker
+fn has_ignore_marker(line: &str) -> bool {
+    INLINE_IGNORE_MARKERS
+        .iter()
+        .any(|marker| line.contains(marker))
+}
+
+/// Secrets in one line. Inline ignore markers are not applied here: they
+/// silence findings, but text sent to a provider is still redacted.
+pub(crate) fn secret_spans(line: &str) -> Vec<SecretSpan> {
+    let mut spans: Vec<SecretSpan> = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = floor_char_boundary(line, start + SCAN_WINDOW);
+        let last = end == line.len();
+        let window = &line[start..end];
+        for rule in RULES.iter() {
+            for captures in rule.pattern.captures_iter(window) {
+                let Some(secret) = captures.get(rule.group) else {
+                    continue;
+                };
+                let mut range = start + secret.start()..start + secret.end();
+                // Cut off by the window: take the whole match from the line
+                if !last && captures.get(0).is_some_and(|m| m.end() == window.len()) {
+                    match rule
+                        .pattern
+                        .captures_at(line, start + captures.get(0).map_or(0, |m| m.start()))
+                        .and_then(|full| full.get(rule.group))
+                    {
+                        Some(full) => range = full.range(),
+                        None => continue,
+                    }
+                }
+                // Already reported by a more specific rule or an earlier window
+                if spans.iter().any(|span| {
+                    span.range.contains(&range.start) || range.contains(&span.range.start)
+                }) {
+                    continue;
+                }
+                if rule.entropy_check && !looks_like_secret(&line[range.clone()]) {
+                    continue;
+                }
+                spans.push(SecretSpan {
+                    rule_id: rule.id,
+                    description: rule.description,
+                    range,
+                });
+            }
+        }
+        if last {
+            break;
+        }
+        start = floor_char_boundary(line, end - SCAN_WINDOW_OVERLAP);
+    }
+    spans.sort_by_key(|span| span.range.start);
+    spans
+}
+
+/// Scan numbered lines of one file (line numbers are 1-based)
+pub(crate) fn scan_lines<'a>(
+    path: &str,
+    lines: impl IntoIterator<Item = (u32, &'a str)>,
+) -> Vec<SecretFinding> {
+    let mut findings = Vec::new();
+    for (line_number, line) in lines
+        .into_iter()
+        .filter(|(_, line)| !has_ignore_marker(line))
+    {
+        for span in secret_spans(line) {
+            let secret = &line[span.range.clone()];
+            findings.push(SecretFinding {
+                rule_id: span.rule_id.to_string(),
+                description: span.description.to_string(),
+                path: path.to_string(),
+                line: line_number,
+                column: line[..span.range.start].chars().count() as u32 + 1,
+                redacted: redact(secret),
+                fingerprint: fingerprint(span.rule_id, path, secret),
+            });
+        }
+    }
+    findings
+}
+
+/// Scan a whole document
+pub(crate) fn scan_text(path: &str, content: &str) -> Vec<SecretFinding> {
+    scan_lines(
+        path,
+        content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i as u32 + 1, line)),
+    )
+}
+
+fn baseline_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(".rainy").join("secrets-baseline.json")
+}
+
+fn load_baseline(workspace_root: &Path) -> SecretsBaseline {
+    std::fs::read_to_string(baseline_path(workspace_root))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Drop findings that are baselined or in ignored paths
+pub(crate) fn filter_findings(
+    workspace_root: &Path,
+    findings: Vec<SecretFinding>,
+) -> Vec<SecretFinding> {
+    let baseline: HashSet<String> = load_baseline(workspace_root)
+        .entries
+        .into_iter()
+        .map(|entry| entry.fingerprint)
+        .collect();
+
+    let mut builder = GitignoreBuilder::new(workspace_root);
+    let workspace = workspace_root.to_str();
+    if let Some(serde_json::Value::Array(patterns)) =
+        crate::configuration_manager::resolve_setting(workspace, "secrets.ignorePaths")
+    {
+        for pattern in patterns.iter().filter_map(|p| p.as_str()) {
+            let _ = builder.add_line(None, pattern);
+        }
+    }
+    let ignored = builder.build().ok();
+
+    findings
+        .into_iter()
+        .filter(|finding| !baseline.contains(&finding.fingerprint))
+        .filter(|finding| {
+            let Some(ignored) = ignored.as_ref() else {
+                return true;
+            };
+            // The matcher panics on paths outside its root; those can't match
+            // workspace patterns, so they are always reported
+            let path = Path::new(&finding.path);
+            let relative = if path.is_absolute() {
+                match path.strip_prefix(workspace_root) {
+                    Ok(relative) => relative,
+                    Err(_) => return true,
+                }
+            } else {
+                path
+            };
+            !ignored
+                .matched_path_or_any_parents(relative, false)
+                .is_ignore()
+        })
+        .collect()
+}
+
+/// Whether commits with unresolved findings should be rejected
+pub(crate) fn blocks_commits(workspace_root: &Path) -> bool {
+    crate::configuration_manager::resolve_setting(workspace_root.to_str(), "secrets.blockCommits")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+fn relative_path(workspace_root: &Path, path: &str) -> String {
+    Path::new(path)
+        .strip_prefix(workspace_root)
+        .unwrap_or(Path::new(path))
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Scan a file for secrets, e.g. on save. Pass `content` to scan unsaved editor text.
+#[tauri::command]
+pub fn scan_file_for_secrets(
+    workspace_root: String,
+    path: String,
+    content: Option<String>,
+) -> Result<Vec<SecretFinding>, String> {
+    let root = Path::new(&workspace_root);
+    let content = match content {
+        Some(content) => content,
+        None => {
+            std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?
+        }
+    };
+
+    let findings = scan_text(&relative_path(root, &path), &content);
+    Ok(filter_findings(root, findings))
+}
+
+/// Accept findings so they are no longer reported
+#[tauri::command]
+pub fn add_secrets_to_baseline(
+    workspace_root: String,
+    findings: Vec<SecretFinding>,
+) -> Result<usize, String> {
+    let root = Path::new(&workspace_root);
+    let mut baseline = load_baseline(root);
+    baseline.version = 1;
+
+    let mut added = 0;
+    for finding in findings {
+        if baseline
+            .entries
+            .iter()
+            .any(|entry| entry.fingerprint == finding.fingerprint)
+        {
+            continue;
+        }
+        baseline.entries.push(BaselineEntry {
+            fingerprint: finding.fingerprint,
+            rule_id: finding.rule_id,
+            path: finding.path,
+        });
+        added += 1;
+    }
+
+    let path = baseline_path(root);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+    let content = serde_json::to_string_pretty(&baseline).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write baseline: {}", e))?;
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Built at runtime so the sources themselves don't trip secret scanners
+    fn aws_key() -> String {
+        format!("AKIA{}", "Q7X2M9TB4KWZ8R5N")
+    }
+
+    fn github_token() -> String {
+        format!("ghp_{}", "aB3dE5fG7hJ9kL2mN4pQ6rS8tU0vW1xY3zA5")
+    }
+
+    fn rule_ids(line: &str) -> Vec<&'static str> {
+        secret_spans(line).iter().map(|span| span.rule_id).collect()
+    }
+
+    #[test]
+    fn finds_known_token_formats() {
+        assert_eq!(
+            rule_ids(&format!("key = {}", aws_key())),
+            ["aws-access-key-id"]
+        );
+        assert_eq!(rule_ids(&github_token()), ["github-token"]);
+        assert_eq!(
+            rule_ids(&format!("-----BEGIN {} KEY-----", "RSA PRIVATE")),
+            ["private-key"]
+        );
+        assert!(rule_ids("nothing to see here").is_empty());
+    }
+
+    #[test]
+    fn generic_secrets_need_entropy_and_no_placeholder() {
+        let line = format!(r#"client_secret = "{}""#, "q8Zr2LmX9vTp4WkB7nJd");
+        let spans = secret_spans(&line);
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].rule_id, "generic-secret");
+        assert_eq!(&line[spans[0].range.clone()], "q8Zr2LmX9vTp4WkB7nJd");
+
+        assert!(rule_ids(r#"password = "aaaaaaaaaaaaaaaa""#).is_empty());
+        assert!(rule_ids(r#"api_key = "your_api_key_goes_here""#).is_empty());
+    }
+
+    #[test]
+    fn a_more_specific_rule_wins_over_generic() {
+        let line = format!(r#"token = "{}""#, github_token());
+        assert_eq!(rule_ids(&line), ["github-token"]);
+    }
+
+    #[test]
+    fn finds_secrets_across_scan_windows() {
+        let key = aws_key();
+        for offset in [
+            SCAN_WINDOW - 10,
+            SCAN_WINDOW - SCAN_WINDOW_OVERLAP - 5,
+            3 * SCAN_WINDOW,
+        ] {
+            let line = format!("{}{} tail", "a ".repeat(offset / 2), key);
+            let spans = secret_spans(&line);
+            assert_eq!(spans.len(), 1, "offset {}", offset);
+            assert_eq!(&line[spans[0].range.clone()], key);
+        }
+    }
+
+    #[test]
+    fn handles_multibyte_text_around_window_edges() {
+        let line = format!("{}{}", "é ".repeat(SCAN_WINDOW / 3), aws_key());
+        assert_eq!(rule_ids(&line), ["aws-access-key-id"]);
+    }
+
+    #[test]
+    fn scan_text_reports_positions_and_masks() {
+        let content = format!("first\n  é {}\n", aws_key());
+        let findings = scan_text("src/config.ts", &content);
+        assert_eq!(findings.len(), 1);
+        let finding = &findings[0];
+        assert_eq!((finding.line, finding.column), (2, 5));
+        assert_eq!(finding.redacted, "AKIA************");
+        assert_eq!(
+            finding.fingerprint,
+            fingerprint("aws-access-key-id", "src/config.ts", &aws_key())
+        );
+    }
+
+    #[test]
+    fn inline_markers_silence_findings_but_not_spans() {
+        let line = format!("{} // rainy-secrets-ignore", aws_key());
+        assert!(scan_text("a", &line).is_empty());
+        assert_eq!(secret_spans(&line).len(), 1);
+    }
+
+    #[test]
+    fn fingerprints_depend_on_path() {
+        assert_ne!(fingerprint("r", "a", "s"), fingerprint("r", "b", "s"));
+        assert_eq!(fingerprint("r", "a", "s"), fingerprint("r", "a", "s"));
+    }
+
+    #[test]
+    fn entropy_of_uniform_and_repeated_text() {
+        assert_eq!(shannon_entropy("aaaa"), 0.0);
+        assert!((shannon_entropy("abcd") - 2.0).abs() < 1e-9);
+    }
+}
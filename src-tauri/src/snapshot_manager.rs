@@ -0,0 +1,422 @@
+//! Snapshot Manager - Scoped file system snapshots for risky operations
+//!
+//! Captures a set of paths before agent runs, bulk replaces or branch switches
+//! with dirty files. File contents are stored once in a content-addressed object
+//! store under the app data directory; each snapshot is a small JSON manifest
+//! that can later be diffed against the working tree or restored.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tauri::{AppHandle, Manager};
+
+/// Maximum number of snapshots kept before the oldest are pruned
+const MAX_SNAPSHOTS: usize = 20;
+
+/// Snapshots older than this are pruned (7 days)
+const MAX_SNAPSHOT_AGE_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Files larger than this are not captured
+const MAX_SNAPSHOT_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Maximum number of files captured in a single snapshot
+const MAX_SNAPSHOT_FILES: usize = 5000;
+
+/// Directories never descended into when snapshotting a folder
+const SKIPPED_DIRS: [&str; 4] = [".git", "node_modules", "target", "dist"];
+
+/// Serializes writes to the snapshot store, so pruning never removes objects
+/// a snapshot being created has stored but not yet referenced
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_store() -> Result<MutexGuard<'static, ()>, String> {
+    STORE_LOCK
+        .lock()
+        .map_err(|e| format!("Failed to acquire snapshot store lock: {}", e))
+}
+
+/// A single file captured in a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    /// Path relative to the workspace root
+    pub path: String,
+    /// Content hash, None when the file did not exist at snapshot time
+    pub hash: Option<String>,
+    pub size: u64,
+}
+
+/// Snapshot manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub id: String,
+    pub label: String,
+    /// Why the snapshot was taken ("agent_run", "bulk_replace", "branch_switch", ...)
+    pub reason: String,
+    pub workspace_root: String,
+    pub created_at: i64,
+    pub files: Vec<SnapshotFile>,
+    /// Files left out for exceeding the size limit; restore leaves them alone
+    #[serde(default)]
+    pub skipped: Vec<String>,
+}
+
+/// Snapshot summary returned by list/create
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub label: String,
+    pub reason: String,
+    pub workspace_root: String,
+    pub created_at: i64,
+    pub file_count: usize,
+    pub total_size: u64,
+    pub skipped: Vec<String>,
+}
+
+/// Difference between a snapshot entry and the working tree
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotFileDiff {
+    pub path: String,
+    /// "unchanged", "modified", "deleted" or "created"
+    pub status: String,
+}
+
+impl From<&Snapshot> for SnapshotInfo {
+    fn from(snapshot: &Snapshot) -> Self {
+        Self {
+            id: snapshot.id.clone(),
+            label: snapshot.label.clone(),
+            reason: snapshot.reason.clone(),
+            workspace_root: snapshot.workspace_root.clone(),
+            created_at: snapshot.created_at,
+            file_count: snapshot.files.len(),
+            total_size: snapshot.files.iter().map(|f| f.size).sum(),
+            skipped: snapshot.skipped.clone(),
+        }
+    }
+}
+
+fn snapshots_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("snapshots");
+
+    fs::create_dir_all(dir.join("objects"))
+        .map_err(|e| format!("Failed to create snapshots directory: {}", e))?;
+
+    Ok(dir)
+}
+
+fn manifest_path(dir: &Path, id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid snapshot ID: {}", id));
+    }
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+fn hash_bytes(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
+}
+
+fn load_snapshot(dir: &Path, id: &str) -> Result<Snapshot, String> {
+    let path = manifest_path(dir, id)?;
+    let content = fs::read_to_string(&path).map_err(|_| format!("Snapshot not found: {}", id))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse snapshot: {}", e))
+}
+
+fn load_all_snapshots(dir: &Path) -> Vec<Snapshot> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut snapshots: Vec<Snapshot> = entries
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+    snapshots
+}
+
+/// Expand requested paths (files or folders) into workspace-relative file paths
+fn collect_files(root: &Path, paths: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    let mut seen = HashSet::new();
+
+    for path in paths {
+        let full = root.join(path);
+        if !full.starts_with(root) || Path::new(path).components().any(|c| c.as_os_str() == "..") {
+            return Err(format!("Path outside workspace: {}", path));
+        }
+
+        let candidates: Vec<PathBuf> = if full.is_dir() {
+            walkdir::WalkDir::new(&full)
+                .into_iter()
+                .filter_entry(|e| {
+                    !(e.file_type().is_dir()
+                        && SKIPPED_DIRS.contains(&e.file_name().to_string_lossy().as_ref()))
+                })
+                .flatten()
+                .filter(|e| e.file_type().is_file())
+                .map(|e| e.into_path())
+                .collect()
+        } else {
+            // Missing files are recorded too, so restore can remove them again
+            vec![full]
+        };
+
+        for candidate in candidates {
+            let relative = candidate
+                .strip_prefix(root)
+                .map_err(|_| format!("Path outside workspace: {}", candidate.display()))?
+                .to_path_buf();
+            if seen.insert(relative.clone()) {
+                files.push(relative);
+            }
+            if files.len() > MAX_SNAPSHOT_FILES {
+                return Err(format!(
+                    "Snapshot exceeds the limit of {} files",
+                    MAX_SNAPSHOT_FILES
+                ));
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Drop snapshots beyond the retention limits and unreferenced objects.
+/// Callers hold the store lock.
+fn prune(dir: &Path, _store: &MutexGuard<'static, ()>) {
+    let now = chrono::Utc::now().timestamp();
+    let snapshots = load_all_snapshots(dir);
+
+    let mut referenced = HashSet::new();
+    for (i, snapshot) in snapshots.iter().enumerate() {
+        if i >= MAX_SNAPSHOTS || now - snapshot.created_at > MAX_SNAPSHOT_AGE_SECS {
+            if let Ok(path) = manifest_path(dir, &snapshot.id) {
+                let _ = fs::remove_file(path);
+            }
+            continue;
+        }
+        referenced.extend(snapshot.files.iter().filter_map(|f| f.hash.clone()));
+    }
+
+    if let Ok(objects) = fs::read_dir(dir.join("objects")) {
+        for object in objects.flatten() {
+            let name = object.file_name().to_string_lossy().to_string();
+            if !referenced.contains(&name) {
+                let _ = fs::remove_file(object.path());
+            }
+        }
+    }
+}
+
+/// Snapshot a set of workspace paths (files or folders). Files over the size
+/// limit are not captured and are listed in `skipped`.
+#[tauri::command]
+pub async fn snapshot_create(
+    app: AppHandle,
+    workspace_root: String,
+    paths: Vec<String>,
+    label: Option<String>,
+    reason: Option<String>,
+) -> Result<SnapshotInfo, String> {
+    let dir = snapshots_dir(&app)?;
+
+    tokio::task::spawn_blocking(move || {
+        let root = PathBuf::from(&workspace_root);
+        let objects = dir.join("objects");
+        let mut files = Vec::new();
+        let mut skipped = Vec::new();
+        let store = lock_store()?;
+
+        for relative in collect_files(&root, &paths)? {
+            let full = root.join(&relative);
+            let path = relative.to_string_lossy().replace('\\', "/");
+
+            if !full.is_file() {
+                files.push(SnapshotFile {
+                    path,
+                    hash: None,
+                    size: 0,
+                });
+                continue;
+            }
+
+            let size = fs::metadata(&full).map(|m| m.len()).unwrap_or(0);
+            if size > MAX_SNAPSHOT_FILE_SIZE {
+                eprintln!("[Snapshot] Skipping large file: {}", path);
+                skipped.push(path);
+                continue;
+            }
+
+            let bytes = fs::read(&full).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            let hash = hash_bytes(&bytes);
+            let object = objects.join(&hash);
+            if !object.exists() {
+                fs::write(&object, &bytes)
+                    .map_err(|e| format!("Failed to store snapshot object: {}", e))?;
+            }
+
+            files.push(SnapshotFile {
+                path,
+                hash: Some(hash),
+                size,
+            });
+        }
+
+        let snapshot = Snapshot {
+            id: uuid::Uuid::new_v4().to_string(),
+            label: label.unwrap_or_else(|| "Snapshot".to_string()),
+            reason: reason.unwrap_or_else(|| "manual".to_string()),
+            workspace_root,
+            created_at: chrono::Utc::now().timestamp(),
+            files,
+            skipped,
+        };
+
+        let content = serde_json::to_string_pretty(&snapshot)
+            .map_err(|e| format!("Failed to serialize snapshot: {}", e))?;
+        fs::write(manifest_path(&dir, &snapshot.id)?, content)
+            .map_err(|e| format!("Failed to write snapshot: {}", e))?;
+
+        prune(&dir, &store);
+        drop(store);
+
+        println!(
+            "[Snapshot] Created {} ({} files, reason: {})",
+            snapshot.id,
+            snapshot.files.len(),
+            snapshot.reason
+        );
+
+        Ok(SnapshotInfo::from(&snapshot))
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {}", e))?
+}
+
+/// List snapshots, newest first, optionally filtered by workspace
+#[tauri::command]
+pub fn snapshot_list(
+    app: AppHandle,
+    workspace_root: Option<String>,
+) -> Result<Vec<SnapshotInfo>, String> {
+    let dir = snapshots_dir(&app)?;
+    Ok(load_all_snapshots(&dir)
+        .iter()
+        .filter(|s| {
+            workspace_root
+                .as_ref()
+                .is_none_or(|root| &s.workspace_root == root)
+        })
+        .map(SnapshotInfo::from)
+        .collect())
+}
+
+/// Compare a snapshot with the current working tree
+#[tauri::command]
+pub async fn snapshot_diff(app: AppHandle, id: String) -> Result<Vec<SnapshotFileDiff>, String> {
+    let dir = snapshots_dir(&app)?;
+    let snapshot = load_snapshot(&dir, &id)?;
+    let root = PathBuf::from(&snapshot.workspace_root);
+
+    tokio::task::spawn_blocking(move || {
+        snapshot
+            .files
+            .iter()
+            .map(|file| {
+                let current = fs::read(root.join(&file.path)).ok().map(|b| hash_bytes(&b));
+                let status = match (&file.hash, &current) {
+                    (Some(before), Some(after)) if before == after => "unchanged",
+                    (Some(_), Some(_)) => "modified",
+                    (Some(_), None) => "deleted",
+                    (None, Some(_)) => "created",
+                    (None, None) => "unchanged",
+                };
+                SnapshotFileDiff {
+                    path: file.path.clone(),
+                    status: status.to_string(),
+                }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {}", e))
+}
+
+/// Restore files from a snapshot (all files, or only the given relative paths).
+/// Files that did not exist when the snapshot was taken are removed.
+#[tauri::command]
+pub async fn snapshot_restore(
+    app: AppHandle,
+    id: String,
+    paths: Option<Vec<String>>,
+) -> Result<Vec<String>, String> {
+    let dir = snapshots_dir(&app)?;
+    let snapshot = load_snapshot(&dir, &id)?;
+    let root = PathBuf::from(&snapshot.workspace_root);
+
+    tokio::task::spawn_blocking(move || {
+        let filter: Option<HashSet<String>> = paths.map(|p| p.into_iter().collect());
+        let mut restored = Vec::new();
+
+        for file in &snapshot.files {
+            if filter.as_ref().is_some_and(|f| !f.contains(&file.path)) {
+                continue;
+            }
+
+            let target = root.join(&file.path);
+            match &file.hash {
+                Some(hash) => {
+                    let bytes = fs::read(dir.join("objects").join(hash))
+                        .map_err(|e| format!("Missing snapshot object for {}: {}", file.path, e))?;
+                    if fs::read(&target).ok().map(|b| hash_bytes(&b)).as_ref() == Some(hash) {
+                        continue;
+                    }
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                    }
+                    fs::write(&target, bytes)
+                        .map_err(|e| format!("Failed to restore {}: {}", file.path, e))?;
+                }
+                None => {
+                    if !target.is_file() {
+                        continue;
+                    }
+                    fs::remove_file(&target)
+                        .map_err(|e| format!("Failed to remove {}: {}", file.path, e))?;
+                }
+            }
+            restored.push(file.path.clone());
+        }
+
+        println!("[Snapshot] Restored {} files from {}", restored.len(), id);
+        Ok(restored)
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {}", e))?
+}
+
+/// Delete a snapshot and any objects no longer referenced
+#[tauri::command]
+pub fn snapshot_delete(app: AppHandle, id: String) -> Result<(), String> {
+    let dir = snapshots_dir(&app)?;
+    let path = manifest_path(&dir, &id)?;
+    let store = lock_store()?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete snapshot: {}", e))?;
+    }
+    prune(&dir, &store);
+    Ok(())
+}
@@ -0,0 +1,191 @@
+//! Startup Profiler
+//!
+//! Records how long each startup phase takes (state setup, plugin init, menu
+//! build, session restore, first window show) relative to process start.
+//! When the first window is shown the profile is complete: a summary is
+//! logged and the profile is appended to `~/.rainy-aether/startup-profiles.json`
+//! so regressions can be spotted across launches.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Number of past launches kept in the history file
+const MAX_PROFILE_HISTORY: usize = 20;
+
+/// A timed startup phase; instant marks have a zero duration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupPhase {
+    pub name: String,
+    /// Milliseconds since process start when the phase began
+    pub start_ms: f64,
+    pub duration_ms: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupProfile {
+    /// Unix timestamp (ms) of process start
+    pub started_at: i64,
+    pub app_version: String,
+    pub phases: Vec<StartupPhase>,
+    /// Milliseconds from process start to the first window being shown
+    pub total_ms: Option<f64>,
+    pub complete: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupProfileReport {
+    pub current: StartupProfile,
+    /// Completed profiles of earlier launches, newest last
+    pub history: Vec<StartupProfile>,
+}
+
+struct Profiler {
+    origin: Instant,
+    profile: StartupProfile,
+}
+
+static PROFILER: Lazy<Mutex<Profiler>> = Lazy::new(|| {
+    Mutex::new(Profiler {
+        origin: Instant::now(),
+        profile: StartupProfile {
+            started_at: chrono::Utc::now().timestamp_millis(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            phases: Vec::new(),
+            total_ms: None,
+            complete: false,
+        },
+    })
+});
+
+fn history_path() -> Option<PathBuf> {
+    Some(
+        dirs::home_dir()?
+            .join(".rainy-aether")
+            .join("startup-profiles.json"),
+    )
+}
+
+fn load_history() -> Vec<StartupProfile> {
+    history_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_history(profile: &StartupProfile) -> Result<(), String> {
+    let path = history_path().ok_or("Could not determine home directory")?;
+    let mut history = load_history();
+    history.push(profile.clone());
+    if history.len() > MAX_PROFILE_HISTORY {
+        history.drain(..history.len() - MAX_PROFILE_HISTORY);
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(&history).map_err(|e| e.to_string())?;
+    std::fs::write(path, content).map_err(|e| e.to_string())
+}
+
+/// Pin the process start time; call first thing in `run()`
+pub fn begin() {
+    Lazy::force(&PROFILER);
+}
+
+fn record(name: &str, started: Instant, ended: Instant) {
+    let Ok(mut profiler) = PROFILER.lock() else {
+        return;
+    };
+    if profiler.profile.complete {
+        return;
+    }
+    let start_ms = started
+        .saturating_duration_since(profiler.origin)
+        .as_secs_f64()
+        * 1000.0;
+    profiler.profile.phases.push(StartupPhase {
+        name: name.to_string(),
+        start_ms,
+        duration_ms: ended.saturating_duration_since(started).as_secs_f64() * 1000.0,
+    });
+}
+
+/// Time a startup phase
+pub(crate) fn phase<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = f();
+    record(name, started, Instant::now());
+    result
+}
+
+/// Record a phase that started at `started` and ends now
+pub(crate) fn phase_since(name: &str, started: Instant) {
+    record(name, started, Instant::now());
+}
+
+/// Record an instant in the startup timeline
+pub(crate) fn mark(name: &str) {
+    let now = Instant::now();
+    record(name, now, now);
+}
+
+/// Record the first window show and finish the profile
+pub(crate) fn complete() {
+    let profile = {
+        let Ok(mut profiler) = PROFILER.lock() else {
+            return;
+        };
+        if profiler.profile.complete {
+            return;
+        }
+        let total_ms = profiler.origin.elapsed().as_secs_f64() * 1000.0;
+        profiler.profile.phases.push(StartupPhase {
+            name: "window.firstShow".to_string(),
+            start_ms: total_ms,
+            duration_ms: 0.0,
+        });
+        profiler.profile.total_ms = Some(total_ms);
+        profiler.profile.complete = true;
+        profiler.profile.clone()
+    };
+
+    println!(
+        "[StartupProfiler] First window shown after {:.0}ms",
+        profile.total_ms.unwrap_or_default()
+    );
+    for phase in &profile.phases {
+        println!(
+            "[StartupProfiler]   {:<24} +{:>7.1}ms {:>7.1}ms",
+            phase.name, phase.start_ms, phase.duration_ms
+        );
+    }
+    if let Err(e) = save_history(&profile) {
+        eprintln!("[StartupProfiler] Failed to save profile history: {}", e);
+    }
+}
+
+/// Record a frontend startup milestone (e.g. "frontend.hydrated")
+#[tauri::command]
+pub fn record_startup_mark(name: String) -> Result<(), String> {
+    mark(&name);
+    Ok(())
+}
+
+/// Get the startup profile of this launch plus earlier launches for comparison
+#[tauri::command]
+pub fn get_startup_profile() -> Result<StartupProfileReport, String> {
+    let current = PROFILER.lock().map_err(|e| e.to_string())?.profile.clone();
+    let mut history = load_history();
+    if current.complete {
+        // This launch is already the last history entry
+        history.retain(|p| p.started_at != current.started_at);
+    }
+
+    Ok(StartupProfileReport { current, history })
+}
@@ -0,0 +1,328 @@
+// Session Environment - Captures the environment seen by terminals and tasks
+// Stored with the session so "works in terminal but not in task" issues can be explained
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tauri::{AppHandle, Manager};
+use tokio::time::{timeout, Duration};
+
+/// Toolchains whose versions are recorded in a snapshot (name, command, args)
+const TOOLCHAIN_PROBES: [(&str, &str, &str); 7] = [
+    ("rustc", "rustc", "--version"),
+    ("cargo", "cargo", "--version"),
+    ("node", "node", "--version"),
+    ("npm", "npm", "--version"),
+    ("python", "python3", "--version"),
+    ("go", "go", "version"),
+    ("git", "git", "--version"),
+];
+
+/// Variable name fragments whose values are never stored in plain text
+const SENSITIVE_MARKERS: [&str; 6] = ["TOKEN", "SECRET", "PASSWORD", "PASSWD", "KEY", "CREDENTIAL"];
+
+const PROBE_TIMEOUT_MS: u64 = 5000;
+
+/// Captured environment for one source ("process" for tasks, "login_shell" for terminals)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvironmentSnapshot {
+    pub source: String,
+    pub captured_at: i64,
+    pub path_entries: Vec<String>,
+    pub variables: BTreeMap<String, String>,
+    /// Toolchain name -> version output (None when not found)
+    pub toolchains: BTreeMap<String, Option<String>>,
+}
+
+/// A variable whose value differs between two snapshots
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedVariable {
+    pub name: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// A toolchain whose version differs between two snapshots
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedToolchain {
+    pub name: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Differences between a stored snapshot and a fresh capture
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvironmentDiff {
+    pub stored_source: String,
+    pub current_source: String,
+    pub path_added: Vec<String>,
+    pub path_removed: Vec<String>,
+    pub variables_added: Vec<String>,
+    pub variables_removed: Vec<String>,
+    pub variables_changed: Vec<ChangedVariable>,
+    pub toolchains_changed: Vec<ChangedToolchain>,
+}
+
+fn storage_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+
+    Ok(app_data_dir.join(".session-environment.json"))
+}
+
+fn load_snapshots(app: &AppHandle) -> Result<HashMap<String, EnvironmentSnapshot>, String> {
+    let path = storage_path(app)?;
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read session environment: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse session environment: {}", e))
+}
+
+fn save_snapshots(
+    app: &AppHandle,
+    snapshots: &HashMap<String, EnvironmentSnapshot>,
+) -> Result<(), String> {
+    let path = storage_path(app)?;
+    let content = serde_json::to_string_pretty(snapshots)
+        .map_err(|e| format!("Failed to serialize session environment: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write session environment: {}", e))
+}
+
+/// Replace sensitive values with a short hash so changes are still detectable
+fn redact(name: &str, value: &str) -> String {
+    let upper = name.to_uppercase();
+    if SENSITIVE_MARKERS
+        .iter()
+        .any(|marker| upper.contains(marker))
+    {
+        let mut hasher = Sha256::new();
+        hasher.update(value.as_bytes());
+        let digest = format!("{:x}", hasher.finalize());
+        format!("<redacted:{}>", &digest[..8])
+    } else {
+        value.to_string()
+    }
+}
+
+/// Login shell used by the integrated terminal (unix only)
+fn login_shell() -> Option<String> {
+    if cfg!(target_os = "windows") {
+        return None;
+    }
+    Some(std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string()))
+}
+
+/// Run a command directly or through the login shell, returning trimmed stdout
+async fn run_probe(shell: Option<&str>, program: &str, args: &[&str]) -> Option<String> {
+    let mut cmd = match shell {
+        Some(shell) => {
+            let mut c = tokio::process::Command::new(shell);
+            c.args(["-l", "-c", &format!("{} {}", program, args.join(" "))]);
+            c
+        }
+        None => {
+            let mut c = tokio::process::Command::new(program);
+            c.args(args);
+            c
+        }
+    };
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let output = timeout(Duration::from_millis(PROBE_TIMEOUT_MS), cmd.output())
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // Some tools (older python) print their version on stderr
+    let text = if output.stdout.is_empty() {
+        output.stderr
+    } else {
+        output.stdout
+    };
+    let text = String::from_utf8_lossy(&text).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+async fn capture(source: &str) -> Result<EnvironmentSnapshot, String> {
+    let shell = match source {
+        "process" => None,
+        "login_shell" => {
+            Some(login_shell().ok_or("Login shell capture is not supported on this platform")?)
+        }
+        other => return Err(format!("Unknown environment source: {}", other)),
+    };
+
+    let raw: Vec<(String, String)> = match shell.as_deref() {
+        Some(shell) => run_probe(Some(shell), "env", &[])
+            .await
+            .ok_or("Failed to read login shell environment")?
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        None => std::env::vars().collect(),
+    };
+
+    let path_entries = raw
+        .iter()
+        .find(|(k, _)| k == "PATH" || k == "Path")
+        .map(|(_, v)| {
+            std::env::split_paths(v)
+                .map(|p| p.to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let variables = raw.iter().map(|(k, v)| (k.clone(), redact(k, v))).collect();
+
+    let mut toolchains = BTreeMap::new();
+    for (name, program, arg) in TOOLCHAIN_PROBES {
+        let version = run_probe(shell.as_deref(), program, &[arg]).await;
+        toolchains.insert(name.to_string(), version);
+    }
+
+    Ok(EnvironmentSnapshot {
+        source: source.to_string(),
+        captured_at: chrono::Utc::now().timestamp(),
+        path_entries,
+        variables,
+        toolchains,
+    })
+}
+
+fn diff(stored: &EnvironmentSnapshot, current: &EnvironmentSnapshot) -> EnvironmentDiff {
+    let path_added = current
+        .path_entries
+        .iter()
+        .filter(|p| !stored.path_entries.contains(p))
+        .cloned()
+        .collect();
+    let path_removed = stored
+        .path_entries
+        .iter()
+        .filter(|p| !current.path_entries.contains(p))
+        .cloned()
+        .collect();
+
+    let variables_added = current
+        .variables
+        .keys()
+        .filter(|k| !stored.variables.contains_key(*k))
+        .cloned()
+        .collect();
+    let variables_removed = stored
+        .variables
+        .keys()
+        .filter(|k| !current.variables.contains_key(*k))
+        .cloned()
+        .collect();
+    let variables_changed = stored
+        .variables
+        .iter()
+        .filter(|(k, _)| k.as_str() != "PATH" && k.as_str() != "Path")
+        .filter_map(|(k, before)| {
+            let after = current.variables.get(k)?;
+            (after != before).then(|| ChangedVariable {
+                name: k.clone(),
+                before: before.clone(),
+                after: after.clone(),
+            })
+        })
+        .collect();
+
+    let toolchains_changed = stored
+        .toolchains
+        .iter()
+        .filter_map(|(name, before)| {
+            let after = current.toolchains.get(name).cloned().flatten();
+            (after != *before).then(|| ChangedToolchain {
+                name: name.clone(),
+                before: before.clone(),
+                after,
+            })
+        })
+        .collect();
+
+    EnvironmentDiff {
+        stored_source: stored.source.clone(),
+        current_source: current.source.clone(),
+        path_added,
+        path_removed,
+        variables_added,
+        variables_removed,
+        variables_changed,
+        toolchains_changed,
+    }
+}
+
+/// Capture the environment and store it with the session
+/// Source is "process" (what tasks inherit, default) or "login_shell" (what terminals see)
+#[tauri::command]
+pub async fn capture_session_environment(
+    app: AppHandle,
+    source: Option<String>,
+) -> Result<EnvironmentSnapshot, String> {
+    let source = source.unwrap_or_else(|| "process".to_string());
+    let snapshot = capture(&source).await?;
+
+    let mut snapshots = load_snapshots(&app)?;
+    snapshots.insert(source.clone(), snapshot.clone());
+    save_snapshots(&app, &snapshots)?;
+
+    eprintln!(
+        "[SessionEnvironment] Captured {}: {} variables, {} PATH entries",
+        source,
+        snapshot.variables.len(),
+        snapshot.path_entries.len()
+    );
+
+    Ok(snapshot)
+}
+
+/// Get the stored environment snapshot for a source
+#[tauri::command]
+pub fn get_session_environment(
+    app: AppHandle,
+    source: Option<String>,
+) -> Result<Option<EnvironmentSnapshot>, String> {
+    let source = source.unwrap_or_else(|| "process".to_string());
+    Ok(load_snapshots(&app)?.remove(&source))
+}
+
+/// Diff a stored snapshot against a fresh capture
+/// Compare a stored "login_shell" snapshot against the current "process" to see
+/// why a command works in the terminal but not in a task
+#[tauri::command]
+pub async fn diff_session_environment(
+    app: AppHandle,
+    stored_source: Option<String>,
+    current_source: Option<String>,
+) -> Result<EnvironmentDiff, String> {
+    let stored_source = stored_source.unwrap_or_else(|| "process".to_string());
+    let current_source = current_source.unwrap_or_else(|| stored_source.clone());
+
+    let stored = load_snapshots(&app)?
+        .remove(&stored_source)
+        .ok_or_else(|| format!("No stored environment for source: {}", stored_source))?;
+    let current = capture(&current_source).await?;
+
+    Ok(diff(&stored, &current))
+}
@@ -1,6 +1,8 @@
 // State Manager Module - Centralized session/app state management
 // This module replaces the fragmented TypeScript persistence with a robust Rust backend
 
+pub mod environment;
 pub mod session_state;
 
+pub use environment::*;
 pub use session_state::*;
@@ -101,7 +101,8 @@ pub fn get_session_state(
     state: State<'_, SessionStateManager>,
 ) -> Result<SessionState, String> {
     // Load from disk
-    let session_state = state.load_from_disk(&app)?;
+    let session_state =
+        crate::startup_profiler::phase("session.restore", || state.load_from_disk(&app))?;
 
     // Update in-memory state
     if let Ok(mut guard) = state.state.lock() {
@@ -1,14 +1,18 @@
 use std::collections::HashMap;
 use std::io::{Read, Write};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use base64::Engine;
+use once_cell::sync::Lazy;
 use portable_pty::{native_pty_system, Child, CommandBuilder, PtyPair, PtySize};
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, State};
 
+use crate::problem_matcher::{Diagnostic, ProblemMatcherEngine};
+
 #[cfg(target_os = "windows")]
 use dirs::home_dir;
 
@@ -49,6 +53,10 @@ pub struct TerminalSession {
     pub shutdown: Arc<AtomicBool>,
     pub created_at: u64,
     pub cwd: Option<String>,
+    /// The program enabled bracketed paste mode (`ESC[?2004h`)
+    pub bracketed_paste: Arc<AtomicBool>,
+    /// Problem matchers run over the output ("Compile from terminal")
+    pub problems: Arc<Mutex<Option<ProblemMatcherEngine>>>,
 }
 
 #[derive(Serialize, Clone)]
@@ -57,6 +65,12 @@ struct TerminalDataEvent {
     data: String,
 }
 
+#[derive(Serialize, Clone)]
+struct TerminalProblemsEvent {
+    id: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
 #[derive(Serialize, Clone)]
 struct TerminalStateEvent {
     id: String,
@@ -74,6 +88,244 @@ pub struct TerminalSessionInfo {
 
 use uuid::Uuid;
 
+const BRACKETED_PASTE_ON: &[u8] = b"\x1b[?2004h";
+const BRACKETED_PASTE_OFF: &[u8] = b"\x1b[?2004l";
+const PASTE_START: &str = "\x1b[200~";
+const PASTE_END: &str = "\x1b[201~";
+
+/// Unterminated OSC sequences longer than this are flushed to the output
+/// instead of being held back waiting for a terminator
+const MAX_OSC_LENGTH: usize = 8 * 1024;
+
+/// Session clipboard policies for OSC 52 writes
+const CLIPBOARD_ASK: u8 = 0;
+const CLIPBOARD_ALLOW: u8 = 1;
+const CLIPBOARD_DENY: u8 = 2;
+
+/// OSC 52 writes waiting for the user's permission, by request id. Each
+/// session has at most one; a newer write replaces the one still waiting.
+static PENDING_CLIPBOARD_WRITES: Lazy<Mutex<HashMap<String, PendingClipboardWrite>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct PendingClipboardWrite {
+    session_id: String,
+    text: String,
+    policy: Arc<AtomicU8>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ClipboardRequestEvent {
+    id: String,
+    request_id: String,
+    /// Start of the text the program wants to copy
+    preview: String,
+    length: usize,
+}
+
+/// Control sequences picked out of terminal output
+#[derive(Debug, PartialEq)]
+enum TerminalSignal {
+    BracketedPaste(bool),
+    ClipboardWrite(String),
+}
+
+/// Scans PTY output for bracketed paste mode switches and OSC 52 clipboard
+/// writes, carrying incomplete sequences over to the next read
+#[derive(Default)]
+struct OutputScanner {
+    carry: Vec<u8>,
+}
+
+impl OutputScanner {
+    /// Returns the bytes to forward to the frontend and the signals found.
+    /// OSC 52 sequences are removed from the output; everything else is kept.
+    fn process(&mut self, input: &[u8]) -> (Vec<u8>, Vec<TerminalSignal>) {
+        let mut data = std::mem::take(&mut self.carry);
+        data.extend_from_slice(input);
+
+        let mut output = Vec::with_capacity(data.len());
+        let mut signals = Vec::new();
+        let mut i = 0;
+        while i < data.len() {
+            if data[i] != 0x1b {
+                output.push(data[i]);
+                i += 1;
+                continue;
+            }
+            let rest = &data[i..];
+            if rest.len() == 1 {
+                self.carry = rest.to_vec();
+                break;
+            }
+
+            if rest[1] == b'[' {
+                let mut matched = false;
+                for (sequence, enabled) in [(BRACKETED_PASTE_ON, true), (BRACKETED_PASTE_OFF, false)]
+                {
+                    if rest.starts_with(sequence) {
+                        signals.push(TerminalSignal::BracketedPaste(enabled));
+                        matched = true;
+                    } else if rest.len() < sequence.len() && sequence.starts_with(rest) {
+                        self.carry = rest.to_vec();
+                        return (output, signals);
+                    }
+                }
+                let length = if matched { BRACKETED_PASTE_ON.len() } else { 1 };
+                output.extend_from_slice(&rest[..length]);
+                i += length;
+                continue;
+            }
+
+            if rest[1] == b']' {
+                // Terminated by BEL or ST (ESC \)
+                let end = rest[2..].iter().enumerate().find_map(|(j, &b)| match b {
+                    0x07 => Some((j + 2, 1)),
+                    0x1b if rest.get(j + 3) == Some(&b'\\') => Some((j + 2, 2)),
+                    _ => None,
+                });
+                let Some((body_end, terminator)) = end else {
+                    if rest.len() <= MAX_OSC_LENGTH {
+                        self.carry = rest.to_vec();
+                        return (output, signals);
+                    }
+                    output.extend_from_slice(rest);
+                    break;
+                };
+                let sequence_end = body_end + terminator;
+                match rest[2..body_end].strip_prefix(b"52;") {
+                    Some(body) => {
+                        if let Some(text) = decode_osc52(body) {
+                            signals.push(TerminalSignal::ClipboardWrite(text));
+                        }
+                    }
+                    None => output.extend_from_slice(&rest[..sequence_end]),
+                }
+                i += sequence_end;
+                continue;
+            }
+
+            output.push(data[i]);
+            i += 1;
+        }
+        (output, signals)
+    }
+}
+
+/// Text of an OSC 52 write (`<selection>;<base64>`); queries (`?`) are ignored
+fn decode_osc52(body: &[u8]) -> Option<String> {
+    let separator = body.iter().position(|&b| b == b';')?;
+    let payload = &body[separator + 1..];
+    if payload == b"?" {
+        return None;
+    }
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(payload)
+        .ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Write text to the system clipboard with the platform's clipboard tool
+fn write_system_clipboard(text: &str) -> Result<(), String> {
+    use std::process::{Command, Stdio};
+
+    #[cfg(target_os = "macos")]
+    let candidates: Vec<(&str, Vec<&str>)> = vec![("pbcopy", vec![])];
+    #[cfg(target_os = "windows")]
+    let candidates: Vec<(&str, Vec<&str>)> = vec![(
+        "powershell",
+        vec![
+            "-NoProfile",
+            "-Command",
+            "Set-Clipboard -Value ([Console]::In.ReadToEnd())",
+        ],
+    )];
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let candidates: Vec<(&str, Vec<&str>)> = vec![
+        ("wl-copy", vec![]),
+        ("xclip", vec!["-selection", "clipboard"]),
+        ("xsel", vec!["--clipboard", "--input"]),
+    ];
+
+    for (program, args) in candidates {
+        let Ok(mut child) = Command::new(program)
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .map_err(|e| format!("Failed to write to {}: {}", program, e))?;
+        }
+        let status = child.wait().map_err(|e| e.to_string())?;
+        if status.success() {
+            return Ok(());
+        }
+    }
+    Err("No clipboard tool available".to_string())
+}
+
+/// Apply a terminal's clipboard write according to the session policy and
+/// the `terminal.clipboardWrite` setting ("prompt", "allow" or "deny")
+fn handle_clipboard_write(
+    app: &AppHandle,
+    session_id: &str,
+    cwd: Option<&str>,
+    text: String,
+    policy: &Arc<AtomicU8>,
+) {
+    let setting = crate::configuration_manager::resolve_setting(cwd, "terminal.clipboardWrite")
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| "prompt".to_string());
+    let decision = match setting.as_str() {
+        "allow" => CLIPBOARD_ALLOW,
+        "deny" => CLIPBOARD_DENY,
+        _ => policy.load(Ordering::SeqCst),
+    };
+
+    match decision {
+        CLIPBOARD_ALLOW => {
+            if let Err(e) = write_system_clipboard(&text) {
+                eprintln!("[Terminal] Clipboard write failed: {}", e);
+            }
+        }
+        CLIPBOARD_DENY => {}
+        _ => {
+            let request_id = Uuid::new_v4().to_string();
+            let event = ClipboardRequestEvent {
+                id: session_id.to_string(),
+                request_id: request_id.clone(),
+                preview: text.chars().take(200).collect(),
+                length: text.chars().count(),
+            };
+            if let Ok(mut pending) = PENDING_CLIPBOARD_WRITES.lock() {
+                pending.retain(|_, write| write.session_id != session_id);
+                pending.insert(
+                    request_id,
+                    PendingClipboardWrite {
+                        session_id: session_id.to_string(),
+                        text,
+                        policy: policy.clone(),
+                    },
+                );
+            }
+            let _ = app.emit("terminal/clipboard-request", event);
+        }
+    }
+}
+
+/// Drop a session's unanswered clipboard write once the session is gone
+fn discard_clipboard_requests(session_id: &str) {
+    if let Ok(mut pending) = PENDING_CLIPBOARD_WRITES.lock() {
+        pending.retain(|_, write| write.session_id != session_id);
+    }
+}
+
 fn default_shell() -> String {
     #[cfg(target_os = "windows")]
     {
@@ -284,6 +536,25 @@ pub fn terminal_create(
     let child_arc = Arc::new(Mutex::new(Some(child)));
     let state_arc = Arc::new(Mutex::new(SessionState::Starting));
     let shutdown_arc = Arc::new(AtomicBool::new(false));
+    let bracketed_paste_arc = Arc::new(AtomicBool::new(false));
+    // OSC 52 clipboard policy remembered for this session (`CLIPBOARD_*`)
+    let clipboard_policy = Arc::new(AtomicU8::new(CLIPBOARD_ASK));
+    // Built-in matchers watch the output unless `terminal.detectProblems` is off
+    let detect_problems = crate::configuration_manager::resolve_setting(
+        working_dir.as_deref(),
+        "terminal.detectProblems",
+    )
+    .and_then(|v| v.as_bool())
+    .unwrap_or(true);
+    let problems_arc = Arc::new(Mutex::new(if detect_problems {
+        ProblemMatcherEngine::new(
+            crate::problem_matcher::default_matchers(),
+            working_dir.as_ref().map(std::path::PathBuf::from),
+        )
+        .ok()
+    } else {
+        None
+    }));
 
     let created_at = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -297,6 +568,9 @@ pub fn terminal_create(
     let child_clone = child_arc.clone();
     let shutdown_clone = shutdown_arc.clone();
     let sessions_ref = state.sessions.clone();
+    let bracketed_paste_clone = bracketed_paste_arc.clone();
+    let cwd_clone = working_dir.clone();
+    let problems_clone = problems_arc.clone();
 
     thread::spawn(move || {
         // Give shell a moment to initialize
@@ -317,6 +591,7 @@ pub fn terminal_create(
         }
 
         let mut buf = [0u8; 8192];
+        let mut scanner = OutputScanner::default();
         let mut consecutive_errors: u32 = 0;
         const MAX_CONSECUTIVE_ERRORS: u32 = 5;
 
@@ -347,7 +622,39 @@ pub fn terminal_create(
                 }
                 Ok(n) => {
                     consecutive_errors = 0; // Reset error counter on success
-                    let data = String::from_utf8_lossy(&buf[..n]).to_string();
+                    let (output, signals) = scanner.process(&buf[..n]);
+                    for signal in signals {
+                        match signal {
+                            TerminalSignal::BracketedPaste(enabled) => {
+                                bracketed_paste_clone.store(enabled, Ordering::SeqCst)
+                            }
+                            TerminalSignal::ClipboardWrite(text) => handle_clipboard_write(
+                                &app_handle,
+                                &session_id,
+                                cwd_clone.as_deref(),
+                                text,
+                                &clipboard_policy,
+                            ),
+                        }
+                    }
+                    if output.is_empty() {
+                        continue;
+                    }
+                    let data = String::from_utf8_lossy(&output).to_string();
+                    let diagnostics = problems_clone
+                        .lock()
+                        .ok()
+                        .and_then(|mut engine| engine.as_mut().map(|e| e.feed(&data)))
+                        .unwrap_or_default();
+                    if !diagnostics.is_empty() {
+                        let _ = app_handle.emit(
+                            "terminal/problems",
+                            TerminalProblemsEvent {
+                                id: session_id.clone(),
+                                diagnostics,
+                            },
+                        );
+                    }
                     let payload = TerminalDataEvent {
                         id: session_id.clone(),
                         data,
@@ -408,6 +715,8 @@ pub fn terminal_create(
             }
         }
 
+        discard_clipboard_requests(&session_id);
+
         // Reduced delay before auto-cleanup (500ms instead of 2s)
         thread::sleep(Duration::from_millis(500));
         if let Ok(mut sessions) = sessions_ref.lock() {
@@ -429,6 +738,8 @@ pub fn terminal_create(
                 shutdown: shutdown_arc,
                 created_at,
                 cwd: working_dir,
+                bracketed_paste: bracketed_paste_arc,
+                problems: problems_arc,
             },
         );
     }
@@ -449,6 +760,88 @@ pub fn terminal_write(state: State<TerminalState>, id: String, data: String) ->
             .map_err(|e| format!("write failed: {e}"))?;
         w.flush().ok();
     }
+    if data.contains('\r') {
+        crate::activity_tracker::record_terminal_command(session.cwd.as_deref());
+    }
+    Ok(())
+}
+
+/// Choose the problem matchers run over a session's output; `None` turns
+/// detection off. Problems are reported with `terminal/problems` events.
+#[tauri::command]
+pub fn terminal_set_problem_matchers(
+    state: State<TerminalState>,
+    id: String,
+    matchers: Option<Vec<String>>,
+) -> Result<(), String> {
+    let sessions = state.sessions.lock().map_err(|_| "lock poisoned")?;
+    let session = sessions
+        .get(&id)
+        .ok_or_else(|| format!("unknown session: {id}"))?;
+
+    let engine = match matchers {
+        Some(names) => {
+            let matchers = crate::problem_matcher::resolve_matchers(&names)?;
+            let cwd = session.cwd.as_ref().map(std::path::PathBuf::from);
+            Some(ProblemMatcherEngine::new(matchers, cwd)?)
+        }
+        None => None,
+    };
+    *session.problems.lock().map_err(|_| "lock poisoned")? = engine;
+    Ok(())
+}
+
+/// Paste text into a session, wrapped in bracketed paste markers when the
+/// program asked for them so pasted newlines aren't run as commands
+#[tauri::command]
+pub fn terminal_paste(state: State<TerminalState>, id: String, text: String) -> Result<(), String> {
+    let sessions = state.sessions.lock().map_err(|_| "lock poisoned")?;
+    let session = sessions
+        .get(&id)
+        .ok_or_else(|| format!("unknown session: {id}"))?;
+
+    // Pasted text must not be able to end the paste early
+    let text = text
+        .replace(PASTE_START, "")
+        .replace(PASTE_END, "")
+        .replace("\r\n", "\r")
+        .replace('\n', "\r");
+    let data = if session.bracketed_paste.load(Ordering::SeqCst) {
+        format!("{PASTE_START}{text}{PASTE_END}")
+    } else {
+        text
+    };
+
+    {
+        let mut w = session.writer.lock().map_err(|_| "writer lock poisoned")?;
+        w.write_all(data.as_bytes())
+            .map_err(|e| format!("write failed: {e}"))?;
+        w.flush().ok();
+    }
+    Ok(())
+}
+
+/// Answer a `terminal/clipboard-request`; `remember` applies the answer to
+/// later requests from the same session
+#[tauri::command]
+pub fn terminal_resolve_clipboard_request(
+    request_id: String,
+    allow: bool,
+    remember: Option<bool>,
+) -> Result<(), String> {
+    let request = PENDING_CLIPBOARD_WRITES
+        .lock()
+        .map_err(|_| "lock poisoned")?
+        .remove(&request_id)
+        .ok_or_else(|| format!("unknown clipboard request: {request_id}"))?;
+
+    if remember.unwrap_or(false) {
+        let policy = if allow { CLIPBOARD_ALLOW } else { CLIPBOARD_DENY };
+        request.policy.store(policy, Ordering::SeqCst);
+    }
+    if allow {
+        write_system_clipboard(&request.text)?;
+    }
     Ok(())
 }
 
@@ -488,6 +881,7 @@ pub fn terminal_kill(state: State<TerminalState>, id: String) -> Result<(), Stri
 
     // Signal shutdown to reader thread first
     session.shutdown.store(true, Ordering::SeqCst);
+    discard_clipboard_requests(&id);
 
     // Properly terminate child process with graceful shutdown
     if let Ok(mut child_opt) = session.child.lock() {
@@ -556,14 +950,298 @@ pub fn terminal_get_profiles(state: State<TerminalState>) -> Result<Vec<ShellPro
 }
 
 /// Initialize shell profiles detection
+/// Imported profiles are merged after the detected ones (same name replaces)
 #[tauri::command]
 pub fn terminal_init_profiles(state: State<TerminalState>) -> Result<Vec<ShellProfile>, String> {
-    let detected = detect_available_shells();
+    let mut detected = detect_available_shells();
+    merge_profiles(&mut detected, load_imported_profiles());
     let mut profiles = state.profiles.lock().map_err(|_| "lock poisoned")?;
     *profiles = detected.clone();
     Ok(detected)
 }
 
+/// Result of importing terminal profiles from another terminal
+#[derive(Serialize, Clone)]
+pub struct ProfileImportResult {
+    pub source: String,
+    pub settings_path: String,
+    pub imported: Vec<ShellProfile>,
+    /// Hidden or unresolvable profiles, by name
+    pub skipped: Vec<String>,
+    /// All profiles after merging
+    pub profiles: Vec<ShellProfile>,
+}
+
+/// Imported profiles are kept in ~/.rainy-aether/terminal-profiles.json
+fn imported_profiles_path() -> Option<std::path::PathBuf> {
+    dirs::home_dir().map(|home| home.join(".rainy-aether").join("terminal-profiles.json"))
+}
+
+fn load_imported_profiles() -> Vec<ShellProfile> {
+    imported_profiles_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_imported_profiles(profiles: &[ShellProfile]) -> Result<(), String> {
+    let path = imported_profiles_path().ok_or("Failed to get home directory")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create .rainy-aether directory: {e}"))?;
+    }
+    let content = serde_json::to_string_pretty(profiles)
+        .map_err(|e| format!("Failed to serialize profiles: {e}"))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write profiles: {e}"))
+}
+
+/// Merge profiles into `target`, replacing entries with the same name
+fn merge_profiles(target: &mut Vec<ShellProfile>, incoming: Vec<ShellProfile>) {
+    for profile in incoming {
+        match target.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => target.push(profile),
+        }
+    }
+}
+
+/// Default settings.json location for a source
+fn default_settings_path(source: &str) -> Option<std::path::PathBuf> {
+    match source {
+        "vscode" => {
+            dirs::config_dir().map(|dir| dir.join("Code").join("User").join("settings.json"))
+        }
+        "windows_terminal" => dirs::data_local_dir().map(|dir| {
+            dir.join("Packages")
+                .join("Microsoft.WindowsTerminal_8wekyb3d8bbwe")
+                .join("LocalState")
+                .join("settings.json")
+        }),
+        _ => None,
+    }
+}
+
+/// Parse a JSONC settings file (comments and trailing commas allowed)
+fn read_jsonc(path: &std::path::Path) -> Result<serde_json::Value, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let stripped = crate::icon_theme_manager::strip_json_comments(&content);
+    let trailing_commas = regex::Regex::new(r",(\s*[}\]])").map_err(|e| e.to_string())?;
+    let cleaned = trailing_commas.replace_all(&stripped, "$1");
+    serde_json::from_str(&cleaned).map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+}
+
+/// Expand %VAR% (Windows) and ${env:VAR} (VS Code) references
+fn expand_env_refs(value: &str) -> String {
+    let pattern = regex::Regex::new(r"%([A-Za-z0-9_]+)%|\$\{env:([A-Za-z0-9_]+)\}").unwrap();
+    pattern
+        .replace_all(value, |caps: &regex::Captures| {
+            let name = caps
+                .get(1)
+                .or_else(|| caps.get(2))
+                .map(|m| m.as_str())
+                .unwrap_or("");
+            std::env::var(name).unwrap_or_else(|_| caps[0].to_string())
+        })
+        .to_string()
+}
+
+/// Split a command line into program and arguments, honouring double quotes
+fn split_command_line(commandline: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in commandline.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    parts.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+fn string_list(value: Option<&serde_json::Value>) -> Vec<String> {
+    match value {
+        Some(serde_json::Value::String(s)) => vec![s.clone()],
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Convert terminal.integrated.profiles.<platform> entries
+fn convert_vscode_profiles(settings: &serde_json::Value) -> (Vec<ShellProfile>, Vec<String>) {
+    let platform = if cfg!(target_os = "windows") {
+        "windows"
+    } else if cfg!(target_os = "macos") {
+        "osx"
+    } else {
+        "linux"
+    };
+    let key = format!("terminal.integrated.profiles.{platform}");
+
+    let mut imported = Vec::new();
+    let mut skipped = Vec::new();
+    let Some(entries) = settings.get(&key).and_then(|v| v.as_object()) else {
+        return (imported, skipped);
+    };
+
+    for (name, entry) in entries {
+        // A null entry hides a built-in profile
+        let Some(entry) = entry.as_object() else {
+            skipped.push(name.clone());
+            continue;
+        };
+
+        // "path" may be a list of candidates; use the first that exists
+        let candidates: Vec<String> = string_list(entry.get("path"))
+            .iter()
+            .map(|p| expand_env_refs(p))
+            .collect();
+        let command = candidates
+            .iter()
+            .find(|p| std::path::Path::new(p).exists() || which::which(p).is_ok())
+            .or(candidates.first())
+            .cloned()
+            .or_else(|| match entry.get("source").and_then(|v| v.as_str()) {
+                Some("PowerShell") => Some("powershell.exe".to_string()),
+                Some("Git Bash") => Some("bash.exe".to_string()),
+                _ => None,
+            });
+        let Some(command) = command else {
+            skipped.push(name.clone());
+            continue;
+        };
+
+        let env = entry
+            .get("env")
+            .and_then(|v| v.as_object())
+            .map(|vars| {
+                vars.iter()
+                    .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), expand_env_refs(v))))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        imported.push(ShellProfile {
+            name: entry
+                .get("overrideName")
+                .and_then(|v| v.as_str())
+                .unwrap_or(name)
+                .to_string(),
+            command,
+            args: string_list(entry.get("args")),
+            env,
+        });
+    }
+
+    (imported, skipped)
+}
+
+/// Convert Windows Terminal "profiles" (list form or plain array)
+fn convert_windows_terminal_profiles(
+    settings: &serde_json::Value,
+) -> (Vec<ShellProfile>, Vec<String>) {
+    let mut imported = Vec::new();
+    let mut skipped = Vec::new();
+
+    let list = settings
+        .get("profiles")
+        .and_then(|p| p.get("list").or(Some(p)))
+        .and_then(|p| p.as_array());
+    let Some(list) = list else {
+        return (imported, skipped);
+    };
+
+    for entry in list {
+        let name = entry
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Unnamed")
+            .to_string();
+        if entry
+            .get("hidden")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+        {
+            skipped.push(name);
+            continue;
+        }
+
+        // Dynamic profiles (WSL, Azure) have no commandline
+        let Some(commandline) = entry.get("commandline").and_then(|v| v.as_str()) else {
+            skipped.push(name);
+            continue;
+        };
+        let mut parts = split_command_line(&expand_env_refs(commandline)).into_iter();
+        let Some(command) = parts.next() else {
+            skipped.push(name);
+            continue;
+        };
+
+        imported.push(ShellProfile {
+            name,
+            command,
+            args: parts.collect(),
+            env: HashMap::new(),
+        });
+    }
+
+    (imported, skipped)
+}
+
+/// Import terminal profiles from VS Code ("vscode") or Windows Terminal
+/// ("windows_terminal") settings and merge them into the profile list
+#[tauri::command]
+pub fn terminal_import_profiles(
+    state: State<TerminalState>,
+    source: String,
+    settings_path: Option<String>,
+) -> Result<ProfileImportResult, String> {
+    let path = settings_path
+        .map(std::path::PathBuf::from)
+        .or_else(|| default_settings_path(&source))
+        .ok_or_else(|| format!("Unknown profile source: {source}"))?;
+    let settings = read_jsonc(&path)?;
+
+    let (imported, skipped) = match source.as_str() {
+        "vscode" => convert_vscode_profiles(&settings),
+        "windows_terminal" => convert_windows_terminal_profiles(&settings),
+        other => return Err(format!("Unknown profile source: {other}")),
+    };
+
+    let mut stored = load_imported_profiles();
+    merge_profiles(&mut stored, imported.clone());
+    save_imported_profiles(&stored)?;
+
+    let profiles = terminal_init_profiles(state)?;
+    println!(
+        "[Terminal] Imported {} profile(s) from {} ({} skipped)",
+        imported.len(),
+        source,
+        skipped.len()
+    );
+
+    Ok(ProfileImportResult {
+        source,
+        settings_path: path.to_string_lossy().to_string(),
+        imported,
+        skipped,
+        profiles,
+    })
+}
+
 /// Change the working directory of an existing session
 #[tauri::command]
 pub fn terminal_change_directory(
@@ -597,3 +1275,97 @@ pub fn terminal_change_directory(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn osc52(text: &str, terminator: &str) -> Vec<u8> {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+        format!("\x1b]52;c;{}{}", encoded, terminator).into_bytes()
+    }
+
+    #[test]
+    fn passes_plain_output_through() {
+        let mut scanner = OutputScanner::default();
+        let (output, signals) = scanner.process(b"hello \x1b[31mred\x1b[0m");
+        assert_eq!(output, b"hello \x1b[31mred\x1b[0m");
+        assert!(signals.is_empty());
+    }
+
+    #[test]
+    fn detects_bracketed_paste_split_across_reads() {
+        let mut scanner = OutputScanner::default();
+        let (output, signals) = scanner.process(b"a\x1b[?20");
+        assert_eq!(output, b"a");
+        assert!(signals.is_empty());
+
+        let (output, signals) = scanner.process(b"04hb\x1b[?2004l");
+        assert_eq!(output, b"\x1b[?2004hb\x1b[?2004l");
+        assert_eq!(
+            signals,
+            vec![
+                TerminalSignal::BracketedPaste(true),
+                TerminalSignal::BracketedPaste(false)
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_osc52_with_either_terminator() {
+        let mut scanner = OutputScanner::default();
+        let mut input = b"x".to_vec();
+        input.extend(osc52("copied", "\x07"));
+        input.extend(osc52("again", "\x1b\\"));
+        input.extend(b"y");
+
+        let (output, signals) = scanner.process(&input);
+        assert_eq!(output, b"xy");
+        assert_eq!(
+            signals,
+            vec![
+                TerminalSignal::ClipboardWrite("copied".to_string()),
+                TerminalSignal::ClipboardWrite("again".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn carries_osc52_over_to_the_next_read() {
+        let mut scanner = OutputScanner::default();
+        let sequence = osc52("split", "\x07");
+        let (first, second) = sequence.split_at(6);
+
+        let (output, signals) = scanner.process(first);
+        assert!(output.is_empty());
+        assert!(signals.is_empty());
+
+        let (output, signals) = scanner.process(second);
+        assert!(output.is_empty());
+        assert_eq!(
+            signals,
+            vec![TerminalSignal::ClipboardWrite("split".to_string())]
+        );
+    }
+
+    #[test]
+    fn keeps_other_osc_sequences_and_ignores_queries() {
+        let mut scanner = OutputScanner::default();
+        let (output, signals) = scanner.process(b"\x1b]0;title\x07\x1b]52;c;?\x07");
+        assert_eq!(output, b"\x1b]0;title\x07");
+        assert!(signals.is_empty());
+    }
+
+    #[test]
+    fn flushes_unterminated_osc_past_the_limit() {
+        let mut scanner = OutputScanner::default();
+        let (output, _) = scanner.process(b"\x1b]52;c;");
+        assert!(output.is_empty());
+
+        let filler = vec![b'A'; MAX_OSC_LENGTH];
+        let (output, signals) = scanner.process(&filler);
+        assert_eq!(output.len(), b"\x1b]52;c;".len() + MAX_OSC_LENGTH);
+        assert!(signals.is_empty());
+        assert!(scanner.carry.is_empty());
+    }
+}
@@ -21,8 +21,20 @@ pub struct UpdateProgress {
 }
 
 /// Check for available updates
+/// Scheduled checks pass `background`, which are refused while update checks are paused
 #[tauri::command]
-pub async fn check_for_updates(app: AppHandle) -> Result<UpdateInfo, String> {
+pub async fn check_for_updates(
+    app: AppHandle,
+    background: Option<bool>,
+) -> Result<UpdateInfo, String> {
+    if background.unwrap_or(false)
+        && crate::background_pause::is_paused(
+            crate::background_pause::BackgroundSubsystem::UpdateChecks,
+        )
+    {
+        return Err("Update checks are paused".to_string());
+    }
+
     let current_version = app.package_info().version.to_string();
 
     // Emit checking status
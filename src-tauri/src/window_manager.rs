@@ -55,6 +55,7 @@ pub fn window_show_ready(app: AppHandle, label: Option<String>) -> Result<(), St
         .map_err(|e| format!("Failed to show window: {}", e))?;
 
     eprintln!("[window_manager] ✓ Window shown (frontend ready)");
+    crate::startup_profiler::complete();
     Ok(())
 }
 
@@ -0,0 +1,458 @@
+//! Workspace Bundles
+//!
+//! Packs a workspace's IDE-specific configuration from `.rainy/` (settings,
+//! tasks, recommended extensions, agent config, keybinding overrides) into a
+//! single JSON file that can be shared with a team and imported into another
+//! checkout or machine. File contents are kept verbatim, comments included.
+//! Merging into an existing file edits its top-level members in place, so the
+//! rest of the file, comments included, stays as it was.
+
+use crate::icon_theme_manager::strip_json_comments;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+const BUNDLE_FORMAT: &str = "rainy-workspace-bundle";
+const BUNDLE_VERSION: u32 = 1;
+
+/// Files under `.rainy/` that bundles may carry
+const BUNDLE_FILES: [&str; 5] = [
+    "settings.json",
+    "tasks.json",
+    "extensions.json",
+    "agents.json",
+    "keybindings.json",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleFile {
+    /// Name within `.rainy/`
+    pub name: String,
+    pub content: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceBundle {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    /// RFC 3339
+    pub exported_at: String,
+    /// Name of the workspace folder the bundle was exported from
+    pub workspace_name: Option<String>,
+    pub files: Vec<BundleFile>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleExportResult {
+    pub path: String,
+    pub files: Vec<String>,
+}
+
+/// How files that already exist in the target workspace are handled
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum BundleImportStrategy {
+    /// Keep existing files
+    Skip,
+    /// Replace existing files
+    Overwrite,
+    /// Add the bundle's top-level keys to existing JSON objects, bundle values winning;
+    /// files that aren't JSON objects, or already match, are kept
+    #[default]
+    Merge,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleImportResult {
+    pub written: Vec<String>,
+    pub merged: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+/// A bundle file as it would be imported
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlePreviewEntry {
+    pub name: String,
+    pub size: usize,
+    /// Whether the target workspace already has this file
+    pub exists: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlePreview {
+    pub app_version: String,
+    pub exported_at: String,
+    pub workspace_name: Option<String>,
+    pub files: Vec<BundlePreviewEntry>,
+}
+
+fn rainy_dir(workspace_root: &str) -> PathBuf {
+    Path::new(workspace_root).join(".rainy")
+}
+
+fn read_bundle(bundle_path: &str) -> Result<WorkspaceBundle, String> {
+    let content = std::fs::read_to_string(bundle_path)
+        .map_err(|e| format!("Failed to read bundle: {}", e))?;
+    let bundle: WorkspaceBundle =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid workspace bundle: {}", e))?;
+    if bundle.format != BUNDLE_FORMAT {
+        return Err("Not a workspace bundle".to_string());
+    }
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Bundle version {} is newer than this app supports ({})",
+            bundle.version, BUNDLE_VERSION
+        ));
+    }
+    // Bundles only ever write known files inside .rainy/
+    if let Some(file) = bundle
+        .files
+        .iter()
+        .find(|f| !BUNDLE_FILES.contains(&f.name.as_str()))
+    {
+        return Err(format!("Bundle contains unsupported file '{}'", file.name));
+    }
+    Ok(bundle)
+}
+
+fn parse_object(content: &str) -> Option<Map<String, Value>> {
+    match serde_json::from_str(&strip_json_comments(content)).ok()? {
+        Value::Object(map) => Some(map),
+        _ => None,
+    }
+}
+
+/// A top-level member of a JSONC object: its key and the byte range of its value
+struct Member {
+    key: String,
+    value: Range<usize>,
+}
+
+/// Byte offset after whitespace and comments starting at `i`
+fn skip_trivia(text: &str, mut i: usize) -> usize {
+    let bytes = text.as_bytes();
+    loop {
+        match bytes.get(i) {
+            Some(b) if b.is_ascii_whitespace() => i += 1,
+            Some(b'/') if bytes.get(i + 1) == Some(&b'/') => {
+                i = text[i..].find('\n').map_or(text.len(), |n| i + n);
+            }
+            Some(b'/') if bytes.get(i + 1) == Some(&b'*') => {
+                i = text[i + 2..]
+                    .find("*/")
+                    .map_or(text.len(), |n| i + 2 + n + 2);
+            }
+            _ => return i,
+        }
+    }
+}
+
+/// Byte offset after the string starting at `i`
+fn skip_string(text: &str, i: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut j = i + 1;
+    while j < bytes.len() {
+        match bytes[j] {
+            b'\\' => j += 2,
+            b'"' => return Some(j + 1),
+            _ => j += 1,
+        }
+    }
+    None
+}
+
+/// Byte offset after the value starting at `i`
+fn skip_value(text: &str, i: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    match bytes.get(i)? {
+        b'"' => skip_string(text, i),
+        b'{' | b'[' => {
+            let mut depth = 0;
+            let mut j = i;
+            loop {
+                j = skip_trivia(text, j);
+                match bytes.get(j)? {
+                    b'"' => {
+                        j = skip_string(text, j)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(j + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                j += 1;
+            }
+        }
+        _ => {
+            let end = text[i..]
+                .find(|c: char| c == ',' || c == '}' || c == '/' || c.is_whitespace())
+                .map_or(text.len(), |n| i + n);
+            (end > i).then_some(end)
+        }
+    }
+}
+
+/// Top-level members of a JSONC object and the offset of its closing brace
+fn object_members(text: &str) -> Option<(Vec<Member>, usize)> {
+    let bytes = text.as_bytes();
+    let mut i = skip_trivia(text, 0);
+    if bytes.get(i) != Some(&b'{') {
+        return None;
+    }
+    i += 1;
+    let mut members = Vec::new();
+    loop {
+        i = skip_trivia(text, i);
+        match bytes.get(i)? {
+            b'}' => return Some((members, i)),
+            b'"' => {}
+            _ => return None,
+        }
+        let key_end = skip_string(text, i)?;
+        let key: String = serde_json::from_str(&text[i..key_end]).ok()?;
+        i = skip_trivia(text, key_end);
+        if bytes.get(i) != Some(&b':') {
+            return None;
+        }
+        let value_start = skip_trivia(text, i + 1);
+        let value_end = skip_value(text, value_start)?;
+        members.push(Member {
+            key,
+            value: value_start..value_end,
+        });
+        i = skip_trivia(text, value_end);
+        match bytes.get(i)? {
+            b',' => i += 1,
+            b'}' => return Some((members, i)),
+            _ => return None,
+        }
+    }
+}
+
+/// Indentation of the line containing byte offset `at`
+fn line_indent(text: &str, at: usize) -> &str {
+    let start = text[..at].rfind('\n').map_or(0, |n| n + 1);
+    let line = &text[start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// Pretty JSON for a value written at `indent`
+fn render_value(value: &Value, indent: &str) -> Result<String, String> {
+    let pretty = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    Ok(pretty.replace('\n', &format!("\n{}", indent)))
+}
+
+/// `existing` with the bundle's top-level members set, editing only the
+/// members that change; `None` when `existing` isn't a JSON object, and
+/// `Some(existing)` unchanged when every member already matches
+fn merge_in_place(existing: &str, incoming: &Map<String, Value>) -> Option<Result<String, String>> {
+    parse_object(existing)?;
+    let (members, close) = object_members(existing)?;
+    let indent = members
+        .first()
+        .map(|member| line_indent(existing, member.value.start).to_string())
+        .unwrap_or_else(|| "  ".to_string());
+
+    // (range to replace, replacement), applied back to front
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    let mut added = Vec::new();
+    for (key, value) in incoming {
+        // The last occurrence of a duplicated key is the one that counts
+        match members.iter().rev().find(|member| &member.key == key) {
+            Some(member) => {
+                let current: Option<Value> =
+                    serde_json::from_str(&strip_json_comments(&existing[member.value.clone()]))
+                        .ok();
+                if current.as_ref() != Some(value) {
+                    let indent = line_indent(existing, member.value.start);
+                    match render_value(value, indent) {
+                        Ok(rendered) => edits.push((member.value.clone(), rendered)),
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+            }
+            None => match render_value(value, &indent) {
+                Ok(rendered) => added.push(format!(
+                    "{}{}: {}",
+                    indent,
+                    Value::String(key.clone()),
+                    rendered
+                )),
+                Err(e) => return Some(Err(e)),
+            },
+        }
+    }
+
+    if !added.is_empty() {
+        let joined = added.join(",\n");
+        match members.last() {
+            Some(last) => {
+                let at = last.value.end;
+                edits.push((at..at, format!(",\n{}", joined)));
+            }
+            None => {
+                let closing_indent = line_indent(existing, close).to_string();
+                edits.push((close..close, format!("\n{}\n{}", joined, closing_indent)));
+            }
+        }
+    }
+
+    let mut merged = existing.to_string();
+    edits.sort_by_key(|(range, _)| range.start);
+    for (range, replacement) in edits.into_iter().rev() {
+        merged.replace_range(range, &replacement);
+    }
+    Some(Ok(merged))
+}
+
+/// Export `.rainy/` configuration to a bundle file
+///
+/// `include` limits the export to some of the bundle files (by name).
+#[tauri::command]
+pub fn export_workspace_bundle(
+    workspace_root: String,
+    destination: String,
+    include: Option<Vec<String>>,
+) -> Result<BundleExportResult, String> {
+    let dir = rainy_dir(&workspace_root);
+    let files: Vec<BundleFile> = BUNDLE_FILES
+        .iter()
+        .filter(|name| {
+            include
+                .as_ref()
+                .is_none_or(|include| include.iter().any(|i| i == *name))
+        })
+        .filter_map(|name| {
+            let content = std::fs::read_to_string(dir.join(name)).ok()?;
+            Some(BundleFile {
+                name: name.to_string(),
+                content,
+            })
+        })
+        .collect();
+    if files.is_empty() {
+        return Err("The workspace has no configuration to export".to_string());
+    }
+
+    let bundle = WorkspaceBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        workspace_name: Path::new(&workspace_root)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string()),
+        files,
+    };
+    let json = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| format!("Failed to serialize bundle: {}", e))?;
+    std::fs::write(&destination, json).map_err(|e| format!("Failed to write bundle: {}", e))?;
+
+    println!(
+        "[WorkspaceBundle] Exported {} file(s) to {}",
+        bundle.files.len(),
+        destination
+    );
+    Ok(BundleExportResult {
+        path: destination,
+        files: bundle.files.into_iter().map(|f| f.name).collect(),
+    })
+}
+
+/// Describe a bundle and which of its files the workspace already has
+#[tauri::command]
+pub fn preview_workspace_bundle(
+    workspace_root: String,
+    bundle_path: String,
+) -> Result<BundlePreview, String> {
+    let bundle = read_bundle(&bundle_path)?;
+    let dir = rainy_dir(&workspace_root);
+    Ok(BundlePreview {
+        app_version: bundle.app_version,
+        exported_at: bundle.exported_at,
+        workspace_name: bundle.workspace_name,
+        files: bundle
+            .files
+            .iter()
+            .map(|file| BundlePreviewEntry {
+                name: file.name.clone(),
+                size: file.content.len(),
+                exists: dir.join(&file.name).exists(),
+            })
+            .collect(),
+    })
+}
+
+/// Import a bundle into the workspace's `.rainy/` directory
+#[tauri::command]
+pub fn import_workspace_bundle(
+    workspace_root: String,
+    bundle_path: String,
+    strategy: Option<BundleImportStrategy>,
+    include: Option<Vec<String>>,
+) -> Result<BundleImportResult, String> {
+    let bundle = read_bundle(&bundle_path)?;
+    let strategy = strategy.unwrap_or_default();
+    let dir = rainy_dir(&workspace_root);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create .rainy directory: {}", e))?;
+
+    let mut result = BundleImportResult::default();
+    for file in bundle.files {
+        if include
+            .as_ref()
+            .is_some_and(|include| !include.contains(&file.name))
+        {
+            continue;
+        }
+        let target = dir.join(&file.name);
+        let existing = std::fs::read_to_string(&target).ok();
+
+        let content = match (&existing, strategy) {
+            (None, _) | (Some(_), BundleImportStrategy::Overwrite) => file.content,
+            (Some(_), BundleImportStrategy::Skip) => {
+                result.skipped.push(file.name);
+                continue;
+            }
+            (Some(existing), BundleImportStrategy::Merge) => {
+                let merged = parse_object(&file.content)
+                    .and_then(|incoming| merge_in_place(existing, &incoming))
+                    .transpose()?;
+                match merged {
+                    Some(merged) if &merged != existing => {
+                        std::fs::write(&target, merged)
+                            .map_err(|e| format!("Failed to write {}: {}", file.name, e))?;
+                        result.merged.push(file.name);
+                    }
+                    _ => result.skipped.push(file.name),
+                }
+                continue;
+            }
+        };
+        std::fs::write(&target, content)
+            .map_err(|e| format!("Failed to write {}: {}", file.name, e))?;
+        result.written.push(file.name);
+    }
+
+    println!(
+        "[WorkspaceBundle] Imported into {}: {} written, {} merged, {} skipped",
+        workspace_root,
+        result.written.len(),
+        result.merged.len(),
+        result.skipped.len()
+    );
+    Ok(result)
+}
@@ -0,0 +1,417 @@
+//! Workspace Edit
+//!
+//! Applies LSP `WorkspaceEdit`s (text edits plus file creates, renames and
+//! deletes) as one unit. Every step is journaled; if any step fails, the
+//! steps already done are undone in reverse order so the workspace is left
+//! as it was. Deleted or overwritten paths are set aside next to where they
+//! were, on the same volume, so undoing them is a rename.
+//!
+//! Positions follow the LSP default encoding (UTF-16 code units).
+
+use crate::project_manager::OpenFilesState;
+use lsp_types::{
+    DocumentChangeOperation, DocumentChanges, OneOf, ResourceOp, TextEdit, Uri, WorkspaceEdit,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// A rename performed by the edit
+#[derive(Serialize, Debug, Clone)]
+pub struct RenamedFile {
+    pub from: String,
+    pub to: String,
+}
+
+/// What a workspace edit changed
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct WorkspaceEditSummary {
+    pub files_changed: Vec<String>,
+    pub edits_applied: usize,
+    pub created: Vec<String>,
+    pub renamed: Vec<RenamedFile>,
+    pub deleted: Vec<String>,
+}
+
+/// One step of the edit in the order it must be applied
+enum EditStep {
+    Text { uri: Uri, edits: Vec<TextEdit> },
+    Resource(ResourceOp),
+}
+
+/// Undo information for a completed step
+enum JournalEntry {
+    /// File existed with this content before being overwritten
+    Restore { path: PathBuf, content: Vec<u8> },
+    /// File did not exist before
+    Remove { path: PathBuf },
+    /// Path was renamed from `to` to `from`
+    RenameBack { from: PathBuf, to: PathBuf },
+    /// Deleted or overwritten path was set aside as `backup`
+    RestoreBackup { backup: PathBuf, original: PathBuf },
+}
+
+/// Convert a file:// URI to a local path
+pub(crate) fn uri_to_path(uri: &Uri) -> Result<PathBuf, String> {
+    let raw = uri.as_str();
+    let rest = raw
+        .strip_prefix("file://")
+        .ok_or_else(|| format!("Unsupported URI scheme: {}", raw))?;
+    let decoded = urlencoding::decode(rest).map_err(|e| format!("Invalid URI {}: {}", raw, e))?;
+
+    // file:///C:/dir -> C:/dir on Windows
+    let path = if cfg!(windows) {
+        decoded.trim_start_matches('/').to_string()
+    } else {
+        decoded.to_string()
+    };
+    Ok(PathBuf::from(path))
+}
+
+/// Byte offset of an LSP position; out-of-range positions clamp to the line/file end
+fn position_to_offset(content: &str, line_starts: &[usize], line: u32, character: u32) -> usize {
+    let Some(&start) = line_starts.get(line as usize) else {
+        return content.len();
+    };
+    let end = line_starts
+        .get(line as usize + 1)
+        .map(|next| next - 1)
+        .unwrap_or(content.len());
+    let line_text = content[start..end].trim_end_matches('\r');
+
+    let mut units = 0u32;
+    for (offset, ch) in line_text.char_indices() {
+        if units >= character {
+            return start + offset;
+        }
+        units += ch.len_utf16() as u32;
+    }
+    start + line_text.len()
+}
+
+/// Apply non-overlapping text edits to a document
+pub(crate) fn apply_text_edits(content: &str, edits: &[TextEdit]) -> Result<String, String> {
+    let mut line_starts = vec![0];
+    line_starts.extend(content.match_indices('\n').map(|(i, _)| i + 1));
+
+    let mut ranges: Vec<(usize, usize, &str)> = edits
+        .iter()
+        .map(|edit| {
+            let start = position_to_offset(
+                content,
+                &line_starts,
+                edit.range.start.line,
+                edit.range.start.character,
+            );
+            let end = position_to_offset(
+                content,
+                &line_starts,
+                edit.range.end.line,
+                edit.range.end.character,
+            );
+            (start, end.max(start), edit.new_text.as_str())
+        })
+        .collect();
+    // Stable: inserts at the same position keep their order
+    ranges.sort_by_key(|(start, end, _)| (*start, *end));
+
+    let mut result = String::with_capacity(content.len());
+    let mut cursor = 0;
+    for (start, end, text) in ranges {
+        if start < cursor {
+            return Err("Text edits overlap".to_string());
+        }
+        result.push_str(&content[cursor..start]);
+        result.push_str(text);
+        cursor = end;
+    }
+    result.push_str(&content[cursor..]);
+    Ok(result)
+}
+
+/// Flatten `documentChanges` (preferred) or `changes` into ordered steps
+fn collect_steps(edit: WorkspaceEdit) -> Vec<EditStep> {
+    let to_text_edits = |edits: Vec<OneOf<TextEdit, lsp_types::AnnotatedTextEdit>>| {
+        edits
+            .into_iter()
+            .map(|e| match e {
+                OneOf::Left(edit) => edit,
+                OneOf::Right(annotated) => annotated.text_edit,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    match edit.document_changes {
+        Some(DocumentChanges::Edits(edits)) => edits
+            .into_iter()
+            .map(|e| EditStep::Text {
+                uri: e.text_document.uri,
+                edits: to_text_edits(e.edits),
+            })
+            .collect(),
+        Some(DocumentChanges::Operations(ops)) => ops
+            .into_iter()
+            .map(|op| match op {
+                DocumentChangeOperation::Edit(e) => EditStep::Text {
+                    uri: e.text_document.uri,
+                    edits: to_text_edits(e.edits),
+                },
+                DocumentChangeOperation::Op(op) => EditStep::Resource(op),
+            })
+            .collect(),
+        None => edit
+            .changes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(uri, edits)| EditStep::Text { uri, edits })
+            .collect(),
+    }
+}
+
+/// Reject the edit if any versioned document differs from the editor's version
+fn validate_versions(
+    edit: &WorkspaceEdit,
+    document_versions: &HashMap<String, i32>,
+) -> Result<(), String> {
+    let text_edits: Vec<&lsp_types::TextDocumentEdit> = match &edit.document_changes {
+        Some(DocumentChanges::Edits(edits)) => edits.iter().collect(),
+        Some(DocumentChanges::Operations(ops)) => ops
+            .iter()
+            .filter_map(|op| match op {
+                DocumentChangeOperation::Edit(e) => Some(e),
+                DocumentChangeOperation::Op(_) => None,
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let stale: Vec<String> = text_edits
+        .iter()
+        .filter_map(|e| {
+            let expected = e.text_document.version?;
+            let current = document_versions.get(e.text_document.uri.as_str())?;
+            (*current != expected).then(|| {
+                format!(
+                    "{} (edit for v{}, editor has v{})",
+                    e.text_document.uri.as_str(),
+                    expected,
+                    current
+                )
+            })
+        })
+        .collect();
+
+    if stale.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Workspace edit is out of date: {}",
+            stale.join(", ")
+        ))
+    }
+}
+
+/// Set a path aside so a delete or overwrite can be undone. The backup sits
+/// next to it: a temp directory may be on another volume, where rename fails.
+fn backup_path(edit_id: &str, path: &Path, journal: &mut Vec<JournalEntry>) -> Result<(), String> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let backup = path.with_file_name(format!(".{}.edit-{}-{}", name, edit_id, journal.len()));
+    fs::rename(path, &backup).map_err(|e| format!("Failed to move {:?}: {}", path, e))?;
+    journal.push(JournalEntry::RestoreBackup {
+        backup,
+        original: path.to_path_buf(),
+    });
+    Ok(())
+}
+
+fn apply_step(
+    step: EditStep,
+    edit_id: &str,
+    journal: &mut Vec<JournalEntry>,
+    summary: &mut WorkspaceEditSummary,
+) -> Result<(), String> {
+    match step {
+        EditStep::Text { uri, edits } => {
+            let path = uri_to_path(&uri)?;
+            let original =
+                fs::read(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+            let content = String::from_utf8(original.clone())
+                .map_err(|_| format!("{:?} is not valid UTF-8", path))?;
+            let updated =
+                apply_text_edits(&content, &edits).map_err(|e| format!("{:?}: {}", path, e))?;
+
+            fs::write(&path, updated).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+            journal.push(JournalEntry::Restore {
+                path: path.clone(),
+                content: original,
+            });
+
+            summary.edits_applied += edits.len();
+            let display = path.to_string_lossy().to_string();
+            if !summary.files_changed.contains(&display) {
+                summary.files_changed.push(display);
+            }
+        }
+        EditStep::Resource(ResourceOp::Create(create)) => {
+            let path = uri_to_path(&create.uri)?;
+            let options = create.options.as_ref();
+            if path.exists() {
+                if options.and_then(|o| o.overwrite).unwrap_or(false) {
+                    backup_path(edit_id, &path, journal)?;
+                } else if options.and_then(|o| o.ignore_if_exists).unwrap_or(false) {
+                    return Ok(());
+                } else {
+                    return Err(format!("{:?} already exists", path));
+                }
+            }
+
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+            }
+            fs::write(&path, "").map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+            journal.push(JournalEntry::Remove { path: path.clone() });
+            summary.created.push(path.to_string_lossy().to_string());
+        }
+        EditStep::Resource(ResourceOp::Rename(rename)) => {
+            let from = uri_to_path(&rename.old_uri)?;
+            let to = uri_to_path(&rename.new_uri)?;
+            let options = rename.options.as_ref();
+            if to.exists() {
+                if options.and_then(|o| o.overwrite).unwrap_or(false) {
+                    backup_path(edit_id, &to, journal)?;
+                } else if options.and_then(|o| o.ignore_if_exists).unwrap_or(false) {
+                    return Ok(());
+                } else {
+                    return Err(format!("{:?} already exists", to));
+                }
+            }
+
+            if let Some(parent) = to.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+            }
+            fs::rename(&from, &to)
+                .map_err(|e| format!("Failed to rename {:?} to {:?}: {}", from, to, e))?;
+            journal.push(JournalEntry::RenameBack {
+                from: to.clone(),
+                to: from.clone(),
+            });
+            summary.renamed.push(RenamedFile {
+                from: from.to_string_lossy().to_string(),
+                to: to.to_string_lossy().to_string(),
+            });
+        }
+        EditStep::Resource(ResourceOp::Delete(delete)) => {
+            let path = uri_to_path(&delete.uri)?;
+            let options = delete.options.as_ref();
+            if !path.exists() {
+                if options
+                    .and_then(|o| o.ignore_if_not_exists)
+                    .unwrap_or(false)
+                {
+                    return Ok(());
+                }
+                return Err(format!("{:?} does not exist", path));
+            }
+            if path.is_dir()
+                && !options.and_then(|o| o.recursive).unwrap_or(false)
+                && fs::read_dir(&path)
+                    .map(|mut d| d.next().is_some())
+                    .unwrap_or(false)
+            {
+                return Err(format!(
+                    "{:?} is not empty (recursive delete not requested)",
+                    path
+                ));
+            }
+
+            backup_path(edit_id, &path, journal)?;
+            summary.deleted.push(path.to_string_lossy().to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Undo journaled steps in reverse order (best effort)
+fn rollback(journal: Vec<JournalEntry>) {
+    for entry in journal.into_iter().rev() {
+        let result = match &entry {
+            JournalEntry::Restore { path, content } => fs::write(path, content),
+            JournalEntry::Remove { path } => fs::remove_file(path),
+            JournalEntry::RenameBack { from, to } => fs::rename(from, to),
+            JournalEntry::RestoreBackup { backup, original } => fs::rename(backup, original),
+        };
+        if let Err(e) = result {
+            eprintln!("[WorkspaceEdit] Rollback step failed: {}", e);
+        }
+    }
+}
+
+/// Remove the backups of a completed edit
+fn remove_backups(journal: Vec<JournalEntry>) {
+    for entry in journal {
+        let JournalEntry::RestoreBackup { backup, .. } = entry else {
+            continue;
+        };
+        let result = if backup.is_dir() {
+            fs::remove_dir_all(&backup)
+        } else {
+            fs::remove_file(&backup)
+        };
+        if let Err(e) = result {
+            eprintln!(
+                "[WorkspaceEdit] Failed to remove backup {:?}: {}",
+                backup, e
+            );
+        }
+    }
+}
+
+/// Apply an LSP WorkspaceEdit atomically
+/// `document_versions` maps URIs of documents open in the editor to their current
+/// version; versioned edits for other versions are rejected before anything changes
+#[tauri::command]
+pub fn apply_workspace_edit(
+    edit: WorkspaceEdit,
+    document_versions: Option<HashMap<String, i32>>,
+    open_files: State<'_, OpenFilesState>,
+) -> Result<WorkspaceEditSummary, String> {
+    apply_edit(edit, &document_versions.unwrap_or_default(), &open_files)
+}
+
+/// Apply a WorkspaceEdit, rolling back on failure
+pub(crate) fn apply_edit(
+    edit: WorkspaceEdit,
+    document_versions: &HashMap<String, i32>,
+    open_files: &OpenFilesState,
+) -> Result<WorkspaceEditSummary, String> {
+    validate_versions(&edit, document_versions)?;
+
+    let edit_id = uuid::Uuid::new_v4().to_string();
+    let mut journal = Vec::new();
+    let mut summary = WorkspaceEditSummary::default();
+
+    for step in collect_steps(edit) {
+        if let Err(e) = apply_step(step, &edit_id, &mut journal, &mut summary) {
+            eprintln!(
+                "[WorkspaceEdit] Failed, rolling back {} step(s): {}",
+                journal.len(),
+                e
+            );
+            rollback(journal);
+            return Err(e);
+        }
+    }
+
+    remove_backups(journal);
+
+    // Our own writes should not be reported as external changes
+    for path in summary.files_changed.iter().chain(summary.created.iter()) {
+        open_files.refresh(Path::new(path));
+    }
+
+    Ok(summary)
+}
@@ -0,0 +1,575 @@
+//! Workspace Warmup
+//!
+//! After a workspace opens, the frontend starts one warmup run instead of
+//! having each subsystem index on its own. Stages run in priority order
+//! (icons for the visible explorer rows, git status, file index, symbol
+//! index), a new run for the same workspace cancels the previous one, and
+//! progress for every stage is reported through a single `warmup:progress`
+//! event so the UI can show one "Indexing…" status.
+//!
+//! The symbol index honours the power manager's indexer thread budget, and
+//! runs wait while indexers are paused.
+
+use crate::background_pause::BackgroundSubsystem;
+use crate::code_chunker::{self, ChunkLanguage};
+use crate::icon_theme_manager::{ExplorerIconRequest, IconThemeManagerState};
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Files above this size are listed but not scanned for symbols
+const MAX_SYMBOL_FILE_SIZE: u64 = 1024 * 1024;
+
+const DEFAULT_MAX_FILES: usize = 200_000;
+
+/// Minimum time between progress events of one stage
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(150);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum WarmupStage {
+    IconPrefetch,
+    GitStatus,
+    FileIndex,
+    SymbolIndex,
+}
+
+impl WarmupStage {
+    /// Lower runs first
+    fn priority(self) -> u8 {
+        match self {
+            Self::IconPrefetch => 0,
+            Self::GitStatus => 1,
+            Self::FileIndex => 2,
+            Self::SymbolIndex => 3,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::IconPrefetch => "Loading icons",
+            Self::GitStatus => "Reading git status",
+            Self::FileIndex => "Indexing files",
+            Self::SymbolIndex => "Indexing symbols",
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WarmupOptions {
+    /// Stages to run; all stages when unset
+    pub stages: Option<Vec<WarmupStage>>,
+    /// Explorer rows currently visible, resolved by the icon stage
+    pub visible_entries: Vec<ExplorerIconRequest>,
+    /// Stop the file index after this many files
+    pub max_files: Option<usize>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WarmupState {
+    Started,
+    Progress,
+    Completed,
+    Skipped,
+    Failed,
+    Cancelled,
+    /// The whole run is over
+    Finished,
+}
+
+/// Payload of the `warmup:progress` event
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WarmupProgress {
+    pub workspace_root: String,
+    pub run_id: u64,
+    /// None for the `finished` event
+    pub stage: Option<WarmupStage>,
+    pub label: String,
+    pub state: WarmupState,
+    pub completed: usize,
+    /// Unknown while files are still being discovered
+    pub total: Option<usize>,
+    /// Progress of the whole run, 0.0 to 1.0
+    pub overall: f32,
+    pub message: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedSymbol {
+    pub name: String,
+    /// Declaration keyword ("fn", "class", ...)
+    pub kind: String,
+    pub path: String,
+    /// 1-based
+    pub line: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WarmupStatus {
+    pub running: bool,
+    pub file_count: Option<usize>,
+    pub symbol_count: Option<usize>,
+    /// Unix timestamp (ms) of the last completed file index
+    pub indexed_at: Option<i64>,
+}
+
+#[derive(Default)]
+struct WorkspaceIndex {
+    files: Option<Arc<Vec<PathBuf>>>,
+    symbols: Option<Arc<Vec<IndexedSymbol>>>,
+    indexed_at: Option<i64>,
+}
+
+struct ActiveRun {
+    id: u64,
+    cancelled: Arc<AtomicBool>,
+}
+
+static INDEXES: Lazy<RwLock<HashMap<PathBuf, WorkspaceIndex>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+static RUNS: Lazy<Mutex<HashMap<PathBuf, ActiveRun>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+static NEXT_RUN_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Files found by the last file index of a workspace
+pub(crate) fn indexed_files(workspace_root: &Path) -> Option<Arc<Vec<PathBuf>>> {
+    INDEXES
+        .read()
+        .ok()?
+        .get(workspace_root)
+        .and_then(|index| index.files.clone())
+}
+
+/// Stage outcome: `Err` fails the stage, `Ok(None)` skips it
+type StageResult = Result<Option<String>, String>;
+
+struct Run {
+    app: AppHandle,
+    root: PathBuf,
+    id: u64,
+    cancelled: Arc<AtomicBool>,
+    stage_count: usize,
+    stages_done: usize,
+}
+
+impl Run {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn emit(
+        &self,
+        stage: Option<WarmupStage>,
+        state: WarmupState,
+        completed: usize,
+        total: Option<usize>,
+        message: Option<String>,
+    ) {
+        let stage_fraction = match (state, total) {
+            (WarmupState::Progress, Some(total)) if total > 0 => completed as f32 / total as f32,
+            _ => 0.0,
+        };
+        let overall = if self.stage_count == 0 {
+            1.0
+        } else {
+            ((self.stages_done as f32 + stage_fraction) / self.stage_count as f32).min(1.0)
+        };
+        let progress = WarmupProgress {
+            workspace_root: self.root.to_string_lossy().to_string(),
+            run_id: self.id,
+            stage,
+            label: stage.map_or("Ready", |s| s.label()).to_string(),
+            state,
+            completed,
+            total,
+            overall,
+            message,
+        };
+        if let Err(e) = self.app.emit("warmup:progress", &progress) {
+            eprintln!("[Warmup] Failed to emit progress: {}", e);
+        }
+    }
+
+    fn run_stage(&self, stage: WarmupStage, options: &WarmupOptions) -> StageResult {
+        match stage {
+            WarmupStage::IconPrefetch => self.prefetch_icons(&options.visible_entries),
+            WarmupStage::GitStatus => self.read_git_status(),
+            WarmupStage::FileIndex => {
+                self.index_files(options.max_files.unwrap_or(DEFAULT_MAX_FILES))
+            }
+            WarmupStage::SymbolIndex => self.index_symbols(),
+        }
+    }
+
+    fn prefetch_icons(&self, entries: &[ExplorerIconRequest]) -> StageResult {
+        if entries.is_empty() {
+            return Ok(None);
+        }
+        let state = self.app.state::<IconThemeManagerState>();
+        let resolved =
+            crate::icon_theme_manager::get_explorer_icons_batch(state, entries.to_vec())?;
+        let with_icon = resolved.iter().filter(|r| r.icon.is_some()).count();
+        Ok(Some(format!("{} icons loaded", with_icon)))
+    }
+
+    fn read_git_status(&self) -> StageResult {
+        if git2::Repository::open(&self.root).is_err() {
+            return Ok(None);
+        }
+        let entries = crate::git::status::git_status(
+            self.app.state(),
+            self.root.to_string_lossy().to_string(),
+            None,
+        )?;
+        Ok(Some(format!("{} changed files", entries.len())))
+    }
+
+    fn index_files(&self, max_files: usize) -> StageResult {
+        let mut files = Vec::new();
+        let mut last_emit = Instant::now();
+        let walker = ignore::WalkBuilder::new(&self.root)
+            .hidden(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build();
+
+        for entry in walker {
+            if self.is_cancelled() {
+                return Err("Cancelled".to_string());
+            }
+            let Ok(entry) = entry else {
+                continue;
+            };
+            if entry.file_type().is_some_and(|t| t.is_file()) {
+                files.push(entry.into_path());
+                if files.len() >= max_files {
+                    break;
+                }
+            }
+            if last_emit.elapsed() >= PROGRESS_INTERVAL {
+                last_emit = Instant::now();
+                self.emit(
+                    Some(WarmupStage::FileIndex),
+                    WarmupState::Progress,
+                    files.len(),
+                    None,
+                    None,
+                );
+            }
+        }
+
+        let count = files.len();
+        let mut indexes = INDEXES.write().map_err(|e| e.to_string())?;
+        let index = indexes.entry(self.root.clone()).or_default();
+        index.files = Some(Arc::new(files));
+        index.indexed_at = Some(chrono::Utc::now().timestamp_millis());
+        Ok(Some(if count >= max_files {
+            format!("{} files (limit reached)", count)
+        } else {
+            format!("{} files", count)
+        }))
+    }
+
+    fn index_symbols(&self) -> StageResult {
+        let files = indexed_files(&self.root).ok_or("The file index has not been built")?;
+        let candidates: Vec<&PathBuf> = files
+            .iter()
+            .filter(|path| {
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| ChunkLanguage::from_extension(ext) != ChunkLanguage::Plain)
+            })
+            .collect();
+        let total = candidates.len();
+        let done = AtomicUsize::new(0);
+        let last_emit = Mutex::new(Instant::now());
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(crate::power_manager::current_budget().indexer_threads)
+            .build()
+            .map_err(|e| e.to_string())?;
+        let symbols: Vec<IndexedSymbol> = pool.install(|| {
+            candidates
+                .par_iter()
+                .flat_map_iter(|path| {
+                    crate::background_pause::wait_while_paused(
+                        BackgroundSubsystem::Indexers,
+                        || self.is_cancelled(),
+                    );
+                    let symbols = if self.is_cancelled() {
+                        Vec::new()
+                    } else {
+                        file_symbols(path)
+                    };
+                    let completed = done.fetch_add(1, Ordering::Relaxed) + 1;
+                    if let Ok(mut last) = last_emit.try_lock() {
+                        if last.elapsed() >= PROGRESS_INTERVAL {
+                            *last = Instant::now();
+                            self.emit(
+                                Some(WarmupStage::SymbolIndex),
+                                WarmupState::Progress,
+                                completed,
+                                Some(total),
+                                None,
+                            );
+                        }
+                    }
+                    symbols
+                })
+                .collect()
+        });
+        if self.is_cancelled() {
+            return Err("Cancelled".to_string());
+        }
+
+        let count = symbols.len();
+        INDEXES
+            .write()
+            .map_err(|e| e.to_string())?
+            .entry(self.root.clone())
+            .or_default()
+            .symbols = Some(Arc::new(symbols));
+        Ok(Some(format!("{} symbols in {} files", count, total)))
+    }
+}
+
+fn file_symbols(path: &Path) -> Vec<IndexedSymbol> {
+    let Some(language) = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(ChunkLanguage::from_extension)
+    else {
+        return Vec::new();
+    };
+    if std::fs::metadata(path).map_or(true, |m| m.len() > MAX_SYMBOL_FILE_SIZE) {
+        return Vec::new();
+    }
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    code_chunker::chunk_source(&content, language, code_chunker::DEFAULT_CHUNK_TOKENS)
+        .into_iter()
+        .filter(|chunk| chunk.kind != "block")
+        .filter_map(|chunk| {
+            let name = chunk.name?;
+            // Continuations of an oversized declaration
+            if name.ends_with(')') && name.contains(" (part ") {
+                return None;
+            }
+            Some(IndexedSymbol {
+                name,
+                kind: chunk.kind,
+                path: path.to_string_lossy().to_string(),
+                line: chunk.start_line,
+            })
+        })
+        .collect()
+}
+
+fn run_warmup(mut run: Run, options: WarmupOptions) {
+    let mut stages = options.stages.clone().unwrap_or_else(|| {
+        vec![
+            WarmupStage::IconPrefetch,
+            WarmupStage::GitStatus,
+            WarmupStage::FileIndex,
+            WarmupStage::SymbolIndex,
+        ]
+    });
+    // Symbols are read from the file index
+    if stages.contains(&WarmupStage::SymbolIndex) && !stages.contains(&WarmupStage::FileIndex) {
+        stages.push(WarmupStage::FileIndex);
+    }
+    stages.sort_by_key(|stage| stage.priority());
+    stages.dedup();
+    run.stage_count = stages.len();
+
+    let started = Instant::now();
+    for stage in stages {
+        if run.is_cancelled() {
+            run.emit(Some(stage), WarmupState::Cancelled, 0, None, None);
+            break;
+        }
+        crate::background_pause::wait_while_paused(BackgroundSubsystem::Indexers, || {
+            run.is_cancelled()
+        });
+        if run.is_cancelled() {
+            run.emit(Some(stage), WarmupState::Cancelled, 0, None, None);
+            break;
+        }
+        run.emit(Some(stage), WarmupState::Started, 0, None, None);
+        let stage_started = Instant::now();
+        let result = run.run_stage(stage, &options);
+        run.stages_done += 1;
+        match result {
+            Ok(Some(summary)) => {
+                println!(
+                    "[Warmup] {:?} done in {:?}: {}",
+                    stage,
+                    stage_started.elapsed(),
+                    summary
+                );
+                run.emit(Some(stage), WarmupState::Completed, 0, None, Some(summary));
+            }
+            Ok(None) => run.emit(Some(stage), WarmupState::Skipped, 0, None, None),
+            Err(_) if run.is_cancelled() => {
+                run.emit(Some(stage), WarmupState::Cancelled, 0, None, None);
+                break;
+            }
+            Err(e) => {
+                eprintln!("[Warmup] {:?} failed: {}", stage, e);
+                run.emit(Some(stage), WarmupState::Failed, 0, None, Some(e));
+            }
+        }
+    }
+
+    if !run.is_cancelled() {
+        println!(
+            "[Warmup] {} ready in {:?}",
+            run.root.display(),
+            started.elapsed()
+        );
+        run.emit(None, WarmupState::Finished, 0, None, None);
+    }
+    if let Ok(mut runs) = RUNS.lock() {
+        if runs
+            .get(&run.root)
+            .is_some_and(|active| active.id == run.id)
+        {
+            runs.remove(&run.root);
+        }
+    }
+}
+
+/// Start warming up a workspace, cancelling any earlier run for it; returns the run ID
+#[tauri::command]
+pub fn start_workspace_warmup(
+    app: AppHandle,
+    workspace_root: String,
+    options: Option<WarmupOptions>,
+) -> Result<u64, String> {
+    let root = PathBuf::from(&workspace_root)
+        .canonicalize()
+        .map_err(|e| format!("Invalid workspace root: {}", e))?;
+    let id = NEXT_RUN_ID.fetch_add(1, Ordering::Relaxed);
+    let cancelled = Arc::new(AtomicBool::new(false));
+
+    {
+        let mut runs = RUNS.lock().map_err(|e| e.to_string())?;
+        let previous = runs.insert(
+            root.clone(),
+            ActiveRun {
+                id,
+                cancelled: cancelled.clone(),
+            },
+        );
+        if let Some(previous) = previous {
+            previous.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    let run = Run {
+        app,
+        root,
+        id,
+        cancelled,
+        stage_count: 0,
+        stages_done: 0,
+    };
+    let options = options.unwrap_or_default();
+    std::thread::Builder::new()
+        .name("workspace-warmup".to_string())
+        .spawn(move || run_warmup(run, options))
+        .map_err(|e| format!("Failed to start warmup: {}", e))?;
+    Ok(id)
+}
+
+/// Cancel the running warmup of a workspace, if any
+#[tauri::command]
+pub fn cancel_workspace_warmup(workspace_root: String) -> Result<bool, String> {
+    let root = PathBuf::from(&workspace_root)
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(&workspace_root));
+    let runs = RUNS.lock().map_err(|e| e.to_string())?;
+    Ok(match runs.get(&root) {
+        Some(run) => {
+            run.cancelled.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    })
+}
+
+#[tauri::command]
+pub fn get_workspace_warmup_status(workspace_root: String) -> Result<WarmupStatus, String> {
+    let root = PathBuf::from(&workspace_root)
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(&workspace_root));
+    let running = RUNS.lock().map_err(|e| e.to_string())?.contains_key(&root);
+    let indexes = INDEXES.read().map_err(|e| e.to_string())?;
+    let index = indexes.get(&root);
+    Ok(WarmupStatus {
+        running,
+        file_count: index.and_then(|i| i.files.as_ref()).map(|f| f.len()),
+        symbol_count: index.and_then(|i| i.symbols.as_ref()).map(|s| s.len()),
+        indexed_at: index.and_then(|i| i.indexed_at),
+    })
+}
+
+/// Search the symbol index built by the warmup; prefix matches rank first
+#[tauri::command]
+pub fn search_workspace_symbols(
+    workspace_root: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<IndexedSymbol>, String> {
+    let root = PathBuf::from(&workspace_root)
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(&workspace_root));
+    let symbols = INDEXES
+        .read()
+        .map_err(|e| e.to_string())?
+        .get(&root)
+        .and_then(|index| index.symbols.clone())
+        .ok_or("Symbols have not been indexed for this workspace")?;
+
+    let query = query.to_lowercase();
+    let mut matches: Vec<(u8, &IndexedSymbol)> = symbols
+        .iter()
+        .filter_map(|symbol| {
+            let name = symbol.name.to_lowercase();
+            let rank = if name == query {
+                0
+            } else if name.starts_with(&query) {
+                1
+            } else if name.contains(&query) {
+                2
+            } else {
+                return None;
+            };
+            Some((rank, symbol))
+        })
+        .collect();
+    matches.sort_by(|(a_rank, a), (b_rank, b)| {
+        a_rank
+            .cmp(b_rank)
+            .then(a.name.len().cmp(&b.name.len()))
+            .then(a.path.cmp(&b.path))
+    });
+    Ok(matches
+        .into_iter()
+        .take(limit.unwrap_or(200))
+        .map(|(_, symbol)| symbol.clone())
+        .collect())
+}
@@ -27,6 +27,8 @@ import {
   initializeAgentServer,
   startAgentServer,
 } from "./services/agentServer";
+import { initializeCliService } from "./services/cliService";
+import { initializeDeepLinkService } from "./services/deepLinkService";
 
 const App: React.FC = () => {
   const [isInitialized, setIsInitialized] = useState(false);
@@ -398,6 +400,16 @@ const App: React.FC = () => {
           // Non-fatal - agent features will be unavailable but app continues
         }
 
+        // Files, diffs and merges requested on the command line
+        initializeCliService().catch((error) => {
+          console.warn("[App] CLI request handling failed (non-fatal):", error);
+        });
+
+        // rainy:// links from browsers, terminals and CI logs
+        initializeDeepLinkService().catch((error) => {
+          console.warn("[App] Deep link handling failed (non-fatal):", error);
+        });
+
         // Stage 5: Resources
         loadingActions.startStage("resources");
         // Add a small delay for resource provisioning
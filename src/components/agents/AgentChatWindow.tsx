@@ -11,6 +11,8 @@ import {
   ArrowRight,
   ChevronDown,
   Plus,
+  Square,
+  ShieldCheck,
 } from "lucide-react";
 import { useEffect, useRef, memo, useCallback, useState } from "react";
 import ReactMarkdown from "react-markdown";
@@ -21,7 +23,8 @@ import { useAgentChat } from "@/hooks/useAgentChat";
 import { useTauriDragDrop } from "@/hooks/useTauriDragDrop";
 import { cn } from "@/lib/utils";
 import { useActiveSession, agentActions } from "@/stores/agentStore";
-import { AVAILABLE_MODELS } from "@/services/agent/providers";
+import { AVAILABLE_MODELS, getModelConfig } from "@/services/agent/providers";
+import { useOpenAICompatibleModels } from "@/hooks/useOpenAICompatibleModels";
 import { loadCredential } from "@/services/agent/AgentService";
 import { CodeBlock } from "./CodeBlock";
 import { ToolExecutionList } from "./ToolExecutionList";
@@ -57,6 +60,9 @@ interface ChatInputAreaProps {
   compact: boolean;
   isLoading: boolean;
   onSend: (message: string, images?: ImageAttachment[]) => void;
+  onCancel?: () => void;
+  requireToolApproval: boolean;
+  onToolApprovalChange: (enabled: boolean) => void;
   activeSessionId?: string;
   activeSessionModel?: string;
   selectedSubagent: string | null;
@@ -68,6 +74,9 @@ const ChatInputArea = memo(function ChatInputArea({
   compact,
   isLoading,
   onSend,
+  onCancel,
+  requireToolApproval,
+  onToolApprovalChange,
   activeSessionId,
   activeSessionModel,
   selectedSubagent,
@@ -270,6 +279,8 @@ const ChatInputArea = memo(function ChatInputArea({
     }
   }, [hasContent]);
 
+  const endpointModels = useOpenAICompatibleModels();
+
   const handleModelChange = useCallback(
     (modelId: string) => {
       if (activeSessionId) {
@@ -379,9 +390,7 @@ const ChatInputArea = memo(function ChatInputArea({
                     {!compact && (
                       <span className="truncate max-w-[100px] group-hover:text-foreground/90 transition-colors">
                         {activeSessionModel
-                          ? AVAILABLE_MODELS.find(
-                              (m) => m.id === activeSessionModel
-                            )?.name || "Auto"
+                          ? getModelConfig(activeSessionModel)?.name || "Auto"
                           : "Auto"}
                       </span>
                     )}
@@ -396,7 +405,7 @@ const ChatInputArea = memo(function ChatInputArea({
                     Select Model
                   </DropdownMenuLabel>
                   <DropdownMenuSeparator className="mx-1 opacity-50" />
-                  {AVAILABLE_MODELS.map((model) => (
+                  {[...AVAILABLE_MODELS, ...endpointModels].map((model) => (
                     <DropdownMenuItem
                       key={model.id}
                       onClick={() => handleModelChange(model.id)}
@@ -513,35 +522,76 @@ const ChatInputArea = memo(function ChatInputArea({
                   </span>
                 )}
               </button>
-            </div>
 
-            <div className="flex items-center gap-2">
-              <Button
-                size="icon"
+              {/* Tool Approval Toggle */}
+              <button
+                onClick={() => onToolApprovalChange(!requireToolApproval)}
+                title={
+                  requireToolApproval
+                    ? "File edits and commands wait for your approval"
+                    : "Ask before file edits and commands"
+                }
                 className={cn(
-                  "h-8 w-8 sm:h-9 sm:w-9 rounded-full transition-all duration-300",
-                  hasContent || pendingImages.length > 0
-                    ? "bg-primary text-primary-foreground hover:bg-primary/90 hover:scale-105 shadow-lg shadow-primary/30 hover:shadow-xl hover:shadow-primary/40 active:scale-95"
-                    : "bg-muted/20 text-muted-foreground/30 hover:bg-muted/30 border border-primary/10 cursor-not-allowed",
-                  compact && "h-7 w-7"
+                  "flex items-center gap-1.5 sm:gap-2 px-2.5 py-1.5 rounded-lg transition-all group text-[10px] sm:text-[11px] font-semibold uppercase tracking-wider border border-transparent",
+                  requireToolApproval
+                    ? "text-primary bg-primary/10 border-primary/20"
+                    : "text-muted-foreground/60 hover:bg-primary/10 hover:text-primary hover:border-primary/20"
                 )}
-                onClick={handleSendClick}
-                disabled={
-                  isLoading || (!hasContent && pendingImages.length === 0)
-                }
               >
-                {isLoading ? (
-                  <Loader2 className="h-4 w-4 animate-spin" />
-                ) : (
-                  <ArrowRight
-                    className={cn(
-                      "h-4 w-4 transition-transform duration-300",
-                      (hasContent || pendingImages.length > 0) &&
-                        "translate-x-0"
-                    )}
-                  />
-                )}
-              </Button>
+                <ShieldCheck
+                  className={cn(
+                    "h-3 sm:h-3.5 w-3 sm:w-3.5 transition-colors",
+                    requireToolApproval
+                      ? "text-primary"
+                      : "text-muted-foreground/40 group-hover:text-primary"
+                  )}
+                />
+                {!compact && <span className="hidden sm:inline">Approve</span>}
+              </button>
+            </div>
+
+            <div className="flex items-center gap-2">
+              {isLoading && onCancel ? (
+                <Button
+                  size="icon"
+                  variant="outline"
+                  className={cn(
+                    "h-8 w-8 sm:h-9 sm:w-9 rounded-full transition-all duration-300 hover:scale-105 active:scale-95",
+                    compact && "h-7 w-7"
+                  )}
+                  onClick={onCancel}
+                  title="Stop"
+                >
+                  <Square className="h-3.5 w-3.5 fill-current" />
+                </Button>
+              ) : (
+                <Button
+                  size="icon"
+                  className={cn(
+                    "h-8 w-8 sm:h-9 sm:w-9 rounded-full transition-all duration-300",
+                    hasContent || pendingImages.length > 0
+                      ? "bg-primary text-primary-foreground hover:bg-primary/90 hover:scale-105 shadow-lg shadow-primary/30 hover:shadow-xl hover:shadow-primary/40 active:scale-95"
+                      : "bg-muted/20 text-muted-foreground/30 hover:bg-muted/30 border border-primary/10 cursor-not-allowed",
+                    compact && "h-7 w-7"
+                  )}
+                  onClick={handleSendClick}
+                  disabled={
+                    isLoading || (!hasContent && pendingImages.length === 0)
+                  }
+                >
+                  {isLoading ? (
+                    <Loader2 className="h-4 w-4 animate-spin" />
+                  ) : (
+                    <ArrowRight
+                      className={cn(
+                        "h-4 w-4 transition-transform duration-300",
+                        (hasContent || pendingImages.length > 0) &&
+                          "translate-x-0"
+                      )}
+                    />
+                  )}
+                </Button>
+              )}
             </div>
           </div>
         </div>
@@ -642,6 +692,9 @@ export function AgentChatWindow({
     messages,
     isLoading,
     sendMessage,
+    cancel,
+    requireToolApproval,
+    setToolApproval,
     streamingContent,
     streamingThoughts,
   } = useAgentChat();
@@ -1311,6 +1364,9 @@ export function AgentChatWindow({
           compact={compact}
           isLoading={isLoading}
           onSend={handleSend}
+          onCancel={cancel}
+          requireToolApproval={requireToolApproval}
+          onToolApprovalChange={setToolApproval}
           activeSessionId={activeSession?.id}
           activeSessionModel={activeSession?.model}
           selectedSubagent={selectedSubagent}
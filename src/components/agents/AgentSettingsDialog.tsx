@@ -14,7 +14,7 @@ import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
 import { Separator } from "@/components/ui/separator";
 import { Badge } from "@/components/ui/badge";
-import { saveCredential, loadCredential } from "@/services/agent/AgentService";
+import { saveCredential, loadCredential, deleteCredential } from "@/services/agent/AgentService";
 import { useAgentServer } from "@/hooks/useAgentServer";
 import { toast } from "sonner";
 
@@ -96,6 +96,10 @@ export function AgentSettingsDialog() {
   });
   const [showKeys, setShowKeys] = useState<Record<string, boolean>>({});
 
+  // OpenAI-compatible endpoint (OpenAI, Azure OpenAI, Together, ...)
+  const [openaiBaseUrl, setOpenaiBaseUrl] = useState("");
+  const [savedOpenaiBaseUrl, setSavedOpenaiBaseUrl] = useState("");
+
   // Load existing keys on open
   useEffect(() => {
     if (open) {
@@ -119,6 +123,10 @@ export function AgentSettingsDialog() {
         })
       );
 
+      const baseUrl = (await loadCredential("openai_base_url")) || "";
+      setOpenaiBaseUrl(baseUrl);
+      setSavedOpenaiBaseUrl(baseUrl);
+
       setKeys(loadedKeys);
       setKeyStatus(status as unknown as ApiKeyStatus);
     } catch (error) {
@@ -146,8 +154,18 @@ export function AgentSettingsDialog() {
         }
       }
 
+      const baseUrl = openaiBaseUrl.trim();
+      if (baseUrl !== savedOpenaiBaseUrl) {
+        promises.push(
+          baseUrl
+            ? saveCredential("openai_base_url", baseUrl)
+            : deleteCredential("openai_base_url")
+        );
+      }
+
       if (promises.length > 0) {
         await Promise.all(promises);
+        setSavedOpenaiBaseUrl(baseUrl);
         toast.success("API keys saved successfully");
         setOpen(false);
       } else {
@@ -333,6 +351,23 @@ export function AgentSettingsDialog() {
                     <p className="text-[10px] text-muted-foreground">
                       {provider.description}
                     </p>
+                    {provider.id === 'openai' && (
+                      <div className="space-y-1 pt-1">
+                        <Label htmlFor="openai-base-url" className="text-xs text-muted-foreground">
+                          API Base URL
+                        </Label>
+                        <Input
+                          id="openai-base-url"
+                          placeholder="https://api.openai.com/v1"
+                          value={openaiBaseUrl}
+                          onChange={(e) => setOpenaiBaseUrl(e.target.value)}
+                          className="h-9 text-sm font-mono"
+                        />
+                        <p className="text-[10px] text-muted-foreground">
+                          Leave empty for OpenAI, or point at any OpenAI-compatible endpoint (Azure OpenAI, Together, local servers).
+                        </p>
+                      </div>
+                    )}
                   </div>
                 ))}
               </div>
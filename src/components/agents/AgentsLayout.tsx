@@ -7,7 +7,7 @@ import {
   agentActions,
   useActiveSession,
 } from "@/stores/agentStore";
-import { AVAILABLE_MODELS, getModelConfig } from "@/services/agent/providers";
+import { getModelConfig } from "@/services/agent/providers";
 import { getContextStatus, ContextStatus } from "@/services/agent/TokenCounter";
 import { TokenUsageBar } from "./TokenUsageBar";
 import { Button } from "@/components/ui/button";
@@ -104,8 +104,8 @@ export function AgentsLayout() {
                 <div className="flex items-center gap-1.5 opacity-60">
                   <span className="flex h-1 w-1 rounded-full bg-emerald-500 shrink-0 animate-pulse"></span>
                   <span className="text-[9px] sm:text-[10px] text-muted-foreground/80 font-medium truncate uppercase tracking-widest max-w-[80px] sm:max-w-none">
-                    {AVAILABLE_MODELS.find((m) => m.id === activeSession?.model)
-                      ?.name || "Gemini Flash 2.0"}
+                    {(activeSession && getModelConfig(activeSession.model)?.name) ||
+                      "Gemini Flash 2.0"}
                   </span>
                 </div>
               </div>
@@ -8,7 +8,13 @@ import {
     SelectTrigger,
     SelectValue,
 } from "@/components/ui/select"
-import { AVAILABLE_MODELS, ModelConfig } from "@/services/agent/providers"
+import {
+    AVAILABLE_MODELS,
+    ModelConfig,
+    OPENAI_COMPATIBLE_MODEL_PREFIX,
+    getModelConfig,
+} from "@/services/agent/providers"
+import { useOpenAICompatibleModels } from "@/hooks/useOpenAICompatibleModels"
 import { cn } from "@/lib/utils"
 
 interface ModelSelectorProps {
@@ -29,6 +35,20 @@ export function ModelSelector({ value, onValueChange, className }: ModelSelector
 
     const groqModels = AVAILABLE_MODELS.filter(m => m.provider === 'groq');
     const cerebrasModels = AVAILABLE_MODELS.filter(m => m.provider === 'cerebras');
+    // Listed models first, then whatever else the endpoint serves
+    const endpointModels = useOpenAICompatibleModels();
+    const openaiModels = [
+        ...AVAILABLE_MODELS.filter(m => m.provider === 'openai'),
+        ...endpointModels,
+    ];
+    const selectedModel = getModelConfig(value);
+    if (
+        selectedModel &&
+        value.startsWith(OPENAI_COMPATIBLE_MODEL_PREFIX) &&
+        !openaiModels.some(m => m.id === value)
+    ) {
+        openaiModels.push(selectedModel);
+    }
 
     const getModelIcon = (model: ModelConfig) => {
         if (model.category === 'thinking' && model.supportsThinking) {
@@ -140,6 +160,27 @@ export function ModelSelector({ value, onValueChange, className }: ModelSelector
                     </SelectGroup>
                 )}
 
+                {/* OpenAI-Compatible Models */}
+                {openaiModels.length > 0 && (
+                    <SelectGroup>
+                        <SelectLabel className="text-[10px] font-bold text-muted-foreground/60 uppercase tracking-wider px-2 py-2 mt-2 border-t border-white/5 pt-3">
+                            OpenAI Compatible
+                        </SelectLabel>
+                        {openaiModels.map((model) => (
+                            <SelectItem
+                                key={model.id}
+                                value={model.id}
+                                className="text-xs cursor-pointer rounded-lg focus:bg-white/10 focus:text-white my-0.5"
+                            >
+                                <div className="flex items-center gap-2 w-full">
+                                    <Zap className="h-3.5 w-3.5 text-sky-500" />
+                                    <span className="flex-1 font-medium">{model.name}</span>
+                                </div>
+                            </SelectItem>
+                        ))}
+                    </SelectGroup>
+                )}
+
                 {/* Cerebras Models */}
                 {cerebrasModels.length > 0 && (
                     <SelectGroup>
@@ -30,6 +30,7 @@ import {
     CollapsibleContent,
     CollapsibleTrigger,
 } from '@/components/ui/collapsible';
+import { approveTool } from '@/services/agent/ToolApproval';
 
 interface ToolExecution {
     name: string;
@@ -37,6 +38,9 @@ interface ToolExecution {
     status?: 'pending' | 'running' | 'success' | 'error';
     result?: any;
     error?: string;
+    durationMs?: number;
+    approval?: 'requested' | 'approved' | 'rejected';
+    approvalId?: string;
 }
 
 interface ToolExecutionListProps {
@@ -59,6 +63,7 @@ const TOOL_ICONS: Record<string, React.ComponentType<{ className?: string }>> =
     'read_directory_tree': FolderOpen,
     // Search & commands
     'search_code': Search,
+    'semantic_search': Search,
     'run_command': Terminal,
     'run_tests': Play,
     'get_workspace_info': FolderOpen,
@@ -93,10 +98,41 @@ function getToolIcon(toolName: string) {
     return Icon;
 }
 
+/**
+ * Approve / Reject buttons for a tool call waiting on the user
+ */
+function ApprovalActions({ approvalId }: { approvalId: string }) {
+    return (
+        <div className="flex items-center justify-between gap-2 px-3 py-2 border-t border-amber-500/20 bg-amber-500/5">
+            <span className="text-xs text-amber-300/90">Waiting for your approval</span>
+            <div className="flex items-center gap-1.5">
+                <button
+                    onClick={() => approveTool(approvalId, false)}
+                    className="text-[11px] font-semibold px-2.5 py-1 rounded-md border border-red-500/30 text-red-300 hover:bg-red-500/10 transition-colors"
+                >
+                    Reject
+                </button>
+                <button
+                    onClick={() => approveTool(approvalId, true)}
+                    className="text-[11px] font-semibold px-2.5 py-1 rounded-md border border-green-500/30 bg-green-500/10 text-green-300 hover:bg-green-500/20 transition-colors"
+                >
+                    Approve
+                </button>
+            </div>
+        </div>
+    );
+}
+
 function ToolExecutionItem({ tool, compact }: { tool: ToolExecution; compact?: boolean }) {
-    const [isOpen, setIsOpen] = React.useState(tool.status === 'running' || tool.status === 'error');
+    const awaitingApproval = tool.approval === 'requested' && !!tool.approvalId;
+    const [isOpen, setIsOpen] = React.useState(tool.status === 'running' || tool.status === 'error' || awaitingApproval);
     const Icon = getToolIcon(tool.name);
 
+    // Show the arguments when the call starts waiting for approval
+    React.useEffect(() => {
+        if (awaitingApproval) setIsOpen(true);
+    }, [awaitingApproval]);
+
     const getStatusIcon = () => {
         switch (tool.status) {
             case 'running':
@@ -152,18 +188,18 @@ function ToolExecutionItem({ tool, compact }: { tool: ToolExecution; compact?: b
 
     if (compact) {
         return (
-            <div className={cn(
-                'flex items-center gap-2 px-2 py-1 rounded-md border text-xs transition-colors',
-                getStatusColor()
-            )}>
-                {getStatusIcon()}
-                <Icon className="h-3 w-3 text-muted-foreground" />
-                <span className="font-mono text-foreground/90 font-medium">{tool.name}</span>
-                {primaryArg && (
-                    <span className="text-muted-foreground truncate max-w-[150px] opacity-70">
-                        {primaryArg}
-                    </span>
-                )}
+            <div className={cn('rounded-md border overflow-hidden transition-colors', getStatusColor())}>
+                <div className="flex items-center gap-2 px-2 py-1 text-xs">
+                    {getStatusIcon()}
+                    <Icon className="h-3 w-3 text-muted-foreground" />
+                    <span className="font-mono text-foreground/90 font-medium">{tool.name}</span>
+                    {primaryArg && (
+                        <span className="text-muted-foreground truncate max-w-[150px] opacity-70">
+                            {primaryArg}
+                        </span>
+                    )}
+                </div>
+                {awaitingApproval && <ApprovalActions approvalId={tool.approvalId!} />}
             </div>
         );
     }
@@ -193,6 +229,13 @@ function ToolExecutionItem({ tool, compact }: { tool: ToolExecution; compact?: b
                         </div>
 
                         <div className="flex items-center gap-2 shrink-0">
+                            {tool.durationMs !== undefined && (
+                                <span className="text-[10px] text-muted-foreground/70 font-mono">
+                                    {tool.durationMs < 1000
+                                        ? `${tool.durationMs}ms`
+                                        : `${(tool.durationMs / 1000).toFixed(1)}s`}
+                                </span>
+                            )}
                             <span className={cn(
                                 'text-[10px] px-1.5 py-0.5 rounded-full font-medium uppercase tracking-wider',
                                 tool.status === 'running' ? 'bg-blue-500/20 text-blue-400' :
@@ -211,6 +254,8 @@ function ToolExecutionItem({ tool, compact }: { tool: ToolExecution; compact?: b
                     </div>
                 </CollapsibleTrigger>
 
+                {awaitingApproval && <ApprovalActions approvalId={tool.approvalId!} />}
+
                 <CollapsibleContent>
                     <div className="px-3 py-3 border-t border-border/10 space-y-3 bg-black/20">
                         {/* Arguments Grid */}
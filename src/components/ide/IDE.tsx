@@ -13,6 +13,7 @@ import StartupPage from "./StartupPage";
 import SettingsPage from "./SettingsPage";
 import AgentsView from "./AgentsView";
 import CloneDialog from "./CloneDialog";
+import PassphraseDialog from "./PassphraseDialog";
 import { UpdateNotification } from "./UpdateNotification";
 import { UpdateModal } from "./UpdateModal";
 import { useIDEStore, useIDEState } from "../../stores/ideStore";
@@ -753,6 +754,8 @@ const IDE: React.FC = () => {
         }}
       />
 
+      <PassphraseDialog />
+
       {/* About Dialog */}
       <AboutDialog open={isAboutOpen} onOpenChange={setIsAboutOpen} />
 
@@ -0,0 +1,125 @@
+import React, { useCallback, useEffect, useId, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { KeyRound } from "lucide-react";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogHeader,
+  DialogTitle,
+} from "@/components/ui/dialog";
+
+/** Payload of the backend's `git:passphrase-required` event */
+interface PassphraseRequest {
+  operationId: string;
+  keyPath: string;
+  /** 1 for the first prompt, higher after a wrong passphrase */
+  attempt: number;
+}
+
+/**
+ * Asks for SSH key passphrases while a clone, fetch, pull or push waits.
+ * Requests are answered in the order they arrive; closing the dialog cancels
+ * the operation's prompt.
+ */
+const PassphraseDialog: React.FC = () => {
+  const [queue, setQueue] = useState<PassphraseRequest[]>([]);
+  const [passphrase, setPassphrase] = useState("");
+  const inputId = useId();
+  const current = queue[0];
+
+  useEffect(() => {
+    const isTauriEnv = typeof window !== "undefined" && (window as any).__TAURI__;
+    if (!isTauriEnv) return;
+
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+    listen<PassphraseRequest>("git:passphrase-required", (event) => {
+      setQueue((pending) => [...pending, event.payload]);
+    })
+      .then((fn) => {
+        if (cancelled) fn();
+        else unlisten = fn;
+      })
+      .catch((error) => {
+        console.warn("Failed to listen for git:passphrase-required", error);
+      });
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, []);
+
+  const answer = useCallback(
+    (value: string | null) => {
+      if (!current) return;
+      invoke("git_provide_passphrase", {
+        operationId: current.operationId,
+        passphrase: value,
+      }).catch((error) => {
+        console.warn("Failed to provide passphrase:", error);
+      });
+      setPassphrase("");
+      setQueue((pending) => pending.slice(1));
+    },
+    [current]
+  );
+
+  const keyName = current?.keyPath.split(/[/\\]/).pop() || current?.keyPath;
+
+  return (
+    <Dialog
+      open={Boolean(current)}
+      onOpenChange={(open) => {
+        if (!open) answer(null);
+      }}
+    >
+      <DialogContent className="sm:max-w-[420px]">
+        <DialogHeader>
+          <DialogTitle className="flex items-center gap-2">
+            <KeyRound className="h-4 w-4" />
+            SSH Key Passphrase
+          </DialogTitle>
+          <DialogDescription>
+            {current && current.attempt > 1
+              ? `The passphrase for ${keyName} was not accepted. Try again.`
+              : `Enter the passphrase for ${keyName}.`}
+          </DialogDescription>
+        </DialogHeader>
+        <form
+          className="grid gap-4 py-2"
+          onSubmit={(event) => {
+            event.preventDefault();
+            answer(passphrase);
+          }}
+        >
+          <div className="grid gap-2">
+            <Label htmlFor={inputId}>Passphrase</Label>
+            <Input
+              id={inputId}
+              type="password"
+              value={passphrase}
+              onChange={(event) => setPassphrase(event.target.value)}
+              autoComplete="off"
+              autoFocus
+            />
+            <p className="text-muted-foreground text-xs break-all">{current?.keyPath}</p>
+          </div>
+          <div className="flex justify-end gap-2">
+            <Button type="button" variant="ghost" onClick={() => answer(null)}>
+              Cancel
+            </Button>
+            <Button type="submit">Unlock</Button>
+          </div>
+        </form>
+      </DialogContent>
+    </Dialog>
+  );
+};
+
+export default PassphraseDialog;
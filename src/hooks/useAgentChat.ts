@@ -1,5 +1,5 @@
 import { useState, useCallback, useRef, useEffect, useMemo } from 'react';
-import { AgentService } from '@/services/agent/AgentService';
+import { AgentService, cancelAgentRun } from '@/services/agent/AgentService';
 import { ChatMessage } from '@/types/chat';
 import {
   useActiveSession,
@@ -8,6 +8,7 @@ import {
 } from '@/stores/agentStore';
 import { StreamChunk, getModelConfig } from '@/services/agent/providers';
 import { getContextStatus, ContextStatus } from '@/services/agent/TokenCounter';
+import { metricsCollector, UsageTotals } from '@/services/agent/MetricsCollector';
 
 export function useAgentChat() {
   const activeSession = useActiveSession();
@@ -25,6 +26,9 @@ export function useAgentChat() {
   const sessionId = activeSession?.id;
   const sessionModel = activeSession?.model;
   const sessionSystemPrompt = activeSession?.systemPrompt;
+  const sessionTemplate = activeSession?.template;
+  const requireToolApproval = activeSession?.requireToolApproval ?? false;
+  const budgetUsd = activeSession?.budgetUsd;
 
   // Initialize or update service when active session changes
   useEffect(() => {
@@ -33,12 +37,24 @@ export function useAgentChat() {
         sessionId,
         model: sessionModel,
         systemPrompt: sessionSystemPrompt,
+        template: sessionTemplate,
+        requireToolApproval,
+        budgetUsd,
       });
     } else {
       agentServiceRef.current = null;
     }
   }, [sessionId, sessionModel, sessionSystemPrompt]);
 
+  // Approval mode can change mid-run; the service picks it up for the next tool call
+  useEffect(() => {
+    agentServiceRef.current?.setRequireToolApproval(requireToolApproval);
+  }, [requireToolApproval]);
+
+  useEffect(() => {
+    agentServiceRef.current?.setBudget(budgetUsd);
+  }, [budgetUsd]);
+
   // Memoize setInput to prevent unnecessary re-renders
   const setInput = useCallback((value: string) => {
     setInputState(value);
@@ -154,6 +170,37 @@ export function useAgentChat() {
     }
   }, [input, activeSession]);
 
+  // Stop the in-flight run (provider request and running tools)
+  const cancel = useCallback(() => {
+    if (sessionId) {
+      cancelAgentRun(sessionId);
+    }
+  }, [sessionId]);
+
+  const setToolApproval = useCallback((enabled: boolean) => {
+    if (sessionId) {
+      agentActions.setToolApproval(sessionId, enabled);
+    }
+  }, [sessionId]);
+
+  const setBudget = useCallback((budget: number | undefined) => {
+    if (sessionId) {
+      agentActions.setBudget(sessionId, budget);
+    }
+  }, [sessionId]);
+
+  // Let a session paused by its budget continue for another budget's worth
+  const resumeBudget = useCallback(() => {
+    if (sessionId) {
+      metricsCollector.resume(sessionId);
+    }
+  }, [sessionId]);
+
+  // Token usage and cost of the session, updated as messages arrive
+  const usage: UsageTotals | null = useMemo(() => {
+    return sessionId ? metricsCollector.getSessionUsage(sessionId) : null;
+  }, [sessionId, messages]);
+
   const clearChat = useCallback(() => {
     if (activeSession) {
       agentActions.clearSession(activeSession.id);
@@ -168,6 +215,13 @@ export function useAgentChat() {
     streamingContent,
     streamingThoughts,
     sendMessage,
+    cancel,
+    requireToolApproval,
+    setToolApproval,
+    budgetUsd,
+    setBudget,
+    resumeBudget,
+    usage, // Token usage and cost of the session
     clearChat,
     contextStatus, // Token context usage status
   };
@@ -0,0 +1,53 @@
+/**
+ * useOpenAICompatibleModels Hook
+ *
+ * Models served by the configured OpenAI-compatible endpoint (`GET /models`)
+ * that aren't in AVAILABLE_MODELS, as `openai:<model>` configs. Empty until an
+ * OpenAI API key is set, or when the endpoint can't be listed.
+ */
+
+import { useState, useEffect } from 'react';
+import {
+    AVAILABLE_MODELS,
+    ModelConfig,
+    OPENAI_COMPATIBLE_MODEL_PREFIX,
+    getModelConfig,
+    listOpenAICompatibleModels,
+} from '@/services/agent/providers';
+import { loadCredential } from '@/services/agent/AgentService';
+
+export function useOpenAICompatibleModels(): ModelConfig[] {
+    const [models, setModels] = useState<ModelConfig[]>([]);
+
+    useEffect(() => {
+        let cancelled = false;
+
+        const load = async () => {
+            const apiKey = await loadCredential('openai_api_key');
+            if (!apiKey) return;
+            const baseUrl = (await loadCredential('openai_base_url')) || undefined;
+            const ids = await listOpenAICompatibleModels({ apiKey, baseUrl });
+            if (cancelled) return;
+
+            const listed = new Set(
+                AVAILABLE_MODELS.filter((m) => m.provider === 'openai').map((m) => m.model)
+            );
+            setModels(
+                ids
+                    .filter((id) => !listed.has(id))
+                    .map((id) => getModelConfig(`${OPENAI_COMPATIBLE_MODEL_PREFIX}${id}`))
+                    .filter((model): model is ModelConfig => model !== undefined)
+            );
+        };
+
+        load().catch((error) => {
+            console.warn('[useOpenAICompatibleModels] Failed to list endpoint models:', error);
+        });
+
+        return () => {
+            cancelled = true;
+        };
+    }, []);
+
+    return models;
+}
@@ -192,9 +192,10 @@ class BrainService {
     /**
      * Execute a single tool
      */
-    async executeTool(request: ToolCallRequest): Promise<ToolResult> {
+    async executeTool(request: ToolCallRequest, signal?: AbortSignal): Promise<ToolResult> {
         const response = await fetch(`${this.baseUrl}/api/brain/tool`, {
             method: 'POST',
+            signal,
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
                 tool: request.tool,
@@ -127,7 +127,7 @@ export class AgentHistoryService {
       const fileName = `${sessionId}.json`;
       const filePath = await join(this.historyPath, fileName);
 
-      const content = await invoke<string>('get_file_content', { path: filePath });
+      const { content } = await invoke<{ content: string }>('get_file_content', { path: filePath });
       if (!content) return null;
 
       const session = JSON.parse(content) as AgentSession;
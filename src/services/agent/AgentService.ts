@@ -1,8 +1,24 @@
 import { invoke } from '@tauri-apps/api/core';
-import { ChatMessage } from '@/types/chat';
+import { ChatMessage, ToolCall } from '@/types/chat';
 import { toolRegistry } from './ToolRegistry';
-import { AIProvider, StreamChunk, createProvider, ProviderCredentials, getModelConfig } from './providers';
-import { getContextStatus, truncateToFitContext } from './TokenCounter';
+import { AIProvider, StreamChunk, createProvider, ProviderCredentials, getModelConfig, validateModel } from './providers';
+import { JsonSchema, RequestOptions, createChatMessage, getUsage } from './providers/base';
+import {
+  DEFAULT_RETRY_POLICY,
+  FailoverTarget,
+  ResilientProvider,
+  RetryPolicy,
+  getAgentMetadata,
+} from './providers/resilient';
+import { estimateConversationTokens, estimateMessageTokens, getContextStatus } from './TokenCounter';
+import { contextManager } from './ContextManager';
+import { requestToolApproval, requiresApproval } from './ToolApproval';
+import { metricsCollector } from './MetricsCollector';
+import {
+  createSchemaInstructions,
+  createSchemaRetryPrompt,
+  parseStructuredOutput,
+} from './StructuredOutput';
 import { brainService } from '@/services/BrainService';
 import { getIDEState } from '@/stores/ideStore';
 
@@ -138,8 +154,175 @@ interface AgentConfig {
   sessionId: string;
   model: string;
   systemPrompt: string;
+  /** Prompt template the system prompt was resolved from (see PromptTemplates) */
+  template?: string;
   temperature?: number;
   maxTokens?: number;
+  /** Wall-clock limit for one run (message plus tool rounds); unlimited when unset */
+  timeoutMs?: number;
+  /** Ask the user before running tools that change the workspace (see ToolApproval) */
+  requireToolApproval?: boolean;
+  /** Run independent read-only tool calls concurrently (default true) */
+  parallelTools?: boolean;
+  /** Spend in USD after which the session is paused (see MetricsCollector) */
+  budgetUsd?: number;
+  /** Retries of failed provider requests (429, 5xx, network errors) */
+  retry?: Partial<RetryPolicy>;
+  /** Model to fail over to once the primary model's retries are used up */
+  fallbackModel?: string;
+  /** JSON Schema the final response must conform to (see StructuredOutput) */
+  responseSchema?: JsonSchema;
+}
+
+// ===========================
+// Run Cancellation
+// ===========================
+
+/** Why an agent run stopped before finishing */
+export type AgentRunStopReason = 'cancelled' | 'timeout' | 'budget';
+
+const STOP_MESSAGES: Record<AgentRunStopReason, string> = {
+  cancelled: 'Agent run cancelled',
+  timeout: 'Agent run timed out',
+  budget: 'Agent run paused: budget exceeded',
+};
+
+class AgentRunAborted extends Error {
+  constructor(readonly reason: AgentRunStopReason) {
+    super(STOP_MESSAGES[reason]);
+    this.name = 'AgentRunAborted';
+  }
+}
+
+/** Abort controllers of in-flight runs, by session ID */
+const activeRuns = new Map<string, AbortController>();
+
+/**
+ * Cancel the in-flight run of a session: aborts the provider request and
+ * any running tool, and skips the remaining tool calls.
+ * Returns false when the session has no run in flight.
+ */
+export function cancelAgentRun(sessionId: string): boolean {
+  const controller = activeRuns.get(sessionId);
+  if (!controller) return false;
+  controller.abort(new AgentRunAborted('cancelled'));
+  return true;
+}
+
+/**
+ * Settle with `promise`, or reject as soon as `signal` aborts
+ */
+function raceAbort<T>(promise: Promise<T>, signal: AbortSignal): Promise<T> {
+  if (signal.aborted) return Promise.reject(signal.reason);
+  return new Promise<T>((resolve, reject) => {
+    const onAbort = () => reject(signal.reason);
+    signal.addEventListener('abort', onAbort, { once: true });
+    promise.then(
+      (value) => {
+        signal.removeEventListener('abort', onAbort);
+        resolve(value);
+      },
+      (error) => {
+        signal.removeEventListener('abort', onAbort);
+        reject(error);
+      }
+    );
+  });
+}
+
+interface BackgroundPauseStatus {
+  subsystems: { subsystem: string; paused: boolean }[];
+}
+
+async function agentRunsPaused(): Promise<boolean> {
+  try {
+    const status = await invoke<BackgroundPauseStatus>('get_background_pause_status');
+    return status.subsystems.some((s) => s.subsystem === 'agentRuns' && s.paused);
+  } catch {
+    return false;
+  }
+}
+
+/**
+ * Hold a run between provider requests while agent runs are paused (see
+ * background_pause.rs); the run can still be cancelled while it waits
+ */
+async function waitWhileAgentRunsPaused(signal: AbortSignal): Promise<void> {
+  while (await agentRunsPaused()) {
+    await raceAbort(new Promise((resolve) => setTimeout(resolve, 1000)), signal);
+  }
+}
+
+// ===========================
+// Tool Execution
+// ===========================
+
+/**
+ * Tools that MUST run locally for proper functionality
+ */
+const LOCAL_ONLY_TOOLS = [
+  'apply_file_diff',  // Needs frontend React state
+  'run_command',      // ToolRegistry has better terminal integration (PTY, async events)
+  'run_tests',        // Uses run_command internally
+  'git_status',       // Better handled by local git service
+  'git_commit',       // Better handled by local git service
+  'git_diff',         // Better handled by local git service
+  'semantic_search',  // Queries the embeddings index in the Rust backend
+  // File tools go through the session's workspace sandbox in the Rust backend
+  'read_file',
+  'edit_file',
+  'write_file',
+  'create_file',
+  'smart_edit',
+  'fs_batch_read',
+];
+
+/** Follow-ups asking the model to fix a response that failed schema validation */
+const MAX_SCHEMA_RETRIES = 2;
+
+/** Most tool calls of one batch running at the same time */
+const MAX_PARALLEL_TOOLS = 4;
+
+/**
+ * Whether a tool must not overlap other calls: anything that changes the
+ * workspace, and MCP tools, whose side effects are unknown
+ */
+function runsAlone(toolName: string): boolean {
+  return requiresApproval(toolName) || toolName === 'apply_file_diff' || toolName.includes('.');
+}
+
+/**
+ * Split tool calls into batches that may run concurrently: consecutive
+ * read-only calls share a batch, every other call is a batch of its own
+ */
+function planToolBatches(toolCalls: ToolCall[]): ToolCall[][] {
+  const batches: ToolCall[][] = [];
+  for (const toolCall of toolCalls) {
+    const last = batches[batches.length - 1];
+    if (last && !runsAlone(toolCall.name) && !runsAlone(last[0].name)) {
+      last.push(toolCall);
+    } else {
+      batches.push([toolCall]);
+    }
+  }
+  return batches;
+}
+
+/**
+ * Run `task` for every item with at most `limit` running at once
+ */
+async function runWithConcurrency<T>(
+  items: T[],
+  limit: number,
+  task: (item: T) => Promise<void>
+): Promise<void> {
+  let next = 0;
+  const worker = async () => {
+    while (next < items.length) {
+      await task(items[next++]);
+    }
+  };
+  await Promise.all(Array.from({ length: Math.min(limit, items.length) }, worker));
 }
 
 // ===========================
@@ -155,6 +338,9 @@ export class AgentService {
 
   constructor(config: AgentConfig) {
     this.config = config;
+    if (config.budgetUsd !== undefined) {
+      metricsCollector.setSessionBudget(config.sessionId, config.budgetUsd);
+    }
   }
 
   /**
@@ -191,10 +377,14 @@ export class AgentService {
       // Load credentials from Tauri secure storage
       const geminiKey = await this.loadCredential('gemini_api_key');
       const groqKey = await this.loadCredential('groq_api_key');
+      const openaiKey = await this.loadCredential('openai_api_key');
+      const openaiBaseUrl = await this.loadCredential('openai_base_url');
 
       this.credentials = {
         geminiApiKey: geminiKey || undefined,
         groqApiKey: groqKey || undefined,
+        openaiApiKey: openaiKey || undefined,
+        openaiBaseUrl: openaiBaseUrl || undefined,
       };
 
       // Check if model supports tools
@@ -206,12 +396,21 @@ export class AgentService {
         console.log(`[AgentService] Model ${this.config.model} does not support tool calling - tools will be disabled`);
       }
 
-      // Create provider
-      this.provider = createProvider(
-        this.config.model,
-        this.credentials,
-        this.config.temperature,
-        this.config.maxTokens
+      // Create provider, with retries and optional failover
+      await validateModel(this.config.model, this.credentials);
+      const primary: FailoverTarget = {
+        provider: createProvider(
+          this.config.model,
+          this.credentials,
+          this.config.temperature,
+          this.config.maxTokens
+        ),
+        model: this.config.model,
+      };
+      this.provider = new ResilientProvider(
+        primary,
+        { ...DEFAULT_RETRY_POLICY, ...this.config.retry },
+        await this.createFallback()
       );
 
       this.isInitialized = true;
@@ -221,6 +420,25 @@ export class AgentService {
     }
   }
 
+  /**
+   * Client for the fallback model, if one is configured and usable
+   */
+  private async createFallback(): Promise<FailoverTarget | undefined> {
+    const { fallbackModel } = this.config;
+    if (!fallbackModel || fallbackModel === this.config.model) return undefined;
+    try {
+      await validateModel(fallbackModel, this.credentials);
+      // The fallback uses its own output limit; maxTokens is set for the primary
+      return {
+        provider: createProvider(fallbackModel, this.credentials, this.config.temperature),
+        model: fallbackModel,
+      };
+    } catch (error) {
+      console.warn(`[AgentService] Fallback model ${fallbackModel} unavailable:`, error);
+      return undefined;
+    }
+  }
+
   /**
    * Get the Gemini API key (for title generation)
    */
@@ -244,12 +462,88 @@ export class AgentService {
 
   /**
    * Send a message with streaming support
+   * The run can be stopped with cancelAgentRun(sessionId) and is bounded by
+   * `timeoutMs`; a stopped run resolves with a message whose metadata
+   * carries `runStatus: 'cancelled' | 'timeout'` instead of throwing.
    */
   async sendMessage(
     messages: ChatMessage[],
     onChunk?: (chunk: StreamChunk) => void
+  ): Promise<ChatMessage> {
+    const sessionId = this.config.sessionId;
+    // A paused session stays paused until the budget is raised or resumed
+    if (metricsCollector.checkBudget(sessionId)) {
+      return this.stoppedRunMessage('budget', 0, onChunk);
+    }
+
+    const controller = new AbortController();
+    // A new message supersedes a run still in flight
+    activeRuns.get(sessionId)?.abort(new AgentRunAborted('cancelled'));
+    activeRuns.set(sessionId, controller);
+
+    const startedAt = Date.now();
+    const timer = this.config.timeoutMs
+      ? setTimeout(() => controller.abort(new AgentRunAborted('timeout')), this.config.timeoutMs)
+      : undefined;
+
+    try {
+      return await this.runMessage(messages, controller.signal, onChunk);
+    } catch (error) {
+      if (!controller.signal.aborted) throw error;
+      const reason = controller.signal.reason instanceof AgentRunAborted
+        ? controller.signal.reason.reason
+        : 'cancelled';
+      return this.stoppedRunMessage(reason, Date.now() - startedAt, onChunk);
+    } finally {
+      clearTimeout(timer);
+      if (activeRuns.get(sessionId) === controller) {
+        activeRuns.delete(sessionId);
+      }
+    }
+  }
+
+  /**
+   * Cancel this service's in-flight run, if any
+   */
+  cancel(): boolean {
+    return cancelAgentRun(this.config.sessionId);
+  }
+
+  /**
+   * Result of a run that was cancelled or timed out
+   */
+  private stoppedRunMessage(
+    reason: AgentRunStopReason,
+    elapsedMs: number,
+    onChunk?: (chunk: StreamChunk) => void
+  ): ChatMessage {
+    let content = '> ⏹️ Run cancelled.';
+    if (reason === 'timeout') {
+      content = `> ⏱️ Run timed out after ${Math.round(elapsedMs / 1000)}s. Changes made so far were kept; send another message to continue.`;
+    } else if (reason === 'budget') {
+      const exceeded = metricsCollector.checkBudget(this.config.sessionId);
+      content = exceeded
+        ? `> 💸 Paused: the ${exceeded.scope} budget of $${exceeded.budgetUsd.toFixed(2)} was reached ($${exceeded.spentUsd.toFixed(2)} spent). Raise the budget or resume the session to continue.`
+        : '> 💸 Paused: budget reached.';
+    }
+    const message: ChatMessage = {
+      id: crypto.randomUUID(),
+      role: 'assistant',
+      content,
+      timestamp: new Date(),
+      metadata: { runStatus: reason, elapsedMs },
+    };
+    if (onChunk) onChunk({ type: 'done', fullMessage: message });
+    return message;
+  }
+
+  private async runMessage(
+    messages: ChatMessage[],
+    signal: AbortSignal,
+    onChunk?: (chunk: StreamChunk) => void
   ): Promise<ChatMessage> {
     await this.initialize();
+    await waitWhileAgentRunsPaused(signal);
 
     if (!this.provider) {
       throw new Error('Provider not initialized. Check API credentials.');
@@ -260,28 +554,36 @@ export class AgentService {
     const contextLimits = {
       contextWindow: modelConfig?.contextWindow ?? 32000,
       maxOutputTokens: modelConfig?.maxOutputTokens ?? 8192,
+      provider: modelConfig?.provider,
     };
 
-    // Check context usage and truncate if needed
-    const status = getContextStatus(messages, contextLimits);
-    let processedMessages = messages;
+    // Summarize older turns / truncate so the request fits the context window
+    const provider = this.provider;
+    const prepared = await contextManager.prepare(
+      this.config.sessionId,
+      messages,
+      contextLimits,
+      async (prompt) => this.recordUsage(prompt, await provider.sendMessage(prompt, [], signal)).content,
+      signal
+    );
+    let processedMessages = prepared.messages;
+
+    if (onChunk && prepared.summarizedCount > 0) {
+      onChunk({
+        type: 'text',
+        content: `\n\n> 📝 Summarized ${prepared.summarizedCount} earlier message(s) to stay within the context window.\n\n`,
+      });
+    }
+    if (onChunk && prepared.removedCount > 0) {
+      // Notify user that context was truncated
+      onChunk({
+        type: 'text',
+        content: `\n\n> ⚠️ Context limit reached. Removed ${prepared.removedCount} older message(s) to continue.\n\n`,
+      });
+    }
 
-    if (status.isAtLimit) {
-      // Truncate to fit
-      const { messages: truncated, truncated: wasTruncated, removedCount } = truncateToFitContext(
-        messages,
-        contextLimits
-      );
-      processedMessages = truncated;
-
-      if (wasTruncated && onChunk) {
-        // Notify user that context was truncated
-        onChunk({
-          type: 'text',
-          content: `\n\n> ⚠️ Context limit reached. Removed ${removedCount} older message(s) to continue.\n\n`,
-        });
-      }
-    } else if (status.isNearLimit && onChunk) {
+    const status = getContextStatus(processedMessages, contextLimits);
+    if (status.isNearLimit) {
       // Warn user approaching limit
       console.log(`[AgentService] Context at ${status.percentUsed.toFixed(0)}% - approaching limit`);
     }
@@ -311,16 +613,43 @@ export class AgentService {
       console.log(`[AgentService] Injected ${mcpToolInfo.length} MCP tools into system prompt`);
     }
 
+    // Describe the required answer format in the system prompt
+    if (this.config.responseSchema) {
+      const schemaSection = createSchemaInstructions(this.config.responseSchema);
+      processedMessages = processedMessages[0]?.role === 'system'
+        ? [
+          { ...processedMessages[0], content: processedMessages[0].content + schemaSection },
+          ...processedMessages.slice(1),
+        ]
+        : [createChatMessage('system', schemaSection.trim()), ...processedMessages];
+    }
+
+    // Confine file tools to the current workspace
+    const workspace = getIDEState().workspace;
+    if (workspace) {
+      await invoke('tool_set_session_root', {
+        sessionId: this.config.sessionId,
+        workspaceRoot: workspace.path,
+      });
+    }
+
     // Only include tools if model supports them
-    const tools = this.modelSupportsTools ? toolRegistry.getAllTools() : [];
+    const tools = this.modelSupportsTools ? await toolRegistry.getEnabledTools() : [];
+    const options = this.requestOptions();
 
     // Use streaming if callback provided
     if (onChunk) {
-      const response = await this.provider.streamMessage(processedMessages, tools, onChunk);
-      return await this.handleToolCalls(response, processedMessages, onChunk);
+      const response = this.recordUsage(
+        processedMessages,
+        await this.provider.streamMessage(processedMessages, tools, onChunk, signal, options)
+      );
+      return await this.handleToolCalls(response, processedMessages, signal, onChunk);
     } else {
-      const response = await this.provider.sendMessage(processedMessages, tools);
-      return await this.handleToolCalls(response, processedMessages);
+      const response = this.recordUsage(
+        processedMessages,
+        await this.provider.sendMessage(processedMessages, tools, signal, options)
+      );
+      return await this.handleToolCalls(response, processedMessages, signal);
     }
   }
 
@@ -331,6 +660,7 @@ export class AgentService {
   private async handleToolCalls(
     response: ChatMessage,
     history: ChatMessage[],
+    signal: AbortSignal,
     onChunk?: (chunk: StreamChunk) => void,
     iteration: number = 0
   ): Promise<ChatMessage> {
@@ -339,7 +669,7 @@ export class AgentService {
     const MAX_TOOL_ITERATIONS = 1000;
 
     if (!response.toolCalls || response.toolCalls.length === 0) {
-      return response;
+      return await this.conformToSchema(response, history, signal, onChunk);
     }
 
     if (iteration >= MAX_TOOL_ITERATIONS) {
@@ -358,136 +688,78 @@ Would you like me to:
       return response;
     }
 
-    // Execute all tool calls - try BrainService first, fallback to local ToolRegistry
-    // IMPORTANT: Some tools MUST run locally for proper functionality
-    const useBrain = brainService.connected;
-    const LOCAL_ONLY_TOOLS = [
-      'apply_file_diff',  // Needs frontend React state
-      'run_command',      // ToolRegistry has better terminal integration (PTY, async events)
-      'run_tests',        // Uses run_command internally
-      'git_status',       // Better handled by local git service
-      'git_commit',       // Better handled by local git service
-      'git_diff',         // Better handled by local git service
-    ];
-
-    for (const toolCall of response.toolCalls) {
-      try {
-        toolCall.status = 'pending';
-        if (onChunk) onChunk({ type: 'tool_update', fullMessage: response });
-
-        let result: unknown;
-
-        // Force local execution for tools that need frontend state
-        // Also force local for MCP tools (they're registered in ToolRegistry with API callbacks)
-        const isMCPTool = toolCall.name.includes('.'); // MCP tools use format "serverName.toolName"
-        const forceLocal = LOCAL_ONLY_TOOLS.includes(toolCall.name) || isMCPTool;
+    // Independent read-only calls run concurrently when parallelTools is on;
+    // results stay on their tool calls, so the transcript keeps the model's order
+    const batches = this.config.parallelTools !== false
+      ? planToolBatches(response.toolCalls)
+      : response.toolCalls.map((toolCall) => [toolCall]);
 
-        if (useBrain && !forceLocal) {
-          // Use sidecar brain service (more reliable, no Tauri hangs)
-          // CRITICAL: Pass the user's workspace, NOT the IDE's install path
-          const workspace = getIDEState().workspace;
-          const brainResult = await brainService.executeTool({
-            tool: toolCall.name,
-            args: toolCall.arguments,
-            workspace: workspace?.path,
-          });
-          result = brainResult.data ?? { error: brainResult.error };
-
-          if (!brainResult.success) {
-            throw new Error(brainResult.error || 'Tool execution failed');
-          }
-        } else {
-          // Use local ToolRegistry (for frontend-only tools, MCP tools, or when brain not connected)
-          result = await toolRegistry.executeTool(toolCall.name, toolCall.arguments);
-        }
-
-        toolCall.result = result;
-        toolCall.status = 'success';
-        if (onChunk) onChunk({ type: 'tool_update', fullMessage: response });
-      } catch (error) {
-        toolCall.error = String(error);
-        toolCall.status = 'error';
-        if (onChunk) onChunk({ type: 'tool_update', fullMessage: response });
+    for (const batch of batches) {
+      if (batch.length === 1) {
+        await this.executeToolCall(batch[0], response, signal, onChunk);
+        continue;
       }
+      const batchStartedAt = Date.now();
+      await runWithConcurrency(batch, MAX_PARALLEL_TOOLS, (toolCall) =>
+        this.executeToolCall(toolCall, response, signal, onChunk)
+      );
+      const serialMs = batch.reduce((total, tc) => total + (tc.durationMs ?? 0), 0);
+      console.log(
+        `[AgentService] Ran ${batch.length} tools in parallel in ${Date.now() - batchStartedAt}ms (${serialMs}ms combined)`
+      );
     }
 
-    // Create a message with tool results to send back to the LLM
-    const toolResultsContent = response.toolCalls.map(tc => {
-      if (tc.status === 'success') {
-        // Special formatting for command outputs - show stdout directly, not JSON
-        if (tc.name === 'run_command' && tc.result && typeof tc.result === 'object') {
-          const result = tc.result as Record<string, unknown>;
-          const stdout = result.stdout || result.output || '';
-          const exitCode = result.exitCode ?? result.exitedWithErrors;
-          const duration = result.duration || '';
-
-          // For commands, show the actual output clearly
-          let output = `Tool run_command completed (${duration}ms):\n`;
-          if (exitCode !== undefined && exitCode !== 0 && exitCode !== false) {
-            output += `Exit status: ${exitCode}\n`;
-          }
-          output += `--- Command Output ---\n${stdout}\n--- End Output ---`;
-
-          // Larger limit for command outputs (8000 chars)
-          if (output.length > 8000) {
-            output = output.slice(0, 8000) + '\n... (output truncated, showing first 8000 chars)';
-          }
-          return output;
-        }
-
-        // For other tools, use JSON but with better truncation
-        const resultStr = JSON.stringify(tc.result, null, 2);
-        const truncated = resultStr.length > 5000
-          ? resultStr.slice(0, 5000) + '\n... (truncated, showing first 5000 chars)'
-          : resultStr;
-        return `Tool ${tc.name} executed successfully:\n${truncated}`;
-      } else {
-        return `Tool ${tc.name} failed with error: ${tc.error}`;
-      }
-    }).join('\n\n');
-
-    const toolResultsMessage: ChatMessage = {
-      id: crypto.randomUUID(),
-      role: 'user',
-      content: `Tool execution results:\n\n${toolResultsContent}\n\nAnalyze these results and continue your task. If you need more information, use additional tools. When you have enough information, present your findings clearly to the user.`,
-      timestamp: new Date(),
-    };
+    if (signal.aborted) {
+      if (onChunk) onChunk({ type: 'tool_update', fullMessage: response });
+      throw signal.reason;
+    }
 
-    // Send tool results back to LLM
+    // Send tool results back to LLM. Results travel with the tool calls and
+    // each provider sends them in its own format (see formatToolResult), so
+    // a replayed history carries them too.
     if (!this.provider) {
       return response;
     }
 
-    const tools = toolRegistry.getAllTools();
+    const tools = await toolRegistry.getEnabledTools();
+    await waitWhileAgentRunsPaused(signal);
 
     try {
       let nextResponse: ChatMessage;
+      const nextMessages = [...history, response];
 
       if (onChunk) {
         nextResponse = await this.provider.streamMessage(
-          [...history, response, toolResultsMessage],
+          nextMessages,
           tools,
-          onChunk
+          onChunk,
+          signal,
+          this.requestOptions()
         );
       } else {
         nextResponse = await this.provider.sendMessage(
-          [...history, response, toolResultsMessage],
-          tools
+          nextMessages,
+          tools,
+          signal,
+          this.requestOptions()
         );
       }
+      this.recordUsage(nextMessages, nextResponse);
 
       // If the LLM wants more tool calls, recursively handle them
       if (nextResponse.toolCalls && nextResponse.toolCalls.length > 0) {
         return await this.handleToolCalls(
           nextResponse,
-          [...history, response, toolResultsMessage],
+          nextMessages,
+          signal,
           onChunk,
           iteration + 1
         );
       }
 
-      return nextResponse;
+      return await this.conformToSchema(nextResponse, nextMessages, signal, onChunk);
     } catch (error) {
+      if (signal.aborted) throw error;
       console.error('Failed to get response after tool execution:', error);
       // Return the original response with tool results if call fails
       response.content = response.content || 'Tool execution completed. Check the results above.';
@@ -495,6 +767,230 @@ Would you like me to:
     }
   }
 
+  private requestOptions(): RequestOptions | undefined {
+    return this.config.responseSchema ? { responseSchema: this.config.responseSchema } : undefined;
+  }
+
+  /**
+   * Validate a final response against `responseSchema`, re-prompting with the
+   * errors (without tools) up to MAX_SCHEMA_RETRIES times. The parsed value
+   * and the outcome are kept in `metadata.structuredOutput`; a response that
+   * still does not conform is returned with `valid: false`.
+   *
+   * Retries are not streamed: the corrected answer replaces the streamed
+   * response (same message id) instead of being appended after it.
+   */
+  private async conformToSchema(
+    response: ChatMessage,
+    history: ChatMessage[],
+    signal: AbortSignal,
+    onChunk?: (chunk: StreamChunk) => void
+  ): Promise<ChatMessage> {
+    const schema = this.config.responseSchema;
+    if (!schema || !this.provider) return response;
+
+    const streamedId = response.id;
+    let messages = history;
+    let result = parseStructuredOutput(response.content, schema);
+    for (let attempt = 1; !result.valid && attempt <= MAX_SCHEMA_RETRIES; attempt++) {
+      console.warn(
+        `[AgentService] Response failed schema validation (attempt ${attempt}): ${result.errors.join('; ')}`
+      );
+      messages = [...messages, response, createChatMessage('user', createSchemaRetryPrompt(result.errors))];
+      const options = this.requestOptions();
+      response = this.recordUsage(
+        messages,
+        await this.provider.sendMessage(messages, [], signal, options)
+      );
+      result = parseStructuredOutput(response.content, schema);
+    }
+
+    const retried = response.id !== streamedId;
+    response = {
+      ...response,
+      id: streamedId,
+      metadata: {
+        ...response.metadata,
+        structuredOutput: { valid: result.valid, value: result.value, errors: result.errors },
+      },
+    };
+    if (onChunk && retried) onChunk({ type: 'done', fullMessage: response });
+    return response;
+  }
+
+  /**
+   * Add a provider request to the session's usage and cost, estimating the
+   * token counts when the provider reported none. Usage and cost are kept in
+   * the response's metadata. Pauses the run once a budget is used up.
+   */
+  private recordUsage(sent: ChatMessage[], response: ChatMessage): ChatMessage {
+    // Priced as the model that answered, which differs after a failover
+    const modelConfig = getModelConfig(getAgentMetadata(response)?.model ?? this.config.model);
+    if (!modelConfig) return response;
+
+    const reported = getUsage(response);
+    const usage = reported ?? {
+      promptTokens: estimateConversationTokens(sent, modelConfig.provider),
+      completionTokens: estimateMessageTokens(response, modelConfig.provider),
+    };
+    const { costUsd, exceeded } = metricsCollector.record(
+      this.config.sessionId,
+      modelConfig.provider,
+      modelConfig.model,
+      usage,
+      !reported
+    );
+    response.metadata = { ...response.metadata, usage, costUsd };
+
+    if (exceeded) {
+      activeRuns.get(this.config.sessionId)?.abort(new AgentRunAborted('budget'));
+    }
+    return response;
+  }
+
+  /**
+   * Run one tool call, recording its result or error, status and latency on
+   * the call. Tries BrainService first, falling back to the local ToolRegistry.
+   * Never throws; a cancelled run marks the call 'Cancelled'.
+   */
+  private async executeToolCall(
+    toolCall: ToolCall,
+    response: ChatMessage,
+    signal: AbortSignal,
+    onChunk?: (chunk: StreamChunk) => void
+  ): Promise<void> {
+    if (signal.aborted) {
+      // Remaining calls are skipped, not run
+      toolCall.error = 'Cancelled';
+      toolCall.status = 'error';
+      return;
+    }
+    // Checked here so calls run by the brain sidecar are covered too
+    const denied = await toolRegistry.workspacePolicyError(toolCall.name, toolCall.arguments);
+    if (denied) {
+      toolCall.status = 'error';
+      toolCall.error = denied;
+      if (onChunk) onChunk({ type: 'tool_update', fullMessage: response });
+      return;
+    }
+    if (this.config.requireToolApproval && requiresApproval(toolCall.name)) {
+      const approved = await this.awaitApproval(toolCall, response, signal, onChunk);
+      if (!approved) {
+        toolCall.status = 'error';
+        toolCall.error = signal.aborted ? 'Cancelled' : 'Rejected by user';
+        if (onChunk) onChunk({ type: 'tool_update', fullMessage: response });
+        return;
+      }
+    }
+    const startedAt = Date.now();
+    try {
+      toolCall.status = 'pending';
+      toolCall.startedAt = startedAt;
+      if (onChunk) onChunk({ type: 'tool_update', fullMessage: response });
+
+      let result: unknown;
+
+      // Force local execution for tools that need frontend state
+      // Also force local for MCP tools (they're registered in ToolRegistry with API callbacks)
+      const isMCPTool = toolCall.name.includes('.'); // MCP tools use format "serverName.toolName"
+      const forceLocal = LOCAL_ONLY_TOOLS.includes(toolCall.name) || isMCPTool;
+
+      if (brainService.connected && !forceLocal) {
+        // Use sidecar brain service (more reliable, no Tauri hangs)
+        // CRITICAL: Pass the user's workspace, NOT the IDE's install path
+        const workspace = getIDEState().workspace;
+        const brainResult = await raceAbort(
+          brainService.executeTool({
+            tool: toolCall.name,
+            args: toolCall.arguments,
+            workspace: workspace?.path,
+          }, signal),
+          signal
+        );
+        result = brainResult.data ?? { error: brainResult.error };
+
+        if (!brainResult.success) {
+          throw new Error(brainResult.error || 'Tool execution failed');
+        }
+      } else {
+        // Use local ToolRegistry (for frontend-only tools, MCP tools, or when brain not connected)
+        result = await raceAbort(
+          toolRegistry.executeTool(toolCall.name, toolCall.arguments, {
+            signal,
+            sessionId: this.config.sessionId,
+            requestApproval: () => this.awaitApproval(toolCall, response, signal, onChunk),
+          }),
+          signal
+        );
+      }
+
+      // Results travel to the provider with the tool call
+      toolCall.result = await this.redactToolOutput(result);
+      toolCall.status = 'success';
+      toolCall.durationMs = Date.now() - startedAt;
+      if (onChunk) onChunk({ type: 'tool_update', fullMessage: response });
+    } catch (error) {
+      toolCall.error = signal.aborted
+        ? 'Cancelled'
+        : await this.redactToolOutput(String(error)).catch(() => 'Tool failed (error withheld: redaction unavailable)');
+      toolCall.status = 'error';
+      toolCall.durationMs = Date.now() - startedAt;
+      if (onChunk) onChunk({ type: 'tool_update', fullMessage: response });
+    }
+  }
+
+  /**
+   * Tool output with secrets and stored API keys replaced, before it is sent
+   * to the provider. Strings are redacted as they are, other values through
+   * their JSON. Throws if the backend can't redact, so nothing unfiltered
+   * goes out.
+   */
+  private async redactToolOutput<T>(value: T): Promise<T | string> {
+    const text = typeof value === 'string' ? value : JSON.stringify(value);
+    if (text === undefined) return value;
+    const redacted = await invoke<{ text: string; redactions: unknown[] }>('agent_redact', {
+      text,
+      direction: 'prompt',
+      runId: this.config.sessionId,
+      workspaceRoot: getIDEState().workspace?.path,
+    });
+    if (redacted.redactions.length === 0) return value;
+    if (typeof value === 'string') return redacted.text;
+    try {
+      return JSON.parse(redacted.text) as T;
+    } catch {
+      // A custom pattern matched across JSON syntax
+      return redacted.text;
+    }
+  }
+
+  /**
+   * Pause before a tool call until the user approves or rejects it
+   */
+  private async awaitApproval(
+    toolCall: ToolCall,
+    response: ChatMessage,
+    signal: AbortSignal,
+    onChunk?: (chunk: StreamChunk) => void
+  ): Promise<boolean> {
+    const { runId, approved } = requestToolApproval({
+      sessionId: this.config.sessionId,
+      toolCallId: toolCall.id,
+      toolName: toolCall.name,
+      arguments: toolCall.arguments,
+    }, signal);
+
+    toolCall.status = 'pending';
+    toolCall.approval = 'requested';
+    toolCall.approvalId = runId;
+    if (onChunk) onChunk({ type: 'tool_update', fullMessage: response });
+
+    const result = await approved;
+    // A cancelled run leaves the request unanswered rather than rejected
+    toolCall.approval = result ? 'approved' : signal.aborted ? undefined : 'rejected';
+    return result;
+  }
+
   /**
    * Update the model for this service
    */
@@ -520,6 +1016,36 @@ Would you like me to:
     this.isInitialized = false;
   }
 
+  /**
+   * Turn approval mode on or off; applies from the next tool call
+   */
+  setRequireToolApproval(enabled: boolean): void {
+    this.config.requireToolApproval = enabled;
+  }
+
+  /**
+   * Set or clear (undefined) the model to fail over to
+   */
+  setFallbackModel(fallbackModel: string | undefined): void {
+    this.config.fallbackModel = fallbackModel;
+    this.isInitialized = false;
+  }
+
+  /**
+   * Set or clear (undefined) the schema final responses must conform to
+   */
+  setResponseSchema(responseSchema: JsonSchema | undefined): void {
+    this.config.responseSchema = responseSchema;
+  }
+
+  /**
+   * Set or clear (undefined) the session's budget in USD
+   */
+  setBudget(budgetUsd: number | undefined): void {
+    this.config.budgetUsd = budgetUsd;
+    metricsCollector.setSessionBudget(this.config.sessionId, budgetUsd);
+  }
+
   /**
    * Get current configuration
    */
@@ -0,0 +1,252 @@
+/**
+ * Context Manager
+ *
+ * Keeps long agent sessions inside the model's context window. Once the
+ * conversation passes SUMMARIZE_AT_PERCENT of the usable window, the older
+ * turns are condensed by the model into a summary that is appended to the
+ * system prompt, and only the recent turns are sent verbatim.
+ *
+ * The summary is cached per session and extended when the conversation grows
+ * past the threshold again, so summarization runs every so often rather than
+ * on every message. Sliding-window truncation (truncateToFitContext) is the
+ * fallback when summarizing fails or the result still doesn't fit.
+ */
+
+import { ChatMessage, ToolCall } from '@/types/chat';
+import {
+  ContextLimits,
+  estimateConversationTokens,
+  estimateTokens,
+  getContextStatus,
+  slidingWindowStart,
+  truncateToFitContext,
+} from './TokenCounter';
+
+// ===========================
+// Configuration
+// ===========================
+
+/** Summarize once the prompt uses this much of the usable window */
+const SUMMARIZE_AT_PERCENT = 75;
+
+/** Share of the usable window kept as verbatim recent turns after summarizing */
+const RECENT_WINDOW_PERCENT = 40;
+
+/** Share of the usable window the transcript sent for summarizing may use */
+const TRANSCRIPT_PERCENT = 50;
+
+/** Per-message caps in the transcript, in characters */
+const MAX_MESSAGE_CHARS = 4000;
+const MAX_TOOL_ARGS_CHARS = 300;
+const MAX_TOOL_RESULT_CHARS = 800;
+
+export const CONTEXT_SUMMARY_PROMPT = `You condense the earlier part of a conversation between a user and a coding agent so the agent can continue without the full history.
+
+Write a concise summary in Markdown covering:
+- The user's goals and any requirements or preferences they stated
+- Decisions made and approaches tried, including what failed and why
+- Files created, modified or inspected (with paths) and commands run
+- Open questions and the work still remaining
+
+Keep file paths, identifiers and error messages exact. If an existing summary is given, merge it with the new turns into a single summary. Reply with the summary only.`;
+
+// ===========================
+// Types
+// ===========================
+
+/**
+ * Sends the summarization prompt to the model and returns its reply
+ */
+export type Summarizer = (prompt: ChatMessage[]) => Promise<string>;
+
+export interface PreparedContext {
+  /** Messages to send to the provider */
+  messages: ChatMessage[];
+  /** Messages folded into the summary by this call */
+  summarizedCount: number;
+  /** Messages dropped by sliding-window truncation */
+  removedCount: number;
+}
+
+interface SessionSummary {
+  text: string;
+  /** Last conversation message the summary covers */
+  lastMessageId: string;
+}
+
+// ===========================
+// Helpers
+// ===========================
+
+function clip(text: string, maxChars: number): string {
+  return text.length > maxChars ? `${text.slice(0, maxChars)}… [truncated]` : text;
+}
+
+function formatToolOutcome(toolCall: ToolCall): string {
+  if (toolCall.error) return `error: ${clip(toolCall.error, MAX_TOOL_RESULT_CHARS)}`;
+  if (toolCall.result === undefined) return 'no result';
+  const result = typeof toolCall.result === 'string'
+    ? toolCall.result
+    : JSON.stringify(toolCall.result);
+  return clip(result ?? '', MAX_TOOL_RESULT_CHARS);
+}
+
+/**
+ * Render messages as a plain-text transcript for the summarizer
+ */
+function formatTranscript(messages: ChatMessage[]): string {
+  return messages
+    .map((msg) => {
+      const lines = [`${msg.role.toUpperCase()}: ${clip(msg.content, MAX_MESSAGE_CHARS)}`];
+      if (msg.images && msg.images.length > 0) {
+        lines.push(`  [${msg.images.length} image(s) attached]`);
+      }
+      for (const tc of msg.toolCalls ?? []) {
+        const args = clip(JSON.stringify(tc.arguments), MAX_TOOL_ARGS_CHARS);
+        lines.push(`  [tool ${tc.name}(${args}) -> ${formatToolOutcome(tc)}]`);
+      }
+      return lines.join('\n');
+    })
+    .join('\n\n');
+}
+
+/**
+ * Append the summary to the system prompt, or add it as a system message when
+ * there is none. Providers read the system prompt from the first system
+ * message only, so a separate one would be ignored.
+ */
+function injectSummary(systemMessages: ChatMessage[], summary: string): ChatMessage[] {
+  const section = `\n\n## Summary of Earlier Conversation\n\nOlder messages were condensed to fit the context window:\n\n${summary}`;
+  if (systemMessages.length === 0) {
+    return [{
+      id: 'context-summary',
+      role: 'system',
+      content: section.trimStart(),
+      timestamp: new Date(),
+      metadata: { contextSummary: true },
+    }];
+  }
+  const [first, ...rest] = systemMessages;
+  return [{ ...first, content: first.content + section }, ...rest];
+}
+
+// ===========================
+// Context Manager
+// ===========================
+
+export class ContextManager {
+  private summaries = new Map<string, SessionSummary>();
+
+  /**
+   * Fit a session's messages into the context window: summarize older turns
+   * when past the threshold, then truncate if still at the limit.
+   * The system prompt is always kept.
+   */
+  async prepare(
+    sessionId: string,
+    messages: ChatMessage[],
+    limits: ContextLimits,
+    summarize: Summarizer,
+    signal?: AbortSignal
+  ): Promise<PreparedContext> {
+    const { provider } = limits;
+    const systemMessages = messages.filter((m) => m.role === 'system');
+    const conversation = messages.filter((m) => m.role !== 'system');
+    const usable = limits.contextWindow - limits.maxOutputTokens;
+
+    // A summary of messages no longer in the history (cleared or edited) is stale
+    let summary = this.summaries.get(sessionId);
+    let coveredUntil = 0;
+    if (summary) {
+      const index = conversation.findIndex((m) => m.id === summary!.lastMessageId);
+      if (index === -1) {
+        this.summaries.delete(sessionId);
+        summary = undefined;
+      } else {
+        coveredUntil = index + 1;
+      }
+    }
+
+    let summarizedCount = 0;
+    const pending = conversation.slice(coveredUntil);
+    const promptTokens = estimateConversationTokens(systemMessages, provider)
+      + (summary ? estimateTokens(summary.text, provider) : 0)
+      + estimateConversationTokens(pending, provider);
+
+    if (promptTokens > usable * (SUMMARIZE_AT_PERCENT / 100)) {
+      const recentStart = coveredUntil
+        + slidingWindowStart(pending, usable * (RECENT_WINDOW_PERCENT / 100), provider);
+      const older = conversation.slice(coveredUntil, recentStart);
+
+      if (older.length > 0) {
+        try {
+          const text = (await summarize(this.summaryPrompt(older, summary?.text, limits))).trim();
+          if (text) {
+            summary = { text, lastMessageId: older[older.length - 1].id };
+            this.summaries.set(sessionId, summary);
+            coveredUntil = recentStart;
+            summarizedCount = older.length;
+            console.log(`[ContextManager] Summarized ${older.length} message(s) for session ${sessionId}`);
+          }
+        } catch (error) {
+          if (signal?.aborted) throw error;
+          console.warn('[ContextManager] Summarization failed, falling back to truncation:', error);
+        }
+      }
+    }
+
+    let prepared = [
+      ...(summary ? injectSummary(systemMessages, summary.text) : systemMessages),
+      ...conversation.slice(coveredUntil),
+    ];
+
+    let removedCount = 0;
+    if (getContextStatus(prepared, limits).isAtLimit) {
+      const truncated = truncateToFitContext(prepared, limits);
+      prepared = truncated.messages;
+      removedCount = truncated.removedCount;
+    }
+
+    return { messages: prepared, summarizedCount, removedCount };
+  }
+
+  /**
+   * The cached summary of a session, if its history has been summarized
+   */
+  getSummary(sessionId: string): string | undefined {
+    return this.summaries.get(sessionId)?.text;
+  }
+
+  /**
+   * Forget a session's summary, e.g. when the session is deleted
+   */
+  clear(sessionId: string): void {
+    this.summaries.delete(sessionId);
+  }
+
+  private summaryPrompt(
+    older: ChatMessage[],
+    previousSummary: string | undefined,
+    limits: ContextLimits
+  ): ChatMessage[] {
+    // Keep the end of an oversized transcript: the newest turns matter most
+    const maxTranscriptTokens = (limits.contextWindow - limits.maxOutputTokens) * (TRANSCRIPT_PERCENT / 100);
+    let transcript = formatTranscript(older);
+    const overBy = estimateTokens(transcript, limits.provider) - maxTranscriptTokens;
+    if (overBy > 0) {
+      const keepChars = Math.floor(transcript.length * (maxTranscriptTokens / (maxTranscriptTokens + overBy)));
+      transcript = `[…earlier turns omitted]\n\n${transcript.slice(transcript.length - keepChars)}`;
+    }
+
+    const content = previousSummary
+      ? `Existing summary:\n\n${previousSummary}\n\nNew turns to merge in:\n\n${transcript}`
+      : `Conversation to summarize:\n\n${transcript}`;
+
+    return [
+      { id: crypto.randomUUID(), role: 'system', content: CONTEXT_SUMMARY_PROMPT, timestamp: new Date() },
+      { id: crypto.randomUUID(), role: 'user', content, timestamp: new Date() },
+    ];
+  }
+}
+
+export const contextManager = new ContextManager();
@@ -0,0 +1,269 @@
+/**
+ * Metrics Collector
+ *
+ * Token usage and cost of agent runs, per session and across all sessions.
+ * Every provider request is priced from the model's rates for prompt and
+ * completion tokens, using the counts the provider reports or, when it
+ * reports none, estimates from TokenCounter.
+ *
+ * Budgets cap the spend of a session or of all sessions together. Once one
+ * is reached the session is paused: AgentService stops the run, refuses new
+ * ones until resume(), and an `agent/budget-exceeded` event is emitted.
+ */
+
+import { emit } from '@tauri-apps/api/event';
+import type { TokenUsage } from './providers/base';
+
+// ===========================
+// Pricing
+// ===========================
+
+export const BUDGET_EXCEEDED_EVENT = 'agent/budget-exceeded';
+
+/**
+ * USD per million tokens
+ */
+export interface ModelPricing {
+  input: number;
+  output: number;
+  /** Prompt tokens served from the provider's cache; billed as input when unset */
+  cachedInput?: number;
+}
+
+/**
+ * Rates by provider, then by the provider's model name
+ */
+export const MODEL_PRICING: Record<string, Record<string, ModelPricing>> = {
+  gemini: {
+    'gemini-2.5-flash-lite': { input: 0.1, output: 0.4, cachedInput: 0.025 },
+    'gemini-2.5-flash': { input: 0.3, output: 2.5, cachedInput: 0.075 },
+    'gemini-2.5-pro': { input: 1.25, output: 10, cachedInput: 0.31 },
+    'gemini-3-flash-preview': { input: 0.5, output: 3, cachedInput: 0.125 },
+    'gemini-3-pro-preview': { input: 2, output: 12, cachedInput: 0.5 },
+  },
+  groq: {
+    'llama-3.3-70b-versatile': { input: 0.59, output: 0.79 },
+    'moonshotai/kimi-k2-instruct-0905': { input: 1, output: 3 },
+  },
+  cerebras: {
+    'zai-glm-4.6': { input: 2.25, output: 2.75 },
+  },
+  openai: {
+    'gpt-4.1': { input: 2, output: 8, cachedInput: 0.5 },
+    'gpt-4.1-mini': { input: 0.4, output: 1.6, cachedInput: 0.1 },
+    'gpt-4.1-nano': { input: 0.1, output: 0.4, cachedInput: 0.025 },
+    'gpt-4o': { input: 2.5, output: 10, cachedInput: 1.25 },
+    'gpt-4o-mini': { input: 0.15, output: 0.6, cachedInput: 0.075 },
+  },
+};
+
+/**
+ * Rates for models missing from the table, on the expensive side so budgets
+ * trip early rather than late
+ */
+const FALLBACK_PRICING: Record<string, ModelPricing> = {
+  gemini: { input: 2, output: 12 },
+  groq: { input: 1, output: 3 },
+  cerebras: { input: 2.25, output: 2.75 },
+  openai: { input: 2.5, output: 10 },
+};
+
+const DEFAULT_PRICING: ModelPricing = { input: 3, output: 15 };
+
+const warnedUnpriced = new Set<string>();
+
+export function getModelPricing(provider: string, model: string): ModelPricing {
+  const pricing = MODEL_PRICING[provider]?.[model];
+  if (pricing) return pricing;
+
+  const key = `${provider}/${model}`;
+  if (!warnedUnpriced.has(key)) {
+    warnedUnpriced.add(key);
+    console.warn(`[MetricsCollector] No pricing for ${key}, using fallback rates`);
+  }
+  return FALLBACK_PRICING[provider] ?? DEFAULT_PRICING;
+}
+
+/**
+ * Cost of one request in USD
+ */
+export function calculateCost(provider: string, model: string, usage: TokenUsage): number {
+  const pricing = getModelPricing(provider, model);
+  const cached = Math.min(usage.cachedTokens ?? 0, usage.promptTokens);
+  const uncached = usage.promptTokens - cached;
+  return (
+    uncached * pricing.input
+    + cached * (pricing.cachedInput ?? pricing.input)
+    + usage.completionTokens * pricing.output
+  ) / 1_000_000;
+}
+
+// ===========================
+// Types
+// ===========================
+
+export interface UsageTotals {
+  promptTokens: number;
+  completionTokens: number;
+  costUsd: number;
+  requests: number;
+  /** Requests whose token counts were estimated because the provider reported none */
+  estimatedRequests: number;
+}
+
+export interface BudgetExceeded {
+  sessionId: string;
+  scope: 'session' | 'global';
+  budgetUsd: number;
+  /** Spent against the budget, i.e. since the last resume */
+  spentUsd: number;
+}
+
+export interface RecordedUsage {
+  costUsd: number;
+  /** Set when this request used up a budget */
+  exceeded: BudgetExceeded | null;
+}
+
+interface SessionMetrics {
+  totals: UsageTotals;
+  budgetUsd?: number;
+  /** Session cost when the budget was last reset by resume() */
+  budgetBaseUsd: number;
+  /** Whether the event for the current overrun was emitted */
+  notified: boolean;
+}
+
+function emptyTotals(): UsageTotals {
+  return { promptTokens: 0, completionTokens: 0, costUsd: 0, requests: 0, estimatedRequests: 0 };
+}
+
+function addUsage(totals: UsageTotals, usage: TokenUsage, costUsd: number, estimated: boolean): void {
+  totals.promptTokens += usage.promptTokens;
+  totals.completionTokens += usage.completionTokens;
+  totals.costUsd += costUsd;
+  totals.requests += 1;
+  if (estimated) totals.estimatedRequests += 1;
+}
+
+// ===========================
+// Metrics Collector
+// ===========================
+
+export class MetricsCollector {
+  private sessions = new Map<string, SessionMetrics>();
+  private global = emptyTotals();
+  private globalBudgetUsd?: number;
+  private globalBudgetBaseUsd = 0;
+
+  private session(sessionId: string): SessionMetrics {
+    let metrics = this.sessions.get(sessionId);
+    if (!metrics) {
+      metrics = { totals: emptyTotals(), budgetBaseUsd: 0, notified: false };
+      this.sessions.set(sessionId, metrics);
+    }
+    return metrics;
+  }
+
+  /**
+   * Add one provider request to the session and global totals
+   */
+  record(
+    sessionId: string,
+    provider: string,
+    model: string,
+    usage: TokenUsage,
+    estimated = false
+  ): RecordedUsage {
+    const costUsd = calculateCost(provider, model, usage);
+    const metrics = this.session(sessionId);
+    addUsage(metrics.totals, usage, costUsd, estimated);
+    addUsage(this.global, usage, costUsd, estimated);
+
+    const exceeded = this.checkBudget(sessionId);
+    if (exceeded && !metrics.notified) {
+      metrics.notified = true;
+      console.warn(
+        `[MetricsCollector] ${exceeded.scope} budget of $${exceeded.budgetUsd.toFixed(2)} reached `
+        + `($${exceeded.spentUsd.toFixed(4)} spent), pausing session ${sessionId}`
+      );
+      emit(BUDGET_EXCEEDED_EVENT, exceeded).catch((err) => {
+        console.warn('[MetricsCollector] Failed to emit budget event:', err);
+      });
+    }
+    return { costUsd, exceeded };
+  }
+
+  /**
+   * The budget a session has used up, if any; the session budget is checked first
+   */
+  checkBudget(sessionId: string): BudgetExceeded | null {
+    const metrics = this.sessions.get(sessionId);
+    if (metrics?.budgetUsd !== undefined) {
+      const spentUsd = metrics.totals.costUsd - metrics.budgetBaseUsd;
+      if (spentUsd >= metrics.budgetUsd) {
+        return { sessionId, scope: 'session', budgetUsd: metrics.budgetUsd, spentUsd };
+      }
+    }
+    if (this.globalBudgetUsd !== undefined) {
+      const spentUsd = this.global.costUsd - this.globalBudgetBaseUsd;
+      if (spentUsd >= this.globalBudgetUsd) {
+        return { sessionId, scope: 'global', budgetUsd: this.globalBudgetUsd, spentUsd };
+      }
+    }
+    return null;
+  }
+
+  /**
+   * Let a paused session continue: the exceeded budget starts over from the
+   * current spend, allowing another budget's worth
+   */
+  resume(sessionId: string): void {
+    const exceeded = this.checkBudget(sessionId);
+    const metrics = this.session(sessionId);
+    if (exceeded?.scope === 'session') {
+      metrics.budgetBaseUsd = metrics.totals.costUsd;
+    } else if (exceeded?.scope === 'global') {
+      this.globalBudgetBaseUsd = this.global.costUsd;
+    }
+    metrics.notified = false;
+  }
+
+  /**
+   * Set or clear (undefined) a session's budget in USD
+   */
+  setSessionBudget(sessionId: string, budgetUsd: number | undefined): void {
+    const metrics = this.session(sessionId);
+    metrics.budgetUsd = budgetUsd;
+    metrics.notified = false;
+  }
+
+  /**
+   * Set or clear (undefined) the budget shared by all sessions, in USD
+   */
+  setGlobalBudget(budgetUsd: number | undefined): void {
+    this.globalBudgetUsd = budgetUsd;
+    this.globalBudgetBaseUsd = 0;
+    for (const metrics of this.sessions.values()) {
+      metrics.notified = false;
+    }
+  }
+
+  getSessionUsage(sessionId: string): UsageTotals {
+    return { ...(this.sessions.get(sessionId)?.totals ?? emptyTotals()) };
+  }
+
+  getGlobalUsage(): UsageTotals {
+    return { ...this.global };
+  }
+
+  /**
+   * Forget a session's totals and budget, e.g. when the session is deleted.
+   * Global totals keep its spend.
+   */
+  clear(sessionId: string): void {
+    this.sessions.delete(sessionId);
+  }
+}
+
+export const metricsCollector = new MetricsCollector();
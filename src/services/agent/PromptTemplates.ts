@@ -0,0 +1,100 @@
+/**
+ * Prompt Templates
+ *
+ * Named system-prompt templates (agent personas) kept by the backend under
+ * `~/.rainy-aether/prompt-templates`. Content may use `{{workspaceName}}`,
+ * `{{workspaceRoot}}`, `{{language}}` and `{{openFile}}`, filled in from the
+ * IDE when a session is created from the template (AgentConfig.template).
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { getIDEState } from '@/stores/ideStore';
+import { getLanguageFromFilename } from '@/services/monacoConfig';
+import { getLanguageDisplayName } from '@/utils/languageMap';
+
+// ===========================
+// Types
+// ===========================
+
+export interface PromptTemplate {
+  name: string;
+  description?: string;
+  /** System prompt text with `{{variable}}` placeholders */
+  content: string;
+  /** Increases with every save */
+  version: number;
+  createdAt: number;
+  updatedAt: number;
+  /** Variables the content references */
+  variables: string[];
+}
+
+export interface PromptTemplateInput {
+  name: string;
+  description?: string;
+  content: string;
+  /** Version the edit started from; the save fails if the template changed since */
+  baseVersion?: number;
+}
+
+// ===========================
+// CRUD
+// ===========================
+
+export function listPromptTemplates(): Promise<PromptTemplate[]> {
+  return invoke<PromptTemplate[]>('list_prompt_templates');
+}
+
+export function getPromptTemplate(name: string): Promise<PromptTemplate | null> {
+  return invoke<PromptTemplate | null>('get_prompt_template', { name });
+}
+
+export function savePromptTemplate(template: PromptTemplateInput): Promise<PromptTemplate> {
+  return invoke<PromptTemplate>('save_prompt_template', { template });
+}
+
+/**
+ * Returns false if the template did not exist
+ */
+export function deletePromptTemplate(name: string): Promise<boolean> {
+  return invoke<boolean>('delete_prompt_template', { name });
+}
+
+// ===========================
+// Resolution
+// ===========================
+
+/**
+ * Template variables from the current IDE state; unavailable ones are omitted
+ * and stay as placeholders
+ */
+export function getTemplateVariables(): Record<string, string> {
+  const { workspace, openFiles, activeFileId } = getIDEState();
+  const variables: Record<string, string> = {};
+
+  if (workspace) {
+    variables.workspaceName = workspace.name;
+    variables.workspaceRoot = workspace.path;
+  }
+
+  const activeFile = openFiles.find((f) => f.id === activeFileId);
+  if (activeFile) {
+    const root = workspace?.path.replace(/[\\/]+$/, '');
+    variables.openFile = root && activeFile.path.startsWith(root)
+      ? activeFile.path.slice(root.length).replace(/^[\\/]+/, '')
+      : activeFile.path;
+    variables.language = getLanguageDisplayName(getLanguageFromFilename(activeFile.name));
+  }
+
+  return variables;
+}
+
+/**
+ * The system prompt of a template with its variables filled in
+ */
+export function resolvePromptTemplate(
+  name: string,
+  variables: Record<string, string> = getTemplateVariables()
+): Promise<string> {
+  return invoke<string>('render_prompt_template', { name, variables });
+}
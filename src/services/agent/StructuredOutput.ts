@@ -0,0 +1,210 @@
+/**
+ * Structured Output
+ *
+ * JSON responses constrained by a schema (AgentConfig.responseSchema).
+ * Providers with a JSON mode are asked to enforce the schema, but not all of
+ * them can (Groq only guarantees JSON, Gemini drops it while tools are on),
+ * so every final response is also validated here and the agent re-prompts
+ * with the errors when it does not conform.
+ *
+ * Validation covers the JSON Schema keywords models are given in practice:
+ * type, enum, const, properties, required, additionalProperties, items,
+ * minimum/maximum, minLength/maxLength, pattern, minItems/maxItems, anyOf,
+ * oneOf and allOf. `$ref` is not resolved.
+ */
+
+import type { JsonSchema } from './providers/base';
+
+// ===========================
+// Types
+// ===========================
+
+export interface StructuredOutputResult {
+  valid: boolean;
+  /** Parsed JSON; undefined when the response is not JSON */
+  value?: unknown;
+  /** Validation errors, each prefixed with the JSON path it refers to */
+  errors: string[];
+}
+
+// ===========================
+// Parsing
+// ===========================
+
+/**
+ * The JSON text of a response: models often wrap it in a ``` fence or add a
+ * sentence before it despite being told not to
+ */
+export function extractJson(content: string): string {
+  const trimmed = content.trim();
+  const fenced = trimmed.match(/```(?:json)?\s*\n([\s\S]*?)\n?```/i);
+  if (fenced) return fenced[1].trim();
+
+  const start = trimmed.search(/[[{]/);
+  if (start <= 0) return trimmed;
+  const end = Math.max(trimmed.lastIndexOf('}'), trimmed.lastIndexOf(']'));
+  return end > start ? trimmed.slice(start, end + 1) : trimmed;
+}
+
+/**
+ * Parse a response and validate it against the schema
+ */
+export function parseStructuredOutput(content: string, schema: JsonSchema): StructuredOutputResult {
+  let value: unknown;
+  try {
+    value = JSON.parse(extractJson(content));
+  } catch (error) {
+    const reason = error instanceof Error ? error.message : String(error);
+    return { valid: false, errors: [`$: response is not valid JSON (${reason})`] };
+  }
+  const errors = validateJson(value, schema);
+  return { valid: errors.length === 0, value, errors };
+}
+
+// ===========================
+// Validation
+// ===========================
+
+function typeOf(value: unknown): string {
+  if (value === null) return 'null';
+  if (Array.isArray(value)) return 'array';
+  if (typeof value === 'number' && Number.isInteger(value)) return 'integer';
+  return typeof value;
+}
+
+function matchesType(value: unknown, type: string): boolean {
+  const actual = typeOf(value);
+  return actual === type || (type === 'number' && actual === 'integer');
+}
+
+function deepEqual(a: unknown, b: unknown): boolean {
+  return JSON.stringify(a) === JSON.stringify(b);
+}
+
+/**
+ * Check a value against a schema; returns the errors, empty when it conforms
+ */
+export function validateJson(value: unknown, schema: JsonSchema, path = '$'): string[] {
+  const errors: string[] = [];
+  const s = schema as Record<string, any>;
+
+  if (s.type !== undefined) {
+    const types: string[] = Array.isArray(s.type) ? s.type : [s.type];
+    if (!types.some((type) => matchesType(value, type))) {
+      // Nothing below applies to a value of the wrong type
+      return [`${path}: expected ${types.join(' or ')}, got ${typeOf(value)}`];
+    }
+  }
+
+  if (s.enum !== undefined && !s.enum.some((option: unknown) => deepEqual(option, value))) {
+    errors.push(`${path}: must be one of ${JSON.stringify(s.enum)}`);
+  }
+  if (s.const !== undefined && !deepEqual(s.const, value)) {
+    errors.push(`${path}: must be ${JSON.stringify(s.const)}`);
+  }
+
+  if (typeof value === 'string') {
+    if (s.minLength !== undefined && value.length < s.minLength) {
+      errors.push(`${path}: must be at least ${s.minLength} characters`);
+    }
+    if (s.maxLength !== undefined && value.length > s.maxLength) {
+      errors.push(`${path}: must be at most ${s.maxLength} characters`);
+    }
+    if (s.pattern !== undefined) {
+      let pattern: RegExp | undefined;
+      try {
+        pattern = new RegExp(s.pattern);
+      } catch (error) {
+        const reason = error instanceof Error ? error.message : String(error);
+        errors.push(`${path}: schema pattern /${s.pattern}/ is not a valid regular expression (${reason})`);
+      }
+      if (pattern && !pattern.test(value)) {
+        errors.push(`${path}: must match /${s.pattern}/`);
+      }
+    }
+  }
+
+  if (typeof value === 'number') {
+    if (s.minimum !== undefined && value < s.minimum) {
+      errors.push(`${path}: must be >= ${s.minimum}`);
+    }
+    if (s.maximum !== undefined && value > s.maximum) {
+      errors.push(`${path}: must be <= ${s.maximum}`);
+    }
+  }
+
+  if (Array.isArray(value)) {
+    if (s.minItems !== undefined && value.length < s.minItems) {
+      errors.push(`${path}: must have at least ${s.minItems} items`);
+    }
+    if (s.maxItems !== undefined && value.length > s.maxItems) {
+      errors.push(`${path}: must have at most ${s.maxItems} items`);
+    }
+    if (s.items && typeof s.items === 'object') {
+      value.forEach((item, i) => errors.push(...validateJson(item, s.items, `${path}[${i}]`)));
+    }
+  }
+
+  if (typeOf(value) === 'object') {
+    const obj = value as Record<string, unknown>;
+    const properties: Record<string, JsonSchema> = s.properties ?? {};
+    for (const key of s.required ?? []) {
+      if (!(key in obj)) errors.push(`${path}: missing required property "${key}"`);
+    }
+    for (const [key, child] of Object.entries(obj)) {
+      const childPath = `${path}.${key}`;
+      if (properties[key]) {
+        errors.push(...validateJson(child, properties[key], childPath));
+      } else if (s.additionalProperties === false) {
+        errors.push(`${childPath}: unexpected property`);
+      } else if (s.additionalProperties && typeof s.additionalProperties === 'object') {
+        errors.push(...validateJson(child, s.additionalProperties, childPath));
+      }
+    }
+  }
+
+  for (const sub of s.allOf ?? []) {
+    errors.push(...validateJson(value, sub, path));
+  }
+  if (s.anyOf && !s.anyOf.some((sub: JsonSchema) => validateJson(value, sub, path).length === 0)) {
+    errors.push(`${path}: does not match any of the allowed schemas`);
+  }
+  if (s.oneOf) {
+    const matches = s.oneOf.filter((sub: JsonSchema) => validateJson(value, sub, path).length === 0).length;
+    if (matches !== 1) {
+      errors.push(`${path}: must match exactly one of the allowed schemas (matched ${matches})`);
+    }
+  }
+
+  return errors;
+}
+
+// ===========================
+// Prompts
+// ===========================
+
+/**
+ * System prompt section telling the model the shape of its final answer
+ */
+export function createSchemaInstructions(schema: JsonSchema): string {
+  return `
+
+## Response Format
+
+When you give your final answer (after any tool calls), reply with a single JSON value that conforms to this JSON Schema, and nothing else: no prose and no code fences.
+
+\`\`\`json
+${JSON.stringify(schema, null, 2)}
+\`\`\`
+`;
+}
+
+/**
+ * Follow-up asking the model to fix a response that failed validation
+ */
+export function createSchemaRetryPrompt(errors: string[]): string {
+  return `Your previous response does not conform to the required JSON Schema:
+${errors.map((e) => `- ${e}`).join('\n')}
+
+Reply again with only the corrected JSON.`;
+}
@@ -2,7 +2,8 @@
  * Token Counter Service
  * 
  * Estimates token counts for messages and manages context limits.
- * Uses approximation of ~4 characters per token (industry standard estimate).
+ * Uses approximation of ~4 characters per token (industry standard estimate),
+ * adjusted per provider when the provider is known.
  */
 
 import { ChatMessage } from '@/types/chat';
@@ -15,26 +16,40 @@ import { ChatMessage } from '@/types/chat';
  * Approximate characters per token
  * This is a rough estimate - actual tokenization varies by model
  * GPT-like: ~4 chars/token, Gemini: ~3.5 chars/token
- * We use 4 as a conservative estimate
+ * We use 4 as a conservative estimate for unknown providers
  */
 const CHARS_PER_TOKEN = 4;
 
+/**
+ * Per-provider characters per token, from the providers' tokenizers on
+ * typical code and prose. Lower means more tokens for the same text.
+ */
+const CHARS_PER_TOKEN_BY_PROVIDER: Record<string, number> = {
+    gemini: 3.5,
+    groq: 3.7,   // Llama / Qwen tokenizers
+    openai: 4,
+};
+
+function charsPerToken(provider?: string): number {
+    return (provider && CHARS_PER_TOKEN_BY_PROVIDER[provider]) || CHARS_PER_TOKEN;
+}
+
 /**
  * Estimate token count for a string
  */
-export function estimateTokens(text: string): number {
+export function estimateTokens(text: string, provider?: string): number {
     if (!text) return 0;
-    return Math.ceil(text.length / CHARS_PER_TOKEN);
+    return Math.ceil(text.length / charsPerToken(provider));
 }
 
 /**
  * Estimate token count for a message (includes role overhead)
  */
-export function estimateMessageTokens(message: ChatMessage): number {
+export function estimateMessageTokens(message: ChatMessage, provider?: string): number {
     let tokens = 0;
 
     // Content tokens
-    tokens += estimateTokens(message.content);
+    tokens += estimateTokens(message.content, provider);
 
     // Role overhead (~4 tokens for role marker)
     tokens += 4;
@@ -43,20 +58,20 @@ export function estimateMessageTokens(message: ChatMessage): number {
     if (message.toolCalls) {
         for (const tc of message.toolCalls) {
             // Tool name + JSON args
-            tokens += estimateTokens(tc.name);
-            tokens += estimateTokens(JSON.stringify(tc.arguments));
+            tokens += estimateTokens(tc.name, provider);
+            tokens += estimateTokens(JSON.stringify(tc.arguments), provider);
             tokens += 10; // Structure overhead
 
             // Tool result if present
             if (tc.result) {
-                tokens += estimateTokens(JSON.stringify(tc.result));
+                tokens += estimateTokens(JSON.stringify(tc.result), provider);
             }
         }
     }
 
     // Thoughts overhead (for thinking models)
     if (message.thoughts) {
-        tokens += estimateTokens(message.thoughts);
+        tokens += estimateTokens(message.thoughts, provider);
     }
 
     return tokens;
@@ -65,8 +80,8 @@ export function estimateMessageTokens(message: ChatMessage): number {
 /**
  * Estimate total tokens for a conversation
  */
-export function estimateConversationTokens(messages: ChatMessage[]): number {
-    return messages.reduce((total, msg) => total + estimateMessageTokens(msg), 0);
+export function estimateConversationTokens(messages: ChatMessage[], provider?: string): number {
+    return messages.reduce((total, msg) => total + estimateMessageTokens(msg, provider), 0);
 }
 
 // ===========================
@@ -76,6 +91,8 @@ export function estimateConversationTokens(messages: ChatMessage[]): number {
 export interface ContextLimits {
     contextWindow: number;
     maxOutputTokens: number;
+    /** Provider ID, for its token estimate (see CHARS_PER_TOKEN_BY_PROVIDER) */
+    provider?: string;
 }
 
 export interface ContextStatus {
@@ -104,10 +121,10 @@ export function getContextStatus(
 ): ContextStatus {
     // For display: only count user and assistant messages (not system prompt)
     const displayMessages = messages.filter(m => m.role !== 'system');
-    const displayUsedTokens = estimateConversationTokens(displayMessages);
+    const displayUsedTokens = estimateConversationTokens(displayMessages, limits.provider);
 
     // For internal checks: include everything and reserve output space
-    const totalUsedTokens = estimateConversationTokens(messages);
+    const totalUsedTokens = estimateConversationTokens(messages, limits.provider);
     const effectiveMax = limits.contextWindow - limits.maxOutputTokens;
     const internalPercentUsed = (totalUsedTokens / effectiveMax) * 100;
 
@@ -124,62 +141,74 @@ export function getContextStatus(
     };
 }
 
+/**
+ * Index where the sliding window of recent messages begins: the newest
+ * messages that fit in `budget` tokens. The window starts on a user turn, so
+ * it never opens with a reply or tool output whose request was cut off, and
+ * always includes the latest message.
+ */
+export function slidingWindowStart(
+    messages: ChatMessage[],
+    budget: number,
+    provider?: string
+): number {
+    let start = messages.length;
+    let usedTokens = 0;
+
+    // Iterate from most recent to oldest
+    for (let i = messages.length - 1; i >= 0; i--) {
+        const msgTokens = estimateMessageTokens(messages[i], provider);
+        if (usedTokens + msgTokens > budget && start < messages.length) {
+            // We've hit the limit - stop adding messages
+            break;
+        }
+        usedTokens += msgTokens;
+        start = i;
+    }
+
+    while (start < messages.length - 1 && messages[start].role !== 'user') {
+        start++;
+    }
+    return start;
+}
+
 /**
  * Truncate messages to fit within context limit while preserving:
- * 1. System prompt (always kept)
- * 2. Most recent messages (prioritized)
- * 3. User's original question if possible
+ * 1. System messages (always kept, including an injected conversation summary)
+ * 2. Most recent messages (prioritized), starting at a user turn
+ * 3. The latest message, even if the system prompt alone is over budget
  */
 export function truncateToFitContext(
     messages: ChatMessage[],
     limits: ContextLimits,
     targetPercent: number = 70 // Target 70% usage after truncation
 ): { messages: ChatMessage[]; truncated: boolean; removedCount: number } {
-    const systemMessage = messages.find(m => m.role === 'system');
+    const systemMessages = messages.filter(m => m.role === 'system');
     const conversationMessages = messages.filter(m => m.role !== 'system');
 
     // Reserve space for output
     const targetTokens = Math.floor((limits.contextWindow - limits.maxOutputTokens) * (targetPercent / 100));
 
     // Calculate system message tokens
-    const systemTokens = systemMessage ? estimateMessageTokens(systemMessage) : 0;
+    const systemTokens = estimateConversationTokens(systemMessages, limits.provider);
     const availableForConversation = targetTokens - systemTokens;
 
     // If we're already under limit, return as-is
-    const currentConversationTokens = estimateConversationTokens(conversationMessages);
+    const currentConversationTokens = estimateConversationTokens(conversationMessages, limits.provider);
     if (currentConversationTokens <= availableForConversation) {
         return { messages, truncated: false, removedCount: 0 };
     }
 
-    // Keep recent messages from the end
-    const keptMessages: ChatMessage[] = [];
-    let keptTokens = 0;
-
-    // Iterate from most recent to oldest
-    for (let i = conversationMessages.length - 1; i >= 0; i--) {
-        const msg = conversationMessages[i];
-        const msgTokens = estimateMessageTokens(msg);
-
-        if (keptTokens + msgTokens <= availableForConversation) {
-            keptMessages.unshift(msg);
-            keptTokens += msgTokens;
-        } else {
-            // We've hit the limit - stop adding messages
-            break;
-        }
-    }
+    const start = slidingWindowStart(conversationMessages, availableForConversation, limits.provider);
+    const keptMessages = conversationMessages.slice(start);
 
     // Reconstruct messages array
-    const result: ChatMessage[] = [];
-    if (systemMessage) result.push(systemMessage);
-    result.push(...keptMessages);
-
-    const removedCount = conversationMessages.length - keptMessages.length;
+    const result: ChatMessage[] = [...systemMessages, ...keptMessages];
 
     return {
         messages: result,
-        truncated: removedCount > 0,
-        removedCount,
+        truncated: start > 0,
+        removedCount: start,
     };
 }
 
@@ -0,0 +1,137 @@
+/**
+ * Tool Approval
+ *
+ * Optional approval step for tools that change the workspace. When a session
+ * has approval mode on, the agent pauses before each such tool, emits an
+ * `agent/tool-approval-request` event with the arguments, and waits for
+ * approveTool(runId, approved) before executing or skipping the call.
+ *
+ * MCP tools are not covered here; their servers have their own auto-approve
+ * settings. apply_file_diff is already reviewed in the editor.
+ */
+
+import { emit } from '@tauri-apps/api/event';
+
+// ===========================
+// Configuration
+// ===========================
+
+export const TOOL_APPROVAL_REQUEST_EVENT = 'agent/tool-approval-request';
+export const TOOL_APPROVAL_RESOLVED_EVENT = 'agent/tool-approval-resolved';
+
+/**
+ * Tools that write, edit or delete files, or run commands
+ */
+export const APPROVAL_REQUIRED_TOOLS = new Set([
+  'write_file',
+  'create_file',
+  'edit_file',
+  'smart_edit',
+  'format_file',
+  'delete_file',
+  'run_command',
+  'run_tests',
+  'verify_changes',
+  'git_commit',
+]);
+
+export function requiresApproval(toolName: string): boolean {
+  return APPROVAL_REQUIRED_TOOLS.has(toolName);
+}
+
+// ===========================
+// Types
+// ===========================
+
+export interface ToolApprovalRequest {
+  /** ID to answer the request with */
+  runId: string;
+  sessionId: string;
+  toolCallId: string;
+  toolName: string;
+  arguments: Record<string, unknown>;
+  requestedAt: number;
+}
+
+export interface ToolApprovalResolution {
+  runId: string;
+  sessionId: string;
+  toolCallId: string;
+  approved: boolean;
+}
+
+interface PendingApproval {
+  request: ToolApprovalRequest;
+  resolve: (approved: boolean) => void;
+}
+
+// ===========================
+// Pending Approvals
+// ===========================
+
+const pendingApprovals = new Map<string, PendingApproval>();
+
+function settle(runId: string, approved: boolean): boolean {
+  const pending = pendingApprovals.get(runId);
+  if (!pending) return false;
+  pendingApprovals.delete(runId);
+  pending.resolve(approved);
+
+  const { sessionId, toolCallId } = pending.request;
+  const resolution: ToolApprovalResolution = { runId, sessionId, toolCallId, approved };
+  emit(TOOL_APPROVAL_RESOLVED_EVENT, resolution).catch((err) => {
+    console.warn('[ToolApproval] Failed to emit resolution:', err);
+  });
+  return true;
+}
+
+/**
+ * Ask the user to approve a tool call. Resolves with their answer, or with
+ * false when `signal` aborts (the run was cancelled or timed out).
+ */
+export function requestToolApproval(
+  request: Omit<ToolApprovalRequest, 'runId' | 'requestedAt'>,
+  signal?: AbortSignal
+): { runId: string; approved: Promise<boolean> } {
+  const runId = crypto.randomUUID();
+  const fullRequest: ToolApprovalRequest = { ...request, runId, requestedAt: Date.now() };
+
+  const approved = new Promise<boolean>((resolve) => {
+    if (signal?.aborted) {
+      resolve(false);
+      return;
+    }
+    const onAbort = () => settle(runId, false);
+    signal?.addEventListener('abort', onAbort, { once: true });
+    pendingApprovals.set(runId, {
+      request: fullRequest,
+      resolve: (value) => {
+        signal?.removeEventListener('abort', onAbort);
+        resolve(value);
+      },
+    });
+  });
+
+  console.log(`[ToolApproval] Waiting for approval of ${request.toolName} (${runId})`);
+  emit(TOOL_APPROVAL_REQUEST_EVENT, fullRequest).catch((err) => {
+    console.warn('[ToolApproval] Failed to emit approval request:', err);
+  });
+
+  return { runId, approved };
+}
+
+/**
+ * Answer a pending approval request; returns false if it is no longer pending
+ */
+export function approveTool(runId: string, approved: boolean): boolean {
+  return settle(runId, approved);
+}
+
+/**
+ * Requests still waiting for an answer, optionally for one session
+ */
+export function getPendingApprovals(sessionId?: string): ToolApprovalRequest[] {
+  return Array.from(pendingApprovals.values())
+    .map((p) => p.request)
+    .filter((r) => !sessionId || r.sessionId === sessionId);
+}
@@ -16,6 +16,46 @@ import {
   createHelpfulError,
 } from "./toolUtils";
 
+/**
+ * Passed to tools by the agent run executing them
+ */
+export interface ToolExecutionContext {
+  /** Aborted when the run is cancelled or times out; long-running tools should stop early */
+  signal?: AbortSignal;
+  /** Agent session whose sandbox (see tool_set_session_root) file tools run in */
+  sessionId?: string;
+  /** Ask the user to approve the running tool call, e.g. for a path outside the workspace */
+  requestApproval?: () => Promise<boolean>;
+}
+
+/**
+ * Policy error returned by the sandboxed file commands as JSON
+ */
+interface PolicyViolation {
+  code: 'policy_violation';
+  policy: string;
+  path: string;
+  message: string;
+}
+
+/**
+ * Why the workspace sandbox forbids `command`, or null when agents may run it
+ */
+async function commandPolicyError(workspaceRoot: string, command: string): Promise<string | null> {
+  const allowed = await invoke<boolean>('agent_command_allowed', { workspaceRoot, command });
+  return allowed ? null : `Command not allowed by the workspace sandbox (.rainy/agents.json): ${command}`;
+}
+
+function parsePolicyViolation(error: unknown): PolicyViolation | null {
+  if (typeof error !== 'string') return null;
+  try {
+    const parsed = JSON.parse(error);
+    return parsed?.code === 'policy_violation' ? parsed : null;
+  } catch {
+    return null;
+  }
+}
+
 export interface ToolDefinition {
   name: string;
   description: string;
@@ -24,7 +64,7 @@ export interface ToolDefinition {
     properties: Record<string, unknown>;
     required: string[];
   };
-  execute: (args: any) => Promise<any>;
+  execute: (args: any, context?: ToolExecutionContext) => Promise<any>;
   /** If true, tool execution is hidden from UI (no status shown) */
   internal?: boolean;
 }
@@ -56,6 +96,47 @@ class ToolRegistry {
     return await join(workspace.path, path);
   }
 
+  /**
+   * Run a file command in the session's sandbox, which confines paths to the
+   * workspace and applies the agent file policies. A path outside the
+   * workspace is retried once the user approves it, if the workspace allows
+   * that at all (agent.files.allowOutsideWorkspace).
+   */
+  private async sandboxed<T>(
+    command: string,
+    args: Record<string, unknown>,
+    context?: ToolExecutionContext
+  ): Promise<T> {
+    const workspace = getIDEState().workspace;
+    if (!workspace) {
+      throw new Error('No workspace is currently open. Please open a folder first.');
+    }
+    const sessionId = context?.sessionId;
+    if (!sessionId) {
+      throw new Error('File tools can only run inside an agent session');
+    }
+
+    const call = () => invoke<T>(command, { ...args, workspaceRoot: workspace.path, sessionId });
+    try {
+      return await call();
+    } catch (error) {
+      const violation = parsePolicyViolation(error);
+      if (violation?.policy !== 'needs_approval' || !context?.requestApproval) throw error;
+      if (!(await context.requestApproval())) throw error;
+      await invoke('tool_approve_outside_path', { sessionId, path: violation.path });
+      return await call();
+    }
+  }
+
+  private async readFile(path: string, context?: ToolExecutionContext): Promise<string> {
+    const result = await this.sandboxed<{ content: string }>('tool_read_file', { path }, context);
+    return result.content;
+  }
+
+  private async writeFile(path: string, content: string, context?: ToolExecutionContext): Promise<void> {
+    await this.sandboxed('tool_write_file', { path, content, createDirs: true }, context);
+  }
+
   private registerDefaultTools() {
     // --- Workspace Info Tool ---
     this.registerTool({
@@ -101,13 +182,12 @@ class ToolRegistry {
         },
         required: ["path"],
       },
-      execute: async ({ path }) => {
+      execute: async ({ path }, context) => {
         try {
           if (!path || typeof path !== 'string') {
             return { success: false, error: 'Invalid path parameter' };
           }
-          const resolvedPath = await this.resolvePath(path);
-          const content = await invoke<string>("get_file_content", { path: resolvedPath });
+          const content = await this.readFile(path, context);
           return { success: true, content };
         } catch (error) {
           const errorMsg = error instanceof Error ? error.message : String(error);
@@ -137,7 +217,7 @@ class ToolRegistry {
         },
         required: ["path", "old_string", "new_string"],
       },
-      execute: async ({ path, old_string, new_string }) => {
+      execute: async ({ path, old_string, new_string }, context) => {
         try {
           // Enhanced validation for stable editing
           if (!path || typeof path !== 'string') {
@@ -161,7 +241,7 @@ class ToolRegistry {
           const resolvedPath = await this.resolvePath(path);
 
           // Read current content
-          const currentContent = await invoke<string>("get_file_content", { path: resolvedPath });
+          const currentContent = await this.readFile(path, context);
 
           // Normalize line endings for comparison
           const normalizedContent = currentContent.replace(/\r\n/g, '\n');
@@ -200,7 +280,7 @@ class ToolRegistry {
 
           // Perform the replacement
           const newContent = normalizedContent.replace(normalizedOldString, new_string);
-          await invoke("save_file_content", { path: resolvedPath, content: newContent });
+          await this.writeFile(path, newContent, context);
 
           // IMPORTANT: Update Monaco editor if this file is open
           const editor = editorActions.getCurrentEditor();
@@ -217,7 +297,7 @@ class ToolRegistry {
           }
 
           // Post-edit verification
-          const verifyContent = await invoke<string>("get_file_content", { path: resolvedPath });
+          const verifyContent = await this.readFile(path, context);
           const normalizedVerify = verifyContent.replace(/\r\n/g, '\n');
 
           // Check that old_string is gone (unless it equals new_string)
@@ -255,7 +335,7 @@ class ToolRegistry {
         },
         required: ["path", "content"],
       },
-      execute: async ({ path, content }) => {
+      execute: async ({ path, content }, context) => {
         try {
           if (!path || typeof path !== 'string') {
             return { success: false, error: 'Invalid path parameter' };
@@ -264,7 +344,7 @@ class ToolRegistry {
             return { success: false, error: 'Content parameter is required' };
           }
           const resolvedPath = await this.resolvePath(path);
-          await invoke("save_file_content", { path: resolvedPath, content });
+          await this.writeFile(path, content, context);
 
           // IMPORTANT: Update Monaco editor if this file is open
           const editor = editorActions.getCurrentEditor();
@@ -316,7 +396,7 @@ After calling this tool, the user will see the changes highlighted in the editor
         },
         required: ["path", "new_content"],
       },
-      execute: async ({ path, new_content, description }) => {
+      execute: async ({ path, new_content, description }, context) => {
         try {
           if (!path || typeof path !== 'string') {
             return { success: false, error: 'Invalid path parameter' };
@@ -330,9 +410,10 @@ After calling this tool, the user will see the changes highlighted in the editor
           // Read original content
           let originalContent = '';
           try {
-            originalContent = await invoke<string>("get_file_content", { path: resolvedPath });
-          } catch {
-            // File might not exist, that's okay for new files
+            originalContent = await this.readFile(path, context);
+          } catch (error) {
+            // File might not exist, that's okay for new files; policy errors are not
+            if (!String(error).startsWith('File not found')) throw error;
             originalContent = '';
           }
 
@@ -793,16 +874,12 @@ Examples:
         },
         required: ["path"],
       },
-      execute: async ({ path, content }) => {
+      execute: async ({ path, content }, context) => {
         try {
           if (!path || typeof path !== 'string') {
             return { success: false, error: 'Invalid path parameter' };
           }
-          const resolvedPath = await this.resolvePath(path);
-          await invoke("create_file", { path: resolvedPath });
-          if (content) {
-            await invoke("save_file_content", { path: resolvedPath, content });
-          }
+          await this.writeFile(path, content ?? '', context);
           return { success: true, message: `File '${path}' created successfully.` };
         } catch (error) {
           const errorMsg = error instanceof Error ? error.message : String(error);
@@ -890,7 +967,7 @@ Examples:
         },
         required: ["command"],
       },
-      execute: async ({ command, cwd, timeout = 30000 }) => {
+      execute: async ({ command, cwd, timeout = 30000 }, context) => {
         try {
           if (!command || typeof command !== 'string') {
             return { success: false, error: 'Command parameter is required' };
@@ -901,6 +978,11 @@ Examples:
             return { success: false, error: 'No workspace open' };
           }
 
+          const denied = await commandPolicyError(workspace.path, command);
+          if (denied) {
+            return { success: false, error: denied };
+          }
+
           const workingDir = cwd ? await this.resolvePath(cwd) : workspace.path;
 
           // Production timeout: min 30s, max 120s
@@ -949,7 +1031,8 @@ Examples:
             const pollInterval = 500;
             const settlementThreshold = 3; // 1.5 seconds of no change
 
-            while (Date.now() - startTime < effectiveTimeout) {
+            // Stops early when the agent run is cancelled; the session is killed below
+            while (Date.now() - startTime < effectiveTimeout && !context?.signal?.aborted) {
               await new Promise(resolve => setTimeout(resolve, pollInterval));
 
               const currentLength = output.length;
@@ -1060,7 +1143,7 @@ Examples:
           } else {
             // Auto-detect from workspace
             try {
-              const packageJson = await invoke<string>("get_file_content", {
+              const { content: packageJson } = await invoke<{ content: string }>("get_file_content", {
                 path: await join(workspace.path, "package.json")
               });
               if (packageJson) {
@@ -1072,7 +1155,7 @@ Examples:
             } catch {
               // Try Cargo
               try {
-                await invoke<string>("get_file_content", {
+                await invoke("get_file_content", {
                   path: await join(workspace.path, "Cargo.toml")
                 });
                 testCommand = `cargo test ${target || ''}`;
@@ -1243,7 +1326,7 @@ Returns (based on 'include' parameter):
             try {
               // Try package.json first
               const pkgPath = await join(workspace.path, 'package.json');
-              const pkgContent = await invoke<string>("get_file_content", { path: pkgPath });
+              const { content: pkgContent } = await invoke<{ content: string }>("get_file_content", { path: pkgPath });
               const pkg = JSON.parse(pkgContent);
 
               context.dependencies = response_format === 'concise'
@@ -1267,7 +1350,7 @@ Returns (based on 'include' parameter):
               // Try Cargo.toml
               try {
                 const cargoPath = await join(workspace.path, 'Cargo.toml');
-                const cargoContent = await invoke<string>("get_file_content", { path: cargoPath });
+                const { content: cargoContent } = await invoke<{ content: string }>("get_file_content", { path: cargoPath });
                 context.dependencies = {
                   type: 'cargo',
                   content: response_format === 'concise'
@@ -1298,7 +1381,7 @@ Returns (based on 'include' parameter):
           if (sections.includes('readme')) {
             try {
               const readmePath = await join(workspace.path, 'README.md');
-              const readmeContent = await invoke<string>("get_file_content", { path: readmePath });
+              const { content: readmeContent } = await invoke<{ content: string }>("get_file_content", { path: readmePath });
               const formatted = formatFileResponse(readmePath, readmeContent, response_format);
               context.readme = response_format === 'concise' ? formatted.preview : formatted.content;
             } catch { /* no readme */ }
@@ -1321,7 +1404,7 @@ Returns (based on 'include' parameter):
             for (const entry of potentialEntries) {
               try {
                 const entryPath = await join(workspace.path, entry.file);
-                await invoke<string>("get_file_content", { path: entryPath });
+                await invoke("get_file_content", { path: entryPath });
                 entryPoints.push({ name: entry.file, path: entryPath, type: entry.type });
               } catch { /* doesn't exist */ }
             }
@@ -1374,7 +1457,7 @@ Use when you need to read 2+ files. Returns all file contents with metadata.`,
         paths: string[];
         response_format?: ResponseFormat;
         max_chars_per_file?: number;
-      }) => {
+      }, context) => {
         try {
           if (!paths || !Array.isArray(paths) || paths.length === 0) {
             return createHelpfulError('paths array is required and must not be empty', {
@@ -1407,8 +1490,7 @@ Use when you need to read 2+ files. Returns all file contents with metadata.`,
                 continue;
               }
 
-              const resolvedPath = await this.resolvePath(path);
-              let content = await invoke<string>("get_file_content", { path: resolvedPath });
+              let content = await this.readFile(path, context);
 
               // Truncate if too large
               if (content.length > max_chars_per_file) {
@@ -1708,6 +1790,87 @@ Uses ripgrep with smart patterns optimized for common languages.`,
       },
     });
 
+    // --- semantic_search: Embeddings-backed code search ---
+    this.registerTool({
+      name: "semantic_search",
+      description: `Find code by meaning rather than exact text, using the workspace's embeddings index.
+
+Use it when you don't know the identifiers involved, e.g. "where are API keys stored" or "retry logic for HTTP requests". Use search_code or find_symbols when you know the exact text or name.
+The first search in a workspace builds the index, which can take a while.`,
+      parameters: {
+        type: "object",
+        properties: {
+          query: {
+            type: "string",
+            description: "Natural-language description of the code to find."
+          },
+          k: {
+            type: "number",
+            description: "Number of code chunks to return (default: 10, max: 50)."
+          },
+          response_format: {
+            type: "string",
+            description: "'concise' (locations only) or 'detailed' (includes the code). Default: detailed."
+          },
+        },
+        required: ["query"],
+      },
+      execute: async ({
+        query,
+        k = 10,
+        response_format = 'detailed'
+      }: {
+        query: string;
+        k?: number;
+        response_format?: ResponseFormat;
+      }) => {
+        try {
+          if (!query || typeof query !== 'string') {
+            return createHelpfulError('query parameter is required', {
+              tool: 'semantic_search',
+              suggestion: 'Describe the code to find, e.g., "where user sessions are persisted"',
+            });
+          }
+
+          const workspace = getIDEState().workspace;
+          if (!workspace) {
+            return { success: false, error: 'No workspace open' };
+          }
+
+          const matches = await invoke<Array<{
+            relativePath: string;
+            startLine: number;
+            endLine: number;
+            kind: string;
+            name: string | null;
+            score: number;
+            content: string;
+          }>>("semantic_search", { workspaceRoot: workspace.path, query, k });
+
+          const results = matches.map((m) => ({
+            file: m.relativePath,
+            lines: `${m.startLine}-${m.endLine}`,
+            symbol: m.name ? `${m.kind} ${m.name}` : undefined,
+            score: Math.round(m.score * 1000) / 1000,
+            ...(response_format === 'detailed' && { content: m.content }),
+          }));
+
+          return {
+            success: true,
+            query,
+            results,
+            total: results.length,
+            message: results.length > 0
+              ? `Found ${results.length} code chunk(s) related to "${query}"`
+              : `No indexed code related to "${query}". Try search_code instead.`,
+          };
+        } catch (error) {
+          const errorMsg = error instanceof Error ? error.message : String(error);
+          return { success: false, error: `Semantic search failed: ${errorMsg}` };
+        }
+      },
+    });
+
     // --- smart_edit: Combined read + edit + verify ---
     this.registerTool({
       name: "smart_edit",
@@ -1757,7 +1920,7 @@ Use this instead of separate read_file + edit_file + verify_changes calls.`,
         path: string;
         edits: Array<{ find: string; replace: string }>;
         verify?: boolean;
-      }) => {
+      }, context) => {
         try {
           if (!path || typeof path !== 'string') {
             return createHelpfulError('path parameter is required', {
@@ -1778,9 +1941,9 @@ Use this instead of separate read_file + edit_file + verify_changes calls.`,
           // Step 1: Read current content
           let content: string;
           try {
-            content = await invoke<string>("get_file_content", { path: resolvedPath });
-          } catch {
-            return createHelpfulError(`File not found: ${path}`, {
+            content = await this.readFile(path, context);
+          } catch (error) {
+            return createHelpfulError(`Cannot read ${path}: ${String(error)}`, {
               tool: 'smart_edit',
               suggestion: 'Use create_file to create a new file, or check the path',
             });
@@ -1848,7 +2011,7 @@ Use this instead of separate read_file + edit_file + verify_changes calls.`,
           }
 
           // Step 3: Write the file
-          await invoke("save_file_content", { path: resolvedPath, content });
+          await this.writeFile(path, content, context);
 
           // Update Monaco editor if file is open
           const editor = editorActions.getCurrentEditor();
@@ -1861,12 +2024,16 @@ Use this instead of separate read_file + edit_file + verify_changes calls.`,
             }
           }
 
-          // Step 4: Optionally verify
+          // Step 4: Optionally verify. That runs a project command, so it
+          // needs the user's approval like verify_changes itself
           let verification = null;
+          let verificationSkipped = false;
           if (verify) {
             const verifyTool = this.getTool('verify_changes');
-            if (verifyTool) {
-              verification = await verifyTool.execute({ scope: 'type-check' });
+            if (verifyTool && context?.requestApproval && (await context.requestApproval())) {
+              verification = await verifyTool.execute({ scope: 'type-check' }, context);
+            } else {
+              verificationSkipped = true;
             }
           }
 
@@ -1884,7 +2051,8 @@ Use this instead of separate read_file + edit_file + verify_changes calls.`,
               errors: verification.summary?.errors || 0,
             } : null,
             message: `Applied ${successfulEdits.length}/${edits.length} edits to ${path}.` +
-              (verification ? (verification.passed ? ' ✓ Type-check passed.' : ' ⚠ Type errors detected.') : ''),
+              (verification ? (verification.passed ? ' ✓ Type-check passed.' : ' ⚠ Type errors detected.') : '') +
+              (verificationSkipped ? ' Verification was not approved and did not run.' : ''),
           };
         } catch (error) {
           const errorMsg = error instanceof Error ? error.message : String(error);
@@ -1899,7 +2067,7 @@ Use this instead of separate read_file + edit_file + verify_changes calls.`,
    */
   private async fileExists(path: string): Promise<boolean> {
     try {
-      await invoke<string>("get_file_content", { path });
+      await invoke("get_file_content", { path });
       return true;
     } catch {
       return false;
@@ -1989,12 +2157,43 @@ Use this instead of separate read_file + edit_file + verify_changes calls.`,
     return Array.from(this.tools.values());
   }
 
-  async executeTool(name: string, args: any): Promise<any> {
+  /**
+   * Tools the workspace's `.rainy/agents.json` lets agents use
+   */
+  async getEnabledTools(): Promise<ToolDefinition[]> {
+    const tools = this.getAllTools();
+    const workspace = getIDEState().workspace;
+    if (!workspace) return tools;
+    const enabled = await invoke<string[]>('agent_enabled_tools', {
+      workspaceRoot: workspace.path,
+      tools: tools.map((tool) => tool.name),
+    });
+    return tools.filter((tool) => enabled.includes(tool.name));
+  }
+
+  /**
+   * Why `.rainy/agents.json` forbids a tool call, or null when it is allowed
+   */
+  async workspacePolicyError(name: string, args: any): Promise<string | null> {
+    const workspace = getIDEState().workspace;
+    if (!workspace) return null;
+    const workspaceRoot = workspace.path;
+    const enabled = await invoke<string[]>('agent_enabled_tools', { workspaceRoot, tools: [name] });
+    if (enabled.length === 0) {
+      return `Tool ${name} is disabled for this workspace (.rainy/agents.json)`;
+    }
+    if (name === 'run_command' && typeof args?.command === 'string') {
+      return await commandPolicyError(workspaceRoot, args.command);
+    }
+    return null;
+  }
+
+  async executeTool(name: string, args: any, context?: ToolExecutionContext): Promise<any> {
     const tool = this.tools.get(name);
     if (!tool) {
       throw new Error(`Tool ${name} not found`);
     }
-    return await tool.execute(args);
+    return await tool.execute(args, context);
   }
 }
 
@@ -20,25 +20,108 @@ export interface StreamChunk {
   error?: string;
 }
 
+/**
+ * Token counts the provider reported for one request
+ */
+export interface TokenUsage {
+  promptTokens: number;
+  completionTokens: number;
+  /** Prompt tokens served from the provider's prompt cache */
+  cachedTokens?: number;
+}
+
+/**
+ * A JSON Schema document
+ */
+export type JsonSchema = Record<string, unknown>;
+
+/**
+ * Per-request settings besides the messages and tools
+ */
+export interface RequestOptions {
+  /**
+   * Schema the reply must be JSON for. Providers with a JSON mode enforce it
+   * where the API allows; callers validate the reply either way.
+   */
+  responseSchema?: JsonSchema;
+}
+
 export interface AIProvider {
   /**
    * Send a message and get a complete response
+   * Aborting `signal` cancels the in-flight request
    */
   sendMessage(
     messages: ChatMessage[],
-    tools: ToolDefinition[]
+    tools: ToolDefinition[],
+    signal?: AbortSignal,
+    options?: RequestOptions
   ): Promise<ChatMessage>;
 
   /**
    * Send a message and stream the response
+   * Aborting `signal` cancels the in-flight request and stops the stream
    */
   streamMessage(
     messages: ChatMessage[],
     tools: ToolDefinition[],
-    onChunk: (chunk: StreamChunk) => void
+    onChunk: (chunk: StreamChunk) => void,
+    signal?: AbortSignal,
+    options?: RequestOptions
   ): Promise<ChatMessage>;
 }
 
+// ===========================
+// Errors
+// ===========================
+
+/**
+ * A failed provider request, with the HTTP status and the Retry-After delay
+ * when the API sent them (read by the retry policy)
+ */
+export class ProviderRequestError extends Error {
+  constructor(
+    message: string,
+    readonly status?: number,
+    readonly retryAfterMs?: number
+  ) {
+    super(message);
+    this.name = "ProviderRequestError";
+  }
+}
+
+function readHeader(headers: any, name: string): string | undefined {
+  if (!headers) return undefined;
+  const value = typeof headers.get === "function" ? headers.get(name) : headers[name];
+  return value ?? undefined;
+}
+
+/**
+ * Milliseconds to wait from `retry-after-ms` or `retry-after` (seconds or an HTTP date)
+ */
+function parseRetryAfter(headers: any): number | undefined {
+  const ms = Number(readHeader(headers, "retry-after-ms"));
+  if (Number.isFinite(ms) && ms >= 0) return ms;
+
+  const retryAfter = readHeader(headers, "retry-after");
+  if (!retryAfter) return undefined;
+  const seconds = Number(retryAfter);
+  if (Number.isFinite(seconds)) return seconds * 1000;
+  const date = Date.parse(retryAfter);
+  return Number.isNaN(date) ? undefined : Math.max(date - Date.now(), 0);
+}
+
+/**
+ * Wrap an SDK error with a readable message, keeping its status and Retry-After
+ */
+export function toProviderError(prefix: string, error: unknown): ProviderRequestError {
+  if (error instanceof ProviderRequestError) return error;
+  const details = error as { message?: string; status?: unknown; headers?: unknown };
+  const message = error instanceof Error ? error.message : String(error);
+  const status = typeof details?.status === "number" ? details.status : undefined;
+  return new ProviderRequestError(`${prefix}: ${message}`, status, parseRetryAfter(details?.headers));
+}
+
 // ===========================
 // Utility Functions
 // ===========================
@@ -79,6 +162,63 @@ export function convertToolsToFunctionFormat(tools: ToolDefinition[]): any[] {
   }));
 }
 
+/**
+ * Render a tool call's outcome the way it is sent back to the model.
+ * Command output is shown as plain text; other results as (truncated) JSON.
+ */
+export function formatToolResult(toolCall: ToolCall): string {
+  if (toolCall.status === "error" || toolCall.error) {
+    return `Tool ${toolCall.name} failed with error: ${toolCall.error ?? "Tool execution failed"}`;
+  }
+  if (toolCall.status !== "success" && toolCall.result === undefined) {
+    return `Tool ${toolCall.name} was not executed`;
+  }
+
+  // Special formatting for command outputs - show stdout directly, not JSON
+  if (toolCall.name === "run_command" && toolCall.result && typeof toolCall.result === "object") {
+    const result = toolCall.result as Record<string, unknown>;
+    const stdout = result.stdout || result.output || "";
+    const exitCode = result.exitCode ?? result.exitedWithErrors;
+    const duration = result.duration || "";
+
+    let output = `Tool run_command completed (${duration}ms):\n`;
+    if (exitCode !== undefined && exitCode !== 0 && exitCode !== false) {
+      output += `Exit status: ${exitCode}\n`;
+    }
+    output += `--- Command Output ---\n${stdout}\n--- End Output ---`;
+
+    // Larger limit for command outputs (8000 chars)
+    if (output.length > 8000) {
+      output = output.slice(0, 8000) + "\n... (output truncated, showing first 8000 chars)";
+    }
+    return output;
+  }
+
+  const resultStr = JSON.stringify(toolCall.result, null, 2) ?? "null";
+  const truncated = resultStr.length > 5000
+    ? resultStr.slice(0, 5000) + "\n... (truncated, showing first 5000 chars)"
+    : resultStr;
+  return `Tool ${toolCall.name} executed successfully:\n${truncated}`;
+}
+
+/**
+ * Tool results of an assistant turn as one user message, for providers
+ * without a dedicated tool-result role
+ */
+export function formatToolResultsMessage(toolCalls: ToolCall[]): string {
+  const results = toolCalls.map(formatToolResult).join("\n\n");
+  return `Tool execution results:\n\n${results}\n\nAnalyze these results and continue your task. If you need more information, use additional tools. When you have enough information, present your findings clearly to the user.`;
+}
+
+/**
+ * Whether any of the message's tool calls has run (successfully or not)
+ */
+export function hasToolResults(message: ChatMessage): boolean {
+  return (message.toolCalls ?? []).some(
+    (tc) => tc.status === "success" || tc.status === "error" || tc.result !== undefined
+  );
+}
+
 /**
  * Generate a unique message ID
  */
@@ -104,3 +244,20 @@ export function createChatMessage(
     thoughts,
   };
 }
+
+/**
+ * Attach the provider-reported token usage to a response
+ */
+export function withUsage(message: ChatMessage, usage: TokenUsage | undefined): ChatMessage {
+  if (usage) {
+    message.metadata = { ...message.metadata, usage };
+  }
+  return message;
+}
+
+/**
+ * Token usage attached by withUsage, if the provider reported any
+ */
+export function getUsage(message: ChatMessage): TokenUsage | undefined {
+  return message.metadata?.usage as TokenUsage | undefined;
+}
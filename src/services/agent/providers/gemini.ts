@@ -7,6 +7,13 @@ import {
   StreamChunk,
   createChatMessage,
   generateMessageId,
+  formatToolResultsMessage,
+  hasToolResults,
+  withUsage,
+  TokenUsage,
+  ProviderRequestError,
+  toProviderError,
+  RequestOptions,
 } from './base';
 import {
   withResilience,
@@ -40,6 +47,19 @@ export interface GeminiUsageStats {
   estimatedSavings: number;
 }
 
+/**
+ * Token usage from a response's `usageMetadata`
+ */
+function parseUsage(usageMetadata: any): TokenUsage | undefined {
+  if (!usageMetadata) return undefined;
+  return {
+    promptTokens: usageMetadata.promptTokenCount ?? 0,
+    // Thinking tokens are billed as output
+    completionTokens: (usageMetadata.candidatesTokenCount ?? 0) + (usageMetadata.thoughtsTokenCount ?? 0),
+    cachedTokens: usageMetadata.cachedContentTokenCount,
+  };
+}
+
 // Shared circuit breaker for all Gemini API calls
 const geminiCircuitBreaker = new CircuitBreaker({
   failureThreshold: 3,
@@ -48,16 +68,12 @@ const geminiCircuitBreaker = new CircuitBreaker({
   onClose: () => console.log('[GeminiProvider] Circuit breaker CLOSED - resuming requests'),
 });
 
-// Default resilience options for Gemini API calls
+// Timeout and circuit breaker for Gemini API calls. Retries are left to
+// ResilientProvider, which applies the agent's retry policy to every provider.
 const GEMINI_RESILIENCE_OPTIONS: ResilientOptions = {
-  maxRetries: 3,
-  baseDelayMs: 1500,
-  maxDelayMs: 30000,
+  maxRetries: 0,
   timeoutMs: 180000, // 3 minute timeout per attempt (allows for long responses)
   circuitBreaker: geminiCircuitBreaker,
-  onRetry: (attempt, error, nextDelay) => {
-    console.warn(`[GeminiProvider] Retry ${attempt}: ${error.message}. Next attempt in ${nextDelay}ms`);
-  },
 };
 
 export class GeminiProvider implements AIProvider {
@@ -98,11 +114,21 @@ export class GeminiProvider implements AIProvider {
 
   /**
    * Convert our ChatMessage to Gemini's format
-   * IMPORTANT: Don't include thoughts or verbose tool results - 
-   * Gemini has its own thinking system
+   * IMPORTANT: Don't include thoughts - Gemini has its own thinking system.
+   * Tool results follow the model turn that requested them as a user turn,
+   * the same way they are sent back during a run.
    */
   private convertMessagesToGeminiFormat(messages: ChatMessage[]): any[] {
     const result: any[] = [];
+    // Consecutive turns of the same role are merged into one
+    const pushTurn = (role: 'user' | 'model', parts: any[]) => {
+      const last = result[result.length - 1];
+      if (last && last.role === role) {
+        last.parts.push(...parts);
+      } else {
+        result.push({ role, parts });
+      }
+    };
 
     for (const msg of messages) {
       if (msg.role === 'system') continue;
@@ -134,10 +160,11 @@ export class GeminiProvider implements AIProvider {
 
       // Only add if there are parts
       if (parts.length > 0) {
-        result.push({
-          role: msg.role === 'assistant' ? 'model' : 'user',
-          parts,
-        });
+        pushTurn(msg.role === 'assistant' ? 'model' : 'user', parts);
+      }
+
+      if (msg.role === 'assistant' && msg.toolCalls && hasToolResults(msg)) {
+        pushTurn('user', [{ text: formatToolResultsMessage(msg.toolCalls) }]);
       }
     }
 
@@ -217,7 +244,9 @@ export class GeminiProvider implements AIProvider {
    */
   async sendMessage(
     messages: ChatMessage[],
-    tools: ToolDefinition[]
+    tools: ToolDefinition[],
+    signal?: AbortSignal,
+    options?: RequestOptions
   ): Promise<ChatMessage> {
     const systemPrompt = messages.find((m) => m.role === 'system')?.content;
     const geminiMessages = this.convertMessagesToGeminiFormat(messages);
@@ -262,6 +291,17 @@ export class GeminiProvider implements AIProvider {
       config.config.tools = [{ functionDeclarations }];
     }
 
+    // JSON mode can't be combined with function calling
+    if (options?.responseSchema && functionDeclarations.length === 0) {
+      config.config.responseMimeType = 'application/json';
+      config.config.responseJsonSchema = options.responseSchema;
+    }
+
+    // Cancels the request when the agent run is aborted
+    if (signal) {
+      config.config.abortSignal = signal;
+    }
+
     try {
       // Wrap API call with retry and circuit breaker
       const response = await withResilience(
@@ -269,6 +309,8 @@ export class GeminiProvider implements AIProvider {
         GEMINI_RESILIENCE_OPTIONS
       );
 
+      const usage = parseUsage(response.usageMetadata);
+
       // Check for function calls
       const functionCalls = response.functionCalls;
 
@@ -279,24 +321,24 @@ export class GeminiProvider implements AIProvider {
           arguments: fc.args,
         }));
 
-        return createChatMessage(
+        return withUsage(createChatMessage(
           'assistant',
           response.text || '',
           toolCalls
-        );
+        ), usage);
       }
 
       // Regular text response
-      return createChatMessage('assistant', response.text || '');
+      return withUsage(createChatMessage('assistant', response.text || ''), usage);
     } catch (error) {
       console.error('[GeminiProvider] API error after retries:', error);
 
       // Return a user-friendly error message
       const errorMessage = error instanceof Error ? error.message : String(error);
       if (errorMessage.includes('Circuit breaker')) {
-        throw new Error('Service temporarily unavailable. Please try again in a few seconds.');
+        throw new ProviderRequestError('Service temporarily unavailable. Please try again in a few seconds.', 503);
       }
-      throw new Error(`Gemini API failed: ${errorMessage}`);
+      throw toProviderError('Gemini API failed', error);
     }
   }
 
@@ -306,7 +348,9 @@ export class GeminiProvider implements AIProvider {
   async streamMessage(
     messages: ChatMessage[],
     tools: ToolDefinition[],
-    onChunk: (chunk: StreamChunk) => void
+    onChunk: (chunk: StreamChunk) => void,
+    signal?: AbortSignal,
+    options?: RequestOptions
   ): Promise<ChatMessage> {
     const systemPrompt = messages.find((m) => m.role === 'system')?.content;
     const geminiMessages = this.convertMessagesToGeminiFormat(messages);
@@ -351,6 +395,17 @@ export class GeminiProvider implements AIProvider {
       config.config.tools = [{ functionDeclarations }];
     }
 
+    // JSON mode can't be combined with function calling
+    if (options?.responseSchema && functionDeclarations.length === 0) {
+      config.config.responseMimeType = 'application/json';
+      config.config.responseJsonSchema = options.responseSchema;
+    }
+
+    // Cancels the request when the agent run is aborted
+    if (signal) {
+      config.config.abortSignal = signal;
+    }
+
     try {
       // Wrap the stream creation with retry (not the iteration)
       const stream = await withResilience(
@@ -370,6 +425,9 @@ export class GeminiProvider implements AIProvider {
       let lastUsageMetadata: any = null;
 
       for await (const chunk of stream) {
+        if (signal?.aborted) {
+          throw new Error('Request aborted');
+        }
         let chunkText = '';
         let chunkThought = '';
 
@@ -463,12 +521,12 @@ export class GeminiProvider implements AIProvider {
         }
       }
 
-      const finalMessage = createChatMessage(
+      const finalMessage = withUsage(createChatMessage(
         'assistant',
         fullText || '',
         toolCalls.length > 0 ? toolCalls : undefined,
         fullThoughts || undefined
-      );
+      ), parseUsage(lastUsageMetadata));
 
       onChunk({
         type: 'done',
@@ -482,9 +540,9 @@ export class GeminiProvider implements AIProvider {
       // Return a user-friendly error message
       const errorMessage = error instanceof Error ? error.message : String(error);
       if (errorMessage.includes('Circuit breaker')) {
-        throw new Error('Service temporarily unavailable. Please try again in a few seconds.');
+        throw new ProviderRequestError('Service temporarily unavailable. Please try again in a few seconds.', 503);
       }
-      throw new Error(`Gemini streaming failed: ${errorMessage}`);
+      throw toProviderError('Gemini streaming failed', error);
     }
   }
 }
@@ -8,12 +8,28 @@ import {
   createChatMessage,
   generateMessageId,
   convertToolsToFunctionFormat,
+  formatToolResult,
+  withUsage,
+  TokenUsage,
+  toProviderError,
+  RequestOptions,
 } from './base';
 
 // ===========================
 // Groq Provider
 // ===========================
 
+/**
+ * Token usage from a completion's `usage` field
+ */
+function parseUsage(usage: any): TokenUsage | undefined {
+  if (!usage) return undefined;
+  return {
+    promptTokens: usage.prompt_tokens ?? 0,
+    completionTokens: usage.completion_tokens ?? 0,
+  };
+}
+
 export class GroqProvider implements AIProvider {
   private client: Groq;
   private config: AIProviderConfig;
@@ -28,22 +44,35 @@ export class GroqProvider implements AIProvider {
 
   /**
    * Convert our ChatMessage to Groq's format
+   * Tool calls are followed by a `tool` message with each call's result
    */
   private convertMessagesToGroqFormat(messages: ChatMessage[]): any[] {
-    return messages.map((msg) => ({
-      role: msg.role === 'system' ? 'system' : msg.role === 'assistant' ? 'assistant' : 'user',
-      content: msg.content,
-      ...(msg.toolCalls && {
-        tool_calls: msg.toolCalls.map((tc) => ({
-          id: tc.id,
-          type: 'function',
-          function: {
-            name: tc.name,
-            arguments: JSON.stringify(tc.arguments),
-          },
+    return messages.flatMap((msg) => {
+      const converted: any = {
+        role: msg.role === 'system' ? 'system' : msg.role === 'assistant' ? 'assistant' : 'user',
+        content: msg.content,
+      };
+      if (!msg.toolCalls || msg.toolCalls.length === 0) {
+        return [converted];
+      }
+
+      converted.tool_calls = msg.toolCalls.map((tc) => ({
+        id: tc.id,
+        type: 'function',
+        function: {
+          name: tc.name,
+          arguments: JSON.stringify(tc.arguments),
+        },
+      }));
+      return [
+        converted,
+        ...msg.toolCalls.map((tc) => ({
+          role: 'tool',
+          tool_call_id: tc.id,
+          content: formatToolResult(tc),
         })),
-      }),
-    }));
+      ];
+    });
   }
 
   /**
@@ -51,7 +80,9 @@ export class GroqProvider implements AIProvider {
    */
   async sendMessage(
     messages: ChatMessage[],
-    tools: ToolDefinition[]
+    tools: ToolDefinition[],
+    signal?: AbortSignal,
+    options?: RequestOptions
   ): Promise<ChatMessage> {
     try {
       const groqMessages = this.convertMessagesToGroqFormat(messages);
@@ -64,10 +95,15 @@ export class GroqProvider implements AIProvider {
         tool_choice: groqTools.length > 0 ? 'auto' : undefined,
         temperature: this.config.temperature || 0.7,
         max_tokens: this.config.maxTokens || 2048,
-      });
+        // JSON mode can't be combined with tools; the schema itself is in the prompt
+        response_format: options?.responseSchema && groqTools.length === 0
+          ? { type: 'json_object' }
+          : undefined,
+      }, { signal });
 
       const choice = completion.choices[0];
       const message = choice.message;
+      const usage = parseUsage(completion.usage);
 
       // Check if tool calls were made
       if (message.tool_calls && message.tool_calls.length > 0) {
@@ -77,18 +113,18 @@ export class GroqProvider implements AIProvider {
           arguments: JSON.parse(tc.function.arguments),
         }));
 
-        return createChatMessage(
+        return withUsage(createChatMessage(
           'assistant',
           message.content || 'I need to execute some tools to help you.',
           toolCalls
-        );
+        ), usage);
       }
 
       // Regular text response
-      return createChatMessage('assistant', message.content || '');
+      return withUsage(createChatMessage('assistant', message.content || ''), usage);
     } catch (error) {
       console.error('Groq API error:', error);
-      throw new Error(`Groq API failed: ${error}`);
+      throw toProviderError('Groq API failed', error);
     }
   }
 
@@ -98,7 +134,9 @@ export class GroqProvider implements AIProvider {
   async streamMessage(
     messages: ChatMessage[],
     tools: ToolDefinition[],
-    onChunk: (chunk: StreamChunk) => void
+    onChunk: (chunk: StreamChunk) => void,
+    signal?: AbortSignal,
+    options?: RequestOptions
   ): Promise<ChatMessage> {
     try {
       const groqMessages = this.convertMessagesToGroqFormat(messages);
@@ -111,14 +149,23 @@ export class GroqProvider implements AIProvider {
         tool_choice: groqTools.length > 0 ? 'auto' : undefined,
         temperature: this.config.temperature || 0.7,
         max_tokens: this.config.maxTokens || 2048,
+        response_format: options?.responseSchema && groqTools.length === 0
+          ? { type: 'json_object' }
+          : undefined,
         stream: true,
-      });
+      }, { signal });
 
       let fullText = '';
+      let usage: TokenUsage | undefined;
       let toolCalls: ToolCall[] = [];
       const toolCallsInProgress: Map<number, any> = new Map();
 
       for await (const chunk of stream) {
+        // Groq reports usage on the last chunk under `x_groq`
+        const chunkUsage = (chunk as any).x_groq?.usage;
+        if (chunkUsage) {
+          usage = parseUsage(chunkUsage);
+        }
         const delta = chunk.choices[0]?.delta;
 
         if (!delta) continue;
@@ -174,11 +221,11 @@ export class GroqProvider implements AIProvider {
         });
       }
 
-      const finalMessage = createChatMessage(
+      const finalMessage = withUsage(createChatMessage(
         'assistant',
         fullText || 'I need to execute some tools to help you.',
         toolCalls.length > 0 ? toolCalls : undefined
-      );
+      ), usage);
 
       onChunk({
         type: 'done',
@@ -188,7 +235,7 @@ export class GroqProvider implements AIProvider {
       return finalMessage;
     } catch (error) {
       console.error('Groq streaming error:', error);
-      throw new Error(`Groq streaming failed: ${error}`);
+      throw toProviderError('Groq streaming failed', error);
     }
   }
 }
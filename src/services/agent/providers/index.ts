@@ -1,6 +1,10 @@
-import { AIProvider, AIProviderConfig } from './base';
+import { GoogleGenAI } from '@google/genai';
+import Groq from 'groq-sdk';
+import { AIProvider } from './base';
 import { GeminiProvider, GeminiThinkingConfig } from './gemini';
 import { GroqProvider } from './groq';
+import { OpenAICompatibleProvider, listOpenAICompatibleModels } from './openai';
+import { ProviderRegistry } from './registry';
 
 // ===========================
 // Model Configurations
@@ -166,8 +170,54 @@ export const AVAILABLE_MODELS: ModelConfig[] = [
     contextWindow: 32000,      // 32K context
     maxOutputTokens: 8192,     // 8K output
   },
+
+  // ===========================
+  // OpenAI-Compatible Models
+  // ===========================
+  // Served from the configured base URL (OpenAI by default); other models on
+  // the endpoint can be used as "openai:<model>"
+  {
+    id: 'gpt-4.1',
+    name: 'GPT-4.1',
+    provider: 'openai',
+    model: 'gpt-4.1',
+    description: 'OpenAI GPT-4.1',
+    contextWindow: 1047576,    // 1M context
+    maxOutputTokens: 32768,    // 32K output
+    supportsTools: true,
+  },
+  {
+    id: 'gpt-4.1-mini',
+    name: 'GPT-4.1 Mini',
+    provider: 'openai',
+    model: 'gpt-4.1-mini',
+    description: 'Fast and affordable OpenAI model',
+    contextWindow: 1047576,    // 1M context
+    maxOutputTokens: 32768,    // 32K output
+    supportsTools: true,
+  },
 ];
 
+/** Prefix for models served by the OpenAI-compatible endpoint that aren't listed above */
+export const OPENAI_COMPATIBLE_MODEL_PREFIX = 'openai:';
+
+/**
+ * Model configuration for an arbitrary model on the OpenAI-compatible endpoint
+ */
+function openAICompatibleModelConfig(modelId: string): ModelConfig {
+  const model = modelId.slice(OPENAI_COMPATIBLE_MODEL_PREFIX.length);
+  return {
+    id: modelId,
+    name: model,
+    provider: 'openai',
+    model,
+    description: 'Model on the configured OpenAI-compatible endpoint',
+    contextWindow: 128000,     // Conservative defaults for unknown models
+    maxOutputTokens: 8192,
+    supportsTools: true,
+  };
+}
+
 // ===========================
 // Provider Factory
 // ===========================
@@ -175,6 +225,85 @@ export const AVAILABLE_MODELS: ModelConfig[] = [
 export interface ProviderCredentials {
   geminiApiKey?: string;
   groqApiKey?: string;
+  openaiApiKey?: string;
+  /** OpenAI-compatible endpoint (OpenAI, Azure OpenAI, Together, ...); defaults to OpenAI */
+  openaiBaseUrl?: string;
+}
+
+/**
+ * Built-in providers. Models whose provider has no factory here (e.g. Cerebras)
+ * fail with UnknownProviderError.
+ */
+export const providerRegistry = new ProviderRegistry();
+
+providerRegistry.register('gemini', {
+  create(config, model, credentials) {
+    if (!credentials.geminiApiKey) {
+      throw new Error('Gemini API key not configured');
+    }
+    return new GeminiProvider({ ...config, apiKey: credentials.geminiApiKey }, model.thinkingConfig);
+  },
+  async listModels(credentials) {
+    if (!credentials.geminiApiKey) {
+      throw new Error('Gemini API key not configured');
+    }
+    const client = new GoogleGenAI({ apiKey: credentials.geminiApiKey });
+    const names: string[] = [];
+    for await (const model of await client.models.list()) {
+      if (model.name) names.push(model.name.replace(/^models\//, ''));
+    }
+    return names;
+  },
+});
+
+providerRegistry.register('groq', {
+  create(config, _model, credentials) {
+    if (!credentials.groqApiKey) {
+      throw new Error('Groq API key not configured');
+    }
+    return new GroqProvider({ ...config, apiKey: credentials.groqApiKey });
+  },
+  async listModels(credentials) {
+    if (!credentials.groqApiKey) {
+      throw new Error('Groq API key not configured');
+    }
+    const client = new Groq({ apiKey: credentials.groqApiKey, dangerouslyAllowBrowser: true });
+    const { data } = await client.models.list();
+    return data.map((model) => model.id);
+  },
+});
+
+providerRegistry.register('openai', {
+  create(config, _model, credentials) {
+    if (!credentials.openaiApiKey) {
+      throw new Error('OpenAI API key not configured');
+    }
+    return new OpenAICompatibleProvider({
+      ...config,
+      apiKey: credentials.openaiApiKey,
+      baseUrl: credentials.openaiBaseUrl || undefined,
+    });
+  },
+  async listModels(credentials) {
+    if (!credentials.openaiApiKey) {
+      throw new Error('OpenAI API key not configured');
+    }
+    return listOpenAICompatibleModels({
+      apiKey: credentials.openaiApiKey,
+      baseUrl: credentials.openaiBaseUrl || undefined,
+    });
+  },
+});
+
+/**
+ * Check that the model's provider serves it (see ProviderRegistry.validateModel)
+ */
+export async function validateModel(modelId: string, credentials: ProviderCredentials): Promise<void> {
+  const modelConfig = getModelConfig(modelId);
+  if (!modelConfig) {
+    throw new Error(`Model ${modelId} not found`);
+  }
+  await providerRegistry.validateModel(modelConfig, credentials);
 }
 
 /**
@@ -186,7 +315,7 @@ export function createProvider(
   temperature?: number,
   maxTokens?: number
 ): AIProvider {
-  const modelConfig = AVAILABLE_MODELS.find((m) => m.id === modelId);
+  const modelConfig = getModelConfig(modelId);
 
   if (!modelConfig) {
     throw new Error(`Model ${modelId} not found`);
@@ -195,38 +324,19 @@ export function createProvider(
   // Use model's maxOutputTokens if not explicitly set
   const effectiveMaxTokens = maxTokens ?? modelConfig.maxOutputTokens;
 
-  const config: AIProviderConfig = {
-    apiKey: '',
-    model: modelConfig.model,
-    temperature,
-    maxTokens: effectiveMaxTokens,
-  };
-
-  switch (modelConfig.provider) {
-    case 'gemini':
-      if (!credentials.geminiApiKey) {
-        throw new Error('Gemini API key not configured');
-      }
-      config.apiKey = credentials.geminiApiKey;
-      return new GeminiProvider(config, modelConfig.thinkingConfig);
-
-    case 'groq':
-      if (!credentials.groqApiKey) {
-        throw new Error('Groq API key not configured');
-      }
-      config.apiKey = credentials.groqApiKey;
-      return new GroqProvider(config);
-
-    default:
-      throw new Error(`Unsupported provider: ${modelConfig.provider}`);
-  }
+  return providerRegistry.resolve(modelConfig, credentials, temperature, effectiveMaxTokens);
 }
 
 /**
  * Get model configuration by ID
  */
 export function getModelConfig(modelId: string): ModelConfig | undefined {
-  return AVAILABLE_MODELS.find((m) => m.id === modelId);
+  const listed = AVAILABLE_MODELS.find((m) => m.id === modelId);
+  if (listed) return listed;
+  if (modelId.startsWith(OPENAI_COMPATIBLE_MODEL_PREFIX) && modelId.length > OPENAI_COMPATIBLE_MODEL_PREFIX.length) {
+    return openAICompatibleModelConfig(modelId);
+  }
+  return undefined;
 }
 
 /**
@@ -239,9 +349,13 @@ export function getAllModels(): ModelConfig[] {
 /**
  * Get models by provider
  */
-export function getModelsByProvider(provider: 'gemini' | 'groq'): ModelConfig[] {
+export function getModelsByProvider(provider: ModelConfig['provider']): ModelConfig[] {
   return AVAILABLE_MODELS.filter((m) => m.provider === provider);
 }
 
 // Re-export types
 export type { AIProvider, AIProviderConfig, StreamChunk } from './base';
+export { listOpenAICompatibleModels, DEFAULT_OPENAI_BASE_URL } from './openai';
+export type { OpenAICompatibleConfig } from './openai';
+export { ProviderRegistry, UnknownProviderError, UnknownModelError } from './registry';
+export type { ProviderFactory } from './registry';
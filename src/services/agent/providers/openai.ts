@@ -0,0 +1,315 @@
+import OpenAI from 'openai';
+import { ChatMessage, ToolCall } from '@/types/chat';
+import { ToolDefinition } from '../ToolRegistry';
+import {
+  AIProvider,
+  AIProviderConfig,
+  StreamChunk,
+  createChatMessage,
+  generateMessageId,
+  convertToolsToFunctionFormat,
+  formatToolResult,
+  withUsage,
+  TokenUsage,
+  toProviderError,
+  RequestOptions,
+} from './base';
+
+// ===========================
+// OpenAI-Compatible Provider
+// ===========================
+
+export const DEFAULT_OPENAI_BASE_URL = 'https://api.openai.com/v1';
+
+export interface OpenAICompatibleConfig extends AIProviderConfig {
+  /**
+   * Endpoint root, e.g. https://api.together.xyz/v1 or, for Azure OpenAI,
+   * https://<resource>.openai.azure.com/openai/deployments/<deployment>
+   */
+  baseUrl?: string;
+  /** Azure OpenAI `api-version` (defaults to 2024-10-21 for Azure endpoints) */
+  apiVersion?: string;
+  /** Extra headers sent with every request */
+  headers?: Record<string, string>;
+}
+
+function isAzureEndpoint(baseUrl: string): boolean {
+  return /\.openai\.azure\.com/i.test(baseUrl);
+}
+
+/**
+ * Create an SDK client for any OpenAI-compatible endpoint.
+ * Azure endpoints authenticate with the `api-key` header and need `api-version`.
+ */
+function createClient(config: OpenAICompatibleConfig): OpenAI {
+  const baseURL = (config.baseUrl || DEFAULT_OPENAI_BASE_URL).replace(/\/+$/, '');
+  const azure = isAzureEndpoint(baseURL);
+
+  return new OpenAI({
+    apiKey: config.apiKey,
+    baseURL,
+    defaultHeaders: {
+      ...(azure && { 'api-key': config.apiKey }),
+      ...config.headers,
+    },
+    defaultQuery: azure ? { 'api-version': config.apiVersion || '2024-10-21' } : undefined,
+    dangerouslyAllowBrowser: true, // Required for Tauri WebView
+  });
+}
+
+/**
+ * List the model IDs served by an OpenAI-compatible endpoint (`GET /models`)
+ */
+export async function listOpenAICompatibleModels(
+  config: Pick<OpenAICompatibleConfig, 'apiKey' | 'baseUrl' | 'apiVersion' | 'headers'>
+): Promise<string[]> {
+  const client = createClient({ ...config, model: '' });
+  const ids: string[] = [];
+  for await (const model of client.models.list()) {
+    ids.push(model.id);
+  }
+  return ids.sort();
+}
+
+/**
+ * Token usage from a completion's `usage` field
+ */
+function parseUsage(usage: any): TokenUsage | undefined {
+  if (!usage) return undefined;
+  return {
+    promptTokens: usage.prompt_tokens ?? 0,
+    completionTokens: usage.completion_tokens ?? 0,
+    cachedTokens: usage.prompt_tokens_details?.cached_tokens,
+  };
+}
+
+/**
+ * `response_format` constraining the reply to the schema
+ */
+function responseFormat(options?: RequestOptions): any {
+  if (!options?.responseSchema) return undefined;
+  return {
+    type: 'json_schema',
+    json_schema: { name: 'response', schema: options.responseSchema },
+  };
+}
+
+/**
+ * Parse streamed/returned tool arguments, tolerating empty or malformed JSON
+ */
+function parseToolArguments(raw: string | undefined): Record<string, unknown> {
+  if (!raw) return {};
+  try {
+    return JSON.parse(raw);
+  } catch {
+    console.warn('[OpenAIProvider] Could not parse tool arguments:', raw);
+    return {};
+  }
+}
+
+export class OpenAICompatibleProvider implements AIProvider {
+  private client: OpenAI;
+  private config: OpenAICompatibleConfig;
+
+  constructor(config: OpenAICompatibleConfig) {
+    this.config = config;
+    this.client = createClient(config);
+  }
+
+  /**
+   * Convert our ChatMessage to the chat completions format.
+   * Every assistant tool call is answered by a `tool` message, which the
+   * API requires before the next turn.
+   */
+  private convertMessages(messages: ChatMessage[]): any[] {
+    const converted: any[] = [];
+
+    for (const msg of messages) {
+      if (msg.role === 'assistant' && msg.toolCalls && msg.toolCalls.length > 0) {
+        converted.push({
+          role: 'assistant',
+          content: msg.content || null,
+          tool_calls: msg.toolCalls.map((tc) => ({
+            id: tc.id,
+            type: 'function',
+            function: {
+              name: tc.name,
+              arguments: JSON.stringify(tc.arguments),
+            },
+          })),
+        });
+        for (const tc of msg.toolCalls) {
+          converted.push({
+            role: 'tool',
+            tool_call_id: tc.id,
+            content: formatToolResult(tc),
+          });
+        }
+        continue;
+      }
+
+      converted.push({
+        role: msg.role,
+        content: msg.content,
+      });
+    }
+
+    return converted;
+  }
+
+  /**
+   * Send a non-streaming message
+   */
+  async sendMessage(
+    messages: ChatMessage[],
+    tools: ToolDefinition[],
+    signal?: AbortSignal,
+    options?: RequestOptions
+  ): Promise<ChatMessage> {
+    try {
+      const openaiTools = convertToolsToFunctionFormat(tools);
+
+      const completion = await this.client.chat.completions.create({
+        model: this.config.model,
+        messages: this.convertMessages(messages),
+        tools: openaiTools.length > 0 ? openaiTools : undefined,
+        tool_choice: openaiTools.length > 0 ? 'auto' : undefined,
+        temperature: this.config.temperature ?? 0.7,
+        max_tokens: this.config.maxTokens || 4096,
+        response_format: responseFormat(options),
+      }, { signal });
+
+      const message = completion.choices[0]?.message;
+      if (!message) {
+        throw new Error('Empty response from model');
+      }
+      const usage = parseUsage(completion.usage);
+
+      if (message.tool_calls && message.tool_calls.length > 0) {
+        const toolCalls: ToolCall[] = message.tool_calls
+          .filter((tc: any) => tc.type === 'function')
+          .map((tc: any) => ({
+            id: tc.id || generateMessageId(),
+            name: tc.function.name,
+            arguments: parseToolArguments(tc.function.arguments),
+          }));
+
+        return withUsage(createChatMessage(
+          'assistant',
+          message.content || 'I need to execute some tools to help you.',
+          toolCalls
+        ), usage);
+      }
+
+      return withUsage(createChatMessage('assistant', message.content || ''), usage);
+    } catch (error) {
+      console.error('OpenAI-compatible API error:', error);
+      throw toProviderError('OpenAI-compatible API failed', error);
+    }
+  }
+
+  /**
+   * Send a streaming message
+   */
+  async streamMessage(
+    messages: ChatMessage[],
+    tools: ToolDefinition[],
+    onChunk: (chunk: StreamChunk) => void,
+    signal?: AbortSignal,
+    options?: RequestOptions
+  ): Promise<ChatMessage> {
+    try {
+      const openaiTools = convertToolsToFunctionFormat(tools);
+
+      const stream = await this.client.chat.completions.create({
+        model: this.config.model,
+        messages: this.convertMessages(messages),
+        tools: openaiTools.length > 0 ? openaiTools : undefined,
+        tool_choice: openaiTools.length > 0 ? 'auto' : undefined,
+        temperature: this.config.temperature ?? 0.7,
+        max_tokens: this.config.maxTokens || 4096,
+        response_format: responseFormat(options),
+        stream: true,
+        // The last chunk then carries the token usage
+        stream_options: { include_usage: true },
+      }, { signal });
+
+      let fullText = '';
+      let usage: TokenUsage | undefined;
+      const toolCallsInProgress: Map<number, { id: string; name: string; arguments: string }> =
+        new Map();
+
+      for await (const chunk of stream) {
+        if (chunk.usage) {
+          usage = parseUsage(chunk.usage);
+        }
+        const delta = chunk.choices[0]?.delta;
+
+        if (!delta) continue;
+
+        // Handle text content
+        if (delta.content) {
+          fullText += delta.content;
+          onChunk({
+            type: 'text',
+            content: delta.content,
+          });
+        }
+
+        // Tool calls arrive in pieces, keyed by index
+        if (delta.tool_calls) {
+          for (const tcDelta of delta.tool_calls) {
+            const index = tcDelta.index;
+
+            if (!toolCallsInProgress.has(index)) {
+              toolCallsInProgress.set(index, {
+                id: tcDelta.id || generateMessageId(),
+                name: '',
+                arguments: '',
+              });
+            }
+
+            const tc = toolCallsInProgress.get(index)!;
+
+            if (tcDelta.function?.name) {
+              tc.name = tcDelta.function.name;
+            }
+
+            if (tcDelta.function?.arguments) {
+              tc.arguments += tcDelta.function.arguments;
+            }
+          }
+        }
+      }
+
+      const toolCalls: ToolCall[] = Array.from(toolCallsInProgress.values()).map((tc) => ({
+        id: tc.id,
+        name: tc.name,
+        arguments: parseToolArguments(tc.arguments),
+      }));
+
+      toolCalls.forEach((tc) => {
+        onChunk({
+          type: 'tool_call',
+          toolCall: tc,
+        });
+      });
+
+      const finalMessage = withUsage(createChatMessage(
+        'assistant',
+        fullText || (toolCalls.length > 0 ? 'I need to execute some tools to help you.' : ''),
+        toolCalls.length > 0 ? toolCalls : undefined
+      ), usage);
+
+      onChunk({
+        type: 'done',
+        fullMessage: finalMessage,
+      });
+
+      return finalMessage;
+    } catch (error) {
+      console.error('OpenAI-compatible streaming error:', error);
+      throw toProviderError('OpenAI-compatible streaming failed', error);
+    }
+  }
+}
@@ -0,0 +1,175 @@
+import type { AIProvider, AIProviderConfig } from './base';
+import type { ModelConfig, ProviderCredentials } from './index';
+
+// ===========================
+// Errors
+// ===========================
+
+/**
+ * No provider is registered for the model's `provider` field
+ */
+export class UnknownProviderError extends Error {
+  constructor(readonly provider: string) {
+    super(`Unknown provider: ${provider}`);
+    this.name = 'UnknownProviderError';
+  }
+}
+
+/**
+ * The provider does not serve the requested model
+ */
+export class UnknownModelError extends Error {
+  constructor(readonly provider: string, readonly model: string) {
+    super(`Model ${model} is not available from provider ${provider}`);
+    this.name = 'UnknownModelError';
+  }
+}
+
+// ===========================
+// Provider Registry
+// ===========================
+
+export interface ProviderFactory {
+  /**
+   * Create a client for the model; throws when credentials are missing
+   */
+  create(config: AIProviderConfig, model: ModelConfig, credentials: ProviderCredentials): AIProvider;
+
+  /**
+   * Model names the provider serves, asked from its API
+   */
+  listModels?(credentials: ProviderCredentials): Promise<string[]>;
+}
+
+/**
+ * Maps `ModelConfig.provider` to the factory building its client.
+ * Clients are cached per model and generation settings, so switching back and
+ * forth between models reuses the same SDK instance. A provider's clients and
+ * model list are dropped when its credentials change.
+ */
+export class ProviderRegistry {
+  private factories = new Map<string, ProviderFactory>();
+  private clients = new Map<string, { provider: string; client: AIProvider }>();
+  private modelLists = new Map<string, Promise<string[]>>();
+  private credentials = new Map<string, ProviderCredentials>();
+
+  /**
+   * Register (or replace) the factory for a provider
+   */
+  register(provider: string, factory: ProviderFactory): void {
+    this.factories.set(provider, factory);
+    this.clearCache(provider);
+  }
+
+  has(provider: string): boolean {
+    return this.factories.has(provider);
+  }
+
+  /**
+   * Registered provider IDs
+   */
+  providers(): string[] {
+    return Array.from(this.factories.keys());
+  }
+
+  /**
+   * Check the model against the list the provider serves. Throws
+   * UnknownModelError when it is missing; when the list cannot be fetched
+   * (offline, no key yet) the model is let through and the request itself
+   * reports the problem.
+   */
+  async validateModel(model: ModelConfig, credentials: ProviderCredentials): Promise<void> {
+    const factory = this.factories.get(model.provider);
+    if (!factory) {
+      throw new UnknownProviderError(model.provider);
+    }
+    if (!factory.listModels) return;
+
+    this.useCredentials(model.provider, credentials);
+    let list = this.modelLists.get(model.provider);
+    if (!list) {
+      list = factory.listModels(credentials);
+      this.modelLists.set(model.provider, list);
+    }
+
+    let served: string[];
+    try {
+      served = await list;
+    } catch (error) {
+      this.modelLists.delete(model.provider);
+      console.warn(`[ProviderRegistry] Could not list ${model.provider} models:`, error);
+      return;
+    }
+    if (!served.includes(model.model)) {
+      throw new UnknownModelError(model.provider, model.model);
+    }
+  }
+
+  /**
+   * Get the client for a model, creating it on first use
+   */
+  resolve(
+    model: ModelConfig,
+    credentials: ProviderCredentials,
+    temperature?: number,
+    maxTokens?: number
+  ): AIProvider {
+    const factory = this.factories.get(model.provider);
+    if (!factory) {
+      throw new UnknownProviderError(model.provider);
+    }
+
+    const config: AIProviderConfig = {
+      apiKey: '',
+      model: model.model,
+      temperature,
+      maxTokens,
+    };
+
+    this.useCredentials(model.provider, credentials);
+    const key = JSON.stringify([model.provider, model.id, temperature, maxTokens]);
+    const cached = this.clients.get(key);
+    if (cached) {
+      return cached.client;
+    }
+
+    const client = factory.create(config, model, credentials);
+    this.clients.set(key, { provider: model.provider, client });
+    return client;
+  }
+
+  /**
+   * Drop cached clients and model lists, e.g. after API keys change
+   */
+  clearCache(provider?: string): void {
+    if (!provider) {
+      this.clients.clear();
+      this.modelLists.clear();
+      this.credentials.clear();
+      return;
+    }
+    for (const [key, entry] of this.clients) {
+      if (entry.provider === provider) {
+        this.clients.delete(key);
+      }
+    }
+    this.modelLists.delete(provider);
+    this.credentials.delete(provider);
+  }
+
+  /**
+   * Forget what was cached for a provider under different credentials
+   */
+  private useCredentials(provider: string, credentials: ProviderCredentials): void {
+    const previous = this.credentials.get(provider);
+    if (previous && !sameCredentials(previous, credentials)) {
+      this.clearCache(provider);
+    }
+    this.credentials.set(provider, { ...credentials });
+  }
+}
+
+function sameCredentials(a: ProviderCredentials, b: ProviderCredentials): boolean {
+  const keys = new Set([...Object.keys(a), ...Object.keys(b)] as (keyof ProviderCredentials)[]);
+  return [...keys].every((key) => a[key] === b[key]);
+}
@@ -0,0 +1,161 @@
+import { ChatMessage } from '@/types/chat';
+import { ToolDefinition } from '../ToolRegistry';
+import type { AIProvider, RequestOptions, StreamChunk } from './base';
+import { withRetry } from './retryUtils';
+
+// ===========================
+// Retry Policy
+// ===========================
+
+export interface RetryPolicy {
+  /** Retries after the first attempt; 0 disables retrying */
+  maxRetries: number;
+  /** Delay before the first retry, doubled (with jitter) for each further one */
+  baseDelayMs: number;
+  /** Longest wait between attempts, also caps Retry-After */
+  maxDelayMs: number;
+}
+
+export const DEFAULT_RETRY_POLICY: RetryPolicy = {
+  maxRetries: 3,
+  baseDelayMs: 1000,
+  maxDelayMs: 30000,
+};
+
+/**
+ * Retry and failover details of a response, kept in `metadata.agent`
+ */
+export interface AgentMetadata {
+  /** Model ID that produced the response */
+  model: string;
+  /** Failed attempts before the response, across both models */
+  retries: number;
+  /** Primary model ID when the response came from the fallback */
+  failedOverFrom?: string;
+  /** Error that made the request fail over */
+  failoverReason?: string;
+}
+
+export interface FailoverTarget {
+  provider: AIProvider;
+  /** Model ID, as in AgentConfig.model */
+  model: string;
+}
+
+/**
+ * A stream failed after part of the response was shown. Retrying or failing
+ * over would repeat that output, so the error is passed on as is.
+ */
+class StreamInterruptedError extends Error {
+  readonly retryable = false;
+
+  constructor(readonly cause: unknown) {
+    super(cause instanceof Error ? cause.message : String(cause));
+    this.name = 'StreamInterruptedError';
+  }
+}
+
+// ===========================
+// Resilient Provider
+// ===========================
+
+/**
+ * Wraps a provider with the retry policy (jittered exponential backoff,
+ * honoring Retry-After) for 429, 5xx and network errors, and fails over to a
+ * secondary model once the primary's retries are used up.
+ * A cancelled request is never retried.
+ */
+export class ResilientProvider implements AIProvider {
+  constructor(
+    private primary: FailoverTarget,
+    private policy: RetryPolicy = DEFAULT_RETRY_POLICY,
+    private fallback?: FailoverTarget
+  ) {}
+
+  async sendMessage(
+    messages: ChatMessage[],
+    tools: ToolDefinition[],
+    signal?: AbortSignal,
+    options?: RequestOptions
+  ): Promise<ChatMessage> {
+    return this.run((provider) => provider.sendMessage(messages, tools, signal, options), signal);
+  }
+
+  async streamMessage(
+    messages: ChatMessage[],
+    tools: ToolDefinition[],
+    onChunk: (chunk: StreamChunk) => void,
+    signal?: AbortSignal,
+    options?: RequestOptions
+  ): Promise<ChatMessage> {
+    return this.run(async (provider) => {
+      let streamed = false;
+      try {
+        return await provider.streamMessage(
+          messages,
+          tools,
+          (chunk) => {
+            streamed = true;
+            onChunk(chunk);
+          },
+          signal,
+          options
+        );
+      } catch (error) {
+        throw streamed ? new StreamInterruptedError(error) : error;
+      }
+    }, signal);
+  }
+
+  private async run(
+    call: (provider: AIProvider) => Promise<ChatMessage>,
+    signal?: AbortSignal
+  ): Promise<ChatMessage> {
+    let retries = 0;
+    const attempt = (target: FailoverTarget) => withRetry(() => call(target.provider), {
+      ...this.policy,
+      signal,
+      onRetry: (n, error, delayMs) => {
+        retries++;
+        console.warn(
+          `[ResilientProvider] ${target.model} attempt ${n} failed (${error.message}), retrying in ${delayMs}ms`
+        );
+      },
+    });
+
+    let response: ChatMessage;
+    let metadata: AgentMetadata;
+    try {
+      response = await attempt(this.primary);
+      metadata = { model: this.primary.model, retries };
+    } catch (error) {
+      if (error instanceof StreamInterruptedError) throw error.cause;
+      if (!this.fallback || signal?.aborted) throw error;
+
+      const reason = error instanceof Error ? error.message : String(error);
+      console.warn(`[ResilientProvider] ${this.primary.model} failed (${reason}), failing over to ${this.fallback.model}`);
+      retries++;
+      try {
+        response = await attempt(this.fallback);
+      } catch (fallbackError) {
+        throw fallbackError instanceof StreamInterruptedError ? fallbackError.cause : fallbackError;
+      }
+      metadata = {
+        model: this.fallback.model,
+        retries,
+        failedOverFrom: this.primary.model,
+        failoverReason: reason,
+      };
+    }
+
+    response.metadata = { ...response.metadata, agent: metadata };
+    return response;
+  }
+}
+
+/**
+ * Retry and failover details of a response wrapped by ResilientProvider
+ */
+export function getAgentMetadata(message: ChatMessage): AgentMetadata | undefined {
+  return message.metadata?.agent as AgentMetadata | undefined;
+}
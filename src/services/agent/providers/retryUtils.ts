@@ -11,9 +11,21 @@
 
 /**
  * Sleep for a specified number of milliseconds
+ * Rejects with the abort reason as soon as `signal` aborts
  */
-export function sleep(ms: number): Promise<void> {
-    return new Promise(resolve => setTimeout(resolve, ms));
+export function sleep(ms: number, signal?: AbortSignal): Promise<void> {
+    if (signal?.aborted) return Promise.reject(signal.reason);
+    return new Promise((resolve, reject) => {
+        const onAbort = () => {
+            clearTimeout(timer);
+            reject(signal!.reason);
+        };
+        const timer = setTimeout(() => {
+            signal?.removeEventListener('abort', onAbort);
+            resolve();
+        }, ms);
+        signal?.addEventListener('abort', onAbort, { once: true });
+    });
 }
 
 /**
@@ -39,6 +51,8 @@ export interface RetryOptions {
     retryableErrors: string[];
     /** Callback for logging retry attempts */
     onRetry?: (attempt: number, error: Error, nextDelayMs: number) => void;
+    /** Stops retrying (and waiting) once aborted */
+    signal?: AbortSignal;
 }
 
 export const DEFAULT_RETRY_OPTIONS: RetryOptions = {
@@ -69,9 +83,17 @@ export const DEFAULT_RETRY_OPTIONS: RetryOptions = {
 // ===========================
 
 /**
- * Check if an error is retryable based on configured patterns
+ * Check if an error is retryable: errors may opt out with `retryable: false`,
+ * errors with an HTTP status retry on 408, 409, 429 and 5xx, and others are
+ * matched against the configured patterns
  */
 export function isRetryableError(error: Error, patterns: string[]): boolean {
+    const { retryable, status } = error as { retryable?: boolean; status?: unknown };
+    if (retryable === false) return false;
+    if (typeof status === 'number') {
+        return status === 408 || status === 409 || status === 429 || status >= 500;
+    }
+
     const errorString = error.message.toLowerCase() + (error.name?.toLowerCase() || '');
 
     return patterns.some(pattern =>
@@ -111,11 +133,15 @@ export async function withRetry<T>(
     let lastError: Error = new Error('Unknown error');
 
     for (let attempt = 0; attempt <= config.maxRetries; attempt++) {
+        if (config.signal?.aborted) throw config.signal.reason;
         try {
             return await fn();
         } catch (error) {
             lastError = error instanceof Error ? error : new Error(String(error));
 
+            // A cancelled request is not a failure
+            if (config.signal?.aborted) throw error;
+
             // Check if we've exhausted retries
             if (attempt >= config.maxRetries) {
                 console.error(`[RetryUtils] All ${config.maxRetries} retries exhausted`, lastError);
@@ -128,10 +154,13 @@ export async function withRetry<T>(
                 throw lastError;
             }
 
-            // Calculate delay with exponential backoff
+            // Calculate delay with exponential backoff, or wait as long as
+            // the server asked to with Retry-After
             const exponentialDelay = config.baseDelayMs * Math.pow(2, attempt);
-            const cappedDelay = Math.min(exponentialDelay, config.maxDelayMs);
-            const delayWithJitter = addJitter(cappedDelay);
+            const retryAfterMs = (lastError as { retryAfterMs?: number }).retryAfterMs;
+            const delayWithJitter = retryAfterMs !== undefined
+                ? Math.min(Math.max(retryAfterMs, 0), config.maxDelayMs)
+                : addJitter(Math.min(exponentialDelay, config.maxDelayMs));
 
             console.warn(
                 `[RetryUtils] Attempt ${attempt + 1}/${config.maxRetries} failed. ` +
@@ -143,7 +172,7 @@ export async function withRetry<T>(
             config.onRetry?.(attempt + 1, lastError, delayWithJitter);
 
             // Wait before retrying
-            await sleep(delayWithJitter);
+            await sleep(delayWithJitter, config.signal);
         }
     }
 
@@ -8,8 +8,9 @@
 
 import {
     createProvider,
+    validateModel,
     ProviderCredentials,
-    AVAILABLE_MODELS,
+    getModelConfig,
     ModelConfig,
 } from '@/services/agent/providers';
 import type { AIProvider } from '@/services/agent/providers/base';
@@ -70,7 +71,7 @@ export function selectModelForTask(task: TaskType): string {
  * Get model configuration by ID
  */
 export function getModel(modelId: string): ModelConfig | undefined {
-    return AVAILABLE_MODELS.find(m => m.id === modelId);
+    return getModelConfig(modelId);
 }
 
 /**
@@ -117,8 +118,9 @@ export function getRecommendedModel(options: {
 export function createInferenceAdapter(options: InferenceOptions) {
     let provider: AIProvider | null = null;
 
-    const getProvider = () => {
+    const getProvider = async () => {
         if (!provider) {
+            await validateModel(options.modelId, options.credentials);
             provider = createProvider(
                 options.modelId,
                 options.credentials,
@@ -133,7 +135,7 @@ export function createInferenceAdapter(options: InferenceOptions) {
         messages: ChatMessage[],
         tools: ToolDefinition[]
     ): Promise<InferenceResult> => {
-        const p = getProvider();
+        const p = await getProvider();
         const response = await p.sendMessage(messages, tools);
 
         return {
@@ -157,12 +159,17 @@ export function createStreamingAdapter(options: InferenceOptions) {
         options.temperature,
         options.maxTokens
     );
+    let validated: Promise<void> | null = null;
 
     return async (
         messages: ChatMessage[],
         tools: ToolDefinition[],
         onChunk: (chunk: { type: string; content?: string }) => void
     ): Promise<InferenceResult> => {
+        if (!validated) {
+            validated = validateModel(options.modelId, options.credentials);
+        }
+        await validated;
         const response = await provider.streamMessage(messages, tools, (chunk) => {
             onChunk({
                 type: chunk.type,
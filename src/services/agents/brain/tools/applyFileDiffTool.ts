@@ -44,7 +44,7 @@ Use this tool when you want to:
 
             let originalContent: string;
             try {
-                originalContent = await invoke<string>('get_file_content', { path });
+                originalContent = (await invoke<{ content: string }>('get_file_content', { path })).content;
             } catch {
                 // File might not exist, try alternate command
                 try {
@@ -90,6 +90,10 @@ export abstract class BaseAgent {
         const filesModified: string[] = [];
         let output = '';
 
+        // File reads within this run share a cache, byte budget and rate limit
+        const runId = `${this.config.name}_${Date.now()}_${Math.random().toString(36).slice(2, 8)}`;
+        this.executor.beginRun(runId);
+
         try {
             // Build messages
             const messages: Message[] = [
@@ -151,7 +155,7 @@ export abstract class BaseAgent {
                         toolsUsed.push(name);
 
                         // Execute tool
-                        const call = createToolCall(name, (args || {}) as Record<string, unknown>);
+                        const call = createToolCall(name, (args || {}) as Record<string, unknown>, runId);
                         const execution = await this.executor.execute(call);
 
                         if (execution.result?.success && name.includes('file') && name !== 'read_file') {
@@ -192,6 +196,8 @@ export abstract class BaseAgent {
                 filesModified,
                 error: error instanceof Error ? error.message : String(error),
             };
+        } finally {
+            this.executor.endRun(runId);
         }
     }
 
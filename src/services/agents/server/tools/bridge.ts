@@ -1299,6 +1299,7 @@ export function createConfiguredExecutor(workspace?: string): ToolExecutor {
         onToolError: (call, error) => {
             console.error(`[Tool] Error: ${call.tool}:`, error.message);
         },
+        fileModified: async (file) => (await fs.stat(resolvePath(file))).mtimeMs,
     });
 
     registerToolHandlers(executor);
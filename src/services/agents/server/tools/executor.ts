@@ -4,6 +4,7 @@
  * Executes tools with support for:
  * - Parallel execution (respecting tool.parallel flag)
  * - Caching (respecting tool.cacheable flag)
+ * - Per-run file read cache, byte budget and rate limit (see beginRun)
  * - Timeout handling
  * - Retry logic
  */
@@ -26,6 +27,21 @@ export interface ExecutorConfig {
     onToolStart?: (call: ToolCall) => void;
     onToolComplete?: (execution: ToolExecution) => void;
     onToolError?: (call: ToolCall, error: Error) => void;
+    /** Last-modified time (ms) of a file; enables the per-run read cache */
+    fileModified?: (path: string) => Promise<number | undefined>;
+}
+
+export interface RunOptions {
+    byteBudget: number;          // Bytes of file content the run may read
+    maxReadsPerMinute: number;   // Further reads wait until the window moves on
+}
+
+export interface RunStats {
+    runId: string;
+    cacheHits: number;
+    cacheMisses: number;
+    bytesRead: number;
+    byteBudget: number;
 }
 
 export interface BatchOptions {
@@ -94,6 +110,34 @@ class LRUCache<T> {
     }
 }
 
+// ===========================
+// Run State
+// ===========================
+
+/** Tools returning file contents, read from `path` or `paths` */
+const READ_TOOLS = new Set(['read_file', 'fs_batch_read']);
+
+const DEFAULT_RUN_OPTIONS: RunOptions = {
+    byteBudget: 4 * 1024 * 1024,
+    maxReadsPerMinute: 120,
+};
+
+const RATE_WINDOW_MS = 60000;
+
+interface RunState {
+    options: RunOptions;
+    /** Read results by call and the modification times of the files read */
+    reads: Map<string, ToolResult>;
+    readTimes: number[];
+    bytesRead: number;
+    hits: number;
+    misses: number;
+}
+
+function resultBytes(result: ToolResult): number {
+    return new TextEncoder().encode(JSON.stringify(result.data ?? null)).length;
+}
+
 // ===========================
 // Tool Executor
 // ===========================
@@ -102,6 +146,7 @@ export class ToolExecutor {
     private config: ExecutorConfig;
     private handlers: Map<string, ToolHandler>;
     private cache: LRUCache<ToolResult>;
+    private runs = new Map<string, RunState>();
 
     constructor(config: Partial<ExecutorConfig> = {}) {
         this.config = {
@@ -130,6 +175,38 @@ export class ToolExecutor {
         }
     }
 
+    /**
+     * Start an agent run. File reads from calls carrying its `runId` are
+     * cached by path and modification time, charged to the run's byte
+     * budget and rate limited.
+     */
+    beginRun(runId: string, options: Partial<RunOptions> = {}): void {
+        this.runs.set(runId, {
+            options: { ...DEFAULT_RUN_OPTIONS, ...options },
+            reads: new Map(),
+            readTimes: [],
+            bytesRead: 0,
+            hits: 0,
+            misses: 0,
+        });
+    }
+
+    /**
+     * End a run, dropping its read cache
+     */
+    endRun(runId: string): RunStats | undefined {
+        const run = this.runs.get(runId);
+        if (!run) return undefined;
+        this.runs.delete(runId);
+        return {
+            runId,
+            cacheHits: run.hits,
+            cacheMisses: run.misses,
+            bytesRead: run.bytesRead,
+            byteBudget: run.options.byteBudget,
+        };
+    }
+
     /**
      * Execute a single tool
      */
@@ -148,6 +225,11 @@ export class ToolExecutor {
             return execution;
         }
 
+        const run = call.runId ? this.runs.get(call.runId) : undefined;
+        if (run && READ_TOOLS.has(schema.name)) {
+            return this.executeRead(call, execution, run);
+        }
+
         // Check cache
         if (this.config.enableCache && schema.cacheable) {
             const cacheKey = this.getCacheKey(call);
@@ -160,13 +242,28 @@ export class ToolExecutor {
             }
         }
 
-        // Get handler
+        await this.runHandler(call, execution);
+
+        // Cache successful results
+        const result = execution.result;
+        if (result?.success && this.config.enableCache && schema.cacheable) {
+            const cacheKey = this.getCacheKey(call);
+            this.cache.set(cacheKey, result, schema.cacheTimeout || 30000);
+        }
+
+        return execution;
+    }
+
+    /**
+     * Run the call's handler with its timeout, recording the outcome on `execution`
+     */
+    private async runHandler(call: ToolCall, execution: ToolExecution): Promise<void> {
         const handler = this.handlers.get(call.tool);
         if (!handler) {
             execution.status = 'error';
             execution.result = { success: false, error: `No handler registered for tool: ${call.tool}` };
             execution.endTime = Date.now();
-            return execution;
+            return;
         }
 
         // Execute with timeout
@@ -174,7 +271,7 @@ export class ToolExecutor {
         this.config.onToolStart?.(call);
 
         try {
-            const timeout = schema.timeout || this.config.defaultTimeout;
+            const timeout = getToolByName(call.tool)?.timeout || this.config.defaultTimeout;
             const result = await this.executeWithTimeout(handler, call.args, timeout);
 
             execution.status = result.success ? 'success' : 'error';
@@ -182,12 +279,6 @@ export class ToolExecutor {
             execution.endTime = Date.now();
             execution.result.duration = execution.endTime - execution.startTime!;
 
-            // Cache successful results
-            if (result.success && this.config.enableCache && schema.cacheable) {
-                const cacheKey = this.getCacheKey(call);
-                this.cache.set(cacheKey, result, schema.cacheTimeout || 30000);
-            }
-
             this.config.onToolComplete?.(execution);
         } catch (error) {
             execution.status = 'error';
@@ -198,10 +289,88 @@ export class ToolExecutor {
             execution.endTime = Date.now();
             this.config.onToolError?.(call, error instanceof Error ? error : new Error(String(error)));
         }
+    }
 
+    /**
+     * A file read within a run: rate limited, served from the run's cache
+     * while the files are unchanged, and charged to its byte budget
+     */
+    private async executeRead(call: ToolCall, execution: ToolExecution, run: RunState): Promise<ToolExecution> {
+        await this.throttleRead(run);
+
+        const key = await this.readCacheKey(call);
+        let result = key ? run.reads.get(key) : undefined;
+        const cached = result !== undefined;
+        if (result) {
+            run.hits++;
+        } else {
+            await this.runHandler(call, execution);
+            result = execution.result;
+            if (!result?.success) return execution;
+            run.misses++;
+            if (key) run.reads.set(key, result);
+        }
+
+        const bytes = resultBytes(result);
+        const remaining = run.options.byteBudget - run.bytesRead;
+        if (bytes > remaining) {
+            execution.status = 'error';
+            execution.result = {
+                success: false,
+                error: `Read budget exceeded: ${bytes} bytes requested, ${remaining} of ${run.options.byteBudget} bytes left in this run. Read line ranges or fewer files.`,
+            };
+            execution.endTime = Date.now();
+            return execution;
+        }
+        run.bytesRead += bytes;
+
+        execution.status = 'success';
+        execution.result = { ...result, cached, budgetRemaining: remaining - bytes };
+        if (cached) {
+            execution.endTime = Date.now();
+        }
         return execution;
     }
 
+    /**
+     * Wait until the run may read again under its per-minute limit
+     */
+    private async throttleRead(run: RunState): Promise<void> {
+        for (;;) {
+            const now = Date.now();
+            while (run.readTimes.length > 0 && run.readTimes[0] <= now - RATE_WINDOW_MS) {
+                run.readTimes.shift();
+            }
+            if (run.readTimes.length < run.options.maxReadsPerMinute) {
+                run.readTimes.push(now);
+                return;
+            }
+            const wait = run.readTimes[0] + RATE_WINDOW_MS - now;
+            await new Promise(resolve => setTimeout(resolve, wait));
+        }
+    }
+
+    /**
+     * Cache key of a read: the call plus the modification time of every file
+     * it reads. Undefined (not cached) when a time is unavailable.
+     */
+    private async readCacheKey(call: ToolCall): Promise<string | undefined> {
+        const fileModified = this.config.fileModified;
+        if (!fileModified) return undefined;
+
+        const { path, paths } = call.args as { path?: unknown; paths?: unknown };
+        const files = Array.isArray(paths) ? paths : [path];
+        if (!files.every((file): file is string => typeof file === 'string')) return undefined;
+
+        try {
+            const times = await Promise.all(files.map(file => fileModified(file)));
+            if (times.some(time => time === undefined)) return undefined;
+            return `${this.getCacheKey(call)}@${times.join(',')}`;
+        } catch {
+            return undefined;
+        }
+    }
+
     /**
      * Execute multiple tools in batch
      */
@@ -398,11 +567,12 @@ export function createToolExecutor(config?: Partial<ExecutorConfig>): ToolExecut
 /**
  * Helper to create a tool call
  */
-export function createToolCall(tool: string, args: Record<string, unknown> = {}): ToolCall {
+export function createToolCall(tool: string, args: Record<string, unknown> = {}, runId?: string): ToolCall {
     return {
         id: `${tool}_${Date.now()}_${Math.random().toString(36).slice(2, 8)}`,
         tool,
         args,
         timestamp: Date.now(),
+        runId,
     };
 }
@@ -56,6 +56,7 @@ export interface ToolResult<T = unknown> {
     error?: string;
     duration?: number;       // Execution time in ms
     cached?: boolean;        // Was result from cache
+    budgetRemaining?: number; // Bytes left in the run's read budget (file reads within a run)
 }
 
 export interface ToolCall {
@@ -63,6 +64,7 @@ export interface ToolCall {
     tool: string;
     args: Record<string, unknown>;
     timestamp: number;
+    runId?: string;          // Agent run the call belongs to (see ToolExecutor.beginRun)
 }
 
 export interface ToolExecution extends ToolCall {
@@ -0,0 +1,95 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { ideActions, subscribeIDEState } from '@/stores/ideStore';
+
+const isTauri = typeof window !== 'undefined' && '__TAURI__' in window;
+
+/**
+ * A request from the command line (see src-tauri/src/cli.rs). Requests made
+ * with `--wait` carry a token; the waiting process returns once every file
+ * the request opened has been closed.
+ */
+type CliRequest =
+  | { action: 'open'; paths: string[]; folders: string[]; waitToken?: string | null }
+  | { action: 'diff'; left: string; right: string; waitToken?: string | null }
+  | { action: 'merge'; local: string; remote: string; base: string; result: string; waitToken?: string | null };
+
+/**
+ * Handle the launch arguments and listen for later launches
+ */
+export async function initializeCliService(): Promise<void> {
+  if (!isTauri) return;
+
+  await listen<CliRequest>('cli:open', (event) => {
+    void handleCliRequest(event.payload);
+  });
+
+  // The launch arguments arrived before anything was listening
+  const pending = await invoke<CliRequest[]>('cli_take_pending');
+  for (const request of pending) {
+    await handleCliRequest(request);
+  }
+}
+
+function fileName(path: string): string {
+  return path.replace(/\\/g, '/').split('/').pop() || path;
+}
+
+async function openPaths(paths: string[], opened: string[]): Promise<void> {
+  for (const path of paths) {
+    await ideActions.openFile({ name: fileName(path), path, is_directory: false });
+    opened.push(path);
+  }
+}
+
+async function handleCliRequest(request: CliRequest): Promise<void> {
+  const opened: string[] = [];
+  try {
+    switch (request.action) {
+      case 'open': {
+        const folder = request.folders[request.folders.length - 1];
+        if (folder) {
+          await ideActions.openWorkspace({ name: fileName(folder), path: folder, type: 'folder' });
+        }
+        await openPaths(request.paths, opened);
+        break;
+      }
+      case 'diff':
+        await openPaths([request.left, request.right], opened);
+        break;
+      case 'merge':
+        // The result holds the conflict markers to resolve
+        await openPaths([request.result], opened);
+        break;
+    }
+  } catch (error) {
+    console.error('[CLI] Failed to open request:', error);
+  }
+
+  if (request.waitToken) {
+    releaseWhenClosed(request.waitToken, opened);
+  }
+}
+
+/**
+ * Signal the waiting process once none of `paths` is open in the editor
+ */
+function releaseWhenClosed(waitToken: string, paths: string[]): void {
+  const release = () => {
+    invoke('cli_wait_done', { waitToken }).catch((error) => {
+      console.error('[CLI] Failed to release waiting process:', error);
+    });
+  };
+  const anyOpen = () => ideActions.getState().openFiles.some((file) => paths.includes(file.path));
+
+  if (!anyOpen()) {
+    release();
+    return;
+  }
+  const unsubscribe = subscribeIDEState(() => {
+    if (!anyOpen()) {
+      unsubscribe();
+      release();
+    }
+  });
+}
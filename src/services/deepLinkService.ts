@@ -0,0 +1,109 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { ideActions } from '@/stores/ideStore';
+import { editorActions } from '@/stores/editorStore';
+import { toastActions } from '@/stores/toastStore';
+
+const isTauri = typeof window !== 'undefined' && '__TAURI__' in window;
+
+/**
+ * A validated `rainy://` link (see src-tauri/src/deep_link.rs). Paths are
+ * absolute and canonical; lines and columns are 1-based.
+ */
+type DeepLinkAction =
+  | { action: 'open-file'; path: string; line?: number | null; column?: number | null; workspaceRoot?: string | null }
+  | { action: 'open-workspace'; path: string }
+  | { action: 'open-diff'; path: string; base?: string | null; head?: string | null; workspaceRoot?: string | null };
+
+interface RejectedDeepLink {
+  url: string;
+  reason: string;
+  /** Set when the only problem is that this workspace is not trusted yet */
+  untrustedWorkspace?: string | null;
+}
+
+/**
+ * Open links as they arrive, and the one the app was launched with
+ */
+export async function initializeDeepLinkService(): Promise<void> {
+  if (!isTauri) return;
+
+  await listen<DeepLinkAction>('deep-link:open', (event) => {
+    void handleDeepLink(event.payload);
+  });
+  await listen<RejectedDeepLink>('deep-link:rejected', (event) => {
+    showRejected(event.payload);
+  });
+
+  // The launch link arrived before anything was listening
+  const pending = await invoke<DeepLinkAction[]>('deep_link_take_pending');
+  for (const action of pending) {
+    await handleDeepLink(action);
+  }
+}
+
+function fileName(path: string): string {
+  return path.replace(/\\/g, '/').split('/').pop() || path;
+}
+
+async function ensureWorkspace(root?: string | null): Promise<void> {
+  if (!root || ideActions.getState().workspace?.path === root) return;
+  await ideActions.openWorkspace({ name: fileName(root), path: root, type: 'folder' });
+}
+
+async function handleDeepLink(link: DeepLinkAction): Promise<void> {
+  try {
+    switch (link.action) {
+      case 'open-workspace':
+        await ensureWorkspace(link.path);
+        break;
+      case 'open-file':
+        await ensureWorkspace(link.workspaceRoot);
+        await ideActions.openFile({ name: fileName(link.path), path: link.path, is_directory: false });
+        if (link.line) {
+          editorActions.goToPosition(link.line, link.column ?? 1);
+        }
+        break;
+      case 'open-diff':
+        // Like `rainy --diff`, open the file; the editor gutter shows its changes
+        await ensureWorkspace(link.workspaceRoot);
+        await ideActions.openFile({ name: fileName(link.path), path: link.path, is_directory: false });
+        break;
+    }
+  } catch (error) {
+    console.error('[DeepLink] Failed to open link:', error);
+    toastActions.error('Deep Link', `Could not open ${link.path}: ${String(error)}`);
+  }
+}
+
+/**
+ * Explain a refused link; an untrusted workspace can be trusted from here
+ */
+function showRejected(rejected: RejectedDeepLink): void {
+  const root = rejected.untrustedWorkspace;
+  if (!root) {
+    toastActions.warning('Deep Link Refused', rejected.reason);
+    return;
+  }
+
+  toastActions.warning('Untrusted Workspace', `A link asked to open ${root}. Trust this folder to open links into it.`, {
+    duration: 0,
+    action: {
+      label: 'Trust and Open',
+      onClick: () => {
+        void trustAndOpen(root, rejected.url);
+      },
+    },
+  });
+}
+
+async function trustAndOpen(root: string, url: string): Promise<void> {
+  try {
+    await invoke('deep_link_trust_workspace', { path: root });
+    const link = await invoke<DeepLinkAction>('deep_link_parse', { url });
+    await handleDeepLink(link);
+  } catch (error) {
+    console.error('[DeepLink] Failed to trust workspace:', error);
+    toastActions.error('Deep Link', String(error));
+  }
+}
@@ -217,7 +217,7 @@ export async function showErrorMessage(message: string, ...items: string[]): Pro
  */
 export async function readFile(uri: string): Promise<Uint8Array> {
   try {
-    const content = await invoke<string>('get_file_content', { path: uri });
+    const { content } = await invoke<{ content: string }>('get_file_content', { path: uri });
     return new TextEncoder().encode(content);
   } catch (error) {
     console.error('[chatbotAPI] Error reading file:', error);
@@ -87,7 +87,7 @@ export class WorkspaceFS {
     const path = this.uriToPath(uri);
 
     try {
-      const content = await invoke<string>('get_file_content', { path });
+      const { content } = await invoke<{ content: string }>('get_file_content', { path });
       return content;
     } catch (error) {
       console.error(`[WorkspaceFS] Error reading file: ${path}`, error);
@@ -88,7 +88,7 @@ class ProjectContextService {
     // Load tsconfig.json
     try {
       const tsconfigPath = `${workspacePath}/tsconfig.json`;
-      const tsconfigContent = await invoke<string>('get_file_content', { path: tsconfigPath });
+      const { content: tsconfigContent } = await invoke<{ content: string }>('get_file_content', { path: tsconfigPath });
       context.tsconfig = this.parseTSConfig(tsconfigContent);
       console.info('[ProjectContext] Loaded tsconfig.json');
     } catch (error) {
@@ -98,7 +98,7 @@ class ProjectContextService {
     // Load package.json
     try {
       const packageJsonPath = `${workspacePath}/package.json`;
-      const packageJsonContent = await invoke<string>('get_file_content', { path: packageJsonPath });
+      const { content: packageJsonContent } = await invoke<{ content: string }>('get_file_content', { path: packageJsonPath });
       context.packageJson = JSON.parse(packageJsonContent);
       console.info('[ProjectContext] Loaded package.json');
     } catch (error) {
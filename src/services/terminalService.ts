@@ -10,6 +10,7 @@
 
 import { invoke } from "@tauri-apps/api/core";
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { ask } from "@tauri-apps/plugin-dialog";
 
 export type SessionState = "starting" | "active" | "exited" | "error";
 
@@ -47,6 +48,14 @@ interface TerminalErrorEvent {
   error: string;
 }
 
+/** A program in the terminal asked to write the clipboard (OSC 52) */
+interface TerminalClipboardRequestEvent {
+  id: string;
+  requestId: string;
+  preview: string;
+  length: number;
+}
+
 type DataCallback = (id: string, data: string) => void;
 type StateCallback = (id: string, state: SessionState) => void;
 type ExitCallback = (id: string) => void;
@@ -62,6 +71,7 @@ class TerminalService {
   private unlistenState: UnlistenFn | null = null;
   private unlistenExit: UnlistenFn | null = null;
   private unlistenError: UnlistenFn | null = null;
+  private unlistenClipboard: UnlistenFn | null = null;
 
   private initialized = false;
   private resizeDebounceTimers = new Map<string, NodeJS.Timeout>();
@@ -125,6 +135,13 @@ class TerminalService {
         });
       });
 
+      this.unlistenClipboard = await listen<TerminalClipboardRequestEvent>(
+        "terminal/clipboard-request",
+        (event) => {
+          void this.answerClipboardRequest(event.payload);
+        }
+      );
+
       this.initialized = true;
       console.log("TerminalService initialized successfully");
     } catch (error) {
@@ -144,6 +161,7 @@ class TerminalService {
       this.unlistenState?.();
       this.unlistenExit?.();
       this.unlistenError?.();
+      this.unlistenClipboard?.();
 
       this.dataCallbacks.clear();
       this.stateCallbacks.clear();
@@ -167,6 +185,28 @@ class TerminalService {
     }
   }
 
+  /**
+   * Ask the user whether a terminal program may set the clipboard. The answer
+   * applies to the rest of the session so the prompt is not repeated.
+   */
+  private async answerClipboardRequest(request: TerminalClipboardRequestEvent): Promise<void> {
+    const more = request.length > request.preview.length ? "…" : "";
+    const allow = await ask(
+      `A program in the terminal wants to copy ${request.length} characters to the clipboard:\n\n${request.preview}${more}\n\nAllow clipboard writes from this terminal?`,
+      { title: "Terminal Clipboard", kind: "warning", okLabel: "Allow", cancelLabel: "Deny" }
+    );
+    try {
+      await invoke("terminal_resolve_clipboard_request", {
+        requestId: request.requestId,
+        allow,
+        remember: true,
+      });
+    } catch (error) {
+      // Superseded by a newer write, or the terminal has closed
+      console.warn("Clipboard request was not applied:", error);
+    }
+  }
+
   // Event listener registration
   onData(callback: DataCallback): () => void {
     this.dataCallbacks.add(callback);
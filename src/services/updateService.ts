@@ -29,7 +29,7 @@ export async function initializeUpdateService(): Promise<void> {
 /**
  * Check for available updates
  */
-export async function checkForUpdates(): Promise<UpdateInfo | null> {
+export async function checkForUpdates(background = false): Promise<UpdateInfo | null> {
   if (!isTauri) {
     console.warn('Update checking is only available in Tauri mode');
     return null;
@@ -41,7 +41,8 @@ export async function checkForUpdates(): Promise<UpdateInfo | null> {
       message: 'Checking for updates...',
     });
 
-    const updateInfo = await invoke<UpdateInfo>('check_for_updates');
+    // Background checks are skipped while update checks are paused
+    const updateInfo = await invoke<UpdateInfo>('check_for_updates', { background });
     updateActions.setUpdateInfo(updateInfo);
 
     if (updateInfo.available) {
@@ -50,6 +51,11 @@ export async function checkForUpdates(): Promise<UpdateInfo | null> {
 
     return updateInfo;
   } catch (error) {
+    if (background) {
+      console.warn('Background update check skipped:', error);
+      updateActions.setUpdateProgress({ status: 'idle' });
+      return null;
+    }
     console.error('Failed to check for updates:', error);
     updateActions.setUpdateProgress({
       status: 'error',
@@ -119,14 +125,14 @@ export function startAutoUpdateCheck(intervalHours: number = 24): void {
 
   // Check immediately if needed
   if (updateActions.shouldAutoCheck()) {
-    checkForUpdates();
+    checkForUpdates(true);
   }
 
   // Set up interval
   const intervalMs = intervalHours * 60 * 60 * 1000;
   autoCheckInterval = window.setInterval(() => {
     if (updateActions.shouldAutoCheck()) {
-      checkForUpdates();
+      checkForUpdates(true);
     }
   }, intervalMs);
 
@@ -1,7 +1,9 @@
 import { useSyncExternalStore } from 'react';
-import { ChatMessage } from '@/types/chat';
+import { invoke } from '@tauri-apps/api/core';
+import { ChatMessage, ToolCall } from '@/types/chat';
 import { agentHistoryService } from '@/services/agent/AgentHistoryService';
 import { DEFAULT_SYSTEM_PROMPT } from '@/services/agent/agentSystemPrompt';
+import { resolvePromptTemplate } from '@/services/agent/PromptTemplates';
 
 // ===========================
 // Types & Interfaces
@@ -13,11 +15,29 @@ export interface AgentSession {
   description?: string;  // Auto-generated description based on conversation
   model: string;
   systemPrompt: string;
+  /** Prompt template the system prompt was created from */
+  template?: string;
   messages: ChatMessage[];
+  /** Ask before running tools that write files or run commands */
+  requireToolApproval?: boolean;
+  /** Spend in USD after which the agent pauses; no limit when unset */
+  budgetUsd?: number;
   createdAt: Date;
   lastMessageAt: Date;
 }
 
+/**
+ * One tool call of a session with the assistant message that made it
+ */
+export interface AgentToolRun {
+  sessionId: string;
+  messageId: string;
+  /** When the requesting message was created */
+  timestamp: Date;
+  /** Arguments, result/error, status and timing of the call */
+  toolCall: ToolCall;
+}
+
 export interface AgentState {
   sessions: AgentSession[];
   activeSessionId: string | null;
@@ -140,7 +160,8 @@ export const agentActions = {
   createSession(
     name: string,
     model: string = 'gemini-flash-latest',  // Gemini 3 Flash (default for Auto mode)
-    systemPrompt: string = DEFAULT_SYSTEM_PROMPT
+    systemPrompt: string = DEFAULT_SYSTEM_PROMPT,
+    template?: string
   ): string {
     const sessionId = crypto.randomUUID();
     const now = new Date();
@@ -150,6 +171,7 @@ export const agentActions = {
       name,
       model,
       systemPrompt,
+      template,
       messages: [
         {
           id: crypto.randomUUID(),
@@ -174,6 +196,19 @@ export const agentActions = {
     return sessionId;
   },
 
+  /**
+   * Create a session whose system prompt is a prompt template, with its
+   * variables filled in from the current workspace and open file
+   */
+  async createSessionFromTemplate(
+    name: string,
+    template: string,
+    model?: string
+  ): Promise<string> {
+    const systemPrompt = await resolvePromptTemplate(template);
+    return this.createSession(name, model, systemPrompt, template);
+  },
+
   /**
    * Delete a session
    */
@@ -194,6 +229,11 @@ export const agentActions = {
 
     // Delete from history
     agentHistoryService.deleteSession(sessionId);
+
+    // Drop the session's file sandbox and its approved outside paths
+    invoke('tool_end_session', { sessionId }).catch((err) => {
+      console.warn('Failed to end agent file sandbox:', err);
+    });
   },
 
   /**
@@ -312,6 +352,56 @@ export const agentActions = {
     }
   },
 
+  /**
+   * Turn tool approval mode on or off for a session
+   */
+  setToolApproval(sessionId: string, enabled: boolean) {
+    let updatedSession: AgentSession | undefined;
+
+    setState((prev) => ({
+      ...prev,
+      sessions: prev.sessions.map((session) => {
+        if (session.id === sessionId) {
+          updatedSession = {
+            ...session,
+            requireToolApproval: enabled,
+          };
+          return updatedSession;
+        }
+        return session;
+      }),
+    }));
+
+    if (updatedSession) {
+      agentHistoryService.saveSession(updatedSession);
+    }
+  },
+
+  /**
+   * Set or clear (undefined) a session's budget in USD
+   */
+  setBudget(sessionId: string, budgetUsd: number | undefined) {
+    let updatedSession: AgentSession | undefined;
+
+    setState((prev) => ({
+      ...prev,
+      sessions: prev.sessions.map((session) => {
+        if (session.id === sessionId) {
+          updatedSession = {
+            ...session,
+            budgetUsd,
+          };
+          return updatedSession;
+        }
+        return session;
+      }),
+    }));
+
+    if (updatedSession) {
+      agentHistoryService.saveSession(updatedSession);
+    }
+  },
+
   /**
    * Update session title and description (auto-generated)
    */
@@ -380,6 +470,21 @@ export const agentActions = {
     return currentState.sessions.find((s) => s.id === sessionId);
   },
 
+  /**
+   * Look up a tool call in a session's history, e.g. to show exactly what
+   * the agent read or wrote in one step
+   */
+  getToolRun(sessionId: string, toolCallId: string): AgentToolRun | undefined {
+    const session = currentState.sessions.find((s) => s.id === sessionId);
+    for (const message of session?.messages ?? []) {
+      const toolCall = message.toolCalls?.find((tc) => tc.id === toolCallId);
+      if (toolCall) {
+        return { sessionId, messageId: message.id, timestamp: message.timestamp, toolCall };
+      }
+    }
+    return undefined;
+  },
+
   /**
    * Get the active session
    */
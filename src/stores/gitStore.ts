@@ -710,11 +710,15 @@ export async function setRemoteUrl(name: string, url: string) {
   await refreshRemotes();
 }
 
-export async function fetch(remote?: string, prune = false) {
+/**
+ * Fetch from a remote; `background` marks an automatic fetch, which is
+ * skipped while auto-fetch is paused
+ */
+export async function fetch(remote?: string, prune = false, background = false) {
   const wsPath = git.workspacePath;
   if (!wsPath) throw new Error("No workspace open");
 
-  await invoke<string>("git_fetch", { path: wsPath, remote, prune });
+  await invoke<string>("git_fetch", { path: wsPath, remote, prune, background });
   await Promise.all([refreshHistory(), refreshBranches()]);
 }
 
@@ -1114,14 +1118,14 @@ export async function getConfig(key: string) {
   const wsPath = git.workspacePath;
   if (!wsPath) throw new Error("No workspace open");
 
-  return await invoke<string>("git_get_config", { path: wsPath, key });
+  return await invoke<string | null>("git_get_config", { path: wsPath, key });
 }
 
 export async function setConfig(key: string, value: string) {
   const wsPath = git.workspacePath;
   if (!wsPath) throw new Error("No workspace open");
 
-  await invoke<string>("git_set_config", { path: wsPath, key, value });
+  await invoke<void>("git_set_config", { path: wsPath, key, value });
 }
 
 // ============================================================================
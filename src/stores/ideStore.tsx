@@ -2,8 +2,8 @@ import React, { createContext, useContext, useEffect, useMemo, useSyncExternalSt
 
 import { loadFromStore, saveToStore } from "./app-store";
 import { invoke } from "@tauri-apps/api/core";
-import { open, message, save } from "@tauri-apps/plugin-dialog";
-import { listen } from "@tauri-apps/api/event";
+import { open, message, save, ask } from "@tauri-apps/plugin-dialog";
+import { getCurrentWindow } from "@tauri-apps/api/window";
 
 type UnlistenFn = () => void;
 type TimeoutHandle = ReturnType<typeof setTimeout>;
@@ -32,6 +32,9 @@ export interface FileNode {
   size?: number;
   modified?: number;
   children_loaded?: boolean; // Indicates if children have been loaded from backend
+  is_symlink?: boolean;
+  symlink_target?: string | null; // Link target as written in the link
+  symlink_loop?: boolean; // Link back into one of its own parent folders; never expanded
 }
 
 export interface OpenFile {
@@ -41,6 +44,8 @@ export interface OpenFile {
   content: string;
   isDirty: boolean;
   isPinned?: boolean;
+  /** Last-modified time (ms) of the version on disk the editor holds */
+  modified?: number;
 }
 
 export interface Workspace {
@@ -117,6 +122,7 @@ const subscribe = (listener: IDEStateListener) => {
 
 // Export getState for non-React contexts (e.g., agent initialization)
 export const getIDEState = getState;
+export const subscribeIDEState = subscribe;
 
 // Hook to subscribe to IDE state changes
 export const useIDEState = () => useSyncExternalStore(subscribe, getState, getState);
@@ -332,9 +338,17 @@ const renameNode = async (node: FileNode, newName: string) => {
   }
 };
 
+interface TrashedItem {
+  originalPath: string;
+  trashId: string | null;
+}
+
+// The last delete, kept so it can be undone
+let lastTrashed: TrashedItem | null = null;
+
 const deleteNode = async (node: FileNode) => {
   try {
-    await invoke("delete_path", { path: node.path });
+    lastTrashed = await invoke<TrashedItem>("delete_to_trash", { path: node.path });
 
     setState((prev) => {
       const removeFromTree = (current: FileNode): FileNode | null => {
@@ -377,6 +391,28 @@ const deleteNode = async (node: FileNode) => {
   }
 };
 
+/**
+ * Restore the last deleted file or folder from the trash; returns false when
+ * there is nothing to restore
+ */
+const undoDelete = async (): Promise<boolean> => {
+  const item = lastTrashed;
+  if (!item?.trashId) return false;
+  try {
+    await invoke("restore_from_trash", { trashId: item.trashId, originalPath: item.originalPath });
+    lastTrashed = null;
+    const workspace = getState().workspace;
+    if (workspace) {
+      await openWorkspace(workspace, false);
+    }
+    return true;
+  } catch (error) {
+    console.error("Failed to restore:", error);
+    await message(`Failed to restore: ${error}`, { title: "Error" });
+    return false;
+  }
+};
+
 const setProjectTree = (tree: FileNode | null) => {
   setState((prev) => ({ ...prev, projectTree: tree }));
 };
@@ -533,6 +569,62 @@ const createNewFile = () => {
   }));
 };
 
+/** Text of a file and its last-modified time (ms) when it was read */
+interface FileContent {
+  content: string;
+  modified: number;
+}
+
+/** What `save_file_content` returns; `modified` is sent with the next save */
+interface SaveFileResult {
+  modified: number;
+}
+
+/**
+ * Write an open file. A file changed on disk since it was loaded is only
+ * overwritten once the user confirms. Returns the new last-modified time, or
+ * null if the user kept the version on disk.
+ */
+const writeOpenFile = async (file: OpenFile, content: string): Promise<number | null> => {
+  try {
+    const result = await invoke<SaveFileResult>("save_file_content", {
+      path: file.path,
+      content,
+      expectedModified: file.modified,
+    });
+    return result.modified;
+  } catch (error) {
+    if (!String(error).includes("modified on disk")) throw error;
+    const overwrite = await ask(
+      `"${file.name}" has changed on disk since it was opened. Overwrite it with your version?`,
+      { title: "File Changed on Disk", kind: "warning" },
+    );
+    if (!overwrite) return null;
+    const result = await invoke<SaveFileResult>("save_file_content", {
+      path: file.path,
+      content,
+      force: true,
+    });
+    return result.modified;
+  }
+};
+
+/**
+ * Re-read an open file from disk, replacing the editor's copy
+ */
+const reloadFile = async (fileId: string) => {
+  const file = getState().openFiles.find((openFile) => openFile.id === fileId);
+  if (!file) return;
+
+  const { content, modified } = await invoke<FileContent>("get_file_content", { path: file.path });
+  setState((prev) => ({
+    ...prev,
+    openFiles: prev.openFiles.map((openFile) =>
+      openFile.id === fileId ? { ...openFile, content, isDirty: false, modified } : openFile,
+    ),
+  }));
+};
+
 const openFile = async (fileNode: FileNode) => {
   const existingFile = getState().openFiles.find((file) => file.path === fileNode.path);
   if (existingFile) {
@@ -541,13 +633,16 @@ const openFile = async (fileNode: FileNode) => {
   }
 
   try {
-    const content = await invoke<string>("get_file_content", { path: fileNode.path });
+    const { content, modified } = await invoke<FileContent>("get_file_content", {
+      path: fileNode.path,
+    });
     const newFile: OpenFile = {
       id: fileNode.path,
       name: fileNode.name,
       path: fileNode.path,
       content,
       isDirty: false,
+      modified,
     };
 
     setState((prev) => ({
@@ -950,6 +1045,7 @@ const saveFile = async (fileId: string) => {
     const { editorActions } = await import("./editorStore");
     const settings = getSettingsState();
 
+    let content = file.content;
     if (settings.editor.formatOnSave && file.path) {
       // Format the document before saving
       try {
@@ -969,22 +1065,20 @@ const saveFile = async (fileId: string) => {
             ),
           }));
           // Use formatted content for saving
-          await invoke("save_file_content", { path: file.path, content: formattedContent });
-        } else {
-          await invoke("save_file_content", { path: file.path, content: file.content });
+          content = formattedContent;
         }
       } catch (formatError) {
         console.warn("Format on save failed, saving without formatting:", formatError);
-        await invoke("save_file_content", { path: file.path, content: file.content });
       }
-    } else {
-      await invoke("save_file_content", { path: file.path, content: file.content });
     }
 
+    const modified = await writeOpenFile(file, content);
+    if (modified === null) return;
+
     setState((prev) => ({
       ...prev,
       openFiles: prev.openFiles.map((openFile) =>
-        openFile.id === fileId ? { ...openFile, isDirty: false } : openFile,
+        openFile.id === fileId ? { ...openFile, isDirty: false, modified } : openFile,
       ),
     }));
   } catch (error) {
@@ -1006,14 +1100,17 @@ const saveFileAs = async (fileId: string) => {
     });
     if (typeof selected !== "string" || !selected) return;
 
-    await invoke("save_file_content", { path: selected, content: file.content });
+    const { modified } = await invoke<SaveFileResult>("save_file_content", {
+      path: selected,
+      content: file.content,
+    });
     const name = selected.replace(/\\/g, "/").split("/").pop() || defaultName;
 
     setState((prev) => ({
       ...prev,
       openFiles: prev.openFiles.map((openFile) =>
         openFile.id === fileId
-          ? { ...openFile, id: selected, path: selected, name, isDirty: false }
+          ? { ...openFile, id: selected, path: selected, name, isDirty: false, modified }
           : openFile,
       ),
       activeFileId: prev.activeFileId === fileId ? selected : prev.activeFileId,
@@ -1153,6 +1250,7 @@ const ideActions = {
   createFolderAt,
   renameNode,
   deleteNode,
+  undoDelete,
   setProjectTree,
   loadDirectoryChildren,
   toggleSidebar,
@@ -1166,6 +1264,7 @@ const ideActions = {
   revealWorkspace,
   createNewFile,
   openFile,
+  reloadFile,
   openFolderDialog,
   openWorkspace,
   loadWorkspace: openWorkspace, // Alias for openWorkspace
@@ -1317,6 +1416,87 @@ const initializeFromStorage = async () => {
   }
 };
 
+/** Paths the backend is tracking for external changes (see track_open_file) */
+const trackedPaths = new Set<string>();
+
+/**
+ * Keep the backend's open file tracking in step with the open tabs
+ */
+const syncTrackedFiles = () => {
+  const openPaths = new Set(getState().openFiles.map((file) => file.path));
+
+  for (const path of openPaths) {
+    if (trackedPaths.has(path)) continue;
+    trackedPaths.add(path);
+    invoke("track_open_file", { path }).catch((error) => {
+      trackedPaths.delete(path);
+      console.warn("Failed to track open file:", path, error);
+    });
+  }
+  for (const path of trackedPaths) {
+    if (openPaths.has(path)) continue;
+    trackedPaths.delete(path);
+    invoke("untrack_open_file", { path }).catch((error) => {
+      console.warn("Failed to untrack open file:", path, error);
+    });
+  }
+};
+
+/** Payload of the file-changed-externally event */
+interface ExternalChangeEvent {
+  path: string;
+  deleted: boolean;
+  lines_added: number;
+  lines_removed: number;
+  first_changed_line: number | null;
+}
+
+/**
+ * Reload open files changed by other programs. Unsaved edits are only
+ * discarded once the user confirms; a deleted file is left dirty so saving
+ * restores it.
+ */
+const setupExternalChangeListener = async (): Promise<UnlistenFn | null> => {
+  if (!isTauriEnv()) {
+    return null;
+  }
+
+  try {
+    return await getCurrentWindow().listen<ExternalChangeEvent>("file-changed-externally", async (event) => {
+      const change = event.payload;
+      const file = getState().openFiles.find((openFile) => pathsEqual(openFile.path, change.path));
+      if (!file) return;
+
+      if (change.deleted) {
+        setState((prev) => ({
+          ...prev,
+          openFiles: prev.openFiles.map((openFile) =>
+            openFile.id === file.id ? { ...openFile, isDirty: true } : openFile,
+          ),
+        }));
+        return;
+      }
+
+      if (file.isDirty) {
+        const reload = await ask(
+          `"${file.name}" has changed on disk (+${change.lines_added} -${change.lines_removed} lines). Reload it and discard your changes?`,
+          { title: "File Changed on Disk", kind: "warning" },
+        );
+        if (!reload) return;
+      }
+
+      try {
+        await reloadFile(file.id);
+      } catch (error) {
+        console.error("Failed to reload externally changed file:", error);
+      }
+    });
+  } catch (error) {
+    console.error("Failed to register file-changed-externally listener:", error);
+    return null;
+  }
+};
+
 const setupFileChangeListener = async (
   setReloadTimeout: (updater: (handle: TimeoutHandle | null) => TimeoutHandle | null) => void,
 ): Promise<UnlistenFn | null> => {
@@ -1325,7 +1505,8 @@ const setupFileChangeListener = async (
   }
 
   try {
-    const unlisten = await listen("file-change", (event) => {
+    // Only this window's watcher events
+    const unlisten = await getCurrentWindow().listen("file-change", (event) => {
       const changedPaths = (event.payload as string[]) ?? [];
       const snapshot = getState();
       const workspace = snapshot.workspace;
@@ -1367,6 +1548,8 @@ export const IDEProvider: React.FC<React.PropsWithChildren> = ({ children }) =>
     let reloadTimeout: TimeoutHandle | null = null;
     let cancelled = false;
     let unlisten: UnlistenFn | null = null;
+    let unlistenExternal: UnlistenFn | null = null;
+    const unsubscribeTracking = isTauriEnv() ? subscribe(syncTrackedFiles) : null;
 
     const setReloadTimeout = (updater: (handle: TimeoutHandle | null) => TimeoutHandle | null) => {
       if (reloadTimeout) {
@@ -1387,10 +1570,13 @@ export const IDEProvider: React.FC<React.PropsWithChildren> = ({ children }) =>
       }
 
       unlisten = await setupFileChangeListener(setReloadTimeout);
+      unlistenExternal = await setupExternalChangeListener();
     })();
 
     return () => {
       cancelled = true;
+      unsubscribeTracking?.();
+      unlistenExternal?.();
       if (reloadTimeout) {
         clearTimeout(reloadTimeout);
       }
@@ -78,6 +78,18 @@ export interface ToolCall {
 
   /** Status of the tool call */
   status?: 'pending' | 'success' | 'error';
+
+  /** When execution started (ms since epoch) */
+  startedAt?: number;
+
+  /** How long execution took, in milliseconds */
+  durationMs?: number;
+
+  /** Approval state when the session requires approval for this tool */
+  approval?: 'requested' | 'approved' | 'rejected';
+
+  /** ID to answer the approval request with (see approveTool) */
+  approvalId?: string;
 }
 
 /**